@@ -8,4 +8,5 @@ pub static SPDM_TIME_IMPL: SpdmTime = SpdmTime {
         use std::{thread, time::Duration};
         thread::sleep(Duration::from_millis(time as u64));
     },
+    get_current_time_cb: || None,
 };
@@ -89,6 +89,7 @@ fn run_spdm(spdm: Vec<i32>) {
                         0,
                         SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
                         SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+                        None,
                         &mut total_number,
                         &mut spdm_measurement_record_structure,
                     )
@@ -24,6 +24,11 @@ fn fuzz_send_receive_spdm_algorithm(fuzzdata: &[u8]) {
         req_config_info,
         req_provision_info,
     );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCapabilities);
     requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
 
     let _ = requester.send_receive_spdm_algorithm().is_err();
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use fuzzlib::*;
+use spdmlib::common::SpdmConnectionState;
 use spdmlib::protocol::*;
 
 fn fuzz_send_receive_spdm_challenge(fuzzdata: &[u8]) {
@@ -25,6 +26,10 @@ fn fuzz_send_receive_spdm_challenge(fuzzdata: &[u8]) {
         req_provision_info,
     );
     requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
     requester
         .common
@@ -24,6 +24,11 @@ fn fuzz_send_receive_spdm_digest(fuzzdata: &[u8]) {
         req_provision_info,
     );
 
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
     requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
@@ -5,6 +5,7 @@
 use fuzzlib::spdmlib::message::SpdmKeyExchangeMutAuthAttributes;
 use fuzzlib::*;
 use spdmlib::common::session::{SpdmSession, SpdmSessionState};
+use spdmlib::common::SpdmConnectionState;
 use spdmlib::protocol::*;
 
 fn fuzz_session_based_mutual_authenticate(fuzzdata: &[u8]) {
@@ -29,6 +30,11 @@ fn fuzz_session_based_mutual_authenticate(fuzzdata: &[u8]) {
             req_provision_info,
         );
 
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
         requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
@@ -75,6 +81,11 @@ fn fuzz_session_based_mutual_authenticate(fuzzdata: &[u8]) {
             req_provision_info,
         );
 
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
         requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
@@ -122,6 +133,11 @@ fn fuzz_session_based_mutual_authenticate(fuzzdata: &[u8]) {
             req_provision_info,
         );
 
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
         requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
@@ -169,6 +185,11 @@ fn fuzz_session_based_mutual_authenticate(fuzzdata: &[u8]) {
             req_provision_info,
         );
 
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
         requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
         requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
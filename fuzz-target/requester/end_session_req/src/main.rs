@@ -6,6 +6,7 @@ use fuzzlib::{
     spdmlib::common::session::{SpdmSession, SpdmSessionState},
     *,
 };
+use spdmlib::common::SpdmConnectionState;
 use spdmlib::protocol::*;
 
 fn fuzz_send_receive_spdm_end_session(fuzzdata: &[u8]) {
@@ -26,6 +27,11 @@ fn fuzz_send_receive_spdm_end_session(fuzzdata: &[u8]) {
         req_config_info,
         req_provision_info,
     );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
     requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     requester.common.session[0] = SpdmSession::new();
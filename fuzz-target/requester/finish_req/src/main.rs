@@ -6,6 +6,7 @@ use fuzzlib::{
     spdmlib::common::session::{SpdmSession, SpdmSessionState},
     *,
 };
+use spdmlib::common::SpdmConnectionState;
 use spdmlib::protocol::*;
 
 fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
@@ -50,6 +51,10 @@ fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
         requester.common.negotiate_info.key_schedule_sel = SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE;
 
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         requester.common.session[0] = SpdmSession::new();
         requester.common.session[0].setup(4294836221).unwrap();
@@ -112,6 +117,10 @@ fn fuzz_send_receive_spdm_finish(fuzzdata: &[u8]) {
         requester.common.negotiate_info.key_schedule_sel = SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE;
 
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         requester.common.session[0] = SpdmSession::new();
         requester.common.session[0].setup(4294836221).unwrap();
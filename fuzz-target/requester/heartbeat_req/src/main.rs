@@ -2,6 +2,7 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+use spdmlib::common::SpdmConnectionState;
 use spdmlib::protocol::*;
 
 use fuzzlib::{
@@ -27,6 +28,11 @@ fn fuzz_send_receive_spdm_heartbeat(fuzzdata: &[u8]) {
         req_config_info,
         req_provision_info,
     );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
     requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     requester.common.session[0] = SpdmSession::new();
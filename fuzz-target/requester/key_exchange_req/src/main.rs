@@ -41,6 +41,10 @@ fn fuzz_send_receive_spdm_key_exchange(fuzzdata: &[u8]) {
             SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
         requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         let _ = requester.send_receive_spdm_key_exchange(
             0,
@@ -79,6 +83,10 @@ fn fuzz_send_receive_spdm_key_exchange(fuzzdata: &[u8]) {
             SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
         requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         let _ = requester.send_receive_spdm_key_exchange(
             0,
@@ -113,6 +121,10 @@ fn fuzz_send_receive_spdm_key_exchange(fuzzdata: &[u8]) {
             SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
         requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         let _ = requester.send_receive_spdm_key_exchange(
             0,
@@ -153,6 +165,10 @@ fn fuzz_send_receive_spdm_key_exchange(fuzzdata: &[u8]) {
         requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP
             | SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         let _ = requester.send_receive_spdm_key_exchange(
             0,
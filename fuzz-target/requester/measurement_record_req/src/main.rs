@@ -0,0 +1,156 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use fuzzlib::{spdmlib::message::SpdmMeasurementOperation, *};
+use spdmlib::common::SpdmConnectionState;
+use spdmlib::message::*;
+use spdmlib::protocol::*;
+
+fn fuzz_handle_spdm_measurement_record_response(fuzzdata: &[u8]) {
+    // fuzzdata is everything of MEASUREMENTS after the SPDM header.
+    let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let receive_used = 2 + fuzzdata.len().min(config::MAX_SPDM_MSG_SIZE - 2);
+    receive_buffer[0] = SpdmVersion::SpdmVersion12.get_u8();
+    receive_buffer[1] = SpdmRequestResponseCode::SpdmResponseMeasurements.get_u8();
+    receive_buffer[2..receive_used].copy_from_slice(&fuzzdata[..receive_used - 2]);
+
+    // TCD:
+    // - id: 0
+    // - title: 'Fuzz SPDM handle measurement record response'
+    // - description: '<p>Verify a signed RequestAll, a signed and an unsigned QueryTotalNumber response against a negotiated context with the responder cert chain in slot 0.</p>'
+    // -
+    // TCD:
+    // - id: 1
+    // - title: 'Fuzz SPDM handle measurement record response'
+    // - description: '<p>Verify a signed RequestAll response with a cert chain in slot 0 whose DER is cut short.</p>'
+    // -
+    let cases = [
+        (
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            false,
+        ),
+        (
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            false,
+        ),
+        (
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            false,
+        ),
+        (
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            true,
+        ),
+    ];
+    for (measurement_attributes, measurement_operation, truncate_cert_chain) in cases {
+        let (req_config_info, req_provision_info) = req_create_info();
+        let shared_buffer = SharedBuffer::new();
+
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+        let mut device_io_requester = fake_device_io::FakeSpdmDeviceIo::new(&shared_buffer);
+
+        let mut requester = requester::RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap,
+            req_config_info,
+            req_provision_info,
+        );
+
+        requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+        requester.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::CERT_CAP;
+        requester.common.negotiate_info.rsp_capabilities_sel =
+            SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+        requester
+            .common
+            .negotiate_info
+            .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+        requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        requester.common.negotiate_info.base_asym_sel =
+            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+        requester.common.negotiate_info.measurement_hash_sel =
+            SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+
+        let mut cert_chain = get_rsp_cert_chain_buff();
+        if truncate_cert_chain {
+            // keep the header consistent, only the DER is cut short.
+            cert_chain.data_size -= cert_chain.data_size / 4;
+            cert_chain.data[..2].copy_from_slice(&cert_chain.data_size.to_le_bytes());
+        }
+        requester.common.peer_info.peer_cert_chain[0] = Some(cert_chain);
+
+        requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let send_used = match requester.encode_spdm_measurement_record(
+            measurement_attributes,
+            measurement_operation,
+            None,
+            0,
+            &mut send_buffer,
+        ) {
+            Ok(send_used) => send_used,
+            Err(_) => continue,
+        };
+
+        let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+        let _ = requester.handle_spdm_measurement_record_response(
+            None,
+            0,
+            measurement_attributes,
+            measurement_operation,
+            &mut spdm_measurement_record_structure,
+            &send_buffer[..send_used],
+            &receive_buffer[..receive_used],
+        );
+    }
+}
+
+#[cfg(not(feature = "use_libfuzzer"))]
+fn main() {
+    #[cfg(all(feature = "fuzzlogfile", feature = "fuzz"))]
+    flexi_logger::Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory("traces")
+                .basename("foo")
+                .discriminant("Sample4711A")
+                .suffix("trc"),
+        )
+        .print_message()
+        .create_symlink("current_run")
+        .start()
+        .unwrap();
+
+    #[cfg(not(feature = "fuzz"))]
+    {
+        let args: Vec<String> = std::env::args().collect();
+        if args.len() < 2 {
+            // Here you can replace the single-step debugging value in the fuzzdata array.
+            let fuzzdata = [
+                0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5f, 0x25, 0x5c, 0x4a, 0x2c, 0x4e, 0x68, 0x1d,
+                0x33, 0x0e, 0x1b, 0x63, 0x57, 0x18, 0x6a, 0x4a, 0x30, 0xf6, 0x9f, 0x8c, 0x77, 0x2a,
+                0x2b, 0x3c, 0x9d, 0x50, 0x43, 0x0a, 0x73, 0xf3, 0xd5, 0x0b, 0x00, 0x00,
+            ];
+            fuzz_handle_spdm_measurement_record_response(&fuzzdata);
+        } else {
+            let path = &args[1];
+            let data = std::fs::read(path).expect("read crash file fail");
+            fuzz_handle_spdm_measurement_record_response(data.as_slice());
+        }
+    }
+    #[cfg(feature = "fuzz")]
+    afl::fuzz!(|data: &[u8]| {
+        fuzz_handle_spdm_measurement_record_response(data);
+    });
+}
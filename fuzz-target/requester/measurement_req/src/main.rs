@@ -52,6 +52,10 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
         requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
 
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         let mut total_number = 0;
         let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
@@ -60,6 +64,7 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
             0,
             SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
             SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
             &mut total_number,
             &mut spdm_measurement_record_structure,
         );
@@ -103,6 +108,10 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
         requester.common.negotiate_info.measurement_hash_sel =
             SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         let mut total_number = 0;
         let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
@@ -111,6 +120,7 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
             0,
             SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
             SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
             &mut total_number,
             &mut spdm_measurement_record_structure,
         );
@@ -155,6 +165,10 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
         requester.common.negotiate_info.measurement_hash_sel =
             SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         let mut total_number = 0;
         let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
@@ -163,6 +177,7 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
             0,
             SpdmMeasurementAttributes::RAW_BIT_STREAM_REQUESTED,
             SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
             &mut total_number,
             &mut spdm_measurement_record_structure,
         );
@@ -206,6 +221,10 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
         requester.common.negotiate_info.measurement_hash_sel =
             SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
         let mut total_number = 0;
         let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
@@ -214,6 +233,7 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
             0,
             SpdmMeasurementAttributes::empty(),
             SpdmMeasurementOperation::Unknown(4),
+            None,
             &mut total_number,
             &mut spdm_measurement_record_structure,
         );
@@ -285,6 +305,10 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
         requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
 
         requester.common.reset_runtime_info();
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
         let mut total_number = 0;
         let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
         let _ = requester.send_receive_spdm_measurement(
@@ -292,6 +316,7 @@ fn fuzz_send_receive_spdm_measurement(fuzzdata: &[u8]) {
             0,
             SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
             SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
             &mut total_number,
             &mut spdm_measurement_record_structure,
         );
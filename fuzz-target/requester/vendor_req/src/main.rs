@@ -28,6 +28,11 @@ fn fuzz_send_spdm_vendor_defined_request(fuzzdata: &[u8]) {
         req_provision_info,
     );
 
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
     requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
@@ -7,5 +7,7 @@
 
 mod header;
 pub use header::*;
+mod smbus;
+pub use smbus::*;
 
 extern crate codec;
@@ -0,0 +1,363 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use codec::{Codec, Reader, Writer};
+use spdmlib::common::SpdmTransportEncap;
+use spdmlib::error::{SpdmResult, SPDM_STATUS_DECAP_FAIL, SPDM_STATUS_ENCAP_FAIL};
+
+use crate::header::MctpTransportEncap;
+
+/// SMBus command code of an MCTP packet (DSP0237).
+pub const SMBUS_COMMAND_CODE_MCTP: u8 = 0x0F;
+
+/// MCTP header version of DSP0236 1.x.
+pub const MCTP_HEADER_VERSION: u8 = 0x01;
+
+/// Baseline transmission unit: the MCTP payload bytes of one packet.
+pub const MCTP_BASELINE_TRANSMISSION_UNIT: usize = 64;
+
+// Destination Slave Address (1) + Command Code (1) + Byte Count (1) +
+// Source Slave Address (1) + MCTP header (4)
+const SMBUS_PACKET_HEADER_SIZE: usize = 8;
+// the part of the header Byte Count covers: Source Slave Address (1) +
+// MCTP header (4)
+const SMBUS_BYTE_COUNT_HEADER_SIZE: usize = 5;
+
+const MCTP_FLAG_SOM: u8 = 0b1000_0000;
+const MCTP_FLAG_EOM: u8 = 0b0100_0000;
+const MCTP_PACKET_SEQUENCE_MASK: u8 = 0b0011_0000;
+const MCTP_FLAG_TAG_OWNER: u8 = 0b0000_1000;
+const MCTP_MESSAGE_TAG_MASK: u8 = 0b0000_0111;
+
+/// SMBus Packet Error Code: CRC-8 with polynomial x^8 + x^2 + x + 1.
+pub fn smbus_pec(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// MCTP over SMBus/I2C binding (DSP0237).
+///
+/// A message is split into SMBus block writes of at most
+/// `MCTP_BASELINE_TRANSMISSION_UNIT` payload bytes each, laid out back to
+/// back in the transport buffer:
+///
+/// | DestSlaveAddr | CommandCode 0x0F | ByteCount | SrcSlaveAddr |
+/// | MCTP header (4) | payload | PEC |
+///
+/// Every packet adds 9 bytes, so SENDER_BUFFER_SIZE and
+/// RECEIVER_BUFFER_SIZE need that much headroom per 64 byte packet over
+/// the largest message carried.
+///
+/// Slave addresses are 7-bit. The requester side owns the message tag and
+/// uses a new one per message; the responder side echoes the tag of the
+/// last message it received.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct I2cTransportEncap {
+    pub local_address: u8,
+    pub remote_address: u8,
+    pub local_eid: u8,
+    pub remote_eid: u8,
+    pub tag_owner: bool,
+    // tag of the message being sent by the owner, or last received otherwise.
+    pub message_tag: u8,
+}
+
+impl I2cTransportEncap {
+    pub fn new(
+        local_address: u8,
+        remote_address: u8,
+        local_eid: u8,
+        remote_eid: u8,
+        tag_owner: bool,
+    ) -> Self {
+        I2cTransportEncap {
+            local_address,
+            remote_address,
+            local_eid,
+            remote_eid,
+            tag_owner,
+            message_tag: 0,
+        }
+    }
+
+    fn encap_message(&mut self, message: &[u8], transport_buffer: &mut [u8]) -> SpdmResult<usize> {
+        let tag_owner_flag = if self.tag_owner {
+            self.message_tag = (self.message_tag + 1) & MCTP_MESSAGE_TAG_MASK;
+            MCTP_FLAG_TAG_OWNER
+        } else {
+            0
+        };
+
+        let mut writer = Writer::init(transport_buffer);
+        for (packet_sequence, payload) in
+            message.chunks(MCTP_BASELINE_TRANSMISSION_UNIT).enumerate()
+        {
+            let mut flags = tag_owner_flag
+                | (((packet_sequence as u8) << 4) & MCTP_PACKET_SEQUENCE_MASK)
+                | self.message_tag;
+            if packet_sequence == 0 {
+                flags |= MCTP_FLAG_SOM;
+            }
+            if (packet_sequence + 1) * MCTP_BASELINE_TRANSMISSION_UNIT >= message.len() {
+                flags |= MCTP_FLAG_EOM;
+            }
+
+            let packet_start = writer.used();
+            let byte_count = (SMBUS_BYTE_COUNT_HEADER_SIZE + payload.len()) as u8;
+            for byte in [
+                self.remote_address << 1,
+                SMBUS_COMMAND_CODE_MCTP,
+                byte_count,
+                (self.local_address << 1) | 1,
+                MCTP_HEADER_VERSION,
+                self.remote_eid,
+                self.local_eid,
+                flags,
+            ] {
+                writer.push(byte).ok_or(SPDM_STATUS_ENCAP_FAIL)?;
+            }
+            writer
+                .extend_from_slice(payload)
+                .ok_or(SPDM_STATUS_ENCAP_FAIL)?;
+            let pec = smbus_pec(&writer.used_slice()[packet_start..]);
+            writer.push(pec).ok_or(SPDM_STATUS_ENCAP_FAIL)?;
+        }
+        Ok(writer.used())
+    }
+
+    fn decap_message(&mut self, transport_buffer: &[u8], message: &mut [u8]) -> SpdmResult<usize> {
+        let mut reader = Reader::init(transport_buffer);
+        let mut message_size = 0usize;
+        let mut message_tag = None;
+        let mut packet_sequence = 0u8;
+        loop {
+            let packet_start = reader.used();
+            let mut header = [0u8; SMBUS_PACKET_HEADER_SIZE];
+            header.copy_from_slice(
+                reader
+                    .take(SMBUS_PACKET_HEADER_SIZE)
+                    .ok_or(SPDM_STATUS_DECAP_FAIL)?,
+            );
+            let byte_count = header[2] as usize;
+            let flags = header[7];
+            if header[0] != self.local_address << 1
+                || header[1] != SMBUS_COMMAND_CODE_MCTP
+                || byte_count <= SMBUS_BYTE_COUNT_HEADER_SIZE
+                || header[3] != (self.remote_address << 1) | 1
+                || header[4] & 0x0F != MCTP_HEADER_VERSION
+                || header[5] != self.local_eid
+                || header[6] != self.remote_eid
+            {
+                return Err(SPDM_STATUS_DECAP_FAIL);
+            }
+            let payload_size = byte_count - SMBUS_BYTE_COUNT_HEADER_SIZE;
+            reader.take(payload_size).ok_or(SPDM_STATUS_DECAP_FAIL)?;
+            let payload_end = reader.used();
+            let payload = &transport_buffer[(payload_end - payload_size)..payload_end];
+            let pec = u8::read(&mut reader).ok_or(SPDM_STATUS_DECAP_FAIL)?;
+            if pec != smbus_pec(&transport_buffer[packet_start..payload_end]) {
+                return Err(SPDM_STATUS_DECAP_FAIL);
+            }
+
+            // SOM only on the first packet, one tag and consecutive
+            // sequence numbers across the message.
+            let tag = flags & (MCTP_FLAG_TAG_OWNER | MCTP_MESSAGE_TAG_MASK);
+            if (flags & MCTP_FLAG_SOM != 0) != message_tag.is_none()
+                || matches!(message_tag, Some(message_tag) if message_tag != tag)
+                || (flags & MCTP_PACKET_SEQUENCE_MASK) >> 4 != packet_sequence
+            {
+                return Err(SPDM_STATUS_DECAP_FAIL);
+            }
+            message_tag = Some(tag);
+            packet_sequence = (packet_sequence + 1) & 0x3;
+
+            if message.len() < message_size + payload.len() {
+                return Err(SPDM_STATUS_DECAP_FAIL);
+            }
+            message[message_size..(message_size + payload.len())].copy_from_slice(payload);
+            message_size += payload.len();
+
+            if flags & MCTP_FLAG_EOM != 0 {
+                break;
+            }
+        }
+        if reader.any_left() {
+            return Err(SPDM_STATUS_DECAP_FAIL);
+        }
+
+        // the owner expects its own tag back with the owner bit cleared.
+        let tag = message_tag.unwrap_or_default();
+        if self.tag_owner {
+            if tag != self.message_tag {
+                return Err(SPDM_STATUS_DECAP_FAIL);
+            }
+        } else if tag & MCTP_FLAG_TAG_OWNER == 0 {
+            return Err(SPDM_STATUS_DECAP_FAIL);
+        } else {
+            self.message_tag = tag & MCTP_MESSAGE_TAG_MASK;
+        }
+        Ok(message_size)
+    }
+}
+
+impl SpdmTransportEncap for I2cTransportEncap {
+    fn encap(
+        &mut self,
+        spdm_buffer: &[u8],
+        transport_buffer: &mut [u8],
+        secured_message: bool,
+    ) -> SpdmResult<usize> {
+        let mut message = [0u8; spdmlib::config::SENDER_BUFFER_SIZE];
+        let message_size =
+            MctpTransportEncap {}.encap(spdm_buffer, &mut message, secured_message)?;
+        self.encap_message(&message[..message_size], transport_buffer)
+    }
+
+    fn decap(
+        &mut self,
+        transport_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, bool)> {
+        let mut message = [0u8; spdmlib::config::RECEIVER_BUFFER_SIZE];
+        let message_size = self.decap_message(transport_buffer, &mut message)?;
+        MctpTransportEncap {}.decap(&message[..message_size], spdm_buffer)
+    }
+
+    fn encap_app(
+        &mut self,
+        spdm_buffer: &[u8],
+        app_buffer: &mut [u8],
+        is_app_message: bool,
+    ) -> SpdmResult<usize> {
+        MctpTransportEncap {}.encap_app(spdm_buffer, app_buffer, is_app_message)
+    }
+
+    fn decap_app(
+        &mut self,
+        app_buffer: &[u8],
+        spdm_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, bool)> {
+        MctpTransportEncap {}.decap_app(app_buffer, spdm_buffer)
+    }
+
+    fn get_sequence_number_count(&mut self) -> u8 {
+        2
+    }
+    fn get_max_random_count(&mut self) -> u16 {
+        32
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::header::MctpMessageType;
+
+    fn encap_pair() -> (I2cTransportEncap, I2cTransportEncap) {
+        (
+            I2cTransportEncap::new(0x10, 0x20, 8, 9, true),
+            I2cTransportEncap::new(0x20, 0x10, 9, 8, false),
+        )
+    }
+
+    #[test]
+    fn test_case0_smbus_pec() {
+        assert_eq!(smbus_pec(b"123456789"), 0xF4);
+        assert_eq!(smbus_pec(&[]), 0);
+    }
+
+    #[test]
+    fn test_case0_encap_decap_multi_packet() {
+        let (mut requester, mut responder) = encap_pair();
+        let mut spdm_buffer = [0u8; 150];
+        for (i, byte) in spdm_buffer.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        let mut transport_buffer = [0u8; 256];
+        let used = requester
+            .encap(&spdm_buffer, &mut transport_buffer, false)
+            .unwrap();
+        // 151 message bytes (type + SPDM) take three packets.
+        assert_eq!(used, 151 + 3 * 9);
+        assert_eq!(
+            &transport_buffer[..8],
+            &[
+                0x20 << 1,
+                SMBUS_COMMAND_CODE_MCTP,
+                5 + 64,
+                (0x10 << 1) | 1,
+                MCTP_HEADER_VERSION,
+                9,
+                8,
+                MCTP_FLAG_SOM | MCTP_FLAG_TAG_OWNER | 1
+            ]
+        );
+        assert_eq!(
+            transport_buffer[8],
+            MctpMessageType::MctpMessageTypeSpdm.get_u8()
+        );
+
+        let mut received = [0u8; 256];
+        let (size, secured_message) = responder
+            .decap(&transport_buffer[..used], &mut received)
+            .unwrap();
+        assert_eq!(&received[..size], &spdm_buffer[..]);
+        assert!(!secured_message);
+
+        // the response carries the request tag back.
+        let used = responder
+            .encap(&spdm_buffer[..10], &mut transport_buffer, true)
+            .unwrap();
+        assert_eq!(transport_buffer[7], MCTP_FLAG_SOM | MCTP_FLAG_EOM | 1);
+        let (size, secured_message) = requester
+            .decap(&transport_buffer[..used], &mut received)
+            .unwrap();
+        assert_eq!(&received[..size], &spdm_buffer[..10]);
+        assert!(secured_message);
+    }
+
+    #[test]
+    fn test_case1_decap_rejects_bad_packets() {
+        let (mut requester, mut responder) = encap_pair();
+        let spdm_buffer = [0x5au8; 100];
+        let mut transport_buffer = [0u8; 256];
+        let used = requester
+            .encap(&spdm_buffer, &mut transport_buffer, false)
+            .unwrap();
+        let mut received = [0u8; 256];
+
+        // a flipped bit fails the PEC.
+        let mut corrupted = transport_buffer;
+        corrupted[20] ^= 1;
+        assert!(responder.decap(&corrupted[..used], &mut received).is_err());
+
+        // a missing last packet leaves the message without EOM.
+        let first_packet = 8 + 64 + 1;
+        assert!(responder
+            .decap(&transport_buffer[..first_packet], &mut received)
+            .is_err());
+
+        // a packet for another slave is not ours.
+        let (mut other, _) = encap_pair();
+        other.local_address = 0x30;
+        assert!(other
+            .decap(&transport_buffer[..used], &mut received)
+            .is_err());
+
+        assert!(responder
+            .decap(&transport_buffer[..used], &mut received)
+            .is_ok());
+    }
+}
@@ -176,6 +176,8 @@ run_basic_test() {
     pushd test/spdmlib-test
     echo_command cargo test -- --test-threads=1
     echo_command cargo test --no-default-features -- --test-threads=1
+    echo_command cargo test --features key-log -- --test-threads=1
+    echo_command cargo test --no-default-features --features transcript-fixture -- --test-threads=1
     popd
 }
 
@@ -102,6 +102,7 @@ cmds=(
     "certificate_req"
     "challenge_req" #remove cert_chain = RSP_CERT_CHAIN_BUFF >> OK
     "measurement_req"
+    "measurement_record_req"
     "key_exchange_req" #remove cert_chain = RSP_CERT_CHAIN_BUFF >> OK
     "psk_exchange_req" #remove cert_chain = RSP_CERT_CHAIN_BUFF >> OK
     "finish_req"       #remove cert_chain = RSP_CERT_CHAIN_BUFF >> OK
@@ -13,6 +13,7 @@ format-patch() {
     git reset --hard 0b7cbf2d327d7665d9d06072bf46b2e7ca05f065
     git clean -xdf
     git apply ../patches/webpki/0001-Add-support-for-verifying-certificate-chain-with-EKU.patch
+    git apply ../patches/webpki/0002-Allow-verifying-a-certificate-chain-without-a-time.patch
     popd
 }
 
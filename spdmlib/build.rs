@@ -35,6 +35,7 @@ impl SpdmConfig {
 
         assert!(self.cert_config.max_cert_chain_data_size <= 0xFFFF);
         // no need to check max_cert_chain_data_size against max_spdm_msg_size
+        assert!(self.cert_config.max_cert_chain_depth >= 1);
 
         assert!(self.measurement_config.max_measurement_record_size <= 0xFFFFFF);
         assert!(self.measurement_config.max_measurement_val_len <= 0xFFFF - 7);
@@ -44,6 +45,8 @@ impl SpdmConfig {
         );
         assert!(self.measurement_config.max_measurement_val_len >= 32);
         assert!(self.measurement_config.max_measurement_record_size < self.max_spdm_msg_size);
+        assert!(self.measurement_config.max_measurement_blocks >= 1);
+        assert!(self.measurement_config.max_measurement_blocks <= 0xFF);
 
         assert!(self.psk_config.max_psk_context_size >= 32);
         assert!(self.psk_config.max_psk_context_size <= 0xFFFF);
@@ -60,12 +63,24 @@ impl SpdmConfig {
 #[derive(Debug, PartialEq, Deserialize)]
 struct SpdmCertConfig {
     max_cert_chain_data_size: usize,
+    #[serde(default = "default_max_cert_chain_depth")]
+    max_cert_chain_depth: usize,
+}
+
+fn default_max_cert_chain_depth() -> usize {
+    8
 }
 
 #[derive(Debug, PartialEq, Deserialize)]
 struct SpdmMeasurementConfig {
     max_measurement_record_size: usize,
     max_measurement_val_len: usize,
+    #[serde(default = "default_max_measurement_blocks")]
+    max_measurement_blocks: usize,
+}
+
+fn default_max_measurement_blocks() -> usize {
+    64
 }
 
 #[derive(Debug, PartialEq, Deserialize)]
@@ -93,6 +108,11 @@ macro_rules! TEMPLATE {
 /// This is used in SpdmCertChainData without SpdmCertChainHeader.
 pub const MAX_SPDM_CERT_CHAIN_DATA_SIZE: usize = {cert_chain_data_sz}; // 0x1000;
 
+/// This is the maximum number of certificates, root and leaf included, in a
+/// peer certificate chain. Deeper chains are rejected before any signature is
+/// checked. Defaults to 8.
+pub const MAX_SPDM_CERT_CHAIN_DEPTH: usize = {cert_chain_depth}; // 8
+
 /// This is used in SpdmMeasurementsResponsePayload
 pub const MAX_SPDM_MEASUREMENT_RECORD_SIZE: usize = {meas_rec_sz}; // 0x1000
 
@@ -100,6 +120,10 @@ pub const MAX_SPDM_MEASUREMENT_RECORD_SIZE: usize = {meas_rec_sz}; // 0x1000
 /// It should be MAX (MAX MEASUREMENT_MANIFEST_LEN, MAX supported DIGEST SIZE)
 pub const MAX_SPDM_MEASUREMENT_VALUE_LEN: usize = {meas_val_len}; // 0x400
 
+/// This is the maximum number_of_blocks accepted in a MEASUREMENTS response.
+/// It bounds the per-block work done for an untrusted peer. Defaults to 64.
+pub const MAX_SPDM_MEASUREMENT_BLOCKS: usize = {meas_blocks}; // 64
+
 /// This is used in SpdmPskExchangeRequestPayload / SpdmPskExchangeResponsePayload
 /// It should be no smaller than negoatiated DIGEST SIZE.
 pub const MAX_SPDM_PSK_CONTEXT_SIZE: usize = {psk_ctx_sz};
@@ -174,8 +198,10 @@ fn main() {
         &mut to_generate,
         TEMPLATE!(),
         cert_chain_data_sz = spdm_config.cert_config.max_cert_chain_data_size,
+        cert_chain_depth = spdm_config.cert_config.max_cert_chain_depth,
         meas_rec_sz = spdm_config.measurement_config.max_measurement_record_size,
         meas_val_len = spdm_config.measurement_config.max_measurement_val_len,
+        meas_blocks = spdm_config.measurement_config.max_measurement_blocks,
         psk_ctx_sz = spdm_config.psk_config.max_psk_context_size,
         psk_hint_sz = spdm_config.psk_config.max_psk_hint_size,
         session_cnt = spdm_config.max_session_count,
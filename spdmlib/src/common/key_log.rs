@@ -0,0 +1,102 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! Session secrets handed out as they are derived, for analysis tools to
+//! decrypt captured secured messages, the way SSLKEYLOGFILE does for TLS.
+//!
+//! Whatever is registered here sees every session secret, which is why it
+//! only exists with the `key-log` feature.
+
+use conquer_once::spin::OnceCell;
+
+use crate::error::{SpdmResult, SPDM_STATUS_BUFFER_FULL};
+
+pub const SPDM_KEY_LOG_REQUEST_HANDSHAKE_SECRET: &str = "SPDM_REQUEST_HANDSHAKE_SECRET";
+pub const SPDM_KEY_LOG_RESPONSE_HANDSHAKE_SECRET: &str = "SPDM_RESPONSE_HANDSHAKE_SECRET";
+pub const SPDM_KEY_LOG_REQUEST_DATA_SECRET: &str = "SPDM_REQUEST_DATA_SECRET";
+pub const SPDM_KEY_LOG_RESPONSE_DATA_SECRET: &str = "SPDM_RESPONSE_DATA_SECRET";
+pub const SPDM_KEY_LOG_EXPORT_MASTER_SECRET: &str = "SPDM_EXPORT_MASTER_SECRET";
+
+/// Receives each session secret with its label when it is derived. A
+/// KEY_UPDATE logs the new data secret of a direction under the same
+/// label again, so a later line supersedes an earlier one.
+#[derive(Clone)]
+pub struct SpdmKeyLog {
+    pub log_secret_cb: fn(session_id: u32, label: &str, secret: &[u8]),
+}
+
+static SPDM_KEY_LOG: OnceCell<SpdmKeyLog> = OnceCell::uninit();
+
+pub fn register(context: SpdmKeyLog) -> bool {
+    SPDM_KEY_LOG.try_init_once(|| context).is_ok()
+}
+
+pub(crate) fn log_secret(session_id: u32, label: &str, secret: &[u8]) {
+    if let Ok(key_log) = SPDM_KEY_LOG.try_get() {
+        (key_log.log_secret_cb)(session_id, label, secret)
+    }
+}
+
+/// Write one key log line into `buf`:
+///
+/// `<label> <session id> <secret>\n`
+///
+/// with the session id, in place of the client random of TLS, as 8 hex
+/// digits and the secret in hex. Returns the bytes written, or
+/// SPDM_STATUS_BUFFER_FULL if `buf` is too small.
+pub fn write_key_log_line(
+    session_id: u32,
+    label: &str,
+    secret: &[u8],
+    buf: &mut [u8],
+) -> SpdmResult<usize> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    let size = label.len() + 1 + 8 + 1 + secret.len() * 2 + 1;
+    if buf.len() < size {
+        return Err(SPDM_STATUS_BUFFER_FULL);
+    }
+
+    let mut used = 0;
+    let mut push = |byte: u8| {
+        buf[used] = byte;
+        used += 1;
+    };
+    label.bytes().for_each(&mut push);
+    push(b' ');
+    for byte in session_id.to_be_bytes().iter() {
+        push(HEX[(byte >> 4) as usize]);
+        push(HEX[(byte & 0xf) as usize]);
+    }
+    push(b' ');
+    for byte in secret {
+        push(HEX[(byte >> 4) as usize]);
+        push(HEX[(byte & 0xf) as usize]);
+    }
+    push(b'\n');
+    Ok(used)
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case0_write_key_log_line() {
+        let mut buf = [0u8; 64];
+        let used = write_key_log_line(
+            0xfffe_0001,
+            SPDM_KEY_LOG_REQUEST_DATA_SECRET,
+            &[0x01, 0xab],
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(&buf[..used], b"SPDM_REQUEST_DATA_SECRET fffe0001 01ab\n");
+
+        assert_eq!(
+            write_key_log_line(0, SPDM_KEY_LOG_REQUEST_DATA_SECRET, &[0x01], &mut buf[..30]),
+            Err(SPDM_STATUS_BUFFER_FULL)
+        );
+    }
+}
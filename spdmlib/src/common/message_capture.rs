@@ -0,0 +1,112 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+extern crate alloc;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpdmCaptureDirection {
+    Sent,
+    Received,
+}
+
+/// Ring of the last SPDM messages a context sent and received, for
+/// conformance tooling that needs the exact bytes without wrapping the
+/// transport.
+///
+/// Messages are kept as they were before transport encapsulation and after
+/// decapsulation, secured ones in plaintext. Each chunk of a chunked
+/// transfer is a message of its own. The capacity comes from
+/// `SpdmConfigInfo::message_capture_capacity`; 0, the default, records
+/// nothing.
+#[derive(Debug, Clone, Default)]
+pub struct SpdmMessageCapture {
+    capacity: usize,
+    messages: VecDeque<(SpdmCaptureDirection, Vec<u8>)>,
+}
+
+impl SpdmMessageCapture {
+    pub fn new(capacity: usize) -> Self {
+        SpdmMessageCapture {
+            capacity,
+            messages: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Keep `message`, dropping the oldest one when the ring is full.
+    pub fn record(&mut self, direction: SpdmCaptureDirection, message: &[u8]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.messages.len() == self.capacity {
+            self.messages.pop_front();
+        }
+        self.messages.push_back((direction, message.to_vec()));
+    }
+
+    /// Captured messages, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = (SpdmCaptureDirection, &[u8])> {
+        self.messages
+            .iter()
+            .map(|(direction, message)| (*direction, message.as_slice()))
+    }
+
+    pub fn last_sent(&self) -> Option<&[u8]> {
+        self.last(SpdmCaptureDirection::Sent)
+    }
+
+    pub fn last_received(&self) -> Option<&[u8]> {
+        self.last(SpdmCaptureDirection::Received)
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    fn last(&self, direction: SpdmCaptureDirection) -> Option<&[u8]> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|(d, _)| *d == direction)
+            .map(|(_, message)| message.as_slice())
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case0_message_capture_ring() {
+        let mut capture = SpdmMessageCapture::default();
+        capture.record(SpdmCaptureDirection::Sent, &[1]);
+        assert_eq!(capture.iter().count(), 0);
+        assert!(capture.last_sent().is_none());
+
+        let mut capture = SpdmMessageCapture::new(2);
+        capture.record(SpdmCaptureDirection::Sent, &[1]);
+        capture.record(SpdmCaptureDirection::Received, &[2]);
+        capture.record(SpdmCaptureDirection::Sent, &[3]);
+        assert_eq!(capture.last_sent(), Some(&[3u8][..]));
+        assert_eq!(capture.last_received(), Some(&[2u8][..]));
+        let captured: Vec<_> = capture.iter().collect();
+        assert_eq!(
+            captured,
+            [
+                (SpdmCaptureDirection::Received, &[2u8][..]),
+                (SpdmCaptureDirection::Sent, &[3u8][..])
+            ]
+        );
+
+        capture.record(SpdmCaptureDirection::Sent, &[4]);
+        assert!(capture.last_received().is_none());
+        capture.clear();
+        assert!(capture.last_sent().is_none());
+    }
+}
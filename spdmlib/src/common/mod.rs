@@ -2,23 +2,45 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+#[cfg(feature = "key-log")]
+pub mod key_log;
 pub mod key_schedule;
+#[cfg(feature = "alloc")]
+pub mod message_capture;
 pub mod opaque;
 pub mod session;
+#[cfg(all(feature = "serde", feature = "alloc"))]
+pub mod snapshot;
 pub mod spdm_codec;
+#[cfg(all(feature = "transcript-fixture", not(feature = "hashed-transcript-data")))]
+pub mod transcript_fixture;
+pub mod transcript_storage;
 
-use crate::message::SpdmRequestResponseCode;
+use crate::message::{
+    SpdmRequestResponseCode, SpdmSupportedEventTypesResponsePayload, SpdmVersionStruct,
+};
 use crate::{crypto, protocol::*};
+extern crate alloc;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use alloc::vec;
 
+#[cfg(feature = "alloc")]
+pub use message_capture::*;
 pub use opaque::*;
 pub use spdm_codec::SpdmCodec;
+pub use transcript_storage::*;
 
 use crate::config::{self, MAX_SPDM_SESSION_COUNT};
 use crate::error::{
     SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_DECAP_FAIL,
-    SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_INVALID_STATE_LOCAL,
-    SPDM_STATUS_SESSION_NUMBER_EXCEED,
+    SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_INVALID_STATE_LOCAL, SPDM_STATUS_RECEIVE_FAIL,
+    SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW, SPDM_STATUS_SESSION_NUMBER_EXCEED,
 };
+#[cfg(feature = "hashed-transcript-data")]
+use crate::error::SPDM_STATUS_INVALID_MSG_FIELD;
+#[cfg(all(feature = "alloc", not(feature = "hashed-transcript-data")))]
+use crate::error::SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE;
 
 use codec::enum_builder;
 use codec::{Codec, Reader, Writer};
@@ -51,6 +73,39 @@ impl Default for SpdmConnectionState {
     }
 }
 
+impl SpdmConnectionState {
+    /// Whether a connection in this state may move to `next`.
+    ///
+    /// VERSION restarts the connection from any state. CAPABILITIES and
+    /// ALGORITHMS each follow exactly the previous step. DIGESTS, CERTIFICATE
+    /// and CHALLENGE_AUTH may come in any order once algorithms are
+    /// negotiated. Nothing leads back to `SpdmConnectionNotStarted`; that
+    /// takes a context reset.
+    pub fn can_transition_to(&self, next: SpdmConnectionState) -> bool {
+        match next {
+            SpdmConnectionState::SpdmConnectionAfterVersion => true,
+            SpdmConnectionState::SpdmConnectionAfterCapabilities => {
+                *self == SpdmConnectionState::SpdmConnectionAfterVersion
+            }
+            SpdmConnectionState::SpdmConnectionNegotiated => {
+                *self == SpdmConnectionState::SpdmConnectionAfterCapabilities
+            }
+            SpdmConnectionState::SpdmConnectionAfterDigest
+            | SpdmConnectionState::SpdmConnectionAfterCertificate
+            | SpdmConnectionState::SpdmConnectionAuthenticated => matches!(
+                *self,
+                SpdmConnectionState::SpdmConnectionNegotiated
+                    | SpdmConnectionState::SpdmConnectionAfterDigest
+                    | SpdmConnectionState::SpdmConnectionAfterCertificate
+                    | SpdmConnectionState::SpdmConnectionAuthenticated
+            ),
+            SpdmConnectionState::SpdmConnectionNotStarted | SpdmConnectionState::Unknown(_) => {
+                false
+            }
+        }
+    }
+}
+
 #[cfg(feature = "hashed-transcript-data")]
 pub use crate::crypto::SpdmHashCtx;
 
@@ -77,8 +132,23 @@ pub trait SpdmDeviceIo {
 
     fn receive(&mut self, buffer: &mut [u8], timeout: usize) -> Result<usize, usize>;
 
+    /// Receive one message, giving up after `timeout` microseconds with
+    /// `SPDM_STATUS_RECEIVE_TIMEOUT`. The default suits transports that
+    /// cannot bound the wait: it only passes the timeout on to `receive`,
+    /// which may block for longer.
+    fn receive_with_timeout(&mut self, buffer: &mut [u8], timeout: usize) -> SpdmResult<usize> {
+        self.receive(buffer, timeout)
+            .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)
+    }
+
     fn flush_all(&mut self) -> SpdmResult;
 
+    /// Whether several requests may be sent before the first response is
+    /// received. The responses must come back in request order.
+    fn supports_pipelining(&self) -> bool {
+        false
+    }
+
     #[cfg(feature = "downcast")]
     fn as_any(&mut self) -> &mut dyn Any;
 }
@@ -126,6 +196,37 @@ impl Debug for dyn SpdmTransportEncap {
     }
 }
 
+/// Observer for raw secured-message records, called with the session id,
+/// the sequence number of the record, the encrypted record bytes and whether
+/// the record is being sent (true) or received (false).
+pub type SpdmSecuredMessageObserver =
+    fn(session_id: u32, sequence_number: u64, record: &[u8], is_send: bool);
+
+/// Callback for events delivered by the responder in SEND_EVENT, called with
+/// the session id, the event count and the raw event list.
+pub type SpdmEventCallback = fn(session_id: u32, event_count: u32, event_list: &[u8]) -> SpdmResult;
+
+/// Transcript and session access surface of `SpdmContext`.
+///
+/// Extension code that only records messages into the transcripts and looks
+/// up sessions can be written against this trait, and unit tested with a
+/// mock instead of a fully provisioned `SpdmContext`.
+pub trait SpdmContextAccess {
+    fn get_immutable_session_via_id(&self, session_id: u32) -> Option<&SpdmSession>;
+    fn get_session_via_id(&mut self, session_id: u32) -> Option<&mut SpdmSession>;
+
+    fn append_message_a(&mut self, new_message: &[u8]) -> SpdmResult;
+    fn reset_message_a(&mut self);
+    fn append_message_b(&mut self, new_message: &[u8]) -> SpdmResult;
+    fn reset_message_b(&mut self);
+    fn append_message_c(&mut self, new_message: &[u8]) -> SpdmResult;
+    fn reset_message_c(&mut self);
+    fn append_message_m(&mut self, session_id: Option<u32>, new_message: &[u8]) -> SpdmResult;
+    fn reset_message_m(&mut self, session_id: Option<u32>);
+    fn append_message_k(&mut self, session_id: u32, new_message: &[u8]) -> SpdmResult;
+    fn reset_message_k(&mut self, session_id: u32);
+}
+
 pub struct SpdmContext<'a> {
     pub device_io: &'a mut dyn SpdmDeviceIo,
     pub transport_encap: &'a mut dyn SpdmTransportEncap,
@@ -141,6 +242,20 @@ pub struct SpdmContext<'a> {
     pub encap_context: SpdmEncapContext,
 
     pub session: [SpdmSession; config::MAX_SPDM_SESSION_COUNT],
+
+    pub secured_message_observer: Option<SpdmSecuredMessageObserver>,
+
+    #[cfg(feature = "alloc")]
+    pub message_capture: SpdmMessageCapture,
+
+    pub event_callback: Option<SpdmEventCallback>, // used by requester only
+    pub supported_event_groups: SpdmSupportedEventTypesResponsePayload, // used by responder only
+
+    // out of session L1/L2 transcript, see set_l1l2_transcript_storage().
+    #[cfg(feature = "alloc")]
+    l1l2_transcript_storage: Option<Box<dyn SpdmTranscriptStorage>>,
+    #[cfg(feature = "alloc")]
+    l1l2_transcript_started: bool,
 }
 
 impl<'a> SpdmContext<'a> {
@@ -150,6 +265,8 @@ impl<'a> SpdmContext<'a> {
         config_info: SpdmConfigInfo,
         provision_info: SpdmProvisionInfo,
     ) -> Self {
+        #[cfg(feature = "alloc")]
+        let message_capture = SpdmMessageCapture::new(config_info.message_capture_capacity);
         SpdmContext {
             device_io,
             transport_encap,
@@ -161,9 +278,37 @@ impl<'a> SpdmContext<'a> {
             #[cfg(feature = "mut-auth")]
             encap_context: SpdmEncapContext::default(),
             session: gen_array(config::MAX_SPDM_SESSION_COUNT),
+            secured_message_observer: None,
+            #[cfg(feature = "alloc")]
+            message_capture,
+            event_callback: None,
+            supported_event_groups: SpdmSupportedEventTypesResponsePayload::default(),
+            #[cfg(feature = "alloc")]
+            l1l2_transcript_storage: None,
+            #[cfg(feature = "alloc")]
+            l1l2_transcript_started: false,
         }
     }
 
+    /// Keep the out of session L1/L2 measurement transcript in `storage`
+    /// instead of the built-in one, from the next message on. In-session
+    /// transcripts are not affected.
+    #[cfg(feature = "alloc")]
+    pub fn set_l1l2_transcript_storage(&mut self, mut storage: Box<dyn SpdmTranscriptStorage>) {
+        storage.reset();
+        self.l1l2_transcript_storage = Some(storage);
+        self.l1l2_transcript_started = false;
+    }
+
+    /// Whether a MEASUREMENTS signature over the L1/L2 transcript of
+    /// `session_id` can be generated or verified, see `SpdmTranscriptStorage`.
+    #[cfg(all(feature = "alloc", not(feature = "hashed-transcript-data")))]
+    pub fn l1l2_transcript_signable(&self, session_id: Option<u32>) -> bool {
+        session_id.is_some()
+            || self.l1l2_transcript_storage.is_none()
+            || self.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion12.get_u8()
+    }
+
     pub fn get_hash_size(&self) -> u16 {
         self.negotiate_info.base_hash_sel.get_size()
     }
@@ -176,6 +321,13 @@ impl<'a> SpdmContext<'a> {
 
     pub fn reset_runtime_info(&mut self) {
         self.runtime_info = SpdmRuntimeInfo::default();
+        #[cfg(feature = "alloc")]
+        {
+            if let Some(storage) = self.l1l2_transcript_storage.as_mut() {
+                storage.reset();
+            }
+            self.l1l2_transcript_started = false;
+        }
     }
 
     pub fn reset_negotiate_info(&mut self) {
@@ -183,7 +335,15 @@ impl<'a> SpdmContext<'a> {
     }
 
     pub fn reset_peer_info(&mut self) {
-        self.peer_info = SpdmPeerInfo::default();
+        // chains provisioned locally do not come from the peer.
+        let mut peer_info = SpdmPeerInfo::default();
+        for slot in 0..SPDM_MAX_SLOT_NUMBER {
+            if self.peer_info.peer_cert_chain_trust[slot] == Some(SpdmCertChainTrust::Provisioned) {
+                peer_info.peer_cert_chain[slot] = self.peer_info.peer_cert_chain[slot].take();
+                peer_info.peer_cert_chain_trust[slot] = Some(SpdmCertChainTrust::Provisioned);
+            }
+        }
+        self.peer_info = peer_info;
     }
 
     pub fn reset_context(&mut self) {
@@ -196,12 +356,47 @@ impl<'a> SpdmContext<'a> {
         }
     }
 
+    /// Forget the current peer so the context can be pointed at another
+    /// device: negotiated parameters, connection and session state,
+    /// transcripts and every certificate chain fetched from the peer.
+    /// Local configuration and provisioning are kept.
+    pub fn clear_peer_data(&mut self) {
+        self.reset_context();
+        #[cfg(feature = "mut-auth")]
+        {
+            self.encap_context = SpdmEncapContext::default();
+        }
+    }
+
+    /// Drop the provisioned root certificate peer chains are checked
+    /// against. Provision the next peer's root before fetching its chain,
+    /// without one any root is accepted.
+    pub fn clear_provisioned_peer_data(&mut self) {
+        self.provision_info.peer_root_cert_data = None;
+    }
+
     pub fn get_immutable_session_via_id(&self, session_id: u32) -> Option<&SpdmSession> {
         self.session
             .iter()
             .find(|&session| session.get_session_id() == session_id)
     }
 
+    /// Whether messages of `session_id` go in the clear: a KEY_EXCHANGE
+    /// session still handshaking, with the handshake negotiated in the
+    /// clear. PSK sessions are always secured.
+    pub fn is_handshake_in_the_clear(&self, session_id: u32) -> bool {
+        if !self.negotiate_info.handshake_in_the_clear() {
+            return false;
+        }
+        match self.get_immutable_session_via_id(session_id) {
+            Some(session) => {
+                !session.get_use_psk()
+                    && session.get_session_state() == SpdmSessionState::SpdmSessionHandshaking
+            }
+            None => false,
+        }
+    }
+
     pub fn get_session_via_id(&mut self, session_id: u32) -> Option<&mut SpdmSession> {
         self.session
             .iter_mut()
@@ -212,6 +407,53 @@ impl<'a> SpdmContext<'a> {
         self.get_session_via_id(0)
     }
 
+    /// Mark the session as the most recently active one.
+    pub fn touch_session(&mut self, session_id: u32) {
+        let last_activity = self
+            .session
+            .iter()
+            .map(|session| session.get_last_activity())
+            .max()
+            .unwrap_or(0);
+        if let Some(session) = self.get_session_via_id(session_id) {
+            session.set_last_activity(last_activity + 1);
+        }
+    }
+
+    /// Make room for one more session under `max_session_count`, as
+    /// `session_limit_policy` allows.
+    pub fn reserve_session(&mut self) -> SpdmResult {
+        let max_session_count = match self.config_info.max_session_count {
+            0 => MAX_SPDM_SESSION_COUNT,
+            max_session_count => max_session_count.min(MAX_SPDM_SESSION_COUNT),
+        };
+        loop {
+            let session_count = self
+                .session
+                .iter()
+                .filter(|session| session.get_session_id() != INVALID_SESSION_ID)
+                .count();
+            if session_count < max_session_count {
+                return Ok(());
+            }
+            if self.config_info.session_limit_policy != SpdmSessionLimitPolicy::EvictOldestIdle {
+                return Err(SPDM_STATUS_SESSION_NUMBER_EXCEED);
+            }
+
+            let session = self
+                .session
+                .iter_mut()
+                .filter(|session| {
+                    session.get_session_state() == SpdmSessionState::SpdmSessionEstablished
+                })
+                .min_by_key(|session| session.get_last_activity())
+                .ok_or(SPDM_STATUS_SESSION_NUMBER_EXCEED)?;
+            let session_id = session.get_session_id();
+            info!("evict idle session {:08x}\n", session_id);
+            let _ = session.teardown(session_id);
+        }
+    }
+
     pub fn get_session_status(&self) -> [(u32, SpdmSessionState); config::MAX_SPDM_SESSION_COUNT] {
         let mut status =
             [(0u32, SpdmSessionState::SpdmSessionNotStarted); config::MAX_SPDM_SESSION_COUNT];
@@ -378,6 +620,21 @@ impl<'a> SpdmContext<'a> {
     }
 
     pub fn append_message_m(&mut self, session_id: Option<u32>, new_message: &[u8]) -> SpdmResult {
+        #[cfg(feature = "alloc")]
+        if session_id.is_none() {
+            if let Some(storage) = self.l1l2_transcript_storage.as_mut() {
+                if !self.l1l2_transcript_started {
+                    if self.negotiate_info.spdm_version_sel.get_u8()
+                        >= SpdmVersion::SpdmVersion12.get_u8()
+                    {
+                        storage.append(self.runtime_info.message_a.as_ref())?;
+                    }
+                    self.l1l2_transcript_started = true;
+                }
+                return storage.append(new_message);
+            }
+        }
+
         #[cfg(not(feature = "hashed-transcript-data"))]
         match session_id {
             None => self
@@ -461,6 +718,14 @@ impl<'a> SpdmContext<'a> {
         Ok(())
     }
     pub fn reset_message_m(&mut self, session_id: Option<u32>) {
+        #[cfg(feature = "alloc")]
+        if session_id.is_none() {
+            if let Some(storage) = self.l1l2_transcript_storage.as_mut() {
+                storage.reset();
+                self.l1l2_transcript_started = false;
+            }
+        }
+
         #[cfg(not(feature = "hashed-transcript-data"))]
         match session_id {
             None => self.runtime_info.message_m.reset_message(),
@@ -867,6 +1132,90 @@ impl<'a> SpdmContext<'a> {
         Ok(transcript_hash)
     }
 
+    #[cfg(not(feature = "hashed-transcript-data"))]
+    pub fn calc_message_l1l2_data(&self, session_id: Option<u32>) -> SpdmResult<ManagedBufferL1L2> {
+        #[cfg(feature = "alloc")]
+        if session_id.is_none() && self.l1l2_transcript_storage.is_some() {
+            error!("L1/L2 transcript storage only provides the hash!\n");
+            return Err(SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE);
+        }
+
+        let mut message_l1l2 = ManagedBufferL1L2::default();
+        if self.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion12.get_u8() {
+            message_l1l2
+                .append_message(self.runtime_info.message_a.as_ref())
+                .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+        }
+
+        let message_m = match session_id {
+            None => &self.runtime_info.message_m,
+            Some(session_id) => {
+                &self
+                    .get_immutable_session_via_id(session_id)
+                    .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
+                    .runtime_info
+                    .message_m
+            }
+        };
+        message_l1l2
+            .append_message(message_m.as_ref())
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+        debug!("message_l1l2 - {:02x?}", message_l1l2.as_ref());
+
+        Ok(message_l1l2)
+    }
+
+    /// Hash of the L1/L2 measurement transcript as it stands, i.e. the value a
+    /// MEASUREMENTS signature covers. The transcript itself is left untouched.
+    #[cfg(not(feature = "hashed-transcript-data"))]
+    pub fn calc_message_l1l2_hash(&self, session_id: Option<u32>) -> SpdmResult<SpdmDigestStruct> {
+        #[cfg(feature = "alloc")]
+        if let (None, Some(storage)) = (session_id, self.l1l2_transcript_storage.as_ref()) {
+            let message_l1l2_hash = storage.finalize(self.negotiate_info.base_hash_sel)?;
+            debug!("message_l1l2_hash - {:02x?}", message_l1l2_hash.as_ref());
+            return Ok(message_l1l2_hash);
+        }
+
+        let message_l1l2 = self.calc_message_l1l2_data(session_id)?;
+        let message_l1l2_hash =
+            crypto::hash::hash_all(self.negotiate_info.base_hash_sel, message_l1l2.as_ref())
+                .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
+        debug!("message_l1l2_hash - {:02x?}", message_l1l2_hash.as_ref());
+        Ok(message_l1l2_hash)
+    }
+
+    /// Hash of the L1/L2 measurement transcript as it stands, i.e. the value a
+    /// MEASUREMENTS signature covers. The transcript itself is left untouched.
+    #[cfg(feature = "hashed-transcript-data")]
+    pub fn calc_message_l1l2_hash(&self, session_id: Option<u32>) -> SpdmResult<SpdmDigestStruct> {
+        #[cfg(feature = "alloc")]
+        if let (None, Some(storage)) = (session_id, self.l1l2_transcript_storage.as_ref()) {
+            let message_l1l2_hash = storage.finalize(self.negotiate_info.base_hash_sel)?;
+            debug!("message_l1l2_hash - {:02x?}", message_l1l2_hash.as_ref());
+            return Ok(message_l1l2_hash);
+        }
+
+        let digest_context_l1l2 = match session_id {
+            None => &self.runtime_info.digest_context_l1l2,
+            Some(session_id) => {
+                &self
+                    .get_immutable_session_via_id(session_id)
+                    .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?
+                    .runtime_info
+                    .digest_context_l1l2
+            }
+        };
+        let message_l1l2_hash = crypto::hash::hash_ctx_finalize(
+            digest_context_l1l2
+                .as_ref()
+                .cloned()
+                .ok_or(SPDM_STATUS_CRYPTO_ERROR)?,
+        )
+        .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
+        debug!("message_l1l2_hash - {:02x?}", message_l1l2_hash.as_ref());
+        Ok(message_l1l2_hash)
+    }
+
     pub fn get_certchain_hash_local(
         &self,
         use_psk: bool,
@@ -951,9 +1300,101 @@ impl<'a> SpdmContext<'a> {
         }
     }
 
+    /// The largest SPDM messages that go in one transfer, without chunking,
+    /// given the negotiated DataTransferSize of each side and transport
+    /// buffers of `send_buffer_size` and `receive_buffer_size` bytes. The
+    /// transport overhead is found by encapsulating messages of growing
+    /// size, so call it between requests and their responses.
+    /// `secured_message` counts the secured message record and its scratch
+    /// buffers in as well.
+    pub fn get_message_size_limit(
+        &mut self,
+        is_requester: bool,
+        secured_message: bool,
+        send_buffer_size: usize,
+        receive_buffer_size: usize,
+    ) -> SpdmMessageSizeLimit {
+        let (send_transfer_size, receive_transfer_size) = if is_requester {
+            (
+                self.negotiate_info.rsp_data_transfer_size_sel,
+                self.negotiate_info.req_data_transfer_size_sel,
+            )
+        } else {
+            (
+                self.negotiate_info.req_data_transfer_size_sel,
+                self.negotiate_info.rsp_data_transfer_size_sel,
+            )
+        };
+        let (send_buffer_size, receive_buffer_size) = if secured_message {
+            (
+                send_buffer_size.min(config::SENDER_BUFFER_SIZE),
+                receive_buffer_size.min(config::RECEIVER_BUFFER_SIZE),
+            )
+        } else {
+            (send_buffer_size, receive_buffer_size)
+        };
+
+        let max_send_size = self.get_max_encapsulated_size(secured_message, send_buffer_size);
+        let max_receive_size =
+            self.get_max_encapsulated_size(secured_message, receive_buffer_size);
+        // before SPDM 1.2 no DataTransferSize is negotiated.
+        let limit = |transfer_size: u32, max_size: usize| match transfer_size {
+            0 => max_size,
+            transfer_size => max_size.min(transfer_size as usize),
+        };
+        SpdmMessageSizeLimit {
+            max_send_size: limit(send_transfer_size, max_send_size),
+            max_receive_size: limit(receive_transfer_size, max_receive_size),
+        }
+    }
+
+    // The largest SPDM message the transport, and the secured message
+    // record if `secured_message`, fit in `buffer_size` bytes. Transports
+    // may pad or split messages into packets, so this searches for the
+    // size instead of taking a fixed header size off.
+    fn get_max_encapsulated_size(&mut self, secured_message: bool, buffer_size: usize) -> usize {
+        let message = vec![0u8; buffer_size];
+        let mut buffer = vec![0u8; buffer_size];
+
+        let (mut low, mut high) = (0usize, buffer_size);
+        while low < high {
+            let size = high - (high - low) / 2;
+            if self
+                .transport_encap
+                .encap(&message[..size], &mut buffer, secured_message)
+                .is_ok()
+            {
+                low = size;
+            } else {
+                high = size - 1;
+            }
+        }
+        if !secured_message {
+            return low;
+        }
+
+        // session id, sequence number, length, application data length and
+        // the AEAD tag around the application message.
+        let record_overhead = 4
+            + self.transport_encap.get_sequence_number_count() as usize
+            + 2
+            + 2
+            + self.negotiate_info.aead_sel.get_tag_size() as usize;
+        let app_overhead = match self.transport_encap.encap_app(&message[..1], &mut buffer, false) {
+            Ok(used) => used - 1,
+            Err(_) => return 0,
+        };
+        low.saturating_sub(record_overhead + app_overhead)
+    }
+
     pub fn encap(&mut self, send_buffer: &[u8], transport_buffer: &mut [u8]) -> SpdmResult<usize> {
-        self.transport_encap
-            .encap(send_buffer, transport_buffer, false)
+        let used = self
+            .transport_encap
+            .encap(send_buffer, transport_buffer, false)?;
+        #[cfg(feature = "alloc")]
+        self.message_capture
+            .record(SpdmCaptureDirection::Sent, send_buffer);
+        Ok(used)
     }
 
     pub fn encode_secured_message(
@@ -973,15 +1414,36 @@ impl<'a> SpdmContext<'a> {
             .get_session_via_id(session_id)
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
 
+        if spdm_session.is_sequence_number_exhausted(is_requester)
+            && (is_app_message || !is_rekey_or_end_session(send_buffer))
+        {
+            error!("sequence number exhausted, KEY_UPDATE or END_SESSION required\n");
+            return Err(SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW);
+        }
+
+        let sequence_number = spdm_session.get_sequence_number(is_requester);
         let mut encoded_send_buffer = [0u8; config::SENDER_BUFFER_SIZE];
         let encode_size = spdm_session.encode_spdm_secured_message(
             &app_buffer[0..used],
             &mut encoded_send_buffer,
             is_requester,
         )?;
-
-        self.transport_encap
-            .encap(&encoded_send_buffer[..encode_size], transport_buffer, true)
+        self.observe_secured_message(
+            session_id,
+            sequence_number,
+            &encoded_send_buffer[..encode_size],
+            true,
+        );
+
+        let used = self.transport_encap.encap(
+            &encoded_send_buffer[..encode_size],
+            transport_buffer,
+            true,
+        )?;
+        #[cfg(feature = "alloc")]
+        self.message_capture
+            .record(SpdmCaptureDirection::Sent, send_buffer);
+        Ok(used)
     }
 
     pub fn decap(
@@ -997,6 +1459,9 @@ impl<'a> SpdmContext<'a> {
             return Err(SPDM_STATUS_DECAP_FAIL); //need check
         }
 
+        #[cfg(feature = "alloc")]
+        self.message_capture
+            .record(SpdmCaptureDirection::Received, &receive_buffer[..used]);
         Ok(used)
     }
 
@@ -1015,13 +1480,30 @@ impl<'a> SpdmContext<'a> {
             return Err(SPDM_STATUS_DECAP_FAIL);
         }
 
+        self.decode_secured_record(session_id, &encoded_receive_buffer[..used], receive_buffer)
+    }
+
+    /// `decode_secured_message` for a secured message already taken out of
+    /// its transport.
+    pub fn decode_secured_record(
+        &mut self,
+        session_id: u32,
+        encoded_receive_buffer: &[u8],
+        receive_buffer: &mut [u8],
+    ) -> SpdmResult<usize> {
+        let sequence_number = self
+            .get_session_via_id(session_id)
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
+            .get_sequence_number(false);
+        self.observe_secured_message(session_id, sequence_number, encoded_receive_buffer, false);
+
         let spdm_session = self
             .get_session_via_id(session_id)
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
 
         let mut app_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
         let decode_size = spdm_session.decode_spdm_secured_message(
-            &encoded_receive_buffer[..used],
+            encoded_receive_buffer,
             &mut app_buffer,
             false,
         )?;
@@ -1030,14 +1512,81 @@ impl<'a> SpdmContext<'a> {
             .transport_encap
             .decap_app(&app_buffer[0..decode_size], receive_buffer)?;
 
+        #[cfg(feature = "alloc")]
+        self.message_capture
+            .record(SpdmCaptureDirection::Received, &receive_buffer[..used.0]);
         Ok(used.0)
     }
+
+    pub fn observe_secured_message(
+        &self,
+        session_id: u32,
+        sequence_number: u64,
+        record: &[u8],
+        is_send: bool,
+    ) {
+        if let Some(observer) = self.secured_message_observer {
+            observer(session_id, sequence_number, record, is_send);
+        }
+    }
+}
+
+fn is_rekey_or_end_session(send_buffer: &[u8]) -> bool {
+    if send_buffer.len() < 2 {
+        return false;
+    }
+    let opcode = send_buffer[1];
+    opcode == SpdmRequestResponseCode::SpdmRequestKeyUpdate.get_u8()
+        || opcode == SpdmRequestResponseCode::SpdmResponseKeyUpdateAck.get_u8()
+        || opcode == SpdmRequestResponseCode::SpdmRequestEndSession.get_u8()
+        || opcode == SpdmRequestResponseCode::SpdmResponseEndSessionAck.get_u8()
+}
+
+impl<'a> SpdmContextAccess for SpdmContext<'a> {
+    fn get_immutable_session_via_id(&self, session_id: u32) -> Option<&SpdmSession> {
+        SpdmContext::get_immutable_session_via_id(self, session_id)
+    }
+    fn get_session_via_id(&mut self, session_id: u32) -> Option<&mut SpdmSession> {
+        SpdmContext::get_session_via_id(self, session_id)
+    }
+
+    fn append_message_a(&mut self, new_message: &[u8]) -> SpdmResult {
+        SpdmContext::append_message_a(self, new_message)
+    }
+    fn reset_message_a(&mut self) {
+        SpdmContext::reset_message_a(self)
+    }
+    fn append_message_b(&mut self, new_message: &[u8]) -> SpdmResult {
+        SpdmContext::append_message_b(self, new_message)
+    }
+    fn reset_message_b(&mut self) {
+        SpdmContext::reset_message_b(self)
+    }
+    fn append_message_c(&mut self, new_message: &[u8]) -> SpdmResult {
+        SpdmContext::append_message_c(self, new_message)
+    }
+    fn reset_message_c(&mut self) {
+        SpdmContext::reset_message_c(self)
+    }
+    fn append_message_m(&mut self, session_id: Option<u32>, new_message: &[u8]) -> SpdmResult {
+        SpdmContext::append_message_m(self, session_id, new_message)
+    }
+    fn reset_message_m(&mut self, session_id: Option<u32>) {
+        SpdmContext::reset_message_m(self, session_id)
+    }
+    fn append_message_k(&mut self, session_id: u32, new_message: &[u8]) -> SpdmResult {
+        SpdmContext::append_message_k(self, session_id, new_message)
+    }
+    fn reset_message_k(&mut self, session_id: u32) {
+        SpdmContext::reset_message_k(self, session_id)
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct SpdmConfigInfo {
     pub spdm_version: [SpdmVersion; MAX_SPDM_VERSION_COUNT],
-    pub req_capabilities: SpdmRequestCapabilityFlags,
+    pub spdm_version_preference: [SpdmVersion; MAX_SPDM_VERSION_COUNT], // used by requester only, empty selects the highest common version
+    pub req_capabilities: SpdmRequestCapabilityFlags, // empty lets the requester derive them
     pub rsp_capabilities: SpdmResponseCapabilityFlags,
     pub req_ct_exponent: u8,
     pub rsp_ct_exponent: u8,
@@ -1049,18 +1598,104 @@ pub struct SpdmConfigInfo {
     pub aead_algo: SpdmAeadAlgo,
     pub req_asym_algo: SpdmReqAsymAlgo,
     pub key_schedule_algo: SpdmKeyScheduleAlgo,
+    pub ext_asym_algo: Option<SpdmExtAlgStruct>, // used by requester only, offered next to base_asym_algo, no backend can use it if selected
+    pub ext_hash_algo: Option<SpdmExtAlgStruct>, // used by requester only, offered next to base_hash_algo, no backend can use it if selected
     pub opaque_support: SpdmOpaqueSupport,
     pub session_policy: u8,
     pub runtime_content_change_support: bool,
+    pub require_content_change_support: bool, // used by requester only
+    pub check_measurement_count: bool, // used by requester only, RequestAll must return the QueryTotalNumber count unless content changed
+    pub unsigned_measurement_fallback: bool, // used by requester only, drops SIGNATURE_REQUESTED when the responder lacks MEAS_CAP_SIG
+    pub fixed_length_measurement_request: bool, // used by requester only, unsigned GET_MEASUREMENTS still carry a zero nonce and SlotIDParam for responders expecting them
+    pub reject_echoed_measurement_nonce: bool, // used by requester only, fails MEASUREMENTS whose nonce is the request nonce instead of only warning
+    pub message_capture_capacity: usize, // number of sent and received messages kept in SpdmContext::message_capture, 0 keeps none
     pub data_transfer_size: u32,
     pub max_spdm_msg_size: u32,
+    pub cert_portion_length: u16, // used by requester only, 0 selects the default
+    pub min_rsa_key_bits: u16, // used by requester only, 0 accepts any RSA key size
+    pub allow_self_signed_peer_cert: bool, // used by requester only, trust on first use
+    pub cert_model: [SpdmCertificateModel; SPDM_MAX_SLOT_NUMBER], // used by responder only, CertModel of each slot reported in CERTIFICATE from SPDM 1.3
     pub heartbeat_period: u8, // used by responder only
+    pub basic_mut_auth_requested: bool, // used by responder only
+    pub require_mut_auth: bool, // used by responder only, refuses sessions from requesters that cannot sign FINISH
+    pub serve_cached_measurements: bool, // used by responder only, ignores the requester nonce
     pub secure_spdm_version: [u8; MAX_SECURE_SPDM_VERSION_COUNT], // used by responder only
+    pub tolerate_malformed_opaque: bool, // keeps opaque data not in the negotiated format instead of failing
+    pub max_session_count: usize, // used by responder only, 0 or more than MAX_SPDM_SESSION_COUNT allows MAX_SPDM_SESSION_COUNT
+    pub session_limit_policy: SpdmSessionLimitPolicy, // used by responder only
+}
+
+impl SpdmConfigInfo {
+    /// Requester capabilities backed by the configured algorithms and the
+    /// provisioned certificate chains. `RequesterContext::new` advertises
+    /// these when `req_capabilities` is left empty.
+    pub fn derive_req_capabilities(
+        &self,
+        provision_info: &SpdmProvisionInfo,
+    ) -> SpdmRequestCapabilityFlags {
+        let mut flags = SpdmRequestCapabilityFlags::empty();
+
+        if !self.aead_algo.is_empty() && !self.key_schedule_algo.is_empty() {
+            flags |= SpdmRequestCapabilityFlags::ENCRYPT_CAP
+                | SpdmRequestCapabilityFlags::MAC_CAP
+                | SpdmRequestCapabilityFlags::PSK_CAP
+                | SpdmRequestCapabilityFlags::HBEAT_CAP
+                | SpdmRequestCapabilityFlags::KEY_UPD_CAP;
+            if !self.dhe_algo.is_empty() {
+                flags |= SpdmRequestCapabilityFlags::KEY_EX_CAP;
+            }
+        }
+
+        let has_cert_chain = provision_info
+            .my_cert_chain_data
+            .iter()
+            .any(|cert_chain| cert_chain.is_some())
+            || provision_info
+                .my_cert_chain
+                .iter()
+                .any(|cert_chain| cert_chain.is_some());
+        if has_cert_chain && !self.req_asym_algo.is_empty() {
+            flags |= SpdmRequestCapabilityFlags::CERT_CAP | SpdmRequestCapabilityFlags::CHAL_CAP;
+            #[cfg(feature = "mut-auth")]
+            {
+                flags |= SpdmRequestCapabilityFlags::MUT_AUTH_CAP
+                    | SpdmRequestCapabilityFlags::ENCAP_CAP;
+            }
+        }
+
+        flags
+    }
+}
+
+/// How many responder nonces `SpdmNonceHistory` remembers.
+pub const SPDM_NONCE_HISTORY_SIZE: usize = 8;
+
+/// The last `SPDM_NONCE_HISTORY_SIZE` responder nonces accepted, the
+/// oldest one forgotten first.
+#[derive(Debug, Clone, Default)]
+pub struct SpdmNonceHistory {
+    nonces: [[u8; SPDM_NONCE_SIZE]; SPDM_NONCE_HISTORY_SIZE],
+    count: usize,
+    next: usize,
+}
+
+impl SpdmNonceHistory {
+    pub fn contains(&self, nonce: &[u8; SPDM_NONCE_SIZE]) -> bool {
+        self.nonces[..self.count].contains(nonce)
+    }
+
+    pub fn insert(&mut self, nonce: &[u8; SPDM_NONCE_SIZE]) {
+        self.nonces[self.next] = *nonce;
+        self.next = (self.next + 1) % SPDM_NONCE_HISTORY_SIZE;
+        self.count = (self.count + 1).min(SPDM_NONCE_HISTORY_SIZE);
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct SpdmNegotiateInfo {
     pub spdm_version_sel: SpdmVersion,
+    pub rsp_spdm_version_count: u8, // used by requester only, number of valid rsp_spdm_versions
+    pub rsp_spdm_versions: [SpdmVersionStruct; MAX_SPDM_VERSION_COUNT], // used by requester only, as advertised in VERSION
     pub req_capabilities_sel: SpdmRequestCapabilityFlags,
     pub rsp_capabilities_sel: SpdmResponseCapabilityFlags,
     pub req_ct_exponent_sel: u8,
@@ -1073,6 +1708,8 @@ pub struct SpdmNegotiateInfo {
     pub aead_sel: SpdmAeadAlgo,
     pub req_asym_sel: SpdmReqAsymAlgo,
     pub key_schedule_sel: SpdmKeyScheduleAlgo,
+    pub ext_asym_sel: Option<SpdmExtAlgStruct>, // the requester fails negotiation without a base_asym_sel
+    pub ext_hash_sel: Option<SpdmExtAlgStruct>, // the requester fails negotiation without a base_hash_sel
     pub opaque_data_support: SpdmOpaqueSupport,
     pub termination_policy_set: bool, // used by responder to take action when code or configuration changed.
     pub req_data_transfer_size_sel: u32, // spdm 1.2
@@ -1081,9 +1718,52 @@ pub struct SpdmNegotiateInfo {
     pub rsp_max_spdm_msg_size_sel: u32, // spdm 1.2
 }
 
+impl SpdmNegotiateInfo {
+    /// The version list of the last VERSION response, in the order the
+    /// responder advertised it. Empty before GET_VERSION.
+    pub fn rsp_spdm_versions(&self) -> &[SpdmVersionStruct] {
+        &self.rsp_spdm_versions[..(self.rsp_spdm_version_count as usize)
+            .min(self.rsp_spdm_versions.len())]
+    }
+
+    /// Whether FINISH and FINISH_RSP go in the clear. Only the case when
+    /// both sides set HANDSHAKE_IN_THE_CLEAR_CAP, else the handshake is
+    /// protected with the handshake secrets.
+    pub fn handshake_in_the_clear(&self) -> bool {
+        self.req_capabilities_sel
+            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
+            && self
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
+    }
+
+    /// Whether messages larger than the DataTransferSize of the receiver
+    /// may be sent in chunks: SPDM 1.2 on, with CHUNK_CAP on both sides.
+    pub fn chunk_supported(&self) -> bool {
+        self.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion12.get_u8()
+            && self
+                .req_capabilities_sel
+                .contains(SpdmRequestCapabilityFlags::CHUNK_CAP)
+            && self
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::CHUNK_CAP)
+    }
+
+    /// Whether DIGESTS carries KeyPairID, CertificateInfo and KeyUsageMask
+    /// after the digests (MULTI_KEY_CONN_RSP). Only a SPDM 1.3 responder
+    /// with MULTI_KEY_CAP_ONLY, the negotiated MULTI_KEY_CAP_NEG mode is not
+    /// supported.
+    pub fn multi_key_conn_rsp(&self) -> bool {
+        self.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion13.get_u8()
+            && self
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::MULTI_KEY_CAP_ONLY)
+    }
+}
+
 const MAX_MANAGED_BUFFER_A_SIZE: usize = 150 + 2 * MAX_SPDM_VERSION_COUNT;
 const MAX_MANAGED_BUFFER_B_SIZE: usize =
-    24 + SPDM_MAX_HASH_SIZE * SPDM_MAX_SLOT_NUMBER + config::MAX_SPDM_CERT_CHAIN_DATA_SIZE;
+    24 + (SPDM_MAX_HASH_SIZE + 4) * SPDM_MAX_SLOT_NUMBER + config::MAX_SPDM_CERT_CHAIN_DATA_SIZE;
 const MAX_MANAGED_BUFFER_C_SIZE: usize =
     78 + SPDM_MAX_HASH_SIZE * 2 + SPDM_MAX_ASYM_KEY_SIZE + MAX_SPDM_OPAQUE_SIZE;
 const MAX_MANAGED_BUFFER_M_SIZE: usize = 47
@@ -1435,10 +2115,33 @@ pub struct SpdmRuntimeInfo {
 }
 
 impl SpdmRuntimeInfo {
+    /// Overwrite the state without checking the transition, for restoring
+    /// a saved connection. Message flows use `transition_connection_state`.
     pub fn set_connection_state(&mut self, connection_state: SpdmConnectionState) {
         self.connection_state = connection_state;
     }
 
+    /// Move to `next` if `SpdmConnectionState::can_transition_to` allows it.
+    /// An illegal transition is an ordering bug in the caller: it asserts
+    /// in debug builds and fails with SPDM_STATUS_INVALID_STATE_LOCAL
+    /// otherwise, leaving the state unchanged.
+    pub fn transition_connection_state(&mut self, next: SpdmConnectionState) -> SpdmResult {
+        if !self.connection_state.can_transition_to(next) {
+            error!(
+                "!!! illegal connection state transition {:?} -> {:?} !!!\n",
+                self.connection_state, next
+            );
+            debug_assert!(
+                false,
+                "illegal connection state transition {:?} -> {:?}",
+                self.connection_state, next
+            );
+            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+        }
+        self.connection_state = next;
+        Ok(())
+    }
+
     pub fn get_connection_state(&self) -> SpdmConnectionState {
         self.connection_state
     }
@@ -1472,6 +2175,8 @@ impl SpdmRuntimeInfo {
 pub struct SpdmProvisionInfo {
     pub my_cert_chain_data: [Option<SpdmCertChainData>; SPDM_MAX_SLOT_NUMBER],
     pub my_cert_chain: [Option<SpdmCertChainBuffer>; SPDM_MAX_SLOT_NUMBER],
+    // Reported in DIGESTS when the responder sets MULTI_KEY_CAP_ONLY.
+    pub my_key_usage_mask: [SpdmKeyUsageMask; SPDM_MAX_SLOT_NUMBER],
     pub peer_root_cert_data: Option<SpdmCertChainData>,
 }
 
@@ -1479,6 +2184,63 @@ pub struct SpdmProvisionInfo {
 pub struct SpdmPeerInfo {
     pub peer_cert_chain: [Option<SpdmCertChainBuffer>; SPDM_MAX_SLOT_NUMBER],
     pub peer_cert_chain_temp: Option<SpdmCertChainBuffer>,
+    // None when the peer did not report a key usage for the slot, the key
+    // may then be used for anything (SPDM 1.2 and earlier).
+    pub peer_key_usage_mask: [Option<SpdmKeyUsageMask>; SPDM_MAX_SLOT_NUMBER],
+    // Slots that hold a certificate chain according to the last DIGESTS,
+    // None until DIGESTS has been received.
+    pub peer_provisioned_slot_mask: Option<u8>,
+    // How the certificate chain of each slot was accepted by
+    // GET_CERTIFICATE or that it was provisioned locally, None for a chain
+    // that came otherwise.
+    pub peer_cert_chain_trust: [Option<SpdmCertChainTrust>; SPDM_MAX_SLOT_NUMBER],
+    // CertModel of each slot from its last CERTIFICATE, None before SPDM
+    // 1.3.
+    pub peer_cert_model: [Option<SpdmCertificateModel>; SPDM_MAX_SLOT_NUMBER],
+    // Digest of each slot from the last DIGESTS, None for a slot it did
+    // not report. GET_CERTIFICATE checks the fetched chain against it.
+    pub peer_digests: [Option<SpdmDigestStruct>; SPDM_MAX_SLOT_NUMBER],
+}
+
+/// How a peer certificate chain was accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpdmCertChainTrust {
+    /// Every certificate is signed by its predecessor, up to the root.
+    Chained,
+    /// A single self-signed certificate, accepted as is because
+    /// `SpdmConfigInfo::allow_self_signed_peer_cert` is set. Nothing vouches
+    /// for its issuer.
+    SelfSignedUnverifiedIssuer,
+    /// Stored as received in `RequesterContext::decode_only` mode. Neither
+    /// the chain nor its digest from DIGESTS was checked, and it is not in
+    /// the transcript.
+    Unverified,
+    /// Provisioned locally rather than fetched from the peer, see
+    /// `ResponderContext::provision_requester_cert_chain`. Kept when the
+    /// peer info is reset.
+    Provisioned,
+}
+
+/// What a responder does with a new session once `max_session_count`
+/// sessions are open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpdmSessionLimitPolicy {
+    /// Refuse the new session with ERROR(SessionLimitExceeded).
+    #[default]
+    Reject,
+    /// End the established session that has been idle the longest to make
+    /// room. Sessions still in their handshake are never ended, the new one
+    /// is refused if all of them are.
+    EvictOldestIdle,
+}
+
+/// The largest SPDM messages that go in one transfer, as returned by
+/// `SpdmContext::get_message_size_limit`. A larger one is sent or received
+/// in chunks if both sides support it, else it fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpdmMessageSizeLimit {
+    pub max_send_size: usize,
+    pub max_receive_size: usize,
 }
 
 #[cfg(feature = "mut-auth")]
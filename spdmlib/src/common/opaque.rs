@@ -17,7 +17,9 @@ pub const MAX_SPDM_OPAQUE_SIZE: usize = 1024;
 pub const MAX_SECURE_SPDM_VERSION_COUNT: usize = 0x02;
 pub const MAX_OPAQUE_LIST_ELEMENTS_COUNT: usize = 3;
 
-pub const DMTF_SPEC_ID: u32 = 0x444D546;
+/// "DMTF" as a big-endian u32, the SpecID of opaque data in the general
+/// format before SPDM 1.2.
+pub const DMTF_SPEC_ID: u32 = 0x444D_5446;
 pub const DMTF_OPAQUE_VERSION: u8 = 0x01;
 pub const SM_DATA_VERSION: u8 = 0x01;
 pub const DMTF_ID: u8 = 0x00;
@@ -528,6 +530,166 @@ impl SpdmCodec for SecuredMessageDMTFSupportedVersion {
     }
 }
 
+/// One element of the general opaque data format, its data left as is.
+/// Elements defined by the DMTF have `DMTF_ID` and no vendor ID, their data
+/// starts with SMDataVersion and SMDataID.
+#[derive(Clone, Debug)]
+pub struct OpaqueElement {
+    pub header: OpaqueElementHeader,
+    pub data_len: u16,
+    pub data: [u8; MAX_SPDM_OPAQUE_SIZE],
+}
+
+impl Default for OpaqueElement {
+    fn default() -> Self {
+        Self {
+            header: OpaqueElementHeader::default(),
+            data_len: 0,
+            data: [0u8; MAX_SPDM_OPAQUE_SIZE],
+        }
+    }
+}
+
+impl OpaqueElement {
+    pub fn is_dmtf(&self) -> bool {
+        self.header.id == DMTF_ID && self.header.vendor_len == DMTF_VENDOR_LEN
+    }
+
+    /// SMDataID of a DMTF element of SMDataVersion `SM_DATA_VERSION`.
+    pub fn dmtf_sm_data_id(&self) -> Option<u8> {
+        if !self.is_dmtf() || self.data_len < 2 || self.data[0] != SM_DATA_VERSION {
+            return None;
+        }
+        Some(self.data[1])
+    }
+
+    // ID, VendorLen, VendorID, OpaqueElementDataLen and the data, without
+    // the AlignPadding.
+    fn unpadded_len(&self) -> usize {
+        2 + self.header.vendor_len as usize + 2 + self.data_len as usize
+    }
+}
+
+impl SpdmCodec for OpaqueElement {
+    fn spdm_encode(
+        &self,
+        context: &mut SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        if self.header.vendor_len as usize > MAX_SPDM_VENDOR_DEFINED_VENDOR_ID_LEN
+            || self.data_len as usize > MAX_SPDM_OPAQUE_SIZE
+        {
+            return Err(SPDM_STATUS_BUFFER_FULL);
+        }
+        let mut cnt = 0usize;
+        cnt += self.header.spdm_encode(context, bytes)?;
+        cnt += self
+            .data_len
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        for d in self.data.iter().take(self.data_len as usize) {
+            cnt += d.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+        let unpadded_len = self.unpadded_len();
+        for _i in unpadded_len..((unpadded_len + 3) & !3) {
+            cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // AlignPadding
+        }
+        Ok(cnt)
+    }
+    fn spdm_read(context: &mut SpdmContext, r: &mut Reader) -> Option<OpaqueElement> {
+        let header = OpaqueElementHeader::spdm_read(context, r)?;
+        if header.vendor_len as usize > MAX_SPDM_VENDOR_DEFINED_VENDOR_ID_LEN {
+            return None;
+        }
+        let data_len = u16::read(r)?;
+        if data_len as usize > MAX_SPDM_OPAQUE_SIZE {
+            return None;
+        }
+        let mut element = OpaqueElement {
+            header,
+            data_len,
+            ..Default::default()
+        };
+        for d in element.data.iter_mut().take(data_len as usize) {
+            *d = u8::read(r)?;
+        }
+        let unpadded_len = element.unpadded_len();
+        for _i in unpadded_len..((unpadded_len + 3) & !3) {
+            u8::read(r)?; // AlignPadding
+        }
+
+        Some(element)
+    }
+}
+
+/// Opaque data in the general format, the secured message version
+/// negotiation of KEY_EXCHANGE and PSK_EXCHANGE is carried in: the header,
+/// with the DMTF SpecID and OpaqueVersion before SPDM 1.2, then the
+/// elements. Decoding fails on a wrong SpecID or OpaqueVersion, more than
+/// MAX_OPAQUE_LIST_ELEMENTS_COUNT elements, or bytes left after the last.
+#[derive(Clone, Debug, Default)]
+pub struct SecuredMessageGeneralOpaqueData {
+    pub header: SecuredMessageGeneralOpaqueDataHeader,
+    pub elements: [OpaqueElement; MAX_OPAQUE_LIST_ELEMENTS_COUNT],
+}
+
+impl SecuredMessageGeneralOpaqueData {
+    /// The first DMTF element of `sm_data_id`.
+    pub fn find_dmtf_element(&self, sm_data_id: u8) -> Option<&OpaqueElement> {
+        self.elements
+            .iter()
+            .take(self.header.total_elements as usize)
+            .find(|element| element.dmtf_sm_data_id() == Some(sm_data_id))
+    }
+}
+
+impl SpdmCodec for SecuredMessageGeneralOpaqueData {
+    fn spdm_encode(
+        &self,
+        context: &mut SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        if self.header.total_elements as usize > MAX_OPAQUE_LIST_ELEMENTS_COUNT {
+            return Err(SPDM_STATUS_BUFFER_FULL);
+        }
+        let mut cnt = 0usize;
+        cnt += self.header.spdm_encode(context, bytes)?;
+        for element in self
+            .elements
+            .iter()
+            .take(self.header.total_elements as usize)
+        {
+            cnt += element.spdm_encode(context, bytes)?;
+        }
+        Ok(cnt)
+    }
+    fn spdm_read(
+        context: &mut SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SecuredMessageGeneralOpaqueData> {
+        let header = SecuredMessageGeneralOpaqueDataHeader::spdm_read(context, r)?;
+        if header.total_elements as usize > MAX_OPAQUE_LIST_ELEMENTS_COUNT {
+            return None;
+        }
+        let mut general_opaque_data = SecuredMessageGeneralOpaqueData {
+            header,
+            ..Default::default()
+        };
+        for element in general_opaque_data
+            .elements
+            .iter_mut()
+            .take(general_opaque_data.header.total_elements as usize)
+        {
+            *element = OpaqueElement::spdm_read(context, r)?;
+        }
+        if r.any_left() {
+            return None;
+        }
+
+        Some(general_opaque_data)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SpdmOpaqueStruct {
     pub data_size: u16,
@@ -558,7 +720,7 @@ impl SpdmCodec for SpdmOpaqueStruct {
         }
         Ok(cnt)
     }
-    fn spdm_read(_context: &mut SpdmContext, r: &mut Reader) -> Option<SpdmOpaqueStruct> {
+    fn spdm_read(context: &mut SpdmContext, r: &mut Reader) -> Option<SpdmOpaqueStruct> {
         let data_size = u16::read(r)?;
         if data_size > MAX_SPDM_OPAQUE_SIZE as u16 {
             return None;
@@ -567,38 +729,71 @@ impl SpdmCodec for SpdmOpaqueStruct {
         for d in data.iter_mut().take(data_size as usize) {
             *d = u8::read(r)?;
         }
+        let opaque = SpdmOpaqueStruct { data_size, data };
+
+        // With OpaqueDataFmt1 negotiated the opaque data shall be in the
+        // general format. Data that is not is either refused, or kept as
+        // the raw bytes when the peer is known to be sloppy about it; the
+        // message is consumed all the same, so transcripts still carry
+        // exactly what was on the wire.
+        if data_size != 0
+            && context.negotiate_info.spdm_version_sel.get_u8()
+                >= SpdmVersion::SpdmVersion12.get_u8()
+            && context.negotiate_info.opaque_data_support == SpdmOpaqueSupport::OPAQUE_DATA_FMT1
+            && opaque.get_general_opaque_data(context).is_none()
+        {
+            if !context.config_info.tolerate_malformed_opaque {
+                return None;
+            }
+            warn!("opaque data not in the general format, kept as raw bytes\n");
+        }
 
-        Some(SpdmOpaqueStruct { data_size, data })
+        Some(opaque)
     }
 }
 
 impl SpdmOpaqueStruct {
+    /// The opaque data decoded as `SecuredMessageGeneralOpaqueData`.
+    pub fn get_general_opaque_data(
+        &self,
+        context: &mut SpdmContext,
+    ) -> Option<SecuredMessageGeneralOpaqueData> {
+        let mut r = Reader::init(&self.data[0..self.data_size as usize]);
+        SecuredMessageGeneralOpaqueData::spdm_read(context, &mut r)
+    }
+
+    /// The DMTF supported version list element, wherever it is among the
+    /// elements.
     pub fn rsp_get_dmtf_supported_secure_spdm_version_list(
         &self,
         context: &mut SpdmContext,
     ) -> Option<SecuredMessageVersionList> {
-        let mut r = Reader::init(&self.data[0..self.data_size as usize]);
-        let secured_message_dmtf_supported_version =
-            SecuredMessageDMTFSupportedVersion::spdm_read(context, &mut r)?;
+        let general_opaque_data = self.get_general_opaque_data(context)?;
+        let element = general_opaque_data.find_dmtf_element(SUPPORTED_VERSION_LIST_SM_DATA_ID)?;
+        let mut r = Reader::init(&element.data[2..element.data_len as usize]);
+        let secured_msg_vers = SecuredMessageVersionList::spdm_read(context, &mut r)?;
+        if secured_msg_vers.version_count as usize > MAX_SECURE_SPDM_VERSION_COUNT || r.any_left() {
+            return None;
+        }
 
-        Some(SecuredMessageVersionList {
-            ..secured_message_dmtf_supported_version.opaque_element_dmtf_supported_version_list[0]
-                .secured_msg_vers
-        })
+        Some(secured_msg_vers)
     }
 
+    /// The DMTF version selection element, wherever it is among the
+    /// elements.
     pub fn req_get_dmtf_secure_spdm_version_selection(
         &self,
         context: &mut SpdmContext,
     ) -> Option<SecuredMessageVersion> {
-        let mut r = Reader::init(&self.data[0..self.data_size as usize]);
-        let secured_message_dmtf_version_selection =
-            SecuredMessageDMTFVersionSelection::spdm_read(context, &mut r)?;
+        let general_opaque_data = self.get_general_opaque_data(context)?;
+        let element = general_opaque_data.find_dmtf_element(VERSION_SELECTION_SM_DATA_ID)?;
+        let mut r = Reader::init(&element.data[2..element.data_len as usize]);
+        let selected_version = SecuredMessageVersion::spdm_read(context, &mut r)?;
+        if r.any_left() {
+            return None;
+        }
 
-        Some(SecuredMessageVersion {
-            ..secured_message_dmtf_version_selection.opaque_element_dmtf_version_selection_list[0]
-                .selected_version
-        })
+        Some(selected_version)
     }
 }
 
@@ -637,3 +832,7 @@ impl SpdmOpaqueSupport {
         self.is_no_more_than_one_selected() && self.is_valid()
     }
 }
+
+#[cfg(all(test,))]
+#[path = "opaque_test.rs"]
+mod opaque_test;
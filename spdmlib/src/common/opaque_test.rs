@@ -0,0 +1,207 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use super::*;
+use crate::common::{SpdmConfigInfo, SpdmProvisionInfo};
+use crate::message::testlib::*;
+
+fn opaque_struct(data: &[u8]) -> SpdmOpaqueStruct {
+    let mut opaque = SpdmOpaqueStruct {
+        data_size: data.len() as u16,
+        ..Default::default()
+    };
+    opaque.data[..data.len()].copy_from_slice(data);
+    opaque
+}
+
+#[test]
+fn test_case0_general_opaque_data_dsp0277() {
+    create_spdm_context!(context);
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+
+    let data = &REQ_DMTF_OPAQUE_DATA_SUPPORT_VERSION_LIST_DSP0277;
+    let mut reader = Reader::init(data);
+    let general_opaque_data =
+        SecuredMessageGeneralOpaqueData::spdm_read(&mut context, &mut reader).unwrap();
+    assert_eq!(general_opaque_data.header.total_elements, 1);
+    let element = &general_opaque_data.elements[0];
+    assert!(element.is_dmtf());
+    assert_eq!(
+        element.dmtf_sm_data_id(),
+        Some(SUPPORTED_VERSION_LIST_SM_DATA_ID)
+    );
+    assert_eq!(element.data_len, 7);
+    assert!(general_opaque_data
+        .find_dmtf_element(VERSION_SELECTION_SM_DATA_ID)
+        .is_none());
+
+    let mut buffer = [0u8; MAX_SPDM_OPAQUE_SIZE];
+    let mut writer = Writer::init(&mut buffer);
+    let used = general_opaque_data
+        .spdm_encode(&mut context, &mut writer)
+        .unwrap();
+    assert_eq!(&buffer[..used], &data[..]);
+
+    let versions = opaque_struct(data)
+        .rsp_get_dmtf_supported_secure_spdm_version_list(&mut context)
+        .unwrap();
+    assert_eq!(versions.version_count, 2);
+    assert_eq!(
+        versions.versions_list[0].get_secure_spdm_version(),
+        DMTF_SECURE_SPDM_VERSION_10
+    );
+    assert_eq!(
+        versions.versions_list[1].get_secure_spdm_version(),
+        DMTF_SECURE_SPDM_VERSION_11
+    );
+
+    // the header magic and version are checked.
+    let mut bad_data = *data;
+    bad_data[0] = 0x47;
+    let mut reader = Reader::init(&bad_data);
+    assert!(SecuredMessageGeneralOpaqueData::spdm_read(&mut context, &mut reader).is_none());
+    let mut bad_data = *data;
+    bad_data[4] = DMTF_OPAQUE_VERSION + 1;
+    let mut reader = Reader::init(&bad_data);
+    assert!(SecuredMessageGeneralOpaqueData::spdm_read(&mut context, &mut reader).is_none());
+}
+
+#[test]
+fn test_case1_general_opaque_data_fmt1() {
+    create_spdm_context!(context);
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.negotiate_info.opaque_data_support = SpdmOpaqueSupport::OPAQUE_DATA_FMT1;
+
+    let data = &RSP_DMTF_OPAQUE_DATA_VERSION_SELECTION_DSP0274_FMT1;
+    let selected_version = opaque_struct(data)
+        .req_get_dmtf_secure_spdm_version_selection(&mut context)
+        .unwrap();
+    assert_eq!(
+        selected_version.get_secure_spdm_version(),
+        DMTF_SECURE_SPDM_VERSION_11
+    );
+
+    // the raw DSP0277 header is not the FMT1 one.
+    let data = &RSP_DMTF_OPAQUE_DATA_VERSION_SELECTION_DSP0277;
+    assert!(opaque_struct(data)
+        .req_get_dmtf_secure_spdm_version_selection(&mut context)
+        .is_none());
+
+    // nor are opaque data of no general format understood.
+    context.negotiate_info.opaque_data_support = SpdmOpaqueSupport::empty();
+    let data = &RSP_DMTF_OPAQUE_DATA_VERSION_SELECTION_DSP0274_FMT1;
+    assert!(opaque_struct(data)
+        .get_general_opaque_data(&mut context)
+        .is_none());
+}
+
+#[test]
+fn test_case2_general_opaque_data_vendor_element() {
+    create_spdm_context!(context);
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.negotiate_info.opaque_data_support = SpdmOpaqueSupport::OPAQUE_DATA_FMT1;
+
+    let mut vendor_element = OpaqueElement {
+        header: OpaqueElementHeader {
+            id: 0x01,
+            vendor_len: 2,
+            ..Default::default()
+        },
+        data_len: 3,
+        ..Default::default()
+    };
+    vendor_element.header.vendor_id[..2].copy_from_slice(&[0x86, 0x80]);
+    vendor_element.data[..3].copy_from_slice(&[1, 2, 3]);
+
+    let mut dmtf_element = OpaqueElement {
+        data_len: 4,
+        ..Default::default()
+    };
+    dmtf_element.data[..4].copy_from_slice(&[
+        SM_DATA_VERSION,
+        VERSION_SELECTION_SM_DATA_ID,
+        0x00,
+        DMTF_SECURE_SPDM_VERSION_10,
+    ]);
+
+    let mut general_opaque_data = SecuredMessageGeneralOpaqueData {
+        header: SecuredMessageGeneralOpaqueDataHeader { total_elements: 2 },
+        ..Default::default()
+    };
+    general_opaque_data.elements[0] = vendor_element;
+    general_opaque_data.elements[1] = dmtf_element;
+
+    let mut buffer = [0u8; MAX_SPDM_OPAQUE_SIZE];
+    let mut writer = Writer::init(&mut buffer);
+    let used = general_opaque_data
+        .spdm_encode(&mut context, &mut writer)
+        .unwrap();
+    // the vendor element is padded to 4 bytes, the DMTF one needs none.
+    assert_eq!(used, 4 + 12 + 8);
+    assert_eq!(&buffer[4..16], &[1, 2, 0x86, 0x80, 3, 0, 1, 2, 3, 0, 0, 0]);
+
+    // the DMTF element is found behind the vendor one.
+    let selected_version = opaque_struct(&buffer[..used])
+        .req_get_dmtf_secure_spdm_version_selection(&mut context)
+        .unwrap();
+    assert_eq!(
+        selected_version.get_secure_spdm_version(),
+        DMTF_SECURE_SPDM_VERSION_10
+    );
+    assert!(opaque_struct(&buffer[..used])
+        .rsp_get_dmtf_supported_secure_spdm_version_list(&mut context)
+        .is_none());
+
+    // trailing bytes or a truncated element are refused.
+    let mut reader = Reader::init(&buffer[..used + 1]);
+    assert!(SecuredMessageGeneralOpaqueData::spdm_read(&mut context, &mut reader).is_none());
+    let mut reader = Reader::init(&buffer[..used - 1]);
+    assert!(SecuredMessageGeneralOpaqueData::spdm_read(&mut context, &mut reader).is_none());
+}
+
+#[test]
+fn test_case3_opaque_struct_decode_policy() {
+    create_spdm_context!(context);
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.negotiate_info.opaque_data_support = SpdmOpaqueSupport::OPAQUE_DATA_FMT1;
+
+    let mut buffer = [0u8; MAX_SPDM_OPAQUE_SIZE];
+    let mut writer = Writer::init(&mut buffer);
+    let malformed = opaque_struct(&[0xFF, 0, 0, 0, 1, 2]);
+    let used = malformed.spdm_encode(&mut context, &mut writer).unwrap();
+
+    // fatal by default.
+    assert!(!context.config_info.tolerate_malformed_opaque);
+    let mut reader = Reader::init(&buffer[..used]);
+    assert!(SpdmOpaqueStruct::spdm_read(&mut context, &mut reader).is_none());
+
+    // tolerated, the raw bytes are kept and consumed.
+    context.config_info.tolerate_malformed_opaque = true;
+    let mut reader = Reader::init(&buffer[..used]);
+    let opaque = SpdmOpaqueStruct::spdm_read(&mut context, &mut reader).unwrap();
+    assert_eq!(reader.used(), used);
+    assert_eq!(&opaque.data[..opaque.data_size as usize], &buffer[2..used]);
+    assert!(opaque.get_general_opaque_data(&mut context).is_none());
+
+    // well formed or empty opaque data, or opaque data before SPDM 1.2,
+    // are read either way.
+    context.config_info.tolerate_malformed_opaque = false;
+    for data in [
+        &RSP_DMTF_OPAQUE_DATA_VERSION_SELECTION_DSP0274_FMT1[..],
+        &[][..],
+    ] {
+        let mut writer = Writer::init(&mut buffer);
+        let used = opaque_struct(data)
+            .spdm_encode(&mut context, &mut writer)
+            .unwrap();
+        let mut reader = Reader::init(&buffer[..used]);
+        let opaque = SpdmOpaqueStruct::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(&opaque.data[..opaque.data_size as usize], data);
+    }
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    let mut writer = Writer::init(&mut buffer);
+    let used = malformed.spdm_encode(&mut context, &mut writer).unwrap();
+    let mut reader = Reader::init(&buffer[..used]);
+    assert!(SpdmOpaqueStruct::spdm_read(&mut context, &mut reader).is_some());
+}
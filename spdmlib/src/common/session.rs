@@ -11,7 +11,9 @@ use crate::error::SPDM_STATUS_CRYPTO_ERROR;
 use crate::error::SPDM_STATUS_DECODE_AEAD_FAIL;
 use crate::error::SPDM_STATUS_INVALID_STATE_LOCAL;
 use crate::error::SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW;
-use crate::message::SpdmKeyExchangeMutAuthAttributes;
+#[cfg(feature = "mut-auth")]
+use crate::message::SpdmKeyUpdateOperation;
+use crate::message::{SpdmEventListStruct, SpdmKeyExchangeMutAuthAttributes};
 
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
@@ -33,6 +35,11 @@ enum_builder! {
         SpdmSessionEstablished => 0x2
     }
 }
+// Once the sequence number of a direction reaches this value, only KEY_UPDATE
+// and END_SESSION traffic may be sent, so the session can still be rekeyed or
+// closed before the AEAD nonce space wraps.
+pub const SPDM_SEQUENCE_NUMBER_REKEY_THRESHOLD: u64 = 0xFFFF_FFFF_FFFF_0000;
+
 impl Default for SpdmSessionState {
     fn default() -> SpdmSessionState {
         SpdmSessionState::SpdmSessionNotStarted
@@ -96,6 +103,7 @@ pub struct SpdmSessionRuntimeInfo {
     pub message_k: ManagedBufferK,
     pub message_f: ManagedBufferF,
     pub message_m: ManagedBufferM,
+    pub measurement_nonces: SpdmNonceHistory, // used by requester only
 }
 
 #[derive(Clone, Default)]
@@ -108,6 +116,7 @@ pub struct SpdmSessionRuntimeInfo {
     pub req_cert_hash: Option<SpdmDigestStruct>,
     pub digest_context_th: Option<SpdmHashCtx>,
     pub digest_context_l1l2: Option<SpdmHashCtx>,
+    pub measurement_nonces: SpdmNonceHistory, // used by requester only
 }
 
 #[derive(Clone)]
@@ -125,8 +134,15 @@ pub struct SpdmSession {
     pub runtime_info: SpdmSessionRuntimeInfo,
     key_schedule: SpdmKeySchedule,
     slot_id: u8,
+    handshake_transcript_hash: Option<SpdmDigestStruct>,
     pub heartbeat_period: u8, // valid only when HEARTBEAT cap set
     pub secure_spdm_version_sel: u8,
+    pub event_subscription: Option<SpdmEventListStruct>, // spdm 1.3, used by responder only
+    // next KEY_UPDATE operation to send as encapsulated request, None if no
+    // key update is in progress. Used by responder only.
+    #[cfg(feature = "mut-auth")]
+    pub encap_key_update_op: Option<SpdmKeyUpdateOperation>,
+    last_activity: u64,
 }
 
 impl Default for SpdmSession {
@@ -150,9 +166,14 @@ impl SpdmSession {
             runtime_info: SpdmSessionRuntimeInfo::default(),
             key_schedule: SpdmKeySchedule::new(),
             slot_id: 0,
+            handshake_transcript_hash: None,
             heartbeat_period: 0,
             secure_spdm_version_sel: DMTF_SECURE_SPDM_VERSION_11,
             mut_auth_requested: SpdmKeyExchangeMutAuthAttributes::default(),
+            event_subscription: None,
+            #[cfg(feature = "mut-auth")]
+            encap_key_update_op: None,
+            last_activity: 0,
         }
     }
 
@@ -172,6 +193,26 @@ impl SpdmSession {
         self.application_secret.response_direction.sequence_number
     }
 
+    /// Sequence number that protects the next record in the given direction,
+    /// taken from the handshake or application secret depending on the
+    /// session state.
+    pub fn get_sequence_number(&self, is_requester: bool) -> u64 {
+        match (self.session_state, is_requester) {
+            (SpdmSessionState::SpdmSessionHandshaking, true) => {
+                self.handshake_secret.request_direction.sequence_number
+            }
+            (SpdmSessionState::SpdmSessionHandshaking, false) => {
+                self.handshake_secret.response_direction.sequence_number
+            }
+            (_, true) => self.application_secret.request_direction.sequence_number,
+            (_, false) => self.application_secret.response_direction.sequence_number,
+        }
+    }
+
+    pub fn is_sequence_number_exhausted(&self, is_requester: bool) -> bool {
+        self.get_sequence_number(is_requester) >= SPDM_SEQUENCE_NUMBER_REKEY_THRESHOLD
+    }
+
     pub fn set_default(&mut self) {
         self.session_id = INVALID_SESSION_ID;
         self.use_psk = false;
@@ -184,15 +225,50 @@ impl SpdmSession {
         self.transport_param = SpdmSessionTransportParam::default();
         self.runtime_info = SpdmSessionRuntimeInfo::default();
         self.key_schedule = SpdmKeySchedule::default();
+        self.handshake_transcript_hash = None;
         self.heartbeat_period = 0;
         self.secure_spdm_version_sel = DMTF_SECURE_SPDM_VERSION_11;
         self.mut_auth_requested = SpdmKeyExchangeMutAuthAttributes::empty();
+        self.event_subscription = None;
+        #[cfg(feature = "mut-auth")]
+        {
+            self.encap_key_update_op = None;
+        }
+        self.last_activity = 0;
+    }
+
+    /// Heartbeat period in seconds, None when heartbeat is disabled.
+    pub fn get_heartbeat_period(&self) -> Option<u8> {
+        match self.heartbeat_period {
+            0 => None,
+            heartbeat_period => Some(heartbeat_period),
+        }
     }
 
     pub fn get_session_id(&self) -> u32 {
         self.session_id
     }
 
+    /// Stamp of the last message of the session, a larger one is more
+    /// recent. Set by `SpdmContext::touch_session`.
+    pub fn get_last_activity(&self) -> u64 {
+        self.last_activity
+    }
+
+    pub fn set_last_activity(&mut self, last_activity: u64) {
+        self.last_activity = last_activity;
+    }
+
+    /// TH2, the transcript hash over the whole handshake that the data
+    /// secrets were derived from, to bind an upper layer protocol to this
+    /// session. None until the session is established.
+    pub fn get_handshake_transcript_hash(&self) -> Option<&SpdmDigestStruct> {
+        if self.session_state != SpdmSessionState::SpdmSessionEstablished {
+            return None;
+        }
+        self.handshake_transcript_hash.as_ref()
+    }
+
     pub fn setup(&mut self, session_id: u32) -> SpdmResult {
         if self.session_id == INVALID_SESSION_ID {
             self.set_default();
@@ -361,6 +437,19 @@ impl SpdmSession {
             "!!! response_handshake_secret !!!: {:02x?}\n",
             self.handshake_secret.response_handshake_secret.as_ref()
         );
+        #[cfg(feature = "key-log")]
+        {
+            key_log::log_secret(
+                self.session_id,
+                key_log::SPDM_KEY_LOG_REQUEST_HANDSHAKE_SECRET,
+                self.handshake_secret.request_handshake_secret.as_ref(),
+            );
+            key_log::log_secret(
+                self.session_id,
+                key_log::SPDM_KEY_LOG_RESPONSE_HANDSHAKE_SECRET,
+                self.handshake_secret.response_handshake_secret.as_ref(),
+            );
+        }
         self.handshake_secret.request_finished_key = if let Some(rfk) =
             self.key_schedule.derive_finished_key(
                 spdm_version,
@@ -498,6 +587,19 @@ impl SpdmSession {
             "!!! response_data_secret !!!: {:02x?}\n",
             self.application_secret.response_data_secret.as_ref()
         );
+        #[cfg(feature = "key-log")]
+        {
+            key_log::log_secret(
+                self.session_id,
+                key_log::SPDM_KEY_LOG_REQUEST_DATA_SECRET,
+                self.application_secret.request_data_secret.as_ref(),
+            );
+            key_log::log_secret(
+                self.session_id,
+                key_log::SPDM_KEY_LOG_RESPONSE_DATA_SECRET,
+                self.application_secret.response_data_secret.as_ref(),
+            );
+        }
 
         let res = if let Some(aki) = self.key_schedule.derive_aead_key_iv(
             spdm_version,
@@ -565,6 +667,13 @@ impl SpdmSession {
         } else {
             return Err(SPDM_STATUS_CRYPTO_ERROR);
         };
+        #[cfg(feature = "key-log")]
+        key_log::log_secret(
+            self.session_id,
+            key_log::SPDM_KEY_LOG_EXPORT_MASTER_SECRET,
+            self.application_secret.export_master_secret.as_ref(),
+        );
+        self.handshake_transcript_hash = Some(th2.clone());
 
         Ok(())
     }
@@ -602,6 +711,12 @@ impl SpdmSession {
                 "!!! request_data_secret !!!: {:02x?}\n",
                 self.application_secret.request_data_secret.as_ref()
             );
+            #[cfg(feature = "key-log")]
+            key_log::log_secret(
+                self.session_id,
+                key_log::SPDM_KEY_LOG_REQUEST_DATA_SECRET,
+                self.application_secret.request_data_secret.as_ref(),
+            );
 
             let res = if let Some(aki) = self.key_schedule.derive_aead_key_iv(
                 spdm_version,
@@ -651,6 +766,12 @@ impl SpdmSession {
                 "!!! response_data_secret !!!: {:02x?}\n",
                 self.application_secret.response_data_secret.as_ref()
             );
+            #[cfg(feature = "key-log")]
+            key_log::log_secret(
+                self.session_id,
+                key_log::SPDM_KEY_LOG_RESPONSE_DATA_SECRET,
+                self.application_secret.response_data_secret.as_ref(),
+            );
 
             let res = if let Some(aki) = self.key_schedule.derive_aead_key_iv(
                 spdm_version,
@@ -0,0 +1,280 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use super::{SpdmConnectionState, SpdmContext, SpdmNegotiateInfo, SpdmOpaqueSupport};
+use crate::error::{SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_INVALID_PARAMETER};
+use crate::message::{spdm_version_list_has_duplicates, SpdmVersionStruct};
+use crate::protocol::*;
+use codec::Codec;
+
+/// Layout version of `SpdmConnectionStateSnapshot`.
+/// Bump it whenever a field is added, removed or reinterpreted.
+pub const SPDM_CONNECTION_STATE_SNAPSHOT_VERSION: u8 = 3;
+
+/// Serializable snapshot of a negotiated connection.
+///
+/// It carries the VCA transcript, the negotiated parameters and the peer
+/// certificate chains, so a requester can resume talking to a responder
+/// that kept its own state across a restart without running
+/// GET_VERSION/GET_CAPABILITIES/NEGOTIATE_ALGORITHMS again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpdmConnectionStateSnapshot {
+    pub snapshot_version: u8,
+    pub spdm_version_sel: u8,
+    pub rsp_spdm_versions: Vec<(u8, u8)>, // (version, update) as advertised in VERSION
+    pub req_capabilities_sel: u32,
+    pub rsp_capabilities_sel: u32,
+    pub req_ct_exponent_sel: u8,
+    pub rsp_ct_exponent_sel: u8,
+    pub measurement_specification_sel: u8,
+    pub measurement_hash_sel: u32,
+    pub base_hash_sel: u32,
+    pub base_asym_sel: u32,
+    pub dhe_sel: u16,
+    pub aead_sel: u16,
+    pub req_asym_sel: u16,
+    pub key_schedule_sel: u16,
+    pub ext_asym_sel: Option<(u8, u16)>, // (registry id, algorithm id)
+    pub ext_hash_sel: Option<(u8, u16)>, // (registry id, algorithm id)
+    pub opaque_data_support: u8,
+    pub termination_policy_set: bool,
+    pub req_data_transfer_size_sel: u32,
+    pub req_max_spdm_msg_size_sel: u32,
+    pub rsp_data_transfer_size_sel: u32,
+    pub rsp_max_spdm_msg_size_sel: u32,
+    pub message_a: Vec<u8>,
+    pub peer_cert_chain: Vec<Option<Vec<u8>>>,
+}
+
+impl SpdmConnectionStateSnapshot {
+    /// Rebuild the negotiate info, rejecting any field that could not have
+    /// come out of a successful NEGOTIATE_ALGORITHMS.
+    fn to_negotiate_info(&self) -> Option<SpdmNegotiateInfo> {
+        if self.snapshot_version != SPDM_CONNECTION_STATE_SNAPSHOT_VERSION {
+            return None;
+        }
+
+        let spdm_version_sel = SpdmVersion::read_bytes(&[self.spdm_version_sel])?;
+        if let SpdmVersion::Unknown(_) = spdm_version_sel {
+            return None;
+        }
+
+        if self.rsp_spdm_versions.len() > MAX_SPDM_VERSION_COUNT {
+            return None;
+        }
+        let mut rsp_spdm_versions: [SpdmVersionStruct; MAX_SPDM_VERSION_COUNT] = Default::default();
+        for (entry, (version, update)) in rsp_spdm_versions
+            .iter_mut()
+            .zip(self.rsp_spdm_versions.iter())
+        {
+            entry.version = SpdmVersion::read_bytes(&[*version])?;
+            entry.update = *update;
+        }
+        if spdm_version_list_has_duplicates(&rsp_spdm_versions[..self.rsp_spdm_versions.len()]) {
+            return None;
+        }
+
+        let negotiate_info = SpdmNegotiateInfo {
+            spdm_version_sel,
+            rsp_spdm_version_count: self.rsp_spdm_versions.len() as u8,
+            rsp_spdm_versions,
+            req_capabilities_sel: SpdmRequestCapabilityFlags::from_bits(self.req_capabilities_sel)?,
+            rsp_capabilities_sel: SpdmResponseCapabilityFlags::from_bits(
+                self.rsp_capabilities_sel,
+            )?,
+            req_ct_exponent_sel: self.req_ct_exponent_sel,
+            rsp_ct_exponent_sel: self.rsp_ct_exponent_sel,
+            measurement_specification_sel: SpdmMeasurementSpecification::from_bits(
+                self.measurement_specification_sel,
+            )?,
+            measurement_hash_sel: SpdmMeasurementHashAlgo::from_bits(self.measurement_hash_sel)?,
+            base_hash_sel: SpdmBaseHashAlgo::from_bits(self.base_hash_sel)?,
+            base_asym_sel: SpdmBaseAsymAlgo::from_bits(self.base_asym_sel)?,
+            dhe_sel: SpdmDheAlgo::from_bits(self.dhe_sel)?,
+            aead_sel: SpdmAeadAlgo::from_bits(self.aead_sel)?,
+            req_asym_sel: SpdmReqAsymAlgo::from_bits(self.req_asym_sel)?,
+            key_schedule_sel: SpdmKeyScheduleAlgo::from_bits(self.key_schedule_sel)?,
+            ext_asym_sel: self
+                .ext_asym_sel
+                .map(|(registry_id, algorithm_id)| SpdmExtAlgStruct {
+                    registry_id,
+                    algorithm_id,
+                }),
+            ext_hash_sel: self
+                .ext_hash_sel
+                .map(|(registry_id, algorithm_id)| SpdmExtAlgStruct {
+                    registry_id,
+                    algorithm_id,
+                }),
+            opaque_data_support: SpdmOpaqueSupport::from_bits(self.opaque_data_support)?,
+            termination_policy_set: self.termination_policy_set,
+            req_data_transfer_size_sel: self.req_data_transfer_size_sel,
+            req_max_spdm_msg_size_sel: self.req_max_spdm_msg_size_sel,
+            rsp_data_transfer_size_sel: self.rsp_data_transfer_size_sel,
+            rsp_max_spdm_msg_size_sel: self.rsp_max_spdm_msg_size_sel,
+        };
+
+        // hash and asym are always negotiated, either as a base or as an
+        // extended algorithm, everything else is optional but must be a
+        // single selection when present.
+        let hash_sel_valid = match negotiate_info.ext_hash_sel {
+            Some(_) => negotiate_info.base_hash_sel.is_empty(),
+            None => negotiate_info.base_hash_sel.is_valid_one_select(),
+        };
+        let asym_sel_valid = match negotiate_info.ext_asym_sel {
+            Some(_) => negotiate_info.base_asym_sel.is_empty(),
+            None => negotiate_info.base_asym_sel.is_valid_one_select(),
+        };
+        if !hash_sel_valid
+            || !asym_sel_valid
+            || !negotiate_info
+                .measurement_specification_sel
+                .is_no_more_than_one_selected()
+            || !negotiate_info
+                .measurement_hash_sel
+                .is_no_more_than_one_selected()
+            || !negotiate_info.dhe_sel.is_no_more_than_one_selected()
+            || !negotiate_info.aead_sel.is_no_more_than_one_selected()
+            || !negotiate_info.req_asym_sel.is_no_more_than_one_selected()
+            || !negotiate_info
+                .key_schedule_sel
+                .is_no_more_than_one_selected()
+            || !negotiate_info
+                .opaque_data_support
+                .is_no_more_than_one_selected()
+        {
+            return None;
+        }
+
+        // a session needs DHE (unless PSK only), AEAD and key schedule together.
+        let has_aead = !negotiate_info.aead_sel.is_empty();
+        if has_aead == negotiate_info.key_schedule_sel.is_empty()
+            || (!negotiate_info.dhe_sel.is_empty() && !has_aead)
+        {
+            return None;
+        }
+
+        if spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion12.get_u8()
+            && (negotiate_info.req_data_transfer_size_sel
+                > negotiate_info.req_max_spdm_msg_size_sel
+                || negotiate_info.rsp_data_transfer_size_sel
+                    > negotiate_info.rsp_max_spdm_msg_size_sel)
+        {
+            return None;
+        }
+
+        Some(negotiate_info)
+    }
+}
+
+impl<'a> SpdmContext<'a> {
+    /// Snapshot the negotiated connection for a later `import_connection_state`.
+    pub fn export_connection_state(&self) -> SpdmConnectionStateSnapshot {
+        let negotiate_info = &self.negotiate_info;
+        SpdmConnectionStateSnapshot {
+            snapshot_version: SPDM_CONNECTION_STATE_SNAPSHOT_VERSION,
+            spdm_version_sel: negotiate_info.spdm_version_sel.get_u8(),
+            rsp_spdm_versions: negotiate_info
+                .rsp_spdm_versions()
+                .iter()
+                .map(|entry| (entry.version.get_u8(), entry.update))
+                .collect(),
+            req_capabilities_sel: negotiate_info.req_capabilities_sel.bits(),
+            rsp_capabilities_sel: negotiate_info.rsp_capabilities_sel.bits(),
+            req_ct_exponent_sel: negotiate_info.req_ct_exponent_sel,
+            rsp_ct_exponent_sel: negotiate_info.rsp_ct_exponent_sel,
+            measurement_specification_sel: negotiate_info.measurement_specification_sel.bits(),
+            measurement_hash_sel: negotiate_info.measurement_hash_sel.bits(),
+            base_hash_sel: negotiate_info.base_hash_sel.bits(),
+            base_asym_sel: negotiate_info.base_asym_sel.bits(),
+            dhe_sel: negotiate_info.dhe_sel.bits(),
+            aead_sel: negotiate_info.aead_sel.bits(),
+            req_asym_sel: negotiate_info.req_asym_sel.bits(),
+            key_schedule_sel: negotiate_info.key_schedule_sel.bits(),
+            ext_asym_sel: negotiate_info
+                .ext_asym_sel
+                .map(|algo| (algo.registry_id, algo.algorithm_id)),
+            ext_hash_sel: negotiate_info
+                .ext_hash_sel
+                .map(|algo| (algo.registry_id, algo.algorithm_id)),
+            opaque_data_support: negotiate_info.opaque_data_support.bits(),
+            termination_policy_set: negotiate_info.termination_policy_set,
+            req_data_transfer_size_sel: negotiate_info.req_data_transfer_size_sel,
+            req_max_spdm_msg_size_sel: negotiate_info.req_max_spdm_msg_size_sel,
+            rsp_data_transfer_size_sel: negotiate_info.rsp_data_transfer_size_sel,
+            rsp_max_spdm_msg_size_sel: negotiate_info.rsp_max_spdm_msg_size_sel,
+            message_a: self.runtime_info.message_a.as_ref().to_vec(),
+            peer_cert_chain: self
+                .peer_info
+                .peer_cert_chain
+                .iter()
+                .map(|cert_chain| {
+                    cert_chain
+                        .as_ref()
+                        .map(|cert_chain| cert_chain.as_ref().to_vec())
+                })
+                .collect(),
+        }
+    }
+
+    /// Restore a snapshot taken by `export_connection_state`.
+    ///
+    /// The whole snapshot is validated before the context is touched, so a
+    /// rejected snapshot leaves the context unchanged.
+    pub fn import_connection_state(
+        &mut self,
+        snapshot: &SpdmConnectionStateSnapshot,
+    ) -> SpdmResult {
+        let negotiate_info = snapshot
+            .to_negotiate_info()
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+
+        if snapshot.peer_cert_chain.len() != SPDM_MAX_SLOT_NUMBER {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        let mut peer_cert_chain: [Option<SpdmCertChainBuffer>; SPDM_MAX_SLOT_NUMBER] =
+            Default::default();
+        for (slot, cert_chain) in snapshot.peer_cert_chain.iter().enumerate() {
+            if let Some(cert_chain) = cert_chain {
+                if SpdmCertChainHeader::parse(cert_chain, negotiate_info.base_hash_sel).is_none() {
+                    return Err(SPDM_STATUS_INVALID_PARAMETER);
+                }
+                let mut cert_chain_buffer = SpdmCertChainBuffer::default();
+                if cert_chain.len() > cert_chain_buffer.data.len() {
+                    return Err(SPDM_STATUS_BUFFER_FULL);
+                }
+                cert_chain_buffer.data[..cert_chain.len()].copy_from_slice(cert_chain);
+                cert_chain_buffer.data_size = cert_chain.len() as u16;
+                peer_cert_chain[slot] = Some(cert_chain_buffer);
+            }
+        }
+
+        let mut message_a = super::ManagedBufferA::default();
+        message_a
+            .append_message(&snapshot.message_a)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+
+        self.reset_context();
+        self.negotiate_info = negotiate_info;
+        self.runtime_info.message_a = message_a;
+        self.peer_info.peer_cert_chain = peer_cert_chain;
+        self.runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test,))]
+#[path = "../message/mod_test.common.inc.rs"]
+mod testlib;
+
+#[cfg(all(test,))]
+#[path = "snapshot_test.rs"]
+mod snapshot_test;
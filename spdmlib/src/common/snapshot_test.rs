@@ -0,0 +1,117 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use super::testlib::*;
+use super::*;
+use crate::common::{SpdmConfigInfo, SpdmProvisionInfo};
+
+fn negotiate(context: &mut SpdmContext) {
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.negotiate_info.rsp_spdm_version_count = 2;
+    context.negotiate_info.rsp_spdm_versions[0].version = SpdmVersion::SpdmVersion11;
+    context.negotiate_info.rsp_spdm_versions[1].version = SpdmVersion::SpdmVersion12;
+    context.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::CERT_CAP;
+    context.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::CHAL_CAP;
+    context.negotiate_info.measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    context.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    context.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    context.negotiate_info.dhe_sel = SpdmDheAlgo::SECP_384_R1;
+    context.negotiate_info.aead_sel = SpdmAeadAlgo::AES_256_GCM;
+    context.negotiate_info.key_schedule_sel = SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE;
+    context.negotiate_info.opaque_data_support = SpdmOpaqueSupport::OPAQUE_DATA_FMT1;
+    context.negotiate_info.req_data_transfer_size_sel = 0x1200;
+    context.negotiate_info.req_max_spdm_msg_size_sel = 0x1200;
+    context.negotiate_info.rsp_data_transfer_size_sel = 0x1200;
+    context.negotiate_info.rsp_max_spdm_msg_size_sel = 0x1200;
+    context
+        .runtime_info
+        .message_a
+        .append_message(&[0x10, 0x84, 0, 0, 0x12, 0x04, 0, 0])
+        .unwrap();
+
+    let mut cert_chain = SpdmCertChainBuffer::default();
+    let data_size = 4 + SHA384_DIGEST_SIZE + 16;
+    cert_chain.data[..2].copy_from_slice(&(data_size as u16).to_le_bytes());
+    cert_chain.data[4..data_size].copy_from_slice(&[0x5a; SHA384_DIGEST_SIZE + 16]);
+    cert_chain.data_size = data_size as u16;
+    context.peer_info.peer_cert_chain[0] = Some(cert_chain);
+}
+
+#[test]
+fn test_connection_state_round_trip() {
+    create_spdm_context!(context);
+    negotiate(&mut context);
+    let snapshot = context.export_connection_state();
+
+    let blob = serde_json::to_vec(&snapshot).unwrap();
+    let restored: SpdmConnectionStateSnapshot = serde_json::from_slice(&blob).unwrap();
+    assert_eq!(restored, snapshot);
+
+    create_spdm_context!(context_new);
+    assert!(context_new.import_connection_state(&restored).is_ok());
+    assert_eq!(context_new.export_connection_state(), snapshot);
+    assert_eq!(
+        context_new.runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionNegotiated
+    );
+    assert_eq!(
+        context_new.runtime_info.message_a.as_ref(),
+        context.runtime_info.message_a.as_ref()
+    );
+    assert_eq!(
+        context_new.peer_info.peer_cert_chain[0]
+            .as_ref()
+            .unwrap()
+            .as_ref(),
+        context.peer_info.peer_cert_chain[0]
+            .as_ref()
+            .unwrap()
+            .as_ref()
+    );
+    assert!(context_new.peer_info.peer_cert_chain[1].is_none());
+}
+
+#[test]
+fn test_connection_state_import_rejects_inconsistent_snapshot() {
+    create_spdm_context!(context);
+    negotiate(&mut context);
+    let snapshot = context.export_connection_state();
+
+    let mut bad_snapshots = [
+        snapshot.clone(),
+        snapshot.clone(),
+        snapshot.clone(),
+        snapshot.clone(),
+        snapshot.clone(),
+        snapshot.clone(),
+        snapshot.clone(),
+    ];
+    bad_snapshots[0].snapshot_version += 1;
+    bad_snapshots[1].spdm_version_sel = 0x20;
+    bad_snapshots[2].base_hash_sel |= SpdmBaseHashAlgo::TPM_ALG_SHA_256.bits();
+    bad_snapshots[3].key_schedule_sel = 0;
+    bad_snapshots[4].rsp_data_transfer_size_sel = 0x1201;
+    // the chain is too short to carry a SHA-512 root hash.
+    bad_snapshots[5].base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_512.bits();
+    bad_snapshots[5].peer_cert_chain[0]
+        .as_mut()
+        .unwrap()
+        .truncate(4 + SHA384_DIGEST_SIZE);
+    bad_snapshots[5].peer_cert_chain[0].as_mut().unwrap()[..2]
+        .copy_from_slice(&((4 + SHA384_DIGEST_SIZE) as u16).to_le_bytes());
+    bad_snapshots[6].rsp_spdm_versions[1] = bad_snapshots[6].rsp_spdm_versions[0];
+
+    for bad_snapshot in bad_snapshots.iter() {
+        create_spdm_context!(context_new);
+        assert!(context_new.import_connection_state(bad_snapshot).is_err());
+        // a rejected snapshot leaves the context untouched.
+        assert_eq!(
+            context_new.runtime_info.get_connection_state(),
+            SpdmConnectionState::SpdmConnectionNotStarted
+        );
+        assert!(context_new.runtime_info.message_a.as_ref().is_empty());
+    }
+}
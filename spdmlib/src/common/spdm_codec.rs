@@ -83,6 +83,7 @@ impl SpdmMeasurementRecordStructure {
         let mut reader = Reader::init(&self.measurement_record_data[..measurement_record_length]);
 
         let mut cur_index = 0u8;
+        let mut blocks_length = 0usize;
         for _ in 0..self.number_of_blocks as usize {
             let measurement_block = SpdmMeasurementBlockStructure::spdm_read(context, &mut reader);
             if measurement_block.is_none() {
@@ -93,8 +94,16 @@ impl SpdmMeasurementRecordStructure {
                 return false;
             }
             cur_index = measurement_block.index;
+            // Index (1) + MeasurementSpecification (1) + MeasurementSize (2)
+            blocks_length += 4 + measurement_block.measurement_size as usize;
         }
-        if reader.any_left() {
+        // a declared length short of the blocks fails the last block above,
+        // one past them leaves bytes behind.
+        if blocks_length != measurement_record_length || reader.any_left() {
+            error!(
+                "!!! measurement record length {} but blocks take {} !!!\n",
+                measurement_record_length, blocks_length
+            );
             return false;
         }
         true
@@ -132,6 +141,9 @@ impl SpdmCodec for SpdmMeasurementRecordStructure {
         r: &mut Reader,
     ) -> Option<SpdmMeasurementRecordStructure> {
         let number_of_blocks = u8::read(r)?;
+        if number_of_blocks as usize > config::MAX_SPDM_MEASUREMENT_BLOCKS {
+            return None;
+        }
         let measurement_record_length = u24::read(r)?;
         if measurement_record_length.get() as usize > config::MAX_SPDM_MEASUREMENT_RECORD_SIZE {
             return None;
@@ -0,0 +1,62 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+use super::{ManagedBufferA, ManagedBufferB, ManagedBufferC, ManagedBufferM, SpdmContext};
+use crate::error::{SpdmResult, SPDM_STATUS_BUFFER_FULL};
+
+/// The connection transcript buffers of a context, as raw bytes.
+///
+/// Export them after a known-good run and load them into a fresh context
+/// to replay the run as a regression fixture. Session transcripts are not
+/// part of it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpdmTranscriptFixture {
+    pub message_a: Vec<u8>,
+    pub message_b: Vec<u8>,
+    pub message_c: Vec<u8>,
+    pub message_m: Vec<u8>,
+}
+
+impl<'a> SpdmContext<'a> {
+    /// Copy of the transcript appended so far.
+    pub fn export_transcript(&self) -> SpdmTranscriptFixture {
+        SpdmTranscriptFixture {
+            message_a: self.runtime_info.message_a.as_ref().to_vec(),
+            message_b: self.runtime_info.message_b.as_ref().to_vec(),
+            message_c: self.runtime_info.message_c.as_ref().to_vec(),
+            message_m: self.runtime_info.message_m.as_ref().to_vec(),
+        }
+    }
+
+    /// Replace the transcript with one taken by `export_transcript`.
+    ///
+    /// A fixture too large for the buffers leaves the context unchanged.
+    pub fn import_transcript(&mut self, fixture: &SpdmTranscriptFixture) -> SpdmResult {
+        let mut message_a = ManagedBufferA::default();
+        message_a
+            .append_message(&fixture.message_a)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+        let mut message_b = ManagedBufferB::default();
+        message_b
+            .append_message(&fixture.message_b)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+        let mut message_c = ManagedBufferC::default();
+        message_c
+            .append_message(&fixture.message_c)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+        let mut message_m = ManagedBufferM::default();
+        message_m
+            .append_message(&fixture.message_m)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+
+        self.runtime_info.message_a = message_a;
+        self.runtime_info.message_b = message_b;
+        self.runtime_info.message_c = message_c;
+        self.runtime_info.message_m = message_m;
+        Ok(())
+    }
+}
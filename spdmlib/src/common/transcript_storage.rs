@@ -0,0 +1,99 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::ManagedBufferL1L2;
+use crate::crypto;
+use crate::error::{SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_CRYPTO_ERROR};
+use crate::protocol::{SpdmBaseHashAlgo, SpdmDigestStruct};
+
+#[cfg(feature = "hashed-transcript-data")]
+use crate::error::SPDM_STATUS_INVALID_PARAMETER;
+
+/// Where the out of session L1/L2 measurement transcript is kept.
+///
+/// By default a context keeps it in `SpdmRuntimeInfo`, as a fixed
+/// `ManagedBufferM` or, with `hashed-transcript-data`, as a running hash.
+/// `SpdmContext::set_l1l2_transcript_storage` hands it to a storage of the
+/// integrator's choosing instead, e.g. a growable one for measurement
+/// records past the fixed buffer size.
+///
+/// The context appends VCA first for SPDM 1.2 and above, then each
+/// GET_MEASUREMENTS/MEASUREMENTS pair, and resets the storage whenever the
+/// transcript starts over. Only the hash is read back, which is what SPDM
+/// 1.2 and above sign. SPDM 1.0 and 1.1 sign the transcript itself, so
+/// while a storage is in use signed measurements outside a session fail
+/// with SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE there.
+pub trait SpdmTranscriptStorage {
+    fn append(&mut self, message: &[u8]) -> SpdmResult;
+
+    fn reset(&mut self);
+
+    /// Hash of everything appended since the last reset. The storage is
+    /// left as it is, more messages may follow.
+    fn finalize(&self, base_hash_algo: SpdmBaseHashAlgo) -> SpdmResult<SpdmDigestStruct>;
+}
+
+impl SpdmTranscriptStorage for ManagedBufferL1L2 {
+    fn append(&mut self, message: &[u8]) -> SpdmResult {
+        self.append_message(message)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.reset_message()
+    }
+
+    fn finalize(&self, base_hash_algo: SpdmBaseHashAlgo) -> SpdmResult<SpdmDigestStruct> {
+        crypto::hash::hash_all(base_hash_algo, self.as_ref()).ok_or(SPDM_STATUS_CRYPTO_ERROR)
+    }
+}
+
+/// Transcript storage that only keeps a running hash. `finalize` must be
+/// asked for the algorithm the storage was created with.
+#[cfg(feature = "hashed-transcript-data")]
+#[derive(Clone)]
+pub struct SpdmHashedTranscript {
+    base_hash_algo: SpdmBaseHashAlgo,
+    digest_context: Option<crypto::SpdmHashCtx>,
+}
+
+#[cfg(feature = "hashed-transcript-data")]
+impl SpdmHashedTranscript {
+    pub fn new(base_hash_algo: SpdmBaseHashAlgo) -> Self {
+        SpdmHashedTranscript {
+            base_hash_algo,
+            digest_context: None,
+        }
+    }
+}
+
+#[cfg(feature = "hashed-transcript-data")]
+impl SpdmTranscriptStorage for SpdmHashedTranscript {
+    fn append(&mut self, message: &[u8]) -> SpdmResult {
+        if self.digest_context.is_none() {
+            self.digest_context = crypto::hash::hash_ctx_init(self.base_hash_algo);
+        }
+        let digest_context = self
+            .digest_context
+            .as_mut()
+            .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
+        crypto::hash::hash_ctx_update(digest_context, message)
+    }
+
+    fn reset(&mut self) {
+        self.digest_context = None;
+    }
+
+    fn finalize(&self, base_hash_algo: SpdmBaseHashAlgo) -> SpdmResult<SpdmDigestStruct> {
+        if base_hash_algo != self.base_hash_algo {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        match self.digest_context.as_ref() {
+            Some(digest_context) => crypto::hash::hash_ctx_finalize(digest_context.clone()),
+            None => crypto::hash::hash_all(base_hash_algo, &[]),
+        }
+        .ok_or(SPDM_STATUS_CRYPTO_ERROR)
+    }
+}
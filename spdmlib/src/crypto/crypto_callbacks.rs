@@ -103,6 +103,10 @@ pub struct SpdmCertOperation {
     pub get_cert_from_cert_chain_cb: GetCertFromCertChainCb,
 
     pub verify_cert_chain_cb: fn(cert_chain: &[u8]) -> SpdmResult,
+
+    /// Ok if the DER certificate verifies with its own public key and is
+    /// within its validity period.
+    pub verify_self_signed_cert_cb: fn(cert: &[u8]) -> SpdmResult,
 }
 
 type GenerateKeyPairCb =
@@ -153,6 +153,160 @@ fn test_case_chacha20_poly1305() {
     assert_eq!(out_plain_text_len, plain_text.len());
 }
 
+#[cfg(feature = "spdm-ring")]
+#[test]
+fn test_case_self_test() {
+    assert!(super::self_test().is_all());
+}
+
+#[cfg(feature = "spdm-ring")]
+#[test]
+fn test_case_self_test_broken_backend() {
+    use super::self_test::*;
+    use super::{SpdmAead, SpdmAsymVerify, SpdmDhe, SpdmHkdf, SpdmHmac};
+    use crate::error::SpdmResult;
+    use crate::protocol::*;
+
+    // every digest comes back as zeros.
+    assert!(!hash_self_test(|base_hash_algo, _data| {
+        let size = base_hash_algo.get_size() as usize;
+        Some(SpdmDigestStruct::from(&[0u8; SPDM_MAX_HASH_SIZE][..size]))
+    }));
+
+    // the tag is right but any tag is accepted.
+    let broken_hmac = SpdmHmac {
+        hmac_cb: super::hmac::hmac,
+        hmac_verify_cb: |_, _, _, _| -> SpdmResult { Ok(()) },
+    };
+    assert!(!hmac_self_test(&broken_hmac));
+
+    // extract ignores the salt.
+    let broken_hkdf = SpdmHkdf {
+        hkdf_extract_cb: |hash_algo, _salt, ikm| super::hkdf::hkdf_extract(hash_algo, &[], ikm),
+        hkdf_expand_cb: super::hkdf::hkdf_expand,
+    };
+    assert!(!hkdf_self_test(&broken_hkdf));
+
+    // decryption skips the tag check.
+    let broken_aead = SpdmAead {
+        encrypt_cb: super::aead::encrypt,
+        decrypt_cb: |_, _, _, _, cipher_text, _, plain_text| -> SpdmResult<usize> {
+            plain_text[..cipher_text.len()].copy_from_slice(cipher_text);
+            Ok(cipher_text.len())
+        },
+    };
+    assert!(!aead_self_test(&broken_aead));
+
+    let broken_dhe = SpdmDhe {
+        generate_key_pair_cb: |_| None,
+    };
+    assert!(!dhe_self_test(&broken_dhe));
+
+    let broken_asym_verify = SpdmAsymVerify {
+        verify_cb: |_, _, _, _, _| -> SpdmResult { Ok(()) },
+    };
+    assert!(!asym_verify_self_test(&broken_asym_verify));
+}
+
+#[cfg(feature = "spdm-ring")]
+#[test]
+fn test_case_asym_backend_per_algo() {
+    use super::{asym_verify, SpdmAsymVerify};
+    use crate::error::{SpdmResult, SPDM_STATUS_VERIF_FAIL};
+    use crate::protocol::*;
+    use crate::secret::{asym_sign, SpdmSecretAsymSign};
+
+    // each backend fills the signature with its own marker.
+    fn marked(size: usize, marker: u8) -> SpdmSignatureStruct {
+        let mut signature = SpdmSignatureStruct {
+            data_size: size as u16,
+            ..Default::default()
+        };
+        signature.data[..size].fill(marker);
+        signature
+    }
+    let ecdsa_sign = SpdmSecretAsymSign {
+        sign_cb: |_, _, _| Some(marked(ECDSA_ECC_NIST_P256_KEY_SIZE, 0xEC)),
+    };
+    let rsa_sign = SpdmSecretAsymSign {
+        sign_cb: |_, _, _| Some(marked(RSASSA_3072_KEY_SIZE, 0x5A)),
+    };
+    assert!(asym_sign::register_for_algo(
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256,
+        ecdsa_sign.clone()
+    ));
+    assert!(asym_sign::register_for_algo(
+        SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072,
+        rsa_sign.clone()
+    ));
+    // already taken, or not a single algorithm.
+    assert!(!asym_sign::register_for_algo(
+        SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072,
+        ecdsa_sign.clone()
+    ));
+    assert!(!asym_sign::register_for_algo(
+        SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048 | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072,
+        rsa_sign
+    ));
+
+    let hash_algo = SpdmBaseHashAlgo::TPM_ALG_SHA_256;
+    let ecdsa_signature = asym_sign::sign(
+        hash_algo,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256,
+        b"data",
+    )
+    .unwrap();
+    assert_eq!(
+        ecdsa_signature.as_ref(),
+        &[0xEC; ECDSA_ECC_NIST_P256_KEY_SIZE][..]
+    );
+    let rsa_signature =
+        asym_sign::sign(hash_algo, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072, b"data").unwrap();
+    assert_eq!(rsa_signature.as_ref(), &[0x5A; RSASSA_3072_KEY_SIZE][..]);
+
+    // each verifier only accepts the marker of its signer.
+    let ecdsa_verify = SpdmAsymVerify {
+        verify_cb: |_, _, _, _, signature| -> SpdmResult {
+            match signature.as_ref().iter().all(|b| *b == 0xEC) {
+                true => Ok(()),
+                false => Err(SPDM_STATUS_VERIF_FAIL),
+            }
+        },
+    };
+    let rsa_verify = SpdmAsymVerify {
+        verify_cb: |_, _, _, _, signature| -> SpdmResult {
+            match signature.as_ref().iter().all(|b| *b == 0x5A) {
+                true => Ok(()),
+                false => Err(SPDM_STATUS_VERIF_FAIL),
+            }
+        },
+    };
+    assert!(asym_verify::register_for_algo(
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256,
+        ecdsa_verify
+    ));
+    assert!(asym_verify::register_for_algo(
+        SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072,
+        rsa_verify
+    ));
+    let ecdsa = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256;
+    let rsa = SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072;
+    assert!(asym_verify::verify(hash_algo, ecdsa, &[], b"data", &ecdsa_signature).is_ok());
+    assert!(asym_verify::verify(hash_algo, rsa, &[], b"data", &rsa_signature).is_ok());
+    assert!(asym_verify::verify(hash_algo, rsa, &[], b"data", &marked(384, 0xEC)).is_err());
+
+    // other algorithms still go to the default backend, which rejects the
+    // marker as a P-384 signature over an empty certificate.
+    assert!(asym_verify::verify(
+        hash_algo,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+        &[],
+        b"data",
+        &marked(ECDSA_ECC_NIST_P384_KEY_SIZE, 0xEC)
+    )
+    .is_err());
+}
+
 fn from_hex(hex_str: &str) -> Result<Vec<u8>, String> {
     if hex_str.len() % 2 != 0 {
         return Err(String::from(
@@ -0,0 +1,176 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::protocol::{SpdmBaseAsymAlgo, SpdmSignatureStruct};
+
+// reference: https://www.itu.int/rec/T-REC-X.690/en
+const ASN1_TAG_INTEGER: u8 = 0x02;
+const ASN1_TAG_SEQUENCE: u8 = 0x30;
+const ASN1_LENGTH_MULTI_OCTET_MASK: u8 = 0x80;
+
+fn is_ecdsa(base_asym_algo: SpdmBaseAsymAlgo) -> bool {
+    base_asym_algo == SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256
+        || base_asym_algo == SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384
+}
+
+// One minimally encoded, non-negative INTEGER at the front of `der`,
+// returned without its sign octet.
+fn read_der_unsigned_integer<'a>(der: &'a [u8], offset: &mut usize) -> Option<&'a [u8]> {
+    if *der.get(*offset)? != ASN1_TAG_INTEGER {
+        return None;
+    }
+    let length = *der.get(*offset + 1)?;
+    // r and s never need more than 0x7F octets, so only the short form.
+    if length == 0 || length & ASN1_LENGTH_MULTI_OCTET_MASK != 0 {
+        return None;
+    }
+    let value = der.get((*offset + 2)..(*offset + 2 + length as usize))?;
+    if value[0] & 0x80 != 0 {
+        return None;
+    }
+    let value = if value.len() > 1 && value[0] == 0 {
+        if value[1] & 0x80 == 0 {
+            return None;
+        }
+        &value[1..]
+    } else {
+        value
+    };
+    *offset += 2 + length as usize;
+    Some(value)
+}
+
+/// Convert a DER encoded ECDSA signature (`SEQUENCE { r INTEGER, s INTEGER }`)
+/// into the fixed size r || s layout (IEEE P1363) SPDM carries for
+/// `base_asym_algo`. Returns None for a non ECDSA algorithm, a malformed or
+/// non-minimal encoding, trailing bytes, or r or s too large for the curve.
+pub fn ecdsa_signature_der_to_p1363(
+    base_asym_algo: SpdmBaseAsymAlgo,
+    der: &[u8],
+) -> Option<SpdmSignatureStruct> {
+    if !is_ecdsa(base_asym_algo) {
+        return None;
+    }
+    if der.len() < 2
+        || der[0] != ASN1_TAG_SEQUENCE
+        || der[1] & ASN1_LENGTH_MULTI_OCTET_MASK != 0
+        || der[1] as usize != der.len() - 2
+    {
+        return None;
+    }
+
+    let mut offset = 2usize;
+    let r = read_der_unsigned_integer(der, &mut offset)?;
+    let s = read_der_unsigned_integer(der, &mut offset)?;
+    if offset != der.len() {
+        return None;
+    }
+
+    let signature_size = base_asym_algo.get_size() as usize;
+    let half_size = signature_size / 2;
+    if r.len() > half_size || s.len() > half_size {
+        return None;
+    }
+    let mut signature = SpdmSignatureStruct {
+        data_size: signature_size as u16,
+        ..Default::default()
+    };
+    signature.data[(half_size - r.len())..half_size].copy_from_slice(r);
+    signature.data[(signature_size - s.len())..signature_size].copy_from_slice(s);
+    Some(signature)
+}
+
+/// Bring an ECDSA signature into the SPDM r || s layout: one of the curve
+/// size is taken as already in that layout, any other size must be DER and
+/// is converted. Other algorithms are returned unchanged.
+pub fn ecdsa_signature_to_p1363(
+    base_asym_algo: SpdmBaseAsymAlgo,
+    signature: &SpdmSignatureStruct,
+) -> Option<SpdmSignatureStruct> {
+    if !is_ecdsa(base_asym_algo) || signature.data_size == base_asym_algo.get_size() {
+        return Some(signature.clone());
+    }
+    let der = signature.data.get(..signature.data_size as usize)?;
+    ecdsa_signature_der_to_p1363(base_asym_algo, der)
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::protocol::ECDSA_ECC_NIST_P256_KEY_SIZE;
+
+    fn signature_struct(data: &[u8]) -> SpdmSignatureStruct {
+        let mut signature = SpdmSignatureStruct {
+            data_size: data.len() as u16,
+            ..Default::default()
+        };
+        signature.data[..data.len()].copy_from_slice(data);
+        signature
+    }
+
+    #[test]
+    fn test_case0_ecdsa_signature_der_to_p1363() {
+        let p256 = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256;
+        // r with its sign octet, s shorter than the curve size.
+        let mut der = [0u8; 2 + 2 + 33 + 2 + 31];
+        der[..4].copy_from_slice(&[0x30, 68, 0x02, 33]);
+        der[4] = 0x00;
+        der[5..37].copy_from_slice(&[0x81; 32]);
+        der[37..39].copy_from_slice(&[0x02, 31]);
+        der[39..].copy_from_slice(&[0x22; 31]);
+
+        let signature = ecdsa_signature_der_to_p1363(p256, &der).unwrap();
+        assert_eq!(signature.data_size as usize, ECDSA_ECC_NIST_P256_KEY_SIZE);
+        assert_eq!(signature.data[..32], [0x81; 32]);
+        assert_eq!(signature.data[32], 0);
+        assert_eq!(signature.data[33..64], [0x22; 31]);
+
+        let converted = ecdsa_signature_to_p1363(p256, &signature_struct(&der)).unwrap();
+        assert_eq!(converted.as_ref(), signature.as_ref());
+        // an r || s signature is passed through.
+        let converted = ecdsa_signature_to_p1363(p256, &signature).unwrap();
+        assert_eq!(converted.as_ref(), signature.as_ref());
+    }
+
+    #[test]
+    fn test_case1_ecdsa_signature_der_to_p1363() {
+        let p256 = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256;
+        let mut der = [0u8; 2 + 2 + 32 + 2 + 32];
+        der[..4].copy_from_slice(&[0x30, 68, 0x02, 32]);
+        der[4..36].copy_from_slice(&[0x11; 32]);
+        der[36..38].copy_from_slice(&[0x02, 32]);
+        der[38..].copy_from_slice(&[0x22; 32]);
+        assert!(ecdsa_signature_der_to_p1363(p256, &der).is_some());
+
+        // trailing bytes or a wrong outer length.
+        let mut long_der = der.to_vec();
+        long_der.push(0);
+        assert!(ecdsa_signature_der_to_p1363(p256, &long_der).is_none());
+        // a negative s.
+        let mut bad_der = der;
+        bad_der[38] = 0x80;
+        assert!(ecdsa_signature_der_to_p1363(p256, &bad_der).is_none());
+        // a redundant leading zero.
+        let mut bad_der = der;
+        bad_der[4] = 0x00;
+        assert!(ecdsa_signature_der_to_p1363(p256, &bad_der).is_none());
+        // r and s of a P-384 signature do not fit P-256, nor does DER an RSA key.
+        let mut p384_der = [0u8; 2 + 2 + 48 + 2 + 48];
+        p384_der[..4].copy_from_slice(&[0x30, 100, 0x02, 48]);
+        p384_der[4..52].copy_from_slice(&[0x11; 48]);
+        p384_der[52..54].copy_from_slice(&[0x02, 48]);
+        p384_der[54..].copy_from_slice(&[0x22; 48]);
+        assert!(ecdsa_signature_der_to_p1363(p256, &p384_der).is_none());
+        assert!(ecdsa_signature_der_to_p1363(
+            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+            &p384_der
+        )
+        .is_some());
+        assert!(
+            ecdsa_signature_der_to_p1363(SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048, &der).is_none()
+        );
+        // neither r || s of the curve size nor DER.
+        assert!(ecdsa_signature_to_p1363(p256, &signature_struct(&[0x11; 63])).is_none());
+    }
+}
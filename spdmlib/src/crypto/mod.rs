@@ -4,7 +4,12 @@
 
 pub mod bytes_mut_scrubbed;
 mod crypto_callbacks;
+mod ecdsa_signature;
+mod self_test;
 mod x509v3;
+pub use ecdsa_signature::*;
+pub(crate) use self_test::SELF_TEST_MESSAGE;
+pub use self_test::{self_test, SpdmCryptoSelfTestResult};
 pub use x509v3::*;
 
 #[cfg(feature = "spdm-ring")]
@@ -29,6 +34,25 @@ static CRYPTO_CERT_OPERATION: OnceCell<SpdmCertOperation> = OnceCell::uninit();
 static CRYPTO_HKDF: OnceCell<SpdmHkdf> = OnceCell::uninit();
 static CRYPTO_RAND: OnceCell<SpdmCryptoRandom> = OnceCell::uninit();
 
+// Backends registered for a single SpdmBaseAsymAlgo, indexed by its bit.
+pub(crate) const ASYM_ALGO_BACKEND_COUNT: usize = 32;
+#[allow(clippy::declare_interior_mutable_const)]
+const ASYM_VERIFY_UNINIT: OnceCell<SpdmAsymVerify> = OnceCell::uninit();
+static CRYPTO_ASYM_VERIFY_BY_ALGO: [OnceCell<SpdmAsymVerify>; ASYM_ALGO_BACKEND_COUNT] =
+    [ASYM_VERIFY_UNINIT; ASYM_ALGO_BACKEND_COUNT];
+
+// The backend slot of `base_asym_algo`, None unless it is one algorithm.
+pub(crate) fn asym_algo_backend_index(
+    base_asym_algo: crate::protocol::SpdmBaseAsymAlgo,
+) -> Option<usize> {
+    let bits = base_asym_algo.bits();
+    if bits.is_power_of_two() {
+        Some(bits.trailing_zeros() as usize)
+    } else {
+        None
+    }
+}
+
 pub mod hash {
     use super::CRYPTO_HASH;
     use crate::crypto::SpdmHash;
@@ -196,9 +220,9 @@ pub mod hmac {
 }
 
 pub mod asym_verify {
-    use super::CRYPTO_ASYM_VERIFY;
+    use super::{CRYPTO_ASYM_VERIFY, CRYPTO_ASYM_VERIFY_BY_ALGO};
     use crate::crypto::SpdmAsymVerify;
-    use crate::error::{SpdmResult, SPDM_STATUS_INVALID_STATE_LOCAL};
+    use crate::error::{SpdmResult, SPDM_STATUS_INVALID_STATE_LOCAL, SPDM_STATUS_VERIF_FAIL};
     use crate::protocol::{SpdmBaseAsymAlgo, SpdmBaseHashAlgo, SpdmSignatureStruct};
 
     #[cfg(not(any(feature = "spdm-ring")))]
@@ -218,6 +242,20 @@ pub mod asym_verify {
         CRYPTO_ASYM_VERIFY.try_get_or_init(|| context).is_ok()
     }
 
+    /// Register a backend used for `base_asym_algo` only, in place of the
+    /// one from `register`. Fails for a set of several algorithms or one
+    /// that already has a backend.
+    pub fn register_for_algo(base_asym_algo: SpdmBaseAsymAlgo, context: SpdmAsymVerify) -> bool {
+        match super::asym_algo_backend_index(base_asym_algo) {
+            Some(index) => CRYPTO_ASYM_VERIFY_BY_ALGO[index]
+                .try_init_once(|| context)
+                .is_ok(),
+            None => false,
+        }
+    }
+
+    /// An ECDSA signature may be given DER encoded; the callback always
+    /// sees the fixed size r || s layout of the curve.
     pub fn verify(
         base_hash_algo: SpdmBaseHashAlgo,
         base_asym_algo: SpdmBaseAsymAlgo,
@@ -225,15 +263,22 @@ pub mod asym_verify {
         data: &[u8],
         signature: &SpdmSignatureStruct,
     ) -> SpdmResult {
-        (CRYPTO_ASYM_VERIFY
-            .try_get_or_init(|| DEFAULT.clone())
-            .map_err(|_| SPDM_STATUS_INVALID_STATE_LOCAL)?
-            .verify_cb)(
+        let signature = super::ecdsa_signature_to_p1363(base_asym_algo, signature)
+            .ok_or(SPDM_STATUS_VERIF_FAIL)?;
+        let backend = match super::asym_algo_backend_index(base_asym_algo)
+            .and_then(|index| CRYPTO_ASYM_VERIFY_BY_ALGO[index].get())
+        {
+            Some(backend) => backend,
+            None => CRYPTO_ASYM_VERIFY
+                .try_get_or_init(|| DEFAULT.clone())
+                .map_err(|_| SPDM_STATUS_INVALID_STATE_LOCAL)?,
+        };
+        (backend.verify_cb)(
             base_hash_algo,
             base_asym_algo,
             public_cert_der,
             data,
-            signature,
+            &signature,
         )
     }
 }
@@ -282,6 +327,7 @@ pub mod cert_operation {
                                       _index: isize|
          -> SpdmResult<(usize, usize)> { unimplemented!() },
         verify_cert_chain_cb: |_cert_chain: &[u8]| -> SpdmResult { unimplemented!() },
+        verify_self_signed_cert_cb: |_cert: &[u8]| -> SpdmResult { unimplemented!() },
     };
 
     #[cfg(feature = "spdm-ring")]
@@ -304,6 +350,13 @@ pub mod cert_operation {
             .map_err(|_| SPDM_STATUS_INVALID_STATE_LOCAL)?
             .verify_cert_chain_cb)(cert_chain)
     }
+
+    pub fn verify_self_signed_cert(cert: &[u8]) -> SpdmResult {
+        (CRYPTO_CERT_OPERATION
+            .try_get_or_init(|| DEFAULT.clone())
+            .map_err(|_| SPDM_STATUS_INVALID_STATE_LOCAL)?
+            .verify_self_signed_cert_cb)(cert)
+    }
 }
 
 pub mod hkdf {
@@ -0,0 +1,327 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! Power-on self-test of the registered crypto backends.
+//!
+//! Each primitive is run against a known answer vector, or checked pairwise
+//! where the output is randomized, through the same dispatch functions the
+//! protocol code uses, so a mis-wired `register` shows up before the first
+//! handshake.
+
+use super::{aead, asym_verify, dhe, hash, hkdf, hmac};
+use crate::crypto::{SpdmAead, SpdmAsymVerify, SpdmDhe, SpdmHkdf, SpdmHmac};
+use crate::protocol::*;
+
+bitflags! {
+    #[derive(Default)]
+    pub struct SpdmCryptoSelfTestResult: u32 {
+        const HASH = 0b0000_0001;
+        const HMAC = 0b0000_0010;
+        const HKDF = 0b0000_0100;
+        const AEAD = 0b0000_1000;
+        const DHE = 0b0001_0000;
+        const ASYM_VERIFY = 0b0010_0000;
+    }
+}
+
+/// Run every self-test against the registered backends and report the
+/// primitives that passed.
+///
+/// Signing is not covered here, the signing key is provisioned through
+/// `secret::asym_sign`, see `secret::asym_sign::self_test`.
+pub fn self_test() -> SpdmCryptoSelfTestResult {
+    let mut result = SpdmCryptoSelfTestResult::empty();
+    if hash_self_test(hash::hash_all) {
+        result |= SpdmCryptoSelfTestResult::HASH;
+    }
+    if hmac_self_test(&SpdmHmac {
+        hmac_cb: hmac::hmac,
+        hmac_verify_cb: hmac::hmac_verify,
+    }) {
+        result |= SpdmCryptoSelfTestResult::HMAC;
+    }
+    if hkdf_self_test(&SpdmHkdf {
+        hkdf_extract_cb: hkdf::hkdf_extract,
+        hkdf_expand_cb: hkdf::hkdf_expand,
+    }) {
+        result |= SpdmCryptoSelfTestResult::HKDF;
+    }
+    if aead_self_test(&SpdmAead {
+        encrypt_cb: aead::encrypt,
+        decrypt_cb: aead::decrypt,
+    }) {
+        result |= SpdmCryptoSelfTestResult::AEAD;
+    }
+    if dhe_self_test(&SpdmDhe {
+        generate_key_pair_cb: dhe::generate_key_pair,
+    }) {
+        result |= SpdmCryptoSelfTestResult::DHE;
+    }
+    if asym_verify_self_test(&SpdmAsymVerify {
+        verify_cb: asym_verify::verify,
+    }) {
+        result |= SpdmCryptoSelfTestResult::ASYM_VERIFY;
+    }
+    result
+}
+
+// SpdmHash grows streaming callbacks with hashed-transcript-data, only the
+// one-shot hash is tested.
+pub(crate) fn hash_self_test(
+    hash_all: fn(SpdmBaseHashAlgo, &[u8]) -> Option<SpdmDigestStruct>,
+) -> bool {
+    // FIPS 180-2 "abc"
+    let vectors: [(SpdmBaseHashAlgo, &[u8]); 2] = [
+        (SpdmBaseHashAlgo::TPM_ALG_SHA_256, &SHA256_ABC),
+        (SpdmBaseHashAlgo::TPM_ALG_SHA_384, &SHA384_ABC),
+    ];
+    vectors.iter().all(|(base_hash_algo, expected)| {
+        matches!(hash_all(*base_hash_algo, b"abc"), Some(digest) if digest.as_ref() == *expected)
+    })
+}
+
+pub(crate) fn hmac_self_test(backend: &SpdmHmac) -> bool {
+    // RFC 4231 test case 2
+    let key = b"Jefe";
+    let data = b"what do ya want for nothing?";
+    let vectors: [(SpdmBaseHashAlgo, &[u8]); 2] = [
+        (SpdmBaseHashAlgo::TPM_ALG_SHA_256, &HMAC_SHA256_JEFE),
+        (SpdmBaseHashAlgo::TPM_ALG_SHA_384, &HMAC_SHA384_JEFE),
+    ];
+    vectors.iter().all(|(base_hash_algo, expected)| {
+        match (backend.hmac_cb)(*base_hash_algo, key, data) {
+            Some(tag) if tag.as_ref() == *expected => {}
+            _ => return false,
+        }
+        let mut tag = SpdmDigestStruct::from(*expected);
+        if (backend.hmac_verify_cb)(*base_hash_algo, key, data, &tag).is_err() {
+            return false;
+        }
+        tag.data[0] ^= 1;
+        (backend.hmac_verify_cb)(*base_hash_algo, key, data, &tag).is_err()
+    })
+}
+
+pub(crate) fn hkdf_self_test(backend: &SpdmHkdf) -> bool {
+    // RFC 5869 test case 1
+    let ikm = SpdmDigestStruct::from(&[0x0bu8; 22][..]);
+    let salt = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+    ];
+    let info = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9];
+
+    let prk = match (backend.hkdf_extract_cb)(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+        &salt,
+        &SpdmHkdfInputKeyingMaterial::SpdmDigest(&ikm),
+    ) {
+        Some(prk) if prk.as_ref() == HKDF_SHA256_PRK => prk,
+        _ => return false,
+    };
+    let okm = (backend.hkdf_expand_cb)(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+        &prk,
+        &info,
+        HKDF_SHA256_OKM.len() as u16,
+    );
+    matches!(okm, Some(okm) if okm.as_ref() == HKDF_SHA256_OKM)
+}
+
+pub(crate) fn aead_self_test(backend: &SpdmAead) -> bool {
+    // SP 800-38D AES-256-GCM, Count = 0
+    let key = SpdmAeadKeyStruct::from(&AES_256_GCM_KEY[..]);
+    let iv = SpdmAeadIvStruct::from(&AES_256_GCM_IV[..]);
+
+    let mut tag = [0u8; 16];
+    let mut cipher_text = [0u8; 16];
+    match (backend.encrypt_cb)(
+        SpdmAeadAlgo::AES_256_GCM,
+        &key,
+        &iv,
+        &AES_256_GCM_AAD,
+        &AES_256_GCM_PT,
+        &mut tag,
+        &mut cipher_text,
+    ) {
+        Ok((16, 16)) if cipher_text == AES_256_GCM_CT && tag == AES_256_GCM_TAG => {}
+        _ => return false,
+    }
+
+    let mut plain_text = [0u8; 16];
+    match (backend.decrypt_cb)(
+        SpdmAeadAlgo::AES_256_GCM,
+        &key,
+        &iv,
+        &AES_256_GCM_AAD,
+        &cipher_text,
+        &tag,
+        &mut plain_text,
+    ) {
+        Ok(16) if plain_text == AES_256_GCM_PT => {}
+        _ => return false,
+    }
+
+    // a forged tag must be refused.
+    tag[0] ^= 1;
+    (backend.decrypt_cb)(
+        SpdmAeadAlgo::AES_256_GCM,
+        &key,
+        &iv,
+        &AES_256_GCM_AAD,
+        &cipher_text,
+        &tag,
+        &mut plain_text,
+    )
+    .is_err()
+}
+
+// key generation is randomized, so both sides of an exchange must simply
+// agree on the same secret.
+pub(crate) fn dhe_self_test(backend: &SpdmDhe) -> bool {
+    let generate = || (backend.generate_key_pair_cb)(SpdmDheAlgo::SECP_384_R1);
+    let ((public_a, private_a), (public_b, private_b)) = match (generate(), generate()) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return false,
+    };
+    if public_a.as_ref() == public_b.as_ref() {
+        return false;
+    }
+
+    let final_key_a = private_a.compute_final_key(&public_b);
+    let final_key_b = private_b.compute_final_key(&public_a);
+    match (final_key_a, final_key_b) {
+        (Some(a), Some(b)) => a.data_size != 0 && a.as_ref() == b.as_ref(),
+        _ => false,
+    }
+}
+
+pub(crate) fn asym_verify_self_test(backend: &SpdmAsymVerify) -> bool {
+    let mut signature = SpdmSignatureStruct {
+        data_size: ECDSA_P384_SHA384_SIGNATURE.len() as u16,
+        ..Default::default()
+    };
+    signature.data[..ECDSA_P384_SHA384_SIGNATURE.len()]
+        .copy_from_slice(&ECDSA_P384_SHA384_SIGNATURE);
+
+    let verify = |data: &[u8], signature: &SpdmSignatureStruct| {
+        (backend.verify_cb)(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+            &ECDSA_P384_CERT,
+            data,
+            signature,
+        )
+    };
+    if verify(SELF_TEST_MESSAGE, &signature).is_err() {
+        return false;
+    }
+    signature.data[0] ^= 1;
+    verify(SELF_TEST_MESSAGE, &signature).is_err()
+}
+
+pub(crate) const SELF_TEST_MESSAGE: &[u8] = b"SPDM crypto self test";
+
+const SHA256_ABC: [u8; 32] = [
+    0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23,
+    0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff, 0x61, 0xf2, 0x00, 0x15, 0xad,
+];
+
+const SHA384_ABC: [u8; 48] = [
+    0xcb, 0x00, 0x75, 0x3f, 0x45, 0xa3, 0x5e, 0x8b, 0xb5, 0xa0, 0x3d, 0x69, 0x9a, 0xc6, 0x50, 0x07,
+    0x27, 0x2c, 0x32, 0xab, 0x0e, 0xde, 0xd1, 0x63, 0x1a, 0x8b, 0x60, 0x5a, 0x43, 0xff, 0x5b, 0xed,
+    0x80, 0x86, 0x07, 0x2b, 0xa1, 0xe7, 0xcc, 0x23, 0x58, 0xba, 0xec, 0xa1, 0x34, 0xc8, 0x25, 0xa7,
+];
+
+const HMAC_SHA256_JEFE: [u8; 32] = [
+    0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95, 0x75, 0xc7,
+    0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9, 0x64, 0xec, 0x38, 0x43,
+];
+
+const HMAC_SHA384_JEFE: [u8; 48] = [
+    0xaf, 0x45, 0xd2, 0xe3, 0x76, 0x48, 0x40, 0x31, 0x61, 0x7f, 0x78, 0xd2, 0xb5, 0x8a, 0x6b, 0x1b,
+    0x9c, 0x7e, 0xf4, 0x64, 0xf5, 0xa0, 0x1b, 0x47, 0xe4, 0x2e, 0xc3, 0x73, 0x63, 0x22, 0x44, 0x5e,
+    0x8e, 0x22, 0x40, 0xca, 0x5e, 0x69, 0xe2, 0xc7, 0x8b, 0x32, 0x39, 0xec, 0xfa, 0xb2, 0x16, 0x49,
+];
+
+const HKDF_SHA256_PRK: [u8; 32] = [
+    0x07, 0x77, 0x09, 0x36, 0x2c, 0x2e, 0x32, 0xdf, 0x0d, 0xdc, 0x3f, 0x0d, 0xc4, 0x7b, 0xba, 0x63,
+    0x90, 0xb6, 0xc7, 0x3b, 0xb5, 0x0f, 0x9c, 0x31, 0x22, 0xec, 0x84, 0x4a, 0xd7, 0xc2, 0xb3, 0xe5,
+];
+
+const HKDF_SHA256_OKM: [u8; 42] = [
+    0x3c, 0xb2, 0x5f, 0x25, 0xfa, 0xac, 0xd5, 0x7a, 0x90, 0x43, 0x4f, 0x64, 0xd0, 0x36, 0x2f, 0x2a,
+    0x2d, 0x2d, 0x0a, 0x90, 0xcf, 0x1a, 0x5a, 0x4c, 0x5d, 0xb0, 0x2d, 0x56, 0xec, 0xc4, 0xc5, 0xbf,
+    0x34, 0x00, 0x72, 0x08, 0xd5, 0xb8, 0x87, 0x18, 0x58, 0x65,
+];
+
+const AES_256_GCM_KEY: [u8; 32] = [
+    0x92, 0xe1, 0x1d, 0xcd, 0xaa, 0x86, 0x6f, 0x5c, 0xe7, 0x90, 0xfd, 0x24, 0x50, 0x1f, 0x92, 0x50,
+    0x9a, 0xac, 0xf4, 0xcb, 0x8b, 0x13, 0x39, 0xd5, 0x0c, 0x9c, 0x12, 0x40, 0x93, 0x5d, 0xd0, 0x8b,
+];
+const AES_256_GCM_IV: [u8; 12] = [
+    0xac, 0x93, 0xa1, 0xa6, 0x14, 0x52, 0x99, 0xbd, 0xe9, 0x02, 0xf2, 0x1a,
+];
+const AES_256_GCM_PT: [u8; 16] = [
+    0x2d, 0x71, 0xbc, 0xfa, 0x91, 0x4e, 0x4a, 0xc0, 0x45, 0xb2, 0xaa, 0x60, 0x95, 0x5f, 0xad, 0x24,
+];
+const AES_256_GCM_AAD: [u8; 16] = [
+    0x1e, 0x08, 0x89, 0x01, 0x6f, 0x67, 0x60, 0x1c, 0x8e, 0xbe, 0xa4, 0x94, 0x3b, 0xc2, 0x3a, 0xd6,
+];
+const AES_256_GCM_CT: [u8; 16] = [
+    0x89, 0x95, 0xae, 0x2e, 0x6d, 0xf3, 0xdb, 0xf9, 0x6f, 0xac, 0x7b, 0x71, 0x37, 0xba, 0xe6, 0x7f,
+];
+const AES_256_GCM_TAG: [u8; 16] = [
+    0xec, 0xa5, 0xaa, 0x77, 0xd5, 0x1d, 0x4a, 0x0a, 0x14, 0xd9, 0xc5, 0x1e, 0x1d, 0xa4, 0x74, 0xab,
+];
+
+// SELF_TEST_MESSAGE signed with test_key/ecp384/end_responder.key.
+const ECDSA_P384_SHA384_SIGNATURE: [u8; 96] = [
+    0xf3, 0x00, 0xf5, 0x6c, 0x3b, 0x6c, 0x08, 0xa7, 0x93, 0x93, 0x53, 0xc3, 0x30, 0xae, 0xd0, 0x49,
+    0xa8, 0x41, 0xe3, 0x81, 0x75, 0x86, 0x87, 0x06, 0xaa, 0xbd, 0x6b, 0x41, 0x12, 0x2d, 0x28, 0xad,
+    0x6c, 0x48, 0x1d, 0xf1, 0x1e, 0x35, 0x38, 0x2e, 0xb2, 0x7d, 0xa1, 0x68, 0xd9, 0xb5, 0xcb, 0x40,
+    0xc9, 0xf2, 0x33, 0xe6, 0xd3, 0x7c, 0x07, 0x74, 0x6a, 0x48, 0x0b, 0xe6, 0x43, 0xc4, 0x3c, 0x84,
+    0x44, 0xcd, 0xb4, 0x27, 0x49, 0x17, 0x29, 0x86, 0x90, 0xb7, 0xb5, 0xa8, 0xa6, 0x1e, 0xc0, 0x76,
+    0xe0, 0x43, 0xc8, 0x7a, 0x7e, 0xf3, 0x7b, 0x55, 0xcd, 0x65, 0x53, 0xd1, 0x31, 0xe0, 0x42, 0x87,
+];
+
+// test_key/ecp384/end_responder.cert.der
+const ECDSA_P384_CERT: [u8; 582] = [
+    0x30, 0x82, 0x02, 0x42, 0x30, 0x82, 0x01, 0xc8, 0xa0, 0x03, 0x02, 0x01, 0x02, 0x02, 0x01, 0x03,
+    0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03, 0x30, 0x30, 0x31, 0x2e,
+    0x30, 0x2c, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x25, 0x44, 0x4d, 0x54, 0x46, 0x20, 0x6c, 0x69,
+    0x62, 0x73, 0x70, 0x64, 0x6d, 0x20, 0x45, 0x43, 0x50, 0x32, 0x35, 0x36, 0x20, 0x69, 0x6e, 0x74,
+    0x65, 0x72, 0x6d, 0x65, 0x64, 0x69, 0x61, 0x74, 0x65, 0x20, 0x63, 0x65, 0x72, 0x74, 0x30, 0x1e,
+    0x17, 0x0d, 0x32, 0x33, 0x30, 0x34, 0x30, 0x33, 0x30, 0x35, 0x35, 0x35, 0x35, 0x34, 0x5a, 0x17,
+    0x0d, 0x33, 0x33, 0x30, 0x33, 0x33, 0x31, 0x30, 0x35, 0x35, 0x35, 0x35, 0x34, 0x5a, 0x30, 0x2d,
+    0x31, 0x2b, 0x30, 0x29, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x22, 0x44, 0x4d, 0x54, 0x46, 0x20,
+    0x6c, 0x69, 0x62, 0x73, 0x70, 0x64, 0x6d, 0x20, 0x45, 0x43, 0x50, 0x32, 0x35, 0x36, 0x20, 0x72,
+    0x65, 0x73, 0x70, 0x6f, 0x6e, 0x64, 0x65, 0x72, 0x20, 0x63, 0x65, 0x72, 0x74, 0x30, 0x76, 0x30,
+    0x10, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x05, 0x2b, 0x81, 0x04, 0x00,
+    0x22, 0x03, 0x62, 0x00, 0x04, 0x3e, 0x61, 0x4a, 0x1f, 0xd8, 0xe6, 0xb5, 0x3f, 0xfc, 0x25, 0x4c,
+    0xba, 0x05, 0x70, 0x07, 0xf4, 0xb4, 0x4f, 0x1f, 0x1e, 0x90, 0x8c, 0x61, 0x52, 0x03, 0x10, 0x8d,
+    0x88, 0x14, 0xb2, 0xd1, 0x61, 0xfa, 0x14, 0xf9, 0x82, 0xbf, 0x43, 0xf1, 0xf4, 0x79, 0x21, 0x99,
+    0x04, 0x27, 0x78, 0x0e, 0x22, 0xdc, 0x55, 0x5f, 0xe1, 0x8b, 0xf7, 0x41, 0x29, 0x88, 0x24, 0x58,
+    0xb0, 0xef, 0x2c, 0x07, 0xcc, 0x92, 0xe0, 0x68, 0x33, 0x81, 0xd8, 0x5f, 0x48, 0x43, 0xfa, 0xfb,
+    0x7a, 0xc5, 0x77, 0x57, 0x62, 0x43, 0xa3, 0x4e, 0x0b, 0x97, 0x36, 0x05, 0xab, 0x1b, 0xf9, 0x13,
+    0xc5, 0x67, 0x5a, 0xa1, 0xc8, 0xa3, 0x81, 0xb8, 0x30, 0x81, 0xb5, 0x30, 0x0c, 0x06, 0x03, 0x55,
+    0x1d, 0x13, 0x01, 0x01, 0xff, 0x04, 0x02, 0x30, 0x00, 0x30, 0x0b, 0x06, 0x03, 0x55, 0x1d, 0x0f,
+    0x04, 0x04, 0x03, 0x02, 0x05, 0xe0, 0x30, 0x1d, 0x06, 0x03, 0x55, 0x1d, 0x0e, 0x04, 0x16, 0x04,
+    0x14, 0x08, 0xfc, 0xa6, 0x09, 0x35, 0x10, 0xff, 0x39, 0xd3, 0x07, 0xe7, 0x73, 0xe8, 0x32, 0xff,
+    0x9b, 0xcc, 0x3a, 0xc0, 0x9e, 0x30, 0x31, 0x06, 0x03, 0x55, 0x1d, 0x11, 0x04, 0x2a, 0x30, 0x28,
+    0xa0, 0x26, 0x06, 0x0a, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1c, 0x82, 0x12, 0x01, 0xa0, 0x18,
+    0x0c, 0x16, 0x41, 0x43, 0x4d, 0x45, 0x3a, 0x57, 0x49, 0x44, 0x47, 0x45, 0x54, 0x3a, 0x31, 0x32,
+    0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x30, 0x30, 0x2a, 0x06, 0x03, 0x55, 0x1d, 0x25, 0x01,
+    0x01, 0xff, 0x04, 0x20, 0x30, 0x1e, 0x06, 0x08, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x01,
+    0x06, 0x08, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x03, 0x02, 0x06, 0x08, 0x2b, 0x06, 0x01, 0x05,
+    0x05, 0x07, 0x03, 0x09, 0x30, 0x1a, 0x06, 0x0a, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1c, 0x82,
+    0x12, 0x06, 0x04, 0x0c, 0x06, 0x0a, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x83, 0x1c, 0x82, 0x12, 0x02,
+    0x30, 0x0a, 0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03, 0x03, 0x68, 0x00, 0x30,
+    0x65, 0x02, 0x31, 0x00, 0xff, 0xa7, 0x12, 0x00, 0x73, 0xf7, 0x07, 0x35, 0xc9, 0xb6, 0x72, 0xcc,
+    0x88, 0xc6, 0x4d, 0x98, 0x51, 0xcf, 0xd9, 0x41, 0xfd, 0x47, 0x05, 0x3f, 0x50, 0x1c, 0xcb, 0xa9,
+    0xd6, 0x27, 0x51, 0xfc, 0xb2, 0x7c, 0x65, 0x3a, 0xcb, 0xb3, 0xaa, 0x1f, 0x6e, 0xcb, 0xe3, 0xe9,
+    0x2b, 0x42, 0xff, 0xad, 0x02, 0x30, 0x6d, 0x37, 0x3f, 0x3c, 0xa5, 0xcd, 0x50, 0xd5, 0x90, 0x11,
+    0x5b, 0xa1, 0xdb, 0xda, 0x96, 0x19, 0xa4, 0xda, 0xb3, 0x2d, 0x81, 0x07, 0xe6, 0x1e, 0x56, 0x35,
+    0x01, 0x96, 0x9f, 0x89, 0xb5, 0x24, 0xc2, 0x10, 0xbf, 0x1f, 0x28, 0x42, 0xda, 0x9b, 0x21, 0x7f,
+    0x4e, 0x86, 0xc2, 0x6b, 0xd8, 0xad,
+];
@@ -65,9 +65,9 @@ fn asym_verify(
         | (SpdmBaseHashAlgo::TPM_ALG_SHA_512, SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096) => {
             &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY
         }
-        _ => {
-            panic!();
-        }
+        // EdDSA signs the message itself, whatever the negotiated hash.
+        (_, SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519) => &webpki::ED25519,
+        _ => return Err(SPDM_STATUS_VERIF_FAIL),
     };
 
     x509v3::check_cert_chain_format(public_cert_der, base_asym_algo)?;
@@ -103,8 +103,9 @@ fn asym_verify(
                 | SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096
                 | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048
                 | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072
-                | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096 => {
-                    // RSASSA or RSAPSS
+                | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096
+                | SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519 => {
+                    // RSASSA, RSAPSS or EdDSA
                     match cert.verify_signature(algorithm, data, signature.as_ref()) {
                         Ok(()) => Ok(()),
                         Err(_) => Err(SPDM_STATUS_VERIF_FAIL),
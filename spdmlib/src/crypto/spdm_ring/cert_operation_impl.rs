@@ -7,21 +7,34 @@ use alloc::vec;
 use alloc::vec::Vec;
 use core::convert::TryFrom;
 
+use crate::config;
 use crate::crypto::SpdmCertOperation;
-use crate::error::{SpdmResult, SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_STATE_LOCAL};
+use crate::error::{SpdmResult, SPDM_STATUS_INVALID_CERT};
 use ring::io::der;
 
 pub static DEFAULT: SpdmCertOperation = SpdmCertOperation {
     get_cert_from_cert_chain_cb: get_cert_from_cert_chain,
     verify_cert_chain_cb: verify_cert_chain,
+    verify_self_signed_cert_cb: verify_self_signed_cert,
 };
 
+static ALL_SIGALGS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::ED25519,
+];
+
 fn get_cert_from_cert_chain(cert_chain: &[u8], index: isize) -> SpdmResult<(usize, usize)> {
     let mut offset = 0usize;
     let mut this_index = 0isize;
     let cert_chain_size = cert_chain.len();
     loop {
-        if cert_chain[offset..].len() < 4 || offset > cert_chain.len() {
+        if offset > cert_chain.len() || cert_chain[offset..].len() < 4 {
             return Err(SPDM_STATUS_INVALID_CERT);
         }
         if cert_chain[offset] != 0x30 || cert_chain[offset + 1] != 0x82 {
@@ -45,19 +58,9 @@ fn get_cert_from_cert_chain(cert_chain: &[u8], index: isize) -> SpdmResult<(usiz
     }
 }
 
-fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
-    static EKU_SPDM_RESPONDER_AUTH: &[u8] = &[40 + 3, 6, 1, 5, 5, 7, 3, 1];
-
-    static ALL_SIGALGS: &[&webpki::SignatureAlgorithm] = &[
-        &webpki::RSA_PKCS1_2048_8192_SHA256,
-        &webpki::RSA_PKCS1_2048_8192_SHA384,
-        &webpki::RSA_PKCS1_2048_8192_SHA512,
-        &webpki::ECDSA_P256_SHA256,
-        &webpki::ECDSA_P256_SHA384,
-        &webpki::ECDSA_P384_SHA256,
-        &webpki::ECDSA_P384_SHA384,
-    ];
-
+// Split a DER chain into its certificates, failing as soon as it holds more
+// than MAX_SPDM_CERT_CHAIN_DEPTH of them.
+fn split_cert_chain(cert_chain: &[u8]) -> SpdmResult<Vec<&[u8]>> {
     let certs_der = untrusted::Input::from(cert_chain);
     let reader = &mut untrusted::Reader::new(certs_der);
 
@@ -66,6 +69,13 @@ fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
         let start = reader.mark();
         match der::expect_tag_and_get_value(reader, der::Tag::Sequence) {
             Ok(_) => {
+                if certs.len() == config::MAX_SPDM_CERT_CHAIN_DEPTH {
+                    error!(
+                        "cert chain deeper than {} certs\n",
+                        config::MAX_SPDM_CERT_CHAIN_DEPTH
+                    );
+                    return Err(SPDM_STATUS_INVALID_CERT);
+                }
                 let end = reader.mark();
                 let cert = reader
                     .get_input_between_marks(start, end)
@@ -75,6 +85,13 @@ fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
             Err(_) => break,
         }
     }
+    Ok(certs)
+}
+
+fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
+    static EKU_SPDM_RESPONDER_AUTH: &[u8] = &[40 + 3, 6, 1, 5, 5, 7, 3, 1];
+
+    let certs = split_cert_chain(cert_chain)?;
     let certs_len = certs.len();
 
     let (ca, inters, ee): (&[u8], &[&[u8]], &[u8]) = match certs_len {
@@ -90,24 +107,13 @@ fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
         return Err(SPDM_STATUS_INVALID_CERT);
     };
 
-    #[cfg(any(target_os = "uefi", target_os = "none"))]
-    let timestamp = {
-        if let Some(ts) = sys_time::get_sys_time() {
-            ts as u64
-        } else {
-            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
-        }
-    };
-    #[cfg(not(any(target_os = "uefi", target_os = "none")))]
-    let timestamp = {
-        extern crate std;
-        if let Ok(ds) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
-            ds.as_secs()
-        } else {
-            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+    let time = match crate::time::get_current_time() {
+        Some(timestamp) => Some(webpki::Time::from_seconds_since_unix_epoch(timestamp)),
+        None => {
+            warn!("!!! no trusted time, certificate validity periods are not checked !!!\n");
+            None
         }
     };
-    let time = webpki::Time::from_seconds_since_unix_epoch(timestamp);
 
     let cert = if let Ok(eec) = webpki::EndEntityCert::try_from(ee) {
         eec
@@ -134,6 +140,48 @@ fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
         Err(SPDM_STATUS_INVALID_CERT)
     }
 }
+
+// A certificate signed with its own key, within its validity period.
+fn verify_self_signed_cert(cert: &[u8]) -> SpdmResult {
+    // Certificate ::= SEQUENCE { tbsCertificate, signatureAlgorithm, signatureValue }
+    let (tbs, signature) =
+        untrusted::Input::from(cert).read_all(SPDM_STATUS_INVALID_CERT, |reader| {
+            der::nested(
+                reader,
+                der::Tag::Sequence,
+                SPDM_STATUS_INVALID_CERT,
+                |cert| {
+                    let start = cert.mark();
+                    der::expect_tag_and_get_value(cert, der::Tag::Sequence)
+                        .map_err(|_| SPDM_STATUS_INVALID_CERT)?;
+                    let tbs = cert
+                        .get_input_between_marks(start, cert.mark())
+                        .map_err(|_| SPDM_STATUS_INVALID_CERT)?;
+                    der::expect_tag_and_get_value(cert, der::Tag::Sequence)
+                        .map_err(|_| SPDM_STATUS_INVALID_CERT)?;
+                    let signature = der::bit_string_with_no_unused_bits(cert)
+                        .map_err(|_| SPDM_STATUS_INVALID_CERT)?;
+                    Ok((tbs, signature))
+                },
+            )
+        })?;
+
+    let ee = webpki::EndEntityCert::try_from(cert).map_err(|_| SPDM_STATUS_INVALID_CERT)?;
+    if !ALL_SIGALGS.iter().any(|alg| {
+        ee.verify_signature(
+            alg,
+            tbs.as_slice_less_safe(),
+            signature.as_slice_less_safe(),
+        )
+        .is_ok()
+    }) {
+        error!("self-signed cert signature - fail!\n");
+        return Err(SPDM_STATUS_INVALID_CERT);
+    }
+
+    crate::crypto::check_cert_validity(cert).map_err(|_| SPDM_STATUS_INVALID_CERT)
+}
+
 #[cfg(all(test,))]
 mod tests {
     use super::*;
@@ -181,6 +229,30 @@ mod tests {
         assert!(status);
     }
 
+    #[test]
+    fn test_case6_cert_chain_depth() {
+        let cert_chain = &include_bytes!("public_cert.der")[..];
+        let (cert_begin, cert_end) = get_cert_from_cert_chain(cert_chain, 0).unwrap();
+        let cert = &cert_chain[cert_begin..cert_end];
+
+        let mut deep_chain = Vec::new();
+        for _ in 0..config::MAX_SPDM_CERT_CHAIN_DEPTH {
+            deep_chain.extend_from_slice(cert);
+        }
+        assert_eq!(
+            split_cert_chain(&deep_chain).unwrap().len(),
+            config::MAX_SPDM_CERT_CHAIN_DEPTH
+        );
+
+        // one more certificate than allowed.
+        deep_chain.extend_from_slice(cert);
+        assert_eq!(split_cert_chain(&deep_chain), Err(SPDM_STATUS_INVALID_CERT));
+        assert_eq!(
+            verify_cert_chain(&deep_chain),
+            Err(SPDM_STATUS_INVALID_CERT)
+        );
+    }
+
     /// verfiy cert chain
     #[test]
     fn test_verify_cert_chain_case1() {
@@ -212,4 +284,61 @@ mod tests {
         cert_chain[1380] = 0xFF;
         assert!(verify_cert_chain(&cert_chain).is_err());
     }
+
+    #[test]
+    fn test_verify_self_signed_cert() {
+        let cert = &include_bytes!("../../../../test_key/ecp384/ca.cert.der")[..];
+        assert!(verify_self_signed_cert(cert).is_ok());
+
+        // issued by the CA, not by its own key.
+        let leaf = &include_bytes!("../../../../test_key/ecp384/inter.cert.der")[..];
+        assert_eq!(verify_self_signed_cert(leaf), Err(SPDM_STATUS_INVALID_CERT));
+
+        // the last bytes are in the signature.
+        let mut tampered = cert.to_vec();
+        let len = tampered.len();
+        tampered[len - 2] ^= 0x01;
+        assert_eq!(
+            verify_self_signed_cert(&tampered),
+            Err(SPDM_STATUS_INVALID_CERT)
+        );
+    }
+
+    std::thread_local! {
+        static MOCK_TIME: core::cell::Cell<Option<u64>> = const { core::cell::Cell::new(None) };
+    }
+
+    // tests on other threads keep seeing no trusted time.
+    fn get_mock_time() -> Option<u64> {
+        MOCK_TIME.with(|time| time.get())
+    }
+
+    #[test]
+    fn test_verify_cert_chain_time_source() {
+        crate::time::register(crate::time::SpdmTime {
+            sleep_cb: |_: usize| unimplemented!(),
+            get_current_time_cb: get_mock_time,
+        });
+
+        // valid from 2022-11-09 to 2023-10-31.
+        let cert_chain =
+            &include_bytes!("../../../../test_key/crypto_chains/ca_selfsigned.crt.der")[..];
+        assert!(verify_cert_chain(cert_chain).is_ok());
+
+        // 2023-03-28
+        MOCK_TIME.with(|time| time.set(Some(1680000000)));
+        assert!(verify_cert_chain(cert_chain).is_ok());
+
+        // 2023-11-14
+        MOCK_TIME.with(|time| time.set(Some(1700000000)));
+        assert_eq!(verify_cert_chain(cert_chain), Err(SPDM_STATUS_INVALID_CERT));
+        assert_eq!(
+            verify_self_signed_cert(cert_chain),
+            Err(SPDM_STATUS_INVALID_CERT)
+        );
+
+        MOCK_TIME.with(|time| time.set(None));
+        assert!(verify_cert_chain(cert_chain).is_ok());
+        assert!(verify_self_signed_cert(cert_chain).is_ok());
+    }
 }
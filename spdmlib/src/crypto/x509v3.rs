@@ -13,8 +13,11 @@ const ASN1_TAG_CLASS_CONTEXT_SPECIFIC_MASK: u8 = 0x80;
 const ASN1_FORM_CONSTRUCTED_MASK: u8 = 0x20;
 
 const ASN1_TAG_NUMBER_INTEGER: u8 = 0x2;
+const ASN1_TAG_NUMBER_BIT_STRING: u8 = 0x3;
 const ASN1_TAG_NUMBER_OBJECT_IDENTIFIER: u8 = 0x6;
 const ASN1_TAG_NUMBER_SEQUENCE: u8 = 0x10;
+const ASN1_TAG_NUMBER_UTC_TIME: u8 = 0x17;
+const ASN1_TAG_NUMBER_GENERALIZED_TIME: u8 = 0x18;
 
 const ASN1_TAG_SEQUENCE: u8 =
     ASN1_TAG_CLASS_UNIVERSAL_MASK | ASN1_FORM_CONSTRUCTED_MASK | ASN1_TAG_NUMBER_SEQUENCE;
@@ -27,6 +30,8 @@ const OID_RSA_SHA384RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x0
 const OID_RSA_SHA512RSA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0du8];
 const OID_ECDSA_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02u8];
 const OID_ECDSA_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03u8];
+const OID_EDDSA_ED25519: &[u8] = &[0x2b, 0x65, 0x70u8];
+const OID_RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01u8];
 
 // reference: https://www.rfc-editor.org/rfc/rfc5280.txt
 // IN DER encoded certificate chain slice
@@ -86,6 +91,208 @@ fn check_cert_format(cert: &[u8], base_asym_algo: SpdmBaseAsymAlgo) -> SpdmResul
     }
 }
 
+// IN DER encoded certificate slice
+// OUT Ok Some(modulus size in bits) for an RSA subject public key
+// OUT Ok None for any other key type
+// OUT Error Mulformed certificate found
+pub fn get_cert_rsa_key_bits(cert: &[u8]) -> SpdmResult<Option<usize>> {
+    // Certificate ::= SEQUENCE { tbsCertificate TBSCertificate, ... }
+    let cert = enter_sequence(cert)?;
+    let tbs = enter_sequence(cert)?;
+
+    // version, serialNumber, signature, issuer, validity, subject
+    let mut t_walker = check_version(tbs)?;
+    t_walker += check_and_skip_common_tag(&tbs[t_walker..])?;
+    for _ in 0..4 {
+        t_walker += check_and_skip_common_sequence(&tbs[t_walker..])?;
+    }
+
+    // SubjectPublicKeyInfo ::= SEQUENCE {
+    //     algorithm         AlgorithmIdentifier,
+    //     subjectPublicKey  BIT STRING }
+    let public_key_info = enter_sequence(&tbs[t_walker..])?;
+    let algorithm = enter_sequence(public_key_info)?;
+    if check_object_identifier(algorithm, Some(OID_RSA_ENCRYPTION)).is_err() {
+        check_object_identifier(algorithm, None)?;
+        return Ok(None);
+    }
+    let p_walker = check_and_skip_common_sequence(public_key_info)?;
+    let public_key = &public_key_info[p_walker..];
+    if public_key.is_empty() || public_key[0] != ASN1_TAG_NUMBER_BIT_STRING {
+        return Err(SPDM_STATUS_VERIF_FAIL);
+    }
+    let (bit_string_length, bytes_consumed) = check_length(&public_key[1..])?;
+    let bit_string = public_key
+        .get((1 + bytes_consumed)..(1 + bytes_consumed + bit_string_length))
+        .ok_or(SPDM_STATUS_VERIF_FAIL)?;
+    // no unused bits
+    if bit_string.first() != Some(&0) {
+        return Err(SPDM_STATUS_VERIF_FAIL);
+    }
+
+    // RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+    let rsa_public_key = enter_sequence(&bit_string[1..])?;
+    if rsa_public_key.is_empty() || rsa_public_key[0] != ASN1_TAG_NUMBER_INTEGER {
+        return Err(SPDM_STATUS_VERIF_FAIL);
+    }
+    let (modulus_length, bytes_consumed) = check_length(&rsa_public_key[1..])?;
+    let modulus = rsa_public_key
+        .get((1 + bytes_consumed)..(1 + bytes_consumed + modulus_length))
+        .ok_or(SPDM_STATUS_VERIF_FAIL)?;
+    let modulus = match modulus.iter().position(|b| *b != 0) {
+        Some(first_non_zero) => &modulus[first_non_zero..],
+        None => return Err(SPDM_STATUS_VERIF_FAIL),
+    };
+    Ok(Some(
+        modulus.len() * 8 - modulus[0].leading_zeros() as usize,
+    ))
+}
+
+// IN DER encoded certificate slice
+// OUT Ok true if the issuer and the subject names are the same
+// OUT Error Mulformed certificate found
+pub fn is_cert_self_issued(cert: &[u8]) -> SpdmResult<bool> {
+    // Certificate ::= SEQUENCE { tbsCertificate TBSCertificate, ... }
+    let cert = enter_sequence(cert)?;
+    let tbs = enter_sequence(cert)?;
+
+    // version, serialNumber, signature
+    let mut t_walker = check_version(tbs)?;
+    t_walker += check_and_skip_common_tag(&tbs[t_walker..])?;
+    t_walker += check_and_skip_common_sequence(&tbs[t_walker..])?;
+
+    // issuer, validity, subject
+    let issuer_length = check_name(&tbs[t_walker..])?;
+    let issuer = &tbs[t_walker..(t_walker + issuer_length)];
+    t_walker += issuer_length;
+    t_walker += check_validity(&tbs[t_walker..])?;
+    let subject_length = check_name(&tbs[t_walker..])?;
+    let subject = &tbs[t_walker..(t_walker + subject_length)];
+
+    Ok(issuer == subject)
+}
+
+// IN DER encoded certificate slice
+// OUT Ok (notBefore, notAfter) in seconds since the Unix epoch
+// OUT Error Mulformed certificate found
+pub fn get_cert_validity(cert: &[u8]) -> SpdmResult<(u64, u64)> {
+    // Certificate ::= SEQUENCE { tbsCertificate TBSCertificate, ... }
+    let cert = enter_sequence(cert)?;
+    let tbs = enter_sequence(cert)?;
+
+    // version, serialNumber, signature, issuer
+    let mut t_walker = check_version(tbs)?;
+    t_walker += check_and_skip_common_tag(&tbs[t_walker..])?;
+    t_walker += check_and_skip_common_sequence(&tbs[t_walker..])?;
+    t_walker += check_name(&tbs[t_walker..])?;
+
+    // Validity ::= SEQUENCE { notBefore Time, notAfter Time }
+    let validity = enter_sequence(&tbs[t_walker..])?;
+    let (not_before, v_walker) = read_time(validity)?;
+    let (not_after, _) = read_time(&validity[v_walker..])?;
+    Ok((not_before, not_after))
+}
+
+// IN DER encoded certificate slice
+// OUT Ok if `time::get_current_time` is within its validity period, the
+//     check is skipped with a warning without a trusted time
+// OUT Error Mulformed certificate found or not valid at the current time
+pub fn check_cert_validity(cert: &[u8]) -> SpdmResult {
+    let (not_before, not_after) = get_cert_validity(cert)?;
+    if not_before > not_after {
+        return Err(SPDM_STATUS_VERIF_FAIL);
+    }
+    match crate::time::get_current_time() {
+        Some(now) if now < not_before || now > not_after => {
+            error!("cert is not valid at {}\n", now);
+            Err(SPDM_STATUS_VERIF_FAIL)
+        }
+        Some(_) => Ok(()),
+        None => {
+            warn!("!!! no trusted time, certificate validity periods are not checked !!!\n");
+            Ok(())
+        }
+    }
+}
+
+// IN bytes slice starting with a UTCTime or a GeneralizedTime
+// OUT Ok (seconds since the Unix epoch, bytes consumed)
+// OUT Error Mulformed certificate found
+// reference: rfc5280 4.1.2.5, YYMMDDHHMMSSZ or YYYYMMDDHHMMSSZ
+fn read_time(data: &[u8]) -> SpdmResult<(u64, usize)> {
+    let bytes_consumed = check_and_skip_common_tag(data)?;
+    let (length, length_consumed) = check_length(&data[1..])?;
+    let value = &data[(1 + length_consumed)..bytes_consumed];
+
+    let (year, value) = match (data[0], length) {
+        (ASN1_TAG_NUMBER_UTC_TIME, 13) => {
+            let year = read_two_digits(&value[0..2])?;
+            (
+                if year >= 50 { 1900 + year } else { 2000 + year },
+                &value[2..],
+            )
+        }
+        (ASN1_TAG_NUMBER_GENERALIZED_TIME, 15) => (
+            read_two_digits(&value[0..2])? * 100 + read_two_digits(&value[2..4])?,
+            &value[4..],
+        ),
+        _ => return Err(SPDM_STATUS_VERIF_FAIL),
+    };
+    let month = read_two_digits(&value[0..2])?;
+    let day = read_two_digits(&value[2..4])?;
+    let hour = read_two_digits(&value[4..6])?;
+    let minute = read_two_digits(&value[6..8])?;
+    let second = read_two_digits(&value[8..10])?;
+    let is_leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if is_leap_year => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if value[10] != b'Z'
+        || year < 1970
+        || !(1..=12).contains(&month)
+        || !(1..=days_in_month).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return Err(SPDM_STATUS_VERIF_FAIL);
+    }
+
+    // days since 1970-01-01 in the proleptic Gregorian calendar, counting
+    // years from March so the leap day comes last.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    Ok((
+        days * 86400 + hour * 3600 + minute * 60 + second,
+        bytes_consumed,
+    ))
+}
+
+fn read_two_digits(data: &[u8]) -> SpdmResult<u64> {
+    match data {
+        [tens @ b'0'..=b'9', ones @ b'0'..=b'9'] => Ok(((tens - b'0') * 10 + (ones - b'0')) as u64),
+        _ => Err(SPDM_STATUS_VERIF_FAIL),
+    }
+}
+
+// IN bytes slice starting with a SEQUENCE
+// OUT Ok the SEQUENCE content
+// OUT Error Mulformed certificate found
+fn enter_sequence(data: &[u8]) -> SpdmResult<&[u8]> {
+    check_tag_is_sequence(data)?;
+    let (length, bytes_consumed) = check_length(&data[1..])?;
+    data.get((1 + bytes_consumed)..(1 + bytes_consumed + length))
+        .ok_or(SPDM_STATUS_VERIF_FAIL)
+}
+
 fn check_tbs_certificate(
     data: &[u8],
     base_asym_algo: SpdmBaseAsymAlgo,
@@ -295,6 +502,7 @@ fn get_oid_by_base_asym_algo(base_asym_algo: SpdmBaseAsymAlgo) -> Option<&'stati
         SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096 => Some(OID_RSA_SHA512RSA),
         SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096 => Some(OID_RSA_SHA512RSA),
         SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384 => Some(OID_ECDSA_SHA384),
+        SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519 => Some(OID_EDDSA_ED25519),
         _ => None,
     }
 }
@@ -660,6 +868,8 @@ mod tests {
             .expect("unable to read ca cert!");
         let ct5 = std::fs::read("../test_key/rsa4096/bundle_responder.certchain.der")
             .expect("unable to read ca cert!");
+        let ct6 = std::fs::read("../test_key/ed25519/bundle_responder.certchain.der")
+            .expect("unable to read ca cert!");
 
         let ct1_wrong = [0x30, 0x82, 0x01, 0xA8, 0xA0];
 
@@ -683,6 +893,14 @@ mod tests {
             check_cert_chain_format(&ct5, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096),
             Ok(3)
         );
+        assert_eq!(
+            check_cert_chain_format(&ct6, SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519),
+            Ok(3)
+        );
+        assert_eq!(
+            check_cert_chain_format(&ct6, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
         assert_eq!(
             check_cert_chain_format(&ct3, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256),
             Err(SPDM_STATUS_VERIF_FAIL)
@@ -692,4 +910,63 @@ mod tests {
             Err(SPDM_STATUS_VERIF_FAIL)
         );
     }
+
+    #[test]
+    fn test_case0_get_cert_rsa_key_bits() {
+        let c1 = std::fs::read("../test_key/rsa2048/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+        let c2 = std::fs::read("../test_key/rsa3072/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+        let c3 = std::fs::read("../test_key/rsa4096/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+        let c4 = std::fs::read("../test_key/ecp384/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+
+        assert_eq!(get_cert_rsa_key_bits(&c1), Ok(Some(2048)));
+        assert_eq!(get_cert_rsa_key_bits(&c2), Ok(Some(3072)));
+        assert_eq!(get_cert_rsa_key_bits(&c3), Ok(Some(4096)));
+        assert_eq!(get_cert_rsa_key_bits(&c4), Ok(None));
+        assert_eq!(
+            get_cert_rsa_key_bits(&c1[..c1.len() / 2]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    #[test]
+    fn test_case0_is_cert_self_issued() {
+        let ca = std::fs::read("../test_key/ecp384/ca.cert.der").expect("unable to read ca cert!");
+        let leaf = std::fs::read("../test_key/ecp384/end_responder.cert.der")
+            .expect("unable to read leaf cert!");
+
+        assert_eq!(is_cert_self_issued(&ca), Ok(true));
+        assert_eq!(is_cert_self_issued(&leaf), Ok(false));
+        assert_eq!(
+            is_cert_self_issued(&ca[..ca.len() / 4]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+
+    #[test]
+    fn test_case0_get_cert_validity() {
+        let ca = std::fs::read("../test_key/ecp384/ca.cert.der").expect("unable to read ca cert!");
+
+        // 2023-04-03 05:55:53 to 2033-03-31 05:55:53
+        assert_eq!(get_cert_validity(&ca), Ok((1680501353, 1995861353)));
+        assert_eq!(
+            get_cert_validity(&ca[..ca.len() / 4]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+
+        assert_eq!(read_time(b"\x17\x0d491231235959Z"), Ok((2524607999, 15)));
+        assert_eq!(read_time(b"\x18\x0f19700101000000Z"), Ok((0, 17)));
+        assert_eq!(read_time(b"\x17\x0d240229000000Z"), Ok((1709164800, 15)));
+        assert_eq!(
+            read_time(b"\x17\x0d230229000000Z"),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+        assert_eq!(
+            read_time(b"\x17\x0d2301010000000"),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
 }
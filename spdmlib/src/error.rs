@@ -61,6 +61,10 @@ pub enum StatusCodeCore {
     SESSION_TRY_DISCARD_KEY_UPDATE = 17,
 
     // only in Rust-SPDM
+    NONCE_ECHO_PEER = 0xFA,
+    NONCE_REUSE_PEER = 0xFB,
+    CONTENT_CHANGE_UNSUPPORTED_PEER = 0xFC,
+    RESET_REQUIRED_PEER = 0xFD,
     DECODE_AEAD_FAIL = 0xFE,
 }
 
@@ -87,6 +91,10 @@ impl TryFrom<u16> for StatusCodeCore {
             15 => Ok(Self::SESSION_MSG_ERROR),
             16 => Ok(Self::ACQUIRE_FAIL),
             17 => Ok(Self::SESSION_TRY_DISCARD_KEY_UPDATE),
+            0xFA => Ok(Self::NONCE_ECHO_PEER),
+            0xFB => Ok(Self::NONCE_REUSE_PEER),
+            0xFC => Ok(Self::CONTENT_CHANGE_UNSUPPORTED_PEER),
+            0xFD => Ok(Self::RESET_REQUIRED_PEER),
             0xFE => Ok(Self::DECODE_AEAD_FAIL),
             _ => Err(()),
         }
@@ -136,6 +144,8 @@ impl Default for StatusCodeCrypto {
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StatusCodeCertParse {
     INVALID_CERT = 0,
+    WEAK_CERT_KEY = 1,
+    CERT_DIGEST_MISMATCH = 2,
 }
 
 impl TryFrom<u16> for StatusCodeCertParse {
@@ -144,6 +154,8 @@ impl TryFrom<u16> for StatusCodeCertParse {
     fn try_from(value: u16) -> core::result::Result<Self, Self::Error> {
         match value {
             0 => Ok(Self::INVALID_CERT),
+            1 => Ok(Self::WEAK_CERT_KEY),
+            2 => Ok(Self::CERT_DIGEST_MISMATCH),
             _ => Err(()),
         }
     }
@@ -168,6 +180,7 @@ pub enum StatusCodeTransport {
     DECAP_APP_FAIL = 0xFD,
     ENCAP_FAIL = 0xFC,
     ENCAP_APP_FAIL = 0xFB,
+    RECEIVE_TIMEOUT = 0xFA,
 }
 
 impl TryFrom<u16> for StatusCodeTransport {
@@ -181,6 +194,7 @@ impl TryFrom<u16> for StatusCodeTransport {
             0xFD => Ok(Self::DECAP_APP_FAIL),
             0xFC => Ok(Self::ENCAP_FAIL),
             0xFB => Ok(Self::ENCAP_APP_FAIL),
+            0xFA => Ok(Self::RECEIVE_TIMEOUT),
             _ => Err(()),
         }
     }
@@ -199,6 +213,9 @@ impl Default for StatusCodeTransport {
 pub enum StatusCodeMeasCollect {
     MEAS_INVALID_INDEX = 0,
     MEAS_INTERNAL_ERROR = 1,
+    MEAS_SIGNATURE_UNAVAILABLE = 2,
+    MEAS_COUNT_MISMATCH = 3,
+    MEAS_RAW_BIT_STREAM_UNAVAILABLE = 4,
 }
 
 impl TryFrom<u16> for StatusCodeMeasCollect {
@@ -208,6 +225,9 @@ impl TryFrom<u16> for StatusCodeMeasCollect {
         match value {
             0 => Ok(Self::MEAS_INVALID_INDEX),
             1 => Ok(Self::MEAS_INTERNAL_ERROR),
+            2 => Ok(Self::MEAS_SIGNATURE_UNAVAILABLE),
+            3 => Ok(Self::MEAS_COUNT_MISMATCH),
+            4 => Ok(Self::MEAS_RAW_BIT_STREAM_UNAVAILABLE),
             _ => Err(()),
         }
     }
@@ -479,6 +499,34 @@ pub const SPDM_STATUS_RESYNCH_PEER: SpdmStatus = spdm_return_status!(
     StatusCode::CORE(StatusCodeCore::RESYNCH_PEER)
 );
 
+/* Received a ResetRequired error message: the device has to be reset
+ * before the request takes effect. */
+pub const SPDM_STATUS_RESET_REQUIRED_PEER: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::CORE(StatusCodeCore::RESET_REQUIRED_PEER)
+);
+
+/* The peer cannot report measurement content changes, while
+ * `require_content_change_support` asks for it. */
+pub const SPDM_STATUS_CONTENT_CHANGE_UNSUPPORTED_PEER: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::CORE(StatusCodeCore::CONTENT_CHANGE_UNSUPPORTED_PEER)
+);
+
+/* The peer sent a nonce it already sent in a recent response, so its
+ * random number generator cannot be trusted. */
+pub const SPDM_STATUS_NONCE_REUSE_PEER: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::CORE(StatusCodeCore::NONCE_REUSE_PEER)
+);
+
+/* The peer returned the nonce of the request as its own, which does not
+ * show that its signature covers the request nonce. */
+pub const SPDM_STATUS_NONCE_ECHO_PEER: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::CORE(StatusCodeCore::NONCE_ECHO_PEER)
+);
+
 /* Unable to append new data to buffer due to resource exhaustion. */
 pub const SPDM_STATUS_BUFFER_FULL: SpdmStatus = spdm_return_status!(
     StatusSeverity::ERROR,
@@ -555,6 +603,18 @@ pub const SPDM_STATUS_INVALID_CERT: SpdmStatus = spdm_return_status!(
     StatusCode::CERT_PARSE(StatusCodeCertParse::INVALID_CERT)
 );
 
+/*  Certificate is well formed but its public key is below the local policy. */
+pub const SPDM_STATUS_WEAK_CERT_KEY: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::CERT_PARSE(StatusCodeCertParse::WEAK_CERT_KEY)
+);
+
+/*  Certificate chain does not hash to the digest the peer reported for its slot. */
+pub const SPDM_STATUS_CERT_DIGEST_MISMATCH: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::CERT_PARSE(StatusCodeCertParse::CERT_DIGEST_MISMATCH)
+);
+
 /* - Transport Errors - */
 
 /*  Unable to send message to peer. */
@@ -569,6 +629,12 @@ pub const SPDM_STATUS_RECEIVE_FAIL: SpdmStatus = spdm_return_status!(
     StatusCode::TRANSPORT(StatusCodeTransport::RECEIVE_FAIL)
 );
 
+/*  No message arrived from peer within the timeout. */
+pub const SPDM_STATUS_RECEIVE_TIMEOUT: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::TRANSPORT(StatusCodeTransport::RECEIVE_TIMEOUT)
+);
+
 /*  Unable to decap transport buffer. */
 pub const SPDM_STATUS_DECAP_FAIL: SpdmStatus = spdm_return_status!(
     StatusSeverity::ERROR,
@@ -607,6 +673,29 @@ pub const SPDM_STATUS_MEAS_INTERNAL_ERROR: SpdmStatus = spdm_return_status!(
     StatusCode::MEAS_COLLECT(StatusCodeMeasCollect::MEAS_INTERNAL_ERROR)
 );
 
+/*  Unable to get signed measurements: the responder does not advertise
+ *  MEAS_CAP_SIG, the session was established with a PSK and no
+ *  certificate chain of the responder is known, or an L1/L2 transcript
+ *  storage cannot back an SPDM 1.0/1.1 signature. */
+pub const SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::MEAS_COLLECT(StatusCodeMeasCollect::MEAS_SIGNATURE_UNAVAILABLE)
+);
+
+/*  RequestAll returned another number of blocks than QueryTotalNumber
+ *  reported, and the responder did not signal a content change. */
+pub const SPDM_STATUS_MEAS_COUNT_MISMATCH: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::MEAS_COLLECT(StatusCodeMeasCollect::MEAS_COUNT_MISMATCH)
+);
+
+/*  The raw bit stream was requested for a measurement index the responder
+ *  only reports as a digest. */
+pub const SPDM_STATUS_MEAS_RAW_BIT_STREAM_UNAVAILABLE: SpdmStatus = spdm_return_status!(
+    StatusSeverity::ERROR,
+    StatusCode::MEAS_COLLECT(StatusCodeMeasCollect::MEAS_RAW_BIT_STREAM_UNAVAILABLE)
+);
+
 /* - Random Number Generation Errors - */
 
 /*  Unable to produce random number due to lack of entropy. */
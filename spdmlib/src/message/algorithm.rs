@@ -17,6 +17,10 @@ pub struct SpdmNegotiateAlgorithmsRequestPayload {
     pub other_params_support: SpdmOpaqueSupport,
     pub base_asym_algo: SpdmBaseAsymAlgo,
     pub base_hash_algo: SpdmBaseHashAlgo,
+    pub ext_asym_count: u8,
+    pub ext_asym: [SpdmExtAlgStruct; SPDM_MAX_EXT_ALG_COUNT],
+    pub ext_hash_count: u8,
+    pub ext_hash: [SpdmExtAlgStruct; SPDM_MAX_EXT_ALG_COUNT],
     pub alg_struct_count: u8,
     pub alg_struct: [SpdmAlgStruct; 4],
 }
@@ -40,7 +44,7 @@ impl SpdmCodec for SpdmNegotiateAlgorithmsRequestPayload {
 
         cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
 
-        let mut length: u16 = 32;
+        let mut length: u16 = 32 + 4 * (self.ext_asym_count + self.ext_hash_count) as u16;
         if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
             let alg_fixed_count = 2u8;
             length += ((2 + alg_fixed_count) * self.alg_struct_count) as u16;
@@ -73,12 +77,25 @@ impl SpdmCodec for SpdmNegotiateAlgorithmsRequestPayload {
             cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // reserved2
         }
 
-        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // ext_asym_count
+        cnt += self
+            .ext_asym_count
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
 
-        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // ext_hash_count
+        cnt += self
+            .ext_hash_count
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
 
         cnt += 0u16.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // reserved3
 
+        for algo in self.ext_asym.iter().take(self.ext_asym_count as usize) {
+            cnt += algo.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+        for algo in self.ext_hash.iter().take(self.ext_hash_count as usize) {
+            cnt += algo.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+
         if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
             for algo in self.alg_struct.iter().take(self.alg_struct_count as usize) {
                 cnt += algo.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
@@ -91,12 +108,9 @@ impl SpdmCodec for SpdmNegotiateAlgorithmsRequestPayload {
         context: &mut common::SpdmContext,
         r: &mut Reader,
     ) -> Option<SpdmNegotiateAlgorithmsRequestPayload> {
-        let mut alg_struct_count = 0;
+        let mut num_alg_struct = 0;
         if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
-            alg_struct_count = u8::read(r)?; // param1
-            if alg_struct_count > 4 {
-                return None;
-            }
+            num_alg_struct = u8::read(r)?; // param1
         } else {
             u8::read(r)?; // param1
         }
@@ -122,17 +136,25 @@ impl SpdmCodec for SpdmNegotiateAlgorithmsRequestPayload {
         }
 
         let ext_asym_count = u8::read(r)?;
-        if ext_asym_count != 0 {
-            return None;
-        }
-
         let ext_hash_count = u8::read(r)?;
-        if ext_hash_count != 0 {
+        if ext_asym_count as usize + ext_hash_count as usize > SPDM_MAX_EXT_ALG_COUNT {
             return None;
         }
 
         u16::read(r)?; // reserved3
 
+        let mut ext_asym = [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT];
+        for algo in ext_asym.iter_mut().take(ext_asym_count as usize) {
+            *algo = SpdmExtAlgStruct::read(r)?;
+        }
+        let mut ext_hash = [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT];
+        for algo in ext_hash.iter_mut().take(ext_hash_count as usize) {
+            *algo = SpdmExtAlgStruct::read(r)?;
+        }
+
+        // unknown AlgTypes are skipped, so only the known ones are kept.
+        let alg_struct_start = r.used();
+        let mut alg_struct_count = 0u8;
         let mut alg_struct = gen_array_clone(SpdmAlgStruct::default(), 4);
         if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
             let mut dhe_present = false;
@@ -140,8 +162,8 @@ impl SpdmCodec for SpdmNegotiateAlgorithmsRequestPayload {
             let mut req_asym_present = false;
             let mut key_schedule_present = false;
             let mut current_type = SpdmAlgType::Unknown(0);
-            for algo in alg_struct.iter_mut().take(alg_struct_count as usize) {
-                let alg = SpdmAlgStruct::read(r)?;
+            for _i in 0..num_alg_struct {
+                let alg = SpdmAlgStruct::read_tolerant(r)?;
                 if current_type.get_u8() >= alg.alg_type.get_u8() {
                     return None;
                 }
@@ -172,23 +194,21 @@ impl SpdmCodec for SpdmNegotiateAlgorithmsRequestPayload {
                         key_schedule_present = true;
                     }
                     SpdmAlg::SpdmAlgoUnknown(_) => {
-                        return None;
+                        continue;
                     }
                 }
-                *algo = alg;
+                alg_struct[alg_struct_count as usize] = alg;
+                alg_struct_count += 1;
             }
         }
 
         //
         // check length
         //
-        let mut calc_length: u16 = 32;
-        if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
-            let alg_fixed_count = 2u8;
-            calc_length += ((2 + alg_fixed_count) * alg_struct_count) as u16;
-        }
-
-        if length != calc_length {
+        let calc_length = 32
+            + 4 * (ext_asym_count as usize + ext_hash_count as usize)
+            + (r.used() - alg_struct_start);
+        if length as usize != calc_length {
             return None;
         }
 
@@ -197,6 +217,10 @@ impl SpdmCodec for SpdmNegotiateAlgorithmsRequestPayload {
             other_params_support,
             base_asym_algo,
             base_hash_algo,
+            ext_asym_count,
+            ext_asym,
+            ext_hash_count,
+            ext_hash,
             alg_struct_count,
             alg_struct,
         })
@@ -210,6 +234,8 @@ pub struct SpdmAlgorithmsResponsePayload {
     pub measurement_hash_algo: SpdmMeasurementHashAlgo,
     pub base_asym_sel: SpdmBaseAsymAlgo,
     pub base_hash_sel: SpdmBaseHashAlgo,
+    pub ext_asym_sel: Option<SpdmExtAlgStruct>,
+    pub ext_hash_sel: Option<SpdmExtAlgStruct>,
     pub alg_struct_count: u8,
     pub alg_struct: [SpdmAlgStruct; 4],
 }
@@ -233,7 +259,8 @@ impl SpdmCodec for SpdmAlgorithmsResponsePayload {
 
         cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
 
-        let mut length: u16 = 36;
+        let mut length: u16 =
+            36 + 4 * (self.ext_asym_sel.is_some() as u16 + self.ext_hash_sel.is_some() as u16);
         if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
             let alg_fixed_count = 2u8;
             length += ((2 + alg_fixed_count) * self.alg_struct_count) as u16;
@@ -270,12 +297,20 @@ impl SpdmCodec for SpdmAlgorithmsResponsePayload {
             cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // reserved2
         }
 
-        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // ext_asym_count
+        cnt += (self.ext_asym_sel.is_some() as u8)
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // ext_asym_sel_count
 
-        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // ext_hash_count
+        cnt += (self.ext_hash_sel.is_some() as u8)
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // ext_hash_sel_count
 
         cnt += 0u16.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // reserved3
 
+        for algo in self.ext_asym_sel.iter().chain(self.ext_hash_sel.iter()) {
+            cnt += algo.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+
         if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
             for algo in self.alg_struct.iter().take(self.alg_struct_count as usize) {
                 cnt += algo.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
@@ -368,6 +403,41 @@ impl SpdmCodec for SpdmAlgorithmsResponsePayload {
         if !base_asym_sel.is_no_more_than_one_selected() {
             return None;
         }
+
+        let base_hash_sel = SpdmBaseHashAlgo::read(r)?;
+        if !base_hash_sel.is_no_more_than_one_selected() {
+            return None;
+        }
+
+        for _i in 0..12 {
+            u8::read(r)?; // reserved2
+        }
+
+        let ext_asym_sel_count = u8::read(r)?;
+        let ext_hash_sel_count = u8::read(r)?;
+        if ext_asym_sel_count > 1 || ext_hash_sel_count > 1 {
+            return None;
+        }
+
+        u16::read(r)?; // reserved3
+
+        let ext_asym_sel = if ext_asym_sel_count == 1 {
+            Some(SpdmExtAlgStruct::read(r)?)
+        } else {
+            None
+        };
+        let ext_hash_sel = if ext_hash_sel_count == 1 {
+            Some(SpdmExtAlgStruct::read(r)?)
+        } else {
+            None
+        };
+        // a base and an extended selection exclude each other.
+        if (ext_asym_sel.is_some() && !base_asym_sel.is_empty())
+            || (ext_hash_sel.is_some() && !base_hash_sel.is_empty())
+        {
+            return None;
+        }
+
         if (context
             .negotiate_info
             .rsp_capabilities_sel
@@ -389,14 +459,11 @@ impl SpdmCodec for SpdmAlgorithmsResponsePayload {
                     .req_capabilities_sel
                     .contains(SpdmRequestCapabilityFlags::KEY_EX_CAP)))
             && !base_asym_sel.is_valid_one_select()
+            && ext_asym_sel.is_none()
         {
             return None;
         }
 
-        let base_hash_sel = SpdmBaseHashAlgo::read(r)?;
-        if !base_hash_sel.is_no_more_than_one_selected() {
-            return None;
-        }
         if (context
             .negotiate_info
             .rsp_capabilities_sel
@@ -430,26 +497,11 @@ impl SpdmCodec for SpdmAlgorithmsResponsePayload {
                     .req_capabilities_sel
                     .contains(SpdmRequestCapabilityFlags::PSK_CAP)))
             && !base_hash_sel.is_valid_one_select()
+            && ext_hash_sel.is_none()
         {
             return None;
         }
 
-        for _i in 0..12 {
-            u8::read(r)?; // reserved2
-        }
-
-        let ext_asym_count = u8::read(r)?;
-        if ext_asym_count != 0 {
-            return None;
-        }
-
-        let ext_hash_count = u8::read(r)?;
-        if ext_hash_count != 0 {
-            return None;
-        }
-
-        u16::read(r)?; // reserved3
-
         let mut alg_struct = gen_array_clone(SpdmAlgStruct::default(), 4);
         if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
             let mut dhe_present = false;
@@ -580,7 +632,7 @@ impl SpdmCodec for SpdmAlgorithmsResponsePayload {
             }
         }
 
-        let mut calc_length: u16 = 36;
+        let mut calc_length: u16 = 36 + 4 * (ext_asym_sel_count + ext_hash_sel_count) as u16;
         if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
             let alg_fixed_count = 2u8;
             calc_length += ((2 + alg_fixed_count) * alg_struct_count) as u16;
@@ -596,6 +648,8 @@ impl SpdmCodec for SpdmAlgorithmsResponsePayload {
             measurement_hash_algo,
             base_asym_sel,
             base_hash_sel,
+            ext_asym_sel,
+            ext_hash_sel,
             alg_struct_count,
             alg_struct,
         })
@@ -621,6 +675,10 @@ mod tests {
             other_params_support: SpdmOpaqueSupport::empty(),
             base_asym_algo: SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048,
             base_hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+            ext_asym_count: 0,
+            ext_asym: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
+            ext_hash_count: 0,
+            ext_hash: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
             alg_struct_count: 4,
             alg_struct: [
                 SpdmAlgStruct {
@@ -714,6 +772,10 @@ mod tests {
             other_params_support: SpdmOpaqueSupport::empty(),
             base_asym_algo: SpdmBaseAsymAlgo::empty(),
             base_hash_algo: SpdmBaseHashAlgo::empty(),
+            ext_asym_count: 0,
+            ext_asym: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
+            ext_hash_count: 0,
+            ext_hash: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
             alg_struct_count: 0,
             alg_struct: gen_array_clone(SpdmAlgStruct::default(), 4),
         };
@@ -748,6 +810,10 @@ mod tests {
             other_params_support: SpdmOpaqueSupport::empty(),
             base_asym_algo: SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048,
             base_hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+            ext_asym_count: 0,
+            ext_asym: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
+            ext_hash_count: 0,
+            ext_hash: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
             alg_struct_count: 0,
             alg_struct: gen_array_clone(SpdmAlgStruct::default(), 4),
         };
@@ -778,6 +844,8 @@ mod tests {
             measurement_hash_algo: SpdmMeasurementHashAlgo::RAW_BIT_STREAM,
             base_asym_sel: SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048,
             base_hash_sel: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+            ext_asym_sel: None,
+            ext_hash_sel: None,
             alg_struct_count: 4,
             alg_struct: [
                 SpdmAlgStruct {
@@ -881,6 +949,8 @@ mod tests {
             measurement_hash_algo: SpdmMeasurementHashAlgo::RAW_BIT_STREAM,
             base_asym_sel: SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048,
             base_hash_sel: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+            ext_asym_sel: None,
+            ext_hash_sel: None,
             alg_struct_count: 0,
             alg_struct: gen_array_clone(SpdmAlgStruct::default(), 4),
         };
@@ -909,6 +979,8 @@ mod tests {
             measurement_hash_algo: SpdmMeasurementHashAlgo::empty(),
             base_asym_sel: SpdmBaseAsymAlgo::empty(),
             base_hash_sel: SpdmBaseHashAlgo::empty(),
+            ext_asym_sel: None,
+            ext_hash_sel: None,
             alg_struct_count: 0,
             alg_struct: gen_array_clone(SpdmAlgStruct::default(), 4),
         };
@@ -938,6 +1010,47 @@ mod tests {
         assert_eq!(spdm_sturct_data.alg_struct_count, 0);
         assert_eq!(16, reader.left());
     }
+    #[test]
+    fn test_case3_spdm_algorithms_response_payload() {
+        let u8_slice = &mut [0u8; 48];
+        let mut writer = Writer::init(u8_slice);
+        let ext_asym_sel = SpdmExtAlgStruct {
+            registry_id: 0x1,
+            algorithm_id: 0x10,
+        };
+        let mut value = SpdmAlgorithmsResponsePayload {
+            measurement_specification_sel: SpdmMeasurementSpecification::empty(),
+            other_params_selection: SpdmOpaqueSupport::empty(),
+            measurement_hash_algo: SpdmMeasurementHashAlgo::empty(),
+            base_asym_sel: SpdmBaseAsymAlgo::empty(),
+            base_hash_sel: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+            ext_asym_sel: Some(ext_asym_sel),
+            ext_hash_sel: None,
+            alg_struct_count: 0,
+            alg_struct: gen_array_clone(SpdmAlgStruct::default(), 4),
+        };
+
+        create_spdm_context!(context);
+        context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+        context.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CHAL_CAP;
+
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(38));
+        let mut reader = Reader::init(u8_slice);
+        let spdm_sturct_data =
+            SpdmAlgorithmsResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(spdm_sturct_data.base_asym_sel, SpdmBaseAsymAlgo::empty());
+        assert_eq!(spdm_sturct_data.ext_asym_sel, Some(ext_asym_sel));
+        assert_eq!(spdm_sturct_data.ext_hash_sel, None);
+        assert_eq!(10, reader.left());
+
+        // a base and an extended asym selection at once is rejected.
+        value.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048;
+        let u8_slice = &mut [0u8; 48];
+        let mut writer = Writer::init(u8_slice);
+        assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
+        let mut reader = Reader::init(u8_slice);
+        assert!(SpdmAlgorithmsResponsePayload::spdm_read(&mut context, &mut reader).is_none());
+    }
 }
 
 #[cfg(all(test,))]
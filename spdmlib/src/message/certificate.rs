@@ -4,14 +4,26 @@
 
 use crate::common::spdm_codec::SpdmCodec;
 use crate::error::SPDM_STATUS_BUFFER_FULL;
+use crate::protocol::{SpdmCertificateModel, SpdmVersion};
 use crate::{common, error::SpdmStatus};
 use codec::{Codec, Reader, Writer};
 
 pub(crate) const MAX_SPDM_CERT_PORTION_LEN: usize = 512;
 
+bitflags! {
+    /// Param2 of GET_CERTIFICATE, reserved before SPDM 1.3.
+    #[derive(Default)]
+    pub struct SpdmGetCertificateRequestAttributes: u8 {
+        /// Ask for the size of the certificate chain only, returned as
+        /// RemainderLength with an empty portion.
+        const SLOT_SIZE_REQUESTED = 0b0000_0001;
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SpdmGetCertificateRequestPayload {
     pub slot_id: u8,
+    pub request_attribute: SpdmGetCertificateRequestAttributes,
     pub offset: u16,
     pub length: u16,
 }
@@ -19,15 +31,24 @@ pub struct SpdmGetCertificateRequestPayload {
 impl SpdmCodec for SpdmGetCertificateRequestPayload {
     fn spdm_encode(
         &self,
-        _context: &mut common::SpdmContext,
+        context: &mut common::SpdmContext,
         bytes: &mut Writer,
     ) -> Result<usize, SpdmStatus> {
         let mut cnt = 0usize;
+        let request_attribute = if context.negotiate_info.spdm_version_sel.get_u8()
+            >= SpdmVersion::SpdmVersion13.get_u8()
+        {
+            self.request_attribute.bits()
+        } else {
+            0
+        };
         cnt += self
             .slot_id
             .encode(bytes)
             .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
-        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        cnt += request_attribute
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
         cnt += self
             .offset
             .encode(bytes)
@@ -40,16 +61,22 @@ impl SpdmCodec for SpdmGetCertificateRequestPayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        context: &mut common::SpdmContext,
         r: &mut Reader,
     ) -> Option<SpdmGetCertificateRequestPayload> {
-        let slot_id = u8::read(r)?; // param1
-        u8::read(r)?; // param2
+        let mut slot_id = u8::read(r)?; // param1
+        let param2 = u8::read(r)?; // param2
+        let mut request_attribute = SpdmGetCertificateRequestAttributes::empty();
+        if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion13.get_u8() {
+            slot_id &= 0xF;
+            request_attribute = SpdmGetCertificateRequestAttributes::from_bits_truncate(param2);
+        }
         let offset = u16::read(r)?;
         let length = u16::read(r)?;
 
         Some(SpdmGetCertificateRequestPayload {
             slot_id,
+            request_attribute,
             offset,
             length,
         })
@@ -59,6 +86,8 @@ impl SpdmCodec for SpdmGetCertificateRequestPayload {
 #[derive(Debug, Clone)]
 pub struct SpdmCertificateResponsePayload {
     pub slot_id: u8,
+    /// CertInfo, SPDM 1.3 and later.
+    pub cert_model: SpdmCertificateModel,
     pub portion_length: u16,
     pub remainder_length: u16,
     pub cert_chain: [u8; MAX_SPDM_CERT_PORTION_LEN],
@@ -67,6 +96,7 @@ impl Default for SpdmCertificateResponsePayload {
     fn default() -> SpdmCertificateResponsePayload {
         SpdmCertificateResponsePayload {
             slot_id: 0,
+            cert_model: SpdmCertificateModel::default(),
             portion_length: 0,
             remainder_length: 0,
             cert_chain: [0u8; MAX_SPDM_CERT_PORTION_LEN],
@@ -77,15 +107,24 @@ impl Default for SpdmCertificateResponsePayload {
 impl SpdmCodec for SpdmCertificateResponsePayload {
     fn spdm_encode(
         &self,
-        _context: &mut common::SpdmContext,
+        context: &mut common::SpdmContext,
         bytes: &mut Writer,
     ) -> Result<usize, SpdmStatus> {
         let mut cnt = 0usize;
+        let cert_info = if context.negotiate_info.spdm_version_sel.get_u8()
+            >= SpdmVersion::SpdmVersion13.get_u8()
+        {
+            self.cert_model.get_u8() & 0x7
+        } else {
+            0
+        };
         cnt += self
             .slot_id
             .encode(bytes)
             .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
-        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        cnt += cert_info
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
         cnt += self
             .portion_length
             .encode(bytes)
@@ -102,15 +141,21 @@ impl SpdmCodec for SpdmCertificateResponsePayload {
     }
 
     fn spdm_read(
-        _context: &mut common::SpdmContext,
+        context: &mut common::SpdmContext,
         r: &mut Reader,
     ) -> Option<SpdmCertificateResponsePayload> {
-        let slot_id = u8::read(r)?; // param1
-        u8::read(r)?; // param2
+        let mut slot_id = u8::read(r)?; // param1
+        let param2 = u8::read(r)?; // param2
+        let mut cert_model = SpdmCertificateModel::default();
+        if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion13.get_u8() {
+            slot_id &= 0xF;
+            cert_model = SpdmCertificateModel::read_bytes(&[param2 & 0x7])?;
+        }
         let portion_length = u16::read(r)?;
         let remainder_length = u16::read(r)?;
         let mut response = SpdmCertificateResponsePayload {
             slot_id,
+            cert_model,
             portion_length,
             remainder_length,
             ..Default::default()
@@ -181,6 +226,62 @@ mod tests {
             assert_eq!(spdm_get_certificate_request_payload.cert_chain[i], 100u8);
         }
     }
+    #[test]
+    fn test_case1_spdm_certificate_version13_params() {
+        let u8_slice = &mut [0u8; 6 + MAX_SPDM_CERT_PORTION_LEN];
+
+        create_spdm_context!(context);
+        context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+        let request = SpdmGetCertificateRequestPayload {
+            slot_id: 3,
+            request_attribute: SpdmGetCertificateRequestAttributes::SLOT_SIZE_REQUESTED,
+            offset: 0,
+            length: 0,
+        };
+        let mut writer = Writer::init(u8_slice);
+        assert!(request.spdm_encode(&mut context, &mut writer).is_ok());
+        assert_eq!(&u8_slice[..2], &[3, 0]);
+
+        context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion13;
+        let mut writer = Writer::init(u8_slice);
+        assert!(request.spdm_encode(&mut context, &mut writer).is_ok());
+        assert_eq!(&u8_slice[..2], &[3, 1]);
+        // the upper half of param1 is reserved from SPDM 1.3.
+        u8_slice[0] = 0xF3;
+        let mut reader = Reader::init(&u8_slice[..6]);
+        let request =
+            SpdmGetCertificateRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(request.slot_id, 3);
+        assert_eq!(
+            request.request_attribute,
+            SpdmGetCertificateRequestAttributes::SLOT_SIZE_REQUESTED
+        );
+
+        let response = SpdmCertificateResponsePayload {
+            slot_id: 3,
+            cert_model: SpdmCertificateModel::SpdmCertModelAliasCert,
+            remainder_length: 0x400,
+            ..Default::default()
+        };
+        let mut writer = Writer::init(u8_slice);
+        assert!(response.spdm_encode(&mut context, &mut writer).is_ok());
+        assert_eq!(&u8_slice[..2], &[3, 2]);
+        let mut reader = Reader::init(&u8_slice[..6]);
+        let response =
+            SpdmCertificateResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(
+            response.cert_model,
+            SpdmCertificateModel::SpdmCertModelAliasCert
+        );
+        assert_eq!(response.portion_length, 0);
+        assert_eq!(response.remainder_length, 0x400);
+
+        context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+        let mut reader = Reader::init(&u8_slice[..6]);
+        let response =
+            SpdmCertificateResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(response.cert_model, SpdmCertificateModel::SpdmCertModelNone);
+    }
 }
 
 #[cfg(all(test,))]
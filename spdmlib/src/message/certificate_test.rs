@@ -16,6 +16,7 @@ fn test_certificate_struct() {
     let writer = &mut Writer::init(u8_slice);
     let request = SpdmGetCertificateRequestPayload {
         slot_id: 3,
+        request_attribute: SpdmGetCertificateRequestAttributes::empty(),
         offset: 0,
         length: 1024,
     };
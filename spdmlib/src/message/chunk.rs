@@ -0,0 +1,331 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common;
+use crate::common::spdm_codec::SpdmCodec;
+use crate::config;
+use crate::error::{SpdmStatus, SPDM_STATUS_BUFFER_FULL};
+use codec::{Codec, Reader, Writer};
+
+/// SPDMVersion, RequestResponseCode, Param1, Param2, ChunkSeqNo, Reserved
+/// and ChunkSize, the part of CHUNK_SEND and CHUNK_RESPONSE before the
+/// chunk. The first chunk is preceded by LargeMessageSize too.
+pub const SPDM_CHUNK_HEADER_SIZE: usize = 12;
+pub const SPDM_CHUNK_LARGE_MESSAGE_SIZE_SIZE: usize = 4;
+/// SPDMVersion, RequestResponseCode, Param1, Param2 and ChunkSeqNo of
+/// CHUNK_SEND_ACK, followed by the response to the large request.
+pub const SPDM_CHUNK_SEND_ACK_HEADER_SIZE: usize = 6;
+
+bitflags! {
+    #[derive(Default)]
+    pub struct SpdmChunkAttributes: u8 {
+        const LAST_CHUNK = 0b0000_0001;
+    }
+}
+
+impl Codec for SpdmChunkAttributes {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        self.bits().encode(bytes)
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmChunkAttributes> {
+        let bits = u8::read(r)?;
+
+        SpdmChunkAttributes::from_bits(bits & SpdmChunkAttributes::all().bits())
+    }
+}
+
+bitflags! {
+    #[derive(Default)]
+    pub struct SpdmChunkSendAckAttributes: u8 {
+        const EARLY_ERROR_DETECTED = 0b0000_0001;
+    }
+}
+
+impl Codec for SpdmChunkSendAckAttributes {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        self.bits().encode(bytes)
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmChunkSendAckAttributes> {
+        let bits = u8::read(r)?;
+
+        SpdmChunkSendAckAttributes::from_bits(bits & SpdmChunkSendAckAttributes::all().bits())
+    }
+}
+
+/// One chunk of a large message, as carried by CHUNK_SEND and
+/// CHUNK_RESPONSE alike. `large_message_size` is only on the wire in the
+/// first chunk.
+#[derive(Debug, Clone)]
+pub struct SpdmChunkPayload {
+    pub attributes: SpdmChunkAttributes,
+    pub handle: u8,
+    pub chunk_seq_no: u16,
+    pub chunk_size: u32,
+    pub large_message_size: u32,
+    pub chunk: [u8; config::MAX_SPDM_MSG_SIZE],
+}
+
+impl Default for SpdmChunkPayload {
+    fn default() -> SpdmChunkPayload {
+        SpdmChunkPayload {
+            attributes: SpdmChunkAttributes::empty(),
+            handle: 0,
+            chunk_seq_no: 0,
+            chunk_size: 0,
+            large_message_size: 0,
+            chunk: [0u8; config::MAX_SPDM_MSG_SIZE],
+        }
+    }
+}
+
+impl SpdmCodec for SpdmChunkPayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        let mut cnt = 0usize;
+        cnt += self
+            .attributes
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        cnt += self
+            .handle
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        cnt += self
+            .chunk_seq_no
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        cnt += 0u16.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // reserved
+        cnt += self
+            .chunk_size
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        if self.chunk_seq_no == 0 {
+            cnt += self
+                .large_message_size
+                .encode(bytes)
+                .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+
+        for d in self.chunk.iter().take(self.chunk_size as usize) {
+            cnt += d.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+        Ok(cnt)
+    }
+
+    fn spdm_read(_context: &mut common::SpdmContext, r: &mut Reader) -> Option<SpdmChunkPayload> {
+        let attributes = SpdmChunkAttributes::read(r)?; // param1
+        let handle = u8::read(r)?; // param2
+        let chunk_seq_no = u16::read(r)?;
+        u16::read(r)?; // reserved
+        let chunk_size = u32::read(r)?;
+        if chunk_size as usize > config::MAX_SPDM_MSG_SIZE {
+            return None;
+        }
+        let large_message_size = if chunk_seq_no == 0 { u32::read(r)? } else { 0 };
+        let mut payload = SpdmChunkPayload {
+            attributes,
+            handle,
+            chunk_seq_no,
+            chunk_size,
+            large_message_size,
+            ..Default::default()
+        };
+
+        for data in payload.chunk.iter_mut().take(chunk_size as usize) {
+            *data = u8::read(r)?;
+        }
+        Some(payload)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SpdmChunkGetRequestPayload {
+    pub handle: u8,
+    pub chunk_seq_no: u16,
+}
+
+impl SpdmCodec for SpdmChunkGetRequestPayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        let mut cnt = 0usize;
+        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        cnt += self
+            .handle
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        cnt += self
+            .chunk_seq_no
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        Ok(cnt)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmChunkGetRequestPayload> {
+        u8::read(r)?; // param1
+        let handle = u8::read(r)?; // param2
+        let chunk_seq_no = u16::read(r)?;
+
+        Some(SpdmChunkGetRequestPayload {
+            handle,
+            chunk_seq_no,
+        })
+    }
+}
+
+/// CHUNK_SEND_ACK. `response` holds the response to the large request
+/// once its last chunk arrived, or an ERROR after an early error.
+#[derive(Debug, Clone)]
+pub struct SpdmChunkSendAckResponsePayload {
+    pub attributes: SpdmChunkSendAckAttributes,
+    pub handle: u8,
+    pub chunk_seq_no: u16,
+    pub response_size: u32,
+    pub response: [u8; config::MAX_SPDM_MSG_SIZE],
+}
+
+impl Default for SpdmChunkSendAckResponsePayload {
+    fn default() -> SpdmChunkSendAckResponsePayload {
+        SpdmChunkSendAckResponsePayload {
+            attributes: SpdmChunkSendAckAttributes::empty(),
+            handle: 0,
+            chunk_seq_no: 0,
+            response_size: 0,
+            response: [0u8; config::MAX_SPDM_MSG_SIZE],
+        }
+    }
+}
+
+impl SpdmCodec for SpdmChunkSendAckResponsePayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        let mut cnt = 0usize;
+        cnt += self
+            .attributes
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        cnt += self
+            .handle
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        cnt += self
+            .chunk_seq_no
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+
+        for d in self.response.iter().take(self.response_size as usize) {
+            cnt += d.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+        Ok(cnt)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmChunkSendAckResponsePayload> {
+        let attributes = SpdmChunkSendAckAttributes::read(r)?; // param1
+        let handle = u8::read(r)?; // param2
+        let chunk_seq_no = u16::read(r)?;
+
+        // the response takes the rest of the message.
+        let rest = r.take(r.left())?;
+        if rest.len() > config::MAX_SPDM_MSG_SIZE {
+            return None;
+        }
+        let mut payload = SpdmChunkSendAckResponsePayload {
+            attributes,
+            handle,
+            chunk_seq_no,
+            response_size: rest.len() as u32,
+            ..Default::default()
+        };
+        payload.response[..rest.len()].copy_from_slice(rest);
+        Some(payload)
+    }
+}
+
+#[cfg(all(test,))]
+#[path = "mod_test.common.inc.rs"]
+mod testlib;
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::common::{SpdmConfigInfo, SpdmContext, SpdmProvisionInfo};
+    use testlib::{create_spdm_context, DeviceIO, TransportEncap};
+
+    #[test]
+    fn test_case0_spdm_chunk_payload() {
+        let u8_slice = &mut [0u8; 32];
+        create_spdm_context!(context);
+
+        // the first chunk carries LargeMessageSize.
+        let mut value = SpdmChunkPayload {
+            handle: 7,
+            chunk_size: 3,
+            large_message_size: 100,
+            ..Default::default()
+        };
+        value.chunk[..3].copy_from_slice(&[1, 2, 3]);
+        let mut writer = Writer::init(u8_slice);
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(17));
+        let mut reader = Reader::init(&u8_slice[..17]);
+        let chunk = SpdmChunkPayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(chunk.handle, 7);
+        assert_eq!(chunk.large_message_size, 100);
+        assert_eq!(&chunk.chunk[..chunk.chunk_size as usize], &[1, 2, 3]);
+
+        // later ones do not.
+        value.chunk_seq_no = 1;
+        value.attributes = SpdmChunkAttributes::LAST_CHUNK;
+        let mut writer = Writer::init(u8_slice);
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(13));
+        let mut reader = Reader::init(&u8_slice[..13]);
+        let chunk = SpdmChunkPayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(chunk.attributes, SpdmChunkAttributes::LAST_CHUNK);
+        assert_eq!(chunk.large_message_size, 0);
+        assert_eq!(&chunk.chunk[..chunk.chunk_size as usize], &[1, 2, 3]);
+
+        // a chunk cut short.
+        let mut reader = Reader::init(&u8_slice[..12]);
+        assert!(SpdmChunkPayload::spdm_read(&mut context, &mut reader).is_none());
+    }
+
+    #[test]
+    fn test_case0_spdm_chunk_send_ack_response_payload() {
+        let u8_slice = &mut [0u8; 16];
+        create_spdm_context!(context);
+
+        let mut value = SpdmChunkSendAckResponsePayload {
+            handle: 3,
+            chunk_seq_no: 2,
+            response_size: 4,
+            ..Default::default()
+        };
+        value.response[..4].copy_from_slice(&[0x12, 0x60, 0, 0]);
+        let mut writer = Writer::init(u8_slice);
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(8));
+        let mut reader = Reader::init(&u8_slice[..8]);
+        let ack = SpdmChunkSendAckResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(ack.handle, 3);
+        assert_eq!(ack.chunk_seq_no, 2);
+        assert_eq!(
+            &ack.response[..ack.response_size as usize],
+            &[0x12, 0x60, 0, 0]
+        );
+    }
+}
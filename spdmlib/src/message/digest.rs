@@ -5,7 +5,7 @@
 use crate::common;
 use crate::common::spdm_codec::SpdmCodec;
 use crate::error::{SpdmStatus, SPDM_STATUS_BUFFER_FULL};
-use crate::protocol::{gen_array_clone, SpdmDigestStruct, SPDM_MAX_SLOT_NUMBER};
+use crate::protocol::{gen_array_clone, SpdmDigestStruct, SpdmKeyUsageMask, SPDM_MAX_SLOT_NUMBER};
 use codec::{Codec, Reader, Writer};
 
 #[derive(Debug, Clone, Default)]
@@ -38,6 +38,10 @@ impl SpdmCodec for SpdmGetDigestsRequestPayload {
 pub struct SpdmDigestsResponsePayload {
     pub slot_mask: u8,
     pub digests: [SpdmDigestStruct; SPDM_MAX_SLOT_NUMBER],
+    // packed like digests, only on the wire with MULTI_KEY_CONN_RSP (SPDM 1.3)
+    pub key_pair_id: [u8; SPDM_MAX_SLOT_NUMBER],
+    pub certificate_info: [u8; SPDM_MAX_SLOT_NUMBER],
+    pub key_usage_mask: [SpdmKeyUsageMask; SPDM_MAX_SLOT_NUMBER],
 }
 
 impl SpdmCodec for SpdmDigestsResponsePayload {
@@ -63,6 +67,23 @@ impl SpdmCodec for SpdmDigestsResponsePayload {
         for digest in self.digests.iter().take(count as usize) {
             cnt += digest.spdm_encode(context, bytes)?;
         }
+        if context.negotiate_info.multi_key_conn_rsp() {
+            for key_pair_id in self.key_pair_id.iter().take(count as usize) {
+                cnt += key_pair_id
+                    .encode(bytes)
+                    .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+            }
+            for certificate_info in self.certificate_info.iter().take(count as usize) {
+                cnt += certificate_info
+                    .encode(bytes)
+                    .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+            }
+            for key_usage_mask in self.key_usage_mask.iter().take(count as usize) {
+                cnt += key_usage_mask
+                    .encode(bytes)
+                    .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+            }
+        }
         Ok(cnt)
     }
 
@@ -84,7 +105,29 @@ impl SpdmCodec for SpdmDigestsResponsePayload {
         for digest in digests.iter_mut().take(slot_count as usize) {
             *digest = SpdmDigestStruct::spdm_read(context, r)?;
         }
-        Some(SpdmDigestsResponsePayload { slot_mask, digests })
+
+        let mut key_pair_id = [0u8; SPDM_MAX_SLOT_NUMBER];
+        let mut certificate_info = [0u8; SPDM_MAX_SLOT_NUMBER];
+        let mut key_usage_mask = [SpdmKeyUsageMask::default(); SPDM_MAX_SLOT_NUMBER];
+        if context.negotiate_info.multi_key_conn_rsp() {
+            for id in key_pair_id.iter_mut().take(slot_count as usize) {
+                *id = u8::read(r)?;
+            }
+            for info in certificate_info.iter_mut().take(slot_count as usize) {
+                *info = u8::read(r)?;
+            }
+            for mask in key_usage_mask.iter_mut().take(slot_count as usize) {
+                *mask = SpdmKeyUsageMask::read(r)?;
+            }
+        }
+
+        Some(SpdmDigestsResponsePayload {
+            slot_mask,
+            digests,
+            key_pair_id,
+            certificate_info,
+            key_usage_mask,
+        })
     }
 }
 
@@ -113,6 +156,7 @@ mod tests {
                 },
                 SPDM_MAX_SLOT_NUMBER,
             ),
+            ..Default::default()
         };
         for i in 0..SPDM_MAX_SLOT_NUMBER {
             for j in 0..SPDM_MAX_HASH_SIZE {
@@ -169,6 +213,53 @@ mod tests {
         assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
     }
     #[test]
+    fn test_case2_spdm_digests_response_payload_multi_key() {
+        let u8_slice = &mut [0u8; 2 + 2 * (SHA384_DIGEST_SIZE + 4)];
+        let mut writer = Writer::init(u8_slice);
+
+        let mut value = SpdmDigestsResponsePayload {
+            slot_mask: 0b00000101,
+            digests: gen_array_clone(
+                SpdmDigestStruct {
+                    data_size: SHA384_DIGEST_SIZE as u16,
+                    data: Box::new([0x5au8; SPDM_MAX_HASH_SIZE]),
+                },
+                SPDM_MAX_SLOT_NUMBER,
+            ),
+            ..Default::default()
+        };
+        value.key_pair_id[..2].copy_from_slice(&[1, 3]);
+        value.key_usage_mask[0] = SpdmKeyUsageMask::KEY_EX_USE;
+        value.key_usage_mask[1] =
+            SpdmKeyUsageMask::CHALLENGE_USE | SpdmKeyUsageMask::MEASUREMENT_USE;
+
+        create_spdm_context!(context);
+
+        context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion13;
+        context.negotiate_info.rsp_capabilities_sel =
+            SpdmResponseCapabilityFlags::MULTI_KEY_CAP_ONLY;
+        context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+
+        assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
+        assert_eq!(0, writer.left());
+        let mut reader = Reader::init(u8_slice);
+        let payload = SpdmDigestsResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(0, reader.left());
+        assert_eq!(payload.key_pair_id[..2], [1, 3]);
+        assert_eq!(payload.key_usage_mask[0], SpdmKeyUsageMask::KEY_EX_USE);
+        assert_eq!(
+            payload.key_usage_mask[1],
+            SpdmKeyUsageMask::CHALLENGE_USE | SpdmKeyUsageMask::MEASUREMENT_USE
+        );
+
+        // without MULTI_KEY_CAP_ONLY the tail is left unread.
+        context.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::empty();
+        let mut reader = Reader::init(u8_slice);
+        let payload = SpdmDigestsResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(8, reader.left());
+        assert_eq!(payload.key_usage_mask[0], SpdmKeyUsageMask::empty());
+    }
+    #[test]
     fn test_case0_spdm_get_digests_request_payload() {
         let u8_slice = &mut [0u8; 2];
         let mut writer = Writer::init(u8_slice);
@@ -179,11 +179,38 @@ impl SpdmCodec for SpdmErrorResponseVendorExtData {
     }
 }
 
+/// Handle of the large response to fetch with CHUNK_GET.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpdmErrorResponseLargeResponseExtData {
+    pub handle: u8,
+}
+
+impl SpdmCodec for SpdmErrorResponseLargeResponseExtData {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        self.handle
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmErrorResponseLargeResponseExtData> {
+        let handle = u8::read(r)?;
+        Some(SpdmErrorResponseLargeResponseExtData { handle })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SpdmErrorResponseExtData {
     SpdmErrorExtDataNone(SpdmErrorResponseNoneExtData),
     SpdmErrorExtDataNotReady(SpdmErrorResponseNotReadyExtData),
     SpdmErrorExtDataVendorDefined(SpdmErrorResponseVendorExtData),
+    SpdmErrorExtDataLargeResponse(SpdmErrorResponseLargeResponseExtData),
 }
 impl Default for SpdmErrorResponseExtData {
     fn default() -> SpdmErrorResponseExtData {
@@ -224,6 +251,9 @@ impl SpdmCodec for SpdmErrorResponsePayload {
             SpdmErrorResponseExtData::SpdmErrorExtDataNone(extended_data) => {
                 cnt += extended_data.spdm_encode(context, bytes)?;
             }
+            SpdmErrorResponseExtData::SpdmErrorExtDataLargeResponse(extended_data) => {
+                cnt += extended_data.spdm_encode(context, bytes)?;
+            }
         }
 
         Ok(cnt)
@@ -247,6 +277,11 @@ impl SpdmCodec for SpdmErrorResponsePayload {
                     SpdmErrorResponseVendorExtData::spdm_read(context, r)?,
                 ))
             }
+            SpdmErrorCode::SpdmErrorLargeResponse => {
+                Some(SpdmErrorResponseExtData::SpdmErrorExtDataLargeResponse(
+                    SpdmErrorResponseLargeResponseExtData::spdm_read(context, r)?,
+                ))
+            }
             _ => Some(SpdmErrorResponseExtData::SpdmErrorExtDataNone(
                 SpdmErrorResponseNoneExtData::spdm_read(context, r)?,
             )),
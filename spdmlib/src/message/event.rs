@@ -0,0 +1,351 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common;
+use crate::common::spdm_codec::SpdmCodec;
+use crate::error::{SpdmStatus, SPDM_STATUS_BUFFER_FULL};
+use codec::{Codec, Reader, Writer};
+
+pub const MAX_SPDM_EVENT_LIST_SIZE: usize = 0x100;
+
+/// Opaque event group or event list, prefixed by its 4 byte length.
+#[derive(Debug, Clone)]
+pub struct SpdmEventListStruct {
+    pub data_size: u32,
+    pub data: [u8; MAX_SPDM_EVENT_LIST_SIZE],
+}
+
+impl Default for SpdmEventListStruct {
+    fn default() -> SpdmEventListStruct {
+        SpdmEventListStruct {
+            data_size: 0,
+            data: [0u8; MAX_SPDM_EVENT_LIST_SIZE],
+        }
+    }
+}
+
+impl SpdmEventListStruct {
+    pub fn from_slice(list: &[u8]) -> Option<SpdmEventListStruct> {
+        if list.len() > MAX_SPDM_EVENT_LIST_SIZE {
+            return None;
+        }
+        let mut event_list = SpdmEventListStruct {
+            data_size: list.len() as u32,
+            ..Default::default()
+        };
+        event_list.data[..list.len()].copy_from_slice(list);
+        Some(event_list)
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data[..self.data_size as usize]
+    }
+}
+
+impl Codec for SpdmEventListStruct {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.data_size.encode(bytes)?;
+        for d in self.data.iter().take(self.data_size as usize) {
+            cnt += d.encode(bytes)?;
+        }
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmEventListStruct> {
+        let data_size = u32::read(r)?;
+        if data_size as usize > MAX_SPDM_EVENT_LIST_SIZE {
+            return None;
+        }
+        let mut data = [0u8; MAX_SPDM_EVENT_LIST_SIZE];
+        for d in data.iter_mut().take(data_size as usize) {
+            *d = u8::read(r)?;
+        }
+        Some(SpdmEventListStruct { data_size, data })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SpdmGetSupportedEventTypesRequestPayload {}
+
+impl SpdmCodec for SpdmGetSupportedEventTypesRequestPayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        Ok(2)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmGetSupportedEventTypesRequestPayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+
+        Some(SpdmGetSupportedEventTypesRequestPayload {})
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SpdmSupportedEventTypesResponsePayload {
+    pub supported_event_groups_count: u8,
+    pub supported_event_groups_list: SpdmEventListStruct,
+}
+
+impl SpdmCodec for SpdmSupportedEventTypesResponsePayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        let mut cnt = 0usize;
+        cnt += self
+            .supported_event_groups_count
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        cnt += self
+            .supported_event_groups_list
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        Ok(cnt)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmSupportedEventTypesResponsePayload> {
+        let supported_event_groups_count = u8::read(r)?; // param1
+        u8::read(r)?; // param2
+        let supported_event_groups_list = SpdmEventListStruct::read(r)?;
+
+        Some(SpdmSupportedEventTypesResponsePayload {
+            supported_event_groups_count,
+            supported_event_groups_list,
+        })
+    }
+}
+
+/// A subscribe_event_group_count of zero clears every subscription, in which
+/// case the subscribe list is absent from the message.
+#[derive(Debug, Clone, Default)]
+pub struct SpdmSubscribeEventTypesRequestPayload {
+    pub subscribe_event_group_count: u8,
+    pub subscribe_list: SpdmEventListStruct,
+}
+
+impl SpdmCodec for SpdmSubscribeEventTypesRequestPayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        let mut cnt = 0usize;
+        cnt += self
+            .subscribe_event_group_count
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        if self.subscribe_event_group_count != 0 {
+            cnt += self
+                .subscribe_list
+                .encode(bytes)
+                .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+        Ok(cnt)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmSubscribeEventTypesRequestPayload> {
+        let subscribe_event_group_count = u8::read(r)?; // param1
+        u8::read(r)?; // param2
+        let subscribe_list = if subscribe_event_group_count != 0 {
+            SpdmEventListStruct::read(r)?
+        } else {
+            SpdmEventListStruct::default()
+        };
+
+        Some(SpdmSubscribeEventTypesRequestPayload {
+            subscribe_event_group_count,
+            subscribe_list,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SpdmSubscribeEventTypesAckResponsePayload {}
+
+impl SpdmCodec for SpdmSubscribeEventTypesAckResponsePayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        Ok(2)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmSubscribeEventTypesAckResponsePayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+
+        Some(SpdmSubscribeEventTypesAckResponsePayload {})
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SpdmSendEventRequestPayload {
+    pub event_count: u32,
+    pub event_list: SpdmEventListStruct,
+}
+
+impl SpdmCodec for SpdmSendEventRequestPayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        let mut cnt = 0usize;
+        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        cnt += self
+            .event_count
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        for d in self
+            .event_list
+            .data
+            .iter()
+            .take(self.event_list.data_size as usize)
+        {
+            cnt += d.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+        Ok(cnt)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmSendEventRequestPayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+        let event_count = u32::read(r)?;
+        // The event list runs to the end of the message.
+        let event_list = SpdmEventListStruct::from_slice(r.rest())?;
+
+        Some(SpdmSendEventRequestPayload {
+            event_count,
+            event_list,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SpdmEventAckResponsePayload {}
+
+impl SpdmCodec for SpdmEventAckResponsePayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        Ok(2)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmEventAckResponsePayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+
+        Some(SpdmEventAckResponsePayload {})
+    }
+}
+
+#[cfg(all(test,))]
+#[path = "mod_test.common.inc.rs"]
+mod testlib;
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::common::{SpdmConfigInfo, SpdmContext, SpdmProvisionInfo};
+    use testlib::{create_spdm_context, DeviceIO, TransportEncap};
+
+    #[test]
+    fn test_case0_spdm_supported_event_types_response_payload() {
+        let u8_slice = &mut [0u8; 16];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmSupportedEventTypesResponsePayload {
+            supported_event_groups_count: 1,
+            supported_event_groups_list: SpdmEventListStruct::from_slice(&[1, 2, 3, 4]).unwrap(),
+        };
+
+        create_spdm_context!(context);
+
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(10));
+        let mut reader = Reader::init(u8_slice);
+        let ret =
+            SpdmSupportedEventTypesResponsePayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(ret.supported_event_groups_count, 1);
+        assert_eq!(ret.supported_event_groups_list.as_slice(), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_case0_spdm_subscribe_event_types_request_payload() {
+        create_spdm_context!(context);
+
+        // An empty subscription carries no list.
+        let u8_slice = &mut [0u8; 16];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmSubscribeEventTypesRequestPayload::default();
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(2));
+
+        let u8_slice = &mut [0u8; 16];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmSubscribeEventTypesRequestPayload {
+            subscribe_event_group_count: 1,
+            subscribe_list: SpdmEventListStruct::from_slice(&[5, 6]).unwrap(),
+        };
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(8));
+        let mut reader = Reader::init(&u8_slice[..8]);
+        let ret =
+            SpdmSubscribeEventTypesRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(ret.subscribe_event_group_count, 1);
+        assert_eq!(ret.subscribe_list.as_slice(), &[5, 6]);
+        assert_eq!(reader.left(), 0);
+    }
+
+    #[test]
+    fn test_case0_spdm_send_event_request_payload() {
+        let u8_slice = &mut [0u8; 16];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmSendEventRequestPayload {
+            event_count: 2,
+            event_list: SpdmEventListStruct::from_slice(&[7, 8, 9]).unwrap(),
+        };
+
+        create_spdm_context!(context);
+
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(9));
+        let mut reader = Reader::init(&u8_slice[..9]);
+        let ret = SpdmSendEventRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(ret.event_count, 2);
+        assert_eq!(ret.event_list.as_slice(), &[7, 8, 9]);
+        assert_eq!(reader.left(), 0);
+    }
+}
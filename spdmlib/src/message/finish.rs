@@ -5,9 +5,7 @@
 use crate::common;
 use crate::common::spdm_codec::SpdmCodec;
 use crate::error::{SpdmStatus, SPDM_STATUS_BUFFER_FULL};
-use crate::protocol::{
-    SpdmDigestStruct, SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags, SpdmSignatureStruct,
-};
+use crate::protocol::{SpdmDigestStruct, SpdmSignatureStruct};
 use codec::{Codec, Reader, Writer};
 
 bitflags! {
@@ -97,14 +95,7 @@ impl SpdmCodec for SpdmFinishResponsePayload {
         let mut cnt = 0usize;
         cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
         cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
-        let in_clear_text = context
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && context
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+        let in_clear_text = context.negotiate_info.handshake_in_the_clear();
         if in_clear_text {
             cnt += self.verify_data.spdm_encode(context, bytes)?;
         }
@@ -118,14 +109,7 @@ impl SpdmCodec for SpdmFinishResponsePayload {
         u8::read(r)?; // param1
         u8::read(r)?; // param2
 
-        let in_clear_text = context
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && context
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+        let in_clear_text = context.negotiate_info.handshake_in_the_clear();
 
         let verify_data = if in_clear_text {
             SpdmDigestStruct::spdm_read(context, r)?
@@ -5,7 +5,9 @@
 use super::*;
 use crate::{
     common::{SpdmCodec, SpdmConfigInfo, SpdmContext, SpdmProvisionInfo},
-    protocol::{SpdmBaseAsymAlgo, SpdmBaseHashAlgo},
+    protocol::{
+        SpdmBaseAsymAlgo, SpdmBaseHashAlgo, SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags,
+    },
 };
 use testlib::{create_spdm_context, DeviceIO, TransportEncap};
 
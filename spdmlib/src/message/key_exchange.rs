@@ -8,7 +8,7 @@ use crate::common::spdm_codec::SpdmCodec;
 use crate::error::{SpdmStatus, SPDM_STATUS_BUFFER_FULL};
 use crate::protocol::{
     SpdmDheExchangeStruct, SpdmDigestStruct, SpdmMeasurementSummaryHashType, SpdmRandomStruct,
-    SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags, SpdmSignatureStruct,
+    SpdmResponseCapabilityFlags, SpdmSignatureStruct,
 };
 use codec::{Codec, Reader, Writer};
 
@@ -183,14 +183,7 @@ impl SpdmCodec for SpdmKeyExchangeResponsePayload {
         cnt += self.opaque.spdm_encode(context, bytes)?;
         cnt += self.signature.spdm_encode(context, bytes)?;
 
-        let in_clear_text = context
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && context
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+        let in_clear_text = context.negotiate_info.handshake_in_the_clear();
         if !in_clear_text {
             cnt += self.verify_data.spdm_encode(context, bytes)?;
         }
@@ -225,14 +218,7 @@ impl SpdmCodec for SpdmKeyExchangeResponsePayload {
         };
         let opaque = SpdmOpaqueStruct::spdm_read(context, r)?;
         let signature = SpdmSignatureStruct::spdm_read(context, r)?;
-        let in_clear_text = context
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && context
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+        let in_clear_text = context.negotiate_info.handshake_in_the_clear();
         let verify_data = if !in_clear_text {
             SpdmDigestStruct::spdm_read(context, r)?
         } else {
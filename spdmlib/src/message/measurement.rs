@@ -6,7 +6,9 @@ use crate::common;
 use crate::common::opaque::SpdmOpaqueStruct;
 use crate::common::spdm_codec::SpdmCodec;
 use crate::error::{SpdmStatus, SPDM_STATUS_BUFFER_FULL};
-use crate::protocol::{SpdmMeasurementRecordStructure, SpdmNonceStruct, SpdmSignatureStruct};
+use crate::protocol::{
+    SpdmMeasurementRecordStructure, SpdmNonceStruct, SpdmSignatureStruct, SPDM_NONCE_SIZE,
+};
 use codec::enum_builder;
 use codec::{Codec, Reader, Writer};
 
@@ -74,14 +76,19 @@ impl SpdmCodec for SpdmGetMeasurementsRequestPayload {
             .measurement_operation
             .encode(bytes)
             .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
-        if self
+        let signature_requested = self
             .measurement_attributes
-            .contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED)
-        {
-            cnt += self
-                .nonce
-                .encode(bytes)
-                .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+            .contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED);
+        // without a signature the spec omits Nonce and SlotIDParam, some
+        // responders only take the request at its signed length though.
+        if signature_requested || context.config_info.fixed_length_measurement_request {
+            let zero_nonce = SpdmNonceStruct::default();
+            let nonce = if signature_requested {
+                &self.nonce
+            } else {
+                &zero_nonce
+            };
+            cnt += nonce.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
             if context.negotiate_info.spdm_version_sel.get_u8()
                 >= SpdmVersion::SpdmVersion11.get_u8()
             {
@@ -157,23 +164,11 @@ impl SpdmCodec for SpdmMeasurementsResponsePayload {
                 .encode(bytes)
                 .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
         }
-        if context.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion12.get_u8()
-            && context.runtime_info.need_measurement_signature
-        {
-            cnt += (self.slot_id | self.content_changed.bits())
-                .encode(bytes)
-                .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
-        } else if context.negotiate_info.spdm_version_sel.get_u8()
-            >= SpdmVersion::SpdmVersion11.get_u8()
-            && context.runtime_info.need_measurement_signature
-        {
-            cnt += self
-                .slot_id
-                .encode(bytes)
-                .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param 2
-        } else {
-            cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param 2
-        }
+        let (slot_id_mask, content_changed_mask) = measurements_response_param2_mask(context);
+        cnt += ((self.slot_id & slot_id_mask)
+            | (self.content_changed.bits() & content_changed_mask))
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
         cnt += self.measurement_record.spdm_encode(context, bytes)?;
         cnt += self
             .nonce
@@ -192,8 +187,15 @@ impl SpdmCodec for SpdmMeasurementsResponsePayload {
     ) -> Option<SpdmMeasurementsResponsePayload> {
         let number_of_measurement = u8::read(r)?; // param1
         let param2 = u8::read(r)?; // param2
-        let slot_id = param2 & MEASUREMENT_RESPONDER_PARAM2_SLOT_ID_MASK; // Bit [3:0]
-        let content_changed = param2 & MEASUREMENT_RESPONDER_PARAM2_CONTENT_CHANGED_MASK; // Bit [5:4]
+        let (slot_id_mask, content_changed_mask) = measurements_response_param2_mask(context);
+        if param2 & !(slot_id_mask | content_changed_mask) != 0 {
+            return None;
+        }
+        let slot_id = param2 & slot_id_mask; // Bit [3:0]
+        let content_changed = param2 & content_changed_mask; // Bit [5:4]
+        if content_changed == MEASUREMENT_RESPONDER_PARAM2_CONTENT_CHANGED_MASK {
+            return None;
+        }
         let content_changed = SpdmMeasurementContentChanged::from_bits(content_changed)?;
         let measurement_record = SpdmMeasurementRecordStructure::spdm_read(context, r)?;
         let nonce = SpdmNonceStruct::read(r)?;
@@ -215,6 +217,41 @@ impl SpdmCodec for SpdmMeasurementsResponsePayload {
     }
 }
 
+impl SpdmMeasurementsResponsePayload {
+    /// Offset of the signature from the start of the payload, i.e. the
+    /// number of payload bytes (param1 through opaque data) the signature
+    /// covers. It is derived from the decoded fields so it holds whatever
+    /// the record and opaque lengths are.
+    pub fn signature_offset(&self) -> usize {
+        2 // param1, param2
+            + 4 // number_of_blocks, measurement_record_length
+            + self.measurement_record.measurement_record_length.get() as usize
+            + SPDM_NONCE_SIZE
+            + 2 // opaque_length
+            + self.opaque.data_size as usize
+    }
+}
+
+// Param2 of MEASUREMENTS only carries the slot id (1.1) and the content
+// changed field (1.2) when a signature was requested, the rest is reserved.
+fn measurements_response_param2_mask(context: &common::SpdmContext) -> (u8, u8) {
+    if !context.runtime_info.need_measurement_signature {
+        return (0, 0);
+    }
+    let spdm_version_sel = context.negotiate_info.spdm_version_sel.get_u8();
+    let slot_id_mask = if spdm_version_sel >= SpdmVersion::SpdmVersion11.get_u8() {
+        MEASUREMENT_RESPONDER_PARAM2_SLOT_ID_MASK
+    } else {
+        0
+    };
+    let content_changed_mask = if spdm_version_sel >= SpdmVersion::SpdmVersion12.get_u8() {
+        MEASUREMENT_RESPONDER_PARAM2_CONTENT_CHANGED_MASK
+    } else {
+        0
+    };
+    (slot_id_mask, content_changed_mask)
+}
+
 #[cfg(all(test,))]
 #[path = "mod_test.common.inc.rs"]
 mod testlib;
@@ -314,6 +351,44 @@ mod tests {
         assert_eq!(0, reader.left());
     }
     #[test]
+    fn test_case2_spdm_get_measurements_request_payload_fixed_length() {
+        let value = SpdmGetMeasurementsRequestPayload {
+            measurement_attributes: SpdmMeasurementAttributes::empty(),
+            measurement_operation: SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            nonce: SpdmNonceStruct {
+                data: [100u8; SPDM_NONCE_SIZE],
+            },
+            slot_id: 0x7,
+        };
+
+        create_spdm_context!(context);
+        context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+
+        let u8_slice = &mut [0u8; 2 + SPDM_NONCE_SIZE + 1];
+        let mut writer = Writer::init(u8_slice);
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(2));
+
+        context.config_info.fixed_length_measurement_request = true;
+        let mut writer = Writer::init(u8_slice);
+        assert_eq!(
+            value.spdm_encode(&mut context, &mut writer),
+            Ok(2 + SPDM_NONCE_SIZE + 1)
+        );
+        assert_eq!(&u8_slice[2..2 + SPDM_NONCE_SIZE], &[0u8; SPDM_NONCE_SIZE]);
+        assert_eq!(u8_slice[2 + SPDM_NONCE_SIZE], 0x7);
+
+        // the responder takes what the spec defines and leaves the rest.
+        let mut reader = Reader::init(u8_slice);
+        let get_measurements =
+            SpdmGetMeasurementsRequestPayload::spdm_read(&mut context, &mut reader).unwrap();
+        assert_eq!(
+            get_measurements.measurement_operation,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+        );
+        assert_eq!(get_measurements.slot_id, 0);
+        assert_eq!(SPDM_NONCE_SIZE + 1, reader.left());
+    }
+    #[test]
     fn test_case0_spdm_measurements_response_payload() {
         create_spdm_context!(context);
 
@@ -440,6 +515,60 @@ mod tests {
         }
         assert_eq!(0, reader.left());
     }
+    #[test]
+    fn test_case0_spdm_measurement_record_length() {
+        create_spdm_context!(context);
+        context.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+
+        let mut measurement_block = SpdmMeasurementBlockStructure {
+            index: 1u8,
+            measurement_specification: SpdmMeasurementSpecification::DMTF,
+            measurement_size: 3 + SHA384_DIGEST_SIZE as u16,
+            measurement: SpdmDmtfMeasurementStructure {
+                r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementRom,
+                representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest,
+                value_size: SHA384_DIGEST_SIZE as u16,
+                value: [100u8; MAX_SPDM_MEASUREMENT_VALUE_LEN],
+            },
+        };
+        let mut measurement_record_data = [0u8; config::MAX_SPDM_MEASUREMENT_RECORD_SIZE];
+        let mut measurement_record_data_writer = Writer::init(&mut measurement_record_data);
+        for _i in 0..2 {
+            assert!(measurement_block
+                .spdm_encode(&mut context, &mut measurement_record_data_writer)
+                .is_ok());
+            measurement_block.index += 1;
+        }
+        let blocks_length = measurement_record_data_writer.used();
+        assert_eq!(blocks_length, 2 * (7 + SHA384_DIGEST_SIZE));
+
+        let mut encode_record = |measurement_record_length: usize| {
+            let record = SpdmMeasurementRecordStructure {
+                number_of_blocks: 2,
+                measurement_record_length: u24::new(measurement_record_length as u32),
+                measurement_record_data,
+            };
+            let mut buf = [0u8; 4 + config::MAX_SPDM_MEASUREMENT_RECORD_SIZE];
+            let mut writer = Writer::init(&mut buf);
+            assert!(record.spdm_encode(&mut context, &mut writer).is_ok());
+            let used = writer.used();
+            let mut reader = Reader::init(&buf[..used]);
+            SpdmMeasurementRecordStructure::spdm_read(&mut context, &mut reader)
+        };
+
+        let record = encode_record(blocks_length).unwrap();
+        assert_eq!(record.number_of_blocks, 2);
+        assert_eq!(
+            record.measurement_record_length.get() as usize,
+            blocks_length
+        );
+        // declared too short: the second block is cut off.
+        assert!(encode_record(blocks_length - 1).is_none());
+        assert!(encode_record(7 + SHA384_DIGEST_SIZE).is_none());
+        // declared too long: bytes past the last block.
+        assert!(encode_record(blocks_length + 1).is_none());
+        assert!(encode_record(blocks_length + 7 + SHA384_DIGEST_SIZE).is_none());
+    }
 }
 
 #[cfg(all(test,))]
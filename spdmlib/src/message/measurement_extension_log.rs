@@ -0,0 +1,191 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::spdm_codec::SpdmCodec;
+use crate::error::SPDM_STATUS_BUFFER_FULL;
+use crate::{common, error::SpdmStatus};
+use codec::{Codec, Reader, Writer};
+
+pub(crate) const MAX_SPDM_MEL_PORTION_LEN: usize = 512;
+
+/// Largest measurement extension log a requester fetches.
+pub const MAX_SPDM_MEL_SIZE: usize = 0x4000;
+
+#[derive(Debug, Clone, Default)]
+pub struct SpdmGetMeasurementExtensionLogRequestPayload {
+    pub offset: u32,
+    pub length: u32,
+}
+
+impl SpdmCodec for SpdmGetMeasurementExtensionLogRequestPayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        let mut cnt = 0usize;
+        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        cnt += self
+            .offset
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        cnt += self
+            .length
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        Ok(cnt)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmGetMeasurementExtensionLogRequestPayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+        let offset = u32::read(r)?;
+        let length = u32::read(r)?;
+
+        Some(SpdmGetMeasurementExtensionLogRequestPayload { offset, length })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SpdmMeasurementExtensionLogResponsePayload {
+    pub portion_length: u32,
+    pub remainder_length: u32,
+    pub measurement_extension_log: [u8; MAX_SPDM_MEL_PORTION_LEN],
+}
+
+impl Default for SpdmMeasurementExtensionLogResponsePayload {
+    fn default() -> SpdmMeasurementExtensionLogResponsePayload {
+        SpdmMeasurementExtensionLogResponsePayload {
+            portion_length: 0,
+            remainder_length: 0,
+            measurement_extension_log: [0u8; MAX_SPDM_MEL_PORTION_LEN],
+        }
+    }
+}
+
+impl SpdmCodec for SpdmMeasurementExtensionLogResponsePayload {
+    fn spdm_encode(
+        &self,
+        _context: &mut common::SpdmContext,
+        bytes: &mut Writer,
+    ) -> Result<usize, SpdmStatus> {
+        let mut cnt = 0usize;
+        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param1
+        cnt += 0u8.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?; // param2
+        cnt += self
+            .portion_length
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        cnt += self
+            .remainder_length
+            .encode(bytes)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+
+        for d in self
+            .measurement_extension_log
+            .iter()
+            .take(self.portion_length as usize)
+        {
+            cnt += d.encode(bytes).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+        }
+        Ok(cnt)
+    }
+
+    fn spdm_read(
+        _context: &mut common::SpdmContext,
+        r: &mut Reader,
+    ) -> Option<SpdmMeasurementExtensionLogResponsePayload> {
+        u8::read(r)?; // param1
+        u8::read(r)?; // param2
+        let portion_length = u32::read(r)?;
+        if portion_length as usize > MAX_SPDM_MEL_PORTION_LEN {
+            return None;
+        }
+        let remainder_length = u32::read(r)?;
+        let mut response = SpdmMeasurementExtensionLogResponsePayload {
+            portion_length,
+            remainder_length,
+            ..Default::default()
+        };
+
+        for data in response
+            .measurement_extension_log
+            .iter_mut()
+            .take(portion_length as usize)
+        {
+            *data = u8::read(r)?;
+        }
+        Some(response)
+    }
+}
+
+#[cfg(all(test,))]
+#[path = "mod_test.common.inc.rs"]
+mod testlib;
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::common::{SpdmConfigInfo, SpdmContext, SpdmProvisionInfo};
+    use testlib::{create_spdm_context, DeviceIO, TransportEncap};
+
+    #[test]
+    fn test_case0_spdm_get_measurement_extension_log_request_payload() {
+        let u8_slice = &mut [0u8; 10];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmGetMeasurementExtensionLogRequestPayload {
+            offset: 0x1234,
+            length: MAX_SPDM_MEL_PORTION_LEN as u32,
+        };
+
+        create_spdm_context!(context);
+
+        assert_eq!(value.spdm_encode(&mut context, &mut writer), Ok(10));
+        let mut reader = Reader::init(u8_slice);
+        let request =
+            SpdmGetMeasurementExtensionLogRequestPayload::spdm_read(&mut context, &mut reader)
+                .unwrap();
+        assert_eq!(request.offset, 0x1234);
+        assert_eq!(request.length, MAX_SPDM_MEL_PORTION_LEN as u32);
+        assert_eq!(0, reader.left());
+    }
+
+    #[test]
+    fn test_case0_spdm_measurement_extension_log_response_payload() {
+        let u8_slice = &mut [0u8; 10 + MAX_SPDM_MEL_PORTION_LEN];
+        let mut writer = Writer::init(u8_slice);
+        let value = SpdmMeasurementExtensionLogResponsePayload {
+            portion_length: MAX_SPDM_MEL_PORTION_LEN as u32,
+            remainder_length: 100,
+            measurement_extension_log: [100u8; MAX_SPDM_MEL_PORTION_LEN],
+        };
+
+        create_spdm_context!(context);
+
+        assert!(value.spdm_encode(&mut context, &mut writer).is_ok());
+        let mut reader = Reader::init(u8_slice);
+        let response =
+            SpdmMeasurementExtensionLogResponsePayload::spdm_read(&mut context, &mut reader)
+                .unwrap();
+        assert_eq!(response.portion_length, MAX_SPDM_MEL_PORTION_LEN as u32);
+        assert_eq!(response.remainder_length, 100);
+        assert_eq!(
+            response.measurement_extension_log,
+            [100u8; MAX_SPDM_MEL_PORTION_LEN]
+        );
+        assert_eq!(0, reader.left());
+
+        // a portion larger than any response carries.
+        u8_slice[2..6].copy_from_slice(&(MAX_SPDM_MEL_PORTION_LEN as u32 + 1).to_le_bytes());
+        let mut reader = Reader::init(u8_slice);
+        assert!(
+            SpdmMeasurementExtensionLogResponsePayload::spdm_read(&mut context, &mut reader)
+                .is_none()
+        );
+    }
+}
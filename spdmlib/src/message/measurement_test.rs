@@ -3,12 +3,15 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use super::*;
+use crate::common::opaque::{SpdmOpaqueSupport, MAX_SPDM_OPAQUE_SIZE};
+use crate::protocol::*;
 use crate::{
     common::{SpdmCodec, SpdmConfigInfo, SpdmContext, SpdmProvisionInfo},
     config::MAX_SPDM_MEASUREMENT_RECORD_SIZE,
 };
 use bit_field::BitField;
 use byteorder::{ByteOrder, LittleEndian};
+use codec::u24;
 use testlib::{create_spdm_context, DeviceIO, TransportEncap};
 
 #[test]
@@ -62,3 +65,272 @@ fn test_measurement_response() {
     let ret = SpdmMeasurementsResponsePayload::spdm_read(context, reader);
     assert!(ret.is_none())
 }
+
+fn encode_measurement_response_param2(
+    context: &mut SpdmContext,
+    slot_id: u8,
+    content_changed: SpdmMeasurementContentChanged,
+) -> u8 {
+    let u8_slice = &mut [0u8; 1024];
+    let writer = &mut Writer::init(u8_slice);
+    let response = SpdmMeasurementsResponsePayload {
+        slot_id,
+        content_changed,
+        signature: SpdmSignatureStruct {
+            data_size: ECDSA_ECC_NIST_P256_KEY_SIZE as u16,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(response.spdm_encode(context, writer).is_ok());
+    u8_slice[1]
+}
+
+fn decode_measurement_response_param2(
+    context: &mut SpdmContext,
+    param2: u8,
+) -> Option<SpdmMeasurementsResponsePayload> {
+    let u8_slice = &mut [0u8; 1024];
+    let writer = &mut Writer::init(u8_slice);
+    let response = SpdmMeasurementsResponsePayload {
+        signature: SpdmSignatureStruct {
+            data_size: ECDSA_ECC_NIST_P256_KEY_SIZE as u16,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(response.spdm_encode(context, writer).is_ok());
+    let used = writer.used();
+    u8_slice[1] = param2;
+    let reader = &mut Reader::init(&u8_slice[..used]);
+    SpdmMeasurementsResponsePayload::spdm_read(context, reader)
+}
+
+#[test]
+fn test_measurement_response_param2_version_11() {
+    create_spdm_context!(context);
+    let context = &mut context;
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    context.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256;
+    context.runtime_info.need_measurement_signature = true;
+
+    // Content changed is not part of a 1.1 response.
+    let param2 = encode_measurement_response_param2(
+        context,
+        3,
+        SpdmMeasurementContentChanged::DETECTED_CHANGE,
+    );
+    assert_eq!(param2, 3);
+
+    let response = decode_measurement_response_param2(context, 3).unwrap();
+    assert_eq!(response.slot_id, 3);
+    assert_eq!(
+        response.content_changed,
+        SpdmMeasurementContentChanged::NOT_SUPPORTED
+    );
+
+    // Bit [7:4] is reserved in 1.1.
+    assert!(decode_measurement_response_param2(context, 0x13).is_none());
+    assert!(decode_measurement_response_param2(context, 0x83).is_none());
+}
+
+#[test]
+fn test_measurement_response_param2_version_12() {
+    create_spdm_context!(context);
+    let context = &mut context;
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256;
+    context.runtime_info.need_measurement_signature = true;
+
+    let param2 = encode_measurement_response_param2(
+        context,
+        3,
+        SpdmMeasurementContentChanged::DETECTED_CHANGE,
+    );
+    assert_eq!(param2, 0x13);
+
+    let response = decode_measurement_response_param2(context, 0x23).unwrap();
+    assert_eq!(response.slot_id, 3);
+    assert_eq!(
+        response.content_changed,
+        SpdmMeasurementContentChanged::NO_CHANGE
+    );
+
+    // Content changed value 11b and bit [7:6] are reserved.
+    assert!(decode_measurement_response_param2(context, 0x33).is_none());
+    assert!(decode_measurement_response_param2(context, 0x43).is_none());
+
+    // Without a signature the whole field is reserved.
+    context.runtime_info.need_measurement_signature = false;
+    let param2 = encode_measurement_response_param2(
+        context,
+        3,
+        SpdmMeasurementContentChanged::DETECTED_CHANGE,
+    );
+    assert_eq!(param2, 0);
+    assert!(decode_measurement_response_param2(context, 0).is_some());
+    assert!(decode_measurement_response_param2(context, 0x10).is_none());
+}
+
+#[test]
+fn test_measurement_response_blocks_exceed_cap() {
+    create_spdm_context!(context);
+    let context = &mut context;
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.runtime_info.need_measurement_signature = false;
+
+    // A response claiming more blocks than MAX_SPDM_MEASUREMENT_BLOCKS is rejected
+    // before any of its blocks are parsed.
+    let u8_slice = &mut [0u8; 2 + 4 + SPDM_NONCE_SIZE + 2];
+    u8_slice[0] = 0xFF;
+    u8_slice[2] = (crate::config::MAX_SPDM_MEASUREMENT_BLOCKS + 1) as u8;
+    let reader = &mut Reader::init(u8_slice);
+    assert!(SpdmMeasurementsResponsePayload::spdm_read(context, reader).is_none());
+}
+
+#[test]
+fn test_measurement_response_signature_offset() {
+    create_spdm_context!(context);
+    let context = &mut context;
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    context.negotiate_info.measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    context.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::RAW_BIT_STREAM;
+    context.runtime_info.need_measurement_signature = true;
+    let signature_size = context.negotiate_info.base_asym_sel.get_size();
+
+    for opaque_size in [0usize, 1, 7, 64, MAX_SPDM_OPAQUE_SIZE] {
+        let mut measurement_record = SpdmMeasurementRecordStructure {
+            number_of_blocks: 1,
+            ..Default::default()
+        };
+        let mut value = [0u8; crate::config::MAX_SPDM_MEASUREMENT_VALUE_LEN];
+        value[..4].copy_from_slice(&[0xAA; 4]);
+        let block = SpdmMeasurementBlockStructure {
+            index: 1,
+            measurement_specification: SpdmMeasurementSpecification::DMTF,
+            measurement_size: 3 + 4,
+            measurement: SpdmDmtfMeasurementStructure {
+                r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware,
+                representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit,
+                value_size: 4,
+                value,
+            },
+        };
+        let mut record_writer = Writer::init(&mut measurement_record.measurement_record_data);
+        assert!(block.encode(&mut record_writer).is_ok());
+        measurement_record.measurement_record_length = u24::new(record_writer.used() as u32);
+        let mut opaque = SpdmOpaqueStruct {
+            data_size: opaque_size as u16,
+            data: [0u8; MAX_SPDM_OPAQUE_SIZE],
+        };
+        opaque.data[..opaque_size]
+            .iter_mut()
+            .for_each(|d| *d = 0xCC);
+        let value = SpdmMeasurementsResponsePayload {
+            number_of_measurement: 1,
+            slot_id: 0,
+            content_changed: SpdmMeasurementContentChanged::NOT_SUPPORTED,
+            measurement_record,
+            nonce: SpdmNonceStruct {
+                data: [0xBB; SPDM_NONCE_SIZE],
+            },
+            opaque,
+            signature: SpdmSignatureStruct {
+                data_size: signature_size,
+                data: [0xEE; SPDM_MAX_ASYM_KEY_SIZE],
+            },
+        };
+
+        let u8_slice = &mut [0u8; 2 + 4 + 11 + SPDM_NONCE_SIZE + 2 + MAX_SPDM_OPAQUE_SIZE + 512];
+        let mut writer = Writer::init(u8_slice);
+        assert!(value.spdm_encode(context, &mut writer).is_ok());
+        let used = writer.used();
+
+        // the signed part ends with the nonce and the opaque data, and the
+        // signature is exactly what follows it.
+        let signature_offset = value.signature_offset();
+        let signed = &u8_slice[..signature_offset];
+        assert_eq!(
+            &signed[signed.len() - opaque_size..],
+            &value.opaque.data[..opaque_size]
+        );
+        let opaque_length_offset = signed.len() - opaque_size - 2;
+        assert_eq!(
+            &signed[opaque_length_offset..opaque_length_offset + 2],
+            &(opaque_size as u16).to_le_bytes()
+        );
+        assert_eq!(
+            &signed[opaque_length_offset - SPDM_NONCE_SIZE..opaque_length_offset],
+            &[0xBB; SPDM_NONCE_SIZE]
+        );
+        assert_eq!(
+            &u8_slice[signature_offset..used],
+            &[0xEE; SPDM_MAX_ASYM_KEY_SIZE][..signature_size as usize]
+        );
+
+        // the decoded structure yields the same offset.
+        let mut reader = Reader::init(&u8_slice[..used]);
+        let decoded = SpdmMeasurementsResponsePayload::spdm_read(context, &mut reader).unwrap();
+        assert_eq!(decoded.signature_offset(), signature_offset);
+        assert_eq!(reader.used(), used);
+    }
+}
+
+#[test]
+fn test_measurement_response_malformed_opaque() {
+    create_spdm_context!(context);
+    let context = &mut context;
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.negotiate_info.opaque_data_support = SpdmOpaqueSupport::OPAQUE_DATA_FMT1;
+    context.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    context.negotiate_info.measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    context.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::RAW_BIT_STREAM;
+    context.runtime_info.need_measurement_signature = true;
+    let signature_size = context.negotiate_info.base_asym_sel.get_size();
+
+    // seven bytes cannot be opaque data in the general format.
+    let mut opaque = SpdmOpaqueStruct {
+        data_size: 7,
+        data: [0u8; MAX_SPDM_OPAQUE_SIZE],
+    };
+    opaque.data[..7].iter_mut().for_each(|d| *d = 0xCC);
+    let value = SpdmMeasurementsResponsePayload {
+        number_of_measurement: 0,
+        slot_id: 0,
+        content_changed: SpdmMeasurementContentChanged::NOT_SUPPORTED,
+        measurement_record: SpdmMeasurementRecordStructure::default(),
+        nonce: SpdmNonceStruct {
+            data: [0xBB; SPDM_NONCE_SIZE],
+        },
+        opaque,
+        signature: SpdmSignatureStruct {
+            data_size: signature_size,
+            data: [0xEE; SPDM_MAX_ASYM_KEY_SIZE],
+        },
+    };
+    let u8_slice = &mut [0u8; 2 + 4 + SPDM_NONCE_SIZE + 2 + 7 + SPDM_MAX_ASYM_KEY_SIZE];
+    let mut writer = Writer::init(u8_slice);
+    assert!(value.spdm_encode(context, &mut writer).is_ok());
+    let used = writer.used();
+
+    // refused by default.
+    let mut reader = Reader::init(&u8_slice[..used]);
+    assert!(SpdmMeasurementsResponsePayload::spdm_read(context, &mut reader).is_none());
+
+    // tolerated, the raw opaque data is kept and still covered by the
+    // signature.
+    context.config_info.tolerate_malformed_opaque = true;
+    let mut reader = Reader::init(&u8_slice[..used]);
+    let decoded = SpdmMeasurementsResponsePayload::spdm_read(context, &mut reader).unwrap();
+    assert_eq!(reader.used(), used);
+    assert_eq!(decoded.opaque.data_size, 7);
+    assert_eq!(&decoded.opaque.data[..7], &[0xCC; 7]);
+    let signature_offset = decoded.signature_offset();
+    assert_eq!(signature_offset, value.signature_offset());
+    assert_eq!(
+        &u8_slice[signature_offset - 7..signature_offset],
+        &[0xCC; 7]
+    );
+    assert_eq!(signature_offset + signature_size as usize, used);
+}
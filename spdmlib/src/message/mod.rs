@@ -29,21 +29,29 @@ pub mod key_update;
 pub mod psk_exchange;
 pub mod psk_finish;
 pub mod respond_if_ready;
+// SPDM 1.2
+pub mod chunk;
+// SPDM 1.3
+pub mod event;
+pub mod measurement_extension_log;
 
 pub use algorithm::*;
 pub use capability::*;
 pub use certificate::*;
 pub use challenge::*;
+pub use chunk::*;
 pub use digest::*;
 #[cfg(feature = "mut-auth")]
 pub use encapsulated::*;
 pub use end_session::*;
 pub use error::*;
+pub use event::*;
 pub use finish::*;
 pub use heartbeat::*;
 pub use key_exchange::*;
 pub use key_update::*;
 pub use measurement::*;
+pub use measurement_extension_log::*;
 pub use psk_exchange::*;
 pub use psk_finish::*;
 pub use version::*;
@@ -75,6 +83,14 @@ enum_builder! {
         SpdmResponseEncapsulatedRequest => 0x6A,
         SpdmResponseEncapsulatedResponseAck => 0x6B,
         SpdmResponseEndSessionAck => 0x6C,
+        // 1.2 response
+        SpdmResponseChunkSendAck => 0x05,
+        SpdmResponseChunkResponse => 0x06,
+        // 1.3 response
+        SpdmResponseSupportedEventTypes => 0x62,
+        SpdmResponseSubscribeEventTypesAck => 0x70,
+        SpdmResponseEventAck => 0x71,
+        SpdmResponseMeasurementExtensionLog => 0x6F,
 
         // 1.0 rerquest
         SpdmRequestGetDigests => 0x81,
@@ -95,7 +111,15 @@ enum_builder! {
         SpdmRequestKeyUpdate => 0xE9,
         SpdmRequestGetEncapsulatedRequest => 0xEA,
         SpdmRequestDeliverEncapsulatedResponse => 0xEB,
-        SpdmRequestEndSession => 0xEC
+        SpdmRequestEndSession => 0xEC,
+        // 1.2 request
+        SpdmRequestChunkSend => 0x85,
+        SpdmRequestChunkGet => 0x86,
+        // 1.3 request
+        SpdmRequestGetSupportedEventTypes => 0xE2,
+        SpdmRequestSubscribeEventTypes => 0xF0,
+        SpdmRequestSendEvent => 0xF1,
+        SpdmRequestGetMeasurementExtensionLog => 0xEF
     }
 }
 impl Default for SpdmRequestResponseCode {
@@ -235,6 +259,23 @@ pub enum SpdmMessagePayload {
     SpdmEndSessionRequest(SpdmEndSessionRequestPayload),
     SpdmEndSessionResponse(SpdmEndSessionResponsePayload),
 
+    SpdmGetSupportedEventTypesRequest(SpdmGetSupportedEventTypesRequestPayload),
+    SpdmSupportedEventTypesResponse(SpdmSupportedEventTypesResponsePayload),
+
+    SpdmSubscribeEventTypesRequest(SpdmSubscribeEventTypesRequestPayload),
+    SpdmSubscribeEventTypesAckResponse(SpdmSubscribeEventTypesAckResponsePayload),
+
+    SpdmSendEventRequest(SpdmSendEventRequestPayload),
+    SpdmEventAckResponse(SpdmEventAckResponsePayload),
+
+    SpdmGetMeasurementExtensionLogRequest(SpdmGetMeasurementExtensionLogRequestPayload),
+    SpdmMeasurementExtensionLogResponse(SpdmMeasurementExtensionLogResponsePayload),
+
+    SpdmChunkSendRequest(SpdmChunkPayload),
+    SpdmChunkSendAckResponse(SpdmChunkSendAckResponsePayload),
+    SpdmChunkGetRequest(SpdmChunkGetRequestPayload),
+    SpdmChunkResponse(SpdmChunkPayload),
+
     // Add new SPDM command here.
     SpdmErrorResponse(SpdmErrorResponsePayload),
     SpdmVendorDefinedRequest(SpdmVendorDefinedRequestPayload),
@@ -403,6 +444,67 @@ impl SpdmMessage {
                 ))
             }
 
+            SpdmRequestResponseCode::SpdmResponseSupportedEventTypes => {
+                Some(SpdmMessagePayload::SpdmSupportedEventTypesResponse(
+                    SpdmSupportedEventTypesResponsePayload::spdm_read(context, r)?,
+                ))
+            }
+            SpdmRequestResponseCode::SpdmRequestGetSupportedEventTypes => {
+                Some(SpdmMessagePayload::SpdmGetSupportedEventTypesRequest(
+                    SpdmGetSupportedEventTypesRequestPayload::spdm_read(context, r)?,
+                ))
+            }
+
+            SpdmRequestResponseCode::SpdmResponseSubscribeEventTypesAck => {
+                Some(SpdmMessagePayload::SpdmSubscribeEventTypesAckResponse(
+                    SpdmSubscribeEventTypesAckResponsePayload::spdm_read(context, r)?,
+                ))
+            }
+            SpdmRequestResponseCode::SpdmRequestSubscribeEventTypes => {
+                Some(SpdmMessagePayload::SpdmSubscribeEventTypesRequest(
+                    SpdmSubscribeEventTypesRequestPayload::spdm_read(context, r)?,
+                ))
+            }
+
+            SpdmRequestResponseCode::SpdmResponseEventAck => {
+                Some(SpdmMessagePayload::SpdmEventAckResponse(
+                    SpdmEventAckResponsePayload::spdm_read(context, r)?,
+                ))
+            }
+            SpdmRequestResponseCode::SpdmRequestSendEvent => {
+                Some(SpdmMessagePayload::SpdmSendEventRequest(
+                    SpdmSendEventRequestPayload::spdm_read(context, r)?,
+                ))
+            }
+
+            SpdmRequestResponseCode::SpdmResponseMeasurementExtensionLog => {
+                Some(SpdmMessagePayload::SpdmMeasurementExtensionLogResponse(
+                    SpdmMeasurementExtensionLogResponsePayload::spdm_read(context, r)?,
+                ))
+            }
+            SpdmRequestResponseCode::SpdmRequestGetMeasurementExtensionLog => {
+                Some(SpdmMessagePayload::SpdmGetMeasurementExtensionLogRequest(
+                    SpdmGetMeasurementExtensionLogRequestPayload::spdm_read(context, r)?,
+                ))
+            }
+
+            SpdmRequestResponseCode::SpdmRequestChunkSend => Some(
+                SpdmMessagePayload::SpdmChunkSendRequest(SpdmChunkPayload::spdm_read(context, r)?),
+            ),
+            SpdmRequestResponseCode::SpdmResponseChunkSendAck => {
+                Some(SpdmMessagePayload::SpdmChunkSendAckResponse(
+                    SpdmChunkSendAckResponsePayload::spdm_read(context, r)?,
+                ))
+            }
+            SpdmRequestResponseCode::SpdmRequestChunkGet => {
+                Some(SpdmMessagePayload::SpdmChunkGetRequest(
+                    SpdmChunkGetRequestPayload::spdm_read(context, r)?,
+                ))
+            }
+            SpdmRequestResponseCode::SpdmResponseChunkResponse => Some(
+                SpdmMessagePayload::SpdmChunkResponse(SpdmChunkPayload::spdm_read(context, r)?),
+            ),
+
             // Add new SPDM command here.
             SpdmRequestResponseCode::SpdmResponseError => {
                 Some(SpdmMessagePayload::SpdmErrorResponse(
@@ -547,6 +649,47 @@ impl SpdmCodec for SpdmMessage {
                 cnt += payload.spdm_encode(context, bytes)?;
             }
 
+            SpdmMessagePayload::SpdmGetSupportedEventTypesRequest(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+            SpdmMessagePayload::SpdmSupportedEventTypesResponse(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+
+            SpdmMessagePayload::SpdmSubscribeEventTypesRequest(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+            SpdmMessagePayload::SpdmSubscribeEventTypesAckResponse(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+
+            SpdmMessagePayload::SpdmSendEventRequest(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+            SpdmMessagePayload::SpdmEventAckResponse(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+
+            SpdmMessagePayload::SpdmGetMeasurementExtensionLogRequest(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+            SpdmMessagePayload::SpdmMeasurementExtensionLogResponse(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+
+            SpdmMessagePayload::SpdmChunkSendRequest(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+            SpdmMessagePayload::SpdmChunkSendAckResponse(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+            SpdmMessagePayload::SpdmChunkGetRequest(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+            SpdmMessagePayload::SpdmChunkResponse(payload) => {
+                cnt += payload.spdm_encode(context, bytes)?;
+            }
+
             // Add new SPDM command here.
             SpdmMessagePayload::SpdmErrorResponse(payload) => {
                 cnt += payload.spdm_encode(context, bytes)?;
@@ -568,7 +711,7 @@ impl SpdmCodec for SpdmMessage {
 
 #[cfg(all(test,))]
 #[path = "mod_test.common.inc.rs"]
-mod testlib;
+pub(crate) mod testlib;
 
 #[cfg(all(test,))]
 mod tests {
@@ -712,6 +855,10 @@ mod tests {
                     other_params_support: SpdmOpaqueSupport::empty(),
                     base_asym_algo: SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048,
                     base_hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+                    ext_asym_count: 0,
+                    ext_asym: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
+                    ext_hash_count: 0,
+                    ext_hash: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
                     alg_struct_count: 4,
                     alg_struct: [
                         SpdmAlgStruct {
@@ -798,6 +945,8 @@ mod tests {
                 measurement_hash_algo: SpdmMeasurementHashAlgo::RAW_BIT_STREAM,
                 base_asym_sel: SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048,
                 base_hash_sel: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+                ext_asym_sel: None,
+                ext_hash_sel: None,
                 alg_struct_count: 4,
                 alg_struct: [
                     SpdmAlgStruct {
@@ -885,6 +1034,7 @@ mod tests {
             },
             payload: SpdmMessagePayload::SpdmCertificateResponse(SpdmCertificateResponsePayload {
                 slot_id: 100,
+                cert_model: SpdmCertificateModel::default(),
                 portion_length: MAX_SPDM_CERT_PORTION_LEN as u16,
                 remainder_length: 100,
                 cert_chain: [100u8; MAX_SPDM_CERT_PORTION_LEN],
@@ -1569,6 +1719,7 @@ mod tests {
             payload: SpdmMessagePayload::SpdmGetCertificateRequest(
                 SpdmGetCertificateRequestPayload {
                     slot_id: 100,
+                    request_attribute: SpdmGetCertificateRequestAttributes::empty(),
                     offset: 100,
                     length: 100,
                 },
@@ -1656,6 +1807,7 @@ mod tests {
                     },
                     SPDM_MAX_SLOT_NUMBER,
                 ),
+                ..Default::default()
             }),
         };
         create_spdm_context!(context);
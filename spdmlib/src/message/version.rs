@@ -53,6 +53,16 @@ impl Codec for SpdmVersionStruct {
     }
 }
 
+/// Whether `versions` lists some version more than once. The entries of a
+/// VERSION response may come in any order, but each only once.
+pub fn spdm_version_list_has_duplicates(versions: &[SpdmVersionStruct]) -> bool {
+    versions.iter().enumerate().any(|(i, entry)| {
+        versions[i + 1..]
+            .iter()
+            .any(|other| other.version == entry.version)
+    })
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SpdmVersionResponsePayload {
     pub version_number_entry_count: u8,
@@ -25,6 +25,8 @@ pub const RSAPSS_4096_KEY_SIZE: usize = 512;
 pub const ECDSA_ECC_NIST_P256_KEY_SIZE: usize = 32 * 2;
 pub const ECDSA_ECC_NIST_P384_KEY_SIZE: usize = 48 * 2;
 
+pub const EDDSA_ED25519_KEY_SIZE: usize = 32 * 2;
+
 pub const SECP_256_R1_KEY_SIZE: usize = 32 * 2;
 pub const SECP_384_R1_KEY_SIZE: usize = 48 * 2;
 
@@ -178,6 +180,7 @@ bitflags! {
         const TPM_ALG_RSASSA_4096 = 0b0010_0000;
         const TPM_ALG_RSAPSS_4096 = 0b0100_0000;
         const TPM_ALG_ECDSA_ECC_NIST_P384 = 0b1000_0000;
+        const TPM_ALG_EDDSA_ED25519 = 0b0100_0000_0000;
         const VALID_MASK = Self::TPM_ALG_RSASSA_2048.bits
             | Self::TPM_ALG_RSAPSS_2048.bits
             | Self::TPM_ALG_RSASSA_3072.bits
@@ -185,7 +188,8 @@ bitflags! {
             | Self::TPM_ALG_ECDSA_ECC_NIST_P256.bits
             | Self::TPM_ALG_RSASSA_4096.bits
             | Self::TPM_ALG_RSAPSS_4096.bits
-            | Self::TPM_ALG_ECDSA_ECC_NIST_P384.bits;
+            | Self::TPM_ALG_ECDSA_ECC_NIST_P384.bits
+            | Self::TPM_ALG_EDDSA_ED25519.bits;
     }
 }
 
@@ -194,6 +198,7 @@ impl SpdmBaseAsymAlgo {
         let prio_table = [
             SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
             SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256,
+            SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519,
             SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096,
             SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_3072,
             SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_2048,
@@ -221,6 +226,7 @@ impl SpdmBaseAsymAlgo {
             SpdmBaseAsymAlgo::TPM_ALG_RSAPSS_4096 => RSAPSS_4096_KEY_SIZE as u16,
             SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256 => ECDSA_ECC_NIST_P256_KEY_SIZE as u16,
             SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384 => ECDSA_ECC_NIST_P384_KEY_SIZE as u16,
+            SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519 => EDDSA_ED25519_KEY_SIZE as u16,
             _ => {
                 panic!("invalid AsymAlgo");
             }
@@ -731,8 +737,129 @@ impl Codec for SpdmAlgStruct {
     }
 }
 
+impl SpdmAlgStruct {
+    /// Read an AlgStruct from a NEGOTIATE_ALGORITHMS request.
+    ///
+    /// Unlike `read`, the ExtAlg entries that follow the fixed field are
+    /// skipped, and an AlgType this crate does not know is skipped over
+    /// using its fixed count and returned as `SpdmAlgoUnknown`.
+    pub fn read_tolerant(r: &mut Reader) -> Option<SpdmAlgStruct> {
+        let alg_type = SpdmAlgType::read(r)?;
+        let alg_count = u8::read(r)?;
+        let alg_fixed_count = ((alg_count as u32 >> 4) & 0xF) as u8;
+        let alg_ext_count = alg_count & 0xF;
+
+        let alg_supported = match alg_type {
+            SpdmAlgType::Unknown(_) => {
+                r.take(alg_fixed_count as usize)?;
+                SpdmAlg::SpdmAlgoUnknown(SpdmUnknownAlgo {})
+            }
+            _ => {
+                if alg_fixed_count != 2 {
+                    return None;
+                }
+                match alg_type {
+                    SpdmAlgType::SpdmAlgTypeDHE => SpdmAlg::SpdmAlgoDhe(SpdmDheAlgo::read(r)?),
+                    SpdmAlgType::SpdmAlgTypeAEAD => SpdmAlg::SpdmAlgoAead(SpdmAeadAlgo::read(r)?),
+                    SpdmAlgType::SpdmAlgTypeReqAsym => {
+                        SpdmAlg::SpdmAlgoReqAsym(SpdmReqAsymAlgo::read(r)?)
+                    }
+                    _ => SpdmAlg::SpdmAlgoKeySchedule(SpdmKeyScheduleAlgo::read(r)?),
+                }
+            }
+        };
+
+        for _i in 0..alg_ext_count {
+            SpdmExtAlgStruct::read(r)?;
+        }
+
+        Some(SpdmAlgStruct {
+            alg_type,
+            alg_supported,
+        })
+    }
+}
+
+/// Upper bound of ExtAsymCount + ExtHashCount in NEGOTIATE_ALGORITHMS.
+pub const SPDM_MAX_EXT_ALG_COUNT: usize = 20;
+
+/// DSP0274 Table: Extended Algorithm field format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpdmExtAlgStruct {
+    pub registry_id: u8,
+    pub algorithm_id: u16,
+}
+
+impl Codec for SpdmExtAlgStruct {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.registry_id.encode(bytes)?;
+        cnt += 0u8.encode(bytes)?; // reserved
+        cnt += self.algorithm_id.encode(bytes)?;
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmExtAlgStruct> {
+        let registry_id = u8::read(r)?;
+        u8::read(r)?; // reserved
+        let algorithm_id = u16::read(r)?;
+        Some(SpdmExtAlgStruct {
+            registry_id,
+            algorithm_id,
+        })
+    }
+}
+
 pub const SPDM_MAX_SLOT_NUMBER: usize = 8;
 
+bitflags! {
+    /// What the key behind a certificate slot may be used for (SPDM 1.3).
+    #[derive(Default)]
+    pub struct SpdmKeyUsageMask: u16 {
+        const KEY_EX_USE = 0b0000_0000_0000_0001;
+        const CHALLENGE_USE = 0b0000_0000_0000_0010;
+        const MEASUREMENT_USE = 0b0000_0000_0000_0100;
+        const ENDPOINT_INFO_USE = 0b0000_0000_0000_1000;
+        const STANDARDS_KEY_USE = 0b0100_0000_0000_0000;
+        const VENDOR_KEY_USE = 0b1000_0000_0000_0000;
+        const VALID_MASK = Self::KEY_EX_USE.bits
+            | Self::CHALLENGE_USE.bits
+            | Self::MEASUREMENT_USE.bits
+            | Self::ENDPOINT_INFO_USE.bits
+            | Self::STANDARDS_KEY_USE.bits
+            | Self::VENDOR_KEY_USE.bits;
+    }
+}
+
+impl Codec for SpdmKeyUsageMask {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        self.bits().encode(bytes)
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmKeyUsageMask> {
+        let bits = u16::read(r)?;
+
+        SpdmKeyUsageMask::from_bits(bits & SpdmKeyUsageMask::VALID_MASK.bits)
+    }
+}
+
+enum_builder! {
+    /// CertModel of the certificate chain in a slot (SPDM 1.3).
+    @U8
+    EnumName: SpdmCertificateModel;
+    EnumVal{
+        SpdmCertModelNone => 0x0,
+        SpdmCertModelDeviceCert => 0x1,
+        SpdmCertModelAliasCert => 0x2,
+        SpdmCertModelGenericCert => 0x3
+    }
+}
+impl Default for SpdmCertificateModel {
+    fn default() -> SpdmCertificateModel {
+        SpdmCertificateModel::SpdmCertModelNone
+    }
+}
+
 enum_builder! {
     @U8
     EnumName: SpdmMeasurementSummaryHashType;
@@ -898,6 +1025,44 @@ impl SpdmCertChainBuffer {
         buff.data_size = pos as u16;
         Some(buff)
     }
+
+    /// The DER certificates of the chain, see `SpdmCertChainHeader::parse`.
+    pub fn der_cert_chain(&self, base_hash_algo: SpdmBaseHashAlgo) -> Option<&[u8]> {
+        let header = SpdmCertChainHeader::parse(self.as_ref(), base_hash_algo)?;
+        Some(&self.as_ref()[header.der_offset..])
+    }
+}
+
+/// Table 28 — Certificate chain format, the part in front of the
+/// certificates: Length (2), Reserved (2) and RootHash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpdmCertChainHeader<'a> {
+    pub length: u16,
+    pub root_hash: &'a [u8],
+    pub der_offset: usize,
+}
+
+impl<'a> SpdmCertChainHeader<'a> {
+    /// Parse the header of `cert_chain`, a whole SPDM certificate chain
+    /// whose root hash is `base_hash_algo` sized.
+    ///
+    /// None unless Length covers exactly `cert_chain` and at least one byte
+    /// of certificates follows the root hash.
+    pub fn parse(cert_chain: &'a [u8], base_hash_algo: SpdmBaseHashAlgo) -> Option<Self> {
+        let der_offset = 4 + base_hash_algo.get_size() as usize;
+        if cert_chain.len() <= der_offset {
+            return None;
+        }
+        let length = u16::from_le_bytes([cert_chain[0], cert_chain[1]]);
+        if length as usize != cert_chain.len() {
+            return None;
+        }
+        Some(SpdmCertChainHeader {
+            length,
+            root_hash: &cert_chain[4..der_offset],
+            der_offset,
+        })
+    }
 }
 
 enum_builder! {
@@ -1729,6 +1894,23 @@ mod tests {
         spdm_measurement_specification.prioritize(value);
     }
     #[test]
+    fn test_case0_spdm_base_asym_algo_prioritize_ed25519() {
+        let mut value =
+            SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519 | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048;
+        value.prioritize(SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519);
+        assert_eq!(value, SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519);
+
+        // only selected when the peer supports it too.
+        let mut value =
+            SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519 | SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048;
+        value.prioritize(SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048);
+        assert_eq!(value, SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048);
+
+        let mut value = SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519;
+        value.prioritize(SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384);
+        assert!(value.is_empty());
+    }
+    #[test]
     fn test_case1_spdm_signature_struct() {
         let bytes_mut = BytesMut::new();
         let spdm_signature_struct = SpdmSignatureStruct::from(bytes_mut);
@@ -1783,6 +1965,9 @@ mod tests {
         value = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
         assert_eq!(value.get_size(), ECDSA_ECC_NIST_P384_KEY_SIZE as u16);
 
+        value = SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519;
+        assert_eq!(value.get_size(), EDDSA_ED25519_KEY_SIZE as u16);
+
         value = SpdmBaseAsymAlgo::empty();
         value.get_size();
     }
@@ -1897,4 +2082,44 @@ mod tests {
         let mut reader = Reader::init(u8_slice);
         SpdmUnknownAlgo::read(&mut reader);
     }
+
+    #[test]
+    fn test_case0_spdm_cert_chain_header() {
+        let root_hash = [0xa5u8; 48];
+        let cert_chain = SpdmCertChainBuffer::new(&[0x30, 0x82, 0x01, 0x02], &root_hash).unwrap();
+        let header =
+            SpdmCertChainHeader::parse(cert_chain.as_ref(), SpdmBaseHashAlgo::TPM_ALG_SHA_384)
+                .unwrap();
+        assert_eq!(header.length, 4 + 48 + 4);
+        assert_eq!(header.root_hash, &root_hash[..]);
+        assert_eq!(header.der_offset, 4 + 48);
+        assert_eq!(
+            cert_chain.der_cert_chain(SpdmBaseHashAlgo::TPM_ALG_SHA_384),
+            Some(&[0x30, 0x82, 0x01, 0x02][..])
+        );
+    }
+
+    #[test]
+    fn test_case1_spdm_cert_chain_header() {
+        let cert_chain =
+            SpdmCertChainBuffer::new(&[0x30, 0x82, 0x01, 0x02], &[0xa5u8; 48]).unwrap();
+        let bytes = cert_chain.as_ref();
+
+        // Length does not cover the buffer.
+        assert!(SpdmCertChainHeader::parse(
+            &bytes[..bytes.len() - 1],
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384
+        )
+        .is_none());
+        let mut longer = bytes.to_vec();
+        longer.push(0);
+        assert!(SpdmCertChainHeader::parse(&longer, SpdmBaseHashAlgo::TPM_ALG_SHA_384).is_none());
+        // no certificate after a larger root hash.
+        assert!(
+            SpdmCertChainHeader::parse(&bytes[..4 + 48], SpdmBaseHashAlgo::TPM_ALG_SHA_384)
+                .is_none()
+        );
+        assert!(SpdmCertChainHeader::parse(bytes, SpdmBaseHashAlgo::TPM_ALG_SHA_512).is_none());
+        assert!(SpdmCertChainHeader::parse(&[], SpdmBaseHashAlgo::TPM_ALG_SHA_384).is_none());
+    }
 }
@@ -21,6 +21,7 @@ bitflags! {
         const HANDSHAKE_IN_THE_CLEAR_CAP = 0b1000_0000_0000_0000;
         const PUB_KEY_ID_CAP = 0b0000_0001_0000_0000_0000_0000;
         const CHUNK_CAP = 0b0000_0010_0000_0000_0000_0000;
+        const EVENT_CAP = 0b0000_0010_0000_0000_0000_0000_0000_0000;
         const VALID_MASK = Self::CERT_CAP.bits
             | Self::CHAL_CAP.bits
             | Self::ENCRYPT_CAP.bits
@@ -34,7 +35,8 @@ bitflags! {
             | Self::KEY_UPD_CAP.bits
             | Self::HANDSHAKE_IN_THE_CLEAR_CAP.bits
             | Self::PUB_KEY_ID_CAP.bits
-            | Self::CHUNK_CAP.bits;
+            | Self::CHUNK_CAP.bits
+            | Self::EVENT_CAP.bits;
     }
 }
 
@@ -75,6 +77,10 @@ bitflags! {
         const SET_CERT_CAP = 0b0000_1000_0000_0000_0000_0000;
         const CSR_CAP = 0b0001_0000_0000_0000_0000_0000;
         const CERT_INSTALL_RESET_CAP = 0b0010_0000_0000_0000_0000_0000;
+        const MEL_CAP = 0b0000_0001_0000_0000_0000_0000_0000_0000;
+        const EVENT_CAP = 0b0000_0010_0000_0000_0000_0000_0000_0000;
+        const MULTI_KEY_CAP_ONLY = 0b0000_0100_0000_0000_0000_0000_0000_0000;
+        const MULTI_KEY_CAP_NEG = 0b0000_1000_0000_0000_0000_0000_0000_0000;
         const VALID_MASK = Self::CACHE_CAP.bits
             | Self::CERT_CAP.bits
             | Self::CHAL_CAP.bits
@@ -96,7 +102,11 @@ bitflags! {
             | Self::ALIAS_CERT_CAP.bits
             | Self::SET_CERT_CAP.bits
             | Self::CSR_CAP.bits
-            | Self::CERT_INSTALL_RESET_CAP.bits;
+            | Self::CERT_INSTALL_RESET_CAP.bits
+            | Self::MEL_CAP.bits
+            | Self::EVENT_CAP.bits
+            | Self::MULTI_KEY_CAP_ONLY.bits
+            | Self::MULTI_KEY_CAP_NEG.bits;
     }
 }
 
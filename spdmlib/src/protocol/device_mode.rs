@@ -0,0 +1,237 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::config;
+use crate::protocol::{
+    SpdmDmtfMeasurementRepresentation, SpdmDmtfMeasurementStructure, SpdmDmtfMeasurementType,
+};
+use codec::{Codec, Reader, Writer};
+
+/// Measurement index SPDM 1.2 reserves for the device mode block.
+pub const SPDM_MEASUREMENT_INDEX_DEVICE_MODE: u8 = 0xFE;
+
+pub const SPDM_DEVICE_MODE_MEASUREMENT_SIZE: usize = 16;
+
+bitflags! {
+    /// OperationalModeCapabilities and OperationalModeState of the device
+    /// mode block; the state has the one current mode set.
+    #[derive(Default)]
+    pub struct SpdmOperationalMode: u32 {
+        const MANUFACTURING = 0b0000_0001;
+        const VALIDATION = 0b0000_0010;
+        const NORMAL = 0b0000_0100;
+        const RECOVERY = 0b0000_1000;
+        const RMA = 0b0001_0000;
+        const DECOMMISSIONED = 0b0010_0000;
+        const VALID_MASK = Self::MANUFACTURING.bits
+            | Self::VALIDATION.bits
+            | Self::NORMAL.bits
+            | Self::RECOVERY.bits
+            | Self::RMA.bits
+            | Self::DECOMMISSIONED.bits;
+    }
+}
+
+impl Codec for SpdmOperationalMode {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        self.bits().encode(bytes)
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmOperationalMode> {
+        let bits = u32::read(r)?;
+
+        Some(SpdmOperationalMode::from_bits_truncate(bits))
+    }
+}
+
+bitflags! {
+    /// DeviceModeCapabilities and DeviceModeAttributes of the device mode
+    /// block. In the capabilities the two `_ACTIVE` bits mean the debug mode
+    /// is supported, the `_HAS_BEEN_ACTIVE` ones that it can be reported.
+    #[derive(Default)]
+    pub struct SpdmDeviceMode: u32 {
+        const NON_INVASIVE_DEBUG_ACTIVE = 0b0000_0001;
+        const INVASIVE_DEBUG_ACTIVE = 0b0000_0010;
+        // since the last reset.
+        const NON_INVASIVE_DEBUG_HAS_BEEN_ACTIVE = 0b0000_0100;
+        const INVASIVE_DEBUG_HAS_BEEN_ACTIVE = 0b0000_1000;
+        // at least once since manufacturing mode was left.
+        const INVASIVE_DEBUG_HAS_BEEN_ACTIVE_SINCE_MFG = 0b0001_0000;
+        const VALID_MASK = Self::NON_INVASIVE_DEBUG_ACTIVE.bits
+            | Self::INVASIVE_DEBUG_ACTIVE.bits
+            | Self::NON_INVASIVE_DEBUG_HAS_BEEN_ACTIVE.bits
+            | Self::INVASIVE_DEBUG_HAS_BEEN_ACTIVE.bits
+            | Self::INVASIVE_DEBUG_HAS_BEEN_ACTIVE_SINCE_MFG.bits;
+    }
+}
+
+impl Codec for SpdmDeviceMode {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        self.bits().encode(bytes)
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmDeviceMode> {
+        let bits = u32::read(r)?;
+
+        Some(SpdmDeviceMode::from_bits_truncate(bits))
+    }
+}
+
+/// Value of the structured device mode measurement (DMTF value type 0x05,
+/// raw bit stream), normally reported at `SPDM_MEASUREMENT_INDEX_DEVICE_MODE`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpdmDeviceModeMeasurement {
+    pub operational_mode_capabilities: SpdmOperationalMode,
+    pub operational_mode_state: SpdmOperationalMode,
+    pub device_mode_capabilities: SpdmDeviceMode,
+    pub device_mode_attributes: SpdmDeviceMode,
+}
+
+impl Codec for SpdmDeviceModeMeasurement {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.operational_mode_capabilities.encode(bytes)?;
+        cnt += self.operational_mode_state.encode(bytes)?;
+        cnt += self.device_mode_capabilities.encode(bytes)?;
+        cnt += self.device_mode_attributes.encode(bytes)?;
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmDeviceModeMeasurement> {
+        let operational_mode_capabilities = SpdmOperationalMode::read(r)?;
+        let operational_mode_state = SpdmOperationalMode::read(r)?;
+        let device_mode_capabilities = SpdmDeviceMode::read(r)?;
+        let device_mode_attributes = SpdmDeviceMode::read(r)?;
+        Some(SpdmDeviceModeMeasurement {
+            operational_mode_capabilities,
+            operational_mode_state,
+            device_mode_capabilities,
+            device_mode_attributes,
+        })
+    }
+}
+
+impl SpdmDeviceModeMeasurement {
+    /// None unless `measurement` is a raw device mode value of the right size.
+    pub fn from_measurement(measurement: &SpdmDmtfMeasurementStructure) -> Option<Self> {
+        if measurement.r#type
+            != SpdmDmtfMeasurementType::SpdmDmtfMeasurementStructuredRepresentationMode
+            || measurement.representation
+                != SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit
+            || measurement.value_size as usize != SPDM_DEVICE_MODE_MEASUREMENT_SIZE
+        {
+            return None;
+        }
+        let mut reader = Reader::init(&measurement.value[..SPDM_DEVICE_MODE_MEASUREMENT_SIZE]);
+        SpdmDeviceModeMeasurement::read(&mut reader)
+    }
+
+    /// The measurement a responder's measurement callback returns for
+    /// `SPDM_MEASUREMENT_INDEX_DEVICE_MODE`.
+    pub fn to_measurement(&self) -> SpdmDmtfMeasurementStructure {
+        let mut value = [0u8; config::MAX_SPDM_MEASUREMENT_VALUE_LEN];
+        let mut writer = Writer::init(&mut value);
+        let value_size = self.encode(&mut writer).unwrap_or(0);
+        SpdmDmtfMeasurementStructure {
+            r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementStructuredRepresentationMode,
+            representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit,
+            value_size: value_size as u16,
+            value,
+        }
+    }
+
+    /// A debug mode is active, or has been since the last reset.
+    pub fn debug_mode(&self) -> bool {
+        self.device_mode_attributes.intersects(
+            SpdmDeviceMode::NON_INVASIVE_DEBUG_ACTIVE
+                | SpdmDeviceMode::INVASIVE_DEBUG_ACTIVE
+                | SpdmDeviceMode::NON_INVASIVE_DEBUG_HAS_BEEN_ACTIVE
+                | SpdmDeviceMode::INVASIVE_DEBUG_HAS_BEEN_ACTIVE,
+        )
+    }
+
+    pub fn manufacturing_mode(&self) -> bool {
+        self.operational_mode_state
+            .contains(SpdmOperationalMode::MANUFACTURING)
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::protocol::{SpdmMeasurementBlockStructure, SpdmMeasurementSpecification};
+
+    #[test]
+    fn test_case0_device_mode_measurement_debug() {
+        // MEASUREMENTS block at index 0xFE: normal operation, invasive debug
+        // supported and active.
+        let block = [
+            0xFE, 0x01, 0x13, 0x00, // Index, MeasurementSpecification, MeasurementSize
+            0x85, 0x10, 0x00, // DMTFSpecMeasurementValueType, DMTFSpecMeasurementValueSize
+            0x3F, 0x00, 0x00, 0x00, // OperationalModeCapabilities
+            0x04, 0x00, 0x00, 0x00, // OperationalModeState
+            0x1F, 0x00, 0x00, 0x00, // DeviceModeCapabilities
+            0x0A, 0x00, 0x00, 0x00, // DeviceModeAttributes
+        ];
+        let mut reader = Reader::init(&block);
+        let block = SpdmMeasurementBlockStructure::read(&mut reader).unwrap();
+        assert!(!reader.any_left());
+        assert_eq!(block.index, SPDM_MEASUREMENT_INDEX_DEVICE_MODE);
+        assert_eq!(
+            block.measurement_specification,
+            SpdmMeasurementSpecification::DMTF
+        );
+
+        let device_mode = SpdmDeviceModeMeasurement::from_measurement(&block.measurement).unwrap();
+        assert_eq!(
+            device_mode.operational_mode_capabilities,
+            SpdmOperationalMode::VALID_MASK
+        );
+        assert_eq!(
+            device_mode.operational_mode_state,
+            SpdmOperationalMode::NORMAL
+        );
+        assert_eq!(
+            device_mode.device_mode_capabilities,
+            SpdmDeviceMode::VALID_MASK
+        );
+        assert_eq!(
+            device_mode.device_mode_attributes,
+            SpdmDeviceMode::INVASIVE_DEBUG_ACTIVE | SpdmDeviceMode::INVASIVE_DEBUG_HAS_BEEN_ACTIVE
+        );
+        assert!(device_mode.debug_mode());
+        assert!(!device_mode.manufacturing_mode());
+
+        // what a responder hands out encodes back to the same value.
+        let measurement = device_mode.to_measurement();
+        assert_eq!(measurement.value_size, block.measurement.value_size);
+        assert_eq!(
+            measurement.value[..SPDM_DEVICE_MODE_MEASUREMENT_SIZE],
+            block.measurement.value[..SPDM_DEVICE_MODE_MEASUREMENT_SIZE]
+        );
+    }
+
+    #[test]
+    fn test_case1_device_mode_measurement_invalid() {
+        let device_mode = SpdmDeviceModeMeasurement {
+            operational_mode_capabilities: SpdmOperationalMode::MANUFACTURING
+                | SpdmOperationalMode::NORMAL,
+            operational_mode_state: SpdmOperationalMode::MANUFACTURING,
+            ..Default::default()
+        };
+        assert!(!device_mode.debug_mode());
+        assert!(device_mode.manufacturing_mode());
+
+        // a digest, or a value of another size, is not a device mode.
+        let mut measurement = device_mode.to_measurement();
+        measurement.representation = SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest;
+        assert!(SpdmDeviceModeMeasurement::from_measurement(&measurement).is_none());
+        let mut measurement = device_mode.to_measurement();
+        measurement.value_size -= 1;
+        assert!(SpdmDeviceModeMeasurement::from_measurement(&measurement).is_none());
+        let mut measurement = device_mode.to_measurement();
+        measurement.r#type = SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware;
+        assert!(SpdmDeviceModeMeasurement::from_measurement(&measurement).is_none());
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use core::fmt::{self, Write};
+
+use crate::error::{SpdmResult, SPDM_STATUS_BUFFER_FULL};
+use crate::protocol::{SpdmDmtfMeasurementRepresentation, SpdmMeasurementBlockStructure};
+
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    used: usize,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.used.checked_add(s.len()).ok_or(fmt::Error)?;
+        self.buf
+            .get_mut(self.used..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.used = end;
+        Ok(())
+    }
+}
+
+/// Write `block` into `buf` as one line of JSON, for logging without
+/// `alloc`:
+///
+/// `{"index":1,"type":1,"representation":"digest","value":"3a0f..."}`
+///
+/// `type` is the DMTFSpecMeasurementValueType without the representation
+/// bit. Returns the bytes written, or SPDM_STATUS_BUFFER_FULL if `buf` is
+/// too small, in which case its content is unspecified.
+pub fn dump_measurement_block(
+    block: &SpdmMeasurementBlockStructure,
+    buf: &mut [u8],
+) -> SpdmResult<usize> {
+    let mut writer = SliceWriter { buf, used: 0 };
+    write_measurement_block(block, &mut writer).map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+    Ok(writer.used)
+}
+
+fn write_measurement_block(
+    block: &SpdmMeasurementBlockStructure,
+    writer: &mut SliceWriter,
+) -> fmt::Result {
+    let measurement = &block.measurement;
+    let representation = match measurement.representation {
+        SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest => "digest",
+        SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit => "raw",
+        SpdmDmtfMeasurementRepresentation::Unknown(_) => "unknown",
+    };
+    write!(
+        writer,
+        "{{\"index\":{},\"type\":{},\"representation\":\"{}\",\"value\":\"",
+        block.index,
+        measurement.r#type.get_u8(),
+        representation
+    )?;
+    let value_size = (measurement.value_size as usize).min(measurement.value.len());
+    for byte in &measurement.value[..value_size] {
+        write!(writer, "{:02x}", byte)?;
+    }
+    writer.write_str("\"}")
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::protocol::{SpdmDmtfMeasurementStructure, SpdmDmtfMeasurementType};
+
+    fn block() -> SpdmMeasurementBlockStructure {
+        let mut measurement = SpdmDmtfMeasurementStructure {
+            r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware,
+            representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit,
+            value_size: 3,
+            ..Default::default()
+        };
+        measurement.value[..3].copy_from_slice(&[0x0a, 0xbc, 0xff]);
+        SpdmMeasurementBlockStructure {
+            index: 2,
+            measurement,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_case0_dump_measurement_block() {
+        let expected = b"{\"index\":2,\"type\":1,\"representation\":\"raw\",\"value\":\"0abcff\"}";
+        let buf = &mut [0u8; 128];
+        let used = dump_measurement_block(&block(), buf).unwrap();
+        assert_eq!(&buf[..used], &expected[..]);
+
+        // exactly large enough.
+        let buf = &mut [0u8; 128][..expected.len()];
+        assert_eq!(dump_measurement_block(&block(), buf), Ok(expected.len()));
+    }
+
+    #[test]
+    fn test_case1_dump_measurement_block_buffer_full() {
+        let buf = &mut [0u8; 32];
+        assert_eq!(
+            dump_measurement_block(&block(), buf),
+            Err(SPDM_STATUS_BUFFER_FULL)
+        );
+        // the value is cut short too.
+        let buf = &mut [0u8; 55];
+        assert_eq!(
+            dump_measurement_block(&block(), buf),
+            Err(SPDM_STATUS_BUFFER_FULL)
+        );
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::config;
+use crate::error::{SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_INVALID_PARAMETER};
+use crate::protocol::{SpdmMeasurementBlockStructure, SpdmMeasurementRecordStructure};
+use codec::{u24, Codec, Writer};
+
+/// Assembles a `SpdmMeasurementRecordStructure` block by block, keeping
+/// `number_of_blocks` and `measurement_record_length` in step with what
+/// was written into `measurement_record_data`.
+///
+/// Each block is written with its MeasurementSize taken from the value
+/// size of its measurement, and blocks must come in ascending index
+/// order, as GET_MEASUREMENTS responses require.
+#[derive(Debug, Clone, Default)]
+pub struct SpdmMeasurementRecordBuilder {
+    record: SpdmMeasurementRecordStructure,
+    last_index: u8,
+}
+
+impl SpdmMeasurementRecordBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `block` to the record.
+    ///
+    /// SPDM_STATUS_INVALID_PARAMETER if its index is 0 or not above the
+    /// previous one, SPDM_STATUS_BUFFER_FULL if the record has no room
+    /// left for it. The blocks added so far are kept on error.
+    pub fn add_block(&mut self, block: &SpdmMeasurementBlockStructure) -> SpdmResult {
+        if block.index <= self.last_index {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        if self.record.number_of_blocks as usize >= config::MAX_SPDM_MEASUREMENT_BLOCKS {
+            return Err(SPDM_STATUS_BUFFER_FULL);
+        }
+
+        let used = self.record.measurement_record_length.get() as usize;
+        let mut block = block.clone();
+        // Type (1) + ValueSize (2)
+        block.measurement_size = 3 + block.measurement.value_size;
+        let mut writer = Writer::init(&mut self.record.measurement_record_data[used..]);
+        let written = block
+            .encode(&mut writer)
+            .map_err(|_| SPDM_STATUS_BUFFER_FULL)?;
+
+        self.record.number_of_blocks += 1;
+        self.record.measurement_record_length = u24::new((used + written) as u32);
+        self.last_index = block.index;
+        Ok(())
+    }
+
+    pub fn number_of_blocks(&self) -> u8 {
+        self.record.number_of_blocks
+    }
+
+    pub fn build(self) -> SpdmMeasurementRecordStructure {
+        self.record
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::protocol::{
+        SpdmDmtfMeasurementRepresentation, SpdmDmtfMeasurementStructure, SpdmDmtfMeasurementType,
+        SpdmMeasurementSpecification,
+    };
+    use codec::Reader;
+
+    fn block(index: u8, value_size: u16) -> SpdmMeasurementBlockStructure {
+        SpdmMeasurementBlockStructure {
+            index,
+            measurement_specification: SpdmMeasurementSpecification::DMTF,
+            // stale on purpose, the builder recomputes it.
+            measurement_size: 0,
+            measurement: SpdmDmtfMeasurementStructure {
+                r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware,
+                representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit,
+                value_size,
+                value: [index; config::MAX_SPDM_MEASUREMENT_VALUE_LEN],
+            },
+        }
+    }
+
+    #[test]
+    fn test_case0_build_multi_block_record() {
+        let mut builder = SpdmMeasurementRecordBuilder::new();
+        assert!(builder.add_block(&block(1, 8)).is_ok());
+        assert!(builder.add_block(&block(2, 16)).is_ok());
+        assert!(builder.add_block(&block(5, 1)).is_ok());
+        assert_eq!(
+            builder.add_block(&block(5, 1)),
+            Err(SPDM_STATUS_INVALID_PARAMETER)
+        );
+        assert_eq!(builder.number_of_blocks(), 3);
+        let record = builder.build();
+        assert_eq!(
+            record.measurement_record_length.get() as usize,
+            3 * (4 + 3) + 8 + 16 + 1
+        );
+
+        // decode the wire form of the record back.
+        let u8_slice = &mut [0u8; 4 + config::MAX_SPDM_MEASUREMENT_RECORD_SIZE];
+        let mut writer = Writer::init(u8_slice);
+        assert!(record.number_of_blocks.encode(&mut writer).is_ok());
+        assert!(record.measurement_record_length.encode(&mut writer).is_ok());
+        assert!(writer
+            .extend_from_slice(
+                &record.measurement_record_data[..record.measurement_record_length.get() as usize]
+            )
+            .is_some());
+        let used = writer.used();
+
+        let mut reader = Reader::init(&u8_slice[..used]);
+        assert_eq!(u8::read(&mut reader), Some(3));
+        assert_eq!(
+            u24::read(&mut reader).unwrap().get() as usize,
+            reader.left()
+        );
+        for (index, value_size) in [(1u8, 8u16), (2, 16), (5, 1)] {
+            let decoded_block = SpdmMeasurementBlockStructure::read(&mut reader).unwrap();
+            assert_eq!(decoded_block.index, index);
+            assert_eq!(decoded_block.measurement_size, 3 + value_size);
+            assert_eq!(decoded_block.measurement.value_size, value_size);
+            assert_eq!(
+                &decoded_block.measurement.value[..value_size as usize],
+                &[index; 16][..value_size as usize]
+            );
+        }
+        assert!(!reader.any_left());
+    }
+
+    #[test]
+    fn test_case1_record_full() {
+        let mut builder = SpdmMeasurementRecordBuilder::new();
+        let value_size = config::MAX_SPDM_MEASUREMENT_VALUE_LEN as u16;
+        let mut index = 1u8;
+        loop {
+            match builder.add_block(&block(index, value_size)) {
+                Ok(()) => index += 1,
+                Err(status) => {
+                    assert_eq!(status, SPDM_STATUS_BUFFER_FULL);
+                    break;
+                }
+            }
+        }
+        let record = builder.build();
+        assert_eq!(record.number_of_blocks, index - 1);
+        assert_eq!(
+            record.measurement_record_length.get() as usize,
+            (index as usize - 1) * (7 + config::MAX_SPDM_MEASUREMENT_VALUE_LEN)
+        );
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::error::{SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD};
+use crate::message::SpdmRequestResponseCode;
+use crate::protocol::{
+    SpdmDmtfMeasurementRepresentation, SpdmMeasurementRecordStructure, SpdmMeasurementSpecification,
+};
+use codec::{u24, Codec, Reader};
+
+/// The measurement record of a MEASUREMENTS response, borrowed from the
+/// bytes it was received in instead of copied into
+/// `SpdmMeasurementRecordStructure`.
+///
+/// Only NumberOfBlocks and MeasurementRecordLength are checked up front.
+/// Each block is checked when `blocks` gets to it, so a record that turns
+/// out malformed midway still hands out the blocks before.
+#[derive(Debug, Clone, Copy)]
+pub struct SpdmMeasurementRecordView<'a> {
+    number_of_blocks: u8,
+    record: &'a [u8],
+}
+
+/// One measurement block of a `SpdmMeasurementRecordView`, in the DMTF
+/// measurement format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpdmMeasurementBlockView<'a> {
+    pub index: u8,
+    pub measurement_specification: SpdmMeasurementSpecification,
+    // DMTFSpecMeasurementValueType, representation bit included.
+    pub value_type: u8,
+    pub value: &'a [u8],
+    offset: usize,
+    bytes: &'a [u8],
+}
+
+impl<'a> SpdmMeasurementBlockView<'a> {
+    /// The block as received, Index through the end of Measurement, to
+    /// forward or hash it without encoding it again.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Where the block starts in the record, i.e. in `as_bytes` of the
+    /// record view or in `measurement_record_data` for a view made by
+    /// `from_record_structure`.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn representation(&self) -> SpdmDmtfMeasurementRepresentation {
+        if self.value_type & 0x80 == 0 {
+            SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest
+        } else {
+            SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit
+        }
+    }
+}
+
+impl<'a> SpdmMeasurementRecordView<'a> {
+    /// View over `bytes` starting at NumberOfBlocks. Bytes past
+    /// MeasurementRecordLength, the rest of the response, are left alone.
+    pub fn new(bytes: &'a [u8]) -> Option<Self> {
+        let mut reader = Reader::init(bytes);
+        let number_of_blocks = u8::read(&mut reader)?;
+        let record_length = u24::read(&mut reader)?.get() as usize;
+        let record = bytes.get(reader.used()..reader.used() + record_length)?;
+        Some(SpdmMeasurementRecordView {
+            number_of_blocks,
+            record,
+        })
+    }
+
+    /// View over the record of a whole MEASUREMENTS response, SPDM header
+    /// included, as it sits in the receive buffer.
+    pub fn from_measurements_response(response: &'a [u8]) -> Option<Self> {
+        // SPDMVersion (1) + RequestResponseCode (1) + Param1 (1) + Param2 (1)
+        if response.len() < 4
+            || response[1] != SpdmRequestResponseCode::SpdmResponseMeasurements.get_u8()
+        {
+            return None;
+        }
+        Self::new(&response[4..])
+    }
+
+    /// View over a record already copied into its structure, e.g. one
+    /// from the measurement collection callback.
+    pub fn from_record_structure(record: &'a SpdmMeasurementRecordStructure) -> Option<Self> {
+        let record_length = record.measurement_record_length.get() as usize;
+        Some(SpdmMeasurementRecordView {
+            number_of_blocks: record.number_of_blocks,
+            record: record.measurement_record_data.get(..record_length)?,
+        })
+    }
+
+    pub fn number_of_blocks(&self) -> u8 {
+        self.number_of_blocks
+    }
+
+    /// The record as received, MeasurementRecordLength bytes.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.record
+    }
+
+    pub fn blocks(&self) -> SpdmMeasurementBlockIter<'a> {
+        SpdmMeasurementBlockIter {
+            record: self.record,
+            offset: 0,
+            blocks_left: self.number_of_blocks,
+            last_index: 0,
+            done: false,
+        }
+    }
+}
+
+/// Blocks of a `SpdmMeasurementRecordView` in record order. A block that
+/// is malformed, out of index order, or bytes left after the last one
+/// yield SPDM_STATUS_INVALID_MSG_FIELD once, and the iteration ends there.
+#[derive(Debug, Clone)]
+pub struct SpdmMeasurementBlockIter<'a> {
+    record: &'a [u8],
+    offset: usize,
+    blocks_left: u8,
+    last_index: u8,
+    done: bool,
+}
+
+impl<'a> SpdmMeasurementBlockIter<'a> {
+    fn next_block(&mut self) -> Option<SpdmMeasurementBlockView<'a>> {
+        let record: &'a [u8] = self.record;
+        let rest = record.get(self.offset..)?;
+        let mut reader = Reader::init(rest);
+        let index = u8::read(&mut reader)?;
+        let measurement_specification = SpdmMeasurementSpecification::read(&mut reader)?;
+        let measurement_size = u16::read(&mut reader)? as usize;
+        // Index (1) + MeasurementSpecification (1) + MeasurementSize (2)
+        let measurement = rest.get(4..4 + measurement_size)?;
+
+        let mut reader = Reader::init(measurement);
+        let value_type = u8::read(&mut reader)?;
+        let value_size = u16::read(&mut reader)? as usize;
+        // Type (1) + ValueSize (2)
+        if index <= self.last_index || 3 + value_size != measurement_size {
+            return None;
+        }
+
+        let offset = self.offset;
+        self.offset += 4 + measurement_size;
+        self.last_index = index;
+        Some(SpdmMeasurementBlockView {
+            index,
+            measurement_specification,
+            value_type,
+            value: &measurement[3..],
+            offset,
+            bytes: &rest[..4 + measurement_size],
+        })
+    }
+}
+
+impl<'a> Iterator for SpdmMeasurementBlockIter<'a> {
+    type Item = SpdmResult<SpdmMeasurementBlockView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.blocks_left == 0 {
+            self.done = true;
+            if self.offset != self.record.len() {
+                error!("!!! measurement record : bytes after the last block !!!\n");
+                return Some(Err(SPDM_STATUS_INVALID_MSG_FIELD));
+            }
+            return None;
+        }
+        match self.next_block() {
+            Some(block) => {
+                self.blocks_left -= 1;
+                Some(Ok(block))
+            }
+            None => {
+                error!("!!! measurement record : malformed block !!!\n");
+                self.done = true;
+                Some(Err(SPDM_STATUS_INVALID_MSG_FIELD))
+            }
+        }
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::protocol::{
+        SpdmDmtfMeasurementStructure, SpdmDmtfMeasurementType, SpdmMeasurementBlockStructure,
+        SpdmMeasurementRecordBuilder,
+    };
+    use codec::Writer;
+
+    fn record_bytes(buf: &mut [u8]) -> usize {
+        let mut builder = SpdmMeasurementRecordBuilder::new();
+        for (index, value_size) in [(1u8, 8u16), (3, 48)] {
+            let mut measurement = SpdmDmtfMeasurementStructure {
+                r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware,
+                representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest,
+                value_size,
+                ..Default::default()
+            };
+            measurement.value[..value_size as usize].fill(index);
+            let block = SpdmMeasurementBlockStructure {
+                index,
+                measurement_specification: SpdmMeasurementSpecification::DMTF,
+                measurement_size: 0,
+                measurement,
+            };
+            assert!(builder.add_block(&block).is_ok());
+        }
+        let record = builder.build();
+        let record_length = record.measurement_record_length.get() as usize;
+
+        let mut writer = Writer::init(buf);
+        assert!(record.number_of_blocks.encode(&mut writer).is_ok());
+        assert!(record.measurement_record_length.encode(&mut writer).is_ok());
+        assert!(writer
+            .extend_from_slice(&record.measurement_record_data[..record_length])
+            .is_some());
+        writer.used()
+    }
+
+    #[test]
+    fn test_case0_iterate_record_view() {
+        // SPDM header, the record, then a nonce that is not part of it.
+        let response = &mut [0u8; 256];
+        response[..4].copy_from_slice(&[0x12, 0x60, 0, 0]);
+        let used = 4 + record_bytes(&mut response[4..]);
+        response[used..used + 32].fill(0xaa);
+
+        let view =
+            SpdmMeasurementRecordView::from_measurements_response(&response[..used + 32]).unwrap();
+        assert_eq!(view.number_of_blocks(), 2);
+        assert_eq!(view.as_bytes().len(), used - 8);
+
+        let mut blocks = view.blocks();
+        let block = blocks.next().unwrap().unwrap();
+        assert_eq!(block.index, 1);
+        assert_eq!(
+            block.measurement_specification,
+            SpdmMeasurementSpecification::DMTF
+        );
+        assert_eq!(block.value_type, 1);
+        assert_eq!(
+            block.representation(),
+            SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest
+        );
+        assert_eq!(block.value, &[1u8; 8]);
+        let block = blocks.next().unwrap().unwrap();
+        assert_eq!(block.index, 3);
+        assert_eq!(block.value, &[3u8; 48][..]);
+        assert!(blocks.next().is_none());
+        // the values point into the response, nothing was copied.
+        assert!(response.as_ptr_range().contains(&block.value.as_ptr()));
+
+        // a record length cut into the second block.
+        response[5] -= 1;
+        let view = SpdmMeasurementRecordView::new(&response[4..used + 32]).unwrap();
+        let mut blocks = view.blocks();
+        assert!(blocks.next().unwrap().is_ok());
+        assert_eq!(blocks.next(), Some(Err(SPDM_STATUS_INVALID_MSG_FIELD)));
+        assert!(blocks.next().is_none());
+
+        // a record length past the end of the response.
+        response[5] = 0xff;
+        assert!(SpdmMeasurementRecordView::new(&response[4..used]).is_none());
+        assert!(SpdmMeasurementRecordView::from_measurements_response(&response[..3]).is_none());
+    }
+    #[test]
+    fn test_case1_block_bytes_reassemble_record() {
+        let buf = &mut [0u8; 256];
+        let used = record_bytes(buf);
+        let view = SpdmMeasurementRecordView::new(&buf[..used]).unwrap();
+
+        let mut record = [0u8; 256];
+        let mut record_length = 0;
+        for block in view.blocks() {
+            let block = block.unwrap();
+            assert_eq!(block.offset(), record_length);
+            // Index (1) + MeasurementSpecification (1) + MeasurementSize (2)
+            // + Type (1) + ValueSize (2)
+            assert_eq!(block.as_bytes().len(), 7 + block.value.len());
+            assert_eq!(block.as_bytes()[0], block.index);
+            assert!(block.as_bytes().ends_with(block.value));
+            record[record_length..record_length + block.as_bytes().len()]
+                .copy_from_slice(block.as_bytes());
+            record_length += block.as_bytes().len();
+        }
+        assert_eq!(&record[..record_length], view.as_bytes());
+
+        // the same offsets index measurement_record_data of the structure.
+        let mut structure = SpdmMeasurementRecordStructure {
+            number_of_blocks: view.number_of_blocks(),
+            measurement_record_length: u24::new(record_length as u32),
+            ..Default::default()
+        };
+        structure.measurement_record_data[..record_length].copy_from_slice(view.as_bytes());
+        let view = SpdmMeasurementRecordView::from_record_structure(&structure).unwrap();
+        for block in view.blocks() {
+            let block = block.unwrap();
+            let offset = block.offset();
+            assert_eq!(
+                &structure.measurement_record_data[offset..offset + block.as_bytes().len()],
+                block.as_bytes()
+            );
+        }
+    }
+}
@@ -9,9 +9,17 @@ use core::convert::TryInto;
 
 mod algo;
 mod capability;
+mod device_mode;
+mod measurement_dump;
+mod measurement_record_builder;
+mod measurement_record_view;
 mod version;
 pub use algo::*;
 pub use capability::*;
+pub use device_mode::*;
+pub use measurement_dump::*;
+pub use measurement_record_builder::*;
+pub use measurement_record_view::*;
 pub use version::*;
 
 // util function
@@ -11,7 +11,8 @@ enum_builder! {
     EnumVal{
         SpdmVersion10 => 0x10,
         SpdmVersion11 => 0x11,
-        SpdmVersion12 => 0x12
+        SpdmVersion12 => 0x12,
+        SpdmVersion13 => 0x13
     }
 }
 impl Default for SpdmVersion {
@@ -0,0 +1,151 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::error::{
+    SpdmResult, SpdmStatus, SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_NEGOTIATION_FAIL,
+    SPDM_STATUS_UNSUPPORTED_CAP, SPDM_STATUS_VERIF_FAIL,
+};
+use crate::protocol::*;
+use crate::requester::*;
+
+/// What `attest` asks of the responder.
+///
+/// An empty algorithm set keeps the one in `config_info`; a non empty one
+/// replaces it in NEGOTIATE_ALGORITHMS and the selected algorithm has to be
+/// in it, also on a connection negotiated before `attest` was called.
+#[derive(Debug, Clone, Copy)]
+pub struct SpdmAttestationPolicy {
+    pub slot_id: u8,
+    pub measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+    pub base_hash_algo: SpdmBaseHashAlgo,
+    pub base_asym_algo: SpdmBaseAsymAlgo,
+    pub measurement_hash_algo: SpdmMeasurementHashAlgo,
+}
+
+impl Default for SpdmAttestationPolicy {
+    fn default() -> Self {
+        SpdmAttestationPolicy {
+            slot_id: 0,
+            measurement_summary_hash_type:
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            base_hash_algo: SpdmBaseHashAlgo::empty(),
+            base_asym_algo: SpdmBaseAsymAlgo::empty(),
+            measurement_hash_algo: SpdmMeasurementHashAlgo::empty(),
+        }
+    }
+}
+
+/// The exchange `attest` was in when it failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpdmAttestationStep {
+    Version,
+    Capabilities,
+    Algorithms,
+    Digests,
+    Certificate,
+    Challenge,
+    Measurements,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpdmAttestationError {
+    pub step: SpdmAttestationStep,
+    pub status: SpdmStatus,
+}
+
+impl From<SpdmAttestationError> for SpdmStatus {
+    fn from(error: SpdmAttestationError) -> Self {
+        error.status
+    }
+}
+
+trait AttestationStepResult<T> {
+    fn at_step(self, step: SpdmAttestationStep) -> Result<T, SpdmAttestationError>;
+}
+
+impl<T> AttestationStepResult<T> for SpdmResult<T> {
+    fn at_step(self, step: SpdmAttestationStep) -> Result<T, SpdmAttestationError> {
+        self.map_err(|status| {
+            error!("!!! attest : {:?} failed : {:?} !!!\n", step, status);
+            SpdmAttestationError { step, status }
+        })
+    }
+}
+
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Run the standard attestation flow: GET_VERSION, GET_CAPABILITIES and
+    /// NEGOTIATE_ALGORITHMS unless the connection is negotiated already,
+    /// then GET_DIGESTS, GET_CERTIFICATE and CHALLENGE for
+    /// `policy.slot_id`, and a signed read of every measurement block.
+    ///
+    /// Only returns a report whose certificate chain, CHALLENGE_AUTH and
+    /// measurement signatures all verified. The error names the step that
+    /// failed and carries its status.
+    pub fn attest(
+        &mut self,
+        policy: &SpdmAttestationPolicy,
+    ) -> Result<SpdmAttestationReport, SpdmAttestationError> {
+        if policy.slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+            return Err(SPDM_STATUS_INVALID_PARAMETER).at_step(SpdmAttestationStep::Certificate);
+        }
+
+        if self
+            .check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)
+            .is_err()
+        {
+            if !policy.base_hash_algo.is_empty() {
+                self.common.config_info.base_hash_algo = policy.base_hash_algo;
+            }
+            if !policy.base_asym_algo.is_empty() {
+                self.common.config_info.base_asym_algo = policy.base_asym_algo;
+            }
+            if !policy.measurement_hash_algo.is_empty() {
+                self.common.config_info.measurement_hash_algo = policy.measurement_hash_algo;
+            }
+            self.send_receive_spdm_version()
+                .at_step(SpdmAttestationStep::Version)?;
+            self.send_receive_spdm_capability()
+                .at_step(SpdmAttestationStep::Capabilities)?;
+            self.send_receive_spdm_algorithm()
+                .at_step(SpdmAttestationStep::Algorithms)?;
+        }
+        let negotiate_info = &self.common.negotiate_info;
+        if !(policy.base_hash_algo.is_empty()
+            || policy.base_hash_algo.contains(negotiate_info.base_hash_sel))
+            || !(policy.base_asym_algo.is_empty()
+                || policy.base_asym_algo.contains(negotiate_info.base_asym_sel))
+            || !(policy.measurement_hash_algo.is_empty()
+                || policy
+                    .measurement_hash_algo
+                    .contains(negotiate_info.measurement_hash_sel))
+        {
+            return Err(SPDM_STATUS_NEGOTIATION_FAIL).at_step(SpdmAttestationStep::Algorithms);
+        }
+
+        self.send_receive_spdm_digest(None)
+            .at_step(SpdmAttestationStep::Digests)?;
+        self.send_receive_spdm_certificate(None, policy.slot_id)
+            .at_step(SpdmAttestationStep::Certificate)?;
+        self.send_receive_spdm_challenge(policy.slot_id, policy.measurement_summary_hash_type)
+            .at_step(SpdmAttestationStep::Challenge)?;
+
+        if !self
+            .common
+            .negotiate_info
+            .rsp_capabilities_sel
+            .contains(SpdmResponseCapabilityFlags::MEAS_CAP_SIG)
+        {
+            return Err(SPDM_STATUS_UNSUPPORTED_CAP).at_step(SpdmAttestationStep::Measurements);
+        }
+        let report = self
+            .fetch_attestation_report(None, policy.slot_id)
+            .at_step(SpdmAttestationStep::Measurements)?;
+        if !report.signature_verified {
+            return Err(SPDM_STATUS_VERIF_FAIL).at_step(SpdmAttestationStep::Measurements);
+        }
+        Ok(report)
+    }
+}
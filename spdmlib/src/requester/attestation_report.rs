@@ -0,0 +1,138 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+extern crate alloc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::config;
+use crate::protocol::*;
+use codec::{Codec, Reader, Writer};
+
+/// Leading bytes of an encoded `SpdmAttestationReport`.
+pub const SPDM_ATTESTATION_REPORT_MAGIC: [u8; 4] = *b"SPDR";
+
+/// Layout version of an encoded `SpdmAttestationReport`.
+/// Bump it whenever a field is added, removed or reinterpreted.
+pub const SPDM_ATTESTATION_REPORT_VERSION: u8 = 1;
+
+// Magic (4) + ReportVersion (1) + SPDMVersion (1) + SignatureVerified (1) +
+// Reserved (1) + BaseHashAlgo (4) + BaseAsymAlgo (4) + MeasurementHashAlgo (4) +
+// MeasurementSpecification (1) + SlotID (1) + LeafCertLength (2) + NumberOfBlocks (1)
+const SPDM_ATTESTATION_REPORT_HEADER_SIZE: usize = 25;
+
+// Index (1) + MeasurementSpecification (1) + MeasurementSize (2) +
+// DMTFSpecMeasurementValueType (1) + DMTFSpecMeasurementValueSize (2) + Value
+const SPDM_MEASUREMENT_BLOCK_MAX_SIZE: usize = 7 + config::MAX_SPDM_MEASUREMENT_VALUE_LEN;
+
+/// Result of one `fetch_attestation_report` run.
+///
+/// All fields little endian, the blocks in their DSP0274 layout:
+///
+/// | Magic "SPDR" | ReportVersion | SPDMVersion | SignatureVerified | Reserved |
+/// | BaseHashAlgo (4) | BaseAsymAlgo (4) | MeasurementHashAlgo (4) |
+/// | MeasurementSpecification | SlotID | LeafCertLength (2) | LeafCert |
+/// | NumberOfBlocks | MeasurementBlock ... |
+#[derive(Debug, Clone, Default)]
+pub struct SpdmAttestationReport {
+    pub spdm_version: SpdmVersion,
+    pub base_hash_algo: SpdmBaseHashAlgo,
+    pub base_asym_algo: SpdmBaseAsymAlgo,
+    pub measurement_specification: SpdmMeasurementSpecification,
+    pub measurement_hash_algo: SpdmMeasurementHashAlgo,
+    pub slot_id: u8,
+    // DER of the leaf certificate of the chain in slot_id.
+    pub leaf_cert: Vec<u8>,
+    // empty if the signature did not verify.
+    pub measurement_blocks: Vec<SpdmMeasurementBlockStructure>,
+    pub signature_verified: bool,
+}
+
+impl SpdmAttestationReport {
+    /// Encode the report. Returns None if the leaf certificate or the block
+    /// list does not fit its length field.
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        if self.leaf_cert.len() > u16::MAX as usize
+            || self.measurement_blocks.len() > u8::MAX as usize
+        {
+            return None;
+        }
+
+        let mut bytes = vec![
+            0u8;
+            SPDM_ATTESTATION_REPORT_HEADER_SIZE
+                + self.leaf_cert.len()
+                + self.measurement_blocks.len() * SPDM_MEASUREMENT_BLOCK_MAX_SIZE
+        ];
+        let mut writer = Writer::init(&mut bytes);
+        writer.extend_from_slice(&SPDM_ATTESTATION_REPORT_MAGIC)?;
+        SPDM_ATTESTATION_REPORT_VERSION.encode(&mut writer).ok()?;
+        self.spdm_version.encode(&mut writer).ok()?;
+        (self.signature_verified as u8).encode(&mut writer).ok()?;
+        0u8.encode(&mut writer).ok()?; // reserved
+        self.base_hash_algo.encode(&mut writer).ok()?;
+        self.base_asym_algo.encode(&mut writer).ok()?;
+        self.measurement_hash_algo.encode(&mut writer).ok()?;
+        self.measurement_specification.encode(&mut writer).ok()?;
+        self.slot_id.encode(&mut writer).ok()?;
+        (self.leaf_cert.len() as u16).encode(&mut writer).ok()?;
+        writer.extend_from_slice(&self.leaf_cert)?;
+        (self.measurement_blocks.len() as u8)
+            .encode(&mut writer)
+            .ok()?;
+        for measurement_block in self.measurement_blocks.iter() {
+            measurement_block.encode(&mut writer).ok()?;
+        }
+
+        let used = writer.used();
+        bytes.truncate(used);
+        Some(bytes)
+    }
+
+    /// Parse a report produced by `to_bytes`. Returns None on a foreign
+    /// magic or version, a truncated blob or trailing bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Option<SpdmAttestationReport> {
+        let mut reader = Reader::init(bytes);
+        if reader.take(SPDM_ATTESTATION_REPORT_MAGIC.len())? != SPDM_ATTESTATION_REPORT_MAGIC {
+            return None;
+        }
+        if u8::read(&mut reader)? != SPDM_ATTESTATION_REPORT_VERSION {
+            return None;
+        }
+        let spdm_version = SpdmVersion::read(&mut reader)?;
+        let signature_verified = match u8::read(&mut reader)? {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        u8::read(&mut reader)?; // reserved
+        let base_hash_algo = SpdmBaseHashAlgo::read(&mut reader)?;
+        let base_asym_algo = SpdmBaseAsymAlgo::read(&mut reader)?;
+        let measurement_hash_algo = SpdmMeasurementHashAlgo::read(&mut reader)?;
+        let measurement_specification = SpdmMeasurementSpecification::read(&mut reader)?;
+        let slot_id = u8::read(&mut reader)?;
+        let leaf_cert_length = u16::read(&mut reader)?;
+        let leaf_cert = reader.take(leaf_cert_length as usize)?.to_vec();
+        let number_of_blocks = u8::read(&mut reader)?;
+        let mut measurement_blocks = Vec::with_capacity(number_of_blocks as usize);
+        for _ in 0..number_of_blocks {
+            measurement_blocks.push(SpdmMeasurementBlockStructure::read(&mut reader)?);
+        }
+        if reader.any_left() {
+            return None;
+        }
+
+        Some(SpdmAttestationReport {
+            spdm_version,
+            base_hash_algo,
+            base_asym_algo,
+            measurement_specification,
+            measurement_hash_algo,
+            slot_id,
+            leaf_cert,
+            measurement_blocks,
+            signature_verified,
+        })
+    }
+}
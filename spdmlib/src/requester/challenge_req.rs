@@ -7,42 +7,55 @@ use crate::crypto;
 use crate::error::SPDM_STATUS_INVALID_STATE_LOCAL;
 use crate::error::{
     SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_ERROR_PEER,
-    SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_VERIF_FAIL,
+    SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER,
+    SPDM_STATUS_VERIF_FAIL,
 };
 use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_challenge(
         &mut self,
         slot_id: u8,
         measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
     ) -> SpdmResult {
-        info!("send spdm challenge\n");
+        self.timed(SpdmRequesterOperation::Challenge, |this| {
+            info!("send spdm challenge\n");
 
-        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        }
+            this.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
 
-        self.common
-            .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestChallenge, None);
+            if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
+            }
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let send_used =
-            self.encode_spdm_challenge(slot_id, measurement_summary_hash_type, &mut send_buffer)?;
-        self.send_message(&send_buffer[..send_used])?;
+            this.common
+                .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestChallenge, None);
 
-        // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let used = self.receive_message(&mut receive_buffer, true)?;
-        self.handle_spdm_challenge_response(
-            0, // NULL
-            slot_id,
-            measurement_summary_hash_type,
-            &send_buffer[..send_used],
-            &receive_buffer[..used],
-        )
+            let mut send_buffer = [0u8; MSG_SIZE];
+            let send_used = this.encode_spdm_challenge(
+                slot_id,
+                measurement_summary_hash_type,
+                &mut send_buffer,
+            )?;
+            this.send_message(&send_buffer[..send_used])?;
+
+            // Receive
+            let mut receive_buffer = [0u8; MSG_SIZE];
+            let used = this.receive_message(&mut receive_buffer, true)?;
+            this.handle_spdm_challenge_response(
+                0, // NULL
+                slot_id,
+                measurement_summary_hash_type,
+                &send_buffer[..send_used],
+                &receive_buffer[..used],
+            )?;
+            this.advance_connection_state(SpdmConnectionState::SpdmConnectionAuthenticated)?;
+            Ok(())
+        })
     }
 
     pub fn encode_spdm_challenge(
@@ -168,14 +181,11 @@ impl<'a> RequesterContext<'a> {
             return Err(SPDM_STATUS_INVALID_PARAMETER);
         }
 
-        let cert_chain_data = &self.common.peer_info.peer_cert_chain[slot_id as usize]
+        let cert_chain_data = self.common.peer_info.peer_cert_chain[slot_id as usize]
             .as_ref()
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-            .data[(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-            ..(self.common.peer_info.peer_cert_chain[slot_id as usize]
-                .as_ref()
-                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-                .data_size as usize)];
+            .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+            .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
         let mut message_sign = ManagedBuffer12Sign::default();
 
@@ -240,14 +250,11 @@ impl<'a> RequesterContext<'a> {
             return Err(SPDM_STATUS_INVALID_PARAMETER);
         }
 
-        let cert_chain_data = &self.common.peer_info.peer_cert_chain[slot_id as usize]
+        let cert_chain_data = self.common.peer_info.peer_cert_chain[slot_id as usize]
             .as_ref()
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-            .data[(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-            ..(self.common.peer_info.peer_cert_chain[slot_id as usize]
-                .as_ref()
-                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-                .data_size as usize)];
+            .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+            .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
         if self.common.negotiate_info.spdm_version_sel.get_u8()
             >= SpdmVersion::SpdmVersion12.get_u8()
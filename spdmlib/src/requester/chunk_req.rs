@@ -0,0 +1,239 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::config;
+use crate::error::{
+    SpdmResult, SPDM_STATUS_BUFFER_TOO_SMALL, SPDM_STATUS_ERROR_PEER,
+    SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_MSG_SIZE, SPDM_STATUS_SEND_FAIL,
+};
+use crate::message::*;
+use crate::requester::*;
+
+// Chunking sits between the request handlers and the transport: a request
+// larger than the DataTransferSize of the responder goes out as CHUNK_SEND,
+// and a response announced with ERROR(LargeResponse) is fetched with
+// CHUNK_GET, so the handlers only ever see whole messages.
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Send `send_buffer`, too large for the responder to take in one
+    /// transfer, as CHUNK_SEND. The response carried by the last
+    /// CHUNK_SEND_ACK is kept for the next `receive_message`.
+    ///
+    /// A chunk is staged in a `config::MAX_SPDM_MSG_SIZE` buffer, so a
+    /// message larger than that is refused even for a larger `MSG_SIZE`.
+    pub(crate) fn send_message_in_chunks(
+        &mut self,
+        session_id: Option<u32>,
+        send_buffer: &[u8],
+    ) -> SpdmResult {
+        let data_transfer_size = self.common.negotiate_info.rsp_data_transfer_size_sel as usize;
+        if !self.common.negotiate_info.chunk_supported()
+            || send_buffer.len() > self.common.negotiate_info.rsp_max_spdm_msg_size_sel as usize
+            || send_buffer.len() > config::MAX_SPDM_MSG_SIZE
+            || data_transfer_size < SPDM_CHUNK_HEADER_SIZE + SPDM_CHUNK_LARGE_MESSAGE_SIZE_SIZE
+        {
+            return Err(SPDM_STATUS_SEND_FAIL);
+        }
+
+        let handle = self.chunk_handle;
+        self.chunk_handle = self.chunk_handle.wrapping_add(1);
+
+        let mut offset = 0usize;
+        let mut chunk_seq_no = 0u16;
+        loop {
+            let mut chunk_capacity = data_transfer_size - SPDM_CHUNK_HEADER_SIZE;
+            if chunk_seq_no == 0 {
+                chunk_capacity -= SPDM_CHUNK_LARGE_MESSAGE_SIZE_SIZE;
+            }
+            let chunk_size = chunk_capacity.min(send_buffer.len() - offset);
+            let last_chunk = offset + chunk_size == send_buffer.len();
+
+            let mut payload = SpdmChunkPayload {
+                handle,
+                chunk_seq_no,
+                chunk_size: chunk_size as u32,
+                large_message_size: send_buffer.len() as u32,
+                ..Default::default()
+            };
+            if last_chunk {
+                payload.attributes = SpdmChunkAttributes::LAST_CHUNK;
+            }
+            payload.chunk[..chunk_size].copy_from_slice(&send_buffer[offset..offset + chunk_size]);
+            let request = SpdmMessage {
+                header: SpdmMessageHeader {
+                    version: self.common.negotiate_info.spdm_version_sel,
+                    request_response_code: SpdmRequestResponseCode::SpdmRequestChunkSend,
+                },
+                payload: SpdmMessagePayload::SpdmChunkSendRequest(payload),
+            };
+            let mut chunk_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let mut writer = Writer::init(&mut chunk_buffer);
+            let used = request.spdm_encode(&mut self.common, &mut writer)?;
+            self.send_single_message(session_id, &chunk_buffer[..used])?;
+
+            let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = self.receive_single_message(session_id, &mut receive_buffer, false)?;
+            let mut reader = Reader::init(&receive_buffer[..used]);
+            match SpdmMessageHeader::read(&mut reader) {
+                Some(message_header)
+                    if message_header.request_response_code
+                        == SpdmRequestResponseCode::SpdmResponseChunkSendAck =>
+                {
+                    let chunk_send_ack =
+                        SpdmChunkSendAckResponsePayload::spdm_read(&mut self.common, &mut reader)
+                            .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+                    if chunk_send_ack.handle != handle
+                        || chunk_send_ack.chunk_seq_no != chunk_seq_no
+                    {
+                        return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                    }
+                    if last_chunk
+                        || chunk_send_ack
+                            .attributes
+                            .contains(SpdmChunkSendAckAttributes::EARLY_ERROR_DETECTED)
+                    {
+                        let response_size = chunk_send_ack.response_size as usize;
+                        if response_size == 0 {
+                            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                        }
+                        self.chunk_send_ack_response =
+                            Some(chunk_send_ack.response[..response_size].to_vec());
+                        return Ok(());
+                    }
+                }
+                // Left to the handler of the request to report.
+                Some(message_header)
+                    if message_header.request_response_code
+                        == SpdmRequestResponseCode::SpdmResponseError =>
+                {
+                    self.chunk_send_ack_response = Some(receive_buffer[..used].to_vec());
+                    return Ok(());
+                }
+                _ => return Err(SPDM_STATUS_INVALID_MSG_FIELD),
+            }
+
+            offset += chunk_size;
+            chunk_seq_no = chunk_seq_no.checked_add(1).ok_or(SPDM_STATUS_SEND_FAIL)?;
+        }
+    }
+
+    /// Receive the next response, whole: the one kept from CHUNK_SEND_ACK
+    /// if any, and one announced with ERROR(LargeResponse) fetched with
+    /// CHUNK_GET.
+    pub(crate) fn receive_whole_message(
+        &mut self,
+        session_id: Option<u32>,
+        receive_buffer: &mut [u8],
+        crypto_request: bool,
+    ) -> SpdmResult<usize> {
+        let used = match self.chunk_send_ack_response.take() {
+            Some(response) => {
+                if response.len() > receive_buffer.len() {
+                    return Err(SPDM_STATUS_BUFFER_TOO_SMALL);
+                }
+                receive_buffer[..response.len()].copy_from_slice(&response);
+                response.len()
+            }
+            None => self.receive_single_message(session_id, receive_buffer, crypto_request)?,
+        };
+
+        if !self.common.negotiate_info.chunk_supported() {
+            return Ok(used);
+        }
+        match self.large_response_handle(&receive_buffer[..used]) {
+            Some(handle) => self.receive_message_in_chunks(session_id, handle, receive_buffer),
+            None => Ok(used),
+        }
+    }
+
+    fn large_response_handle(&mut self, message: &[u8]) -> Option<u8> {
+        let mut reader = Reader::init(message);
+        let message_header = SpdmMessageHeader::read(&mut reader)?;
+        if message_header.request_response_code != SpdmRequestResponseCode::SpdmResponseError {
+            return None;
+        }
+        let error = SpdmErrorResponsePayload::spdm_read(&mut self.common, &mut reader)?;
+        match error.extended_data {
+            SpdmErrorResponseExtData::SpdmErrorExtDataLargeResponse(extended_data) => {
+                Some(extended_data.handle)
+            }
+            _ => None,
+        }
+    }
+
+    fn receive_message_in_chunks(
+        &mut self,
+        session_id: Option<u32>,
+        handle: u8,
+        receive_buffer: &mut [u8],
+    ) -> SpdmResult<usize> {
+        let mut large_message_size = 0usize;
+        let mut offset = 0usize;
+        let mut chunk_seq_no = 0u16;
+        loop {
+            let request = SpdmMessage {
+                header: SpdmMessageHeader {
+                    version: self.common.negotiate_info.spdm_version_sel,
+                    request_response_code: SpdmRequestResponseCode::SpdmRequestChunkGet,
+                },
+                payload: SpdmMessagePayload::SpdmChunkGetRequest(SpdmChunkGetRequestPayload {
+                    handle,
+                    chunk_seq_no,
+                }),
+            };
+            let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let mut writer = Writer::init(&mut send_buffer);
+            let used = request.spdm_encode(&mut self.common, &mut writer)?;
+            self.send_single_message(session_id, &send_buffer[..used])?;
+
+            let mut chunk_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+            let used = self.receive_single_message(session_id, &mut chunk_buffer, false)?;
+            let mut reader = Reader::init(&chunk_buffer[..used]);
+            let message_header =
+                SpdmMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+            match message_header.request_response_code {
+                SpdmRequestResponseCode::SpdmResponseChunkResponse => {}
+                SpdmRequestResponseCode::SpdmResponseError => return Err(SPDM_STATUS_ERROR_PEER),
+                _ => return Err(SPDM_STATUS_INVALID_MSG_FIELD),
+            }
+            let chunk = SpdmChunkPayload::spdm_read(&mut self.common, &mut reader)
+                .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+            if chunk.handle != handle || chunk.chunk_seq_no != chunk_seq_no {
+                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+            }
+            if chunk_seq_no == 0 {
+                // the responder may not send more than our MaxSPDMmsgSize.
+                large_message_size = chunk.large_message_size as usize;
+                if large_message_size
+                    > self.common.negotiate_info.req_max_spdm_msg_size_sel as usize
+                {
+                    return Err(SPDM_STATUS_INVALID_MSG_SIZE);
+                }
+                if large_message_size > receive_buffer.len() {
+                    return Err(SPDM_STATUS_BUFFER_TOO_SMALL);
+                }
+            }
+            let chunk_size = chunk.chunk_size as usize;
+            if offset + chunk_size > large_message_size {
+                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+            }
+            receive_buffer[offset..offset + chunk_size].copy_from_slice(&chunk.chunk[..chunk_size]);
+            offset += chunk_size;
+
+            if chunk.attributes.contains(SpdmChunkAttributes::LAST_CHUNK) {
+                if offset != large_message_size {
+                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                }
+                return Ok(large_message_size);
+            }
+            if chunk_size == 0 {
+                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+            }
+            chunk_seq_no = chunk_seq_no
+                .checked_add(1)
+                .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+        }
+    }
+}
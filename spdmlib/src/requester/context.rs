@@ -2,23 +2,126 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+extern crate alloc;
+use alloc::vec::Vec;
+
 use crate::common::ST1;
-use crate::common::{self, SpdmDeviceIo, SpdmTransportEncap};
+use crate::common::{self, SpdmConnectionState, SpdmDeviceIo, SpdmTransportEncap};
 use crate::config;
-use crate::error::{SpdmResult, SPDM_STATUS_RECEIVE_FAIL, SPDM_STATUS_SEND_FAIL};
+use crate::error::{
+    SpdmResult, SPDM_STATUS_BUFFER_TOO_SMALL, SPDM_STATUS_INVALID_STATE_LOCAL,
+    SPDM_STATUS_INVALID_STATE_PEER,
+};
 use crate::protocol::*;
+#[cfg(feature = "std")]
+use crate::requester::SpdmRequesterMetrics;
+use crate::requester::SpdmRequesterOperation;
+
+/// Transport buffer size of a `RequesterContext` with the default sizes, the
+/// larger of the configured sender and receiver buffers.
+pub const DEFAULT_TRANSPORT_BUFFER_SIZE: usize =
+    if config::SENDER_BUFFER_SIZE > config::RECEIVER_BUFFER_SIZE {
+        config::SENDER_BUFFER_SIZE
+    } else {
+        config::RECEIVER_BUFFER_SIZE
+    };
 
-pub struct RequesterContext<'a> {
+/// `MSG_SIZE` sizes the buffers holding one SPDM message and `TRANSPORT_SIZE`
+/// the ones holding it with its transport header, so a requester can be
+/// given smaller or larger buffers than `config::MAX_SPDM_MSG_SIZE` without
+/// rebuilding the crate; `new` uses the configured sizes and
+/// `new_with_buffer_size` any other. `TRANSPORT_SIZE` has to leave room for
+/// the transport overhead of a `MSG_SIZE` message. Secured messages are in
+/// addition encrypted in `SpdmContext` scratch buffers of the configured
+/// size, which bounds messages within a session.
+pub struct RequesterContext<
+    'a,
+    const MSG_SIZE: usize = { config::MAX_SPDM_MSG_SIZE },
+    const TRANSPORT_SIZE: usize = { DEFAULT_TRANSPORT_BUFFER_SIZE },
+> {
     pub common: common::SpdmContext<'a>,
+    /// Parse responses without checking signatures or certificate chains
+    /// or recording them in the transcripts, for tools inspecting captured
+    /// traffic without a crypto backend. Nothing decoded in this mode is
+    /// authenticated, chains are marked `SpdmCertChainTrust::Unverified`.
+    pub decode_only: bool,
+    /// Set once a MEASUREMENTS signature verifies, cleared by every other
+    /// MEASUREMENTS response, so it stays false for unsigned responses and
+    /// in `decode_only` mode.
+    pub last_measurement_verified: bool,
+    /// Set when SIGNATURE_REQUESTED was dropped from the last measurement
+    /// request because the responder lacks MEAS_CAP_SIG and
+    /// `unsigned_measurement_fallback` is enabled.
+    pub last_measurement_signature_dropped: bool,
+    /// Responder nonces of the last MEASUREMENTS responses accepted
+    /// outside a session, each session keeps its own. One seen again is a
+    /// replayed response or a broken random number generator.
+    pub(crate) measurement_nonces: common::SpdmNonceHistory,
+    /// NumberOfMeasurements of the last QueryTotalNumber, the range single
+    /// measurement indices are checked against before they are requested.
+    pub(crate) peer_measurement_total_number: Option<u8>,
+    /// Handle of the next large request sent as CHUNK_SEND.
+    pub(crate) chunk_handle: u8,
+    /// Response carried by the last CHUNK_SEND_ACK, not received yet.
+    pub(crate) chunk_send_ack_response: Option<Vec<u8>>,
+    /// Own certificate slot FINISH presents when the responder asks for
+    /// mutual authentication without the encapsulated flow, set by
+    /// `select_mut_auth_slot`. None keeps the slot KEY_EXCHANGE_RSP named.
+    pub(crate) mut_auth_slot_id: Option<u8>,
+    /// How long the high level methods took, from `init_connection` to
+    /// `end_session`.
+    #[cfg(feature = "std")]
+    pub metrics: SpdmRequesterMetrics,
 }
 
 impl<'a> RequesterContext<'a> {
+    /// An empty `config_info.req_capabilities` is replaced by
+    /// `SpdmConfigInfo::derive_req_capabilities`; set it to advertise a
+    /// different set.
     pub fn new(
         device_io: &'a mut dyn SpdmDeviceIo,
         transport_encap: &'a mut dyn SpdmTransportEncap,
         config_info: common::SpdmConfigInfo,
         provision_info: common::SpdmProvisionInfo,
     ) -> Self {
+        Self::new_with_buffer_size(device_io, transport_encap, config_info, provision_info)
+    }
+
+    /// `new` with the transcript pre-loaded from a fixture taken by
+    /// `SpdmContext::export_transcript`.
+    #[cfg(all(
+        feature = "transcript-fixture",
+        not(feature = "hashed-transcript-data")
+    ))]
+    pub fn new_with_transcript(
+        device_io: &'a mut dyn SpdmDeviceIo,
+        transport_encap: &'a mut dyn SpdmTransportEncap,
+        config_info: common::SpdmConfigInfo,
+        provision_info: common::SpdmProvisionInfo,
+        fixture: &common::transcript_fixture::SpdmTranscriptFixture,
+    ) -> SpdmResult<Self> {
+        let mut context = Self::new(device_io, transport_encap, config_info, provision_info);
+        context.common.import_transcript(fixture)?;
+        Ok(context)
+    }
+}
+
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// `new` for a requester with other than the configured buffer sizes,
+    /// e.g. `RequesterContext::<1024, 1088>::new_with_buffer_size(..)`.
+    /// `config_info.data_transfer_size` and `max_spdm_msg_size` should not
+    /// exceed `MSG_SIZE`, the responder relies on them.
+    pub fn new_with_buffer_size(
+        device_io: &'a mut dyn SpdmDeviceIo,
+        transport_encap: &'a mut dyn SpdmTransportEncap,
+        mut config_info: common::SpdmConfigInfo,
+        provision_info: common::SpdmProvisionInfo,
+    ) -> Self {
+        if config_info.req_capabilities.is_empty() {
+            config_info.req_capabilities = config_info.derive_req_capabilities(&provision_info);
+        }
         RequesterContext {
             common: common::SpdmContext::new(
                 device_io,
@@ -26,53 +129,164 @@ impl<'a> RequesterContext<'a> {
                 config_info,
                 provision_info,
             ),
+            decode_only: false,
+            last_measurement_verified: false,
+            last_measurement_signature_dropped: false,
+            measurement_nonces: common::SpdmNonceHistory::default(),
+            peer_measurement_total_number: None,
+            chunk_handle: 0,
+            chunk_send_ack_response: None,
+            mut_auth_slot_id: None,
+            #[cfg(feature = "std")]
+            metrics: SpdmRequesterMetrics::default(),
         }
     }
 
+    #[cfg(feature = "std")]
+    pub(crate) fn timed<T>(
+        &mut self,
+        operation: SpdmRequesterOperation,
+        f: impl FnOnce(&mut Self) -> SpdmResult<T>,
+    ) -> SpdmResult<T> {
+        let start = std::time::Instant::now();
+        let result = f(self);
+        self.metrics
+            .record(operation, start.elapsed(), result.is_ok());
+        result
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn timed<T>(
+        &mut self,
+        _operation: SpdmRequesterOperation,
+        f: impl FnOnce(&mut Self) -> SpdmResult<T>,
+    ) -> SpdmResult<T> {
+        f(self)
+    }
+
+    /// Requires no prior state; leaves the connection `SpdmConnectionNegotiated`.
     pub fn init_connection(&mut self) -> SpdmResult {
-        self.send_receive_spdm_version()?;
-        self.send_receive_spdm_capability()?;
-        self.send_receive_spdm_algorithm()
+        self.timed(SpdmRequesterOperation::InitConnection, |this| {
+            this.send_receive_spdm_version()?;
+            this.send_receive_spdm_capability()?;
+            this.send_receive_spdm_algorithm()
+        })
     }
 
+    /// Abandon the connection, e.g. after the responder broke the protocol,
+    /// so it can be negotiated again with `init_connection`: negotiated
+    /// parameters, connection and session state, transcripts and peer data
+    /// go back to how `new` left them. Configuration, provisioning and the
+    /// registered crypto backends are kept, nothing is sent.
+    pub fn reset(&mut self) {
+        self.common.clear_peer_data();
+        self.last_measurement_verified = false;
+        self.measurement_nonces = common::SpdmNonceHistory::default();
+        self.peer_measurement_total_number = None;
+        self.chunk_send_ack_response = None;
+    }
+
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn start_session(
         &mut self,
         use_psk: bool,
         slot_id: u8,
         measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
     ) -> SpdmResult<u32> {
-        if !use_psk {
-            let session_id =
-                self.send_receive_spdm_key_exchange(slot_id, measurement_summary_hash_type)?;
-            #[cfg(not(feature = "mut-auth"))]
-            let req_slot_id: Option<u8> = None;
-            #[cfg(feature = "mut-auth")]
-            self.session_based_mutual_authenticate(session_id)?;
-            #[cfg(feature = "mut-auth")]
-            let req_slot_id = Some(self.common.runtime_info.get_local_used_cert_chain_slot_id());
-            self.send_receive_spdm_finish(req_slot_id, session_id)?;
-            Ok(session_id)
-        } else {
-            let session_id =
-                self.send_receive_spdm_psk_exchange(measurement_summary_hash_type, None)?;
-            self.send_receive_spdm_psk_finish(session_id)?;
-            Ok(session_id)
-        }
+        self.timed(SpdmRequesterOperation::StartSession, |this| {
+            if !use_psk {
+                let session_id =
+                    this.send_receive_spdm_key_exchange(slot_id, measurement_summary_hash_type)?;
+                #[cfg(not(feature = "mut-auth"))]
+                let req_slot_id: Option<u8> = None;
+                #[cfg(feature = "mut-auth")]
+                this.session_based_mutual_authenticate(session_id)?;
+                #[cfg(feature = "mut-auth")]
+                let req_slot_id = Some(this.finish_req_slot_id(session_id)?);
+                this.send_receive_spdm_finish(req_slot_id, session_id)?;
+                Ok(session_id)
+            } else {
+                let session_id =
+                    this.send_receive_spdm_psk_exchange(measurement_summary_hash_type, None)?;
+                this.send_receive_spdm_psk_finish(session_id)?;
+                Ok(session_id)
+            }
+        })
     }
 
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn end_session(&mut self, session_id: u32) -> SpdmResult {
-        self.send_receive_spdm_end_session(session_id)
+        self.timed(SpdmRequesterOperation::EndSession, |this| {
+            this.send_receive_spdm_end_session(session_id)
+        })
     }
 
+    /// Fail with SPDM_STATUS_INVALID_STATE_LOCAL, before anything is sent,
+    /// if the connection has not reached `minimum_state` yet.
+    pub fn check_connection_state(&self, minimum_state: SpdmConnectionState) -> SpdmResult {
+        if self.common.runtime_info.get_connection_state().get_u8() < minimum_state.get_u8() {
+            error!(
+                "!!! connection state {:?} below {:?} !!!\n",
+                self.common.runtime_info.get_connection_state(),
+                minimum_state
+            );
+            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+        }
+        Ok(())
+    }
+
+    /// Fail with SPDM_STATUS_INVALID_STATE_LOCAL, before anything is sent,
+    /// if the connection cannot move to `next_state` from where it is.
+    pub fn check_connection_transition(&self, next_state: SpdmConnectionState) -> SpdmResult {
+        let state = self.common.runtime_info.get_connection_state();
+        if !state.can_transition_to(next_state) {
+            error!(
+                "!!! connection state {:?} cannot move to {:?} !!!\n",
+                state, next_state
+            );
+            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+        }
+        Ok(())
+    }
+
+    // Same transitions as the responder makes when it sends the response.
+    pub(crate) fn advance_connection_state(&mut self, state: SpdmConnectionState) -> SpdmResult {
+        if self.common.runtime_info.get_connection_state().get_u8() < state.get_u8() {
+            self.common
+                .runtime_info
+                .transition_connection_state(state)?;
+        }
+        Ok(())
+    }
+
+    /// The largest SPDM messages this requester sends and receives without
+    /// chunking, in or out of a session, as bounded by its buffers.
+    pub fn get_message_size_limit(
+        &mut self,
+        secured_message: bool,
+    ) -> common::SpdmMessageSizeLimit {
+        let limit = self.common.get_message_size_limit(
+            true,
+            secured_message,
+            TRANSPORT_SIZE,
+            TRANSPORT_SIZE,
+        );
+        common::SpdmMessageSizeLimit {
+            max_send_size: limit.max_send_size.min(MSG_SIZE),
+            max_receive_size: limit.max_receive_size.min(MSG_SIZE),
+        }
+    }
+
+    /// A message larger than the DataTransferSize of the responder is sent
+    /// in chunks when both sides support it, else fails with
+    /// SPDM_STATUS_SEND_FAIL.
     pub fn send_message(&mut self, send_buffer: &[u8]) -> SpdmResult {
         if self.common.negotiate_info.rsp_data_transfer_size_sel != 0
             && send_buffer.len() > self.common.negotiate_info.rsp_data_transfer_size_sel as usize
         {
-            return Err(SPDM_STATUS_SEND_FAIL);
+            return self.send_message_in_chunks(None, send_buffer);
         }
-        let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
-        let used = self.common.encap(send_buffer, &mut transport_buffer)?;
-        self.common.device_io.send(&transport_buffer[..used])
+        self.send_single_message(None, send_buffer)
     }
 
     pub fn send_secured_message(
@@ -85,9 +299,9 @@ impl<'a> RequesterContext<'a> {
             && self.common.negotiate_info.rsp_data_transfer_size_sel != 0
             && (send_buffer.len() > self.common.negotiate_info.rsp_data_transfer_size_sel as usize)
         {
-            return Err(SPDM_STATUS_SEND_FAIL);
+            return self.send_message_in_chunks(Some(session_id), send_buffer);
         }
-        let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
+        let mut transport_buffer = [0u8; TRANSPORT_SIZE];
         let used = self.common.encode_secured_message(
             session_id,
             send_buffer,
@@ -98,29 +312,101 @@ impl<'a> RequesterContext<'a> {
         self.common.device_io.send(&transport_buffer[..used])
     }
 
-    pub fn receive_message(
+    // One SPDM message, in or out of `session_id`, as is.
+    pub(crate) fn send_single_message(
         &mut self,
+        session_id: Option<u32>,
+        send_buffer: &[u8],
+    ) -> SpdmResult {
+        let mut transport_buffer = [0u8; TRANSPORT_SIZE];
+        let used = match session_id {
+            Some(session_id) => self.common.encode_secured_message(
+                session_id,
+                send_buffer,
+                &mut transport_buffer,
+                true,
+                false,
+            )?,
+            None => self.common.encap(send_buffer, &mut transport_buffer)?,
+        };
+        self.common.device_io.send(&transport_buffer[..used])
+    }
+
+    /// Send a message of `session_id` in the clear or secured, as
+    /// `SpdmContext::is_handshake_in_the_clear` says.
+    pub fn send_handshake_message(&mut self, session_id: u32, send_buffer: &[u8]) -> SpdmResult {
+        if self.common.is_handshake_in_the_clear(session_id) {
+            self.send_message(send_buffer)
+        } else {
+            self.send_secured_message(session_id, send_buffer, false)
+        }
+    }
+
+    /// Receive the response to `send_handshake_message`. One protected
+    /// other than negotiated fails with SPDM_STATUS_INVALID_STATE_PEER, not
+    /// with a decap or AEAD failure.
+    pub fn receive_handshake_message(
+        &mut self,
+        session_id: u32,
         receive_buffer: &mut [u8],
         crypto_request: bool,
     ) -> SpdmResult<usize> {
-        info!("receive_message!\n");
-
+        let in_clear_text = self.common.is_handshake_in_the_clear(session_id);
         let timeout: usize = if crypto_request {
             2 << self.common.negotiate_info.rsp_ct_exponent_sel
         } else {
             ST1
         };
 
-        let mut transport_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+        let mut transport_buffer = [0u8; TRANSPORT_SIZE];
         let used = self
             .common
             .device_io
-            .receive(&mut transport_buffer, timeout)
-            .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)?;
+            .receive_with_timeout(&mut transport_buffer, timeout)?;
+
+        let mut spdm_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
+        let (spdm_used, secured_message) = self
+            .common
+            .transport_encap
+            .decap(&transport_buffer[..used], &mut spdm_buffer)?;
+        if secured_message == in_clear_text {
+            error!(
+                "!!! secured {:?} response, handshake in the clear {:?} !!!\n",
+                secured_message, in_clear_text
+            );
+            return Err(SPDM_STATUS_INVALID_STATE_PEER);
+        }
 
-        self.common.decap(&transport_buffer[..used], receive_buffer)
+        if secured_message {
+            return self.common.decode_secured_record(
+                session_id,
+                &spdm_buffer[..spdm_used],
+                receive_buffer,
+            );
+        }
+        if spdm_used > receive_buffer.len() {
+            return Err(SPDM_STATUS_BUFFER_TOO_SMALL);
+        }
+        receive_buffer[..spdm_used].copy_from_slice(&spdm_buffer[..spdm_used]);
+        #[cfg(feature = "alloc")]
+        self.common.message_capture.record(
+            common::SpdmCaptureDirection::Received,
+            &receive_buffer[..spdm_used],
+        );
+        Ok(spdm_used)
     }
 
+    /// A response sent in chunks is received whole.
+    pub fn receive_message(
+        &mut self,
+        receive_buffer: &mut [u8],
+        crypto_request: bool,
+    ) -> SpdmResult<usize> {
+        info!("receive_message!\n");
+        self.receive_whole_message(None, receive_buffer, crypto_request)
+    }
+
+    /// A response sent in chunks is received whole.
     pub fn receive_secured_message(
         &mut self,
         session_id: u32,
@@ -128,22 +414,35 @@ impl<'a> RequesterContext<'a> {
         crypto_request: bool,
     ) -> SpdmResult<usize> {
         info!("receive_secured_message!\n");
+        self.receive_whole_message(Some(session_id), receive_buffer, crypto_request)
+    }
 
+    // One SPDM message, in or out of `session_id`, as is.
+    pub(crate) fn receive_single_message(
+        &mut self,
+        session_id: Option<u32>,
+        receive_buffer: &mut [u8],
+        crypto_request: bool,
+    ) -> SpdmResult<usize> {
         let timeout: usize = if crypto_request {
             2 << self.common.negotiate_info.rsp_ct_exponent_sel
         } else {
             ST1
         };
 
-        let mut transport_buffer = [0u8; config::RECEIVER_BUFFER_SIZE];
-
+        let mut transport_buffer = [0u8; TRANSPORT_SIZE];
         let used = self
             .common
             .device_io
-            .receive(&mut transport_buffer, timeout)
-            .map_err(|_| SPDM_STATUS_RECEIVE_FAIL)?;
+            .receive_with_timeout(&mut transport_buffer, timeout)?;
 
-        self.common
-            .decode_secured_message(session_id, &transport_buffer[..used], receive_buffer)
+        match session_id {
+            Some(session_id) => self.common.decode_secured_message(
+                session_id,
+                &transport_buffer[..used],
+                receive_buffer,
+            ),
+            None => self.common.decap(&transport_buffer[..used], receive_buffer),
+        }
     }
 }
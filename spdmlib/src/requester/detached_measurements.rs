@@ -0,0 +1,207 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::{ManagedBuffer12Sign, ManagedBufferL1L2};
+use crate::config;
+use crate::crypto;
+use crate::error::{
+    SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_INVALID_MSG_FIELD,
+    SPDM_STATUS_INVALID_PARAMETER,
+};
+use crate::message::{SpdmMeasurementAttributes, SpdmMessageHeader, SpdmRequestResponseCode};
+use crate::protocol::*;
+use codec::{u24, Codec, Reader};
+
+/// Negotiated parameters of the connection a measurement exchange was
+/// captured on, for `verify_measurement_exchange`.
+#[derive(Debug, Clone, Copy)]
+pub struct SpdmDetachedMeasurementParams<'a> {
+    pub version: SpdmVersion,
+    pub hash_algo: SpdmBaseHashAlgo,
+    pub asym_algo: SpdmBaseAsymAlgo,
+    /// GET_VERSION through ALGORITHMS as exchanged, the transcript starts
+    /// with them from SPDM 1.2 on and they are ignored before.
+    pub message_a: &'a [u8],
+    /// DER certificate chain of the signing slot, without the SPDM
+    /// certificate chain header.
+    pub cert_chain: &'a [u8],
+}
+
+/// Verify the signature of a MEASUREMENTS response collected earlier,
+/// without a `RequesterContext`.
+///
+/// `record` is the L1/L2 transcript up to the measurement record of the
+/// signed response: the VCA messages from SPDM 1.2 on, then every
+/// GET_MEASUREMENTS and MEASUREMENTS since the last signed one, the signed
+/// response ending after its MeasurementRecord. The response's `nonce` and
+/// `opaque` data are appended to it, the signature covers the result.
+///
+/// `cert_chain` is the DER certificate chain of the signing slot, without
+/// the SPDM certificate chain header.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_measurements_detached(
+    version: SpdmVersion,
+    hash_algo: SpdmBaseHashAlgo,
+    asym_algo: SpdmBaseAsymAlgo,
+    cert_chain: &[u8],
+    record: &[u8],
+    nonce: &[u8; SPDM_NONCE_SIZE],
+    opaque: &[u8],
+    signature: &SpdmSignatureStruct,
+) -> SpdmResult {
+    if opaque.len() > u16::MAX as usize {
+        return Err(SPDM_STATUS_INVALID_PARAMETER);
+    }
+    let opaque_length = (opaque.len() as u16).to_le_bytes();
+
+    let mut message_l1l2 = ManagedBufferL1L2::default();
+    for part in [record, &nonce[..], &opaque_length[..], opaque] {
+        message_l1l2
+            .append_message(part)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+    }
+
+    if version.get_u8() < SpdmVersion::SpdmVersion12.get_u8() {
+        return crypto::asym_verify::verify(
+            hash_algo,
+            asym_algo,
+            cert_chain,
+            message_l1l2.as_ref(),
+            signature,
+        );
+    }
+
+    let message_l1l2_hash =
+        crypto::hash::hash_all(hash_algo, message_l1l2.as_ref()).ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
+    debug!("message_l1l2_hash - {:02x?}", message_l1l2_hash.as_ref());
+
+    let mut message_sign = ManagedBuffer12Sign::default();
+    for part in [
+        &SPDM_VERSION_1_2_SIGNING_PREFIX_CONTEXT[..],
+        &SPDM_VERSION_1_2_SIGNING_CONTEXT_ZEROPAD_6[..],
+        &SPDM_MEASUREMENTS_SIGN_CONTEXT[..],
+        message_l1l2_hash.as_ref(),
+    ] {
+        message_sign
+            .append_message(part)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+    }
+
+    crypto::asym_verify::verify(
+        hash_algo,
+        asym_algo,
+        cert_chain,
+        message_sign.as_ref(),
+        signature,
+    )
+}
+
+/// Verify a GET_MEASUREMENTS / MEASUREMENTS pair exchanged by someone else,
+/// for using the crate as a verifier only: `request` and `response` are the
+/// SPDM messages as they went over the wire, without transport headers.
+///
+/// The request has to ask for a signature, and the pair has to be the
+/// first exchange after `params.message_a`, as L1/L2 also covers unsigned
+/// exchanges before it. The measurement record of a verified response is
+/// returned.
+pub fn verify_measurement_exchange(
+    params: &SpdmDetachedMeasurementParams,
+    request: &[u8],
+    response: &[u8],
+) -> SpdmResult<SpdmMeasurementRecordStructure> {
+    let mut reader = Reader::init(request);
+    let header = SpdmMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+    if header.version != params.version
+        || header.request_response_code != SpdmRequestResponseCode::SpdmRequestGetMeasurements
+    {
+        return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+    }
+    let measurement_attributes = u8::read(&mut reader)
+        .and_then(SpdmMeasurementAttributes::from_bits)
+        .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?; // param1
+    if !measurement_attributes.contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED) {
+        return Err(SPDM_STATUS_INVALID_PARAMETER);
+    }
+    u8::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?; // param2
+    reader
+        .take(SPDM_NONCE_SIZE)
+        .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+    if params.version.get_u8() >= SpdmVersion::SpdmVersion11.get_u8() {
+        u8::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?; // slot id
+    }
+    if reader.any_left() {
+        return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+    }
+
+    let mut reader = Reader::init(response);
+    let header = SpdmMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+    if header.version != params.version
+        || header.request_response_code != SpdmRequestResponseCode::SpdmResponseMeasurements
+    {
+        return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+    }
+    u16::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?; // param1, param2
+    let number_of_blocks = u8::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+    let measurement_record_length = u24::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+    let record_length = measurement_record_length.get() as usize;
+    if record_length > config::MAX_SPDM_MEASUREMENT_RECORD_SIZE {
+        return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+    }
+    let mut record = SpdmMeasurementRecordStructure {
+        number_of_blocks,
+        measurement_record_length,
+        ..Default::default()
+    };
+    record.measurement_record_data[..record_length].copy_from_slice(
+        reader
+            .take(record_length)
+            .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?,
+    );
+    let record_end = reader.used();
+    let mut nonce = [0u8; SPDM_NONCE_SIZE];
+    nonce.copy_from_slice(
+        reader
+            .take(SPDM_NONCE_SIZE)
+            .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?,
+    );
+    let opaque_length = u16::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)? as usize;
+    let opaque_offset = reader.used();
+    reader
+        .take(opaque_length)
+        .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+    let opaque = &response[opaque_offset..reader.used()];
+    let signature_size = params.asym_algo.get_size() as usize;
+    if reader.left() != signature_size {
+        return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+    }
+    let mut signature = SpdmSignatureStruct {
+        data_size: signature_size as u16,
+        ..Default::default()
+    };
+    signature.data[..signature_size].copy_from_slice(&response[response.len() - signature_size..]);
+
+    let mut message_l1l2 = ManagedBufferL1L2::default();
+    if params.version.get_u8() >= SpdmVersion::SpdmVersion12.get_u8() {
+        message_l1l2
+            .append_message(params.message_a)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+    }
+    for part in [request, &response[..record_end]] {
+        message_l1l2
+            .append_message(part)
+            .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+    }
+
+    verify_measurements_detached(
+        params.version,
+        params.hash_algo,
+        params.asym_algo,
+        params.cert_chain,
+        message_l1l2.as_ref(),
+        &nonce,
+        opaque,
+        &signature,
+    )?;
+    Ok(record)
+}
@@ -11,12 +11,14 @@ use crate::{
         SpdmMessage, SpdmMessageHeader, SpdmMessagePayload, SpdmRequestResponseCode,
         MAX_SPDM_CERT_PORTION_LEN,
     },
-    protocol::{SpdmRequestCapabilityFlags, SPDM_MAX_SLOT_NUMBER},
+    protocol::{SpdmCertificateModel, SpdmRequestCapabilityFlags, SPDM_MAX_SLOT_NUMBER},
 };
 
 use super::RequesterContext;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
     pub fn encap_handle_get_certificate(
         &mut self,
         encap_request: &[u8],
@@ -129,6 +131,7 @@ impl<'a> RequesterContext<'a> {
             },
             payload: SpdmMessagePayload::SpdmCertificateResponse(SpdmCertificateResponsePayload {
                 slot_id: slot_id as u8,
+                cert_model: SpdmCertificateModel::default(),
                 portion_length,
                 remainder_length,
                 cert_chain,
@@ -12,16 +12,16 @@ use crate::{
         SpdmMessageHeader, SpdmMessagePayload, SpdmRequestResponseCode,
     },
     protocol::{
-        gen_array_clone, SpdmDigestStruct, SpdmRequestCapabilityFlags, SPDM_MAX_HASH_SIZE,
+        gen_array_clone, SpdmDigestStruct, SpdmKeyUsageMask, SpdmRequestCapabilityFlags,
         SPDM_MAX_SLOT_NUMBER,
     },
 };
-extern crate alloc;
-use alloc::boxed::Box;
 
 use super::RequesterContext;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
     pub fn encap_handle_get_digest(&mut self, encap_request: &[u8], encap_response: &mut Writer) {
         let mut reader = Reader::init(encap_request);
 
@@ -72,9 +72,20 @@ impl<'a> RequesterContext<'a> {
         }
 
         let mut slot_mask = 0u8;
+        let mut slot_count = 0usize;
+        let mut digests = gen_array_clone(SpdmDigestStruct::default(), SPDM_MAX_SLOT_NUMBER);
+        let mut key_usage_mask = [SpdmKeyUsageMask::default(); SPDM_MAX_SLOT_NUMBER];
         for slot_id in 0..SPDM_MAX_SLOT_NUMBER {
-            if self.common.provision_info.my_cert_chain[slot_id].is_some() {
+            if let Some(my_cert_chain) = self.common.provision_info.my_cert_chain[slot_id].as_ref()
+            {
                 slot_mask |= (1 << slot_id) as u8;
+                digests[slot_count] = crypto::hash::hash_all(
+                    self.common.negotiate_info.base_hash_sel,
+                    my_cert_chain.as_ref(),
+                )
+                .unwrap();
+                key_usage_mask[slot_count] = self.common.provision_info.my_key_usage_mask[slot_id];
+                slot_count += 1;
             }
         }
 
@@ -85,13 +96,9 @@ impl<'a> RequesterContext<'a> {
             },
             payload: SpdmMessagePayload::SpdmDigestsResponse(SpdmDigestsResponsePayload {
                 slot_mask,
-                digests: gen_array_clone(
-                    SpdmDigestStruct {
-                        data_size: self.common.negotiate_info.base_hash_sel.get_size(),
-                        data: Box::new([0xffu8; SPDM_MAX_HASH_SIZE]),
-                    },
-                    SPDM_MAX_SLOT_NUMBER,
-                ),
+                digests,
+                key_usage_mask,
+                ..Default::default()
             }),
         };
 
@@ -107,23 +114,6 @@ impl<'a> RequesterContext<'a> {
             return;
         }
 
-        for slot_id in 0..SPDM_MAX_SLOT_NUMBER {
-            if self.common.provision_info.my_cert_chain[slot_id].is_some() {
-                let my_cert_chain = self.common.provision_info.my_cert_chain[slot_id]
-                    .as_ref()
-                    .unwrap();
-                let cert_chain_hash = crypto::hash::hash_all(
-                    self.common.negotiate_info.base_hash_sel,
-                    my_cert_chain.as_ref(),
-                )
-                .unwrap();
-
-                // patch the message before send
-                let used = encap_response.used();
-                encap_response.mut_used_slice()[(used - cert_chain_hash.data_size as usize)..used]
-                    .copy_from_slice(cert_chain_hash.as_ref());
-            }
-        }
         debug!("!!! encap get_digests : complete\n");
     }
 }
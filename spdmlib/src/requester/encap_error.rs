@@ -15,7 +15,9 @@ use crate::{
 
 use super::RequesterContext;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
     pub fn encode_encap_error_response(
         &mut self,
         error_code: SpdmErrorCode,
@@ -0,0 +1,160 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use codec::{Codec, Reader, Writer};
+
+use crate::{
+    common::{session::SpdmSessionState, SpdmCodec},
+    message::{
+        SpdmErrorCode, SpdmKeyUpdateOperation, SpdmKeyUpdateRequestPayload,
+        SpdmKeyUpdateResponsePayload, SpdmMessage, SpdmMessageHeader, SpdmMessagePayload,
+        SpdmRequestResponseCode,
+    },
+    protocol::{SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags},
+};
+
+use super::RequesterContext;
+
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Handle a KEY_UPDATE the responder sent as encapsulated request. The
+    /// responder only rotates its own direction: UpdateKey derives the new
+    /// response direction key, which protects everything the responder sends
+    /// after this KEY_UPDATE_ACK, and VerifyNewKey drops the old one.
+    pub fn encap_handle_key_update(
+        &mut self,
+        session_id: u32,
+        encap_request: &[u8],
+        encap_response: &mut Writer,
+    ) {
+        let mut reader = Reader::init(encap_request);
+
+        if !self
+            .common
+            .negotiate_info
+            .req_capabilities_sel
+            .contains(SpdmRequestCapabilityFlags::KEY_UPD_CAP)
+            || !self
+                .common
+                .negotiate_info
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::KEY_UPD_CAP)
+        {
+            self.encode_encap_error_response(
+                SpdmErrorCode::SpdmErrorUnsupportedRequest,
+                0,
+                encap_response,
+            );
+            return;
+        }
+
+        if let Some(message_header) = SpdmMessageHeader::read(&mut reader) {
+            if message_header.version != self.common.negotiate_info.spdm_version_sel {
+                self.encode_encap_error_response(
+                    SpdmErrorCode::SpdmErrorVersionMismatch,
+                    0,
+                    encap_response,
+                );
+                return;
+            }
+        } else {
+            self.encode_encap_error_response(
+                SpdmErrorCode::SpdmErrorInvalidRequest,
+                0,
+                encap_response,
+            );
+            return;
+        }
+
+        let key_update_req = if let Some(key_update_req) =
+            SpdmKeyUpdateRequestPayload::spdm_read(&mut self.common, &mut reader)
+        {
+            debug!("!!! encap key_update req : {:02x?}\n", key_update_req);
+            key_update_req
+        } else {
+            error!("!!! encap key_update req : fail !!!\n");
+            self.encode_encap_error_response(
+                SpdmErrorCode::SpdmErrorInvalidRequest,
+                0,
+                encap_response,
+            );
+            return;
+        };
+
+        self.common.reset_buffer_via_request_code(
+            SpdmRequestResponseCode::SpdmRequestKeyUpdate,
+            Some(session_id),
+        );
+
+        let spdm_version_sel = self.common.negotiate_info.spdm_version_sel;
+        let session = match self.common.get_session_via_id(session_id) {
+            Some(session)
+                if session.get_session_state() == SpdmSessionState::SpdmSessionEstablished =>
+            {
+                session
+            }
+            _ => {
+                self.encode_encap_error_response(
+                    SpdmErrorCode::SpdmErrorUnexpectedRequest,
+                    0,
+                    encap_response,
+                );
+                return;
+            }
+        };
+        let res = match key_update_req.key_update_operation {
+            SpdmKeyUpdateOperation::SpdmUpdateSingleKey => {
+                session.create_data_secret_update(spdm_version_sel, false, true)
+            }
+            SpdmKeyUpdateOperation::SpdmVerifyNewKey => {
+                session.activate_data_secret_update(spdm_version_sel, false, true, true)
+            }
+            // the requester direction is only ever updated by the requester.
+            _ => {
+                error!("!!! encap key_update req : unsupported operation !!!\n");
+                self.encode_encap_error_response(
+                    SpdmErrorCode::SpdmErrorInvalidRequest,
+                    0,
+                    encap_response,
+                );
+                return;
+            }
+        };
+        if res.is_err() {
+            if key_update_req.key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateSingleKey {
+                let _ = session.activate_data_secret_update(spdm_version_sel, false, true, false);
+            }
+            self.encode_encap_error_response(
+                SpdmErrorCode::SpdmErrorUnspecified,
+                0,
+                encap_response,
+            );
+            return;
+        }
+
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseKeyUpdateAck,
+            },
+            payload: SpdmMessagePayload::SpdmKeyUpdateResponse(SpdmKeyUpdateResponsePayload {
+                key_update_operation: key_update_req.key_update_operation,
+                tag: key_update_req.tag,
+            }),
+        };
+        if response
+            .spdm_encode(&mut self.common, encap_response)
+            .is_err()
+        {
+            self.encode_encap_error_response(
+                SpdmErrorCode::SpdmErrorUnspecified,
+                0,
+                encap_response,
+            );
+            return;
+        }
+        debug!("!!! encap key_update : complete\n");
+    }
+}
@@ -9,20 +9,25 @@ use crate::error::{
 use crate::message::*;
 use crate::requester::*;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_end_session(&mut self, session_id: u32) -> SpdmResult {
         info!("send spdm end_session\n");
 
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
         self.common.reset_buffer_via_request_code(
             SpdmRequestResponseCode::SpdmRequestEndSession,
             Some(session_id),
         );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let used = self.encode_spdm_end_session(&mut send_buffer)?;
         self.send_secured_message(session_id, &send_buffer[..used], false)?;
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let used = self.receive_secured_message(session_id, &mut receive_buffer, false)?;
         self.handle_spdm_end_session_response(session_id, &receive_buffer[..used])
     }
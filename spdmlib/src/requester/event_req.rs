@@ -0,0 +1,278 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::error::{
+    SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD,
+    SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_UNSUPPORTED_CAP,
+};
+use crate::message::*;
+use crate::protocol::*;
+use crate::requester::*;
+
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires at least `SpdmConnectionNegotiated`.
+    pub fn send_receive_spdm_get_supported_event_types(
+        &mut self,
+        session_id: u32,
+    ) -> SpdmResult<SpdmSupportedEventTypesResponsePayload> {
+        info!("send spdm get supported event types\n");
+
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+        self.check_event_cap()?;
+
+        self.common.reset_buffer_via_request_code(
+            SpdmRequestResponseCode::SpdmRequestGetSupportedEventTypes,
+            Some(session_id),
+        );
+
+        let mut send_buffer = [0u8; MSG_SIZE];
+        let used = self.encode_spdm_get_supported_event_types(&mut send_buffer)?;
+        self.send_secured_message(session_id, &send_buffer[..used], false)?;
+
+        // Receive
+        let mut receive_buffer = [0u8; MSG_SIZE];
+        let used = self.receive_secured_message(session_id, &mut receive_buffer, false)?;
+        self.handle_spdm_supported_event_types_response(session_id, &receive_buffer[..used])
+    }
+
+    pub fn encode_spdm_get_supported_event_types(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
+        let mut writer = Writer::init(buf);
+        let request = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmRequestGetSupportedEventTypes,
+            },
+            payload: SpdmMessagePayload::SpdmGetSupportedEventTypesRequest(
+                SpdmGetSupportedEventTypesRequestPayload {},
+            ),
+        };
+        request.spdm_encode(&mut self.common, &mut writer)
+    }
+
+    pub fn handle_spdm_supported_event_types_response(
+        &mut self,
+        session_id: u32,
+        receive_buffer: &[u8],
+    ) -> SpdmResult<SpdmSupportedEventTypesResponsePayload> {
+        let mut reader = Reader::init(receive_buffer);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(message_header) => {
+                if message_header.version != self.common.negotiate_info.spdm_version_sel {
+                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                }
+                match message_header.request_response_code {
+                    SpdmRequestResponseCode::SpdmResponseSupportedEventTypes => {
+                        let supported_event_types =
+                            SpdmSupportedEventTypesResponsePayload::spdm_read(
+                                &mut self.common,
+                                &mut reader,
+                            );
+                        if let Some(supported_event_types) = supported_event_types {
+                            debug!(
+                                "!!! supported event types : {:02x?}\n",
+                                supported_event_types.supported_event_groups_list.as_slice()
+                            );
+                            Ok(supported_event_types)
+                        } else {
+                            error!("!!! supported event types : fail !!!\n");
+                            Err(SPDM_STATUS_INVALID_MSG_FIELD)
+                        }
+                    }
+                    SpdmRequestResponseCode::SpdmResponseError => {
+                        let status = self.spdm_handle_error_response_main(
+                            Some(session_id),
+                            receive_buffer,
+                            SpdmRequestResponseCode::SpdmRequestGetSupportedEventTypes,
+                            SpdmRequestResponseCode::SpdmResponseSupportedEventTypes,
+                        );
+                        match status {
+                            Err(status) => Err(status),
+                            Ok(()) => Err(SPDM_STATUS_ERROR_PEER),
+                        }
+                    }
+                    _ => Err(SPDM_STATUS_ERROR_PEER),
+                }
+            }
+            None => Err(SPDM_STATUS_INVALID_MSG_FIELD),
+        }
+    }
+
+    /// Subscribe to the event groups in `subscribe_list` within the session.
+    /// An empty list with a zero count clears every subscription.
+    ///
+    /// Requires at least `SpdmConnectionNegotiated`.
+    pub fn send_receive_spdm_subscribe_event_types(
+        &mut self,
+        session_id: u32,
+        subscribe_event_group_count: u8,
+        subscribe_list: &[u8],
+    ) -> SpdmResult {
+        info!("send spdm subscribe event types\n");
+
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+        self.check_event_cap()?;
+
+        self.common.reset_buffer_via_request_code(
+            SpdmRequestResponseCode::SpdmRequestSubscribeEventTypes,
+            Some(session_id),
+        );
+
+        let mut send_buffer = [0u8; MSG_SIZE];
+        let used = self.encode_spdm_subscribe_event_types(
+            subscribe_event_group_count,
+            subscribe_list,
+            &mut send_buffer,
+        )?;
+        self.send_secured_message(session_id, &send_buffer[..used], false)?;
+
+        // Receive
+        let mut receive_buffer = [0u8; MSG_SIZE];
+        let used = self.receive_secured_message(session_id, &mut receive_buffer, false)?;
+        self.handle_spdm_subscribe_event_types_response(session_id, &receive_buffer[..used])
+    }
+
+    pub fn encode_spdm_subscribe_event_types(
+        &mut self,
+        subscribe_event_group_count: u8,
+        subscribe_list: &[u8],
+        buf: &mut [u8],
+    ) -> SpdmResult<usize> {
+        if (subscribe_event_group_count == 0) != subscribe_list.is_empty() {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        let subscribe_list =
+            SpdmEventListStruct::from_slice(subscribe_list).ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+
+        let mut writer = Writer::init(buf);
+        let request = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmRequestSubscribeEventTypes,
+            },
+            payload: SpdmMessagePayload::SpdmSubscribeEventTypesRequest(
+                SpdmSubscribeEventTypesRequestPayload {
+                    subscribe_event_group_count,
+                    subscribe_list,
+                },
+            ),
+        };
+        request.spdm_encode(&mut self.common, &mut writer)
+    }
+
+    pub fn handle_spdm_subscribe_event_types_response(
+        &mut self,
+        session_id: u32,
+        receive_buffer: &[u8],
+    ) -> SpdmResult {
+        let mut reader = Reader::init(receive_buffer);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(message_header) => {
+                if message_header.version != self.common.negotiate_info.spdm_version_sel {
+                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                }
+                match message_header.request_response_code {
+                    SpdmRequestResponseCode::SpdmResponseSubscribeEventTypesAck => {
+                        let subscribe_ack = SpdmSubscribeEventTypesAckResponsePayload::spdm_read(
+                            &mut self.common,
+                            &mut reader,
+                        );
+                        if let Some(subscribe_ack) = subscribe_ack {
+                            debug!("!!! subscribe event types ack : {:02x?}\n", subscribe_ack);
+                            Ok(())
+                        } else {
+                            error!("!!! subscribe event types ack : fail !!!\n");
+                            Err(SPDM_STATUS_INVALID_MSG_FIELD)
+                        }
+                    }
+                    SpdmRequestResponseCode::SpdmResponseError => self
+                        .spdm_handle_error_response_main(
+                            Some(session_id),
+                            receive_buffer,
+                            SpdmRequestResponseCode::SpdmRequestSubscribeEventTypes,
+                            SpdmRequestResponseCode::SpdmResponseSubscribeEventTypesAck,
+                        ),
+                    _ => Err(SPDM_STATUS_ERROR_PEER),
+                }
+            }
+            None => Err(SPDM_STATUS_INVALID_MSG_FIELD),
+        }
+    }
+
+    /// Wait for a SEND_EVENT from the responder, hand it to the registered
+    /// event callback and acknowledge it with EVENT_ACK.
+    pub fn receive_spdm_event(&mut self, session_id: u32) -> SpdmResult {
+        info!("receive spdm event\n");
+
+        self.check_event_cap()?;
+
+        let mut receive_buffer = [0u8; MSG_SIZE];
+        let used = self.receive_secured_message(session_id, &mut receive_buffer, false)?;
+        self.handle_spdm_send_event(session_id, &receive_buffer[..used])?;
+
+        let mut send_buffer = [0u8; MSG_SIZE];
+        let used = self.encode_spdm_event_ack(&mut send_buffer)?;
+        self.send_secured_message(session_id, &send_buffer[..used], false)
+    }
+
+    pub fn handle_spdm_send_event(&mut self, session_id: u32, receive_buffer: &[u8]) -> SpdmResult {
+        let mut reader = Reader::init(receive_buffer);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(message_header) => {
+                if message_header.version != self.common.negotiate_info.spdm_version_sel {
+                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                }
+                match message_header.request_response_code {
+                    SpdmRequestResponseCode::SpdmRequestSendEvent => {
+                        let send_event =
+                            SpdmSendEventRequestPayload::spdm_read(&mut self.common, &mut reader);
+                        if let Some(send_event) = send_event {
+                            debug!("!!! send event : {:02x?}\n", send_event.event_count);
+                            match self.common.event_callback {
+                                Some(event_callback) => event_callback(
+                                    session_id,
+                                    send_event.event_count,
+                                    send_event.event_list.as_slice(),
+                                ),
+                                None => Ok(()),
+                            }
+                        } else {
+                            error!("!!! send event : fail !!!\n");
+                            Err(SPDM_STATUS_INVALID_MSG_FIELD)
+                        }
+                    }
+                    _ => Err(SPDM_STATUS_ERROR_PEER),
+                }
+            }
+            None => Err(SPDM_STATUS_INVALID_MSG_FIELD),
+        }
+    }
+
+    pub fn encode_spdm_event_ack(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
+        let mut writer = Writer::init(buf);
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseEventAck,
+            },
+            payload: SpdmMessagePayload::SpdmEventAckResponse(SpdmEventAckResponsePayload {}),
+        };
+        response.spdm_encode(&mut self.common, &mut writer)
+    }
+
+    fn check_event_cap(&self) -> SpdmResult {
+        if self.common.negotiate_info.spdm_version_sel.get_u8()
+            < SpdmVersion::SpdmVersion13.get_u8()
+            || !self
+                .common
+                .negotiate_info
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::EVENT_CAP)
+        {
+            return Err(SPDM_STATUS_UNSUPPORTED_CAP);
+        }
+        Ok(())
+    }
+}
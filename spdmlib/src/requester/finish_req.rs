@@ -10,23 +10,20 @@ use crate::requester::*;
 extern crate alloc;
 use alloc::boxed::Box;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_finish(
         &mut self,
         req_slot_id: Option<u8>,
         session_id: u32,
     ) -> SpdmResult {
         info!("send spdm finish\n");
-        let in_clear_text = self
-            .common
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && self
-                .common
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
+        let in_clear_text = self.common.negotiate_info.handshake_in_the_clear();
         info!("in_clear_text {:?}\n", in_clear_text);
 
         let req_slot_id = if let Some(req_slot_id) = req_slot_id {
@@ -38,7 +35,7 @@ impl<'a> RequesterContext<'a> {
             }
             req_slot_id
         } else {
-            0
+            self.mut_auth_slot_id.unwrap_or(0)
         };
 
         if self.common.get_session_via_id(session_id).is_none() {
@@ -50,7 +47,7 @@ impl<'a> RequesterContext<'a> {
             Some(session_id),
         );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let res = self.encode_spdm_finish(session_id, req_slot_id, &mut send_buffer);
         if res.is_err() {
             let _ = self
@@ -61,11 +58,7 @@ impl<'a> RequesterContext<'a> {
             return Err(res.err().unwrap());
         }
         let send_used = res.unwrap();
-        let res = if in_clear_text {
-            self.send_message(&send_buffer[..send_used])
-        } else {
-            self.send_secured_message(session_id, &send_buffer[..send_used], false)
-        };
+        let res = self.send_handshake_message(session_id, &send_buffer[..send_used]);
         if res.is_err() {
             let _ = self
                 .common
@@ -75,12 +68,8 @@ impl<'a> RequesterContext<'a> {
             return res;
         }
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let res = if in_clear_text {
-            self.receive_message(&mut receive_buffer, false)
-        } else {
-            self.receive_secured_message(session_id, &mut receive_buffer, false)
-        };
+        let mut receive_buffer = [0u8; MSG_SIZE];
+        let res = self.receive_handshake_message(session_id, &mut receive_buffer, false);
         if res.is_err() {
             let _ = self
                 .common
@@ -90,11 +79,7 @@ impl<'a> RequesterContext<'a> {
             return Err(res.err().unwrap());
         }
         let receive_used = res.unwrap();
-        let res = self.handle_spdm_finish_response(
-            session_id,
-            req_slot_id,
-            &receive_buffer[..receive_used],
-        );
+        let res = self.handle_spdm_finish_response(session_id, &receive_buffer[..receive_used]);
         if res.is_err() {
             if let Some(session) = self.common.get_session_via_id(session_id) {
                 let _ = session.teardown(session_id);
@@ -121,6 +106,19 @@ impl<'a> RequesterContext<'a> {
             finish_request_attributes = SpdmFinishRequestAttributes::SIGNATURE_INCLUDED;
             signature.data_size = self.common.negotiate_info.req_asym_sel.get_size();
             is_mut_auth = true;
+            if req_slot_id as usize >= SPDM_MAX_SLOT_NUMBER
+                || self.common.provision_info.my_cert_chain[req_slot_id as usize].is_none()
+            {
+                error!(
+                    "!!! finish : no certificate chain in slot {} !!!\n",
+                    req_slot_id
+                );
+                return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+            }
+            // sign with the cert chain announced in param2.
+            self.common
+                .runtime_info
+                .set_local_used_cert_chain_slot_id(req_slot_id);
         }
 
         let request = SpdmMessage {
@@ -166,9 +164,12 @@ impl<'a> RequesterContext<'a> {
             .get_immutable_session_via_id(session_id)
             .unwrap();
 
-        let transcript_hash =
-            self.common
-                .calc_req_transcript_hash(false, req_slot_id, is_mut_auth, session)?;
+        let transcript_hash = self.common.calc_req_transcript_hash(
+            false,
+            session.get_slot_id(),
+            is_mut_auth,
+            session,
+        )?;
 
         let session = self.common.get_session_via_id(session_id).unwrap();
 
@@ -185,19 +186,9 @@ impl<'a> RequesterContext<'a> {
     pub fn handle_spdm_finish_response(
         &mut self,
         session_id: u32,
-        req_slot_id: u8,
         receive_buffer: &[u8],
     ) -> SpdmResult {
-        let in_clear_text = self
-            .common
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && self
-                .common
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+        let in_clear_text = self.common.negotiate_info.handshake_in_the_clear();
 
         let is_mut_auth = !self
             .common
@@ -235,7 +226,7 @@ impl<'a> RequesterContext<'a> {
 
                             let transcript_hash = self.common.calc_req_transcript_hash(
                                 false,
-                                req_slot_id,
+                                session.get_slot_id(),
                                 is_mut_auth,
                                 session,
                             )?;
@@ -274,7 +265,7 @@ impl<'a> RequesterContext<'a> {
                         // generate the data secret
                         let th2 = self.common.calc_req_transcript_hash(
                             false,
-                            req_slot_id,
+                            session.get_slot_id(),
                             is_mut_auth,
                             session,
                         )?;
@@ -390,20 +381,18 @@ impl<'a> RequesterContext<'a> {
         )
         .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
 
-        let peer_slot_id = self.common.runtime_info.get_local_used_cert_chain_slot_id();
-        let peer_cert = &self.common.provision_info.my_cert_chain[peer_slot_id as usize]
+        let my_slot_id = self.common.runtime_info.get_local_used_cert_chain_slot_id();
+        let my_cert_chain = self.common.provision_info.my_cert_chain[my_slot_id as usize]
             .as_ref()
-            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-            .data[(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-            ..(self.common.peer_info.peer_cert_chain[peer_slot_id as usize]
-                .as_ref()
-                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-                .data_size as usize)];
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        let my_cert = my_cert_chain
+            .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+            .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
         crate::crypto::asym_verify::verify(
             self.common.negotiate_info.base_hash_sel,
             self.common.negotiate_info.base_asym_sel,
-            peer_cert,
+            my_cert,
             transcript_sign.as_ref(),
             &signature,
         )
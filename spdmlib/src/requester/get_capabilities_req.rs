@@ -7,20 +7,32 @@ use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires `SpdmConnectionAfterVersion`.
     pub fn send_receive_spdm_capability(&mut self) -> SpdmResult {
+        self.check_connection_transition(SpdmConnectionState::SpdmConnectionAfterCapabilities)?;
+
         self.common.reset_buffer_via_request_code(
             SpdmRequestResponseCode::SpdmRequestGetCapabilities,
             None,
         );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let send_used = self.encode_spdm_capability(&mut send_buffer)?;
         self.send_message(&send_buffer[..send_used])?;
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let used = self.receive_message(&mut receive_buffer, false)?;
-        self.handle_spdm_capability_response(0, &send_buffer[..send_used], &receive_buffer[..used])
+        self.handle_spdm_capability_response(
+            0,
+            &send_buffer[..send_used],
+            &receive_buffer[..used],
+        )?;
+        self.common
+            .runtime_info
+            .transition_connection_state(SpdmConnectionState::SpdmConnectionAfterCapabilities)
     }
 
     pub fn encode_spdm_capability(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
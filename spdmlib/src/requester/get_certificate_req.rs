@@ -4,14 +4,18 @@
 
 use crate::crypto;
 use crate::error::{
-    SpdmResult, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_CERT,
-    SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_INVALID_STATE_LOCAL,
+    SpdmResult, SPDM_STATUS_CERT_DIGEST_MISMATCH, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_ERROR_PEER,
+    SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER,
+    SPDM_STATUS_INVALID_STATE_LOCAL, SPDM_STATUS_INVALID_STATE_PEER, SPDM_STATUS_UNSUPPORTED_CAP,
+    SPDM_STATUS_WEAK_CERT_KEY,
 };
 use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
     fn send_receive_spdm_certificate_partial(
         &mut self,
         session_id: Option<u32>,
@@ -21,7 +25,7 @@ impl<'a> RequesterContext<'a> {
         length: u16,
     ) -> SpdmResult<(u16, u16)> {
         info!("send spdm certificate\n");
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let send_used =
             self.encode_spdm_certificate_partial(slot_id, offset, length, &mut send_buffer)?;
 
@@ -34,7 +38,7 @@ impl<'a> RequesterContext<'a> {
             }
         }
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let used = match session_id {
             Some(session_id) => {
                 self.receive_secured_message(session_id, &mut receive_buffer, false)?
@@ -59,6 +63,23 @@ impl<'a> RequesterContext<'a> {
         offset: u16,
         length: u16,
         buf: &mut [u8],
+    ) -> SpdmResult<usize> {
+        self.encode_spdm_get_certificate(
+            slot_id,
+            SpdmGetCertificateRequestAttributes::empty(),
+            offset,
+            length,
+            buf,
+        )
+    }
+
+    fn encode_spdm_get_certificate(
+        &mut self,
+        slot_id: u8,
+        request_attribute: SpdmGetCertificateRequestAttributes,
+        offset: u16,
+        length: u16,
+        buf: &mut [u8],
     ) -> SpdmResult<usize> {
         let mut writer = Writer::init(buf);
         let request = SpdmMessage {
@@ -69,6 +90,7 @@ impl<'a> RequesterContext<'a> {
             payload: SpdmMessagePayload::SpdmGetCertificateRequest(
                 SpdmGetCertificateRequestPayload {
                     slot_id,
+                    request_attribute,
                     offset,
                     length,
                 },
@@ -104,25 +126,22 @@ impl<'a> RequesterContext<'a> {
                         if let Some(certificate) = certificate {
                             debug!("!!! certificate : {:02x?}\n", certificate);
 
+                            // neither end may wrap the 16-bit offset or leave the buffer.
+                            let portion_end = offset
+                                .checked_add(certificate.portion_length)
+                                .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+                            let chain_end = portion_end
+                                .checked_add(certificate.remainder_length)
+                                .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
                             if certificate.portion_length > length
-                                || certificate.portion_length
-                                    > config::MAX_SPDM_CERT_CHAIN_DATA_SIZE as u16 - offset
+                                || portion_end as usize > config::MAX_SPDM_CERT_CHAIN_DATA_SIZE
                             {
                                 return Err(SPDM_STATUS_INVALID_MSG_FIELD);
                             }
-                            if certificate.remainder_length
-                                >= config::MAX_SPDM_CERT_CHAIN_DATA_SIZE as u16
-                                    - offset
-                                    - certificate.portion_length
-                            {
+                            if chain_end as usize >= config::MAX_SPDM_CERT_CHAIN_DATA_SIZE {
                                 return Err(SPDM_STATUS_INVALID_MSG_FIELD);
                             }
-                            if total_size != 0
-                                && total_size
-                                    != offset
-                                        + certificate.portion_length
-                                        + certificate.remainder_length
-                            {
+                            if total_size != 0 && total_size != chain_end {
                                 return Err(SPDM_STATUS_INVALID_MSG_FIELD);
                             }
                             if certificate.slot_id != slot_id {
@@ -137,21 +156,28 @@ impl<'a> RequesterContext<'a> {
                                 .as_mut()
                                 .ok_or(SPDM_STATUS_INVALID_STATE_LOCAL)?;
 
-                            peer_cert_chain_temp.data[(offset as usize)
-                                ..(offset as usize + certificate.portion_length as usize)]
+                            peer_cert_chain_temp.data[(offset as usize)..(portion_end as usize)]
                                 .copy_from_slice(
                                     &certificate.cert_chain
                                         [0..(certificate.portion_length as usize)],
                                 );
 
-                            peer_cert_chain_temp.data_size = offset + certificate.portion_length;
+                            peer_cert_chain_temp.data_size = portion_end;
+                            self.common.peer_info.peer_cert_model[slot_id as usize] =
+                                if self.common.negotiate_info.spdm_version_sel.get_u8()
+                                    >= SpdmVersion::SpdmVersion13.get_u8()
+                                {
+                                    Some(certificate.cert_model)
+                                } else {
+                                    None
+                                };
 
                             match session_id {
-                                None => {
+                                None if !self.decode_only => {
                                     self.common.append_message_b(send_buffer)?;
                                     self.common.append_message_b(&receive_buffer[..used])?;
                                 }
-                                Some(_session_id) => {}
+                                _ => {}
                             }
 
                             Ok((certificate.portion_length, certificate.remainder_length))
@@ -179,53 +205,196 @@ impl<'a> RequesterContext<'a> {
         }
     }
 
+    /// Length asked for in each GET_CERTIFICATE.
+    ///
+    /// It is `config_info.cert_portion_length` when set, capped by
+    /// MAX_SPDM_CERT_PORTION_LEN and by what a CERTIFICATE response may
+    /// carry within the negotiated DataTransferSize, or within the
+    /// MaxSPDMmsgSize when the response may come in chunks, and never zero.
+    pub fn cert_portion_length(&self) -> u16 {
+        let mut portion_length = MAX_SPDM_CERT_PORTION_LEN as u16;
+
+        // SPDMVersion, RequestResponseCode, Param1, Param2, PortionLength
+        // and RemainderLength come before the chain portion.
+        let data_transfer_size = if self.common.negotiate_info.chunk_supported() {
+            self.common.negotiate_info.req_max_spdm_msg_size_sel
+        } else {
+            self.common.negotiate_info.req_data_transfer_size_sel
+        };
+        if data_transfer_size != 0 {
+            let max_portion_length = data_transfer_size.saturating_sub(8);
+            if max_portion_length < portion_length as u32 {
+                portion_length = max_portion_length as u16;
+            }
+        }
+
+        let configured = self.common.config_info.cert_portion_length;
+        if configured != 0 && configured < portion_length {
+            portion_length = configured;
+        }
+
+        portion_length.max(1)
+    }
+
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_certificate(
         &mut self,
         session_id: Option<u32>,
         slot_id: u8,
     ) -> SpdmResult {
-        let mut offset = 0u16;
-        let mut length = MAX_SPDM_CERT_PORTION_LEN as u16;
-        let mut total_size = 0u16;
+        self.timed(SpdmRequesterOperation::Certificate, |this| {
+            this.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
 
-        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
-            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
-        }
+            let portion_length_max = this.cert_portion_length();
+            let mut offset = 0u16;
+            let mut length = portion_length_max;
+            let mut total_size = 0u16;
 
-        self.common.reset_buffer_via_request_code(
-            SpdmRequestResponseCode::SpdmRequestGetCertificate,
-            session_id,
-        );
+            if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+                return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+            }
+            if let Some(provisioned_slot_mask) = this.common.peer_info.peer_provisioned_slot_mask {
+                if provisioned_slot_mask & (1 << slot_id) == 0 {
+                    error!("!!! certificate : slot {} is empty !!!\n", slot_id);
+                    return Err(SPDM_STATUS_INVALID_STATE_PEER);
+                }
+            }
 
-        self.common.peer_info.peer_cert_chain_temp = Some(SpdmCertChainBuffer::default());
-        while length != 0 {
-            let (portion_length, remainder_length) = self.send_receive_spdm_certificate_partial(
-                session_id, slot_id, total_size, offset, length,
-            )?;
+            this.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestGetCertificate,
+                session_id,
+            );
+
+            this.common.peer_info.peer_cert_chain_temp = Some(SpdmCertChainBuffer::default());
+            while length != 0 {
+                let (portion_length, remainder_length) = this
+                    .send_receive_spdm_certificate_partial(
+                        session_id, slot_id, total_size, offset, length,
+                    )?;
+                if total_size == 0 {
+                    total_size = portion_length
+                        .checked_add(remainder_length)
+                        .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+                }
+                offset = offset
+                    .checked_add(portion_length)
+                    .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+                length = remainder_length;
+                if length > portion_length_max {
+                    length = portion_length_max;
+                }
+            }
             if total_size == 0 {
-                total_size = portion_length + remainder_length;
+                this.common.peer_info.peer_cert_chain_temp = None;
+                return Err(SPDM_STATUS_INVALID_CERT);
             }
-            offset += portion_length;
-            length = remainder_length;
-            if length > MAX_SPDM_CERT_PORTION_LEN as u16 {
-                length = MAX_SPDM_CERT_PORTION_LEN as u16;
+
+            let mut result = if this.decode_only {
+                warn!("decode only: certificate chain stored unverified\n");
+                Ok(SpdmCertChainTrust::Unverified)
+            } else {
+                this.verify_spdm_certificate_chain_trust()
+                    .and_then(|trust| {
+                        this.verify_spdm_certificate_chain_digest(slot_id)
+                            .map(|_| trust)
+                    })
+            };
+            if let Ok(trust) = result {
+                this.common.peer_info.peer_cert_chain[slot_id as usize] =
+                    this.common.peer_info.peer_cert_chain_temp.clone();
+                this.common.peer_info.peer_cert_chain_trust[slot_id as usize] = Some(trust);
+                if session_id.is_none() {
+                    result = this
+                        .advance_connection_state(
+                            SpdmConnectionState::SpdmConnectionAfterCertificate,
+                        )
+                        .map(|_| trust);
+                }
             }
+            this.common.peer_info.peer_cert_chain_temp = None;
+            result.map(|_| ())
+        })
+    }
+
+    /// Size of the certificate chain in `slot_id`, SPDM header included,
+    /// asked for with SlotSizeRequested (SPDM 1.3) without fetching any of
+    /// it. The exchange is not part of the transcript.
+    pub fn send_receive_spdm_certificate_slot_size(
+        &mut self,
+        session_id: Option<u32>,
+        slot_id: u8,
+    ) -> SpdmResult<u16> {
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+        if self.common.negotiate_info.spdm_version_sel.get_u8()
+            < SpdmVersion::SpdmVersion13.get_u8()
+        {
+            return Err(SPDM_STATUS_UNSUPPORTED_CAP);
         }
-        if total_size == 0 {
-            self.common.peer_info.peer_cert_chain_temp = None;
-            return Err(SPDM_STATUS_INVALID_CERT);
+        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
         }
 
-        let result = self.verify_spdm_certificate_chain();
-        if result.is_ok() {
-            self.common.peer_info.peer_cert_chain[slot_id as usize] =
-                self.common.peer_info.peer_cert_chain_temp.clone();
+        info!("send spdm certificate slot size\n");
+        let mut send_buffer = [0u8; MSG_SIZE];
+        let send_used = self.encode_spdm_get_certificate(
+            slot_id,
+            SpdmGetCertificateRequestAttributes::SLOT_SIZE_REQUESTED,
+            0,
+            0,
+            &mut send_buffer,
+        )?;
+        let mut receive_buffer = [0u8; MSG_SIZE];
+        let used = match session_id {
+            Some(session_id) => {
+                self.send_secured_message(session_id, &send_buffer[..send_used], false)?;
+                self.receive_secured_message(session_id, &mut receive_buffer, false)?
+            }
+            None => {
+                self.send_message(&send_buffer[..send_used])?;
+                self.receive_message(&mut receive_buffer, false)?
+            }
+        };
+
+        let mut reader = Reader::init(&receive_buffer[..used]);
+        let message_header =
+            SpdmMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+        if message_header.version != self.common.negotiate_info.spdm_version_sel {
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+        match message_header.request_response_code {
+            SpdmRequestResponseCode::SpdmResponseCertificate => {
+                let certificate =
+                    SpdmCertificateResponsePayload::spdm_read(&mut self.common, &mut reader)
+                        .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+                if certificate.slot_id != slot_id || certificate.portion_length != 0 {
+                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                }
+                self.common.peer_info.peer_cert_model[slot_id as usize] =
+                    Some(certificate.cert_model);
+                Ok(certificate.remainder_length)
+            }
+            SpdmRequestResponseCode::SpdmResponseError => {
+                let status = self.spdm_handle_error_response_main(
+                    session_id,
+                    &receive_buffer[..used],
+                    SpdmRequestResponseCode::SpdmRequestGetCertificate,
+                    SpdmRequestResponseCode::SpdmResponseCertificate,
+                );
+                match status {
+                    Err(status) => Err(status),
+                    Ok(()) => Err(SPDM_STATUS_ERROR_PEER),
+                }
+            }
+            _ => Err(SPDM_STATUS_ERROR_PEER),
         }
-        self.common.peer_info.peer_cert_chain_temp = None;
-        result
     }
 
     pub fn verify_spdm_certificate_chain(&mut self) -> SpdmResult {
+        self.verify_spdm_certificate_chain_trust().map(|_| ())
+    }
+
+    /// Verify `peer_cert_chain_temp`, returning how it was accepted.
+    pub fn verify_spdm_certificate_chain_trust(&mut self) -> SpdmResult<SpdmCertChainTrust> {
         //
         // 1. Verify the integrity of cert chain
         //
@@ -240,39 +409,40 @@ impl<'a> RequesterContext<'a> {
             .peer_cert_chain_temp
             .as_ref()
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
-        if peer_cert_chain.data_size <= (4 + self.common.negotiate_info.base_hash_sel.get_size()) {
-            return Err(SPDM_STATUS_INVALID_CERT);
-        }
-
-        let data_size_in_cert_chain =
-            peer_cert_chain.data[0] as u16 + ((peer_cert_chain.data[1] as u16) << 8);
-        if data_size_in_cert_chain != peer_cert_chain.data_size {
-            return Err(SPDM_STATUS_INVALID_CERT);
-        }
+        let header = SpdmCertChainHeader::parse(
+            peer_cert_chain.as_ref(),
+            self.common.negotiate_info.base_hash_sel,
+        )
+        .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
-        let data_size =
-            peer_cert_chain.data_size - 4 - self.common.negotiate_info.base_hash_sel.get_size();
+        let der_cert_chain = &peer_cert_chain.as_ref()[header.der_offset..];
+        let data_size = der_cert_chain.len() as u16;
         let mut data = [0u8; config::MAX_SPDM_CERT_CHAIN_DATA_SIZE];
-        data[0..(data_size as usize)].copy_from_slice(
-            &peer_cert_chain.data[(4usize
-                + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-                ..(peer_cert_chain.data_size as usize)],
-        );
+        data[0..(data_size as usize)].copy_from_slice(der_cert_chain);
         let runtime_peer_cert_chain_data = SpdmCertChainData { data_size, data };
         info!("1. get runtime_peer_cert_chain_data!\n");
 
         //
         // 1.1 verify the integrity of the chain
         //
+        let mut trust = SpdmCertChainTrust::Chained;
         if crypto::cert_operation::verify_cert_chain(
             &runtime_peer_cert_chain_data.data[..(runtime_peer_cert_chain_data.data_size as usize)],
         )
         .is_err()
         {
-            error!("cert_chain verification - fail! - TBD later\n");
-            return Err(SPDM_STATUS_INVALID_CERT);
+            if !self.is_single_self_signed_cert_allowed(
+                &runtime_peer_cert_chain_data.data
+                    [..(runtime_peer_cert_chain_data.data_size as usize)],
+            ) {
+                error!("cert_chain verification - fail! - TBD later\n");
+                return Err(SPDM_STATUS_INVALID_CERT);
+            }
+            error!("!!! self-signed cert accepted, its issuer is not verified !!!\n");
+            trust = SpdmCertChainTrust::SelfSignedUnverifiedIssuer;
+        } else {
+            info!("1.1. integrity of cert_chain is verified!\n");
         }
-        info!("1.1. integrity of cert_chain is verified!\n");
 
         //
         // 1.2 verify the root cert hash
@@ -289,15 +459,36 @@ impl<'a> RequesterContext<'a> {
         } else {
             return Err(SPDM_STATUS_CRYPTO_ERROR);
         };
-        if root_hash.data[..(root_hash.data_size as usize)]
-            != peer_cert_chain.data
-                [4usize..(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)]
-        {
+        if root_hash.data[..(root_hash.data_size as usize)] != *header.root_hash {
             error!("root_hash - fail!\n");
             return Err(SPDM_STATUS_INVALID_CERT);
         }
         info!("1.2. root cert hash is verified!\n");
 
+        //
+        // 1.3 verify the leaf key against the minimum RSA key size
+        //
+        if self.common.config_info.min_rsa_key_bits != 0 {
+            let (leaf_cert_begin, leaf_cert_end) =
+                crypto::cert_operation::get_cert_from_cert_chain(
+                    &runtime_peer_cert_chain_data.data
+                        [..(runtime_peer_cert_chain_data.data_size as usize)],
+                    -1,
+                )?;
+            let leaf_cert = &runtime_peer_cert_chain_data.data[leaf_cert_begin..leaf_cert_end];
+            match crypto::get_cert_rsa_key_bits(leaf_cert) {
+                Ok(Some(key_bits))
+                    if key_bits < self.common.config_info.min_rsa_key_bits as usize =>
+                {
+                    error!("leaf cert RSA key of {} bits - too small!\n", key_bits);
+                    return Err(SPDM_STATUS_WEAK_CERT_KEY);
+                }
+                Ok(_) => {}
+                Err(_) => return Err(SPDM_STATUS_INVALID_CERT),
+            }
+            info!("1.3. leaf cert key size is verified!\n");
+        }
+
         //
         // 2. verify the authority of cert chain if provisioned
         //
@@ -319,6 +510,96 @@ impl<'a> RequesterContext<'a> {
         }
 
         info!("cert_chain verification - pass!\n");
+        Ok(trust)
+    }
+
+    /// Check `peer_cert_chain_temp` against the digest DIGESTS reported for
+    /// `slot_id`, if any. A responder serving a chain other than the one it
+    /// advertised gets SPDM_STATUS_CERT_DIGEST_MISMATCH.
+    fn verify_spdm_certificate_chain_digest(&self, slot_id: u8) -> SpdmResult {
+        let digest = match &self.common.peer_info.peer_digests[slot_id as usize] {
+            Some(digest) => digest,
+            None => return Ok(()),
+        };
+        let peer_cert_chain = self
+            .common
+            .peer_info
+            .peer_cert_chain_temp
+            .as_ref()
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        let cert_chain_hash = crypto::hash::hash_all(
+            self.common.negotiate_info.base_hash_sel,
+            peer_cert_chain.as_ref(),
+        )
+        .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
+        if cert_chain_hash.as_ref() != digest.as_ref() {
+            error!("!!! certificate : slot {} digest mismatch !!!\n", slot_id);
+            return Err(SPDM_STATUS_CERT_DIGEST_MISMATCH);
+        }
         Ok(())
     }
+
+    /// Check the chain of `slot_id`, as fetched and verified by
+    /// `send_receive_spdm_certificate`, against a set of acceptable root
+    /// certificates (DER) instead of the single `peer_root_cert_data`.
+    ///
+    /// Returns the index in `trust_anchors` of the root the chain starts
+    /// with, SPDM_STATUS_INVALID_CERT if it is none of them.
+    pub fn verify_peer_cert_chain_trust_anchors(
+        &self,
+        slot_id: u8,
+        trust_anchors: &[&[u8]],
+    ) -> SpdmResult<usize> {
+        let peer_cert_chain = self
+            .common
+            .peer_info
+            .peer_cert_chain
+            .get(slot_id as usize)
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
+            .as_ref()
+            .ok_or_else(|| {
+                error!("peer_cert_chain of slot {} is not populated!\n", slot_id);
+                SPDM_STATUS_INVALID_PARAMETER
+            })?;
+        let header = SpdmCertChainHeader::parse(
+            peer_cert_chain.as_ref(),
+            self.common.negotiate_info.base_hash_sel,
+        )
+        .ok_or(SPDM_STATUS_INVALID_CERT)?;
+
+        let der_cert_chain = &peer_cert_chain.as_ref()[header.der_offset..];
+        let (root_cert_begin, root_cert_end) =
+            crypto::cert_operation::get_cert_from_cert_chain(der_cert_chain, 0)?;
+        let root_cert = &der_cert_chain[root_cert_begin..root_cert_end];
+
+        match trust_anchors
+            .iter()
+            .position(|trust_anchor| *trust_anchor == root_cert)
+        {
+            Some(index) => {
+                info!("root cert is trust anchor {}\n", index);
+                Ok(index)
+            }
+            None => {
+                error!("root cert is none of the trust anchors!\n");
+                Err(SPDM_STATUS_INVALID_CERT)
+            }
+        }
+    }
+
+    // A chain of one self-issued certificate, accepted without a chain
+    // only when `allow_self_signed_peer_cert` is set. It must still verify
+    // with its own key and be within its validity period.
+    fn is_single_self_signed_cert_allowed(&self, cert_chain: &[u8]) -> bool {
+        if !self.common.config_info.allow_self_signed_peer_cert {
+            return false;
+        }
+        match crypto::cert_operation::get_cert_from_cert_chain(cert_chain, 0) {
+            Ok((0, cert_end)) if cert_end == cert_chain.len() => {
+                crypto::is_cert_self_issued(cert_chain) == Ok(true)
+                    && crypto::cert_operation::verify_self_signed_cert(cert_chain).is_ok()
+            }
+            _ => false,
+        }
+    }
 }
@@ -2,21 +2,116 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use crate::error::{SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD};
+use crate::error::{SpdmResult, SpdmStatus, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD};
 use crate::message::*;
+use crate::protocol::{SpdmDigestStruct, SpdmKeyUsageMask, SPDM_MAX_SLOT_NUMBER};
 use crate::requester::*;
 
-impl<'a> RequesterContext<'a> {
+/// Per-slot outcome of `send_receive_spdm_digest_slots`, indexed by slot
+/// id. Slots outside `slot_mask` are None.
+#[derive(Debug, Clone, Default)]
+pub struct SpdmDigestSlotResults {
+    pub slot_mask: u8,
+    pub slots: [Option<SpdmResult<SpdmDigestStruct>>; SPDM_MAX_SLOT_NUMBER],
+}
+
+impl SpdmDigestSlotResults {
+    /// Slots with a well-formed digest and a chain behind it; an all-zero
+    /// digest marks an empty slot.
+    pub fn good_slot_mask(&self) -> u8 {
+        let mut good_slot_mask = 0u8;
+        for (slot_id, slot) in self.slots.iter().enumerate() {
+            if let Some(Ok(digest)) = slot {
+                if digest.as_ref().iter().any(|b| *b != 0) {
+                    good_slot_mask |= 1 << slot_id;
+                }
+            }
+        }
+        good_slot_mask
+    }
+
+    /// The first slot whose digest could not be used, and why.
+    pub fn failed_slot(&self) -> Option<(u8, SpdmStatus)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .find_map(|(slot_id, slot)| match slot {
+                Some(Err(status)) => Some((slot_id as u8, *status)),
+                _ => None,
+            })
+    }
+}
+
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_digest(&mut self, session_id: Option<u32>) -> SpdmResult {
+        self.timed(SpdmRequesterOperation::Digests, |this| {
+            info!("send spdm digest\n");
+
+            let mut send_buffer = [0u8; MSG_SIZE];
+            let mut receive_buffer = [0u8; MSG_SIZE];
+            let (send_used, used) = this.send_receive_spdm_digest_raw(
+                session_id,
+                &mut send_buffer,
+                &mut receive_buffer,
+            )?;
+
+            this.handle_spdm_digest_response(
+                session_id,
+                &send_buffer[..send_used],
+                &receive_buffer[..used],
+            )?;
+            if session_id.is_none() {
+                this.advance_connection_state(SpdmConnectionState::SpdmConnectionAfterDigest)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Like `send_receive_spdm_digest`, but a DIGESTS response that breaks
+    /// off in the middle of the digests still returns the slots before the
+    /// break, with the failing slot and every masked slot after it set to
+    /// the error. `peer_provisioned_slot_mask` covers the good slots only.
+    ///
+    /// Requires at least `SpdmConnectionNegotiated`.
+    pub fn send_receive_spdm_digest_slots(
+        &mut self,
+        session_id: Option<u32>,
+    ) -> SpdmResult<SpdmDigestSlotResults> {
         info!("send spdm digest\n");
 
+        let mut send_buffer = [0u8; MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
+        let (send_used, used) =
+            self.send_receive_spdm_digest_raw(session_id, &mut send_buffer, &mut receive_buffer)?;
+
+        let slot_results = self.handle_spdm_digest_slots_response(
+            session_id,
+            &send_buffer[..send_used],
+            &receive_buffer[..used],
+        )?;
+        if session_id.is_none() {
+            self.advance_connection_state(SpdmConnectionState::SpdmConnectionAfterDigest)?;
+        }
+        Ok(slot_results)
+    }
+
+    fn send_receive_spdm_digest_raw(
+        &mut self,
+        session_id: Option<u32>,
+        send_buffer: &mut [u8],
+        receive_buffer: &mut [u8],
+    ) -> SpdmResult<(usize, usize)> {
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
         self.common.reset_buffer_via_request_code(
             SpdmRequestResponseCode::SpdmRequestGetDigests,
             session_id,
         );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-        let send_used = self.encode_spdm_digest(&mut send_buffer)?;
+        let send_used = self.encode_spdm_digest(send_buffer)?;
         match session_id {
             Some(session_id) => {
                 self.send_secured_message(session_id, &send_buffer[..send_used], false)?;
@@ -26,19 +121,11 @@ impl<'a> RequesterContext<'a> {
             }
         }
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
         let used = match session_id {
-            Some(session_id) => {
-                self.receive_secured_message(session_id, &mut receive_buffer, false)?
-            }
-            None => self.receive_message(&mut receive_buffer, false)?,
+            Some(session_id) => self.receive_secured_message(session_id, receive_buffer, false)?,
+            None => self.receive_message(receive_buffer, false)?,
         };
-
-        self.handle_spdm_digest_response(
-            session_id,
-            &send_buffer[..send_used],
-            &receive_buffer[..used],
-        )
+        Ok((send_used, used))
     }
 
     pub fn encode_spdm_digest(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
@@ -81,6 +168,34 @@ impl<'a> RequesterContext<'a> {
                                 Some(_session_id) => {}
                             }
 
+                            // digests are packed in slot order, and a masked slot
+                            // reporting an all-zero digest has no chain behind it.
+                            let mut provisioned_slot_mask = 0u8;
+                            let mut digest_index = 0usize;
+                            let multi_key_conn = self.common.negotiate_info.multi_key_conn_rsp();
+                            self.common.peer_info.peer_digests = Default::default();
+                            self.common.peer_info.peer_key_usage_mask = Default::default();
+                            for slot_id in 0..SPDM_MAX_SLOT_NUMBER {
+                                if digests.slot_mask & (1 << slot_id) == 0 {
+                                    continue;
+                                }
+                                let digest = &digests.digests[digest_index];
+                                if digest.as_ref().iter().any(|b| *b != 0) {
+                                    provisioned_slot_mask |= 1 << slot_id;
+                                    self.common.peer_info.peer_digests[slot_id] =
+                                        Some(digest.clone());
+                                } else {
+                                    info!("!!! digests : slot {} is empty !!!\n", slot_id);
+                                }
+                                if multi_key_conn {
+                                    self.common.peer_info.peer_key_usage_mask[slot_id] =
+                                        Some(digests.key_usage_mask[digest_index]);
+                                }
+                                digest_index += 1;
+                            }
+                            self.common.peer_info.peer_provisioned_slot_mask =
+                                Some(provisioned_slot_mask);
+
                             Ok(())
                         } else {
                             error!("!!! digests : fail !!!\n");
@@ -100,4 +215,91 @@ impl<'a> RequesterContext<'a> {
             None => Err(SPDM_STATUS_INVALID_MSG_FIELD),
         }
     }
+
+    pub fn handle_spdm_digest_slots_response(
+        &mut self,
+        session_id: Option<u32>,
+        send_buffer: &[u8],
+        receive_buffer: &[u8],
+    ) -> SpdmResult<SpdmDigestSlotResults> {
+        let mut reader = Reader::init(receive_buffer);
+        let message_header =
+            SpdmMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+        if message_header.version != self.common.negotiate_info.spdm_version_sel {
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+        match message_header.request_response_code {
+            SpdmRequestResponseCode::SpdmResponseDigests => {}
+            SpdmRequestResponseCode::SpdmResponseError => {
+                self.spdm_handle_error_response_main(
+                    session_id,
+                    receive_buffer,
+                    SpdmRequestResponseCode::SpdmRequestGetDigests,
+                    SpdmRequestResponseCode::SpdmResponseDigests,
+                )?;
+                return Err(SPDM_STATUS_ERROR_PEER);
+            }
+            _ => return Err(SPDM_STATUS_ERROR_PEER),
+        }
+
+        u8::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?; // param1
+        let slot_mask = u8::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?; // param2
+
+        let mut slot_results = SpdmDigestSlotResults {
+            slot_mask,
+            ..Default::default()
+        };
+        let mut truncated = false;
+        for slot_id in 0..SPDM_MAX_SLOT_NUMBER {
+            if slot_mask & (1 << slot_id) == 0 {
+                continue;
+            }
+            let digest = if truncated {
+                None
+            } else {
+                SpdmDigestStruct::spdm_read(&mut self.common, &mut reader)
+            };
+            slot_results.slots[slot_id] = match digest {
+                Some(digest) => Some(Ok(digest)),
+                None => {
+                    error!("!!! digests : slot {} is malformed !!!\n", slot_id);
+                    truncated = true;
+                    Some(Err(SPDM_STATUS_INVALID_MSG_FIELD))
+                }
+            };
+        }
+        debug!("!!! digests : {:02x?}\n", slot_results);
+
+        // a short tail leaves the slots without a key usage, as for SPDM 1.2.
+        let mut key_usage_mask = [None; SPDM_MAX_SLOT_NUMBER];
+        if !truncated && self.common.negotiate_info.multi_key_conn_rsp() {
+            let slot_count = slot_mask.count_ones() as usize;
+            let mut tail_ok = true;
+            for _ in 0..slot_count * 2 {
+                tail_ok &= u8::read(&mut reader).is_some(); // KeyPairID, CertificateInfo
+            }
+            for (slot_id, mask) in key_usage_mask.iter_mut().enumerate() {
+                if slot_mask & (1 << slot_id) != 0 && tail_ok {
+                    *mask = SpdmKeyUsageMask::read(&mut reader);
+                }
+            }
+        }
+
+        // the transcript takes the response as it was received.
+        if session_id.is_none() {
+            self.common.append_message_b(send_buffer)?;
+            self.common.append_message_b(receive_buffer)?;
+        }
+
+        let good_slot_mask = slot_results.good_slot_mask();
+        self.common.peer_info.peer_provisioned_slot_mask = Some(good_slot_mask);
+        for (slot_id, slot) in slot_results.slots.iter().enumerate() {
+            self.common.peer_info.peer_digests[slot_id] = match slot {
+                Some(Ok(digest)) if good_slot_mask & (1 << slot_id) != 0 => Some(digest.clone()),
+                _ => None,
+            };
+            self.common.peer_info.peer_key_usage_mask[slot_id] = key_usage_mask[slot_id];
+        }
+        Ok(slot_results)
+    }
 }
@@ -0,0 +1,432 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+use crate::crypto;
+use crate::error::{
+    SpdmResult, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD,
+    SPDM_STATUS_UNSUPPORTED_CAP, SPDM_STATUS_VERIF_FAIL,
+};
+use crate::message::*;
+use crate::protocol::*;
+use crate::requester::*;
+
+// NumberOfEntries (4) + MELEntriesLen (4) + Reserved (4)
+const SPDM_MEL_HEADER_SIZE: usize = 12;
+
+/// One entry of a DMTF measurement extension log.
+#[derive(Debug, Clone)]
+pub struct SpdmMelEntry {
+    pub mel_index: u32,
+    // index of the measurement block this entry was extended into.
+    pub meas_index: u32,
+    pub measurement: SpdmDmtfMeasurementStructure,
+}
+
+impl Codec for SpdmMelEntry {
+    fn encode(&self, bytes: &mut Writer) -> Result<usize, codec::EncodeErr> {
+        let mut cnt = 0usize;
+        cnt += self.mel_index.encode(bytes)?;
+        cnt += self.meas_index.encode(bytes)?;
+        cnt += 0u64.encode(bytes)?; // reserved
+        cnt += self.measurement.encode(bytes)?;
+        Ok(cnt)
+    }
+
+    fn read(r: &mut Reader) -> Option<SpdmMelEntry> {
+        let mel_index = u32::read(r)?;
+        let meas_index = u32::read(r)?;
+        u64::read(r)?; // reserved
+        let measurement = SpdmDmtfMeasurementStructure::read(r)?;
+        Some(SpdmMelEntry {
+            mel_index,
+            meas_index,
+            measurement,
+        })
+    }
+}
+
+/// Measurement extension log (MEL) as returned by
+/// GET_MEASUREMENT_EXTENSION_LOG, in DMTF format.
+#[derive(Debug, Clone, Default)]
+pub struct SpdmMeasurementExtensionLog {
+    pub entries: Vec<SpdmMelEntry>,
+}
+
+impl SpdmMeasurementExtensionLog {
+    /// Parse a whole log. Returns None unless the entries fill exactly
+    /// MELEntriesLen and their MEL indices increase.
+    pub fn from_bytes(bytes: &[u8]) -> Option<SpdmMeasurementExtensionLog> {
+        let mut reader = Reader::init(bytes);
+        let number_of_entries = u32::read(&mut reader)?;
+        let mel_entries_len = u32::read(&mut reader)? as usize;
+        u32::read(&mut reader)?; // reserved
+        if bytes.len() != SPDM_MEL_HEADER_SIZE + mel_entries_len {
+            return None;
+        }
+
+        let mut entries: Vec<SpdmMelEntry> = Vec::new();
+        for _ in 0..number_of_entries {
+            let entry = SpdmMelEntry::read(&mut reader)?;
+            if let Some(last) = entries.last() {
+                if entry.mel_index <= last.mel_index {
+                    return None;
+                }
+            }
+            entries.push(entry);
+        }
+        if reader.any_left() {
+            return None;
+        }
+        Some(SpdmMeasurementExtensionLog { entries })
+    }
+
+    /// Replay the extensions of every measurement block in `blocks` that
+    /// has entries in the log: starting from zeros, each entry extends the
+    /// running value as H(running || digest), the digest being the entry
+    /// value itself or the hash of a raw value. The result has to match the
+    /// block's digest. Blocks without entries are not checked.
+    pub fn verify_extensions(
+        &self,
+        measurement_hash_algo: SpdmMeasurementHashAlgo,
+        blocks: &[SpdmMeasurementBlockStructure],
+    ) -> SpdmResult {
+        let hash_algo = match measurement_hash_algo {
+            SpdmMeasurementHashAlgo::TPM_ALG_SHA_256 => SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+            SpdmMeasurementHashAlgo::TPM_ALG_SHA_384 => SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmMeasurementHashAlgo::TPM_ALG_SHA_512 => SpdmBaseHashAlgo::TPM_ALG_SHA_512,
+            _ => return Err(SPDM_STATUS_UNSUPPORTED_CAP),
+        };
+        let hash_size = hash_algo.get_size() as usize;
+
+        for block in blocks {
+            let mut running = [0u8; 2 * SPDM_MAX_HASH_SIZE];
+            let mut extended = false;
+            for entry in self
+                .entries
+                .iter()
+                .filter(|entry| entry.meas_index == block.index as u32)
+            {
+                let value = &entry.measurement.value[..entry.measurement.value_size as usize];
+                match entry.measurement.representation {
+                    SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest => {
+                        if value.len() != hash_size {
+                            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                        }
+                        running[hash_size..2 * hash_size].copy_from_slice(value);
+                    }
+                    _ => {
+                        let digest = crypto::hash::hash_all(hash_algo, value)
+                            .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
+                        running[hash_size..2 * hash_size]
+                            .copy_from_slice(&digest.as_ref()[..hash_size]);
+                    }
+                }
+                let extend = crypto::hash::hash_all(hash_algo, &running[..2 * hash_size])
+                    .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
+                running[..hash_size].copy_from_slice(&extend.as_ref()[..hash_size]);
+                extended = true;
+            }
+            if !extended {
+                continue;
+            }
+
+            let measurement = &block.measurement;
+            if measurement.representation
+                != SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest
+                || measurement.value[..measurement.value_size as usize] != running[..hash_size]
+            {
+                error!(
+                    "!!! measurement extension log : block {} mismatch !!!\n",
+                    block.index
+                );
+                return Err(SPDM_STATUS_VERIF_FAIL);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Fetch the whole measurement extension log, portion by portion, and
+    /// parse it. Needs SPDM 1.3 and MEL_CAP from the responder.
+    ///
+    /// Requires at least `SpdmConnectionNegotiated`.
+    pub fn send_receive_spdm_measurement_extension_log(
+        &mut self,
+        session_id: Option<u32>,
+    ) -> SpdmResult<SpdmMeasurementExtensionLog> {
+        info!("send spdm get measurement extension log\n");
+
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+        if self.common.negotiate_info.spdm_version_sel.get_u8()
+            < SpdmVersion::SpdmVersion13.get_u8()
+            || !self
+                .common
+                .negotiate_info
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::MEL_CAP)
+        {
+            return Err(SPDM_STATUS_UNSUPPORTED_CAP);
+        }
+
+        self.common.reset_buffer_via_request_code(
+            SpdmRequestResponseCode::SpdmRequestGetMeasurementExtensionLog,
+            session_id,
+        );
+
+        let portion_length = self.mel_portion_length();
+        let mut mel = Vec::new();
+        loop {
+            let portion = self.send_receive_spdm_measurement_extension_log_partial(
+                session_id,
+                mel.len() as u32,
+                portion_length,
+            )?;
+            mel.extend_from_slice(
+                &portion.measurement_extension_log[..portion.portion_length as usize],
+            );
+            if mel.len() + portion.remainder_length as usize > MAX_SPDM_MEL_SIZE {
+                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+            }
+            if portion.remainder_length == 0 {
+                break;
+            }
+            if portion.portion_length == 0 {
+                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+            }
+        }
+
+        let log = SpdmMeasurementExtensionLog::from_bytes(&mel).ok_or_else(|| {
+            error!("!!! measurement extension log : fail !!!\n");
+            SPDM_STATUS_INVALID_MSG_FIELD
+        })?;
+        debug!(
+            "!!! measurement extension log : {} entries\n",
+            log.entries.len()
+        );
+        Ok(log)
+    }
+
+    fn send_receive_spdm_measurement_extension_log_partial(
+        &mut self,
+        session_id: Option<u32>,
+        offset: u32,
+        length: u32,
+    ) -> SpdmResult<SpdmMeasurementExtensionLogResponsePayload> {
+        let mut send_buffer = [0u8; MSG_SIZE];
+        let send_used =
+            self.encode_spdm_measurement_extension_log(offset, length, &mut send_buffer)?;
+        match session_id {
+            Some(session_id) => {
+                self.send_secured_message(session_id, &send_buffer[..send_used], false)?;
+            }
+            None => {
+                self.send_message(&send_buffer[..send_used])?;
+            }
+        }
+
+        let mut receive_buffer = [0u8; MSG_SIZE];
+        let used = match session_id {
+            Some(session_id) => {
+                self.receive_secured_message(session_id, &mut receive_buffer, false)?
+            }
+            None => self.receive_message(&mut receive_buffer, false)?,
+        };
+        self.handle_spdm_measurement_extension_log_response(
+            session_id,
+            length,
+            &receive_buffer[..used],
+        )
+    }
+
+    pub fn encode_spdm_measurement_extension_log(
+        &mut self,
+        offset: u32,
+        length: u32,
+        buf: &mut [u8],
+    ) -> SpdmResult<usize> {
+        let mut writer = Writer::init(buf);
+        let request = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code:
+                    SpdmRequestResponseCode::SpdmRequestGetMeasurementExtensionLog,
+            },
+            payload: SpdmMessagePayload::SpdmGetMeasurementExtensionLogRequest(
+                SpdmGetMeasurementExtensionLogRequestPayload { offset, length },
+            ),
+        };
+        request.spdm_encode(&mut self.common, &mut writer)
+    }
+
+    pub fn handle_spdm_measurement_extension_log_response(
+        &mut self,
+        session_id: Option<u32>,
+        length: u32,
+        receive_buffer: &[u8],
+    ) -> SpdmResult<SpdmMeasurementExtensionLogResponsePayload> {
+        let mut reader = Reader::init(receive_buffer);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(message_header) => {
+                if message_header.version != self.common.negotiate_info.spdm_version_sel {
+                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                }
+                match message_header.request_response_code {
+                    SpdmRequestResponseCode::SpdmResponseMeasurementExtensionLog => {
+                        let portion = SpdmMeasurementExtensionLogResponsePayload::spdm_read(
+                            &mut self.common,
+                            &mut reader,
+                        );
+                        match portion {
+                            Some(portion) if portion.portion_length <= length => {
+                                debug!(
+                                    "!!! measurement extension log portion : {} remainder {}\n",
+                                    portion.portion_length, portion.remainder_length
+                                );
+                                Ok(portion)
+                            }
+                            _ => {
+                                error!("!!! measurement extension log portion : fail !!!\n");
+                                Err(SPDM_STATUS_INVALID_MSG_FIELD)
+                            }
+                        }
+                    }
+                    SpdmRequestResponseCode::SpdmResponseError => {
+                        let status = self.spdm_handle_error_response_main(
+                            session_id,
+                            receive_buffer,
+                            SpdmRequestResponseCode::SpdmRequestGetMeasurementExtensionLog,
+                            SpdmRequestResponseCode::SpdmResponseMeasurementExtensionLog,
+                        );
+                        match status {
+                            Err(status) => Err(status),
+                            Ok(()) => Err(SPDM_STATUS_ERROR_PEER),
+                        }
+                    }
+                    _ => Err(SPDM_STATUS_ERROR_PEER),
+                }
+            }
+            None => Err(SPDM_STATUS_INVALID_MSG_FIELD),
+        }
+    }
+
+    /// Length asked for in each GET_MEASUREMENT_EXTENSION_LOG, capped by
+    /// what a MEASUREMENT_EXTENSION_LOG response may carry within the
+    /// negotiated DataTransferSize.
+    fn mel_portion_length(&self) -> u32 {
+        let mut portion_length = MAX_SPDM_MEL_PORTION_LEN as u32;
+
+        // SPDMVersion, RequestResponseCode, Param1, Param2, PortionLength
+        // and RemainderLength come before the log portion.
+        let data_transfer_size = self.common.negotiate_info.req_data_transfer_size_sel;
+        if data_transfer_size != 0 {
+            portion_length = portion_length.min(data_transfer_size.saturating_sub(12));
+        }
+        portion_length.max(1)
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+
+    fn mel_entry(mel_index: u32, meas_index: u32, value: &[u8]) -> Vec<u8> {
+        let mut entry = Vec::new();
+        entry.extend_from_slice(&mel_index.to_le_bytes());
+        entry.extend_from_slice(&meas_index.to_le_bytes());
+        entry.extend_from_slice(&[0u8; 8]);
+        entry.push(0x81); // raw bit stream, firmware
+        entry.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        entry.extend_from_slice(value);
+        entry
+    }
+
+    fn mel(entries: &[Vec<u8>]) -> Vec<u8> {
+        let entries_len: usize = entries.iter().map(|entry| entry.len()).sum();
+        let mut mel = Vec::new();
+        mel.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        mel.extend_from_slice(&(entries_len as u32).to_le_bytes());
+        mel.extend_from_slice(&[0u8; 4]);
+        for entry in entries {
+            mel.extend_from_slice(entry);
+        }
+        mel
+    }
+
+    #[test]
+    fn test_case0_measurement_extension_log_parse() {
+        let bytes = mel(&[
+            mel_entry(0, 1, b"bootloader"),
+            mel_entry(1, 1, b"kernel"),
+            mel_entry(2, 2, b"config"),
+        ]);
+        let log = SpdmMeasurementExtensionLog::from_bytes(&bytes).unwrap();
+        assert_eq!(log.entries.len(), 3);
+        assert_eq!(log.entries[1].mel_index, 1);
+        assert_eq!(log.entries[1].meas_index, 1);
+        assert_eq!(
+            log.entries[1].measurement.r#type,
+            SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware
+        );
+        assert_eq!(&log.entries[2].measurement.value[..6], b"config");
+
+        // truncated, trailing bytes and out of order entries.
+        assert!(SpdmMeasurementExtensionLog::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(SpdmMeasurementExtensionLog::from_bytes(&longer).is_none());
+        let bytes = mel(&[mel_entry(1, 1, b"kernel"), mel_entry(1, 1, b"kernel")]);
+        assert!(SpdmMeasurementExtensionLog::from_bytes(&bytes).is_none());
+    }
+
+    #[test]
+    fn test_case1_measurement_extension_log_verify() {
+        let bytes = mel(&[mel_entry(0, 1, b"bootloader"), mel_entry(1, 1, b"kernel")]);
+        let log = SpdmMeasurementExtensionLog::from_bytes(&bytes).unwrap();
+
+        let algo = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        let mut running = [0u8; 2 * SHA384_DIGEST_SIZE];
+        for value in [&b"bootloader"[..], &b"kernel"[..]] {
+            let digest = crypto::hash::hash_all(algo, value).unwrap();
+            running[SHA384_DIGEST_SIZE..].copy_from_slice(digest.as_ref());
+            let extend = crypto::hash::hash_all(algo, &running).unwrap();
+            running[..SHA384_DIGEST_SIZE].copy_from_slice(extend.as_ref());
+        }
+        let mut block = SpdmMeasurementBlockStructure {
+            index: 1,
+            measurement_specification: SpdmMeasurementSpecification::DMTF,
+            measurement_size: 3 + SHA384_DIGEST_SIZE as u16,
+            measurement: SpdmDmtfMeasurementStructure {
+                r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware,
+                representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest,
+                value_size: SHA384_DIGEST_SIZE as u16,
+                ..Default::default()
+            },
+        };
+        block.measurement.value[..SHA384_DIGEST_SIZE]
+            .copy_from_slice(&running[..SHA384_DIGEST_SIZE]);
+        // block 2 has no entries and is left alone.
+        let other = SpdmMeasurementBlockStructure {
+            index: 2,
+            ..block.clone()
+        };
+        assert!(log
+            .verify_extensions(
+                SpdmMeasurementHashAlgo::TPM_ALG_SHA_384,
+                &[block.clone(), other]
+            )
+            .is_ok());
+
+        block.measurement.value[0] ^= 0xFF;
+        assert_eq!(
+            log.verify_extensions(SpdmMeasurementHashAlgo::TPM_ALG_SHA_384, &[block]),
+            Err(SPDM_STATUS_VERIF_FAIL)
+        );
+    }
+}
@@ -2,41 +2,114 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+#[cfg(not(feature = "hashed-transcript-data"))]
+use crate::common::ManagedBufferL1L2;
 use crate::crypto;
+#[cfg(feature = "alloc")]
+use crate::error::SpdmStatus;
 #[cfg(feature = "hashed-transcript-data")]
 use crate::error::SPDM_STATUS_INVALID_STATE_LOCAL;
 use crate::error::{
-    SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_CRYPTO_ERROR, SPDM_STATUS_ERROR_PEER,
-    SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_VERIF_FAIL,
+    SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_CONTENT_CHANGE_UNSUPPORTED_PEER,
+    SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_MSG_FIELD,
+    SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_MEAS_COUNT_MISMATCH, SPDM_STATUS_MEAS_INVALID_INDEX,
+    SPDM_STATUS_MEAS_RAW_BIT_STREAM_UNAVAILABLE, SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE,
+    SPDM_STATUS_NONCE_ECHO_PEER, SPDM_STATUS_NONCE_REUSE_PEER, SPDM_STATUS_VERIF_FAIL,
 };
 use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Per-slot outcome of `fetch_all_measurements_per_slot`, indexed by slot
+/// id. Slots outside `slot_mask` are None.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct SpdmMeasurementSlotResults {
+    pub slot_mask: u8,
+    pub slots: [Option<SpdmResult<Vec<SpdmMeasurementBlockStructure>>>; SPDM_MAX_SLOT_NUMBER],
+}
+
+#[cfg(feature = "alloc")]
+impl SpdmMeasurementSlotResults {
+    /// The first slot whose measurements could not be verified, and why.
+    pub fn failed_slot(&self) -> Option<(u8, SpdmStatus)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .find_map(|(slot_id, slot)| match slot {
+                Some(Err(status)) => Some((slot_id as u8, *status)),
+                _ => None,
+            })
+    }
+}
+
+/// What `fetch_all_measurements_per_slot` does when a slot fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpdmSlotErrorPolicy {
+    /// Return the status of the first slot that failed.
+    Abort,
+    /// Record the status in the slot and go on with the next one.
+    Collect,
+}
 
-impl<'a> RequesterContext<'a> {
+// The base asymmetric algorithm other than `negotiated` whose signatures are
+// `size` bytes, if any.
+fn other_asym_algo_of_signature_size(
+    negotiated: SpdmBaseAsymAlgo,
+    size: usize,
+) -> Option<SpdmBaseAsymAlgo> {
+    [
+        SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048,
+        SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072,
+        SpdmBaseAsymAlgo::TPM_ALG_RSASSA_4096,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+        SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519,
+    ]
+    .iter()
+    .find(|asym_algo| {
+        asym_algo.get_size() != negotiated.get_size() && asym_algo.get_size() as usize == size
+    })
+    .copied()
+}
+
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
     fn send_receive_spdm_measurement_record(
         &mut self,
         session_id: Option<u32>,
         measurement_attributes: SpdmMeasurementAttributes,
         measurement_operation: SpdmMeasurementOperation,
+        requester_nonce: Option<&[u8]>,
         spdm_measurement_record_structure: &mut SpdmMeasurementRecordStructure,
         slot_id: u8,
     ) -> SpdmResult<u8> {
         info!("send spdm measurement\n");
 
-        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        }
+        let measurement_attributes = self.check_measurement_request(
+            session_id,
+            measurement_attributes,
+            measurement_operation,
+            slot_id,
+        )?;
 
         self.common.reset_buffer_via_request_code(
             SpdmRequestResponseCode::SpdmRequestGetMeasurements,
             session_id,
         );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let send_used = self.encode_spdm_measurement_record(
             measurement_attributes,
             measurement_operation,
+            requester_nonce,
             slot_id,
             &mut send_buffer,
         )?;
@@ -50,7 +123,7 @@ impl<'a> RequesterContext<'a> {
         }
 
         // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let used = match session_id {
             Some(session_id) => {
                 self.receive_secured_message(session_id, &mut receive_buffer, true)?
@@ -69,16 +142,128 @@ impl<'a> RequesterContext<'a> {
         )
     }
 
+    fn measurement_nonces(&mut self, session_id: Option<u32>) -> SpdmResult<&mut SpdmNonceHistory> {
+        match session_id {
+            None => Ok(&mut self.measurement_nonces),
+            Some(session_id) => Ok(&mut self
+                .common
+                .get_session_via_id(session_id)
+                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
+                .runtime_info
+                .measurement_nonces),
+        }
+    }
+
+    // Returns the attributes to send, without SIGNATURE_REQUESTED when the
+    // responder cannot sign and the config allows falling back to unsigned.
+    fn check_measurement_request(
+        &mut self,
+        session_id: Option<u32>,
+        mut measurement_attributes: SpdmMeasurementAttributes,
+        measurement_operation: SpdmMeasurementOperation,
+        slot_id: u8,
+    ) -> SpdmResult<SpdmMeasurementAttributes> {
+        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+
+        // indices from 0xF0 on are reserved or special blocks like the
+        // device mode, which the total number does not count.
+        if let (SpdmMeasurementOperation::Unknown(index), Some(total_number)) =
+            (measurement_operation, self.peer_measurement_total_number)
+        {
+            if index < 0xF0 && index > total_number {
+                error!(
+                    "measurement index {} is beyond the {} reported\n",
+                    index, total_number
+                );
+                return Err(SPDM_STATUS_MEAS_INVALID_INDEX);
+            }
+        }
+
+        self.last_measurement_signature_dropped = false;
+        if measurement_attributes.contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED)
+            && !self
+                .common
+                .negotiate_info
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::MEAS_CAP_SIG)
+        {
+            if !self.common.config_info.unsigned_measurement_fallback {
+                error!("responder does not sign measurements\n");
+                return Err(SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE);
+            }
+            warn!("responder does not sign measurements, requesting them unsigned\n");
+            measurement_attributes -= SpdmMeasurementAttributes::SIGNATURE_REQUESTED;
+            self.last_measurement_signature_dropped = true;
+        }
+
+        // do not ask a key restricted to other uses to sign measurements.
+        if measurement_attributes.contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED) {
+            if let Some(key_usage_mask) =
+                self.common.peer_info.peer_key_usage_mask[slot_id as usize]
+            {
+                if !key_usage_mask.contains(SpdmKeyUsageMask::MEASUREMENT_USE) {
+                    error!("slot {} is not usable for measurement signing\n", slot_id);
+                    return Err(SPDM_STATUS_INVALID_PARAMETER);
+                }
+            }
+
+            #[cfg(all(feature = "alloc", not(feature = "hashed-transcript-data")))]
+            if !self.common.l1l2_transcript_signable(session_id) {
+                error!("L1/L2 transcript storage cannot back this signature\n");
+                return Err(SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE);
+            }
+
+            // a PSK session authenticates the responder without a certificate,
+            // and there is no HMAC alternative to the measurement signature.
+            // Unless the chain was retrieved anyway the signature cannot be
+            // verified, so only unsigned measurements are of use then.
+            if let Some(session_id) = session_id {
+                let use_psk = self
+                    .common
+                    .get_session_via_id(session_id)
+                    .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
+                    .get_use_psk();
+                if use_psk && self.common.peer_info.peer_cert_chain[slot_id as usize].is_none() {
+                    error!("no certificate chain to verify measurements signed in a PSK session\n");
+                    return Err(SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE);
+                }
+            }
+
+            // without the chain of the slot the signature could not be
+            // verified, so do not have the responder sign at all.
+            if !self.decode_only
+                && self.common.peer_info.peer_cert_chain[slot_id as usize].is_none()
+            {
+                error!("peer_cert_chain of slot {} is not populated!\n", slot_id);
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
+            }
+        }
+        Ok(measurement_attributes)
+    }
+
     pub fn encode_spdm_measurement_record(
         &mut self,
         measurement_attributes: SpdmMeasurementAttributes,
         measurement_operation: SpdmMeasurementOperation,
+        requester_nonce: Option<&[u8]>,
         slot_id: u8,
         buf: &mut [u8],
     ) -> SpdmResult<usize> {
         let mut writer = Writer::init(buf);
         let mut nonce = [0u8; SPDM_NONCE_SIZE];
-        crypto::rand::get_random(&mut nonce)?;
+        match requester_nonce {
+            Some(requester_nonce) => {
+                if requester_nonce.len() != SPDM_NONCE_SIZE {
+                    return Err(SPDM_STATUS_INVALID_PARAMETER);
+                }
+                nonce.copy_from_slice(requester_nonce);
+            }
+            None => {
+                crypto::rand::get_random(&mut nonce)?;
+            }
+        }
 
         let request = SpdmMessage {
             header: SpdmMessageHeader {
@@ -108,6 +293,7 @@ impl<'a> RequesterContext<'a> {
         send_buffer: &[u8],
         receive_buffer: &[u8],
     ) -> SpdmResult<u8> {
+        self.last_measurement_verified = false;
         if measurement_attributes.contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED) {
             self.common.runtime_info.need_measurement_signature = true;
         } else {
@@ -122,6 +308,7 @@ impl<'a> RequesterContext<'a> {
                 }
                 match message_header.request_response_code {
                     SpdmRequestResponseCode::SpdmResponseMeasurements => {
+                        let header_used = reader.used();
                         let measurements = SpdmMeasurementsResponsePayload::spdm_read(
                             &mut self.common,
                             &mut reader,
@@ -130,29 +317,104 @@ impl<'a> RequesterContext<'a> {
                         if let Some(measurements) = measurements {
                             debug!("!!! measurements : {:02x?}\n", measurements);
 
+                            // every MEASUREMENTS carries a fresh responder nonce, one
+                            // of a recent response means it is replayed or the
+                            // responder RNG is broken.
+                            if self
+                                .measurement_nonces(session_id)?
+                                .contains(&measurements.nonce.data)
+                            {
+                                error!("!!! measurements : responder nonce reused !!!\n");
+                                return Err(SPDM_STATUS_NONCE_REUSE_PEER);
+                            }
+
+                            // a responder copying the request nonce into its own
+                            // has not shown it signed over the request nonce.
+                            if measurement_attributes
+                                .contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED)
+                                && send_buffer.get(4..4 + SPDM_NONCE_SIZE)
+                                    == Some(&measurements.nonce.data[..])
+                            {
+                                if self.common.config_info.reject_echoed_measurement_nonce {
+                                    error!("!!! measurements : request nonce echoed !!!\n");
+                                    return Err(SPDM_STATUS_NONCE_ECHO_PEER);
+                                }
+                                warn!("measurements : responder nonce is the request nonce\n");
+                            }
+
+                            // the total number comes without blocks, signed or not.
+                            if measurement_operation
+                                == SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber
+                                && (measurements.measurement_record.number_of_blocks != 0
+                                    || measurements
+                                        .measurement_record
+                                        .measurement_record_length
+                                        .get()
+                                        != 0)
+                            {
+                                error!("!!! measurements : record with the total number !!!\n");
+                                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                            }
+
                             if self.common.negotiate_info.spdm_version_sel.get_u8()
                                 >= SpdmVersion::SpdmVersion12.get_u8()
                             {
                                 self.common.runtime_info.content_changed =
                                     measurements.content_changed;
+                                if self.common.config_info.require_content_change_support
+                                    && measurements.content_changed
+                                        == SpdmMeasurementContentChanged::NOT_SUPPORTED
+                                {
+                                    error!("!!! measurements : no content change support !!!\n");
+                                    return Err(SPDM_STATUS_CONTENT_CHANGE_UNSUPPORTED_PEER);
+                                }
                             }
 
-                            let base_asym_size =
-                                self.common.negotiate_info.base_asym_sel.get_size() as usize;
-                            let temp_used = used
-                                - if self.common.runtime_info.need_measurement_signature {
-                                    base_asym_size
+                            // L1/L2 covers everything up to the signature, so locate
+                            // it from the decoded fields and make sure it is the last
+                            // field of the response.
+                            let signature_offset = header_used + measurements.signature_offset();
+                            let signature_size =
+                                if self.common.runtime_info.need_measurement_signature {
+                                    measurements.signature.data_size as usize
                                 } else {
                                     0
                                 };
+                            if signature_offset + signature_size != used {
+                                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                            }
+                            // what follows the opaque data, transport padding aside,
+                            // being the signature of another algorithm is a responder
+                            // signing with it, tell that from a signature that does
+                            // not verify. A shorter one did not even decode.
+                            if signature_size != 0 {
+                                let trailing_size = receive_buffer.len() - signature_offset;
+                                if let Some(asym_algo) = other_asym_algo_of_signature_size(
+                                    self.common.negotiate_info.base_asym_sel,
+                                    trailing_size,
+                                ) {
+                                    error!(
+                                        "!!! measurements : {:?} signature, {:?} negotiated !!!\n",
+                                        asym_algo, self.common.negotiate_info.base_asym_sel
+                                    );
+                                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                                }
+                            }
 
-                            self.common.append_message_m(session_id, send_buffer)?;
-                            self.common
-                                .append_message_m(session_id, &receive_buffer[..temp_used])?;
+                            if self.decode_only {
+                                info!("decode only: measurements are not verified\n");
+                            } else {
+                                self.common.append_message_m(session_id, send_buffer)?;
+                                self.common.append_message_m(
+                                    session_id,
+                                    &receive_buffer[..signature_offset],
+                                )?;
+                            }
 
                             // verify signature
-                            if measurement_attributes
-                                .contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED)
+                            if !self.decode_only
+                                && measurement_attributes
+                                    .contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED)
                             {
                                 if self
                                     .verify_measurement_signature(
@@ -167,20 +429,52 @@ impl<'a> RequesterContext<'a> {
                                     return Err(SPDM_STATUS_VERIF_FAIL);
                                 } else {
                                     self.common.reset_message_m(session_id);
+                                    self.last_measurement_verified = true;
                                     info!("verify_measurement_signature pass");
                                 }
                             }
 
+                            // an all zero nonce is what a responder not filling it in
+                            // sends, it does not identify the response.
+                            if measurements.nonce.data != [0u8; SPDM_NONCE_SIZE] {
+                                self.measurement_nonces(session_id)?
+                                    .insert(&measurements.nonce.data);
+                            }
+
                             *spdm_measurement_record_structure = SpdmMeasurementRecordStructure {
                                 ..measurements.measurement_record
                             };
 
                             match measurement_operation {
                                 SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber => {
+                                    self.peer_measurement_total_number =
+                                        Some(measurements.number_of_measurement);
                                     Ok(measurements.number_of_measurement)
                                 }
                                 SpdmMeasurementOperation::SpdmMeasurementRequestAll => {
-                                    Ok(measurements.measurement_record.number_of_blocks)
+                                    let number_of_blocks =
+                                        measurements.measurement_record.number_of_blocks;
+                                    // a changed measurement set has to be signalled,
+                                    // the new count is then the one to go by.
+                                    if measurements
+                                        .content_changed
+                                        .contains(SpdmMeasurementContentChanged::DETECTED_CHANGE)
+                                    {
+                                        self.peer_measurement_total_number = Some(number_of_blocks);
+                                    } else if let Some(total_number) =
+                                        self.peer_measurement_total_number
+                                    {
+                                        if self.common.config_info.check_measurement_count
+                                            && number_of_blocks != total_number
+                                        {
+                                            error!(
+                                                "!!! measurements : {} blocks, {} reported, no content change !!!\n",
+                                                number_of_blocks, total_number
+                                            );
+                                            return Err(SPDM_STATUS_MEAS_COUNT_MISMATCH);
+                                        }
+                                    }
+                                    Ok(number_of_blocks)
                                 }
                                 _ => Ok(measurements.measurement_record.number_of_blocks),
                             }
@@ -196,6 +490,18 @@ impl<'a> RequesterContext<'a> {
                             SpdmRequestResponseCode::SpdmRequestGetMeasurements,
                             SpdmRequestResponseCode::SpdmResponseMeasurements,
                         );
+                        // how a responder declines the raw bit stream of an
+                        // index it only has the digest of.
+                        if status == Err(SPDM_STATUS_ERROR_PEER)
+                            && matches!(measurement_operation, SpdmMeasurementOperation::Unknown(_))
+                            && measurement_attributes
+                                .contains(SpdmMeasurementAttributes::RAW_BIT_STREAM_REQUESTED)
+                            && receive_buffer.get(2)
+                                == Some(&SpdmErrorCode::SpdmErrorInvalidRequest.get_u8())
+                        {
+                            error!("!!! measurements : raw bit stream unavailable !!!\n");
+                            return Err(SPDM_STATUS_MEAS_RAW_BIT_STREAM_UNAVAILABLE);
+                        }
                         match status {
                             Err(status) => Err(status),
                             Ok(()) => Err(SPDM_STATUS_ERROR_PEER),
@@ -208,24 +514,340 @@ impl<'a> RequesterContext<'a> {
         }
     }
 
+    /// Requires at least `SpdmConnectionNegotiated`.
+    #[allow(clippy::too_many_arguments)]
     pub fn send_receive_spdm_measurement(
         &mut self,
         session_id: Option<u32>,
         slot_id: u8,
         spdm_measuremente_attributes: SpdmMeasurementAttributes,
         measurement_operation: SpdmMeasurementOperation,
+        requester_nonce: Option<&[u8]>, // random nonce is generated when None
         out_total_number: &mut u8, // out, total number when measurement_operation = SpdmMeasurementQueryTotalNumber
         //      number of blocks got measured.
         spdm_measurement_record_structure: &mut SpdmMeasurementRecordStructure, // out
     ) -> SpdmResult {
-        *out_total_number = self.send_receive_spdm_measurement_record(
+        let operation = if spdm_measuremente_attributes
+            .contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED)
+        {
+            SpdmRequesterOperation::SignedMeasurement
+        } else {
+            SpdmRequesterOperation::Measurement
+        };
+        self.timed(operation, |this| {
+            this.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
+            *out_total_number = this.send_receive_spdm_measurement_record(
+                session_id,
+                spdm_measuremente_attributes,
+                measurement_operation,
+                requester_nonce,
+                spdm_measurement_record_structure,
+                slot_id,
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Have the responder sign a MEASUREMENTS without any measurement
+    /// block, as a freshness proof bound to the request nonce. This is the
+    /// total number query with SIGNATURE_REQUESTED, so the record is empty
+    /// and L1/L2 ends with the nonce and opaque data.
+    ///
+    /// SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE if the responder does not
+    /// sign measurements, `unsigned_measurement_fallback` notwithstanding.
+    /// Returns the total number of measurements the response reports.
+    ///
+    /// Requires at least `SpdmConnectionNegotiated`.
+    pub fn send_receive_spdm_measurement_freshness(
+        &mut self,
+        session_id: Option<u32>,
+        slot_id: u8,
+        requester_nonce: Option<&[u8]>, // random nonce is generated when None
+    ) -> SpdmResult<u8> {
+        if !self
+            .common
+            .negotiate_info
+            .rsp_capabilities_sel
+            .contains(SpdmResponseCapabilityFlags::MEAS_CAP_SIG)
+        {
+            error!("responder does not sign measurements\n");
+            return Err(SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE);
+        }
+
+        let mut total_number = 0u8;
+        let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+        self.send_receive_spdm_measurement(
             session_id,
-            spdm_measuremente_attributes,
-            measurement_operation,
-            spdm_measurement_record_structure,
             slot_id,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            requester_nonce,
+            &mut total_number,
+            &mut spdm_measurement_record_structure,
         )?;
-        Ok(())
+        Ok(total_number)
+    }
+
+    /// Fetch the records at `indices`, one GET_MEASUREMENTS per index,
+    /// returned in the same order.
+    ///
+    /// Only the last request carries SIGNATURE_REQUESTED, if
+    /// `measurement_attributes` has it, so one signature covers the whole
+    /// batch. When the device IO supports pipelining every request is sent
+    /// before the first response is read, otherwise the exchanges run one
+    /// after the other. Either way L1/L2 records each request next to its
+    /// own response, exactly as a serial run would.
+    ///
+    /// Requires at least `SpdmConnectionNegotiated`.
+    #[cfg(feature = "alloc")]
+    pub fn send_receive_spdm_measurement_batch(
+        &mut self,
+        session_id: Option<u32>,
+        slot_id: u8,
+        measurement_attributes: SpdmMeasurementAttributes,
+        indices: &[u8],
+    ) -> SpdmResult<Vec<SpdmMeasurementRecordStructure>> {
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+        let requested_attributes = measurement_attributes;
+        let mut measurement_attributes = measurement_attributes;
+        for index in indices {
+            measurement_attributes = self.check_measurement_request(
+                session_id,
+                requested_attributes,
+                SpdmMeasurementOperation::Unknown(*index),
+                slot_id,
+            )?;
+        }
+
+        let last = indices.len().saturating_sub(1);
+        let request_params = |i: usize| {
+            if i == last {
+                (measurement_attributes, slot_id)
+            } else {
+                (
+                    measurement_attributes - SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                    0,
+                )
+            }
+        };
+
+        let mut measurement_records = Vec::with_capacity(indices.len());
+        if !self.common.device_io.supports_pipelining() {
+            for (i, index) in indices.iter().enumerate() {
+                let (request_attributes, request_slot_id) = request_params(i);
+                let mut measurement_record = SpdmMeasurementRecordStructure::default();
+                self.send_receive_spdm_measurement_record(
+                    session_id,
+                    request_attributes,
+                    SpdmMeasurementOperation::Unknown(*index),
+                    None,
+                    &mut measurement_record,
+                    request_slot_id,
+                )?;
+                measurement_records.push(measurement_record);
+            }
+            return Ok(measurement_records);
+        }
+
+        let mut send_buffers = Vec::with_capacity(indices.len());
+        for (i, index) in indices.iter().enumerate() {
+            let (request_attributes, request_slot_id) = request_params(i);
+            self.common.reset_buffer_via_request_code(
+                SpdmRequestResponseCode::SpdmRequestGetMeasurements,
+                session_id,
+            );
+            let mut send_buffer = vec![0u8; MSG_SIZE];
+            let send_used = self.encode_spdm_measurement_record(
+                request_attributes,
+                SpdmMeasurementOperation::Unknown(*index),
+                None,
+                request_slot_id,
+                &mut send_buffer,
+            )?;
+            send_buffer.truncate(send_used);
+            match session_id {
+                Some(session_id) => self.send_secured_message(session_id, &send_buffer, false)?,
+                None => self.send_message(&send_buffer)?,
+            }
+            send_buffers.push(send_buffer);
+        }
+
+        // drain every response before handling any, so that nothing is left
+        // in flight if one of them is rejected.
+        let mut receive_buffers = Vec::with_capacity(indices.len());
+        for _ in indices.iter() {
+            let mut receive_buffer = vec![0u8; MSG_SIZE];
+            let used = match session_id {
+                Some(session_id) => {
+                    self.receive_secured_message(session_id, &mut receive_buffer, true)?
+                }
+                None => self.receive_message(&mut receive_buffer, true)?,
+            };
+            receive_buffer.truncate(used);
+            receive_buffers.push(receive_buffer);
+        }
+
+        for (i, index) in indices.iter().enumerate() {
+            let (request_attributes, request_slot_id) = request_params(i);
+            let mut measurement_record = SpdmMeasurementRecordStructure::default();
+            if let Err(status) = self.handle_spdm_measurement_record_response(
+                session_id,
+                request_slot_id,
+                request_attributes,
+                SpdmMeasurementOperation::Unknown(*index),
+                &mut measurement_record,
+                &send_buffers[i],
+                &receive_buffers[i],
+            ) {
+                self.common.reset_message_m(session_id);
+                return Err(status);
+            }
+            measurement_records.push(measurement_record);
+        }
+        Ok(measurement_records)
+    }
+
+    /// Fetch every measurement block with one signed RequestAll and return
+    /// them in record order. Indices are taken as the responder sends them,
+    /// they need not be contiguous.
+    ///
+    /// Requires at least `SpdmConnectionNegotiated`.
+    #[cfg(feature = "alloc")]
+    pub fn fetch_all_measurements(
+        &mut self,
+        session_id: Option<u32>,
+        slot_id: u8,
+    ) -> SpdmResult<Vec<SpdmMeasurementBlockStructure>> {
+        let mut number_of_blocks = 0u8;
+        let mut measurement_record = SpdmMeasurementRecordStructure::default();
+        self.send_receive_spdm_measurement(
+            session_id,
+            slot_id,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
+            &mut number_of_blocks,
+            &mut measurement_record,
+        )?;
+
+        let mut reader = Reader::init(
+            &measurement_record.measurement_record_data
+                [..measurement_record.measurement_record_length.get() as usize],
+        );
+        let mut measurement_blocks = Vec::with_capacity(number_of_blocks as usize);
+        for _ in 0..number_of_blocks {
+            let measurement_block =
+                SpdmMeasurementBlockStructure::spdm_read(&mut self.common, &mut reader)
+                    .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+            measurement_blocks.push(measurement_block);
+        }
+
+        Ok(measurement_blocks)
+    }
+
+    /// Fetch every measurement block like `fetch_all_measurements` and
+    /// bundle them with the negotiated algorithms and the leaf certificate
+    /// of `slot_id` into an `SpdmAttestationReport`.
+    ///
+    /// A signature that fails to verify still yields a report, with
+    /// `signature_verified` cleared and no blocks.
+    ///
+    /// Requires at least `SpdmConnectionNegotiated`.
+    #[cfg(feature = "alloc")]
+    pub fn fetch_attestation_report(
+        &mut self,
+        session_id: Option<u32>,
+        slot_id: u8,
+    ) -> SpdmResult<SpdmAttestationReport> {
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
+        if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        let leaf_cert = {
+            let cert_chain = self.common.peer_info.peer_cert_chain[slot_id as usize]
+                .as_ref()
+                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+            let certs = cert_chain
+                .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+            let (leaf_begin, leaf_end) =
+                crypto::cert_operation::get_cert_from_cert_chain(certs, -1)?;
+            certs[leaf_begin..leaf_end].to_vec()
+        };
+
+        let (measurement_blocks, signature_verified) =
+            match self.fetch_all_measurements(session_id, slot_id) {
+                Ok(measurement_blocks) => (measurement_blocks, true),
+                Err(status) if status == SPDM_STATUS_VERIF_FAIL => (Vec::new(), false),
+                Err(status) => return Err(status),
+            };
+
+        Ok(SpdmAttestationReport {
+            spdm_version: self.common.negotiate_info.spdm_version_sel,
+            base_hash_algo: self.common.negotiate_info.base_hash_sel,
+            base_asym_algo: self.common.negotiate_info.base_asym_sel,
+            measurement_specification: self.common.negotiate_info.measurement_specification_sel,
+            measurement_hash_algo: self.common.negotiate_info.measurement_hash_sel,
+            slot_id,
+            leaf_cert,
+            measurement_blocks,
+            signature_verified,
+        })
+    }
+
+    /// Run `fetch_all_measurements` once for every slot with a certificate
+    /// chain from GET_CERTIFICATE, and DIGESTS if one was received, each
+    /// slot signing its own measurements.
+    ///
+    /// L1/L2 is reset before each slot and after a failed one, so no slot
+    /// signs over the exchanges of another. `error_policy` decides whether
+    /// the first failure is returned or recorded in its slot.
+    ///
+    /// Requires at least `SpdmConnectionNegotiated`.
+    #[cfg(feature = "alloc")]
+    pub fn fetch_all_measurements_per_slot(
+        &mut self,
+        session_id: Option<u32>,
+        error_policy: SpdmSlotErrorPolicy,
+    ) -> SpdmResult<SpdmMeasurementSlotResults> {
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
+        let provisioned_slot_mask = self
+            .common
+            .peer_info
+            .peer_provisioned_slot_mask
+            .unwrap_or(u8::MAX);
+        let mut slot_results = SpdmMeasurementSlotResults::default();
+        for slot_id in 0..SPDM_MAX_SLOT_NUMBER as u8 {
+            if provisioned_slot_mask & (1 << slot_id) == 0
+                || self.common.peer_info.peer_cert_chain[slot_id as usize].is_none()
+            {
+                continue;
+            }
+            slot_results.slot_mask |= 1 << slot_id;
+
+            self.common.reset_message_m(session_id);
+            let result = self.fetch_all_measurements(session_id, slot_id);
+            if let Err(status) = result {
+                error!(
+                    "!!! measurements of slot {} : fail : {:?} !!!\n",
+                    slot_id, status
+                );
+                self.common.reset_message_m(session_id);
+                if error_policy == SpdmSlotErrorPolicy::Abort {
+                    return Err(status);
+                }
+            }
+            slot_results.slots[slot_id as usize] = Some(result);
+        }
+
+        if slot_results.slot_mask == 0 {
+            error!("!!! measurements : no slot with a certificate chain !!!\n");
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        Ok(slot_results)
     }
 
     #[cfg(feature = "hashed-transcript-data")]
@@ -235,49 +857,27 @@ impl<'a> RequesterContext<'a> {
         session_id: Option<u32>,
         signature: &SpdmSignatureStruct,
     ) -> SpdmResult {
-        let message_l1l2_hash = match session_id {
+        let message_l1l2_hash = self.common.calc_message_l1l2_hash(session_id)?;
+
+        // slot_id comes straight from the caller, an out of range one is
+        // not populated either.
+        let cert_chain = match self
+            .common
+            .peer_info
+            .peer_cert_chain
+            .get(slot_id as usize)
+            .and_then(|cert_chain| cert_chain.as_ref())
+        {
+            Some(cert_chain) => cert_chain,
             None => {
-                let ctx = self
-                    .common
-                    .runtime_info
-                    .digest_context_l1l2
-                    .as_ref()
-                    .cloned()
-                    .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
-                crypto::hash::hash_ctx_finalize(ctx).ok_or(SPDM_STATUS_CRYPTO_ERROR)?
-            }
-            Some(session_id) => {
-                let session = if let Some(s) = self.common.get_immutable_session_via_id(session_id)
-                {
-                    s
-                } else {
-                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
-                };
-                let ctx = session
-                    .runtime_info
-                    .digest_context_l1l2
-                    .as_ref()
-                    .cloned()
-                    .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
-                crypto::hash::hash_ctx_finalize(ctx).ok_or(SPDM_STATUS_CRYPTO_ERROR)?
+                error!("peer_cert_chain is not populated!\n");
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
             }
         };
 
-        debug!("message_l1l2_hash - {:02x?}", message_l1l2_hash.as_ref());
-
-        if self.common.peer_info.peer_cert_chain[slot_id as usize].is_none() {
-            error!("peer_cert_chain is not populated!\n");
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        }
-
-        let cert_chain_data = &self.common.peer_info.peer_cert_chain[slot_id as usize]
-            .as_ref()
-            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-            .data[(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-            ..(self.common.peer_info.peer_cert_chain[slot_id as usize]
-                .as_ref()
-                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-                .data_size as usize)];
+        let cert_chain_data = cert_chain
+            .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+            .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
         let mut message_sign = ManagedBuffer12Sign::default();
         if self.common.negotiate_info.spdm_version_sel.get_u8()
@@ -317,59 +917,33 @@ impl<'a> RequesterContext<'a> {
         session_id: Option<u32>,
         signature: &SpdmSignatureStruct,
     ) -> SpdmResult {
-        let mut message_l1l2 = ManagedBufferL1L2::default();
-
-        if self.common.negotiate_info.spdm_version_sel.get_u8()
+        // see `SpdmTranscriptStorage` for the transcript SPDM 1.0/1.1 sign.
+        let message_l1l2_hash = self.common.calc_message_l1l2_hash(session_id)?;
+        let mut message_l1l2 = if self.common.negotiate_info.spdm_version_sel.get_u8()
             >= SpdmVersion::SpdmVersion12.get_u8()
         {
-            let message_a = self.common.runtime_info.message_a.clone();
-            message_l1l2
-                .append_message(message_a.as_ref())
-                .map_or_else(|| Err(SPDM_STATUS_BUFFER_FULL), |_| Ok(()))?;
-        }
+            ManagedBufferL1L2::default()
+        } else {
+            self.common.calc_message_l1l2_data(session_id)?
+        };
 
-        match session_id {
+        let cert_chain = match self
+            .common
+            .peer_info
+            .peer_cert_chain
+            .get(slot_id as usize)
+            .and_then(|cert_chain| cert_chain.as_ref())
+        {
+            Some(cert_chain) => cert_chain,
             None => {
-                message_l1l2
-                    .append_message(self.common.runtime_info.message_m.as_ref())
-                    .ok_or(SPDM_STATUS_BUFFER_FULL)?;
-            }
-            Some(session_id) => {
-                let session = if let Some(s) = self.common.get_immutable_session_via_id(session_id)
-                {
-                    s
-                } else {
-                    return Err(SPDM_STATUS_INVALID_PARAMETER);
-                };
-                message_l1l2
-                    .append_message(session.runtime_info.message_m.as_ref())
-                    .ok_or(SPDM_STATUS_BUFFER_FULL)?;
+                error!("peer_cert_chain is not populated!\n");
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
             }
-        }
-
-        // we dont need create message hash for verify
-        // we just print message hash for debug purpose
-        debug!("message_l1l2 - {:02x?}", message_l1l2.as_ref());
-        let message_l1l2_hash = crypto::hash::hash_all(
-            self.common.negotiate_info.base_hash_sel,
-            message_l1l2.as_ref(),
-        )
-        .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
-        debug!("message_l1l2_hash - {:02x?}", message_l1l2_hash.as_ref());
-
-        if self.common.peer_info.peer_cert_chain[slot_id as usize].is_none() {
-            error!("peer_cert_chain is not populated!\n");
-            return Err(SPDM_STATUS_INVALID_PARAMETER);
-        }
+        };
 
-        let cert_chain_data = &self.common.peer_info.peer_cert_chain[slot_id as usize]
-            .as_ref()
-            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-            .data[(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-            ..(self.common.peer_info.peer_cert_chain[slot_id as usize]
-                .as_ref()
-                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-                .data_size as usize)];
+        let cert_chain_data = cert_chain
+            .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+            .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
         if self.common.negotiate_info.spdm_version_sel.get_u8()
             >= SpdmVersion::SpdmVersion12.get_u8()
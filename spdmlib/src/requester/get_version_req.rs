@@ -9,18 +9,25 @@ use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires no prior state. GET_VERSION restarts the connection, which
+    /// is `SpdmConnectionAfterVersion` on success.
     pub fn send_receive_spdm_version(&mut self) -> SpdmResult {
         self.common
             .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestGetVersion, None);
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let send_used = self.encode_spdm_version(&mut send_buffer)?;
         self.send_message(&send_buffer[..send_used])?;
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let used = self.receive_message(&mut receive_buffer, false)?;
-        self.handle_spdm_version_response(0, &send_buffer[..send_used], &receive_buffer[..used])
+        self.handle_spdm_version_response(0, &send_buffer[..send_used], &receive_buffer[..used])?;
+        self.common
+            .runtime_info
+            .transition_connection_state(SpdmConnectionState::SpdmConnectionAfterVersion)
     }
 
     pub fn encode_spdm_version(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
@@ -35,6 +42,38 @@ impl<'a> RequesterContext<'a> {
         request.spdm_encode(&mut self.common, &mut writer)
     }
 
+    /// The first of `config_info.spdm_version_preference` the responder
+    /// offers, or when no preference is set the highest offered version
+    /// out of `config_info.spdm_version`. `offered` is sorted highest
+    /// first. Unknown(0) if there is none.
+    fn select_spdm_version(&self, offered: &[SpdmVersionStruct]) -> SpdmVersion {
+        let is_offered = |version: &SpdmVersion| {
+            offered
+                .iter()
+                .any(|spdm_version_struct| spdm_version_struct.version == *version)
+        };
+
+        let mut preference = self
+            .common
+            .config_info
+            .spdm_version_preference
+            .iter()
+            .filter(|version| **version != SpdmVersion::Unknown(0))
+            .peekable();
+        if preference.peek().is_some() {
+            return preference
+                .find(|version| is_offered(version))
+                .copied()
+                .unwrap_or(SpdmVersion::Unknown(0));
+        }
+
+        offered
+            .iter()
+            .map(|spdm_version_struct| spdm_version_struct.version)
+            .find(|version| self.common.config_info.spdm_version.contains(version))
+            .unwrap_or(SpdmVersion::Unknown(0))
+    }
+
     pub fn handle_spdm_version_response(
         &mut self,
         session_id: u32,
@@ -56,25 +95,19 @@ impl<'a> RequesterContext<'a> {
                             mut versions,
                         } = version;
 
-                        versions
+                        let entry_count = (version_number_entry_count as usize).min(versions.len());
+                        if spdm_version_list_has_duplicates(&versions[..entry_count]) {
+                            error!("!!! version : list has duplicates !!!\n");
+                            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                        }
+                        self.common.negotiate_info.rsp_spdm_versions = versions.clone();
+                        self.common.negotiate_info.rsp_spdm_version_count = entry_count as u8;
+
+                        versions[..entry_count]
                             .sort_unstable_by(|a, b| b.version.get_u8().cmp(&a.version.get_u8()));
 
-                        self.common.negotiate_info.spdm_version_sel = SpdmVersion::Unknown(0);
-
-                        for spdm_version_struct in
-                            versions.iter().take(version_number_entry_count as usize)
-                        {
-                            if self
-                                .common
-                                .config_info
-                                .spdm_version
-                                .contains(&spdm_version_struct.version)
-                            {
-                                self.common.negotiate_info.spdm_version_sel =
-                                    spdm_version_struct.version;
-                                break;
-                            }
-                        }
+                        self.common.negotiate_info.spdm_version_sel =
+                            self.select_spdm_version(&versions[..entry_count]);
 
                         match self.common.negotiate_info.spdm_version_sel {
                             SpdmVersion::Unknown(_) => {
@@ -7,12 +7,15 @@ use codec::{Codec, Reader};
 use crate::common::session::SpdmSessionState;
 use crate::error::{
     SpdmResult, SPDM_STATUS_BUSY_PEER, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD,
-    SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_NOT_READY_PEER, SPDM_STATUS_SESSION_MSG_ERROR,
+    SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_NOT_READY_PEER, SPDM_STATUS_RESET_REQUIRED_PEER,
+    SPDM_STATUS_SESSION_MSG_ERROR,
 };
 use crate::message::*;
 use crate::requester::RequesterContext;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
     fn spdm_handle_simple_error_response(
         &mut self,
         session_id: Option<u32>,
@@ -34,6 +37,9 @@ impl<'a> RequesterContext<'a> {
                 session.set_session_state(SpdmSessionState::SpdmSessionNotStarted);
             }
             Err(SPDM_STATUS_INVALID_PARAMETER)
+        } else if error_code == SpdmErrorCode::SpdmErrorResetRequired.get_u8() {
+            // the request was accepted, but only takes effect after a reset.
+            Err(SPDM_STATUS_RESET_REQUIRED_PEER)
         } else {
             Err(SPDM_STATUS_ERROR_PEER)
         }
@@ -2,25 +2,65 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use crate::error::{SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD};
+use crate::error::{
+    SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD,
+    SPDM_STATUS_INVALID_PARAMETER,
+};
 use crate::message::*;
+use crate::protocol::*;
 use crate::requester::*;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Heartbeat period in seconds the responder advertised for the session,
+    /// or None when heartbeat is disabled on it. The responder may tear the
+    /// session down after twice the period without a request.
+    pub fn get_heartbeat_period(&self, session_id: u32) -> SpdmResult<Option<u8>> {
+        let session = self
+            .common
+            .get_immutable_session_via_id(session_id)
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        Ok(session.get_heartbeat_period())
+    }
+
+    // the period of KEY_EXCHANGE_RSP/PSK_EXCHANGE_RSP, or zero (disabled)
+    // unless both sides set HBEAT_CAP.
+    pub(crate) fn negotiated_heartbeat_period(&self, heartbeat_period: u8) -> u8 {
+        if self
+            .common
+            .negotiate_info
+            .req_capabilities_sel
+            .contains(SpdmRequestCapabilityFlags::HBEAT_CAP)
+            && self
+                .common
+                .negotiate_info
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::HBEAT_CAP)
+        {
+            heartbeat_period
+        } else {
+            0
+        }
+    }
+
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_heartbeat(&mut self, session_id: u32) -> SpdmResult {
         info!("send spdm heartbeat\n");
 
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
         self.common.reset_buffer_via_request_code(
             SpdmRequestResponseCode::SpdmRequestHeartbeat,
             Some(session_id),
         );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let used = self.encode_spdm_heartbeat(&mut send_buffer)?;
         self.send_secured_message(session_id, &send_buffer[..used], false)?;
 
         // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let used = self.receive_secured_message(session_id, &mut receive_buffer, false)?;
         self.handle_spdm_heartbeat_response(session_id, &receive_buffer[..used])
     }
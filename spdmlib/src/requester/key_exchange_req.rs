@@ -9,6 +9,7 @@ use crate::common::session::SpdmSession;
 use crate::error::SPDM_STATUS_BUFFER_FULL;
 use crate::error::SPDM_STATUS_CRYPTO_ERROR;
 use crate::error::SPDM_STATUS_ERROR_PEER;
+use crate::error::SPDM_STATUS_INVALID_CERT;
 use crate::error::SPDM_STATUS_INVALID_MSG_FIELD;
 use crate::error::SPDM_STATUS_INVALID_PARAMETER;
 #[cfg(feature = "hashed-transcript-data")]
@@ -25,7 +26,10 @@ use crate::error::SpdmResult;
 use crate::message::*;
 use crate::protocol::{SpdmMeasurementSummaryHashType, SpdmSignatureStruct, SpdmVersion};
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_key_exchange(
         &mut self,
         slot_id: u8,
@@ -33,6 +37,8 @@ impl<'a> RequesterContext<'a> {
     ) -> SpdmResult<u32> {
         info!("send spdm key exchange\n");
 
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
         if slot_id >= SPDM_MAX_SLOT_NUMBER as u8 {
             return Err(SPDM_STATUS_INVALID_PARAMETER);
         }
@@ -42,7 +48,7 @@ impl<'a> RequesterContext<'a> {
         self.common
             .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestKeyExchange, None);
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let (key_exchange_context, send_used) = self.encode_spdm_key_exchange(
             req_session_id,
             &mut send_buffer,
@@ -52,7 +58,7 @@ impl<'a> RequesterContext<'a> {
         self.send_message(&send_buffer[..send_used])?;
 
         // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let receive_used = self.receive_message(&mut receive_buffer, false)?;
         self.handle_spdm_key_exhcange_response(
             req_session_id,
@@ -149,16 +155,7 @@ impl<'a> RequesterContext<'a> {
             self.common.runtime_info.need_measurement_summary_hash = false;
         }
 
-        let in_clear_text = self
-            .common
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && self
-                .common
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+        let in_clear_text = self.common.negotiate_info.handshake_in_the_clear();
         info!("in_clear_text {:?}\n", in_clear_text);
 
         let mut reader = Reader::init(receive_buffer);
@@ -256,6 +253,7 @@ impl<'a> RequesterContext<'a> {
                             session.setup(session_id)?;
 
                             session.set_use_psk(false);
+                            session.set_slot_id(slot_id);
                             session.set_mut_auth_requested(key_exchange_rsp.mut_auth_req);
 
                             session.set_crypto_param(
@@ -374,11 +372,13 @@ impl<'a> RequesterContext<'a> {
                                 }
                             }
 
+                            let heartbeat_period =
+                                self.negotiated_heartbeat_period(key_exchange_rsp.heartbeat_period);
                             // append verify_data after TH1
                             let session = self.common.get_session_via_id(session_id).unwrap();
 
                             session.secure_spdm_version_sel = secure_spdm_version_sel;
-                            session.heartbeat_period = key_exchange_rsp.heartbeat_period;
+                            session.heartbeat_period = heartbeat_period;
 
                             session.set_session_state(
                                 crate::common::session::SpdmSessionState::SpdmSessionHandshaking,
@@ -433,14 +433,11 @@ impl<'a> RequesterContext<'a> {
             return Err(SPDM_STATUS_INVALID_PARAMETER);
         }
 
-        let cert_chain_data = &self.common.peer_info.peer_cert_chain[slot_id as usize]
+        let cert_chain_data = self.common.peer_info.peer_cert_chain[slot_id as usize]
             .as_ref()
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-            .data[(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-            ..(self.common.peer_info.peer_cert_chain[slot_id as usize]
-                .as_ref()
-                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-                .data_size as usize)];
+            .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+            .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
         let mut message_sign = ManagedBuffer12Sign::default();
         if self.common.negotiate_info.spdm_version_sel.get_u8()
@@ -492,14 +489,11 @@ impl<'a> RequesterContext<'a> {
             return Err(SPDM_STATUS_INVALID_PARAMETER);
         }
 
-        let cert_chain_data = &self.common.peer_info.peer_cert_chain[slot_id as usize]
+        let cert_chain_data = self.common.peer_info.peer_cert_chain[slot_id as usize]
             .as_ref()
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-            .data[(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-            ..(self.common.peer_info.peer_cert_chain[slot_id as usize]
-                .as_ref()
-                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-                .data_size as usize)];
+            .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+            .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
         let mut message = self.common.calc_req_transcript_data(
             false,
@@ -9,7 +9,9 @@ use crate::error::{
 use crate::message::*;
 use crate::requester::*;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
     fn send_receive_spdm_key_update_op(
         &mut self,
         session_id: u32,
@@ -23,7 +25,7 @@ impl<'a> RequesterContext<'a> {
             Some(session_id),
         );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let used = self.encode_spdm_key_update_op(key_update_operation, tag, &mut send_buffer)?;
         self.send_secured_message(session_id, &send_buffer[..used], false)?;
 
@@ -38,7 +40,7 @@ impl<'a> RequesterContext<'a> {
             || key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateAllKeys;
         let update_responder = key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateAllKeys;
         session.create_data_secret_update(spdm_version_sel, update_requester, update_responder)?;
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let used = self.receive_secured_message(session_id, &mut receive_buffer, false)?;
 
         self.handle_spdm_key_update_op_response(
@@ -126,11 +128,14 @@ impl<'a> RequesterContext<'a> {
         }
     }
 
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_key_update(
         &mut self,
         session_id: u32,
         key_update_operation: SpdmKeyUpdateOperation,
     ) -> SpdmResult {
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
         if key_update_operation != SpdmKeyUpdateOperation::SpdmUpdateAllKeys
             && key_update_operation != SpdmKeyUpdateOperation::SpdmUpdateSingleKey
         {
@@ -0,0 +1,250 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+extern crate alloc;
+use alloc::boxed::Box;
+use core::any::Any;
+
+use conquer_once::spin::OnceCell;
+
+use crate::error::{SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD};
+use crate::protocol::*;
+use codec::{Codec, Reader};
+
+// DMTFSpecMeasurementValueType is 7 bits, the top bit selects the representation.
+const SPDM_DMTF_MEASUREMENT_TYPE_COUNT: usize = 0x80;
+
+/// Platform defined form of a measurement value, to be downcast by the caller.
+pub type SpdmDecodedMeasurementValue = Box<dyn Any + Send>;
+
+/// Turns the value of a DMTF measurement block of one value type into a
+/// platform defined structure, e.g. for vendor value types.
+///
+/// `decode_measurement_value_cb` gets the representation and the value
+/// bytes. Returning None leaves the block raw.
+#[derive(Clone)]
+pub struct SpdmMeasurementDecoder {
+    pub decode_measurement_value_cb: fn(
+        representation: SpdmDmtfMeasurementRepresentation,
+        value: &[u8],
+    ) -> Option<SpdmDecodedMeasurementValue>,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const UNREGISTERED: OnceCell<SpdmMeasurementDecoder> = OnceCell::uninit();
+static SPDM_MEASUREMENT_DECODERS: [OnceCell<SpdmMeasurementDecoder>;
+    SPDM_DMTF_MEASUREMENT_TYPE_COUNT] = [UNREGISTERED; SPDM_DMTF_MEASUREMENT_TYPE_COUNT];
+
+/// Register the decoder of measurement value type `value_type` (0 - 0x7F).
+/// Returns false for an out of range type or one that already has a decoder.
+pub fn register(value_type: u8, decoder: SpdmMeasurementDecoder) -> bool {
+    match SPDM_MEASUREMENT_DECODERS.get(value_type as usize) {
+        Some(cell) => cell.try_init_once(|| decoder).is_ok(),
+        None => false,
+    }
+}
+
+pub fn decode_measurement_value(
+    measurement: &SpdmDmtfMeasurementStructure,
+) -> Option<SpdmDecodedMeasurementValue> {
+    let decoder = SPDM_MEASUREMENT_DECODERS
+        .get(measurement.r#type.get_u8() as usize)?
+        .try_get()
+        .ok()?;
+    let value = measurement.value.get(..measurement.value_size as usize)?;
+    (decoder.decode_measurement_value_cb)(measurement.representation, value)
+}
+
+/// One block of a measurement record.
+pub struct SpdmDecodedMeasurementBlock {
+    pub block: SpdmMeasurementBlockStructure,
+    // Some if a decoder is registered for the value type and accepted the
+    // value, the raw bytes stay in block.measurement either way.
+    pub decoded_value: Option<SpdmDecodedMeasurementValue>,
+}
+
+/// Walks the blocks of a MEASUREMENTS record in order. A block that does not
+/// parse, or a record shorter or longer than its `number_of_blocks`, yields
+/// one error and ends the iteration.
+pub struct SpdmMeasurementBlockIter<'a> {
+    reader: Reader<'a>,
+    remaining_blocks: u8,
+    failed: bool,
+}
+
+impl<'a> SpdmMeasurementBlockIter<'a> {
+    pub fn new(record: &'a SpdmMeasurementRecordStructure) -> Self {
+        let record_length = record.measurement_record_length.get() as usize;
+        match record.measurement_record_data.get(..record_length) {
+            Some(record_data) => SpdmMeasurementBlockIter {
+                reader: Reader::init(record_data),
+                remaining_blocks: record.number_of_blocks,
+                failed: false,
+            },
+            None => SpdmMeasurementBlockIter {
+                reader: Reader::init(&[]),
+                remaining_blocks: 0,
+                failed: true,
+            },
+        }
+    }
+
+    fn next_block(&mut self) -> SpdmResult<SpdmDecodedMeasurementBlock> {
+        let block = SpdmMeasurementBlockStructure::read(&mut self.reader)
+            .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+        self.remaining_blocks -= 1;
+        if self.remaining_blocks == 0 && self.reader.any_left() {
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+        let decoded_value = decode_measurement_value(&block.measurement);
+        Ok(SpdmDecodedMeasurementBlock {
+            block,
+            decoded_value,
+        })
+    }
+}
+
+impl Iterator for SpdmMeasurementBlockIter<'_> {
+    type Item = SpdmResult<SpdmDecodedMeasurementBlock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            self.failed = false;
+            self.remaining_blocks = 0;
+            return Some(Err(SPDM_STATUS_INVALID_MSG_FIELD));
+        }
+        if self.remaining_blocks == 0 {
+            return None;
+        }
+        let result = self.next_block();
+        if result.is_err() {
+            self.remaining_blocks = 0;
+        }
+        Some(result)
+    }
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::config;
+    use alloc::vec::Vec;
+    use codec::{u24, Writer};
+
+    // a made up vendor value: a 16-bit version and a 32-bit build number.
+    #[derive(Debug, PartialEq, Eq)]
+    struct VendorVersion {
+        version: u16,
+        build: u32,
+    }
+
+    const VENDOR_VERSION_TYPE: u8 = 0x70;
+
+    fn decode_vendor_version(
+        representation: SpdmDmtfMeasurementRepresentation,
+        value: &[u8],
+    ) -> Option<SpdmDecodedMeasurementValue> {
+        if representation != SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit {
+            return None;
+        }
+        let mut reader = Reader::init(value);
+        let version = u16::read(&mut reader)?;
+        let build = u32::read(&mut reader)?;
+        if reader.any_left() {
+            return None;
+        }
+        Some(Box::new(VendorVersion { version, build }))
+    }
+
+    fn measurement_block(index: u8, value_type: u8, value: &[u8]) -> SpdmMeasurementBlockStructure {
+        let mut measurement_value = [0u8; config::MAX_SPDM_MEASUREMENT_VALUE_LEN];
+        measurement_value[..value.len()].copy_from_slice(value);
+        SpdmMeasurementBlockStructure {
+            index,
+            measurement_specification: SpdmMeasurementSpecification::DMTF,
+            measurement_size: value.len() as u16 + 3,
+            measurement: SpdmDmtfMeasurementStructure {
+                r#type: SpdmDmtfMeasurementType::Unknown(value_type),
+                representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit,
+                value_size: value.len() as u16,
+                value: measurement_value,
+            },
+        }
+    }
+
+    fn measurement_record(
+        blocks: &[SpdmMeasurementBlockStructure],
+    ) -> SpdmMeasurementRecordStructure {
+        let mut record = SpdmMeasurementRecordStructure::default();
+        let mut writer = Writer::init(&mut record.measurement_record_data);
+        for block in blocks {
+            block.encode(&mut writer).unwrap();
+        }
+        record.measurement_record_length = u24::new(writer.used() as u32);
+        record.number_of_blocks = blocks.len() as u8;
+        record
+    }
+
+    #[test]
+    fn test_case0_measurement_block_iter_vendor_decoder() {
+        let decoder = SpdmMeasurementDecoder {
+            decode_measurement_value_cb: decode_vendor_version,
+        };
+        assert!(register(VENDOR_VERSION_TYPE, decoder.clone()));
+        assert!(!register(VENDOR_VERSION_TYPE, decoder.clone()));
+        assert!(!register(0x80, decoder));
+
+        let record = measurement_record(&[
+            measurement_block(
+                1,
+                VENDOR_VERSION_TYPE,
+                &[0x02, 0x01, 0x78, 0x56, 0x34, 0x12],
+            ),
+            // no decoder for this vendor type.
+            measurement_block(2, 0x71, &[0xAA, 0xBB]),
+            // the decoder refuses a malformed value.
+            measurement_block(3, VENDOR_VERSION_TYPE, &[0x02]),
+        ]);
+        let blocks: Vec<SpdmDecodedMeasurementBlock> = SpdmMeasurementBlockIter::new(&record)
+            .collect::<SpdmResult<_>>()
+            .unwrap();
+        assert_eq!(blocks.len(), 3);
+
+        assert_eq!(blocks[0].block.index, 1);
+        let vendor_version = blocks[0]
+            .decoded_value
+            .as_ref()
+            .unwrap()
+            .downcast_ref::<VendorVersion>()
+            .unwrap();
+        assert_eq!(
+            vendor_version,
+            &VendorVersion {
+                version: 0x0102,
+                build: 0x1234_5678
+            }
+        );
+
+        assert!(blocks[1].decoded_value.is_none());
+        assert_eq!(blocks[1].block.measurement.value[..2], [0xAA, 0xBB]);
+        assert!(blocks[2].decoded_value.is_none());
+    }
+
+    #[test]
+    fn test_case1_measurement_block_iter_malformed_record() {
+        let mut record = measurement_record(&[measurement_block(1, 0x71, &[0xAA])]);
+        record.number_of_blocks = 2;
+        let mut iter = SpdmMeasurementBlockIter::new(&record);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        // trailing bytes after the last block.
+        let mut record = measurement_record(&[measurement_block(1, 0x71, &[0xAA])]);
+        record.measurement_record_length = u24::new(record.measurement_record_length.get() + 1);
+        let mut iter = SpdmMeasurementBlockIter::new(&record);
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+use crate::protocol::*;
+use codec::{Codec, Reader};
+
+/// Indices that differ between two sets of measurement blocks, each list in
+/// ascending order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpdmMeasurementDiff {
+    // present in both sets with a different value.
+    pub changed: Vec<u8>,
+    // only present in the current set.
+    pub added: Vec<u8>,
+    // only present in the previous set.
+    pub removed: Vec<u8>,
+}
+
+impl SpdmMeasurementDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+fn same_measurement_block(
+    previous: &SpdmMeasurementBlockStructure,
+    current: &SpdmMeasurementBlockStructure,
+) -> bool {
+    let value_size = current.measurement.value_size as usize;
+    previous.measurement_specification == current.measurement_specification
+        && previous.measurement.r#type == current.measurement.r#type
+        && previous.measurement.representation == current.measurement.representation
+        && previous.measurement.value_size == current.measurement.value_size
+        && previous
+            .measurement
+            .value
+            .iter()
+            .take(value_size)
+            .eq(current.measurement.value.iter().take(value_size))
+}
+
+/// Compare the blocks captured by an earlier attestation with freshly
+/// fetched ones, e.g. after a MEASUREMENTS response reports
+/// ContentChanged, so only the listed indices need to be re-appraised.
+pub fn diff_measurement_blocks(
+    previous: &[SpdmMeasurementBlockStructure],
+    current: &[SpdmMeasurementBlockStructure],
+) -> SpdmMeasurementDiff {
+    let mut diff = SpdmMeasurementDiff::default();
+    for current_block in current.iter() {
+        match previous.iter().find(|b| b.index == current_block.index) {
+            Some(previous_block) => {
+                if !same_measurement_block(previous_block, current_block) {
+                    diff.changed.push(current_block.index);
+                }
+            }
+            None => diff.added.push(current_block.index),
+        }
+    }
+    for previous_block in previous.iter() {
+        if !current.iter().any(|b| b.index == previous_block.index) {
+            diff.removed.push(previous_block.index);
+        }
+    }
+
+    diff.changed.sort_unstable();
+    diff.changed.dedup();
+    diff.added.sort_unstable();
+    diff.added.dedup();
+    diff.removed.sort_unstable();
+    diff.removed.dedup();
+    diff
+}
+
+fn measurement_record_blocks(
+    record: &SpdmMeasurementRecordStructure,
+) -> Option<Vec<SpdmMeasurementBlockStructure>> {
+    let record_length = record.measurement_record_length.get() as usize;
+    if record_length > record.measurement_record_data.len() {
+        return None;
+    }
+    let mut reader = Reader::init(&record.measurement_record_data[..record_length]);
+    let mut blocks = Vec::with_capacity(record.number_of_blocks as usize);
+    for _ in 0..record.number_of_blocks {
+        blocks.push(SpdmMeasurementBlockStructure::read(&mut reader)?);
+    }
+    if reader.any_left() {
+        return None;
+    }
+    Some(blocks)
+}
+
+/// `diff_measurement_blocks` on two MEASUREMENTS records. Returns None if
+/// either record does not parse into its `number_of_blocks` blocks.
+pub fn diff_measurement_records(
+    previous: &SpdmMeasurementRecordStructure,
+    current: &SpdmMeasurementRecordStructure,
+) -> Option<SpdmMeasurementDiff> {
+    let previous_blocks = measurement_record_blocks(previous)?;
+    let current_blocks = measurement_record_blocks(current)?;
+    Some(diff_measurement_blocks(&previous_blocks, &current_blocks))
+}
+
+/// Deterministic bytes of the measurement set in `record`, for hashing or
+/// comparing it outside of SPDM, e.g. in reproducible attestation reports.
+///
+/// This is not the SPDM wire form. The blocks are sorted by index and each
+/// one is written as index (1 byte), measurement specification (1 byte),
+/// DMTFSpecMeasurementValueType (1 byte), value size (2 bytes, little
+/// endian) and the value, without the record header or MeasurementSize.
+/// Records carrying the same blocks in another order give the same bytes.
+///
+/// Returns None if the record does not parse into its `number_of_blocks`
+/// blocks or repeats an index.
+pub fn canonicalize_measurement_record(record: &SpdmMeasurementRecordStructure) -> Option<Vec<u8>> {
+    let mut blocks = measurement_record_blocks(record)?;
+    blocks.sort_by_key(|block| block.index);
+    if blocks.windows(2).any(|pair| pair[0].index == pair[1].index) {
+        return None;
+    }
+
+    let mut canonical = Vec::new();
+    for block in blocks.iter() {
+        let value_size = block.measurement.value_size as usize;
+        canonical.push(block.index);
+        canonical.push(block.measurement_specification.bits());
+        canonical
+            .push(block.measurement.r#type.get_u8() + block.measurement.representation.get_u8());
+        canonical.extend_from_slice(&block.measurement.value_size.to_le_bytes());
+        canonical.extend(block.measurement.value.iter().take(value_size));
+    }
+    Some(canonical)
+}
+
+#[cfg(all(test,))]
+mod tests {
+    use super::*;
+    use crate::config;
+    use codec::{u24, Writer};
+
+    fn measurement_block(index: u8, fill: u8) -> SpdmMeasurementBlockStructure {
+        let mut value = [0u8; config::MAX_SPDM_MEASUREMENT_VALUE_LEN];
+        value[..SHA384_DIGEST_SIZE].copy_from_slice(&[fill; SHA384_DIGEST_SIZE]);
+        SpdmMeasurementBlockStructure {
+            index,
+            measurement_specification: SpdmMeasurementSpecification::DMTF,
+            measurement_size: SHA384_DIGEST_SIZE as u16 + 3,
+            measurement: SpdmDmtfMeasurementStructure {
+                r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware,
+                representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest,
+                value_size: SHA384_DIGEST_SIZE as u16,
+                value,
+            },
+        }
+    }
+
+    fn measurement_record(
+        blocks: &[SpdmMeasurementBlockStructure],
+    ) -> SpdmMeasurementRecordStructure {
+        let mut record = SpdmMeasurementRecordStructure::default();
+        let mut writer = Writer::init(&mut record.measurement_record_data);
+        for block in blocks {
+            block.encode(&mut writer).unwrap();
+        }
+        record.measurement_record_length = u24::new(writer.used() as u32);
+        record.number_of_blocks = blocks.len() as u8;
+        record
+    }
+
+    #[test]
+    fn test_case0_diff_measurement_blocks() {
+        let previous = [
+            measurement_block(1, 0x11),
+            measurement_block(2, 0x22),
+            measurement_block(3, 0x33),
+        ];
+        let current = [
+            measurement_block(1, 0x11),
+            measurement_block(2, 0x2F),
+            measurement_block(3, 0x33),
+        ];
+
+        let diff = diff_measurement_blocks(&previous, &current);
+        assert_eq!(diff.changed, [2]);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+
+        let diff = diff_measurement_records(
+            &measurement_record(&previous),
+            &measurement_record(&current),
+        )
+        .unwrap();
+        assert_eq!(diff.changed, [2]);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+
+        assert!(diff_measurement_blocks(&previous, &previous).is_empty());
+    }
+
+    #[test]
+    fn test_case1_diff_measurement_blocks_added_removed() {
+        let previous = [measurement_block(1, 0x11), measurement_block(3, 0x33)];
+        let current = [measurement_block(2, 0x22), measurement_block(1, 0x11)];
+
+        let diff = diff_measurement_blocks(&previous, &current);
+        assert!(diff.changed.is_empty());
+        assert_eq!(diff.added, [2]);
+        assert_eq!(diff.removed, [3]);
+
+        // a record claiming more blocks than it carries does not parse.
+        let mut record = measurement_record(&current);
+        record.number_of_blocks += 1;
+        assert!(diff_measurement_records(&measurement_record(&previous), &record).is_none());
+    }
+
+    #[test]
+    fn test_case2_canonicalize_measurement_record() {
+        let blocks = [
+            measurement_block(1, 0x11),
+            measurement_block(2, 0x22),
+            measurement_block(3, 0x33),
+        ];
+        let reordered = [
+            measurement_block(3, 0x33),
+            measurement_block(1, 0x11),
+            measurement_block(2, 0x22),
+        ];
+
+        let record = measurement_record(&blocks);
+        let reordered_record = measurement_record(&reordered);
+        assert_ne!(
+            record.measurement_record_data[..record.measurement_record_length.get() as usize],
+            reordered_record.measurement_record_data
+                [..reordered_record.measurement_record_length.get() as usize]
+        );
+
+        let canonical = canonicalize_measurement_record(&record).unwrap();
+        assert_eq!(
+            canonicalize_measurement_record(&reordered_record),
+            Some(canonical.clone())
+        );
+        assert_eq!(canonical.len(), 3 * (5 + SHA384_DIGEST_SIZE));
+        assert_eq!(&canonical[..5], &[1, 1, 1, SHA384_DIGEST_SIZE as u8, 0]);
+
+        let changed = measurement_record(&[
+            measurement_block(1, 0x11),
+            measurement_block(2, 0x2F),
+            measurement_block(3, 0x33),
+        ]);
+        assert_ne!(canonicalize_measurement_record(&changed), Some(canonical));
+
+        let repeated =
+            measurement_record(&[measurement_block(1, 0x11), measurement_block(1, 0x11)]);
+        assert!(canonicalize_measurement_record(&repeated).is_none());
+    }
+}
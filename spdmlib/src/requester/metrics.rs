@@ -0,0 +1,69 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+/// High level requester methods timed into `RequesterContext::metrics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpdmRequesterOperation {
+    InitConnection,
+    Digests,
+    Certificate,
+    Challenge,
+    // GET_MEASUREMENTS without, and with SIGNATURE_REQUESTED.
+    Measurement,
+    SignedMeasurement,
+    StartSession,
+    EndSession,
+}
+
+#[cfg(feature = "std")]
+const SPDM_REQUESTER_OPERATION_COUNT: usize = 8;
+
+/// Timing of every call of one operation, failed ones included.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpdmOperationTiming {
+    pub count: u32,
+    pub failed: u32,
+    pub last: Duration,
+    pub max: Duration,
+    pub total: Duration,
+}
+
+/// Wall clock time spent in the high level requester methods, only kept
+/// with the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct SpdmRequesterMetrics {
+    timings: [SpdmOperationTiming; SPDM_REQUESTER_OPERATION_COUNT],
+}
+
+#[cfg(feature = "std")]
+impl SpdmRequesterMetrics {
+    pub fn get(&self, operation: SpdmRequesterOperation) -> &SpdmOperationTiming {
+        &self.timings[operation as usize]
+    }
+
+    pub fn reset(&mut self) {
+        *self = SpdmRequesterMetrics::default();
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        operation: SpdmRequesterOperation,
+        elapsed: Duration,
+        succeeded: bool,
+    ) {
+        let timing = &mut self.timings[operation as usize];
+        timing.count = timing.count.saturating_add(1);
+        if !succeeded {
+            timing.failed = timing.failed.saturating_add(1);
+        }
+        timing.last = elapsed;
+        timing.max = timing.max.max(elapsed);
+        timing.total = timing.total.saturating_add(elapsed);
+    }
+}
@@ -4,7 +4,13 @@
 
 mod context;
 
+#[cfg(feature = "alloc")]
+mod attest;
+#[cfg(feature = "alloc")]
+mod attestation_report;
 mod challenge_req;
+mod chunk_req;
+mod detached_measurements;
 #[cfg(feature = "mut-auth")]
 mod encap_certificate;
 #[cfg(feature = "mut-auth")]
@@ -12,18 +18,27 @@ mod encap_digest;
 #[cfg(feature = "mut-auth")]
 mod encap_error;
 #[cfg(feature = "mut-auth")]
+mod encap_key_update;
+#[cfg(feature = "mut-auth")]
 mod encap_req;
 mod end_session_req;
+mod event_req;
 mod finish_req;
 mod get_capabilities_req;
 mod get_certificate_req;
 mod get_digests_req;
+#[cfg(feature = "alloc")]
+mod get_measurement_extension_log_req;
 pub mod get_measurements_req;
 mod get_version_req;
 mod handle_error_response_req;
 mod heartbeat_req;
 mod key_exchange_req;
 pub mod key_update_req;
+pub mod measurement_decoder;
+#[cfg(feature = "alloc")]
+mod measurement_diff;
+mod metrics;
 #[cfg(feature = "mut-auth")]
 mod mutual_authenticate;
 mod negotiate_algorithms_req;
@@ -31,7 +46,21 @@ mod psk_exchange_req;
 mod psk_finish_req;
 mod vendor_req;
 
+#[cfg(feature = "alloc")]
+pub use attest::*;
+#[cfg(feature = "alloc")]
+pub use attestation_report::*;
 pub use context::RequesterContext;
+pub use detached_measurements::*;
+pub use get_digests_req::SpdmDigestSlotResults;
+#[cfg(feature = "alloc")]
+pub use get_measurement_extension_log_req::*;
+#[cfg(feature = "alloc")]
+pub use get_measurements_req::SpdmMeasurementSlotResults;
+pub use get_measurements_req::SpdmSlotErrorPolicy;
+#[cfg(feature = "alloc")]
+pub use measurement_diff::*;
+pub use metrics::*;
 
 use crate::common::*;
 use crate::config;
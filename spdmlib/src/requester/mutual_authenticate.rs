@@ -3,14 +3,24 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use crate::{
-    error::{SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_STATE_LOCAL},
+    common::SpdmConnectionState,
+    error::{
+        SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER,
+        SPDM_STATUS_INVALID_STATE_LOCAL,
+    },
     message::SpdmKeyExchangeMutAuthAttributes,
+    protocol::SPDM_MAX_SLOT_NUMBER,
 };
 
 use super::RequesterContext;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn session_based_mutual_authenticate(&mut self, session_id: u32) -> SpdmResult<()> {
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
         self.common.construct_my_cert_chain()?;
 
         let spdm_session = self
@@ -28,4 +38,38 @@ impl<'a> RequesterContext<'a> {
             _ => Err(SPDM_STATUS_INVALID_MSG_FIELD),
         }
     }
+
+    /// Present the certificate chain provisioned in `slot_id` when the
+    /// responder asks for mutual authentication without the encapsulated
+    /// flow, where FINISH names the requester's slot. With the encapsulated
+    /// flow the responder picks the slot itself. None goes back to the slot
+    /// KEY_EXCHANGE_RSP named.
+    pub fn select_mut_auth_slot(&mut self, slot_id: Option<u8>) -> SpdmResult {
+        if let Some(slot_id) = slot_id {
+            if slot_id as usize >= SPDM_MAX_SLOT_NUMBER
+                || self.common.provision_info.my_cert_chain_data[slot_id as usize].is_none()
+            {
+                error!(
+                    "!!! mut_auth : no certificate chain in slot {} !!!\n",
+                    slot_id
+                );
+                return Err(SPDM_STATUS_INVALID_PARAMETER);
+            }
+        }
+        self.mut_auth_slot_id = slot_id;
+        Ok(())
+    }
+
+    pub(crate) fn finish_req_slot_id(&self, session_id: u32) -> SpdmResult<u8> {
+        let spdm_session = self
+            .common
+            .get_immutable_session_via_id(session_id)
+            .ok_or(SPDM_STATUS_INVALID_STATE_LOCAL)?;
+        let local_used_slot_id = self.common.runtime_info.get_local_used_cert_chain_slot_id();
+        if spdm_session.get_mut_auth_requested() == SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ {
+            Ok(self.mut_auth_slot_id.unwrap_or(local_used_slot_id))
+        } else {
+            Ok(local_used_slot_id)
+        }
+    }
 }
@@ -3,27 +3,36 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use crate::error::{
-    SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_NEGOTIATION_FAIL,
+    SpdmResult, SPDM_STATUS_CONTENT_CHANGE_UNSUPPORTED_PEER, SPDM_STATUS_ERROR_PEER,
+    SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_NEGOTIATION_FAIL,
 };
 
 use crate::message::*;
 use crate::protocol::*;
 use crate::requester::*;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires `SpdmConnectionAfterCapabilities`.
     pub fn send_receive_spdm_algorithm(&mut self) -> SpdmResult {
+        self.check_connection_transition(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
         self.common.reset_buffer_via_request_code(
             SpdmRequestResponseCode::SpdmRequestNegotiateAlgorithms,
             None,
         );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let send_used = self.encode_spdm_algorithm(&mut send_buffer)?;
         self.send_message(&send_buffer[..send_used])?;
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let used = self.receive_message(&mut receive_buffer, false)?;
-        self.handle_spdm_algorithm_response(0, &send_buffer[..send_used], &receive_buffer[..used])
+        self.handle_spdm_algorithm_response(0, &send_buffer[..send_used], &receive_buffer[..used])?;
+        self.common
+            .runtime_info
+            .transition_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)
     }
 
     pub fn encode_spdm_algorithm(&mut self, buf: &mut [u8]) -> SpdmResult<usize> {
@@ -41,6 +50,12 @@ impl<'a> RequesterContext<'a> {
                     other_params_support,
                     base_asym_algo: self.common.config_info.base_asym_algo,
                     base_hash_algo: self.common.config_info.base_hash_algo,
+                    ext_asym_count: self.common.config_info.ext_asym_algo.is_some() as u8,
+                    ext_asym: [self.common.config_info.ext_asym_algo.unwrap_or_default();
+                        SPDM_MAX_EXT_ALG_COUNT],
+                    ext_hash_count: self.common.config_info.ext_hash_algo.is_some() as u8,
+                    ext_hash: [self.common.config_info.ext_hash_algo.unwrap_or_default();
+                        SPDM_MAX_EXT_ALG_COUNT],
                     alg_struct_count: 4,
                     alg_struct: [
                         SpdmAlgStruct {
@@ -98,14 +113,18 @@ impl<'a> RequesterContext<'a> {
 
                             self.common.negotiate_info.measurement_hash_sel =
                                 algorithms.measurement_hash_algo;
+                            // no crypto backend can use an extended algorithm,
+                            // so a base one must be selected.
                             if algorithms.base_hash_sel.bits() == 0 {
                                 return Err(SPDM_STATUS_NEGOTIATION_FAIL);
                             }
                             self.common.negotiate_info.base_hash_sel = algorithms.base_hash_sel;
+                            self.common.negotiate_info.ext_hash_sel = algorithms.ext_hash_sel;
                             if algorithms.base_asym_sel.bits() == 0 {
                                 return Err(SPDM_STATUS_NEGOTIATION_FAIL);
                             }
                             self.common.negotiate_info.base_asym_sel = algorithms.base_asym_sel;
+                            self.common.negotiate_info.ext_asym_sel = algorithms.ext_asym_sel;
                             for alg in algorithms
                                 .alg_struct
                                 .iter()
@@ -128,6 +147,15 @@ impl<'a> RequesterContext<'a> {
                                 }
                             }
 
+                            // MEASUREMENTS reports content changes from SPDM 1.2 on.
+                            if self.common.config_info.require_content_change_support
+                                && self.common.negotiate_info.spdm_version_sel.get_u8()
+                                    < SpdmVersion::SpdmVersion12.get_u8()
+                            {
+                                error!("!!! algorithms : no content change support !!!\n");
+                                return Err(SPDM_STATUS_CONTENT_CHANGE_UNSUPPORTED_PEER);
+                            }
+
                             self.common.append_message_a(send_buffer)?;
                             self.common.append_message_a(&receive_buffer[..used])?;
 
@@ -17,7 +17,10 @@ use crate::protocol::*;
 use crate::requester::*;
 extern crate alloc;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_psk_exchange(
         &mut self,
         measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
@@ -25,6 +28,8 @@ impl<'a> RequesterContext<'a> {
     ) -> SpdmResult<u32> {
         info!("send spdm psk exchange\n");
 
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
         let psk_hint = if let Some(hint) = psk_hint {
             hint.clone()
         } else {
@@ -34,7 +39,7 @@ impl<'a> RequesterContext<'a> {
         self.common
             .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestPskExchange, None);
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let half_session_id = self.common.get_next_half_session_id(true)?;
         let send_used = self.encode_spdm_psk_exchange(
             half_session_id,
@@ -46,7 +51,7 @@ impl<'a> RequesterContext<'a> {
         self.send_message(&send_buffer[..send_used])?;
 
         // Receive
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let receive_used = self.receive_message(&mut receive_buffer, false)?;
         self.handle_spdm_psk_exchange_response(
             half_session_id,
@@ -308,9 +313,11 @@ impl<'a> RequesterContext<'a> {
                                 );
                             }
 
+                            let heartbeat_period =
+                                self.negotiated_heartbeat_period(psk_exchange_rsp.heartbeat_period);
                             let session = self.common.get_session_via_id(session_id).unwrap();
                             session.secure_spdm_version_sel = secure_spdm_version_sel;
-                            session.heartbeat_period = psk_exchange_rsp.heartbeat_period;
+                            session.heartbeat_period = heartbeat_period;
 
                             Ok(session_id)
                         } else {
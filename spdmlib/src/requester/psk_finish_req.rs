@@ -12,10 +12,15 @@ use crate::requester::*;
 extern crate alloc;
 use alloc::boxed::Box;
 
-impl<'a> RequesterContext<'a> {
+impl<'a, const MSG_SIZE: usize, const TRANSPORT_SIZE: usize>
+    RequesterContext<'a, MSG_SIZE, TRANSPORT_SIZE>
+{
+    /// Requires at least `SpdmConnectionNegotiated`.
     pub fn send_receive_spdm_psk_finish(&mut self, session_id: u32) -> SpdmResult {
         info!("send spdm psk_finish\n");
 
+        self.check_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
+
         if self.common.get_session_via_id(session_id).is_none() {
             return Err(SPDM_STATUS_INVALID_PARAMETER);
         }
@@ -25,7 +30,7 @@ impl<'a> RequesterContext<'a> {
             Some(session_id),
         );
 
-        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut send_buffer = [0u8; MSG_SIZE];
         let res = self.encode_spdm_psk_finish(session_id, &mut send_buffer);
         if res.is_err() {
             let _ = self
@@ -46,7 +51,7 @@ impl<'a> RequesterContext<'a> {
             return res;
         }
 
-        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut receive_buffer = [0u8; MSG_SIZE];
         let res = self.receive_secured_message(session_id, &mut receive_buffer, false);
         if res.is_err() {
             let _ = self
@@ -96,6 +96,9 @@ impl<'a> ResponderContext<'a> {
             .negotiate_info
             .base_asym_sel
             .prioritize(self.common.config_info.base_asym_algo);
+        // extended asym/hash algorithms from the request are never selected.
+        self.common.negotiate_info.ext_asym_sel = None;
+        self.common.negotiate_info.ext_hash_sel = None;
         self.common
             .negotiate_info
             .dhe_sel
@@ -140,6 +143,8 @@ impl<'a> ResponderContext<'a> {
                 measurement_hash_algo: self.common.negotiate_info.measurement_hash_sel,
                 base_asym_sel: self.common.negotiate_info.base_asym_sel,
                 base_hash_sel: self.common.negotiate_info.base_hash_sel,
+                ext_asym_sel: self.common.negotiate_info.ext_asym_sel,
+                ext_hash_sel: self.common.negotiate_info.ext_hash_sel,
                 alg_struct_count: 4,
                 alg_struct: [
                     SpdmAlgStruct {
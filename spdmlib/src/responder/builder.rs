@@ -0,0 +1,178 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use super::ResponderContext;
+use crate::common::{
+    SpdmConfigInfo, SpdmDeviceIo, SpdmOpaqueSupport, SpdmProvisionInfo, SpdmTransportEncap,
+};
+use crate::config;
+use crate::error::{SpdmResult, SPDM_STATUS_INVALID_PARAMETER};
+use crate::protocol::*;
+
+/// Puts together the `SpdmConfigInfo` of a responder from the versions,
+/// capabilities and algorithms it supports, for test responders and
+/// other small setups.
+///
+/// The context built answers GET_VERSION, GET_CAPABILITIES and
+/// NEGOTIATE_ALGORITHMS from this configuration like any other
+/// responder, nothing in `negotiate_info` needs to be set by hand.
+/// Unless configured otherwise it supports SPDM 1.0 to 1.2 and
+/// DataTransferSize and MaxSPDMmsgSize of `config::MAX_SPDM_MSG_SIZE`.
+pub struct ResponderBuilder {
+    config_info: SpdmConfigInfo,
+    provision_info: SpdmProvisionInfo,
+    version_count: usize,
+}
+
+impl Default for ResponderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResponderBuilder {
+    pub fn new() -> Self {
+        ResponderBuilder {
+            config_info: SpdmConfigInfo {
+                spdm_version: [
+                    SpdmVersion::SpdmVersion10,
+                    SpdmVersion::SpdmVersion11,
+                    SpdmVersion::SpdmVersion12,
+                ],
+                data_transfer_size: config::MAX_SPDM_MSG_SIZE as u32,
+                max_spdm_msg_size: config::MAX_SPDM_MSG_SIZE as u32,
+                ..Default::default()
+            },
+            provision_info: SpdmProvisionInfo::default(),
+            version_count: MAX_SPDM_VERSION_COUNT,
+        }
+    }
+
+    /// At most MAX_SPDM_VERSION_COUNT versions, `build` fails otherwise.
+    pub fn spdm_versions(mut self, versions: &[SpdmVersion]) -> Self {
+        self.config_info.spdm_version = [SpdmVersion::Unknown(0); MAX_SPDM_VERSION_COUNT];
+        for (slot, version) in self.config_info.spdm_version.iter_mut().zip(versions) {
+            *slot = *version;
+        }
+        self.version_count = versions.len();
+        self
+    }
+
+    pub fn capabilities(mut self, capabilities: SpdmResponseCapabilityFlags) -> Self {
+        self.config_info.rsp_capabilities = capabilities;
+        self
+    }
+
+    pub fn ct_exponent(mut self, ct_exponent: u8) -> Self {
+        self.config_info.rsp_ct_exponent = ct_exponent;
+        self
+    }
+
+    pub fn data_transfer_size(mut self, data_transfer_size: u32, max_spdm_msg_size: u32) -> Self {
+        self.config_info.data_transfer_size = data_transfer_size;
+        self.config_info.max_spdm_msg_size = max_spdm_msg_size;
+        self
+    }
+
+    pub fn base_hash_algo(mut self, base_hash_algo: SpdmBaseHashAlgo) -> Self {
+        self.config_info.base_hash_algo = base_hash_algo;
+        self
+    }
+
+    pub fn base_asym_algo(mut self, base_asym_algo: SpdmBaseAsymAlgo) -> Self {
+        self.config_info.base_asym_algo = base_asym_algo;
+        self
+    }
+
+    /// Also selects the DMTF measurement specification.
+    pub fn measurement_hash_algo(mut self, measurement_hash_algo: SpdmMeasurementHashAlgo) -> Self {
+        self.config_info.measurement_specification = SpdmMeasurementSpecification::DMTF;
+        self.config_info.measurement_hash_algo = measurement_hash_algo;
+        self
+    }
+
+    pub fn dhe_algo(mut self, dhe_algo: SpdmDheAlgo) -> Self {
+        self.config_info.dhe_algo = dhe_algo;
+        self
+    }
+
+    pub fn aead_algo(mut self, aead_algo: SpdmAeadAlgo) -> Self {
+        self.config_info.aead_algo = aead_algo;
+        self
+    }
+
+    pub fn req_asym_algo(mut self, req_asym_algo: SpdmReqAsymAlgo) -> Self {
+        self.config_info.req_asym_algo = req_asym_algo;
+        self
+    }
+
+    pub fn key_schedule_algo(mut self, key_schedule_algo: SpdmKeyScheduleAlgo) -> Self {
+        self.config_info.key_schedule_algo = key_schedule_algo;
+        self
+    }
+
+    pub fn opaque_support(mut self, opaque_support: SpdmOpaqueSupport) -> Self {
+        self.config_info.opaque_support = opaque_support;
+        self
+    }
+
+    pub fn provision_info(mut self, provision_info: SpdmProvisionInfo) -> Self {
+        self.provision_info = provision_info;
+        self
+    }
+
+    /// SPDM_STATUS_INVALID_PARAMETER if no version or too many are
+    /// configured, the base hash or asym algorithm is missing, or a
+    /// capability lacks the algorithm it needs.
+    pub fn build<'a>(
+        self,
+        device_io: &'a mut dyn SpdmDeviceIo,
+        transport_encap: &'a mut dyn SpdmTransportEncap,
+    ) -> SpdmResult<ResponderContext<'a>> {
+        self.check()?;
+        Ok(ResponderContext::new(
+            device_io,
+            transport_encap,
+            self.config_info,
+            self.provision_info,
+        ))
+    }
+
+    fn check(&self) -> SpdmResult {
+        let config_info = &self.config_info;
+        let capabilities = config_info.rsp_capabilities;
+
+        if self.version_count == 0 || self.version_count > MAX_SPDM_VERSION_COUNT {
+            error!("responder builder : {} versions\n", self.version_count);
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        // NEGOTIATE_ALGORITHMS fails without both, whatever the capabilities.
+        if config_info.base_hash_algo.is_empty() || config_info.base_asym_algo.is_empty() {
+            error!("responder builder : no base hash or asym algorithm\n");
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+
+        let needs_measurement_hash = SpdmResponseCapabilityFlags::MEAS_CAP_NO_SIG
+            | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+        let needs_aead = SpdmResponseCapabilityFlags::ENCRYPT_CAP
+            | SpdmResponseCapabilityFlags::MAC_CAP
+            | SpdmResponseCapabilityFlags::KEY_EX_CAP
+            | SpdmResponseCapabilityFlags::PSK_CAP_WITHOUT_CONTEXT
+            | SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT;
+        let missing = (capabilities.intersects(needs_measurement_hash)
+            && config_info.measurement_hash_algo.is_empty())
+            || (capabilities.contains(SpdmResponseCapabilityFlags::KEY_EX_CAP)
+                && config_info.dhe_algo.is_empty())
+            || (capabilities.intersects(needs_aead)
+                && (config_info.aead_algo.is_empty() || config_info.key_schedule_algo.is_empty()));
+        if missing {
+            error!(
+                "responder builder : capabilities {:?} lack an algorithm\n",
+                capabilities
+            );
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        Ok(())
+    }
+}
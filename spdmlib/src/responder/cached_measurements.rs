@@ -0,0 +1,45 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use conquer_once::spin::OnceCell;
+
+use crate::message::SpdmGetMeasurementsRequestPayload;
+use crate::responder::ResponderContext;
+
+/// Supplies a MEASUREMENTS response signed ahead of time, for devices that
+/// cannot afford to sign per request.
+///
+/// Only consulted for a signed GET_MEASUREMENTS outside a session, and only
+/// when `SpdmConfigInfo::serve_cached_measurements` is set. The callback
+/// writes the whole response, SPDM header included, into `response` and
+/// returns its size, or None to have the request answered as usual. The
+/// cached signature cannot cover the requester nonce nor the transcript of
+/// this connection, so the requester has to agree to verify it some other
+/// way.
+#[derive(Clone)]
+pub struct SpdmCachedMeasurements {
+    pub cached_measurements_cb: fn(
+        ctx: &ResponderContext,
+        get_measurements: &SpdmGetMeasurementsRequestPayload,
+        response: &mut [u8],
+    ) -> Option<usize>,
+}
+
+static SPDM_CACHED_MEASUREMENTS: OnceCell<SpdmCachedMeasurements> = OnceCell::uninit();
+
+pub fn register(context: SpdmCachedMeasurements) -> bool {
+    SPDM_CACHED_MEASUREMENTS.try_init_once(|| context).is_ok()
+}
+
+/// None when no callback is registered.
+pub fn cached_measurements(
+    ctx: &ResponderContext,
+    get_measurements: &SpdmGetMeasurementsRequestPayload,
+    response: &mut [u8],
+) -> Option<usize> {
+    (SPDM_CACHED_MEASUREMENTS
+        .try_get()
+        .ok()?
+        .cached_measurements_cb)(ctx, get_measurements, response)
+}
@@ -52,34 +52,34 @@ impl<'a> ResponderContext<'a> {
         );
 
         let get_certificate =
-            SpdmGetCertificateRequestPayload::spdm_read(&mut self.common, &mut reader);
-        if let Some(get_certificate) = &get_certificate {
-            debug!("!!! get_certificate : {:02x?}\n", get_certificate);
-            if get_certificate.slot_id != 0 {
-                self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
-                return;
-            }
-        } else {
-            error!("!!! get_certificate : fail !!!\n");
-            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
-            return;
-        }
-
-        match session_id {
-            None => {
-                if self
-                    .common
-                    .append_message_b(&bytes[..reader.used()])
-                    .is_err()
-                {
-                    self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+            match SpdmGetCertificateRequestPayload::spdm_read(&mut self.common, &mut reader) {
+                Some(get_certificate) => {
+                    debug!("!!! get_certificate : {:02x?}\n", get_certificate);
+                    get_certificate
+                }
+                None => {
+                    error!("!!! get_certificate : fail !!!\n");
+                    self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
                     return;
                 }
-            }
-            Some(_session_id) => {}
+            };
+        // a size query does not retrieve any of the chain, it stays out of
+        // the transcript.
+        let slot_size_requested = get_certificate
+            .request_attribute
+            .contains(SpdmGetCertificateRequestAttributes::SLOT_SIZE_REQUESTED);
+
+        if session_id.is_none()
+            && !slot_size_requested
+            && self
+                .common
+                .append_message_b(&bytes[..reader.used()])
+                .is_err()
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+            return;
         }
 
-        let get_certificate = get_certificate.unwrap();
         let slot_id = get_certificate.slot_id as usize;
         if slot_id >= SPDM_MAX_SLOT_NUMBER {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
@@ -94,12 +94,15 @@ impl<'a> ResponderContext<'a> {
             .as_ref()
             .unwrap();
 
-        let mut length = get_certificate.length;
+        let (offset, mut length) = if slot_size_requested {
+            (0, 0)
+        } else {
+            (get_certificate.offset, get_certificate.length)
+        };
         if length > MAX_SPDM_CERT_PORTION_LEN as u16 {
             length = MAX_SPDM_CERT_PORTION_LEN as u16;
         }
 
-        let offset = get_certificate.offset;
         if offset > my_cert_chain.data_size {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
             return;
@@ -125,6 +128,7 @@ impl<'a> ResponderContext<'a> {
             },
             payload: SpdmMessagePayload::SpdmCertificateResponse(SpdmCertificateResponsePayload {
                 slot_id: slot_id as u8,
+                cert_model: self.common.config_info.cert_model[slot_id],
                 portion_length,
                 remainder_length,
                 cert_chain,
@@ -136,13 +140,11 @@ impl<'a> ResponderContext<'a> {
             return;
         }
 
-        match session_id {
-            None => {
-                if self.common.append_message_b(writer.used_slice()).is_err() {
-                    self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
-                }
-            }
-            Some(_session_id) => {}
+        if session_id.is_none()
+            && !slot_size_requested
+            && self.common.append_message_b(writer.used_slice()).is_err()
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
         }
     }
 }
@@ -124,6 +124,13 @@ impl<'a> ResponderContext<'a> {
             return;
         }
 
+        let mut slot_mask = 0u8;
+        for slot_id in 0..SPDM_MAX_SLOT_NUMBER {
+            if self.common.provision_info.my_cert_chain[slot_id].is_some() {
+                slot_mask |= (1 << slot_id) as u8;
+            }
+        }
+
         info!("send spdm challenge_auth\n");
 
         let response = SpdmMessage {
@@ -134,7 +141,7 @@ impl<'a> ResponderContext<'a> {
             payload: SpdmMessagePayload::SpdmChallengeAuthResponse(
                 SpdmChallengeAuthResponsePayload {
                     slot_id: slot_id as u8,
-                    slot_mask: 0x1,
+                    slot_mask,
                     challenge_auth_attribute: SpdmChallengeAuthAttribute::empty(),
                     cert_chain_hash,
                     nonce: SpdmNonceStruct { data: nonce },
@@ -150,12 +157,16 @@ impl<'a> ResponderContext<'a> {
                 },
             ),
         };
-        let res = response.spdm_encode(&mut self.common, writer);
+        // the response is only written out once signed, a failure on the
+        // way is answered with ERROR alone.
+        let mut response_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut response_writer = Writer::init(&mut response_buffer);
+        let res = response.spdm_encode(&mut self.common, &mut response_writer);
         if res.is_err() {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
             return;
         }
-        let used = writer.used();
+        let used = response_writer.used();
 
         // generat signature
         let base_asym_size = self.common.negotiate_info.base_asym_sel.get_size() as usize;
@@ -163,7 +174,7 @@ impl<'a> ResponderContext<'a> {
 
         if self
             .common
-            .append_message_c(&writer.used_slice()[..temp_used])
+            .append_message_c(&response_writer.used_slice()[..temp_used])
             .is_err()
         {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
@@ -172,12 +183,22 @@ impl<'a> ResponderContext<'a> {
 
         let signature = self.generate_challenge_auth_signature();
         if signature.is_err() {
-            self.send_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0);
+            self.common.reset_message_b();
+            self.common.reset_message_c();
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
             return;
         }
         let signature = signature.unwrap();
         // patch the message before send
-        writer.mut_used_slice()[(used - base_asym_size)..used].copy_from_slice(signature.as_ref());
+        response_writer.mut_used_slice()[(used - base_asym_size)..used]
+            .copy_from_slice(signature.as_ref());
+        if writer
+            .extend_from_slice(response_writer.used_slice())
+            .is_none()
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+            return;
+        }
 
         self.common.reset_message_b();
         self.common.reset_message_c();
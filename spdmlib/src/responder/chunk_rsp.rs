@@ -0,0 +1,365 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+extern crate alloc;
+use alloc::vec::Vec;
+
+use crate::common::SpdmCodec;
+use crate::error::{SpdmResult, SPDM_STATUS_BUFFER_FULL};
+use crate::message::*;
+use crate::responder::*;
+
+/// A response too large for the requester to take in one transfer, kept
+/// until the requester fetched all of it with CHUNK_GET.
+pub(crate) struct SpdmChunkLargeResponse {
+    pub handle: u8,
+    pub message: Vec<u8>,
+}
+
+/// A request being received with CHUNK_SEND.
+pub(crate) struct SpdmChunkLargeRequest {
+    pub handle: u8,
+    pub next_chunk_seq_no: u16,
+    pub large_message_size: usize,
+    pub message: Vec<u8>,
+}
+
+/// What `ResponderContext::fit_response` made of a response.
+pub(crate) enum SpdmFittedResponse {
+    /// Send the response as it is.
+    AsIs,
+    /// Send the message of this size in the buffer instead: the response
+    /// wrapped in a CHUNK_SEND_ACK, or an ERROR(LargeResponse) for it.
+    Replaced(usize),
+    /// Send the ERROR(ResponseTooLarge) of this size in the buffer instead,
+    /// the response is dropped.
+    TooLarge(usize),
+}
+
+// Chunking sits between the request handlers and the transport: a response
+// larger than the DataTransferSize of the requester is announced with
+// ERROR(LargeResponse) and fetched with CHUNK_GET, and a request received
+// with CHUNK_SEND is dispatched once whole, so the handlers only ever see
+// whole messages.
+impl<'a> ResponderContext<'a> {
+    /// Fit `response` to the DataTransferSize of the requester, see
+    /// `SpdmFittedResponse`. Application messages are never chunked.
+    pub(crate) fn fit_response(
+        &mut self,
+        response: &[u8],
+        is_app_message: bool,
+        buffer: &mut [u8],
+    ) -> SpdmResult<SpdmFittedResponse> {
+        let chunk_send_ack = self.chunk_send_ack.take();
+        let data_transfer_size = self.common.negotiate_info.req_data_transfer_size_sel as usize;
+        let overhead = if chunk_send_ack.is_some() {
+            SPDM_CHUNK_SEND_ACK_HEADER_SIZE
+        } else {
+            0
+        };
+
+        let mut error_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut error_buffer);
+        let (response, delivered) = if is_app_message
+            || data_transfer_size == 0
+            || response.len() + overhead <= data_transfer_size
+        {
+            if chunk_send_ack.is_none() {
+                return Ok(SpdmFittedResponse::AsIs);
+            }
+            (response, true)
+        } else if self.common.negotiate_info.chunk_supported()
+            && response.len() <= self.common.negotiate_info.req_max_spdm_msg_size_sel as usize
+        {
+            let handle = self.chunk_handle;
+            self.chunk_handle = self.chunk_handle.wrapping_add(1);
+            self.chunk_large_response = Some(SpdmChunkLargeResponse {
+                handle,
+                message: response.to_vec(),
+            });
+            let error = SpdmMessage {
+                header: SpdmMessageHeader {
+                    version: self.common.negotiate_info.spdm_version_sel,
+                    request_response_code: SpdmRequestResponseCode::SpdmResponseError,
+                },
+                payload: SpdmMessagePayload::SpdmErrorResponse(SpdmErrorResponsePayload {
+                    error_code: SpdmErrorCode::SpdmErrorLargeResponse,
+                    error_data: 0,
+                    extended_data: SpdmErrorResponseExtData::SpdmErrorExtDataLargeResponse(
+                        SpdmErrorResponseLargeResponseExtData { handle },
+                    ),
+                }),
+            };
+            error.spdm_encode(&mut self.common, &mut writer)?;
+            (writer.used_slice(), true)
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorResponseTooLarge, 0, &mut writer);
+            (writer.used_slice(), false)
+        };
+
+        let used = match chunk_send_ack {
+            Some((handle, chunk_seq_no)) => {
+                let mut chunk_send_ack = SpdmChunkSendAckResponsePayload {
+                    handle,
+                    chunk_seq_no,
+                    response_size: response.len() as u32,
+                    ..Default::default()
+                };
+                chunk_send_ack.response[..response.len()].copy_from_slice(response);
+                let message = SpdmMessage {
+                    header: SpdmMessageHeader {
+                        version: self.common.negotiate_info.spdm_version_sel,
+                        request_response_code: SpdmRequestResponseCode::SpdmResponseChunkSendAck,
+                    },
+                    payload: SpdmMessagePayload::SpdmChunkSendAckResponse(chunk_send_ack),
+                };
+                let mut writer = Writer::init(buffer);
+                message.spdm_encode(&mut self.common, &mut writer)?
+            }
+            None => {
+                if response.len() > buffer.len() {
+                    return Err(SPDM_STATUS_BUFFER_FULL);
+                }
+                buffer[..response.len()].copy_from_slice(response);
+                response.len()
+            }
+        };
+
+        if delivered {
+            Ok(SpdmFittedResponse::Replaced(used))
+        } else {
+            Ok(SpdmFittedResponse::TooLarge(used))
+        }
+    }
+
+    pub fn handle_spdm_chunk_get(&mut self, bytes: &[u8], session_id: Option<u32>) -> SpdmResult {
+        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut send_buffer);
+        self.write_spdm_chunk_response(bytes, &mut writer);
+
+        if let Some(session_id) = session_id {
+            self.send_secured_message(session_id, writer.used_slice(), false)
+        } else {
+            self.send_message(writer.used_slice())
+        }
+    }
+
+    fn write_spdm_chunk_response(&mut self, bytes: &[u8], writer: &mut Writer) {
+        if !self.common.negotiate_info.chunk_supported() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnsupportedRequest, 0, writer);
+            return;
+        }
+        let mut reader = Reader::init(bytes);
+        let message_header = SpdmMessageHeader::read(&mut reader);
+        if let Some(message_header) = message_header {
+            if message_header.version != self.common.negotiate_info.spdm_version_sel {
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorVersionMismatch, 0, writer);
+                return;
+            }
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+
+        let chunk_get = SpdmChunkGetRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let chunk_get = if let Some(chunk_get) = chunk_get {
+            debug!("!!! chunk_get : {:02x?}\n", chunk_get);
+            chunk_get
+        } else {
+            error!("!!! chunk_get : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        };
+
+        let large_response = if let Some(large_response) = self.chunk_large_response.take() {
+            large_response
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnexpectedRequest, 0, writer);
+            return;
+        };
+
+        let chunk_capacity = (self.common.negotiate_info.req_data_transfer_size_sel as usize)
+            .saturating_sub(SPDM_CHUNK_HEADER_SIZE);
+        let first_chunk_capacity =
+            chunk_capacity.saturating_sub(SPDM_CHUNK_LARGE_MESSAGE_SIZE_SIZE);
+        let chunk_seq_no = chunk_get.chunk_seq_no as usize;
+        let offset = if chunk_seq_no == 0 {
+            0
+        } else {
+            first_chunk_capacity + (chunk_seq_no - 1) * chunk_capacity
+        };
+        if chunk_get.handle != large_response.handle
+            || first_chunk_capacity == 0
+            || offset >= large_response.message.len()
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+
+        let capacity = if chunk_seq_no == 0 {
+            first_chunk_capacity
+        } else {
+            chunk_capacity
+        };
+        let chunk_size = capacity.min(large_response.message.len() - offset);
+        let last_chunk = offset + chunk_size == large_response.message.len();
+
+        let mut chunk = SpdmChunkPayload {
+            handle: large_response.handle,
+            chunk_seq_no: chunk_get.chunk_seq_no,
+            chunk_size: chunk_size as u32,
+            large_message_size: large_response.message.len() as u32,
+            ..Default::default()
+        };
+        if last_chunk {
+            chunk.attributes = SpdmChunkAttributes::LAST_CHUNK;
+        }
+        chunk.chunk[..chunk_size]
+            .copy_from_slice(&large_response.message[offset..offset + chunk_size]);
+        if !last_chunk {
+            self.chunk_large_response = Some(large_response);
+        }
+
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseChunkResponse,
+            },
+            payload: SpdmMessagePayload::SpdmChunkResponse(chunk),
+        };
+        let _ = response.spdm_encode(&mut self.common, writer);
+    }
+
+    /// Acknowledge each chunk of a large request, and once it is whole
+    /// dispatch it as if received in one transfer; its response goes in
+    /// the CHUNK_SEND_ACK of the last chunk.
+    pub fn handle_spdm_chunk_send(&mut self, bytes: &[u8], session_id: Option<u32>) -> SpdmResult {
+        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut send_buffer);
+        if let Some((chunk_send_ack, request)) =
+            self.write_spdm_chunk_send_ack_response(bytes, &mut writer)
+        {
+            self.chunk_send_ack = Some(chunk_send_ack);
+            let result = match session_id {
+                Some(session_id) => self.dispatch_secured_message(session_id, &request),
+                None => self.dispatch_message(&request),
+            };
+            self.chunk_send_ack = None;
+            return result;
+        }
+
+        if let Some(session_id) = session_id {
+            self.send_secured_message(session_id, writer.used_slice(), false)
+        } else {
+            self.send_message(writer.used_slice())
+        }
+    }
+
+    // The handle and ChunkSeqNo of the last chunk with the whole request,
+    // once it arrived.
+    fn write_spdm_chunk_send_ack_response(
+        &mut self,
+        bytes: &[u8],
+        writer: &mut Writer,
+    ) -> Option<((u8, u16), Vec<u8>)> {
+        if !self.common.negotiate_info.chunk_supported() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnsupportedRequest, 0, writer);
+            return None;
+        }
+        let mut reader = Reader::init(bytes);
+        let message_header = SpdmMessageHeader::read(&mut reader);
+        if let Some(message_header) = message_header {
+            if message_header.version != self.common.negotiate_info.spdm_version_sel {
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorVersionMismatch, 0, writer);
+                return None;
+            }
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return None;
+        }
+
+        let chunk_send = SpdmChunkPayload::spdm_read(&mut self.common, &mut reader);
+        let chunk_send = if let Some(chunk_send) = chunk_send {
+            chunk_send
+        } else {
+            error!("!!! chunk_send : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return None;
+        };
+
+        if chunk_send.chunk_seq_no == 0 {
+            let large_message_size = chunk_send.large_message_size as usize;
+            if large_message_size > config::MAX_SPDM_MSG_SIZE
+                || large_message_size
+                    > self.common.negotiate_info.rsp_max_spdm_msg_size_sel as usize
+            {
+                self.chunk_large_request = None;
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorRequestTooLarge, 0, writer);
+                return None;
+            }
+            self.chunk_large_request = Some(SpdmChunkLargeRequest {
+                handle: chunk_send.handle,
+                next_chunk_seq_no: 0,
+                large_message_size,
+                message: Vec::new(),
+            });
+        }
+
+        let mut large_request = if let Some(large_request) = self.chunk_large_request.take() {
+            large_request
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnexpectedRequest, 0, writer);
+            return None;
+        };
+        let chunk_size = chunk_send.chunk_size as usize;
+        if chunk_send.handle != large_request.handle
+            || chunk_send.chunk_seq_no != large_request.next_chunk_seq_no
+            || large_request.message.len() + chunk_size > large_request.large_message_size
+        {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return None;
+        }
+        large_request
+            .message
+            .extend_from_slice(&chunk_send.chunk[..chunk_size]);
+
+        let complete = large_request.message.len() == large_request.large_message_size;
+        if chunk_send
+            .attributes
+            .contains(SpdmChunkAttributes::LAST_CHUNK)
+        {
+            if !complete {
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+                return None;
+            }
+            return Some((
+                (chunk_send.handle, chunk_send.chunk_seq_no),
+                large_request.message,
+            ));
+        }
+        let next_chunk_seq_no = chunk_send.chunk_seq_no.checked_add(1);
+        if complete || chunk_size == 0 || next_chunk_seq_no.is_none() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return None;
+        }
+        large_request.next_chunk_seq_no = next_chunk_seq_no.unwrap();
+        self.chunk_large_request = Some(large_request);
+
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseChunkSendAck,
+            },
+            payload: SpdmMessagePayload::SpdmChunkSendAckResponse(
+                SpdmChunkSendAckResponsePayload {
+                    handle: chunk_send.handle,
+                    chunk_seq_no: chunk_send.chunk_seq_no,
+                    ..Default::default()
+                },
+            ),
+        };
+        let _ = response.spdm_encode(&mut self.common, writer);
+        None
+    }
+}
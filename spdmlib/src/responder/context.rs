@@ -3,16 +3,34 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use super::app_message_handler::dispatch_secured_app_message_cb;
+use super::chunk_rsp::{SpdmChunkLargeRequest, SpdmChunkLargeResponse, SpdmFittedResponse};
+#[cfg(feature = "alloc")]
+use crate::common::SpdmCaptureDirection;
+#[cfg(feature = "mut-auth")]
+use crate::common::SpdmCertChainTrust;
 use crate::common::SpdmConnectionState;
 use crate::common::{session::SpdmSessionState, SpdmDeviceIo, SpdmTransportEncap};
 use crate::config;
-use crate::error::{SpdmResult, SPDM_STATUS_UNSUPPORTED_CAP};
+use crate::error::{
+    SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_PARAMETER,
+    SPDM_STATUS_UNSUPPORTED_CAP,
+};
 use crate::message::*;
-use crate::protocol::{SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags};
-use codec::{Codec, Reader, Writer};
+#[cfg(feature = "mut-auth")]
+use crate::protocol::SpdmCertChainBuffer;
+use crate::protocol::{SpdmCertChainData, SPDM_MAX_SLOT_NUMBER};
+use codec::{Codec, Reader};
 
 pub struct ResponderContext<'a> {
     pub common: crate::common::SpdmContext<'a>,
+    pub(crate) last_error: Option<super::SpdmResponderErrorState>,
+    /// Handle of the next response fetched with CHUNK_GET.
+    pub(crate) chunk_handle: u8,
+    pub(crate) chunk_large_response: Option<SpdmChunkLargeResponse>,
+    pub(crate) chunk_large_request: Option<SpdmChunkLargeRequest>,
+    /// Handle and ChunkSeqNo of the last CHUNK_SEND, while the request it
+    /// completed is being handled.
+    pub(crate) chunk_send_ack: Option<(u8, u16)>,
 }
 
 impl<'a> ResponderContext<'a> {
@@ -29,55 +47,142 @@ impl<'a> ResponderContext<'a> {
                 config_info,
                 provision_info,
             ),
+            last_error: None,
+            chunk_handle: 0,
+            chunk_large_response: None,
+            chunk_large_request: None,
+            chunk_send_ack: None,
         }
     }
 
-    pub fn send_message(&mut self, send_buffer: &[u8]) -> SpdmResult {
-        if self.common.negotiate_info.req_data_transfer_size_sel != 0
-            && (send_buffer.len() > self.common.negotiate_info.req_data_transfer_size_sel as usize)
+    /// Provision `slot_id` with a DER encoded certificate chain, root
+    /// certificate first.
+    ///
+    /// The chain is verified before it is stored. The SPDM certificate
+    /// chain header, length and root hash, needs the negotiated hash
+    /// algorithm, so it is built now if one is selected and otherwise when
+    /// NEGOTIATE_ALGORITHMS completes.
+    pub fn provision_cert_chain(&mut self, slot_id: u8, der_cert_chain: &[u8]) -> SpdmResult {
+        let slot = slot_id as usize;
+        if slot >= SPDM_MAX_SLOT_NUMBER {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        if der_cert_chain.is_empty() {
+            return Err(SPDM_STATUS_INVALID_CERT);
+        }
+        if der_cert_chain.len() > config::MAX_SPDM_CERT_CHAIN_DATA_SIZE {
+            return Err(SPDM_STATUS_BUFFER_FULL);
+        }
+        crate::crypto::cert_operation::verify_cert_chain(der_cert_chain)
+            .map_err(|_| SPDM_STATUS_INVALID_CERT)?;
+
+        let mut cert_chain_data = SpdmCertChainData {
+            data_size: der_cert_chain.len() as u16,
+            ..Default::default()
+        };
+        cert_chain_data.data[..der_cert_chain.len()].copy_from_slice(der_cert_chain);
+        self.common.provision_info.my_cert_chain_data[slot] = Some(cert_chain_data);
+        self.common.provision_info.my_cert_chain[slot] = None;
+
+        if self
+            .common
+            .negotiate_info
+            .base_hash_sel
+            .is_valid_one_select()
         {
-            let mut err_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-            let mut writer = Writer::init(&mut err_buffer);
-            self.write_spdm_error(SpdmErrorCode::SpdmErrorResponseTooLarge, 0, &mut writer);
-            return self.send_message(writer.used_slice());
+            self.common.construct_my_cert_chain()?;
+        }
+        Ok(())
+    }
+
+    /// Provision the certificate chain, SPDM header included, that the
+    /// requester signs with from `req_slot_id` under basic mutual auth.
+    /// FINISH only names the slot, so the chain must be known beforehand.
+    /// It is kept across GET_VERSION and `clear_peer_data`.
+    #[cfg(feature = "mut-auth")]
+    pub fn provision_requester_cert_chain(
+        &mut self,
+        req_slot_id: u8,
+        cert_chain: SpdmCertChainBuffer,
+    ) -> SpdmResult {
+        let slot = req_slot_id as usize;
+        if slot >= SPDM_MAX_SLOT_NUMBER {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
         }
+        if cert_chain.data_size == 0 || cert_chain.data_size as usize > cert_chain.data.len() {
+            return Err(SPDM_STATUS_INVALID_CERT);
+        }
+        self.common.peer_info.peer_cert_chain[slot] = Some(cert_chain);
+        self.common.peer_info.peer_cert_chain_trust[slot] = Some(SpdmCertChainTrust::Provisioned);
+        Ok(())
+    }
+
+    /// The largest SPDM messages this responder sends and receives without
+    /// chunking, in or out of a session, as bounded by its buffers.
+    pub fn get_message_size_limit(
+        &mut self,
+        secured_message: bool,
+    ) -> crate::common::SpdmMessageSizeLimit {
+        let limit = self.common.get_message_size_limit(
+            false,
+            secured_message,
+            config::SENDER_BUFFER_SIZE,
+            config::RECEIVER_BUFFER_SIZE,
+        );
+        crate::common::SpdmMessageSizeLimit {
+            max_send_size: limit.max_send_size.min(config::MAX_SPDM_MSG_SIZE),
+            max_receive_size: limit.max_receive_size.min(config::MAX_SPDM_MSG_SIZE),
+        }
+    }
+
+    /// A response larger than the DataTransferSize of the requester is
+    /// sent in chunks when both sides support it, else replaced by
+    /// ERROR(ResponseTooLarge).
+    pub fn send_message(&mut self, send_buffer: &[u8]) -> SpdmResult {
+        let mut fitted_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let (message, delivered) =
+            match self.fit_response(send_buffer, false, &mut fitted_buffer)? {
+                SpdmFittedResponse::AsIs => (send_buffer, true),
+                SpdmFittedResponse::Replaced(used) => (&fitted_buffer[..used], true),
+                SpdmFittedResponse::TooLarge(used) => (&fitted_buffer[..used], false),
+            };
         let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
-        let used = self.common.encap(send_buffer, &mut transport_buffer)?;
+        let used = self.common.encap(message, &mut transport_buffer)?;
         let result = self.common.device_io.send(&transport_buffer[..used]);
-        if result.is_ok() {
+        if result.is_ok() && delivered {
             let opcode = send_buffer[1];
             if opcode == SpdmRequestResponseCode::SpdmResponseVersion.get_u8() {
                 self.common
                     .runtime_info
-                    .set_connection_state(SpdmConnectionState::SpdmConnectionAfterVersion);
+                    .transition_connection_state(SpdmConnectionState::SpdmConnectionAfterVersion)?;
             } else if opcode == SpdmRequestResponseCode::SpdmResponseCapabilities.get_u8() {
-                self.common
-                    .runtime_info
-                    .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCapabilities);
+                self.common.runtime_info.transition_connection_state(
+                    SpdmConnectionState::SpdmConnectionAfterCapabilities,
+                )?;
             } else if opcode == SpdmRequestResponseCode::SpdmResponseAlgorithms.get_u8() {
                 self.common
                     .runtime_info
-                    .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+                    .transition_connection_state(SpdmConnectionState::SpdmConnectionNegotiated)?;
             } else if opcode == SpdmRequestResponseCode::SpdmResponseDigests.get_u8() {
                 if self.common.runtime_info.get_connection_state().get_u8()
                     < SpdmConnectionState::SpdmConnectionAfterDigest.get_u8()
                 {
-                    self.common
-                        .runtime_info
-                        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterDigest);
+                    self.common.runtime_info.transition_connection_state(
+                        SpdmConnectionState::SpdmConnectionAfterDigest,
+                    )?;
                 }
             } else if opcode == SpdmRequestResponseCode::SpdmResponseCertificate.get_u8() {
                 if self.common.runtime_info.get_connection_state().get_u8()
                     < SpdmConnectionState::SpdmConnectionAfterCertificate.get_u8()
                 {
-                    self.common
-                        .runtime_info
-                        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCertificate);
+                    self.common.runtime_info.transition_connection_state(
+                        SpdmConnectionState::SpdmConnectionAfterCertificate,
+                    )?;
                 }
             } else if opcode == SpdmRequestResponseCode::SpdmResponseChallengeAuth.get_u8() {
-                self.common
-                    .runtime_info
-                    .set_connection_state(SpdmConnectionState::SpdmConnectionAuthenticated);
+                self.common.runtime_info.transition_connection_state(
+                    SpdmConnectionState::SpdmConnectionAuthenticated,
+                )?;
             } else if opcode == SpdmRequestResponseCode::SpdmResponseFinishRsp.get_u8() {
                 let session = self
                     .common
@@ -98,26 +203,24 @@ impl<'a> ResponderContext<'a> {
         send_buffer: &[u8],
         is_app_message: bool,
     ) -> SpdmResult {
-        if !is_app_message
-            && self.common.negotiate_info.req_data_transfer_size_sel != 0
-            && send_buffer.len() > self.common.negotiate_info.req_data_transfer_size_sel as usize
-        {
-            let mut err_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
-            let mut writer = Writer::init(&mut err_buffer);
-            self.write_spdm_error(SpdmErrorCode::SpdmErrorResponseTooLarge, 0, &mut writer);
-            return self.send_secured_message(session_id, writer.used_slice(), is_app_message);
-        }
+        let mut fitted_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let (message, delivered) =
+            match self.fit_response(send_buffer, is_app_message, &mut fitted_buffer)? {
+                SpdmFittedResponse::AsIs => (send_buffer, true),
+                SpdmFittedResponse::Replaced(used) => (&fitted_buffer[..used], true),
+                SpdmFittedResponse::TooLarge(used) => (&fitted_buffer[..used], false),
+            };
 
         let mut transport_buffer = [0u8; config::SENDER_BUFFER_SIZE];
         let used = self.common.encode_secured_message(
             session_id,
-            send_buffer,
+            message,
             &mut transport_buffer,
             false,
             is_app_message,
         )?;
         let result = self.common.device_io.send(&transport_buffer[..used]);
-        if result.is_ok() {
+        if result.is_ok() && delivered {
             let opcode = send_buffer[1];
             // change state after message is sent.
             if opcode == SpdmRequestResponseCode::SpdmResponseEndSessionAck.get_u8() {
@@ -136,6 +239,16 @@ impl<'a> ResponderContext<'a> {
         result
     }
 
+    /// Send a message of `session_id` in the clear or secured, as
+    /// `SpdmContext::is_handshake_in_the_clear` says.
+    pub fn send_handshake_message(&mut self, session_id: u32, send_buffer: &[u8]) -> SpdmResult {
+        if self.common.is_handshake_in_the_clear(session_id) {
+            self.send_message(send_buffer)
+        } else {
+            self.send_secured_message(session_id, send_buffer, false)
+        }
+    }
+
     pub fn process_message(
         &mut self,
         timeout: usize,
@@ -148,6 +261,18 @@ impl<'a> ResponderContext<'a> {
                     let mut read = Reader::init(&receive_buffer[0..used]);
                     let session_id = u32::read(&mut read).ok_or((used, receive_buffer))?;
 
+                    let sequence_number = self
+                        .common
+                        .get_session_via_id(session_id)
+                        .ok_or((used, receive_buffer))?
+                        .get_sequence_number(true);
+                    self.common.observe_secured_message(
+                        session_id,
+                        sequence_number,
+                        &receive_buffer[..used],
+                        false,
+                    );
+
                     let spdm_session = self
                         .common
                         .get_session_via_id(session_id)
@@ -164,6 +289,7 @@ impl<'a> ResponderContext<'a> {
                         return Err((used, receive_buffer));
                     }
                     let decode_size = decode_size.unwrap();
+                    self.common.touch_session(session_id);
 
                     let mut spdm_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
                     let decap_result = self
@@ -173,6 +299,11 @@ impl<'a> ResponderContext<'a> {
                     match decap_result {
                         Err(_) => Err((used, receive_buffer)),
                         Ok((decode_size, is_app_message)) => {
+                            #[cfg(feature = "alloc")]
+                            self.common.message_capture.record(
+                                SpdmCaptureDirection::Received,
+                                &spdm_buffer[..decode_size],
+                            );
                             if !is_app_message {
                                 Ok(self
                                     .dispatch_secured_message(
@@ -192,6 +323,10 @@ impl<'a> ResponderContext<'a> {
                         }
                     }
                 } else {
+                    #[cfg(feature = "alloc")]
+                    self.common
+                        .message_capture
+                        .record(SpdmCaptureDirection::Received, &receive_buffer[..used]);
                     Ok(self.dispatch_message(&receive_buffer[0..used]).is_ok())
                 }
             }
@@ -224,7 +359,7 @@ impl<'a> ResponderContext<'a> {
         Ok((used, secured_message))
     }
 
-    fn dispatch_secured_message(&mut self, session_id: u32, bytes: &[u8]) -> SpdmResult {
+    pub(crate) fn dispatch_secured_message(&mut self, session_id: u32, bytes: &[u8]) -> SpdmResult {
         let mut reader = Reader::init(bytes);
 
         let session = self.common.get_immutable_session_via_id(session_id);
@@ -235,18 +370,13 @@ impl<'a> ResponderContext<'a> {
 
         match session.get_session_state() {
             SpdmSessionState::SpdmSessionHandshaking => {
-                let in_clear_text = self
-                    .common
-                    .negotiate_info
-                    .req_capabilities_sel
-                    .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-                    && self
-                        .common
-                        .negotiate_info
-                        .rsp_capabilities_sel
-                        .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
-                if in_clear_text {
-                    return Err(SPDM_STATUS_UNSUPPORTED_CAP);
+                if self.common.is_handshake_in_the_clear(session_id) {
+                    error!("!!! secured message in a handshake negotiated in the clear !!!\n");
+                    return self.handle_error_request(
+                        SpdmErrorCode::SpdmErrorUnexpectedRequest,
+                        None,
+                        bytes,
+                    );
                 }
 
                 match SpdmMessageHeader::read(&mut reader) {
@@ -304,6 +434,14 @@ impl<'a> ResponderContext<'a> {
             SpdmSessionState::SpdmSessionEstablished => {
                 match SpdmMessageHeader::read(&mut reader) {
                     Some(message_header) => match message_header.request_response_code {
+                        #[cfg(feature = "mut-auth")]
+                        SpdmRequestResponseCode::SpdmRequestGetEncapsulatedRequest => {
+                            self.handle_get_encapsulated_request(session_id, bytes)
+                        }
+                        #[cfg(feature = "mut-auth")]
+                        SpdmRequestResponseCode::SpdmRequestDeliverEncapsulatedResponse => {
+                            self.handle_deliver_encapsulated_reponse(session_id, bytes)
+                        }
                         SpdmRequestResponseCode::SpdmRequestGetDigests => {
                             self.handle_spdm_digest(bytes, Some(session_id))
                         }
@@ -329,6 +467,23 @@ impl<'a> ResponderContext<'a> {
                             self.handle_spdm_vendor_defined_request(Some(session_id), bytes)
                         }
 
+                        SpdmRequestResponseCode::SpdmRequestGetSupportedEventTypes => {
+                            self.handle_spdm_get_supported_event_types(session_id, bytes)
+                        }
+                        SpdmRequestResponseCode::SpdmRequestSubscribeEventTypes => {
+                            self.handle_spdm_subscribe_event_types(session_id, bytes)
+                        }
+                        SpdmRequestResponseCode::SpdmResponseEventAck => {
+                            self.handle_spdm_event_ack(session_id, bytes)
+                        }
+
+                        SpdmRequestResponseCode::SpdmRequestChunkGet => {
+                            self.handle_spdm_chunk_get(bytes, Some(session_id))
+                        }
+                        SpdmRequestResponseCode::SpdmRequestChunkSend => {
+                            self.handle_spdm_chunk_send(bytes, Some(session_id))
+                        }
+
                         SpdmRequestResponseCode::SpdmRequestGetVersion
                         | SpdmRequestResponseCode::SpdmRequestGetCapabilities
                         | SpdmRequestResponseCode::SpdmRequestNegotiateAlgorithms
@@ -372,6 +527,15 @@ impl<'a> ResponderContext<'a> {
             dispatch_secured_app_message_cb(self, session_id, bytes, auxiliary_app_data).unwrap();
         self.send_secured_message(session_id, &rsp_app_buffer[..size], true)
     }
+
+    // The KEY_EXCHANGE session a handshake message in the clear is for.
+    fn handshake_in_the_clear_session_id(&self) -> Option<u32> {
+        self.common
+            .runtime_info
+            .get_last_session_id()
+            .filter(|&session_id| self.common.is_handshake_in_the_clear(session_id))
+    }
+
     pub fn dispatch_message(&mut self, bytes: &[u8]) -> SpdmResult {
         let mut reader = Reader::init(bytes);
         match SpdmMessageHeader::read(&mut reader) {
@@ -406,31 +570,18 @@ impl<'a> ResponderContext<'a> {
                     self.handle_spdm_vendor_defined_request(None, bytes)
                 }
 
+                SpdmRequestResponseCode::SpdmRequestChunkGet => {
+                    self.handle_spdm_chunk_get(bytes, None)
+                }
+                SpdmRequestResponseCode::SpdmRequestChunkSend => {
+                    self.handle_spdm_chunk_send(bytes, None)
+                }
+
                 SpdmRequestResponseCode::SpdmRequestFinish => {
-                    let in_clear_text = self
-                        .common
-                        .negotiate_info
-                        .req_capabilities_sel
-                        .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-                        && self
-                            .common
-                            .negotiate_info
-                            .rsp_capabilities_sel
-                            .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
-                    if in_clear_text {
-                        if let Some(session_id) = self.common.runtime_info.get_last_session_id() {
-                            if let Some(session) =
-                                self.common.get_immutable_session_via_id(session_id)
-                            {
-                                if session.get_session_state()
-                                    == SpdmSessionState::SpdmSessionHandshaking
-                                {
-                                    return self.handle_spdm_finish(session_id, bytes);
-                                }
-                            }
-                        }
+                    if let Some(session_id) = self.handshake_in_the_clear_session_id() {
+                        return self.handle_spdm_finish(session_id, bytes);
                     }
-
+                    error!("!!! FINISH in the clear, handshake is not in the clear !!!\n");
                     self.handle_error_request(
                         SpdmErrorCode::SpdmErrorUnexpectedRequest,
                         None,
@@ -438,6 +589,32 @@ impl<'a> ResponderContext<'a> {
                     )
                 }
 
+                #[cfg(feature = "mut-auth")]
+                SpdmRequestResponseCode::SpdmRequestGetEncapsulatedRequest => {
+                    match self.handshake_in_the_clear_session_id() {
+                        Some(session_id) => self.handle_get_encapsulated_request(session_id, bytes),
+                        None => self.handle_error_request(
+                            SpdmErrorCode::SpdmErrorUnexpectedRequest,
+                            None,
+                            bytes,
+                        ),
+                    }
+                }
+
+                #[cfg(feature = "mut-auth")]
+                SpdmRequestResponseCode::SpdmRequestDeliverEncapsulatedResponse => {
+                    match self.handshake_in_the_clear_session_id() {
+                        Some(session_id) => {
+                            self.handle_deliver_encapsulated_reponse(session_id, bytes)
+                        }
+                        None => self.handle_error_request(
+                            SpdmErrorCode::SpdmErrorUnexpectedRequest,
+                            None,
+                            bytes,
+                        ),
+                    }
+                }
+
                 SpdmRequestResponseCode::SpdmRequestPskFinish
                 | SpdmRequestResponseCode::SpdmRequestHeartbeat
                 | SpdmRequestResponseCode::SpdmRequestKeyUpdate
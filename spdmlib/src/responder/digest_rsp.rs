@@ -5,13 +5,11 @@
 use crate::common::SpdmCodec;
 use crate::common::SpdmConnectionState;
 use crate::crypto;
+use crate::error::SpdmResult;
 use crate::message::*;
+use crate::protocol::gen_array_clone;
 use crate::protocol::*;
 use crate::responder::*;
-extern crate alloc;
-use crate::error::SpdmResult;
-use crate::protocol::gen_array_clone;
-use alloc::boxed::Box;
 
 impl<'a> ResponderContext<'a> {
     pub fn handle_spdm_digest(&mut self, bytes: &[u8], session_id: Option<u32>) -> SpdmResult {
@@ -78,12 +76,21 @@ impl<'a> ResponderContext<'a> {
             Some(_session_id) => {}
         }
 
-        let digest_size = self.common.negotiate_info.base_hash_sel.get_size();
-
         let mut slot_mask = 0u8;
+        let mut slot_count = 0usize;
+        let mut digests = gen_array_clone(SpdmDigestStruct::default(), SPDM_MAX_SLOT_NUMBER);
+        let mut key_usage_mask = [SpdmKeyUsageMask::default(); SPDM_MAX_SLOT_NUMBER];
         for slot_id in 0..SPDM_MAX_SLOT_NUMBER {
-            if self.common.provision_info.my_cert_chain[slot_id].is_some() {
+            if let Some(my_cert_chain) = self.common.provision_info.my_cert_chain[slot_id].as_ref()
+            {
                 slot_mask |= (1 << slot_id) as u8;
+                digests[slot_count] = crypto::hash::hash_all(
+                    self.common.negotiate_info.base_hash_sel,
+                    my_cert_chain.as_ref(),
+                )
+                .unwrap();
+                key_usage_mask[slot_count] = self.common.provision_info.my_key_usage_mask[slot_id];
+                slot_count += 1;
             }
         }
 
@@ -95,13 +102,9 @@ impl<'a> ResponderContext<'a> {
             },
             payload: SpdmMessagePayload::SpdmDigestsResponse(SpdmDigestsResponsePayload {
                 slot_mask,
-                digests: gen_array_clone(
-                    SpdmDigestStruct {
-                        data_size: digest_size,
-                        data: Box::new([0xffu8; SPDM_MAX_HASH_SIZE]),
-                    },
-                    SPDM_MAX_SLOT_NUMBER,
-                ),
+                digests,
+                key_usage_mask,
+                ..Default::default()
             }),
         };
         let res = response.spdm_encode(&mut self.common, writer);
@@ -110,24 +113,6 @@ impl<'a> ResponderContext<'a> {
             return;
         }
 
-        for slot_id in 0..SPDM_MAX_SLOT_NUMBER {
-            if self.common.provision_info.my_cert_chain[slot_id].is_some() {
-                let my_cert_chain = self.common.provision_info.my_cert_chain[slot_id]
-                    .as_ref()
-                    .unwrap();
-                let cert_chain_hash = crypto::hash::hash_all(
-                    self.common.negotiate_info.base_hash_sel,
-                    my_cert_chain.as_ref(),
-                )
-                .unwrap();
-
-                // patch the message before send
-                let used = writer.used();
-                writer.mut_used_slice()[(used - cert_chain_hash.data_size as usize)..used]
-                    .copy_from_slice(cert_chain_hash.as_ref());
-            }
-        }
-
         match session_id {
             None => {
                 if self.common.append_message_b(writer.used_slice()).is_err() {
@@ -13,11 +13,11 @@ use crate::{
         SPDM_STATUS_INVALID_STATE_LOCAL,
     },
     message::{
-        SpdmCertificateResponsePayload, SpdmGetCertificateRequestPayload, SpdmMessage,
-        SpdmMessageGeneralPayload, SpdmMessageHeader, SpdmMessagePayload, SpdmRequestResponseCode,
-        MAX_SPDM_CERT_PORTION_LEN,
+        SpdmCertificateResponsePayload, SpdmGetCertificateRequestAttributes,
+        SpdmGetCertificateRequestPayload, SpdmMessage, SpdmMessageGeneralPayload,
+        SpdmMessageHeader, SpdmMessagePayload, SpdmRequestResponseCode, MAX_SPDM_CERT_PORTION_LEN,
     },
-    protocol::{SpdmCertChainBuffer, SpdmCertChainData},
+    protocol::{SpdmCertChainBuffer, SpdmCertChainData, SpdmCertChainHeader},
 };
 
 use super::ResponderContext;
@@ -47,6 +47,7 @@ impl<'a> ResponderContext<'a> {
                         .data_size,
                     length: MAX_SPDM_CERT_PORTION_LEN as u16,
                     slot_id: self.common.encap_context.req_slot_id,
+                    request_attribute: SpdmGetCertificateRequestAttributes::empty(),
                 },
             ),
         };
@@ -152,6 +153,9 @@ impl<'a> ResponderContext<'a> {
         let result = self.verify_spdm_certificate_chain().map(|_| {
             self.common.peer_info.peer_cert_chain[self.common.encap_context.req_slot_id as usize] =
                 self.common.peer_info.peer_cert_chain_temp.clone();
+            // a fetched chain replaces a provisioned one for good.
+            self.common.peer_info.peer_cert_chain_trust
+                [self.common.encap_context.req_slot_id as usize] = None;
             self.common
                 .runtime_info
                 .set_peer_used_cert_chain_slot_id(self.common.encap_context.req_slot_id);
@@ -177,24 +181,16 @@ impl<'a> ResponderContext<'a> {
             .peer_cert_chain_temp
             .as_ref()
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
-        if peer_cert_chain.data_size <= (4 + self.common.negotiate_info.base_hash_sel.get_size()) {
-            return Err(SPDM_STATUS_INVALID_CERT);
-        }
-
-        let data_size_in_cert_chain =
-            peer_cert_chain.data[0] as u16 + ((peer_cert_chain.data[1] as u16) << 8);
-        if data_size_in_cert_chain != peer_cert_chain.data_size {
-            return Err(SPDM_STATUS_INVALID_CERT);
-        }
+        let header = SpdmCertChainHeader::parse(
+            peer_cert_chain.as_ref(),
+            self.common.negotiate_info.base_hash_sel,
+        )
+        .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
-        let data_size =
-            peer_cert_chain.data_size - 4 - self.common.negotiate_info.base_hash_sel.get_size();
+        let der_cert_chain = &peer_cert_chain.as_ref()[header.der_offset..];
+        let data_size = der_cert_chain.len() as u16;
         let mut data = [0u8; config::MAX_SPDM_CERT_CHAIN_DATA_SIZE];
-        data[0..(data_size as usize)].copy_from_slice(
-            &peer_cert_chain.data[(4usize
-                + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-                ..(peer_cert_chain.data_size as usize)],
-        );
+        data[0..(data_size as usize)].copy_from_slice(der_cert_chain);
         let runtime_peer_cert_chain_data = SpdmCertChainData { data_size, data };
         info!("1. get runtime_peer_cert_chain_data!\n");
 
@@ -226,10 +222,7 @@ impl<'a> ResponderContext<'a> {
         } else {
             return Err(SPDM_STATUS_CRYPTO_ERROR);
         };
-        if root_hash.data[..(root_hash.data_size as usize)]
-            != peer_cert_chain.data
-                [4usize..(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)]
-        {
+        if root_hash.data[..(root_hash.data_size as usize)] != *header.root_hash {
             error!("root_hash - fail!\n");
             return Err(SPDM_STATUS_INVALID_CERT);
         }
@@ -0,0 +1,129 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use codec::{Codec, Reader, Writer};
+
+use super::ResponderContext;
+
+use crate::common::session::SpdmSessionState;
+use crate::common::SpdmCodec;
+use crate::error::{
+    SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER,
+    SPDM_STATUS_INVALID_STATE_LOCAL, SPDM_STATUS_UNSUPPORTED_CAP,
+};
+use crate::message::*;
+use crate::protocol::{SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags, SpdmVersion};
+
+fn encap_key_update_tag(key_update_operation: SpdmKeyUpdateOperation) -> u8 {
+    if key_update_operation == SpdmKeyUpdateOperation::SpdmVerifyNewKey {
+        2
+    } else {
+        1
+    }
+}
+
+impl<'a> ResponderContext<'a> {
+    /// Update the response direction key of the established session
+    /// `session_id`. The KEY_UPDATE goes out as encapsulated request on the
+    /// next GET_ENCAPSULATED_REQUEST the requester sends in that session, the
+    /// new key is used from the ENCAPSULATED_RESPONSE_ACK that follows its
+    /// KEY_UPDATE_ACK on. Other sessions are not affected.
+    pub fn start_encap_key_update(&mut self, session_id: u32) -> SpdmResult {
+        if self.common.negotiate_info.spdm_version_sel.get_u8()
+            < SpdmVersion::SpdmVersion11.get_u8()
+        {
+            return Err(SPDM_STATUS_UNSUPPORTED_CAP);
+        }
+        if !self.common.negotiate_info.req_capabilities_sel.contains(
+            SpdmRequestCapabilityFlags::ENCAP_CAP | SpdmRequestCapabilityFlags::KEY_UPD_CAP,
+        ) || !self.common.negotiate_info.rsp_capabilities_sel.contains(
+            SpdmResponseCapabilityFlags::ENCAP_CAP | SpdmResponseCapabilityFlags::KEY_UPD_CAP,
+        ) {
+            return Err(SPDM_STATUS_UNSUPPORTED_CAP);
+        }
+
+        let session = self
+            .common
+            .get_session_via_id(session_id)
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        if session.get_session_state() != SpdmSessionState::SpdmSessionEstablished {
+            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+        }
+        session.encap_key_update_op = Some(SpdmKeyUpdateOperation::SpdmUpdateSingleKey);
+        Ok(())
+    }
+
+    pub fn encode_encap_request_key_update(
+        &mut self,
+        key_update_operation: SpdmKeyUpdateOperation,
+        encap_request: &mut Writer,
+    ) -> SpdmResult {
+        let request = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmRequestKeyUpdate,
+            },
+            payload: SpdmMessagePayload::SpdmKeyUpdateRequest(SpdmKeyUpdateRequestPayload {
+                key_update_operation,
+                tag: encap_key_update_tag(key_update_operation),
+            }),
+        };
+
+        let _ = request.spdm_encode(&mut self.common, encap_request)?;
+
+        Ok(())
+    }
+
+    /// Returns whether another KEY_UPDATE has to be sent, i.e. the VerifyNewKey
+    /// after the UpdateKey has been acknowledged.
+    pub fn handle_encap_response_key_update(
+        &mut self,
+        session_id: u32,
+        encap_response: &[u8],
+    ) -> SpdmResult<bool> {
+        let key_update_operation = self
+            .common
+            .get_immutable_session_via_id(session_id)
+            .and_then(|session| session.encap_key_update_op)
+            .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+
+        let mut reader = Reader::init(encap_response);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(header) => {
+                if header.version != self.common.negotiate_info.spdm_version_sel {
+                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                }
+                if header.request_response_code != SpdmRequestResponseCode::SpdmResponseKeyUpdateAck
+                {
+                    return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+                }
+            }
+            None => return Err(SPDM_STATUS_INVALID_MSG_FIELD),
+        }
+
+        let key_update_rsp = SpdmKeyUpdateResponsePayload::spdm_read(&mut self.common, &mut reader)
+            .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+        debug!("!!! encap key_update rsp : {:02x?}\n", key_update_rsp);
+        if key_update_rsp.key_update_operation != key_update_operation
+            || key_update_rsp.tag != encap_key_update_tag(key_update_operation)
+        {
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+
+        let spdm_version_sel = self.common.negotiate_info.spdm_version_sel;
+        let session = self
+            .common
+            .get_session_via_id(session_id)
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        if key_update_operation == SpdmKeyUpdateOperation::SpdmUpdateSingleKey {
+            session.create_data_secret_update(spdm_version_sel, false, true)?;
+            session.encap_key_update_op = Some(SpdmKeyUpdateOperation::SpdmVerifyNewKey);
+            Ok(true)
+        } else {
+            session.activate_data_secret_update(spdm_version_sel, false, true, true)?;
+            session.encap_key_update_op = None;
+            Ok(false)
+        }
+    }
+}
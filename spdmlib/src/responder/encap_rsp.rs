@@ -5,7 +5,7 @@
 use codec::{Codec, Reader, Writer};
 
 use crate::{
-    common::{SpdmCodec, SpdmConnectionState},
+    common::{session::SpdmSessionState, SpdmCodec, SpdmConnectionState},
     config,
     error::{
         SpdmResult, SPDM_STATUS_BUFFER_FULL, SPDM_STATUS_INVALID_MSG_FIELD,
@@ -14,7 +14,8 @@ use crate::{
     message::{
         SpdmDeliverEncapsulatedResponsePayload, SpdmEncapsulatedRequestPayload,
         SpdmEncapsulatedResponseAckPayload, SpdmEncapsulatedResponseAckPayloadType, SpdmErrorCode,
-        SpdmMessage, SpdmMessageHeader, SpdmMessagePayload, SpdmRequestResponseCode,
+        SpdmKeyUpdateOperation, SpdmMessage, SpdmMessageHeader, SpdmMessagePayload,
+        SpdmRequestResponseCode,
     },
     protocol::{SpdmRequestCapabilityFlags, SpdmResponseCapabilityFlags, SpdmVersion},
 };
@@ -30,12 +31,12 @@ impl<'a> ResponderContext<'a> {
             SpdmRequestResponseCode::SpdmRequestGetEncapsulatedRequest.get_u8(),
             &mut writer,
         );
-        self.write_encap_request_response(bytes, &mut writer);
+        self.write_encap_request_response(session_id, bytes, &mut writer);
 
-        self.send_secured_message(session_id, writer.used_slice(), false)
+        self.send_handshake_message(session_id, writer.used_slice())
     }
 
-    fn write_encap_request_response(&mut self, bytes: &[u8], writer: &mut Writer) {
+    fn write_encap_request_response(&mut self, session_id: u32, bytes: &[u8], writer: &mut Writer) {
         let mut reader = Reader::init(bytes);
         if let Some(request_header) = SpdmMessageHeader::read(&mut reader) {
             if request_header.version != self.common.negotiate_info.spdm_version_sel {
@@ -67,9 +68,23 @@ impl<'a> ResponderContext<'a> {
             return;
         }
 
-        if self.encode_encap_request_get_digest(writer).is_err() {
+        // a pending key update only applies to an established session, the
+        // encapsulated flow of a handshake is mutual authentication.
+        let key_update_op = match self.common.get_immutable_session_via_id(session_id) {
+            Some(session)
+                if session.get_session_state() == SpdmSessionState::SpdmSessionEstablished =>
+            {
+                session.encap_key_update_op
+            }
+            _ => None,
+        };
+        let res = if let Some(key_update_op) = key_update_op {
+            self.encode_encap_request_key_update(key_update_op, writer)
+        } else {
+            self.encode_encap_request_get_digest(writer)
+        };
+        if res.is_err() {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidResponseCode, 0, writer);
-            return;
         }
     }
 
@@ -85,12 +100,17 @@ impl<'a> ResponderContext<'a> {
             SpdmRequestResponseCode::SpdmRequestGetEncapsulatedRequest.get_u8(),
             &mut writer,
         );
-        self.write_encap_response_ack_response(bytes, &mut writer);
+        self.write_encap_response_ack_response(session_id, bytes, &mut writer);
 
-        self.send_secured_message(session_id, writer.used_slice(), false)
+        self.send_handshake_message(session_id, writer.used_slice())
     }
 
-    fn write_encap_response_ack_response(&mut self, bytes: &[u8], writer: &mut Writer) {
+    fn write_encap_response_ack_response(
+        &mut self,
+        session_id: u32,
+        bytes: &[u8],
+        writer: &mut Writer,
+    ) {
         let mut reader = Reader::init(bytes);
         if let Some(request_header) = SpdmMessageHeader::read(&mut reader) {
             if request_header.version != self.common.negotiate_info.spdm_version_sel {
@@ -112,7 +132,12 @@ impl<'a> ResponderContext<'a> {
         };
 
         if self
-            .process_encapsulated_response(&encap_response_payload, &bytes[reader.used()..], writer)
+            .process_encapsulated_response(
+                session_id,
+                &encap_response_payload,
+                &bytes[reader.used()..],
+                writer,
+            )
             .is_err()
         {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidResponseCode, 0, writer);
@@ -157,6 +182,7 @@ impl<'a> ResponderContext<'a> {
 
     fn process_encapsulated_response(
         &mut self,
+        session_id: u32,
         encap_response_payload: &SpdmDeliverEncapsulatedResponsePayload,
         encap_response: &[u8],
         encap_response_ack: &mut Writer,
@@ -221,6 +247,19 @@ impl<'a> ResponderContext<'a> {
                     }
                 }
             }
+            SpdmRequestResponseCode::SpdmResponseKeyUpdateAck => {
+                if self.handle_encap_response_key_update(session_id, encap_response)? {
+                    let _ = ack_params.spdm_encode(&mut self.common, encap_response_ack)?;
+                    self.encode_encap_request_key_update(
+                        SpdmKeyUpdateOperation::SpdmVerifyNewKey,
+                        encap_response_ack,
+                    )
+                } else {
+                    ack_params.payload_type = SpdmEncapsulatedResponseAckPayloadType::Absent;
+                    let _ = ack_params.spdm_encode(&mut self.common, encap_response_ack)?;
+                    Ok(())
+                }
+            }
             _ => Err(SPDM_STATUS_UNSUPPORTED_CAP),
         }
     }
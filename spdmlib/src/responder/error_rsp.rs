@@ -7,6 +7,13 @@ use crate::error::SpdmResult;
 use crate::message::*;
 use crate::responder::*;
 
+/// ErrorCode and ErrorData of an ERROR response the responder sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpdmResponderErrorState {
+    pub error_code: SpdmErrorCode,
+    pub error_data: u8,
+}
+
 impl<'a> ResponderContext<'a> {
     pub fn write_spdm_error(
         &mut self,
@@ -14,6 +21,10 @@ impl<'a> ResponderContext<'a> {
         error_data: u8,
         writer: &mut Writer,
     ) {
+        self.last_error = Some(SpdmResponderErrorState {
+            error_code,
+            error_data,
+        });
         let error = SpdmMessage {
             header: SpdmMessageHeader {
                 version: self.common.negotiate_info.spdm_version_sel,
@@ -40,6 +51,17 @@ impl<'a> ResponderContext<'a> {
 }
 
 impl<'a> ResponderContext<'a> {
+    /// The last ERROR the responder answered with, latched until
+    /// `clear_error_state`. The responder keeps serving requests while an
+    /// error is latched.
+    pub fn last_error(&self) -> Option<SpdmResponderErrorState> {
+        self.last_error
+    }
+
+    pub fn clear_error_state(&mut self) {
+        self.last_error = None;
+    }
+
     pub fn handle_error_request(
         &mut self,
         error_code: SpdmErrorCode,
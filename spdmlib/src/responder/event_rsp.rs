@@ -0,0 +1,237 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::SpdmCodec;
+use crate::error::{
+    SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER,
+    SPDM_STATUS_INVALID_STATE_LOCAL, SPDM_STATUS_UNSUPPORTED_CAP,
+};
+use crate::message::*;
+use crate::protocol::*;
+use crate::responder::*;
+
+impl<'a> ResponderContext<'a> {
+    pub fn handle_spdm_get_supported_event_types(
+        &mut self,
+        session_id: u32,
+        bytes: &[u8],
+    ) -> SpdmResult {
+        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut send_buffer);
+        self.write_spdm_supported_event_types_response(session_id, bytes, &mut writer);
+        self.send_secured_message(session_id, writer.used_slice(), false)
+    }
+
+    pub fn write_spdm_supported_event_types_response(
+        &mut self,
+        session_id: u32,
+        bytes: &[u8],
+        writer: &mut Writer,
+    ) {
+        let mut reader = Reader::init(bytes);
+        let message_header = SpdmMessageHeader::read(&mut reader);
+        if let Some(message_header) = message_header {
+            if message_header.version != self.common.negotiate_info.spdm_version_sel {
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorVersionMismatch, 0, writer);
+                return;
+            }
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+
+        if !self.is_event_cap_negotiated() {
+            self.write_spdm_error(
+                SpdmErrorCode::SpdmErrorUnsupportedRequest,
+                SpdmRequestResponseCode::SpdmRequestGetSupportedEventTypes.get_u8(),
+                writer,
+            );
+            return;
+        }
+
+        self.common.reset_buffer_via_request_code(
+            SpdmRequestResponseCode::SpdmRequestGetSupportedEventTypes,
+            Some(session_id),
+        );
+
+        let get_supported_event_types =
+            SpdmGetSupportedEventTypesRequestPayload::spdm_read(&mut self.common, &mut reader);
+        if let Some(get_supported_event_types) = get_supported_event_types {
+            debug!(
+                "!!! get supported event types : {:02x?}\n",
+                get_supported_event_types
+            );
+        } else {
+            error!("!!! get supported event types : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+
+        info!("send spdm supported event types\n");
+
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseSupportedEventTypes,
+            },
+            payload: SpdmMessagePayload::SpdmSupportedEventTypesResponse(
+                self.common.supported_event_groups.clone(),
+            ),
+        };
+        let res = response.spdm_encode(&mut self.common, writer);
+        if res.is_err() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+        }
+    }
+
+    pub fn handle_spdm_subscribe_event_types(
+        &mut self,
+        session_id: u32,
+        bytes: &[u8],
+    ) -> SpdmResult {
+        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut send_buffer);
+        self.write_spdm_subscribe_event_types_response(session_id, bytes, &mut writer);
+        self.send_secured_message(session_id, writer.used_slice(), false)
+    }
+
+    pub fn write_spdm_subscribe_event_types_response(
+        &mut self,
+        session_id: u32,
+        bytes: &[u8],
+        writer: &mut Writer,
+    ) {
+        let mut reader = Reader::init(bytes);
+        let message_header = SpdmMessageHeader::read(&mut reader);
+        if let Some(message_header) = message_header {
+            if message_header.version != self.common.negotiate_info.spdm_version_sel {
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorVersionMismatch, 0, writer);
+                return;
+            }
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+
+        if !self.is_event_cap_negotiated() {
+            self.write_spdm_error(
+                SpdmErrorCode::SpdmErrorUnsupportedRequest,
+                SpdmRequestResponseCode::SpdmRequestSubscribeEventTypes.get_u8(),
+                writer,
+            );
+            return;
+        }
+
+        self.common.reset_buffer_via_request_code(
+            SpdmRequestResponseCode::SpdmRequestSubscribeEventTypes,
+            Some(session_id),
+        );
+
+        let subscribe_event_types =
+            SpdmSubscribeEventTypesRequestPayload::spdm_read(&mut self.common, &mut reader);
+        let subscribe_event_types = if let Some(subscribe_event_types) = subscribe_event_types {
+            debug!(
+                "!!! subscribe event types : {:02x?}\n",
+                subscribe_event_types.subscribe_list.as_slice()
+            );
+            subscribe_event_types
+        } else {
+            error!("!!! subscribe event types : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        };
+
+        let session = if let Some(session) = self.common.get_session_via_id(session_id) {
+            session
+        } else {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+            return;
+        };
+        session.event_subscription = if subscribe_event_types.subscribe_event_group_count == 0 {
+            None
+        } else {
+            Some(subscribe_event_types.subscribe_list)
+        };
+
+        info!("send spdm subscribe event types ack\n");
+
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseSubscribeEventTypesAck,
+            },
+            payload: SpdmMessagePayload::SpdmSubscribeEventTypesAckResponse(
+                SpdmSubscribeEventTypesAckResponsePayload {},
+            ),
+        };
+        let res = response.spdm_encode(&mut self.common, writer);
+        if res.is_err() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+        }
+    }
+
+    /// Deliver events to a requester that has subscribed within the session.
+    /// The EVENT_ACK reply is consumed by the next `process_message`.
+    pub fn send_spdm_event(
+        &mut self,
+        session_id: u32,
+        event_count: u32,
+        event_list: &[u8],
+    ) -> SpdmResult {
+        info!("send spdm event\n");
+
+        if !self.is_event_cap_negotiated() {
+            return Err(SPDM_STATUS_UNSUPPORTED_CAP);
+        }
+        let session = self
+            .common
+            .get_immutable_session_via_id(session_id)
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        if session.event_subscription.is_none() {
+            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+        }
+
+        let event_list =
+            SpdmEventListStruct::from_slice(event_list).ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut send_buffer);
+        let request = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: self.common.negotiate_info.spdm_version_sel,
+                request_response_code: SpdmRequestResponseCode::SpdmRequestSendEvent,
+            },
+            payload: SpdmMessagePayload::SpdmSendEventRequest(SpdmSendEventRequestPayload {
+                event_count,
+                event_list,
+            }),
+        };
+        let used = request.spdm_encode(&mut self.common, &mut writer)?;
+        self.send_secured_message(session_id, &send_buffer[..used], false)
+    }
+
+    pub fn handle_spdm_event_ack(&mut self, session_id: u32, bytes: &[u8]) -> SpdmResult {
+        let mut reader = Reader::init(bytes);
+        let message_header =
+            SpdmMessageHeader::read(&mut reader).ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+        if message_header.version != self.common.negotiate_info.spdm_version_sel {
+            return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+        }
+        let event_ack = SpdmEventAckResponsePayload::spdm_read(&mut self.common, &mut reader)
+            .ok_or(SPDM_STATUS_INVALID_MSG_FIELD)?;
+        debug!(
+            "!!! event ack : session {:08x} {:02x?}\n",
+            session_id, event_ack
+        );
+        Ok(())
+    }
+
+    fn is_event_cap_negotiated(&self) -> bool {
+        self.common.negotiate_info.spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion13.get_u8()
+            && self
+                .common
+                .negotiate_info
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::EVENT_CAP)
+    }
+}
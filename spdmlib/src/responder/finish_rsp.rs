@@ -18,26 +18,10 @@ use alloc::boxed::Box;
 
 impl<'a> ResponderContext<'a> {
     pub fn handle_spdm_finish(&mut self, session_id: u32, bytes: &[u8]) -> SpdmResult {
-        let in_clear_text = self
-            .common
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && self
-                .common
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
-        info!("in_clear_text {:?}\n", in_clear_text);
-
         let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
         let mut writer = Writer::init(&mut send_buffer);
         self.write_spdm_finish_response(session_id, bytes, &mut writer)?;
-        if in_clear_text {
-            self.send_message(writer.used_slice())
-        } else {
-            self.send_secured_message(session_id, writer.used_slice(), false)
-        }
+        self.send_handshake_message(session_id, writer.used_slice())
     }
 
     // Return true on success, false otherwise.
@@ -74,6 +58,26 @@ impl<'a> ResponderContext<'a> {
         }
         let finish_req = finish_req.unwrap();
 
+        // without the encapsulated flow the requester picks its cert chain
+        // in FINISH param2.
+        if self
+            .common
+            .get_immutable_session_via_id(session_id)
+            .unwrap()
+            .get_mut_auth_requested()
+            == SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ
+        {
+            if finish_req.req_slot_id >= SPDM_MAX_SLOT_NUMBER as u8
+                || self.common.peer_info.peer_cert_chain[finish_req.req_slot_id as usize].is_none()
+            {
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+                return Err(SPDM_STATUS_INVALID_MSG_FIELD);
+            }
+            self.common
+                .runtime_info
+                .set_peer_used_cert_chain_slot_id(finish_req.req_slot_id);
+        }
+
         if self
             .common
             .append_message_f(false, session_id, &bytes[..4])
@@ -90,6 +94,13 @@ impl<'a> ResponderContext<'a> {
             .get_mut_auth_requested();
         let finish_request_attributes = finish_req.finish_request_attributes;
 
+        // a session set up before the policy was turned on.
+        if self.common.config_info.require_mut_auth && mut_auth_attributes.is_empty() {
+            error!("!!! finish : requester not authenticated !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return Err(SPDM_STATUS_INVALID_STATE_PEER);
+        }
+
         if (!mut_auth_attributes.is_empty()
             && !finish_request_attributes.contains(SpdmFinishRequestAttributes::SIGNATURE_INCLUDED))
             || (mut_auth_attributes.is_empty()
@@ -179,16 +190,7 @@ impl<'a> ResponderContext<'a> {
             }
         }
 
-        let in_clear_text = self
-            .common
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && self
-                .common
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+        let in_clear_text = self.common.negotiate_info.handshake_in_the_clear();
 
         info!("send spdm finish rsp\n");
 
@@ -306,14 +308,11 @@ impl<'a> ResponderContext<'a> {
                 .calc_rsp_transcript_hash(false, session.get_slot_id(), true, session)?;
 
         let peer_slot_id = self.common.runtime_info.get_peer_used_cert_chain_slot_id();
-        let peer_cert = &self.common.peer_info.peer_cert_chain[peer_slot_id as usize]
+        let peer_cert = self.common.peer_info.peer_cert_chain[peer_slot_id as usize]
             .as_ref()
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-            .data[(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-            ..(self.common.peer_info.peer_cert_chain[peer_slot_id as usize]
-                .as_ref()
-                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-                .data_size as usize)];
+            .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+            .ok_or(SPDM_STATUS_INVALID_CERT)?;
         let mut transcript_sign = ManagedBuffer12Sign::default();
         if self.common.negotiate_info.spdm_version_sel.get_u8()
             >= SpdmVersion::SpdmVersion12.get_u8()
@@ -353,14 +352,11 @@ impl<'a> ResponderContext<'a> {
                 .calc_rsp_transcript_hash(false, session.get_slot_id(), true, session)?;
 
         let peer_slot_id = self.common.runtime_info.get_peer_used_cert_chain_slot_id();
-        let peer_cert = &self.common.peer_info.peer_cert_chain[peer_slot_id as usize]
+        let peer_cert = self.common.peer_info.peer_cert_chain[peer_slot_id as usize]
             .as_ref()
             .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-            .data[(4usize + self.common.negotiate_info.base_hash_sel.get_size() as usize)
-            ..(self.common.peer_info.peer_cert_chain[peer_slot_id as usize]
-                .as_ref()
-                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?
-                .data_size as usize)];
+            .der_cert_chain(self.common.negotiate_info.base_hash_sel)
+            .ok_or(SPDM_STATUS_INVALID_CERT)?;
 
         let mut transcript_hash_sign = ManagedBuffer12Sign::default();
         if self.common.negotiate_info.spdm_version_sel.get_u8()
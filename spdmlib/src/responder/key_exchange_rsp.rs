@@ -56,6 +56,12 @@ impl<'a> ResponderContext<'a> {
             return Err(SPDM_STATUS_INVALID_MSG_FIELD);
         }
 
+        if self.common.config_info.require_mut_auth && !self.mut_auth_negotiated() {
+            error!("!!! key_exchange : requester cannot authenticate !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return Err(SPDM_STATUS_INVALID_STATE_PEER);
+        }
+
         self.common
             .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestKeyExchange, None);
 
@@ -199,6 +205,12 @@ impl<'a> ResponderContext<'a> {
         let final_key = final_key.unwrap();
         debug!("!!! final_key : {:02x?}\n", final_key.as_ref());
 
+        if self.common.reserve_session().is_err() {
+            error!("!!! session limit reached : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorSessionLimitExceeded, 0, writer);
+            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+        }
+
         let rsp_session_id = self.common.get_next_half_session_id(false);
         if rsp_session_id.is_err() {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorSessionLimitExceeded, 0, writer);
@@ -222,6 +234,17 @@ impl<'a> ResponderContext<'a> {
             return Err(SPDM_STATUS_INVALID_MSG_FIELD);
         }
 
+        // basic mutual auth skips the encapsulated flow, the requester cert
+        // chain must then be provisioned with provision_requester_cert_chain.
+        #[cfg(feature = "mut-auth")]
+        let mut_auth_req = if self.common.config_info.basic_mut_auth_requested {
+            SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ
+        } else {
+            SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ_WITH_GET_DIGESTS
+        };
+        #[cfg(not(feature = "mut-auth"))]
+        let mut_auth_req = SpdmKeyExchangeMutAuthAttributes::empty();
+
         let session = self.common.get_next_avaiable_session();
         if session.is_none() {
             error!("!!! too many sessions : fail !!!\n");
@@ -229,11 +252,6 @@ impl<'a> ResponderContext<'a> {
             return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
         }
 
-        #[cfg(feature = "mut-auth")]
-        let mut_auth_req = SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ_WITH_GET_DIGESTS;
-        #[cfg(not(feature = "mut-auth"))]
-        let mut_auth_req = SpdmKeyExchangeMutAuthAttributes::empty();
-
         let session = session.unwrap();
         let session_id = ((rsp_session_id as u32) << 16) + key_exchange_req.req_session_id as u32;
         session.setup(session_id).unwrap();
@@ -250,6 +268,7 @@ impl<'a> ResponderContext<'a> {
         session.runtime_info.message_a = message_a;
         session.runtime_info.rsp_cert_hash = cert_chain_hash;
         session.runtime_info.req_cert_hash = None;
+        self.common.touch_session(session_id);
 
         let mut random = [0u8; SPDM_RANDOM_SIZE];
         let res = crypto::rand::get_random(&mut random);
@@ -258,16 +277,7 @@ impl<'a> ResponderContext<'a> {
             return Err(SPDM_STATUS_CRYPTO_ERROR);
         }
 
-        let in_clear_text = self
-            .common
-            .negotiate_info
-            .req_capabilities_sel
-            .contains(SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP)
-            && self
-                .common
-                .negotiate_info
-                .rsp_capabilities_sel
-                .contains(SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP);
+        let in_clear_text = self.common.negotiate_info.handshake_in_the_clear();
         info!("in_clear_text {:?}\n", in_clear_text);
 
         info!("send spdm key_exchange rsp\n");
@@ -410,12 +420,13 @@ impl<'a> ResponderContext<'a> {
             }
 
             // patch the message before send
-            writer.mut_used_slice()
-                [(used - base_hash_size - base_asym_size)..(used - base_hash_size)]
-                .copy_from_slice(signature.as_ref());
             writer.mut_used_slice()[(used - base_hash_size)..used].copy_from_slice(hmac.as_ref());
         }
 
+        // the signature goes in both with and without verify_data.
+        writer.mut_used_slice()[temp_used..(temp_used + base_asym_size)]
+            .copy_from_slice(signature.as_ref());
+
         let heartbeat_period = self.common.config_info.heartbeat_period;
         let session = self.common.get_session_via_id(session_id).unwrap();
 
@@ -523,4 +534,25 @@ impl<'a> ResponderContext<'a> {
         )
         .ok_or(SPDM_STATUS_CRYPTO_ERROR)
     }
+
+    // KEY_EXCHANGE_RSP always asks for mutual auth once it is compiled in,
+    // it only works if both sides negotiated MUT_AUTH_CAP.
+    #[cfg(feature = "mut-auth")]
+    fn mut_auth_negotiated(&self) -> bool {
+        self.common
+            .negotiate_info
+            .req_capabilities_sel
+            .contains(SpdmRequestCapabilityFlags::MUT_AUTH_CAP)
+            && self
+                .common
+                .negotiate_info
+                .rsp_capabilities_sel
+                .contains(SpdmResponseCapabilityFlags::MUT_AUTH_CAP)
+            && !self.common.negotiate_info.req_asym_sel.is_empty()
+    }
+
+    #[cfg(not(feature = "mut-auth"))]
+    fn mut_auth_negotiated(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,48 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use conquer_once::spin::OnceCell;
+
+use crate::responder::ResponderContext;
+
+/// Decides per measurement index whether GET_MEASUREMENTS may disclose it.
+///
+/// `session_id` is None for a request outside a session; the context carries
+/// the rest of the connection and session state (for example whether the
+/// requester was mutually authenticated). A refused index is answered with
+/// ERROR(InvalidRequest) when requested alone and omitted from a request
+/// for all blocks.
+#[derive(Clone)]
+pub struct SpdmMeasurementAuthorization {
+    pub authorize_measurement_cb:
+        fn(ctx: &ResponderContext, session_id: Option<u32>, measurement_index: u8) -> bool,
+}
+
+static SPDM_MEASUREMENT_AUTHORIZATION: OnceCell<SpdmMeasurementAuthorization> = OnceCell::uninit();
+
+static DEFAULT: SpdmMeasurementAuthorization = SpdmMeasurementAuthorization {
+    authorize_measurement_cb: |_ctx: &ResponderContext,
+                               _session_id: Option<u32>,
+                               _measurement_index: u8|
+     -> bool { true },
+};
+
+pub fn register(context: SpdmMeasurementAuthorization) -> bool {
+    SPDM_MEASUREMENT_AUTHORIZATION
+        .try_init_once(|| context)
+        .is_ok()
+}
+
+pub fn authorize_measurement(
+    ctx: &ResponderContext,
+    session_id: Option<u32>,
+    measurement_index: u8,
+) -> bool {
+    // unlike try_get_or_init, a lookup before registration leaves the cell
+    // open for a later register.
+    (SPDM_MEASUREMENT_AUTHORIZATION
+        .try_get()
+        .unwrap_or(&DEFAULT)
+        .authorize_measurement_cb)(ctx, session_id, measurement_index)
+}
@@ -14,7 +14,6 @@ use crate::crypto;
 use crate::error::SpdmResult;
 use crate::error::SPDM_STATUS_BUFFER_FULL;
 use crate::error::SPDM_STATUS_CRYPTO_ERROR;
-#[cfg(not(feature = "hashed-transcript-data"))]
 use crate::error::SPDM_STATUS_INVALID_PARAMETER;
 #[cfg(feature = "hashed-transcript-data")]
 use crate::error::SPDM_STATUS_INVALID_STATE_LOCAL;
@@ -22,12 +21,40 @@ use crate::message::*;
 use crate::protocol::*;
 use crate::responder::*;
 use crate::secret;
+use codec::u24;
 
 impl<'a> ResponderContext<'a> {
+    /// Content changed state reported in Param2 of the next signed
+    /// MEASUREMENTS responses, e.g. DETECTED_CHANGE once the measurements were updated
+    /// and NO_CHANGE again after the requester fetched them. It is only
+    /// reported from SPDM 1.2 on with `runtime_content_change_support`,
+    /// and kept until the runtime info is reset, e.g. by GET_VERSION.
+    ///
+    /// SPDM_STATUS_INVALID_PARAMETER for anything but DETECTED_CHANGE or
+    /// NO_CHANGE.
+    pub fn set_measurement_content_changed(
+        &mut self,
+        content_changed: SpdmMeasurementContentChanged,
+    ) -> SpdmResult {
+        if content_changed != SpdmMeasurementContentChanged::DETECTED_CHANGE
+            && content_changed != SpdmMeasurementContentChanged::NO_CHANGE
+        {
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        self.common.runtime_info.content_changed = content_changed;
+        Ok(())
+    }
+
     pub fn handle_spdm_measurement(&mut self, session_id: Option<u32>, bytes: &[u8]) -> SpdmResult {
         let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
         let mut writer = Writer::init(&mut send_buffer);
         self.write_spdm_measurement_response(session_id, bytes, &mut writer);
+        // an ERROR ends the exchanges covered by L1/L2, so that a signature
+        // requested after a failed one does not cover it.
+        if writer.used_slice().get(1) == Some(&SpdmRequestResponseCode::SpdmResponseError.get_u8())
+        {
+            self.common.reset_message_m(session_id);
+        }
         match session_id {
             None => self.send_message(writer.used_slice()),
             Some(session_id) => self.send_secured_message(session_id, writer.used_slice(), false),
@@ -91,6 +118,13 @@ impl<'a> ResponderContext<'a> {
                 self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
                 return;
             }
+
+            if session_id.is_none()
+                && self.common.config_info.serve_cached_measurements
+                && self.write_cached_measurement_response(&get_measurements, writer)
+            {
+                return;
+            }
         } else {
             self.common.runtime_info.need_measurement_signature = false;
 
@@ -126,7 +160,7 @@ impl<'a> ResponderContext<'a> {
         .unwrap()
         .number_of_blocks;
 
-        let number_of_measurement: u8 = if get_measurements.measurement_operation
+        let mut number_of_measurement: u8 = if get_measurements.measurement_operation
             == SpdmMeasurementOperation::SpdmMeasurementRequestAll
             || get_measurements.measurement_operation
                 == SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber
@@ -138,31 +172,71 @@ impl<'a> ResponderContext<'a> {
         let measurement_record = if get_measurements.measurement_operation
             == SpdmMeasurementOperation::SpdmMeasurementRequestAll
         {
-            secret::measurement::measurement_collection(
+            let measurement_record = secret::measurement::measurement_collection(
                 spdm_version_sel,
                 measurement_specification_sel,
                 measurement_hash_sel,
                 SpdmMeasurementOperation::SpdmMeasurementRequestAll.get_u8() as usize,
             )
-            .unwrap()
+            .unwrap();
+            match self.authorized_measurement_record(session_id, &measurement_record) {
+                Some(measurement_record) => measurement_record,
+                None => {
+                    self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+                    return;
+                }
+            }
         } else if let SpdmMeasurementOperation::Unknown(index) =
             get_measurements.measurement_operation
         {
-            if index > real_measurement_block_count {
+            // the device mode block sits at its reserved index, outside the
+            // range counted by number_of_blocks.
+            if (index > real_measurement_block_count && index != SPDM_MEASUREMENT_INDEX_DEVICE_MODE)
+                || !measurement_authorization::authorize_measurement(self, session_id, index)
+            {
                 self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
                 return;
             }
-            secret::measurement::measurement_collection(
+            match secret::measurement::measurement_collection(
                 spdm_version_sel,
                 measurement_specification_sel,
                 measurement_hash_sel,
                 index as usize,
-            )
-            .unwrap()
+            ) {
+                Some(measurement_record) if measurement_record.number_of_blocks != 0 => {
+                    measurement_record
+                }
+                // e.g. a device without device mode block.
+                _ => {
+                    self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+                    return;
+                }
+            }
         } else {
             SpdmMeasurementRecordStructure::default()
         };
 
+        // the raw bit stream of a single index is only there if the
+        // collection reports it raw, a digest does not give it back.
+        if matches!(
+            get_measurements.measurement_operation,
+            SpdmMeasurementOperation::Unknown(_)
+        ) && get_measurements
+            .measurement_attributes
+            .contains(SpdmMeasurementAttributes::RAW_BIT_STREAM_REQUESTED)
+            && !is_raw_bit_stream_record(&measurement_record)
+        {
+            error!("!!! get_measurements : no raw bit stream for the index !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0, writer);
+            return;
+        }
+
+        if get_measurements.measurement_operation
+            == SpdmMeasurementOperation::SpdmMeasurementRequestAll
+        {
+            number_of_measurement = measurement_record.number_of_blocks;
+        }
+
         let content_changed = if runtime_content_change_support
             && (spdm_version_sel.get_u8() >= SpdmVersion::SpdmVersion12.get_u8())
         {
@@ -180,28 +254,29 @@ impl<'a> ResponderContext<'a> {
 
         info!("send spdm measurement\n");
 
+        let measurements = SpdmMeasurementsResponsePayload {
+            number_of_measurement,
+            slot_id: get_measurements.slot_id,
+            content_changed,
+            measurement_record,
+            nonce: SpdmNonceStruct { data: nonce },
+            opaque: SpdmOpaqueStruct {
+                data_size: 0,
+                data: [0u8; MAX_SPDM_OPAQUE_SIZE],
+            },
+            signature: SpdmSignatureStruct {
+                data_size: signature_size,
+                data: [0x60u8; SPDM_MAX_ASYM_KEY_SIZE],
+            },
+        };
+        let signature_offset = measurements.signature_offset();
+        let response_start = writer.used();
         let response = SpdmMessage {
             header: SpdmMessageHeader {
                 version: spdm_version_sel,
                 request_response_code: SpdmRequestResponseCode::SpdmResponseMeasurements,
             },
-            payload: SpdmMessagePayload::SpdmMeasurementsResponse(
-                SpdmMeasurementsResponsePayload {
-                    number_of_measurement,
-                    slot_id: get_measurements.slot_id,
-                    content_changed,
-                    measurement_record,
-                    nonce: SpdmNonceStruct { data: nonce },
-                    opaque: SpdmOpaqueStruct {
-                        data_size: 0,
-                        data: [0u8; MAX_SPDM_OPAQUE_SIZE],
-                    },
-                    signature: SpdmSignatureStruct {
-                        data_size: signature_size,
-                        data: [0x60u8; SPDM_MAX_ASYM_KEY_SIZE],
-                    },
-                },
-            ),
+            payload: SpdmMessagePayload::SpdmMeasurementsResponse(measurements),
         };
 
         let res = response.spdm_encode(&mut self.common, writer);
@@ -217,11 +292,16 @@ impl<'a> ResponderContext<'a> {
             .contains(SpdmMeasurementAttributes::SIGNATURE_REQUESTED)
         {
             let base_asym_size = base_asym_sel.get_size() as usize;
-            let temp_used = used - base_asym_size;
+            // the header is two bytes: SPDMVersion and RequestResponseCode.
+            let signature_offset = response_start + 2 + signature_offset;
+            if signature_offset + base_asym_size != used {
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+                return;
+            }
 
             if self
                 .common
-                .append_message_m(session_id, &writer.used_slice()[..temp_used])
+                .append_message_m(session_id, &writer.used_slice()[..signature_offset])
                 .is_err()
             {
                 self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
@@ -235,8 +315,7 @@ impl<'a> ResponderContext<'a> {
             }
             let signature = signature.unwrap();
             // patch the message before send
-            writer.mut_used_slice()[(used - base_asym_size)..used]
-                .copy_from_slice(signature.as_ref());
+            writer.mut_used_slice()[signature_offset..used].copy_from_slice(signature.as_ref());
 
             self.common.reset_message_m(session_id);
         } else if self
@@ -248,34 +327,79 @@ impl<'a> ResponderContext<'a> {
         }
     }
 
+    // answer with the pre-signed response of the cached_measurements
+    // callback, if it has one. Its signature covers none of L1/L2, which
+    // starts over after it.
+    fn write_cached_measurement_response(
+        &mut self,
+        get_measurements: &SpdmGetMeasurementsRequestPayload,
+        writer: &mut Writer,
+    ) -> bool {
+        let mut response = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let used =
+            match cached_measurements::cached_measurements(self, get_measurements, &mut response) {
+                Some(used) => used.min(response.len()),
+                None => return false,
+            };
+
+        info!("send cached spdm measurement\n");
+        self.common.reset_message_m(None);
+        let mut reader = Reader::init(&response[..used]);
+        match SpdmMessageHeader::read(&mut reader) {
+            Some(header)
+                if header.version == self.common.negotiate_info.spdm_version_sel
+                    && header.request_response_code
+                        == SpdmRequestResponseCode::SpdmResponseMeasurements => {}
+            _ => {
+                error!("!!! cached measurements : invalid response !!!\n");
+                self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+                return true;
+            }
+        }
+        if writer.extend_from_slice(&response[..used]).is_none() {
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
+        }
+        true
+    }
+
+    // keep only the blocks of a RequestAll record the authorization
+    // callback lets us disclose.
+    fn authorized_measurement_record(
+        &self,
+        session_id: Option<u32>,
+        measurement_record: &SpdmMeasurementRecordStructure,
+    ) -> Option<SpdmMeasurementRecordStructure> {
+        let mut reader = Reader::init(
+            &measurement_record.measurement_record_data
+                [..measurement_record.measurement_record_length.get() as usize],
+        );
+        let mut authorized_record = SpdmMeasurementRecordStructure::default();
+        let mut writer = Writer::init(&mut authorized_record.measurement_record_data);
+        let mut number_of_blocks = 0u8;
+        for _ in 0..measurement_record.number_of_blocks {
+            let measurement_block = SpdmMeasurementBlockStructure::read(&mut reader)?;
+            if measurement_authorization::authorize_measurement(
+                self,
+                session_id,
+                measurement_block.index,
+            ) {
+                measurement_block.encode(&mut writer).ok()?;
+                number_of_blocks += 1;
+            }
+        }
+        let used = writer.used();
+
+        authorized_record.number_of_blocks = number_of_blocks;
+        authorized_record.measurement_record_length = u24::new(used as u32);
+        Some(authorized_record)
+    }
+
     #[cfg(feature = "hashed-transcript-data")]
     pub fn generate_measurement_signature(
         &self,
         session_id: Option<u32>,
     ) -> SpdmResult<SpdmSignatureStruct> {
-        let message_l1l2_hash = match session_id {
-            Some(session_id) => crypto::hash::hash_ctx_finalize(
-                self.common
-                    .get_immutable_session_via_id(session_id)
-                    .unwrap()
-                    .runtime_info
-                    .digest_context_l1l2
-                    .as_ref()
-                    .cloned()
-                    .unwrap(),
-            )
-            .ok_or(SPDM_STATUS_CRYPTO_ERROR)?,
-            None => crypto::hash::hash_ctx_finalize(
-                self.common
-                    .runtime_info
-                    .digest_context_l1l2
-                    .as_ref()
-                    .cloned()
-                    .unwrap(),
-            )
-            .ok_or(SPDM_STATUS_CRYPTO_ERROR)?,
-        };
-        debug!("message_l1l2_hash - {:02x?}", message_l1l2_hash.as_ref());
+        let message_l1l2_hash = self.common.calc_message_l1l2_hash(session_id)?;
 
         let mut message_sign = ManagedBuffer12Sign::default();
 
@@ -313,43 +437,15 @@ impl<'a> ResponderContext<'a> {
         &self,
         session_id: Option<u32>,
     ) -> SpdmResult<SpdmSignatureStruct> {
-        let mut message_l1l2 = ManagedBufferL1L2::default();
-        if self.common.negotiate_info.spdm_version_sel.get_u8()
+        // see `SpdmTranscriptStorage` for the transcript SPDM 1.0/1.1 sign.
+        let message_l1l2_hash = self.common.calc_message_l1l2_hash(session_id)?;
+        let mut message_l1l2 = if self.common.negotiate_info.spdm_version_sel.get_u8()
             >= SpdmVersion::SpdmVersion12.get_u8()
         {
-            let message_a = self.common.runtime_info.message_a.clone();
-            message_l1l2
-                .append_message(message_a.as_ref())
-                .map_or_else(|| Err(SPDM_STATUS_BUFFER_FULL), |_| Ok(()))?;
-        }
-
-        match session_id {
-            None => {
-                message_l1l2
-                    .append_message(self.common.runtime_info.message_m.as_ref())
-                    .ok_or(SPDM_STATUS_BUFFER_FULL)?;
-            }
-            Some(session_id) => {
-                let session = if let Some(s) = self.common.get_immutable_session_via_id(session_id)
-                {
-                    s
-                } else {
-                    return Err(SPDM_STATUS_INVALID_PARAMETER);
-                };
-                message_l1l2
-                    .append_message(session.runtime_info.message_m.as_ref())
-                    .ok_or(SPDM_STATUS_BUFFER_FULL)?;
-            }
-        }
-        // we dont need create message hash for verify
-        // we just print message hash for debug purpose
-        let message_l1l2_hash = crypto::hash::hash_all(
-            self.common.negotiate_info.base_hash_sel,
-            message_l1l2.as_ref(),
-        )
-        .ok_or(SPDM_STATUS_CRYPTO_ERROR)?;
-
-        debug!("message_l1l2_hash - {:02x?}", message_l1l2_hash.as_ref());
+            ManagedBufferL1L2::default()
+        } else {
+            self.common.calc_message_l1l2_data(session_id)?
+        };
 
         if self.common.negotiate_info.spdm_version_sel.get_u8()
             >= SpdmVersion::SpdmVersion12.get_u8()
@@ -377,3 +473,14 @@ impl<'a> ResponderContext<'a> {
         .ok_or(SPDM_STATUS_CRYPTO_ERROR)
     }
 }
+
+// every block of the record in raw bit stream representation.
+fn is_raw_bit_stream_record(measurement_record: &SpdmMeasurementRecordStructure) -> bool {
+    match SpdmMeasurementRecordView::from_record_structure(measurement_record) {
+        Some(view) => view.blocks().all(|block| {
+            matches!(block, Ok(block) if block.representation()
+                == SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementRawBit)
+        }),
+        None => false,
+    }
+}
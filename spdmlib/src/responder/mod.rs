@@ -2,20 +2,25 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
+mod builder;
 mod context;
 
 mod algorithm_rsp;
 mod capability_rsp;
 mod certificate_rsp;
 mod challenge_rsp;
+mod chunk_rsp;
 mod digest_rsp;
 #[cfg(feature = "mut-auth")]
 mod encap_get_certificate;
 #[cfg(feature = "mut-auth")]
 mod encap_get_digest;
 #[cfg(feature = "mut-auth")]
+mod encap_key_update;
+#[cfg(feature = "mut-auth")]
 mod encap_rsp;
 mod end_session_rsp;
+mod event_rsp;
 mod finish_rsp;
 mod heartbeat_rsp;
 mod key_exchange_rsp;
@@ -29,8 +34,12 @@ mod error_rsp;
 mod vendor_rsp;
 
 pub mod app_message_handler;
+pub mod cached_measurements;
+pub mod measurement_authorization;
 
+pub use builder::ResponderBuilder;
 pub use context::ResponderContext;
+pub use error_rsp::SpdmResponderErrorState;
 
 use crate::config;
 use codec::{Codec, Reader, Writer};
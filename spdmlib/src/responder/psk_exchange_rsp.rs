@@ -170,14 +170,20 @@ impl<'a> ResponderContext<'a> {
             MAX_SPDM_PSK_CONTEXT_SIZE as u16
         };
         let mut psk_context = [0u8; MAX_SPDM_PSK_CONTEXT_SIZE];
-        if psk_without_context {
-            let res = crypto::rand::get_random(&mut psk_context);
+        if !psk_without_context {
+            let res = crypto::rand::get_random(&mut psk_context[..psk_context_size as usize]);
             if res.is_err() {
                 self.write_spdm_error(SpdmErrorCode::SpdmErrorUnspecified, 0, writer);
                 return Err(SPDM_STATUS_CRYPTO_ERROR);
             }
         }
 
+        if self.common.reserve_session().is_err() {
+            error!("!!! session limit reached : fail !!!\n");
+            self.write_spdm_error(SpdmErrorCode::SpdmErrorSessionLimitExceeded, 0, writer);
+            return Err(SPDM_STATUS_INVALID_STATE_LOCAL);
+        }
+
         let rsp_session_id = self.common.get_next_half_session_id(false);
         if rsp_session_id.is_err() {
             self.write_spdm_error(SpdmErrorCode::SpdmErrorSessionLimitExceeded, 0, writer);
@@ -216,6 +222,7 @@ impl<'a> ResponderContext<'a> {
         session.runtime_info.message_a = message_a;
         session.runtime_info.rsp_cert_hash = None;
         session.runtime_info.req_cert_hash = None;
+        self.common.touch_session(session_id);
 
         info!("send spdm psk_exchange rsp\n");
 
@@ -53,6 +53,25 @@ impl<'a> ResponderContext<'a> {
             return;
         }
 
+        // VERSION lists each supported version once, in ascending order.
+        let mut supported = self.common.config_info.spdm_version;
+        supported.sort_unstable_by_key(|version| version.get_u8());
+        let mut versions: [SpdmVersionStruct; MAX_SPDM_VERSION_COUNT] = Default::default();
+        let mut version_number_entry_count = 0usize;
+        for version in supported.iter() {
+            if *version == SpdmVersion::Unknown(0)
+                || (version_number_entry_count > 0
+                    && versions[version_number_entry_count - 1].version == *version)
+            {
+                continue;
+            }
+            versions[version_number_entry_count] = SpdmVersionStruct {
+                update: 0,
+                version: *version,
+            };
+            version_number_entry_count += 1;
+        }
+
         info!("send spdm version\n");
         let response = SpdmMessage {
             header: SpdmMessageHeader {
@@ -60,21 +79,8 @@ impl<'a> ResponderContext<'a> {
                 request_response_code: SpdmRequestResponseCode::SpdmResponseVersion,
             },
             payload: SpdmMessagePayload::SpdmVersionResponse(SpdmVersionResponsePayload {
-                version_number_entry_count: 3,
-                versions: [
-                    SpdmVersionStruct {
-                        update: 0,
-                        version: self.common.config_info.spdm_version[0],
-                    },
-                    SpdmVersionStruct {
-                        update: 0,
-                        version: self.common.config_info.spdm_version[1],
-                    },
-                    SpdmVersionStruct {
-                        update: 0,
-                        version: self.common.config_info.spdm_version[2],
-                    },
-                ],
+                version_number_entry_count: version_number_entry_count as u8,
+                versions,
             }),
         };
 
@@ -9,6 +9,11 @@ pub use secret_callback::{SpdmSecretAsymSign, SpdmSecretMeasurement, SpdmSecretP
 static SECRET_MEASUREMENT_INSTANCE: OnceCell<SpdmSecretMeasurement> = OnceCell::uninit();
 static SECRET_PSK_INSTANCE: OnceCell<SpdmSecretPsk> = OnceCell::uninit();
 static SECRET_ASYM_INSTANCE: OnceCell<SpdmSecretAsymSign> = OnceCell::uninit();
+#[allow(clippy::declare_interior_mutable_const)]
+const SECRET_ASYM_UNINIT: OnceCell<SpdmSecretAsymSign> = OnceCell::uninit();
+static SECRET_ASYM_INSTANCE_BY_ALGO: [OnceCell<SpdmSecretAsymSign>;
+    crate::crypto::ASYM_ALGO_BACKEND_COUNT] =
+    [SECRET_ASYM_UNINIT; crate::crypto::ASYM_ALGO_BACKEND_COUNT];
 
 pub mod measurement {
     use super::{SpdmSecretMeasurement, SECRET_MEASUREMENT_INSTANCE};
@@ -138,7 +143,7 @@ pub mod psk {
 }
 
 pub mod asym_sign {
-    use super::SECRET_ASYM_INSTANCE;
+    use super::{SECRET_ASYM_INSTANCE, SECRET_ASYM_INSTANCE_BY_ALGO};
     use crate::protocol::{SpdmBaseAsymAlgo, SpdmBaseHashAlgo, SpdmSignatureStruct};
     use crate::secret::SpdmSecretAsymSign;
 
@@ -146,6 +151,21 @@ pub mod asym_sign {
         SECRET_ASYM_INSTANCE.try_init_once(|| context).is_ok()
     }
 
+    /// Register a signer used for `base_asym_algo` only, in place of the
+    /// one from `register`. Fails for a set of several algorithms or one
+    /// that already has a signer.
+    pub fn register_for_algo(
+        base_asym_algo: SpdmBaseAsymAlgo,
+        context: SpdmSecretAsymSign,
+    ) -> bool {
+        match crate::crypto::asym_algo_backend_index(base_asym_algo) {
+            Some(index) => SECRET_ASYM_INSTANCE_BY_ALGO[index]
+                .try_init_once(|| context)
+                .is_ok(),
+            None => false,
+        }
+    }
+
     static DEFAULT: SpdmSecretAsymSign = SpdmSecretAsymSign {
         sign_cb: |_base_hash_algo: SpdmBaseHashAlgo,
                   _base_asym_algo: SpdmBaseAsymAlgo,
@@ -153,14 +173,45 @@ pub mod asym_sign {
          -> Option<SpdmSignatureStruct> { unimplemented!() },
     };
 
+    /// A signer may return an ECDSA signature DER encoded; it is converted
+    /// to the fixed size r || s layout SPDM puts on the wire, and dropped
+    /// if it is neither.
     pub fn sign(
         base_hash_algo: SpdmBaseHashAlgo,
         base_asym_algo: SpdmBaseAsymAlgo,
         data: &[u8],
     ) -> Option<SpdmSignatureStruct> {
-        (SECRET_ASYM_INSTANCE
-            .try_get_or_init(|| DEFAULT.clone())
-            .ok()?
-            .sign_cb)(base_hash_algo, base_asym_algo, data)
+        let instance = match crate::crypto::asym_algo_backend_index(base_asym_algo)
+            .and_then(|index| SECRET_ASYM_INSTANCE_BY_ALGO[index].get())
+        {
+            Some(instance) => instance,
+            None => SECRET_ASYM_INSTANCE
+                .try_get_or_init(|| DEFAULT.clone())
+                .ok()?,
+        };
+        let signature = (instance.sign_cb)(base_hash_algo, base_asym_algo, data)?;
+        crate::crypto::ecdsa_signature_to_p1363(base_asym_algo, &signature)
+    }
+
+    /// Sign a fixed message with the registered signer and verify it
+    /// against `public_cert_der`, the certificate chain of the provisioned
+    /// key, to catch a signer wired to the wrong key or algorithm.
+    pub fn self_test(
+        base_hash_algo: SpdmBaseHashAlgo,
+        base_asym_algo: SpdmBaseAsymAlgo,
+        public_cert_der: &[u8],
+    ) -> bool {
+        let message = crate::crypto::SELF_TEST_MESSAGE;
+        match sign(base_hash_algo, base_asym_algo, message) {
+            Some(signature) => crate::crypto::asym_verify::verify(
+                base_hash_algo,
+                base_asym_algo,
+                public_cert_der,
+                message,
+                &signature,
+            )
+            .is_ok(),
+            None => false,
+        }
     }
 }
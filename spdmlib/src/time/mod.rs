@@ -12,6 +12,7 @@ static TIME_INSTANCE: OnceCell<SpdmTime> = OnceCell::uninit();
 
 static DEFAULT: SpdmTime = SpdmTime {
     sleep_cb: |_: usize| unimplemented!(),
+    get_current_time_cb: || None,
 };
 
 pub fn register(context: SpdmTime) -> bool {
@@ -25,3 +26,9 @@ pub fn sleep(us: usize) {
         .unwrap()
         .sleep_cb)(us)
 }
+
+/// Seconds since the Unix epoch, None until a time source is registered or
+/// while it has no trusted time.
+pub fn get_current_time() -> Option<u64> {
+    (TIME_INSTANCE.get()?.get_current_time_cb)()
+}
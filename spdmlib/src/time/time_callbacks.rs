@@ -5,4 +5,7 @@
 #[derive(Clone)]
 pub struct SpdmTime {
     pub sleep_cb: fn(us: usize),
+    // seconds since the Unix epoch, None while the platform has no trusted
+    // time. Certificate validity periods are checked against it.
+    pub get_current_time_cb: fn() -> Option<u64>,
 }
@@ -5,6 +5,7 @@
 #include <mbedtls/x509.h>
 #include <mbedtls/x509_crt.h>
 #include <mbedtls/asn1.h>
+#include <mbedtls/md.h>
 
 /**
  * Verify X509 certificate chains
@@ -88,6 +89,49 @@ int spdm_verify_cert_chain(char *certchain, size_t certchain_size)
     return ret;
 }
 
+/**
+ * Verify the signature of a self-signed X509 certificate with its own public key
+ *
+ * @param[in]      cert                 ASN.1 DER-encoded X.509 certificate.
+ * @param[in]      cert_size            Size of the certificate, in bytes.
+ *
+ * @retval  0       The certificate is signed with its own key.
+ * @retval  others  Invalid certificate or bad signature.
+ **/
+int spdm_verify_self_signed_cert(unsigned char *cert, size_t cert_size)
+{
+    int ret;
+    mbedtls_x509_crt crt;
+    const mbedtls_md_info_t *md_info;
+    unsigned char hash[MBEDTLS_MD_MAX_SIZE];
+
+    mbedtls_x509_crt_init(&crt);
+
+    ret = mbedtls_x509_crt_parse_der_nocopy(&crt, cert, cert_size);
+    if (ret == 0)
+    {
+        md_info = mbedtls_md_info_from_type(crt.sig_md);
+        if (md_info == NULL)
+        {
+            ret = -1;
+        }
+    }
+    if (ret == 0)
+    {
+        ret = mbedtls_md(md_info, crt.tbs.p, crt.tbs.len, hash);
+    }
+    if (ret == 0)
+    {
+        ret = mbedtls_pk_verify_ext(crt.sig_pk, crt.sig_opts, &crt.pk, crt.sig_md,
+                                    hash, mbedtls_md_get_size(md_info),
+                                    crt.sig.p, crt.sig.len);
+    }
+
+    mbedtls_x509_crt_free(&crt);
+
+    return ret;
+}
+
 /**
  * Certificate Check for SPDM leaf cert.
  *
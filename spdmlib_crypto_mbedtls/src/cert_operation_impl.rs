@@ -4,22 +4,24 @@
 
 extern crate alloc;
 
-use spdmlib::crypto::SpdmCertOperation;
+use spdmlib::config;
+use spdmlib::crypto::{check_cert_validity, SpdmCertOperation};
 use spdmlib::error::{SpdmResult, SPDM_STATUS_INVALID_CERT};
 
 pub static DEFAULT: SpdmCertOperation = SpdmCertOperation {
     get_cert_from_cert_chain_cb: get_cert_from_cert_chain,
     verify_cert_chain_cb: verify_cert_chain,
+    verify_self_signed_cert_cb: verify_self_signed_cert,
 };
 
-use crate::ffi::spdm_verify_cert_chain;
+use crate::ffi::{spdm_verify_cert_chain, spdm_verify_self_signed_cert};
 
 fn get_cert_from_cert_chain(cert_chain: &[u8], index: isize) -> SpdmResult<(usize, usize)> {
     let mut offset = 0usize;
     let mut this_index = 0isize;
     let cert_chain_size = cert_chain.len();
     loop {
-        if cert_chain[offset..].len() < 4 || offset > cert_chain.len() {
+        if offset > cert_chain.len() || cert_chain[offset..].len() < 4 {
             return Err(SPDM_STATUS_INVALID_CERT);
         }
         if cert_chain[offset] != 0x30 || cert_chain[offset + 1] != 0x82 {
@@ -44,12 +46,35 @@ fn get_cert_from_cert_chain(cert_chain: &[u8], index: isize) -> SpdmResult<(usiz
 }
 
 fn verify_cert_chain(cert_chain: &[u8]) -> SpdmResult {
+    // mbedtls takes any depth, refuse a chain deeper than
+    // MAX_SPDM_CERT_CHAIN_DEPTH before handing it over.
+    if get_cert_from_cert_chain(cert_chain, config::MAX_SPDM_CERT_CHAIN_DEPTH as isize).is_ok() {
+        return Err(SPDM_STATUS_INVALID_CERT);
+    }
+
     let ret = unsafe { spdm_verify_cert_chain(cert_chain.as_ptr(), cert_chain.len()) };
-    if ret == 0 {
-        Ok(())
-    } else {
-        Err(SPDM_STATUS_INVALID_CERT)
+    if ret != 0 {
+        return Err(SPDM_STATUS_INVALID_CERT);
+    }
+
+    // mbedtls is built without MBEDTLS_HAVE_TIME_DATE, check the validity
+    // periods against the spdmlib time source.
+    let mut index = 0;
+    while let Ok((cert_begin, cert_end)) = get_cert_from_cert_chain(cert_chain, index) {
+        check_cert_validity(&cert_chain[cert_begin..cert_end])
+            .map_err(|_| SPDM_STATUS_INVALID_CERT)?;
+        index += 1;
     }
+    Ok(())
+}
+
+fn verify_self_signed_cert(cert: &[u8]) -> SpdmResult {
+    let ret = unsafe { spdm_verify_self_signed_cert(cert.as_ptr(), cert.len()) };
+    if ret != 0 {
+        return Err(SPDM_STATUS_INVALID_CERT);
+    }
+
+    check_cert_validity(cert).map_err(|_| SPDM_STATUS_INVALID_CERT)
 }
 
 #[cfg(all(test,))]
@@ -98,4 +123,24 @@ mod tests {
         let status = verify_cert_chain(cert_chain).is_ok();
         assert!(status);
     }
+    #[test]
+    fn test_case6_cert_chain_depth() {
+        let cert_chain = &include_bytes!("public_cert.der")[..];
+        let (cert_begin, cert_end) = get_cert_from_cert_chain(cert_chain, 0).unwrap();
+        let cert = &cert_chain[cert_begin..cert_end];
+
+        // one more certificate than allowed, whatever mbedtls would make of it.
+        let mut deep_chain = Vec::new();
+        for _ in 0..config::MAX_SPDM_CERT_CHAIN_DEPTH + 1 {
+            deep_chain.extend_from_slice(cert);
+        }
+        assert!(
+            get_cert_from_cert_chain(&deep_chain, config::MAX_SPDM_CERT_CHAIN_DEPTH as isize)
+                .is_ok()
+        );
+        assert_eq!(
+            verify_cert_chain(&deep_chain),
+            Err(SPDM_STATUS_INVALID_CERT)
+        );
+    }
 }
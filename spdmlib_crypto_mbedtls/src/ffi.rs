@@ -87,6 +87,8 @@ extern "C" {
 
     pub fn spdm_verify_cert_chain(certchain: *const c_uchar, certchain_size: usize) -> c_int;
 
+    pub fn spdm_verify_self_signed_cert(cert: *const c_uchar, cert_size: usize) -> c_int;
+
     pub fn spdm_ecdh_compute_shared_p256(
         private_key: *const c_uchar,
         private_key_len: usize,
@@ -81,6 +81,7 @@ pub fn req_create_info() -> (common::SpdmConfigInfo, common::SpdmProvisionInfo)
     let provision_info = common::SpdmProvisionInfo {
         my_cert_chain_data: [None, None, None, None, None, None, None, None],
         my_cert_chain: [None, None, None, None, None, None, None, None],
+        my_key_usage_mask: Default::default(),
         peer_root_cert_data: Some(peer_root_cert_data),
     };
 
@@ -165,6 +166,7 @@ pub fn rsp_create_info() -> (common::SpdmConfigInfo, common::SpdmProvisionInfo)
             None,
         ],
         my_cert_chain: [None, None, None, None, None, None, None, None],
+        my_key_usage_mask: Default::default(),
         peer_root_cert_data: None,
     };
 
@@ -10,3 +10,4 @@ pub mod secret_impl_sample;
 pub mod socket_io_transport;
 pub mod spdm_emu;
 pub mod tcp_transport;
+pub mod time;
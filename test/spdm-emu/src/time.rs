@@ -0,0 +1,21 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use spdmlib::time::SpdmTime;
+
+pub static SYSTEM_TIME: SpdmTime = SpdmTime {
+    sleep_cb: sleep,
+    get_current_time_cb: get_system_time,
+};
+
+fn sleep(us: usize) {
+    std::thread::sleep(std::time::Duration::from_micros(us as u64));
+}
+
+fn get_system_time() -> Option<u64> {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_secs())
+}
@@ -181,12 +181,14 @@ fn test_spdm(
                 None,
             ],
             my_cert_chain: [None, None, None, None, None, None, None, None],
+            my_key_usage_mask: Default::default(),
             peer_root_cert_data: Some(peer_root_cert_data),
         }
     } else {
         common::SpdmProvisionInfo {
             my_cert_chain_data: [None, None, None, None, None, None, None, None],
             my_cert_chain: [None, None, None, None, None, None, None, None],
+            my_key_usage_mask: Default::default(),
             peer_root_cert_data: Some(peer_root_cert_data),
         }
     };
@@ -228,6 +230,7 @@ fn test_spdm(
             0,
             SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
             SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
             &mut total_number,
             &mut spdm_measurement_record_structure,
         )
@@ -281,6 +284,7 @@ fn test_spdm(
                 0,
                 SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
                 SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+                None,
                 &mut total_number,
                 &mut spdm_measurement_record_structure,
             )
@@ -345,6 +349,7 @@ fn main() {
 
     #[cfg(feature = "spdm-mbedtls")]
     spdm_emu::crypto::crypto_mbedtls_register_handles();
+    spdmlib::time::register(spdm_emu::time::SYSTEM_TIME.clone());
 
     let since_the_epoch = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
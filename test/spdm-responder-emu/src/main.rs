@@ -85,6 +85,7 @@ fn main() {
 
     #[cfg(feature = "spdm-mbedtls")]
     spdm_emu::crypto::crypto_mbedtls_register_handles();
+    spdmlib::time::register(spdm_emu::time::SYSTEM_TIME.clone());
 
     spdmlib::secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
     spdmlib::secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
@@ -241,6 +242,7 @@ fn handle_message(
             None,
         ],
         my_cert_chain: [None, None, None, None, None, None, None, None],
+        my_key_usage_mask: Default::default(),
         peer_root_cert_data: None,
     };
 
@@ -7,6 +7,7 @@ use spdmlib::crypto::SpdmCryptoRandom;
 use spdmlib::crypto::{SpdmAead, SpdmAsymVerify, SpdmHkdf, SpdmHmac};
 use spdmlib::error::{SpdmResult, SPDM_STATUS_VERIF_FAIL};
 use spdmlib::protocol::*;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 pub static FAKE_HMAC: SpdmHmac = SpdmHmac {
     hmac_cb: fake_hmac,
@@ -34,6 +35,7 @@ pub static FAKE_HKDF: SpdmHkdf = SpdmHkdf {
 pub static FAKE_CERT_OPERATION: SpdmCertOperation = SpdmCertOperation {
     get_cert_from_cert_chain_cb: fake_get_cert_from_cert_chain,
     verify_cert_chain_cb: fake_verify_cert_chain,
+    verify_self_signed_cert_cb: fake_verify_cert_chain,
 };
 
 fn fake_hmac(
@@ -97,11 +99,17 @@ fn fake_decrypt(
     Ok(cipher_text_size)
 }
 
+// Deterministic, but a counter in the leading bytes keeps every output,
+// such as a responder nonce, distinct from the previous ones.
 fn get_random(data: &mut [u8]) -> SpdmResult<usize> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
     #[allow(clippy::needless_range_loop)]
     for i in 0..data.len() {
         data[i] = 0xff;
     }
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed).to_le_bytes();
+    let len = data.len().min(counter.len());
+    data[..len].copy_from_slice(&counter[..len]);
 
     Ok(data.len())
 }
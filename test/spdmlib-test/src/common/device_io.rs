@@ -5,9 +5,9 @@
 #![allow(unused)]
 
 use spdmlib::common::{SpdmDeviceIo, ST1};
-use spdmlib::error::{SpdmResult, SPDM_STATUS_ERROR_PEER};
+use spdmlib::error::{SpdmResult, SPDM_STATUS_ERROR_PEER, SPDM_STATUS_SEND_FAIL};
 use spdmlib::responder;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
 
 pub struct MySpdmDeviceIo;
@@ -150,6 +150,118 @@ impl SharedBuffer {
     }
 }
 
+/// What `RingBuffer::push` does when every slot is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RingBufferFullPolicy {
+    // the new message is refused and send fails.
+    Reject,
+    // the oldest queued message is dropped to make room.
+    DropOldest,
+}
+
+/// Fixed size FIFO of whole messages, SLOTS messages of at most SLOT_SIZE
+/// bytes each, without heap allocation.
+pub struct RingBuffer<const SLOTS: usize, const SLOT_SIZE: usize> {
+    slots: RefCell<[[u8; SLOT_SIZE]; SLOTS]>,
+    lens: RefCell<[usize; SLOTS]>,
+    // index of the oldest message and number of queued messages.
+    head: Cell<usize>,
+    count: Cell<usize>,
+    policy: RingBufferFullPolicy,
+}
+
+impl<const SLOTS: usize, const SLOT_SIZE: usize> RingBuffer<SLOTS, SLOT_SIZE> {
+    pub fn new(policy: RingBufferFullPolicy) -> Self {
+        RingBuffer {
+            slots: RefCell::new([[0u8; SLOT_SIZE]; SLOTS]),
+            lens: RefCell::new([0usize; SLOTS]),
+            head: Cell::new(0),
+            count: Cell::new(0),
+            policy,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.count.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count.get() == 0
+    }
+
+    /// Queue one message. Fails if it is larger than a slot, or if the
+    /// buffer is full and the policy is `Reject`.
+    pub fn push(&self, message: &[u8]) -> SpdmResult {
+        if message.len() > SLOT_SIZE || SLOTS == 0 {
+            return Err(SPDM_STATUS_SEND_FAIL);
+        }
+        if self.count.get() == SLOTS {
+            match self.policy {
+                RingBufferFullPolicy::Reject => return Err(SPDM_STATUS_SEND_FAIL),
+                RingBufferFullPolicy::DropOldest => {
+                    self.head.set((self.head.get() + 1) % SLOTS);
+                    self.count.set(self.count.get() - 1);
+                }
+            }
+        }
+        let tail = (self.head.get() + self.count.get()) % SLOTS;
+        self.slots.borrow_mut()[tail][..message.len()].copy_from_slice(message);
+        self.lens.borrow_mut()[tail] = message.len();
+        self.count.set(self.count.get() + 1);
+        Ok(())
+    }
+
+    /// Dequeue the oldest message into `buffer`. None if the buffer is
+    /// empty or `buffer` is too small, in which case the message stays
+    /// queued.
+    pub fn pop(&self, buffer: &mut [u8]) -> Option<usize> {
+        if self.count.get() == 0 {
+            return None;
+        }
+        let head = self.head.get();
+        let len = self.lens.borrow()[head];
+        buffer
+            .get_mut(..len)?
+            .copy_from_slice(&self.slots.borrow()[head][..len]);
+        self.head.set((head + 1) % SLOTS);
+        self.count.set(self.count.get() - 1);
+        Some(len)
+    }
+}
+
+/// One end of an in-process loopback: sends into `tx` and receives from
+/// `rx`. The peer end uses the same two buffers swapped, so messages
+/// queue up in each direction until the other side drains them.
+pub struct RingBufferDeviceIo<'a, const SLOTS: usize, const SLOT_SIZE: usize> {
+    tx: &'a RingBuffer<SLOTS, SLOT_SIZE>,
+    rx: &'a RingBuffer<SLOTS, SLOT_SIZE>,
+}
+
+impl<'a, const SLOTS: usize, const SLOT_SIZE: usize> RingBufferDeviceIo<'a, SLOTS, SLOT_SIZE> {
+    pub fn new(tx: &'a RingBuffer<SLOTS, SLOT_SIZE>, rx: &'a RingBuffer<SLOTS, SLOT_SIZE>) -> Self {
+        RingBufferDeviceIo { tx, rx }
+    }
+}
+
+impl<const SLOTS: usize, const SLOT_SIZE: usize> SpdmDeviceIo
+    for RingBufferDeviceIo<'_, SLOTS, SLOT_SIZE>
+{
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        log::info!("ring send    RAW - {:02x?}\n", buffer);
+        self.tx.push(buffer)
+    }
+
+    fn receive(&mut self, read_buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        let len = self.rx.pop(read_buffer).ok_or(0usize)?;
+        log::info!("ring receive RAW - {:02x?}\n", &read_buffer[..len]);
+        Ok(len)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        Ok(())
+    }
+}
+
 #[test]
 fn test_fake_device_io() {
     let buffer = SharedBuffer::new();
@@ -161,3 +273,55 @@ fn test_fake_device_io() {
     server.receive(&mut rev, ST1).unwrap();
     assert_eq!(&rev[..=1], SEND_DATA)
 }
+
+#[test]
+fn test_ring_buffer_device_io() {
+    let to_responder = RingBuffer::<4, 16>::new(RingBufferFullPolicy::Reject);
+    let to_requester = RingBuffer::<4, 16>::new(RingBufferFullPolicy::Reject);
+    let mut requester = RingBufferDeviceIo::new(&to_responder, &to_requester);
+    let mut responder = RingBufferDeviceIo::new(&to_requester, &to_responder);
+
+    // several messages queue up in each direction before either is drained.
+    for i in 1..=4u8 {
+        requester.send(&[i; 4][..i as usize]).unwrap();
+    }
+    responder.send(&[0xAA, 0xBB]).unwrap();
+    responder.send(&[0xCC]).unwrap();
+    assert_eq!(to_responder.len(), 4);
+    assert_eq!(to_requester.len(), 2);
+
+    // full: rejected and nothing already queued is lost.
+    assert_eq!(requester.send(&[5]), Err(SPDM_STATUS_SEND_FAIL));
+    assert_eq!(requester.send(&[0u8; 17]), Err(SPDM_STATUS_SEND_FAIL));
+
+    let mut rev = [0u8; 16];
+    for i in 1..=4u8 {
+        let len = responder.receive(&mut rev, ST1).unwrap();
+        assert_eq!(&rev[..len], &[i; 4][..i as usize]);
+    }
+    assert!(responder.receive(&mut rev, ST1).is_err());
+
+    // a message the read buffer cannot hold stays queued.
+    let mut short = [0u8; 1];
+    assert!(requester.receive(&mut short, ST1).is_err());
+    assert_eq!(requester.receive(&mut rev, ST1), Ok(2));
+    assert_eq!(&rev[..2], &[0xAA, 0xBB]);
+    assert_eq!(requester.receive(&mut short, ST1), Ok(1));
+    assert_eq!(short, [0xCC]);
+    assert!(to_requester.is_empty());
+}
+
+#[test]
+fn test_ring_buffer_drop_oldest() {
+    let buffer = RingBuffer::<2, 4>::new(RingBufferFullPolicy::DropOldest);
+    for i in 1..=5u8 {
+        buffer.push(&[i]).unwrap();
+    }
+    assert_eq!(buffer.len(), 2);
+    let mut rev = [0u8; 4];
+    assert_eq!(buffer.pop(&mut rev), Some(1));
+    assert_eq!(rev[0], 4);
+    assert_eq!(buffer.pop(&mut rev), Some(1));
+    assert_eq!(rev[0], 5);
+    assert_eq!(buffer.pop(&mut rev), None);
+}
@@ -0,0 +1,267 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! Measurement provider backed by a static manifest instead of hardware.
+//!
+//! The manifest is a plain concatenation of DSP0274 measurement blocks, in
+//! the same layout they take inside a MEASUREMENTS record. Load it with
+//! `load_measurement_manifest`, then register
+//! `MANIFEST_MEASUREMENT_IMPL_INSTANCE` with
+//! `spdmlib::secret::measurement::register`.
+
+use codec::{u24, Codec, Reader, Writer};
+use spdmlib::config;
+use spdmlib::crypto::hash;
+use spdmlib::message::*;
+use spdmlib::protocol::*;
+use spdmlib::secret::SpdmSecretMeasurement;
+use std::sync::Mutex;
+
+pub static MANIFEST_MEASUREMENT_IMPL_INSTANCE: SpdmSecretMeasurement = SpdmSecretMeasurement {
+    measurement_collection_cb: manifest_measurement_collection,
+    generate_measurement_summary_hash_cb: manifest_generate_measurement_summary_hash,
+};
+
+static MEASUREMENT_MANIFEST: Mutex<Vec<SpdmMeasurementBlockStructure>> = Mutex::new(Vec::new());
+
+/// Parse `manifest` and serve its blocks from now on.
+///
+/// Returns false, keeping the previously loaded manifest, if the manifest
+/// is truncated, uses an index outside 1..=0xFE or repeats an index.
+pub fn load_measurement_manifest(manifest: &[u8]) -> bool {
+    let mut reader = Reader::init(manifest);
+    let mut blocks: Vec<SpdmMeasurementBlockStructure> = Vec::new();
+    while reader.any_left() {
+        let block = match SpdmMeasurementBlockStructure::read(&mut reader) {
+            Some(block) => block,
+            None => return false,
+        };
+        if block.index == 0
+            || block.index == SpdmMeasurementOperation::SpdmMeasurementRequestAll.get_u8()
+            || blocks.iter().any(|b| b.index == block.index)
+        {
+            return false;
+        }
+        blocks.push(block);
+    }
+
+    match MEASUREMENT_MANIFEST.lock() {
+        Ok(mut manifest) => {
+            *manifest = blocks;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+pub fn manifest_measurement_collection(
+    _spdm_version: SpdmVersion,
+    measurement_specification: SpdmMeasurementSpecification,
+    measurement_hash_algo: SpdmMeasurementHashAlgo,
+    measurement_index: usize,
+) -> Option<SpdmMeasurementRecordStructure> {
+    if measurement_specification != SpdmMeasurementSpecification::DMTF {
+        return None;
+    }
+    let blocks = MEASUREMENT_MANIFEST.lock().ok()?;
+
+    if measurement_index
+        == SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber.get_u8() as usize
+    {
+        return Some(SpdmMeasurementRecordStructure {
+            number_of_blocks: blocks.len() as u8,
+            ..Default::default()
+        });
+    }
+
+    let selected: Vec<&SpdmMeasurementBlockStructure> = if measurement_index
+        == SpdmMeasurementOperation::SpdmMeasurementRequestAll.get_u8() as usize
+    {
+        blocks.iter().collect()
+    } else {
+        blocks
+            .iter()
+            .filter(|b| b.index as usize == measurement_index)
+            .collect()
+    };
+    if selected.is_empty() {
+        return None;
+    }
+
+    let mut measurement_record_data = [0u8; config::MAX_SPDM_MEASUREMENT_RECORD_SIZE];
+    let mut writer = Writer::init(&mut measurement_record_data);
+    for block in selected.iter() {
+        // a digest must match the negotiated measurement hash.
+        if block.measurement.representation
+            == SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest
+            && block.measurement.value_size != measurement_hash_algo.get_size()
+        {
+            return None;
+        }
+        block.encode(&mut writer).ok()?;
+    }
+
+    Some(SpdmMeasurementRecordStructure {
+        number_of_blocks: selected.len() as u8,
+        measurement_record_length: u24::new(writer.used() as u32),
+        measurement_record_data,
+    })
+}
+
+/// The TCB summary covers the ROM and firmware blocks, the ALL summary
+/// covers every block of the manifest.
+pub fn manifest_generate_measurement_summary_hash(
+    _spdm_version: SpdmVersion,
+    base_hash_algo: SpdmBaseHashAlgo,
+    _measurement_specification: SpdmMeasurementSpecification,
+    _measurement_hash_algo: SpdmMeasurementHashAlgo,
+    measurement_summary_hash_type: SpdmMeasurementSummaryHashType,
+) -> Option<SpdmDigestStruct> {
+    let tcb_only = match measurement_summary_hash_type {
+        SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll => false,
+        SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeTcb => true,
+        _ => return None,
+    };
+    let blocks = MEASUREMENT_MANIFEST.lock().ok()?;
+
+    let mut measurement_data = [0u8; config::MAX_SPDM_MEASUREMENT_RECORD_SIZE];
+    let mut writer = Writer::init(&mut measurement_data);
+    for block in blocks.iter() {
+        if tcb_only
+            && block.measurement.r#type != SpdmDmtfMeasurementType::SpdmDmtfMeasurementRom
+            && block.measurement.r#type != SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware
+        {
+            continue;
+        }
+        block.measurement.encode(&mut writer).ok()?;
+    }
+    hash::hash_all(base_hash_algo, writer.used_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_block(
+        index: u8,
+        r#type: SpdmDmtfMeasurementType,
+        fill: u8,
+    ) -> SpdmMeasurementBlockStructure {
+        let mut value = [0u8; config::MAX_SPDM_MEASUREMENT_VALUE_LEN];
+        value[..SHA384_DIGEST_SIZE].copy_from_slice(&[fill; SHA384_DIGEST_SIZE]);
+        SpdmMeasurementBlockStructure {
+            index,
+            measurement_specification: SpdmMeasurementSpecification::DMTF,
+            measurement_size: SHA384_DIGEST_SIZE as u16 + 3,
+            measurement: SpdmDmtfMeasurementStructure {
+                r#type,
+                representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest,
+                value_size: SHA384_DIGEST_SIZE as u16,
+                value,
+            },
+        }
+    }
+
+    fn encode_manifest(blocks: &[SpdmMeasurementBlockStructure]) -> Vec<u8> {
+        let mut manifest = [0u8; config::MAX_SPDM_MEASUREMENT_RECORD_SIZE];
+        let mut writer = Writer::init(&mut manifest);
+        for block in blocks {
+            block.encode(&mut writer).unwrap();
+        }
+        writer.used_slice().to_vec()
+    }
+
+    fn collect(measurement_index: u8) -> Option<SpdmMeasurementRecordStructure> {
+        manifest_measurement_collection(
+            SpdmVersion::SpdmVersion12,
+            SpdmMeasurementSpecification::DMTF,
+            SpdmMeasurementHashAlgo::TPM_ALG_SHA_384,
+            measurement_index as usize,
+        )
+    }
+
+    #[test]
+    fn test_case0_manifest_measurement_collection() {
+        let blocks = [
+            manifest_block(1, SpdmDmtfMeasurementType::SpdmDmtfMeasurementRom, 0x11),
+            manifest_block(
+                2,
+                SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware,
+                0x22,
+            ),
+            manifest_block(
+                5,
+                SpdmDmtfMeasurementType::SpdmDmtfMeasurementHardwareConfig,
+                0x55,
+            ),
+        ];
+        let manifest = encode_manifest(&blocks);
+        assert!(load_measurement_manifest(&manifest));
+
+        let total = collect(SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber.get_u8());
+        assert_eq!(total.unwrap().number_of_blocks, 3);
+
+        let all = collect(SpdmMeasurementOperation::SpdmMeasurementRequestAll.get_u8()).unwrap();
+        assert_eq!(all.number_of_blocks, 3);
+        assert_eq!(
+            &all.measurement_record_data[..all.measurement_record_length.get() as usize],
+            manifest.as_slice()
+        );
+
+        let single = collect(5).unwrap();
+        assert_eq!(single.number_of_blocks, 1);
+        assert_eq!(
+            &single.measurement_record_data[..single.measurement_record_length.get() as usize],
+            encode_manifest(&blocks[2..]).as_slice()
+        );
+
+        // index 3 is not in the manifest.
+        assert!(collect(3).is_none());
+
+        // the manifest holds SHA-384 digests only.
+        assert!(manifest_measurement_collection(
+            SpdmVersion::SpdmVersion12,
+            SpdmMeasurementSpecification::DMTF,
+            SpdmMeasurementHashAlgo::TPM_ALG_SHA_256,
+            1,
+        )
+        .is_none());
+
+        let summary_all = manifest_generate_measurement_summary_hash(
+            SpdmVersion::SpdmVersion12,
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmMeasurementSpecification::DMTF,
+            SpdmMeasurementHashAlgo::TPM_ALG_SHA_384,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
+        )
+        .unwrap();
+        let summary_tcb = manifest_generate_measurement_summary_hash(
+            SpdmVersion::SpdmVersion12,
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmMeasurementSpecification::DMTF,
+            SpdmMeasurementHashAlgo::TPM_ALG_SHA_384,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeTcb,
+        )
+        .unwrap();
+        assert_ne!(summary_all.as_ref(), summary_tcb.as_ref());
+    }
+
+    #[test]
+    fn test_case1_load_invalid_measurement_manifest() {
+        let block = manifest_block(1, SpdmDmtfMeasurementType::SpdmDmtfMeasurementRom, 0x11);
+
+        let manifest = encode_manifest(&[block.clone(), block.clone()]);
+        assert!(!load_measurement_manifest(&manifest));
+
+        let manifest = encode_manifest(&[block]);
+        assert!(!load_measurement_manifest(&manifest[..manifest.len() - 1]));
+
+        let manifest = encode_manifest(&[manifest_block(
+            0,
+            SpdmDmtfMeasurementType::SpdmDmtfMeasurementRom,
+            0x11,
+        )]);
+        assert!(!load_measurement_manifest(&manifest));
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+//! Mock of the `SpdmContextAccess` surface, recording every transcript
+//! append in plain vectors so extension code can be checked without a
+//! device, a transport or negotiated algorithms.
+
+use spdmlib::common::session::SpdmSession;
+use spdmlib::common::SpdmContextAccess;
+use spdmlib::error::{SpdmResult, SPDM_STATUS_INVALID_PARAMETER};
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct MockSpdmContext {
+    pub sessions: Vec<SpdmSession>,
+    pub message_a: Vec<u8>,
+    pub message_b: Vec<u8>,
+    pub message_c: Vec<u8>,
+    pub message_m: HashMap<Option<u32>, Vec<u8>>,
+    pub message_k: HashMap<u32, Vec<u8>>,
+}
+
+impl MockSpdmContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a session that `get_session_via_id` will find.
+    pub fn add_session(&mut self, session_id: u32) -> &mut SpdmSession {
+        let mut session = SpdmSession::new();
+        session.setup(session_id).unwrap();
+        self.sessions.push(session);
+        self.sessions.last_mut().unwrap()
+    }
+}
+
+impl SpdmContextAccess for MockSpdmContext {
+    fn get_immutable_session_via_id(&self, session_id: u32) -> Option<&SpdmSession> {
+        self.sessions
+            .iter()
+            .find(|session| session.get_session_id() == session_id)
+    }
+    fn get_session_via_id(&mut self, session_id: u32) -> Option<&mut SpdmSession> {
+        self.sessions
+            .iter_mut()
+            .find(|session| session.get_session_id() == session_id)
+    }
+
+    fn append_message_a(&mut self, new_message: &[u8]) -> SpdmResult {
+        self.message_a.extend_from_slice(new_message);
+        Ok(())
+    }
+    fn reset_message_a(&mut self) {
+        self.message_a.clear();
+    }
+    fn append_message_b(&mut self, new_message: &[u8]) -> SpdmResult {
+        self.message_b.extend_from_slice(new_message);
+        Ok(())
+    }
+    fn reset_message_b(&mut self) {
+        self.message_b.clear();
+    }
+    fn append_message_c(&mut self, new_message: &[u8]) -> SpdmResult {
+        self.message_c.extend_from_slice(new_message);
+        Ok(())
+    }
+    fn reset_message_c(&mut self) {
+        self.message_c.clear();
+    }
+    fn append_message_m(&mut self, session_id: Option<u32>, new_message: &[u8]) -> SpdmResult {
+        if let Some(session_id) = session_id {
+            self.get_immutable_session_via_id(session_id)
+                .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        }
+        self.message_m
+            .entry(session_id)
+            .or_default()
+            .extend_from_slice(new_message);
+        Ok(())
+    }
+    fn reset_message_m(&mut self, session_id: Option<u32>) {
+        self.message_m.remove(&session_id);
+    }
+    fn append_message_k(&mut self, session_id: u32, new_message: &[u8]) -> SpdmResult {
+        self.get_immutable_session_via_id(session_id)
+            .ok_or(SPDM_STATUS_INVALID_PARAMETER)?;
+        self.message_k
+            .entry(session_id)
+            .or_default()
+            .extend_from_slice(new_message);
+        Ok(())
+    }
+    fn reset_message_k(&mut self, session_id: u32) {
+        self.message_k.remove(&session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::device_io::{FakeSpdmDeviceIoReceve, SharedBuffer};
+    use crate::common::transport::PciDoeTransportEncap;
+    use crate::common::util::create_info;
+    use spdmlib::common::SpdmContext;
+    use spdmlib::protocol::*;
+
+    // Stand-in for extension code: records an out-of-session exchange and
+    // drops it again when the exchange fails.
+    fn record_exchange<C: SpdmContextAccess>(
+        context: &mut C,
+        session_id: Option<u32>,
+        request: &[u8],
+        response: &[u8],
+    ) -> SpdmResult {
+        context.append_message_m(session_id, request)?;
+        if response.is_empty() {
+            context.reset_message_m(session_id);
+            return Err(SPDM_STATUS_INVALID_PARAMETER);
+        }
+        context.append_message_m(session_id, response)
+    }
+
+    #[test]
+    fn test_case0_mock_spdm_context() {
+        let mut context = MockSpdmContext::new();
+        let session_id = 0xFFFE_FFFE;
+        context.add_session(session_id);
+
+        assert!(record_exchange(&mut context, None, &[1, 2], &[3, 4]).is_ok());
+        assert!(record_exchange(&mut context, Some(session_id), &[5], &[6]).is_ok());
+        assert_eq!(context.message_m[&None], vec![1, 2, 3, 4]);
+        assert_eq!(context.message_m[&Some(session_id)], vec![5, 6]);
+
+        assert!(record_exchange(&mut context, None, &[7], &[]).is_err());
+        assert!(!context.message_m.contains_key(&None));
+
+        // an unknown session is refused like it is by SpdmContext.
+        assert!(record_exchange(&mut context, Some(1), &[1], &[2]).is_err());
+        assert!(context.get_session_via_id(1).is_none());
+    }
+
+    #[test]
+    fn test_case1_spdm_context_access() {
+        let (config_info, provision_info) = create_info();
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let transport_encap = &mut PciDoeTransportEncap {};
+        let mut context =
+            SpdmContext::new(&mut device_io, transport_encap, config_info, provision_info);
+        context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+
+        // the same extension code runs unchanged against the real context.
+        assert!(record_exchange(&mut context, None, &[1, 2], &[3, 4]).is_ok());
+        assert!(record_exchange(&mut context, None, &[7], &[]).is_err());
+    }
+}
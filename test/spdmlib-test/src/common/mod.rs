@@ -14,3 +14,6 @@ pub mod transport;
 
 pub mod crypto_callback;
 pub mod secret_callback;
+
+pub mod measurement_manifest;
+pub mod mock_context;
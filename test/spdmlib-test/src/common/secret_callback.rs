@@ -286,13 +286,33 @@ fn asym_sign(
         (SpdmBaseHashAlgo::TPM_ALG_SHA_384, SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384) => {
             sign_ecdsa_asym_algo(&ring::signature::ECDSA_P384_SHA384_FIXED_SIGNING, data)
         }
+        (_, SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519) => sign_ed25519_asym_algo(data),
         _ => {
             panic!();
         }
     }
 }
 
-fn sign_ecdsa_asym_algo(
+fn sign_ed25519_asym_algo(data: &[u8]) -> Option<SpdmSignatureStruct> {
+    let crate_dir = get_test_key_directory();
+    let key_file_path = crate_dir.join("test_key/ed25519/end_responder.key.p8");
+    let der_file = std::fs::read(key_file_path).expect("unable to read key der!");
+
+    let key_pair =
+        ring::signature::Ed25519KeyPair::from_pkcs8_maybe_unchecked(der_file.as_slice()).ok()?;
+    let signature = key_pair.sign(data);
+    let signature = signature.as_ref();
+
+    let mut full_signature: [u8; SPDM_MAX_ASYM_KEY_SIZE] = [0u8; SPDM_MAX_ASYM_KEY_SIZE];
+    full_signature[..signature.len()].copy_from_slice(signature);
+
+    Some(SpdmSignatureStruct {
+        data_size: signature.len() as u16,
+        data: full_signature,
+    })
+}
+
+pub fn sign_ecdsa_asym_algo(
     algorithm: &'static ring::signature::EcdsaSigningAlgorithm,
     data: &[u8],
 ) -> Option<SpdmSignatureStruct> {
@@ -105,6 +105,7 @@ pub fn create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
             None,
         ],
         my_cert_chain: [None, None, None, None, None, None, None, None],
+        my_key_usage_mask: Default::default(),
         peer_root_cert_data: Some(peer_root_cert_data),
     };
 
@@ -242,12 +243,14 @@ pub fn req_create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
                 None,
             ],
             my_cert_chain: [None, None, None, None, None, None, None, None],
+            my_key_usage_mask: Default::default(),
             peer_root_cert_data: Some(peer_root_cert_data),
         }
     } else {
         SpdmProvisionInfo {
             my_cert_chain_data: [None, None, None, None, None, None, None, None],
             my_cert_chain: [None, None, None, None, None, None, None, None],
+            my_key_usage_mask: Default::default(),
             peer_root_cert_data: Some(peer_root_cert_data),
         }
     };
@@ -359,6 +362,7 @@ pub fn rsp_create_info() -> (SpdmConfigInfo, SpdmProvisionInfo) {
             None,
         ],
         my_cert_chain: [None, None, None, None, None, None, None, None],
+        my_key_usage_mask: Default::default(),
         peer_root_cert_data: None,
     };
 
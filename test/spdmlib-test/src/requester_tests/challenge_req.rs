@@ -4,10 +4,12 @@
 
 use crate::common::crypto_callback::FAKE_RAND;
 use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedBuffer};
-use crate::common::secret_callback::SECRET_ASYM_IMPL_INSTANCE;
+use crate::common::secret_callback::{SECRET_ASYM_IMPL_INSTANCE, SECRET_MEASUREMENT_IMPL_INSTANCE};
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::{create_info, get_rsp_cert_chain_buff};
-use spdmlib::common::SpdmConnectionState;
+use codec::{Reader, Writer};
+use spdmlib::common::{SpdmCodec, SpdmConnectionState, SpdmContext};
+use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
 use spdmlib::{config, crypto, responder, secret};
@@ -68,6 +70,10 @@ fn test_case0_send_receive_spdm_challenge() {
         req_provision_info,
     );
     requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
     requester
         .common
@@ -90,3 +96,118 @@ fn test_case0_send_receive_spdm_challenge() {
         .is_ok();
     assert!(status);
 }
+
+fn negotiate_challenge(context: &mut SpdmContext) {
+    context.reset_runtime_info();
+    context.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.negotiate_info.rsp_capabilities_sel = context.config_info.rsp_capabilities;
+    context.negotiate_info.measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    context.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    context.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    context.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    context
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+}
+
+#[test]
+fn test_case1_challenge_auth_measurement_summary_hash() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+    crypto::rand::register(FAKE_RAND.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+    negotiate_challenge(&mut responder.common);
+    assert!(responder.common.construct_my_cert_chain().is_ok());
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    negotiate_challenge(&mut requester.common);
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    let cert_chain_hash = crypto::hash::hash_all(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        get_rsp_cert_chain_buff().as_ref(),
+    )
+    .unwrap();
+
+    for measurement_summary_hash_type in [
+        SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeTcb,
+        SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
+    ] {
+        requester
+            .common
+            .reset_buffer_via_request_code(SpdmRequestResponseCode::SpdmRequestChallenge, None);
+        let mut request = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let request_size = requester
+            .encode_spdm_challenge(0, measurement_summary_hash_type, &mut request)
+            .unwrap();
+
+        let mut response = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut response);
+        responder.write_spdm_challenge_response(&request[..request_size], &mut writer);
+        let response_size = writer.used();
+
+        // the signature over the transcript verifies.
+        assert!(requester
+            .handle_spdm_challenge_response(
+                0,
+                0,
+                measurement_summary_hash_type,
+                &request[..request_size],
+                &response[..response_size],
+            )
+            .is_ok());
+
+        let mut reader = Reader::init(&response[..response_size]);
+        let message = SpdmMessage::spdm_read(&mut requester.common, &mut reader).unwrap();
+        let challenge_auth = match message.payload {
+            SpdmMessagePayload::SpdmChallengeAuthResponse(challenge_auth) => challenge_auth,
+            _ => panic!("not CHALLENGE_AUTH"),
+        };
+        assert_eq!(challenge_auth.slot_id, 0);
+        assert_eq!(challenge_auth.slot_mask, 0x1);
+        assert_eq!(
+            challenge_auth.cert_chain_hash.as_ref(),
+            cert_chain_hash.as_ref()
+        );
+        match measurement_summary_hash_type {
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone => {
+                assert_eq!(challenge_auth.measurement_summary_hash.data_size, 0);
+            }
+            _ => {
+                let measurement_summary_hash =
+                    secret::measurement::generate_measurement_summary_hash(
+                        SpdmVersion::SpdmVersion12,
+                        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+                        SpdmMeasurementSpecification::DMTF,
+                        SpdmMeasurementHashAlgo::TPM_ALG_SHA_384,
+                        measurement_summary_hash_type,
+                    )
+                    .unwrap();
+                assert_eq!(
+                    challenge_auth.measurement_summary_hash.as_ref(),
+                    measurement_summary_hash.as_ref()
+                );
+            }
+        }
+    }
+}
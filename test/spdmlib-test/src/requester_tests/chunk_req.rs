@@ -0,0 +1,165 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::transport::PciDoeTransportEncap;
+use crate::common::util::create_info;
+use codec::Writer;
+use spdmlib::common::{SpdmCodec, SpdmConnectionState, SpdmDeviceIo, SpdmTransportEncap};
+use spdmlib::error::{SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_MSG_SIZE};
+use spdmlib::message::*;
+use spdmlib::protocol::*;
+use spdmlib::requester::RequesterContext;
+use spdmlib::{config, requester};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+// Answers each receive with the next of the queued responses.
+struct ScriptedDeviceIo {
+    responses: Rc<RefCell<VecDeque<Vec<u8>>>>,
+}
+
+impl SpdmDeviceIo for ScriptedDeviceIo {
+    fn send(&mut self, _buffer: &[u8]) -> SpdmResult {
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        let response = self.responses.borrow_mut().pop_front().ok_or(0usize)?;
+        buffer[..response.len()].copy_from_slice(&response);
+        Ok(response.len())
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        Ok(())
+    }
+}
+
+fn new_chunking_requester<'a>(
+    device_io: &'a mut ScriptedDeviceIo,
+    transport_encap: &'a mut PciDoeTransportEncap,
+) -> requester::RequesterContext<'a> {
+    let (config_info, provision_info) = create_info();
+    let mut requester =
+        RequesterContext::new(device_io, transport_encap, config_info, provision_info);
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::CHUNK_CAP;
+    requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CHUNK_CAP;
+    requester.common.negotiate_info.req_data_transfer_size_sel = 0x100;
+    requester.common.negotiate_info.req_max_spdm_msg_size_sel = 0x400;
+    requester.common.negotiate_info.rsp_data_transfer_size_sel = 0x100;
+    requester.common.negotiate_info.rsp_max_spdm_msg_size_sel = 0x400;
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester
+}
+
+fn queue_response(
+    requester: &mut RequesterContext,
+    responses: &RefCell<VecDeque<Vec<u8>>>,
+    response: SpdmMessage,
+) {
+    let mut spdm_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let mut writer = Writer::init(&mut spdm_buffer);
+    let spdm_used = response
+        .spdm_encode(&mut requester.common, &mut writer)
+        .unwrap();
+    let mut transport_buffer = [0u8; config::MAX_SPDM_MSG_SIZE + 64];
+    let transport_used = PciDoeTransportEncap {}
+        .encap(&spdm_buffer[..spdm_used], &mut transport_buffer, false)
+        .unwrap();
+    responses
+        .borrow_mut()
+        .push_back(transport_buffer[..transport_used].to_vec());
+}
+
+fn large_response(handle: u8) -> SpdmMessage {
+    SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion12,
+            request_response_code: SpdmRequestResponseCode::SpdmResponseError,
+        },
+        payload: SpdmMessagePayload::SpdmErrorResponse(SpdmErrorResponsePayload {
+            error_code: SpdmErrorCode::SpdmErrorLargeResponse,
+            error_data: 0,
+            extended_data: SpdmErrorResponseExtData::SpdmErrorExtDataLargeResponse(
+                SpdmErrorResponseLargeResponseExtData { handle },
+            ),
+        }),
+    }
+}
+
+fn chunk_response(
+    handle: u8,
+    chunk_seq_no: u16,
+    chunk_size: u32,
+    large_message_size: u32,
+) -> SpdmMessage {
+    SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion12,
+            request_response_code: SpdmRequestResponseCode::SpdmResponseChunkResponse,
+        },
+        payload: SpdmMessagePayload::SpdmChunkResponse(SpdmChunkPayload {
+            handle,
+            chunk_seq_no,
+            chunk_size,
+            large_message_size,
+            ..Default::default()
+        }),
+    }
+}
+
+#[test]
+fn test_case0_reject_large_message_size_over_max_spdm_msg_size() {
+    let responses = Rc::new(RefCell::new(VecDeque::new()));
+    let mut device_io = ScriptedDeviceIo {
+        responses: responses.clone(),
+    };
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let mut requester = new_chunking_requester(&mut device_io, pcidoe_transport_encap);
+
+    // the receive buffer would take it, the negotiated MaxSPDMmsgSize not.
+    queue_response(&mut requester, &responses, large_response(1));
+    queue_response(
+        &mut requester,
+        &responses,
+        chunk_response(1, 0, 0xF0, 0x401),
+    );
+
+    let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    assert_eq!(
+        requester.receive_message(&mut receive_buffer, false),
+        Err(SPDM_STATUS_INVALID_MSG_SIZE)
+    );
+    // no further CHUNK_GET went out.
+    assert!(responses.borrow().is_empty());
+}
+
+#[test]
+fn test_case1_reject_out_of_order_chunk_seq_no() {
+    let responses = Rc::new(RefCell::new(VecDeque::new()));
+    let mut device_io = ScriptedDeviceIo {
+        responses: responses.clone(),
+    };
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let mut requester = new_chunking_requester(&mut device_io, pcidoe_transport_encap);
+
+    // chunk 2 follows chunk 0.
+    queue_response(&mut requester, &responses, large_response(1));
+    queue_response(
+        &mut requester,
+        &responses,
+        chunk_response(1, 0, 0xF0, 0x300),
+    );
+    queue_response(&mut requester, &responses, chunk_response(1, 2, 0xF0, 0));
+
+    let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    assert_eq!(
+        requester.receive_message(&mut receive_buffer, false),
+        Err(SPDM_STATUS_INVALID_MSG_FIELD)
+    );
+}
@@ -6,13 +6,32 @@ use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedB
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
+#[cfg(all(
+    feature = "transcript-fixture",
+    not(feature = "hashed-transcript-data")
+))]
+use crate::common::util::get_rsp_cert_chain_buff;
 use codec::Writer;
-use spdmlib::common::session::{SpdmSession, SpdmSessionState};
-use spdmlib::common::SpdmCodec;
+use mctp_transport::MctpTransportEncap;
+use spdmlib::common::session::{
+    SpdmSession, SpdmSessionState, SPDM_SEQUENCE_NUMBER_REKEY_THRESHOLD,
+};
+#[cfg(all(
+    feature = "transcript-fixture",
+    not(feature = "hashed-transcript-data")
+))]
+use spdmlib::common::{transcript_fixture::SpdmTranscriptFixture, SpdmNegotiateInfo};
+use spdmlib::common::{SpdmCodec, SpdmConnectionState, SpdmDeviceIo, ST1};
+use spdmlib::error::{
+    SpdmResult, SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_STATE_LOCAL,
+    SPDM_STATUS_RECEIVE_TIMEOUT, SPDM_STATUS_SEND_FAIL, SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW,
+};
 use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
 use spdmlib::{config, protocol, responder, secret};
+use std::cell::RefCell;
+use std::sync::Mutex;
 
 #[test]
 fn test_case0_start_session() {
@@ -308,3 +327,794 @@ fn test_case0_receive_secured_message() {
         .is_ok();
     assert!(status);
 }
+
+static OBSERVED_RECORDS: Mutex<Vec<(u64, bool)>> = Mutex::new(Vec::new());
+
+fn record_secured_message(_session_id: u32, sequence_number: u64, record: &[u8], is_send: bool) {
+    assert!(!record.is_empty());
+    OBSERVED_RECORDS
+        .lock()
+        .unwrap()
+        .push((sequence_number, is_send));
+}
+
+#[test]
+fn test_case0_secured_message_observer() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let rsp_session_id = 0x11u16;
+    let session_id = (0x11u32 << 16) + rsp_session_id as u32;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session[0].setup(session_id).unwrap();
+    responder.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    assert!(responder.common.session[0]
+        .set_dhe_secret(
+            SpdmVersion::SpdmVersion12,
+            SpdmDheFinalKeyStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(responder.common.session[0]
+        .generate_handshake_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(responder.common.session[0]
+        .generate_data_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    responder.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let rsp_session_id = 0x11u16;
+    let session_id = (0x11u32 << 16) + rsp_session_id as u32;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session[0].setup(session_id).unwrap();
+    requester.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    assert!(requester.common.session[0]
+        .set_dhe_secret(
+            SpdmVersion::SpdmVersion12,
+            SpdmDheFinalKeyStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(requester.common.session[0]
+        .generate_handshake_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(requester.common.session[0]
+        .generate_data_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    requester.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+
+    requester.common.secured_message_observer = Some(record_secured_message);
+
+    for _ in 0..3 {
+        assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+    }
+
+    let observed = OBSERVED_RECORDS.lock().unwrap();
+    assert_eq!(
+        *observed,
+        [
+            (0, true),
+            (0, false),
+            (1, true),
+            (1, false),
+            (2, true),
+            (2, false)
+        ]
+    );
+}
+
+#[cfg(feature = "key-log")]
+static LOGGED_SECRETS: Mutex<Vec<(u32, String)>> = Mutex::new(Vec::new());
+
+#[cfg(feature = "key-log")]
+fn log_secret(session_id: u32, label: &str, secret: &[u8]) {
+    assert!(!secret.is_empty());
+    LOGGED_SECRETS
+        .lock()
+        .unwrap()
+        .push((session_id, label.to_string()));
+}
+
+#[test]
+#[cfg(feature = "key-log")]
+fn test_case0_key_log() {
+    use spdmlib::common::key_log;
+
+    key_log::register(key_log::SpdmKeyLog {
+        log_secret_cb: log_secret,
+    });
+
+    let session_id = 0xfffe_0003u32;
+    let mut session = SpdmSession::new();
+    session.setup(session_id).unwrap();
+    session.set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    assert!(session
+        .set_dhe_secret(
+            SpdmVersion::SpdmVersion12,
+            SpdmDheFinalKeyStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(session
+        .generate_handshake_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(session
+        .generate_data_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(session
+        .create_data_secret_update(SpdmVersion::SpdmVersion12, true, false)
+        .is_ok());
+
+    let labels: Vec<String> = LOGGED_SECRETS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(id, _)| *id == session_id)
+        .map(|(_, label)| label.clone())
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            key_log::SPDM_KEY_LOG_REQUEST_HANDSHAKE_SECRET,
+            key_log::SPDM_KEY_LOG_RESPONSE_HANDSHAKE_SECRET,
+            key_log::SPDM_KEY_LOG_REQUEST_DATA_SECRET,
+            key_log::SPDM_KEY_LOG_RESPONSE_DATA_SECRET,
+            key_log::SPDM_KEY_LOG_EXPORT_MASTER_SECRET,
+            key_log::SPDM_KEY_LOG_REQUEST_DATA_SECRET,
+        ]
+    );
+}
+
+#[test]
+fn test_case0_sequence_number_exhausted() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let rsp_session_id = 0x11u16;
+    let session_id = (0x11u32 << 16) + rsp_session_id as u32;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session[0].setup(session_id).unwrap();
+    responder.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    assert!(responder.common.session[0]
+        .set_dhe_secret(
+            SpdmVersion::SpdmVersion12,
+            SpdmDheFinalKeyStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(responder.common.session[0]
+        .generate_handshake_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(responder.common.session[0]
+        .generate_data_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    responder.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+
+    responder.common.session[0]
+        .set_request_direction_sequence_number(SPDM_SEQUENCE_NUMBER_REKEY_THRESHOLD - 1);
+    responder.common.session[0]
+        .set_response_direction_sequence_number(SPDM_SEQUENCE_NUMBER_REKEY_THRESHOLD - 1);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let rsp_session_id = 0x11u16;
+    let session_id = (0x11u32 << 16) + rsp_session_id as u32;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session[0].setup(session_id).unwrap();
+    requester.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    assert!(requester.common.session[0]
+        .set_dhe_secret(
+            SpdmVersion::SpdmVersion12,
+            SpdmDheFinalKeyStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(requester.common.session[0]
+        .generate_handshake_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(requester.common.session[0]
+        .generate_data_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    requester.common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+
+    requester.common.session[0]
+        .set_request_direction_sequence_number(SPDM_SEQUENCE_NUMBER_REKEY_THRESHOLD - 1);
+    requester.common.session[0]
+        .set_response_direction_sequence_number(SPDM_SEQUENCE_NUMBER_REKEY_THRESHOLD - 1);
+
+    assert!(requester.send_receive_spdm_heartbeat(session_id).is_ok());
+    assert_eq!(
+        requester.common.session[0].get_sequence_number(true),
+        SPDM_SEQUENCE_NUMBER_REKEY_THRESHOLD
+    );
+
+    // Ordinary traffic is refused once the threshold is reached ...
+    assert_eq!(
+        requester.send_receive_spdm_heartbeat(session_id),
+        Err(SPDM_STATUS_SEQUENCE_NUMBER_OVERFLOW)
+    );
+    // ... but the session can still be closed.
+    assert!(requester.send_receive_spdm_end_session(session_id).is_ok());
+}
+
+#[test]
+fn test_case0_connection_state_preconditions() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    // Nothing but GET_VERSION may start a connection.
+    assert_eq!(
+        requester.send_receive_spdm_capability(),
+        Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+    );
+    assert_eq!(
+        requester.send_receive_spdm_algorithm(),
+        Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+    );
+    assert_eq!(
+        requester.send_receive_spdm_digest(None),
+        Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+    );
+    let mut total_number = 0u8;
+    let mut measurement_record = SpdmMeasurementRecordStructure::default();
+    assert_eq!(
+        requester.send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
+            &mut total_number,
+            &mut measurement_record,
+        ),
+        Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+    );
+    assert_eq!(
+        requester.send_receive_spdm_key_exchange(
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone
+        ),
+        Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+    );
+
+    assert!(requester.send_receive_spdm_version().is_ok());
+    assert_eq!(
+        requester.common.runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionAfterVersion
+    );
+    // NEGOTIATE_ALGORITHMS still needs CAPABILITIES first.
+    assert_eq!(
+        requester.send_receive_spdm_algorithm(),
+        Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+    );
+    assert_eq!(
+        requester.send_receive_spdm_psk_exchange(
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            None
+        ),
+        Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+    );
+
+    assert!(requester.send_receive_spdm_capability().is_ok());
+    assert!(requester.send_receive_spdm_algorithm().is_ok());
+    assert_eq!(
+        requester.common.runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionNegotiated
+    );
+}
+
+// A responder that never answers: every wait runs into its deadline.
+struct SilentDeviceIo<'a> {
+    timeouts: &'a RefCell<Vec<usize>>,
+}
+
+impl SpdmDeviceIo for SilentDeviceIo<'_> {
+    fn send(&mut self, _buffer: &[u8]) -> SpdmResult {
+        Ok(())
+    }
+
+    fn receive(&mut self, _buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        panic!("the requester must not wait without a timeout");
+    }
+
+    fn receive_with_timeout(&mut self, _buffer: &mut [u8], timeout: usize) -> SpdmResult<usize> {
+        self.timeouts.borrow_mut().push(timeout);
+        Err(SPDM_STATUS_RECEIVE_TIMEOUT)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_case0_receive_timeout() {
+    let (req_config_info, req_provision_info) = create_info();
+    let timeouts = RefCell::new(Vec::new());
+    let mut device_io_requester = SilentDeviceIo {
+        timeouts: &timeouts,
+    };
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+
+    assert_eq!(
+        requester.send_receive_spdm_version(),
+        Err(SPDM_STATUS_RECEIVE_TIMEOUT)
+    );
+    assert_eq!(*timeouts.borrow(), [ST1]);
+
+    // requests with cryptographic work wait as long as the responder's CT.
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_ct_exponent_sel = 12;
+    let mut total_number = 0u8;
+    let mut measurement_record = SpdmMeasurementRecordStructure::default();
+    assert_eq!(
+        requester.send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
+            &mut total_number,
+            &mut measurement_record,
+        ),
+        Err(SPDM_STATUS_RECEIVE_TIMEOUT)
+    );
+    assert_eq!(*timeouts.borrow(), [ST1, 2 << 12]);
+}
+
+#[test]
+fn test_case0_reset_after_protocol_error() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    assert!(requester.init_connection().is_ok());
+    assert!(requester.send_receive_spdm_digest(None).is_ok());
+
+    // a VERSION response cut short.
+    let get_version = [
+        SpdmVersion::SpdmVersion10.get_u8(),
+        SpdmRequestResponseCode::SpdmRequestGetVersion.get_u8(),
+        0,
+        0,
+    ];
+    let version = [
+        SpdmVersion::SpdmVersion10.get_u8(),
+        SpdmRequestResponseCode::SpdmResponseVersion.get_u8(),
+        0,
+    ];
+    assert_eq!(
+        requester.handle_spdm_version_response(0, &get_version, &version),
+        Err(SPDM_STATUS_INVALID_MSG_FIELD)
+    );
+
+    requester.reset();
+    assert_eq!(
+        requester.common.runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionNotStarted
+    );
+    assert_eq!(
+        requester.common.negotiate_info.spdm_version_sel,
+        SpdmVersion::Unknown(0)
+    );
+    assert!(requester.common.peer_info.peer_cert_chain[0].is_none());
+    assert_eq!(
+        requester.common.config_info.spdm_version[2],
+        SpdmVersion::SpdmVersion12
+    );
+    assert_eq!(
+        requester.send_receive_spdm_digest(None),
+        Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+    );
+
+    assert!(requester.init_connection().is_ok());
+    assert_eq!(
+        requester.common.negotiate_info.spdm_version_sel,
+        SpdmVersion::SpdmVersion12
+    );
+    assert!(requester.send_receive_spdm_digest(None).is_ok());
+}
+
+#[test]
+#[cfg(all(
+    feature = "transcript-fixture",
+    not(feature = "hashed-transcript-data")
+))]
+fn test_case0_export_and_reload_transcript() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let (fixture, negotiate_info) =
+        export_transcript_of_known_good_run(&mut device_io_requester, pcidoe_transport_encap2);
+
+    let (req_config_info, req_provision_info) = create_info();
+    let mut requester = RequesterContext::new_with_transcript(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+        &fixture,
+    )
+    .unwrap();
+    assert_eq!(requester.common.export_transcript(), fixture);
+
+    let mut total_number: u8 = 0;
+    let mut record = SpdmMeasurementRecordStructure::default();
+
+    requester.common.negotiate_info = negotiate_info;
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // the signature covers the reloaded L1/L2 as well, so it only verifies
+    // if the fixture brought back exactly what the responder has.
+    assert!(requester
+        .send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
+            &mut total_number,
+            &mut record,
+        )
+        .is_ok());
+    assert!(requester.last_measurement_verified);
+}
+
+// the known-good run: VCA, certificate, then an unsigned measurement whose
+// exchange the next signed one still covers.
+#[cfg(all(
+    feature = "transcript-fixture",
+    not(feature = "hashed-transcript-data")
+))]
+fn export_transcript_of_known_good_run(
+    device_io_requester: &mut FakeSpdmDeviceIo,
+    pcidoe_transport_encap: &mut PciDoeTransportEncap,
+) -> (SpdmTranscriptFixture, SpdmNegotiateInfo) {
+    let (req_config_info, req_provision_info) = create_info();
+    let mut requester = RequesterContext::new(
+        device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    assert!(requester.init_connection().is_ok());
+    assert!(requester.send_receive_spdm_digest(None).is_ok());
+    assert!(requester.send_receive_spdm_certificate(None, 0).is_ok());
+    let mut total_number: u8 = 0;
+    let mut record = SpdmMeasurementRecordStructure::default();
+    assert!(requester
+        .send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
+            &mut total_number,
+            &mut record,
+        )
+        .is_ok());
+
+    let fixture = requester.common.export_transcript();
+    assert!(!fixture.message_a.is_empty());
+    assert!(!fixture.message_m.is_empty());
+    (
+        fixture,
+        core::mem::take(&mut requester.common.negotiate_info),
+    )
+}
+
+#[test]
+fn test_case0_message_size_limit() {
+    let (config_info, provision_info) = create_info();
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::<512, 258>::new_with_buffer_size(
+        &mut device_io,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    // SPDM 1.1 negotiates no DataTransferSize, the 8 byte PCI DOE header
+    // and the dword alignment bound it.
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    let limit = requester.get_message_size_limit(false);
+    assert_eq!(limit.max_send_size, 248);
+    assert_eq!(limit.max_receive_size, 248);
+
+    // the DataTransferSize of each side, when smaller.
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_data_transfer_size_sel = 0x80;
+    requester.common.negotiate_info.req_data_transfer_size_sel = 0xA0;
+    let limit = requester.get_message_size_limit(false);
+    assert_eq!(limit.max_send_size, 0x80);
+    assert_eq!(limit.max_receive_size, 0xA0);
+
+    // in a session the record takes the session id, the lengths and the
+    // AEAD tag.
+    requester.common.negotiate_info.rsp_data_transfer_size_sel = 0x400;
+    requester.common.negotiate_info.req_data_transfer_size_sel = 0x400;
+    requester.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_256_GCM;
+    let limit = requester.get_message_size_limit(true);
+    assert_eq!(limit.max_send_size, 248 - 4 - 2 - 2 - 16);
+    assert_eq!(limit.max_receive_size, 248 - 4 - 2 - 2 - 16);
+
+    let (config_info, provision_info) = create_info();
+    let mut device_io = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let mctp_transport_encap = &mut MctpTransportEncap {};
+    let mut requester = RequesterContext::<512, 200>::new_with_buffer_size(
+        &mut device_io,
+        mctp_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    // MCTP adds its message type byte, and in a session a two byte
+    // sequence number and the message type byte of the inner message.
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.aead_sel = SpdmAeadAlgo::CHACHA20_POLY1305;
+    let limit = requester.get_message_size_limit(false);
+    assert_eq!(limit.max_send_size, 200 - 1);
+    let limit = requester.get_message_size_limit(true);
+    assert_eq!(limit.max_send_size, 200 - 1 - 4 - 2 - 2 - 2 - 16 - 1);
+    assert_eq!(limit.max_receive_size, 200 - 1 - 4 - 2 - 2 - 2 - 16 - 1);
+}
+
+#[test]
+fn test_case0_send_message_above_chunk_buffer() {
+    const MSG_SIZE: usize = config::MAX_SPDM_MSG_SIZE * 2;
+    let (config_info, provision_info) = create_info();
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::<MSG_SIZE, { MSG_SIZE + 64 }>::new_with_buffer_size(
+        &mut device_io,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::CHUNK_CAP;
+    requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CHUNK_CAP;
+    requester.common.negotiate_info.rsp_data_transfer_size_sel =
+        (config::MAX_SPDM_MSG_SIZE + 0x100) as u32;
+    requester.common.negotiate_info.rsp_max_spdm_msg_size_sel = MSG_SIZE as u32;
+
+    // a chunk of the message would not fit the chunk buffer.
+    let message = [0u8; config::MAX_SPDM_MSG_SIZE + 0x200];
+    assert_eq!(requester.send_message(&message), Err(SPDM_STATUS_SEND_FAIL));
+    let mut sent = [0u8; MSG_SIZE];
+    assert_eq!(shared_buffer.get_buffer(&mut sent), 0);
+}
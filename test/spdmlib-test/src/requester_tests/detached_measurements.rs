@@ -0,0 +1,162 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use spdmlib::error::{
+    SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_VERIF_FAIL,
+};
+use spdmlib::protocol::*;
+use spdmlib::requester::{
+    verify_measurement_exchange, verify_measurements_detached, SpdmDetachedMeasurementParams,
+};
+
+const CERT_CHAIN: &[u8] =
+    include_bytes!("../../../../test_key/ecp384/bundle_responder.certchain.der");
+
+// GET_MEASUREMENTS and MEASUREMENTS up to the record, VCA left out.
+const RECORD: [u8; 56] = [
+    // GET_MEASUREMENTS, SPDM 1.2, signature requested, index 1, slot 0
+    0x12, 0xe0, 0x01, 0x01, //
+    0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+    0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+    0x00, //
+    // MEASUREMENTS, one block of a 4 byte raw firmware value
+    0x12, 0x60, 0x00, 0x00, 0x01, 0x0b, 0x00, 0x00, //
+    0x01, 0x01, 0x07, 0x00, 0x81, 0x04, 0x00, 0xde, 0xad, 0xbe, 0xef,
+];
+const NONCE: [u8; SPDM_NONCE_SIZE] = [0x22; SPDM_NONCE_SIZE];
+const OPAQUE: [u8; 4] = [0x01, 0x02, 0x03, 0x04];
+// ECDSA P-384 by test_key/ecp384/end_responder.key over the above.
+const SIGNATURE: [u8; 96] = [
+    0xb6, 0xb5, 0x41, 0xb1, 0x05, 0xeb, 0xde, 0x41, 0x51, 0xfa, 0xde, 0xa9, 0x5b, 0x73, 0x0e, 0x1b,
+    0x57, 0xb2, 0x78, 0xb8, 0xa1, 0x5c, 0xe1, 0x59, 0xd1, 0x9b, 0x6d, 0x4a, 0xbc, 0xbe, 0x86, 0xa4,
+    0xf3, 0xf9, 0x40, 0x7e, 0x88, 0x97, 0x15, 0x83, 0x87, 0xae, 0x83, 0x8a, 0x52, 0xb7, 0x94, 0xd0,
+    0x6c, 0x24, 0x44, 0x0a, 0x14, 0x65, 0x81, 0xd5, 0xf5, 0x75, 0x14, 0x37, 0xdf, 0x88, 0x44, 0xc3,
+    0x32, 0x85, 0x13, 0xc1, 0x5e, 0x86, 0x3b, 0xf4, 0xe2, 0xed, 0xbc, 0x2d, 0xee, 0x50, 0xee, 0x32,
+    0xb8, 0x05, 0x79, 0x2d, 0x80, 0xae, 0x51, 0xf9, 0x22, 0x15, 0x82, 0xae, 0x9b, 0x67, 0x32, 0x46,
+];
+
+fn signature() -> SpdmSignatureStruct {
+    let mut data = [0u8; SPDM_MAX_ASYM_KEY_SIZE];
+    data[..SIGNATURE.len()].copy_from_slice(&SIGNATURE);
+    SpdmSignatureStruct {
+        data_size: SIGNATURE.len() as u16,
+        data,
+    }
+}
+
+fn verify(
+    record: &[u8],
+    nonce: &[u8; SPDM_NONCE_SIZE],
+    opaque: &[u8],
+) -> spdmlib::error::SpdmResult {
+    verify_measurements_detached(
+        SpdmVersion::SpdmVersion12,
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+        CERT_CHAIN,
+        record,
+        nonce,
+        opaque,
+        &signature(),
+    )
+}
+
+#[test]
+fn test_case0_verify_measurements_detached() {
+    assert!(verify(&RECORD, &NONCE, &OPAQUE).is_ok());
+}
+
+#[test]
+fn test_case1_verify_measurements_detached_tampered() {
+    let mut record = RECORD;
+    record[RECORD.len() - 1] ^= 1;
+    assert_eq!(
+        verify(&record, &NONCE, &OPAQUE),
+        Err(SPDM_STATUS_VERIF_FAIL)
+    );
+
+    let mut nonce = NONCE;
+    nonce[0] ^= 1;
+    assert_eq!(
+        verify(&RECORD, &nonce, &OPAQUE),
+        Err(SPDM_STATUS_VERIF_FAIL)
+    );
+
+    assert_eq!(
+        verify(&RECORD, &NONCE, &OPAQUE[..3]),
+        Err(SPDM_STATUS_VERIF_FAIL)
+    );
+}
+
+// RECORD, NONCE, OPAQUE and SIGNATURE split back into the messages that
+// were exchanged.
+const REQUEST_SIZE: usize = 4 + SPDM_NONCE_SIZE + 1;
+
+fn response() -> Vec<u8> {
+    let mut response = RECORD[REQUEST_SIZE..].to_vec();
+    response.extend_from_slice(&NONCE);
+    response.extend_from_slice(&(OPAQUE.len() as u16).to_le_bytes());
+    response.extend_from_slice(&OPAQUE);
+    response.extend_from_slice(&SIGNATURE);
+    response
+}
+
+fn verify_exchange(
+    request: &[u8],
+    response: &[u8],
+) -> spdmlib::error::SpdmResult<SpdmMeasurementRecordStructure> {
+    let params = SpdmDetachedMeasurementParams {
+        version: SpdmVersion::SpdmVersion12,
+        hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        asym_algo: SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+        message_a: &[],
+        cert_chain: CERT_CHAIN,
+    };
+    verify_measurement_exchange(&params, request, response)
+}
+
+#[test]
+fn test_case2_verify_measurement_exchange() {
+    let record = verify_exchange(&RECORD[..REQUEST_SIZE], &response()).unwrap();
+    assert_eq!(record.number_of_blocks, 1);
+    assert_eq!(record.measurement_record_length.get(), 11);
+    assert_eq!(
+        &record.measurement_record_data[..11],
+        &RECORD[REQUEST_SIZE + 8..]
+    );
+
+    // a byte of either message changed breaks the signature.
+    let mut request = RECORD[..REQUEST_SIZE].to_vec();
+    request[4] ^= 1;
+    assert_eq!(
+        verify_exchange(&request, &response()).unwrap_err(),
+        SPDM_STATUS_VERIF_FAIL
+    );
+    let mut response = response();
+    response[10] ^= 1;
+    assert_eq!(
+        verify_exchange(&RECORD[..REQUEST_SIZE], &response).unwrap_err(),
+        SPDM_STATUS_VERIF_FAIL
+    );
+
+    // so does trailing or missing data, before anything is verified.
+    let mut response = self::response();
+    response.push(0);
+    assert_eq!(
+        verify_exchange(&RECORD[..REQUEST_SIZE], &response).unwrap_err(),
+        SPDM_STATUS_INVALID_MSG_FIELD
+    );
+    assert_eq!(
+        verify_exchange(&RECORD[..REQUEST_SIZE - 1], &self::response()).unwrap_err(),
+        SPDM_STATUS_INVALID_MSG_FIELD
+    );
+
+    // an unsigned exchange has nothing to verify.
+    let mut request = RECORD[..REQUEST_SIZE].to_vec();
+    request[2] = 0;
+    assert_eq!(
+        verify_exchange(&request, &self::response()).unwrap_err(),
+        SPDM_STATUS_INVALID_PARAMETER
+    );
+}
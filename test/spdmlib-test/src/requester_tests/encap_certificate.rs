@@ -56,6 +56,7 @@ fn test_encap_handle_get_certificate() {
         },
         payload: SpdmMessagePayload::SpdmGetCertificateRequest(SpdmGetCertificateRequestPayload {
             slot_id: 0,
+            request_attribute: SpdmGetCertificateRequestAttributes::empty(),
             offset: 0,
             length: CERT_PORTION_LEN as u16,
         }),
@@ -173,6 +173,7 @@ fn test_receive_encapsulated_response_ack() {
     assert!(encap_header.encode(&mut writer).is_ok());
     let encap_payload = SpdmGetCertificateRequestPayload {
         slot_id: 0,
+        request_attribute: SpdmGetCertificateRequestAttributes::empty(),
         offset: 0,
         length: CERT_PORTION_LEN as u16,
     };
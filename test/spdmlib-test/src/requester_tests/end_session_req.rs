@@ -7,6 +7,7 @@ use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
 use spdmlib::common::session::{SpdmSession, SpdmSessionState};
+use spdmlib::common::SpdmConnectionState;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
 use spdmlib::{responder, secret};
@@ -80,6 +81,11 @@ fn test_case0_send_receive_spdm_end_session() {
         req_provision_info,
     );
 
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     let rsp_session_id = 0xffu16;
     let session_id = (0xffu32 << 16) + rsp_session_id as u32;
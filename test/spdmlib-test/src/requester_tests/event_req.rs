@@ -0,0 +1,194 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedBuffer};
+use crate::common::secret_callback::*;
+use crate::common::transport::PciDoeTransportEncap;
+use crate::common::util::create_info;
+use spdmlib::common::session::{SpdmSession, SpdmSessionState};
+use spdmlib::common::{SpdmConnectionState, SpdmContext, ST1};
+use spdmlib::config;
+use spdmlib::error::SpdmResult;
+use spdmlib::message::*;
+use spdmlib::protocol::*;
+use spdmlib::requester::RequesterContext;
+use spdmlib::{responder, secret};
+use std::sync::Mutex;
+
+fn setup_event_session(common: &mut SpdmContext, session_id: u32) {
+    common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion13;
+    common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::EVENT_CAP;
+    common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    common.session = gen_array_clone(SpdmSession::new(), 4);
+    common.session[0].setup(session_id).unwrap();
+    common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    assert!(common.session[0]
+        .set_dhe_secret(
+            SpdmVersion::SpdmVersion12,
+            SpdmDheFinalKeyStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_DHE_KEY_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(common.session[0]
+        .generate_handshake_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    assert!(common.session[0]
+        .generate_data_secret(
+            SpdmVersion::SpdmVersion12,
+            &SpdmDigestStruct {
+                data_size: 5,
+                data: Box::new([100u8; SPDM_MAX_HASH_SIZE])
+            }
+        )
+        .is_ok());
+    common.session[0].set_session_state(SpdmSessionState::SpdmSessionEstablished);
+}
+
+#[test]
+fn test_case0_send_receive_spdm_subscribe_event_types() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let session_id = (0x11u32 << 16) + 0x11u32;
+    setup_event_session(&mut responder.common, session_id);
+    responder.common.supported_event_groups = SpdmSupportedEventTypesResponsePayload {
+        supported_event_groups_count: 1,
+        supported_event_groups_list: SpdmEventListStruct::from_slice(&[0, 1, 0, 1]).unwrap(),
+    };
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    setup_event_session(&mut requester.common, session_id);
+
+    let supported_event_types = requester
+        .send_receive_spdm_get_supported_event_types(session_id)
+        .unwrap();
+    assert_eq!(supported_event_types.supported_event_groups_count, 1);
+    assert_eq!(
+        supported_event_types.supported_event_groups_list.as_slice(),
+        &[0, 1, 0, 1]
+    );
+
+    assert!(requester
+        .send_receive_spdm_subscribe_event_types(session_id, 1, &[0, 1, 0, 1])
+        .is_ok());
+    assert!(requester
+        .send_receive_spdm_subscribe_event_types(session_id, 0, &[])
+        .is_ok());
+
+    // Event support is only available from SPDM 1.3 onwards.
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    assert!(requester
+        .send_receive_spdm_get_supported_event_types(session_id)
+        .is_err());
+}
+
+static DELIVERED_EVENTS: Mutex<Vec<(u32, u32, Vec<u8>)>> = Mutex::new(Vec::new());
+
+fn record_event(session_id: u32, event_count: u32, event_list: &[u8]) -> SpdmResult {
+    DELIVERED_EVENTS
+        .lock()
+        .unwrap()
+        .push((session_id, event_count, event_list.to_vec()));
+    Ok(())
+}
+
+#[test]
+fn test_case1_receive_spdm_event() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    let session_id = (0x11u32 << 16) + 0x11u32;
+    setup_event_session(&mut responder.common, session_id);
+    setup_event_session(&mut requester.common, session_id);
+    requester.common.event_callback = Some(record_event);
+
+    // No event may be sent before the requester subscribes.
+    assert!(responder
+        .send_spdm_event(session_id, 1, &[1, 2, 3])
+        .is_err());
+
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let used = requester
+        .encode_spdm_subscribe_event_types(1, &[0, 1, 0, 1], &mut send_buffer)
+        .unwrap();
+    requester
+        .send_secured_message(session_id, &send_buffer[..used], false)
+        .unwrap();
+    assert_eq!(responder.process_message(ST1, &[0]), Ok(true));
+    let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let used = requester
+        .receive_secured_message(session_id, &mut receive_buffer, false)
+        .unwrap();
+    assert!(requester
+        .handle_spdm_subscribe_event_types_response(session_id, &receive_buffer[..used])
+        .is_ok());
+
+    assert!(responder.send_spdm_event(session_id, 1, &[1, 2, 3]).is_ok());
+    assert!(requester.receive_spdm_event(session_id).is_ok());
+    assert_eq!(responder.process_message(ST1, &[0]), Ok(true));
+
+    let delivered_events = DELIVERED_EVENTS.lock().unwrap();
+    assert_eq!(delivered_events.len(), 1);
+    assert_eq!(delivered_events[0], (session_id, 1, vec![1, 2, 3]));
+}
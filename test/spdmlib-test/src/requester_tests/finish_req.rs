@@ -7,6 +7,8 @@ use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::{create_info, get_rsp_cert_chain_buff};
 use spdmlib::common::session::{SpdmSession, SpdmSessionState};
+use spdmlib::common::SpdmConnectionState;
+use spdmlib::error::SPDM_STATUS_INVALID_STATE_PEER;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
 use spdmlib::{crypto, responder, secret};
@@ -115,6 +117,10 @@ fn test_case0_send_receive_spdm_finish() {
     requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
 
     requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
     requester.common.session = gen_array_clone(SpdmSession::new(), 4);
     requester.common.session[0].setup(4294901758).unwrap();
@@ -150,3 +156,323 @@ fn test_case0_send_receive_spdm_finish() {
     let status = requester.send_receive_spdm_finish(None, 4294901758).is_ok();
     assert!(status);
 }
+
+#[test]
+#[cfg(feature = "hashed-transcript-data")]
+fn test_case1_send_receive_spdm_finish_protection_mismatch() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.negotiate_info.req_ct_exponent_sel = 0;
+    responder.common.negotiate_info.req_capabilities_sel =
+        SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
+
+    responder.common.negotiate_info.rsp_ct_exponent_sel = 0;
+    responder.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
+
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+
+    responder.common.provision_info.my_cert_chain = [
+        Some(get_rsp_cert_chain_buff()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    ];
+
+    responder.common.reset_runtime_info();
+
+    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session[0].setup(4294901758).unwrap();
+    responder.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    responder.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
+    responder
+        .common
+        .runtime_info
+        .set_last_session_id(Some(4294901758));
+    responder.common.session[0].runtime_info.digest_context_th =
+        Some(crypto::hash::hash_ctx_init(responder.common.negotiate_info.base_hash_sel).unwrap());
+
+    let dhe_secret = SpdmDheFinalKeyStruct {
+        data_size: 48,
+        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
+    };
+    let _ = responder.common.session[0].set_dhe_secret(SpdmVersion::SpdmVersion12, dhe_secret);
+    let _ = responder.common.session[0].generate_handshake_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+    let _ = responder.common.session[0].generate_data_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    // the responder runs the handshake in the clear, the requester does not.
+    requester.common.negotiate_info.req_ct_exponent_sel = 0;
+    requester.common.negotiate_info.req_capabilities_sel =
+        SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
+
+    requester.common.negotiate_info.rsp_ct_exponent_sel = 0;
+    requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::empty();
+
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session[0].setup(4294901758).unwrap();
+    requester.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    requester.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
+    requester.common.session[0].runtime_info.digest_context_th =
+        Some(crypto::hash::hash_ctx_init(requester.common.negotiate_info.base_hash_sel).unwrap());
+
+    let dhe_secret = SpdmDheFinalKeyStruct {
+        data_size: 48,
+        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
+    };
+    let _ = requester.common.session[0].set_dhe_secret(SpdmVersion::SpdmVersion12, dhe_secret);
+    let _ = requester.common.session[0].generate_handshake_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+    let _ = requester.common.session[0].generate_data_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+    assert_eq!(
+        requester.send_receive_spdm_finish(None, 4294901758),
+        Err(SPDM_STATUS_INVALID_STATE_PEER)
+    );
+}
+
+#[test]
+#[cfg(all(feature = "hashed-transcript-data", feature = "mut-auth"))]
+fn test_case2_send_receive_spdm_finish_selected_slot() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, mut req_provision_info) = create_info();
+    req_provision_info.my_cert_chain_data[2] = req_provision_info.my_cert_chain_data[0].clone();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.negotiate_info.req_ct_exponent_sel = 0;
+    responder.common.negotiate_info.req_capabilities_sel =
+        SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
+
+    responder.common.negotiate_info.rsp_ct_exponent_sel = 0;
+    responder.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
+
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.req_asym_sel = SpdmReqAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+
+    responder.common.provision_info.my_cert_chain = [
+        Some(get_rsp_cert_chain_buff()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    ];
+
+    responder.common.peer_info.peer_cert_chain[2] = Some(get_rsp_cert_chain_buff());
+
+    responder.common.reset_runtime_info();
+
+    responder.common.session = gen_array_clone(SpdmSession::new(), 4);
+    responder.common.session[0].setup(4294901758).unwrap();
+    responder.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    responder.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
+    responder.common.session[0]
+        .set_mut_auth_requested(spdmlib::message::SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ);
+    responder
+        .common
+        .runtime_info
+        .set_last_session_id(Some(4294901758));
+    responder.common.session[0].runtime_info.digest_context_th =
+        Some(crypto::hash::hash_ctx_init(responder.common.negotiate_info.base_hash_sel).unwrap());
+
+    let dhe_secret = SpdmDheFinalKeyStruct {
+        data_size: 48,
+        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
+    };
+    let _ = responder.common.session[0].set_dhe_secret(SpdmVersion::SpdmVersion12, dhe_secret);
+    let _ = responder.common.session[0].generate_handshake_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+    let _ = responder.common.session[0].generate_data_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.req_ct_exponent_sel = 0;
+    requester.common.negotiate_info.req_capabilities_sel =
+        SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
+
+    requester.common.negotiate_info.rsp_ct_exponent_sel = 0;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
+
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.req_asym_sel = SpdmReqAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    assert!(requester.common.construct_my_cert_chain().is_ok());
+
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    requester.common.session = gen_array_clone(SpdmSession::new(), 4);
+    requester.common.session[0].setup(4294901758).unwrap();
+    requester.common.session[0].set_crypto_param(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmDheAlgo::SECP_384_R1,
+        SpdmAeadAlgo::AES_256_GCM,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE,
+    );
+    requester.common.session[0].set_session_state(SpdmSessionState::SpdmSessionHandshaking);
+    requester.common.session[0]
+        .set_mut_auth_requested(spdmlib::message::SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ);
+    requester.common.session[0].runtime_info.digest_context_th =
+        Some(crypto::hash::hash_ctx_init(requester.common.negotiate_info.base_hash_sel).unwrap());
+
+    let dhe_secret = SpdmDheFinalKeyStruct {
+        data_size: 48,
+        data: Box::new([0; SPDM_MAX_DHE_KEY_SIZE]),
+    };
+    let _ = requester.common.session[0].set_dhe_secret(SpdmVersion::SpdmVersion12, dhe_secret);
+    let _ = requester.common.session[0].generate_handshake_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+    let _ = requester.common.session[0].generate_data_secret(
+        SpdmVersion::SpdmVersion12,
+        &SpdmDigestStruct {
+            data_size: 48,
+            data: Box::new([0; SPDM_MAX_HASH_SIZE]),
+        },
+    );
+
+    // the responder only knows the requester's chain in slot 2.
+    assert_eq!(
+        requester.select_mut_auth_slot(Some(SPDM_MAX_SLOT_NUMBER as u8)),
+        Err(spdmlib::error::SPDM_STATUS_INVALID_PARAMETER)
+    );
+    assert_eq!(
+        requester.select_mut_auth_slot(Some(1)),
+        Err(spdmlib::error::SPDM_STATUS_INVALID_PARAMETER)
+    );
+    assert!(requester.select_mut_auth_slot(Some(2)).is_ok());
+
+    assert!(requester.send_receive_spdm_finish(None, 4294901758).is_ok());
+    assert_eq!(
+        requester
+            .common
+            .runtime_info
+            .get_local_used_cert_chain_slot_id(),
+        2
+    );
+}
@@ -6,10 +6,12 @@ use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedB
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
-use spdmlib::common::SpdmConnectionState;
+use codec::{Codec, Reader};
+use spdmlib::common::{SpdmCodec, SpdmConnectionState};
+use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
-use spdmlib::{responder, secret};
+use spdmlib::{config, responder, secret};
 
 #[test]
 fn test_case0_send_receive_spdm_capability() {
@@ -44,6 +46,10 @@ fn test_case0_send_receive_spdm_capability() {
     );
 
     requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterVersion);
     requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
@@ -52,3 +58,64 @@ fn test_case0_send_receive_spdm_capability() {
     let status = requester.send_receive_spdm_capability().is_ok();
     assert!(status);
 }
+
+#[test]
+fn test_case1_encode_spdm_capability_derived_from_config() {
+    let (mut req_config_info, req_provision_info) = create_info();
+    req_config_info.req_capabilities = SpdmRequestCapabilityFlags::empty();
+
+    let expected = SpdmRequestCapabilityFlags::CERT_CAP
+        | SpdmRequestCapabilityFlags::CHAL_CAP
+        | SpdmRequestCapabilityFlags::ENCRYPT_CAP
+        | SpdmRequestCapabilityFlags::MAC_CAP
+        | SpdmRequestCapabilityFlags::KEY_EX_CAP
+        | SpdmRequestCapabilityFlags::PSK_CAP
+        | SpdmRequestCapabilityFlags::HBEAT_CAP
+        | SpdmRequestCapabilityFlags::KEY_UPD_CAP;
+    #[cfg(feature = "mut-auth")]
+    let expected =
+        expected | SpdmRequestCapabilityFlags::MUT_AUTH_CAP | SpdmRequestCapabilityFlags::ENCAP_CAP;
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    assert_eq!(requester.common.config_info.req_capabilities, expected);
+
+    // the derived flags go out in GET_CAPABILITIES.
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester.encode_spdm_capability(&mut send_buffer).unwrap();
+    let mut reader = Reader::init(&send_buffer[..send_used]);
+    let message_header = SpdmMessageHeader::read(&mut reader).unwrap();
+    assert_eq!(
+        message_header.request_response_code,
+        SpdmRequestResponseCode::SpdmRequestGetCapabilities
+    );
+    let get_capabilities =
+        SpdmGetCapabilitiesRequestPayload::spdm_read(&mut requester.common, &mut reader).unwrap();
+    assert_eq!(get_capabilities.flags, expected);
+
+    // explicitly configured capabilities are advertised as they are.
+    let (mut req_config_info, req_provision_info) = create_info();
+    req_config_info.req_capabilities =
+        SpdmRequestCapabilityFlags::CERT_CAP | SpdmRequestCapabilityFlags::CHAL_CAP;
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester2 = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let requester = RequesterContext::new(
+        &mut device_io_requester2,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    assert_eq!(
+        requester.common.config_info.req_capabilities,
+        SpdmRequestCapabilityFlags::CERT_CAP | SpdmRequestCapabilityFlags::CHAL_CAP
+    );
+}
@@ -6,7 +6,13 @@ use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedB
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::{create_info, get_rsp_cert_chain_buff};
-use spdmlib::common::SpdmConnectionState;
+use spdmlib::common::{SpdmCertChainTrust, SpdmConnectionState};
+use spdmlib::crypto;
+use spdmlib::error::{
+    SPDM_STATUS_CERT_DIGEST_MISMATCH, SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_PARAMETER,
+    SPDM_STATUS_UNSUPPORTED_CAP, SPDM_STATUS_WEAK_CERT_KEY,
+};
+use spdmlib::message::SpdmRequestResponseCode;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
 use spdmlib::{responder, secret};
@@ -60,9 +66,783 @@ fn test_case0_send_receive_spdm_certificate() {
         req_provision_info,
     );
 
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
 
     let status = requester.send_receive_spdm_certificate(None, 0).is_ok();
     assert!(status);
 }
+
+#[test]
+#[cfg(feature = "hashed-transcript-data")]
+fn test_case1_send_receive_spdm_certificate_version12() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+
+    assert!(requester.send_receive_spdm_certificate(None, 0).is_ok());
+    assert_eq!(
+        requester.common.peer_info.peer_cert_chain[0]
+            .as_ref()
+            .unwrap()
+            .as_ref(),
+        get_rsp_cert_chain_buff().as_ref()
+    );
+
+    // a CERTIFICATE response carrying SPDM 1.1 is refused under 1.2.
+    let send_buffer = [
+        SpdmVersion::SpdmVersion12.get_u8(),
+        SpdmRequestResponseCode::SpdmRequestGetCertificate.get_u8(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    let receive_buffer = [
+        SpdmVersion::SpdmVersion11.get_u8(),
+        SpdmRequestResponseCode::SpdmResponseCertificate.get_u8(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    assert!(requester
+        .handle_spdm_certificate_partial_response(
+            None,
+            0,
+            0,
+            0,
+            0x10,
+            &send_buffer,
+            &receive_buffer
+        )
+        .is_err());
+}
+
+#[test]
+#[cfg(feature = "hashed-transcript-data")]
+fn test_case2_send_receive_spdm_certificate_small_portion() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (mut req_config_info, req_provision_info) = create_info();
+    req_config_info.cert_portion_length = 0x40;
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+
+    // the configured length wins while it fits the transfer size.
+    requester.common.negotiate_info.req_data_transfer_size_sel = 0x1200;
+    assert_eq!(requester.cert_portion_length(), 0x40);
+    requester.common.negotiate_info.req_data_transfer_size_sel = 0x30;
+    assert_eq!(requester.cert_portion_length(), 0x28);
+    requester.common.negotiate_info.req_data_transfer_size_sel = 4;
+    assert_eq!(requester.cert_portion_length(), 1);
+    requester.common.negotiate_info.req_data_transfer_size_sel = 0;
+    assert_eq!(requester.cert_portion_length(), 0x40);
+
+    assert!(requester.send_receive_spdm_certificate(None, 0).is_ok());
+    assert_eq!(
+        requester.common.peer_info.peer_cert_chain[0]
+            .as_ref()
+            .unwrap()
+            .as_ref(),
+        get_rsp_cert_chain_buff().as_ref()
+    );
+}
+
+#[test]
+fn test_case3_handle_spdm_certificate_near_offset_limit() {
+    let (req_config_info, req_provision_info) = create_info();
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.peer_info.peer_cert_chain_temp = Some(SpdmCertChainBuffer::default());
+
+    let send_buffer = [
+        SpdmVersion::SpdmVersion12.get_u8(),
+        SpdmRequestResponseCode::SpdmRequestGetCertificate.get_u8(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    let certificate_response = |portion_length: u16, remainder_length: u16| {
+        let mut receive_buffer = vec![
+            SpdmVersion::SpdmVersion12.get_u8(),
+            SpdmRequestResponseCode::SpdmResponseCertificate.get_u8(),
+            0,
+            0,
+        ];
+        receive_buffer.extend_from_slice(&portion_length.to_le_bytes());
+        receive_buffer.extend_from_slice(&remainder_length.to_le_bytes());
+        receive_buffer.resize(receive_buffer.len() + portion_length as usize, 0x5a);
+        receive_buffer
+    };
+    // the transcript is not touched for a secured exchange.
+    let session_id = Some(0xFFFE_FFFE);
+
+    // a response that stays inside the buffer is taken.
+    assert_eq!(
+        requester.handle_spdm_certificate_partial_response(
+            session_id,
+            0,
+            0,
+            0,
+            0x10,
+            &send_buffer,
+            &certificate_response(0x10, 0x20),
+        ),
+        Ok((0x10, 0x20))
+    );
+
+    // the portion would wrap the 16-bit offset.
+    assert!(requester
+        .handle_spdm_certificate_partial_response(
+            session_id,
+            0,
+            0,
+            0xFFF0,
+            0x20,
+            &send_buffer,
+            &certificate_response(0x20, 0),
+        )
+        .is_err());
+    // the remainder would wrap the 16-bit total.
+    assert!(requester
+        .handle_spdm_certificate_partial_response(
+            session_id,
+            0,
+            0,
+            0xFF00,
+            0x10,
+            &send_buffer,
+            &certificate_response(0x10, 0xFF),
+        )
+        .is_err());
+    assert!(requester
+        .handle_spdm_certificate_partial_response(
+            session_id,
+            0,
+            0,
+            0,
+            0x10,
+            &send_buffer,
+            &certificate_response(0x10, u16::MAX),
+        )
+        .is_err());
+}
+
+#[test]
+fn test_case4_verify_spdm_certificate_chain_min_rsa_key_bits() {
+    let (mut req_config_info, req_provision_info) = create_info();
+    req_config_info.min_rsa_key_bits = 3072;
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.provision_info.peer_root_cert_data = None;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+
+    let cert_chain_buffer = |cert_chain: &[u8]| {
+        let (root_cert_begin, root_cert_end) =
+            crypto::cert_operation::get_cert_from_cert_chain(cert_chain, 0).unwrap();
+        let root_cert_hash = crypto::hash::hash_all(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            &cert_chain[root_cert_begin..root_cert_end],
+        )
+        .unwrap();
+        SpdmCertChainBuffer::new(cert_chain, root_cert_hash.as_ref()).unwrap()
+    };
+    let rsa2048_chain =
+        include_bytes!("../../../../test_key/rsa2048/bundle_responder.certchain.der");
+    let rsa3072_chain =
+        include_bytes!("../../../../test_key/rsa3072/bundle_responder.certchain.der");
+
+    // an undersized RSA leaf is refused even though RSA 2048 was negotiated.
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_RSASSA_2048;
+    requester.common.peer_info.peer_cert_chain_temp = Some(cert_chain_buffer(rsa2048_chain));
+    assert_eq!(
+        requester.verify_spdm_certificate_chain(),
+        Err(SPDM_STATUS_WEAK_CERT_KEY)
+    );
+
+    requester.common.config_info.min_rsa_key_bits = 0;
+    assert!(requester.verify_spdm_certificate_chain().is_ok());
+
+    requester.common.config_info.min_rsa_key_bits = 3072;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072;
+    requester.common.peer_info.peer_cert_chain_temp = Some(cert_chain_buffer(rsa3072_chain));
+    assert!(requester.verify_spdm_certificate_chain().is_ok());
+
+    // the floor does not apply to other key types.
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.peer_info.peer_cert_chain_temp = Some(get_rsp_cert_chain_buff());
+    assert!(requester.verify_spdm_certificate_chain().is_ok());
+}
+
+#[test]
+#[cfg(feature = "hashed-transcript-data")]
+fn test_case5_send_receive_spdm_certificate_self_signed() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    // a chain of nothing but a self-signed certificate.
+    let self_signed_cert = include_bytes!("../../../../test_key/ecp384/ca.cert.der");
+    let cert_hash =
+        crypto::hash::hash_all(SpdmBaseHashAlgo::TPM_ALG_SHA_384, self_signed_cert).unwrap();
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.provision_info.my_cert_chain[0] =
+        Some(SpdmCertChainBuffer::new(self_signed_cert, cert_hash.as_ref()).unwrap());
+
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.provision_info.peer_root_cert_data = None;
+
+    // refused unless self-signed certificates are allowed.
+    assert_eq!(
+        requester.send_receive_spdm_certificate(None, 0),
+        Err(SPDM_STATUS_INVALID_CERT)
+    );
+    assert!(requester.common.peer_info.peer_cert_chain[0].is_none());
+    assert!(requester.common.peer_info.peer_cert_chain_trust[0].is_none());
+
+    requester.common.config_info.allow_self_signed_peer_cert = true;
+    assert!(requester.send_receive_spdm_certificate(None, 0).is_ok());
+    assert!(requester.common.peer_info.peer_cert_chain[0].is_some());
+    assert_eq!(
+        requester.common.peer_info.peer_cert_chain_trust[0],
+        Some(SpdmCertChainTrust::SelfSignedUnverifiedIssuer)
+    );
+
+    // it must still verify with its own key.
+    let mut tampered = self_signed_cert.to_vec();
+    let len = tampered.len();
+    tampered[len - 2] ^= 0x01;
+    let tampered_hash =
+        crypto::hash::hash_all(SpdmBaseHashAlgo::TPM_ALG_SHA_384, &tampered).unwrap();
+    requester.common.peer_info.peer_cert_chain_temp =
+        Some(SpdmCertChainBuffer::new(&tampered, tampered_hash.as_ref()).unwrap());
+    assert_eq!(
+        requester.verify_spdm_certificate_chain_trust(),
+        Err(SPDM_STATUS_INVALID_CERT)
+    );
+
+    // a full chain is still reported as such.
+    requester.common.peer_info.peer_cert_chain_temp = Some(get_rsp_cert_chain_buff());
+    assert_eq!(
+        requester.verify_spdm_certificate_chain_trust(),
+        Ok(SpdmCertChainTrust::Chained)
+    );
+}
+
+#[test]
+#[cfg(feature = "hashed-transcript-data")]
+fn test_case6_send_receive_spdm_certificate_in_chunks() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::CHUNK_CAP;
+    responder.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CHUNK_CAP;
+    responder.common.negotiate_info.req_data_transfer_size_sel = 0x100;
+    responder.common.negotiate_info.req_max_spdm_msg_size_sel = 0x1200;
+    responder.common.negotiate_info.rsp_data_transfer_size_sel = 0x100;
+    responder.common.negotiate_info.rsp_max_spdm_msg_size_sel = 0x1200;
+    responder.common.provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::CHUNK_CAP;
+    requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CHUNK_CAP;
+    requester.common.negotiate_info.req_data_transfer_size_sel = 0x100;
+    requester.common.negotiate_info.req_max_spdm_msg_size_sel = 0x1200;
+    requester.common.negotiate_info.rsp_data_transfer_size_sel = 0x100;
+    requester.common.negotiate_info.rsp_max_spdm_msg_size_sel = 0x1200;
+
+    // portions larger than a transfer come back as CHUNK_RESPONSEs.
+    assert!(requester.cert_portion_length() > 0x100);
+    assert!(requester.send_receive_spdm_certificate(None, 0).is_ok());
+    assert_eq!(
+        requester.common.peer_info.peer_cert_chain[0]
+            .as_ref()
+            .unwrap()
+            .as_ref(),
+        get_rsp_cert_chain_buff().as_ref()
+    );
+}
+
+#[test]
+#[cfg(feature = "hashed-transcript-data")]
+fn test_case7_verify_peer_cert_chain_trust_anchors() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.provision_info.my_cert_chain = [
+        Some(get_rsp_cert_chain_buff()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    ];
+
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+
+    assert!(requester.send_receive_spdm_certificate(None, 0).is_ok());
+
+    let other_root: &[u8] = include_bytes!("../../../../test_key/ecp256/ca.cert.der");
+    let root: &[u8] = include_bytes!("../../../../test_key/ecp384/ca.cert.der");
+    assert_eq!(
+        requester.verify_peer_cert_chain_trust_anchors(0, &[other_root, root]),
+        Ok(1)
+    );
+    assert_eq!(
+        requester.verify_peer_cert_chain_trust_anchors(0, &[other_root]),
+        Err(SPDM_STATUS_INVALID_CERT)
+    );
+    assert_eq!(
+        requester.verify_peer_cert_chain_trust_anchors(1, &[other_root, root]),
+        Err(SPDM_STATUS_INVALID_PARAMETER)
+    );
+}
+
+#[test]
+#[cfg(feature = "hashed-transcript-data")]
+fn test_case8_send_receive_spdm_certificate_version11_and_version13() {
+    for version in [SpdmVersion::SpdmVersion11, SpdmVersion::SpdmVersion13] {
+        let (mut rsp_config_info, rsp_provision_info) = create_info();
+        let (mut req_config_info, req_provision_info) = create_info();
+        rsp_config_info.cert_model[1] = SpdmCertificateModel::SpdmCertModelDeviceCert;
+        req_config_info.message_capture_capacity = 2;
+
+        let shared_buffer = SharedBuffer::new();
+        let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+        secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+        let mut responder = responder::ResponderContext::new(
+            &mut device_io_responder,
+            pcidoe_transport_encap,
+            rsp_config_info,
+            rsp_provision_info,
+        );
+        responder.common.reset_runtime_info();
+        responder.common.negotiate_info.spdm_version_sel = version;
+        responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        responder.common.negotiate_info.base_asym_sel =
+            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+        responder.common.provision_info.my_cert_chain[1] = Some(get_rsp_cert_chain_buff());
+        responder
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+        let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+        let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+        let mut requester = RequesterContext::new(
+            &mut device_io_requester,
+            pcidoe_transport_encap2,
+            req_config_info,
+            req_provision_info,
+        );
+        requester
+            .common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+        requester.common.negotiate_info.spdm_version_sel = version;
+        requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        requester.common.negotiate_info.base_asym_sel =
+            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+
+        let expected = get_rsp_cert_chain_buff();
+        if version == SpdmVersion::SpdmVersion13 {
+            assert_eq!(
+                requester.send_receive_spdm_certificate_slot_size(None, 1),
+                Ok(expected.data_size)
+            );
+        } else {
+            assert_eq!(
+                requester.send_receive_spdm_certificate_slot_size(None, 1),
+                Err(SPDM_STATUS_UNSUPPORTED_CAP)
+            );
+        }
+
+        assert!(requester.send_receive_spdm_certificate(None, 1).is_ok());
+        let request = requester.common.message_capture.last_sent().unwrap();
+        assert_eq!(request[0], version.get_u8());
+        assert_eq!(&request[2..4], &[1, 0]);
+        assert_eq!(
+            requester.common.peer_info.peer_cert_chain[1]
+                .as_ref()
+                .unwrap()
+                .as_ref(),
+            expected.as_ref()
+        );
+        if version == SpdmVersion::SpdmVersion13 {
+            assert_eq!(
+                requester.common.peer_info.peer_cert_model[1],
+                Some(SpdmCertificateModel::SpdmCertModelDeviceCert)
+            );
+        } else {
+            assert!(requester.common.peer_info.peer_cert_model[1].is_none());
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "hashed-transcript-data")]
+fn test_case9_send_receive_spdm_certificate_digest_mismatch() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+
+    assert!(requester.send_receive_spdm_digest(None).is_ok());
+    assert!(requester.common.peer_info.peer_digests[0].is_some());
+    assert!(requester.common.peer_info.peer_digests[1].is_none());
+    assert!(requester.send_receive_spdm_certificate(None, 0).is_ok());
+
+    // as if DIGESTS had advertised another chain than the one served.
+    requester.common.peer_info.peer_cert_chain[0] = None;
+    requester.common.peer_info.peer_digests[0]
+        .as_mut()
+        .unwrap()
+        .data[0] ^= 0xff;
+    assert_eq!(
+        requester.send_receive_spdm_certificate(None, 0),
+        Err(SPDM_STATUS_CERT_DIGEST_MISMATCH)
+    );
+    assert!(requester.common.peer_info.peer_cert_chain[0].is_none());
+}
+
+#[test]
+fn test_case10_decode_only_certificate_response() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    // no backend could make sense of it: neither header nor certificates.
+    let mut cert_chain = SpdmCertChainBuffer {
+        data_size: 512,
+        ..Default::default()
+    };
+    cert_chain.data[..512].fill(0x5a);
+    responder.common.provision_info.my_cert_chain[0] = Some(cert_chain.clone());
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+
+    assert_eq!(
+        requester.send_receive_spdm_certificate(None, 0),
+        Err(SPDM_STATUS_INVALID_CERT)
+    );
+    assert!(requester.common.peer_info.peer_cert_chain[0].is_none());
+
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.decode_only = true;
+    assert!(requester.send_receive_spdm_certificate(None, 0).is_ok());
+    assert_eq!(
+        requester.common.peer_info.peer_cert_chain[0]
+            .as_ref()
+            .unwrap()
+            .as_ref(),
+        cert_chain.as_ref()
+    );
+    assert_eq!(
+        requester.common.peer_info.peer_cert_chain_trust[0],
+        Some(SpdmCertChainTrust::Unverified)
+    );
+    // message B was never recorded.
+    #[cfg(feature = "hashed-transcript-data")]
+    assert!(requester.common.runtime_info.digest_context_m1m2.is_none());
+    #[cfg(not(feature = "hashed-transcript-data"))]
+    assert!(requester.common.runtime_info.message_b.as_ref().is_empty());
+}
@@ -6,7 +6,10 @@ use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedB
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
-use spdmlib::common::SpdmConnectionState;
+use codec::Writer;
+use spdmlib::common::{SpdmCodec, SpdmConnectionState};
+use spdmlib::error::{SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_STATE_PEER};
+use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
 use spdmlib::{config, responder, secret};
@@ -58,8 +61,159 @@ fn test_case0_send_receive_spdm_digest() {
         req_config_info,
         req_provision_info,
     );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
 
     let status = requester.send_receive_spdm_digest(None).is_ok();
     assert!(status);
 }
+
+#[test]
+fn test_case1_handle_spdm_digest_with_empty_slot() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // slots 0 and 2 are masked, but slot 2 reports an all-zero digest.
+    let mut digests = gen_array_clone(
+        SpdmDigestStruct::from(&[0u8; SHA384_DIGEST_SIZE][..]),
+        SPDM_MAX_SLOT_NUMBER,
+    );
+    digests[0] = SpdmDigestStruct::from(&[0x5au8; SHA384_DIGEST_SIZE][..]);
+    let response = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion12,
+            request_response_code: SpdmRequestResponseCode::SpdmResponseDigests,
+        },
+        payload: SpdmMessagePayload::SpdmDigestsResponse(SpdmDigestsResponsePayload {
+            slot_mask: 0b0000_0101,
+            digests,
+            ..Default::default()
+        }),
+    };
+    let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let mut writer = Writer::init(&mut receive_buffer);
+    let used = response
+        .spdm_encode(&mut requester.common, &mut writer)
+        .unwrap();
+
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester.encode_spdm_digest(&mut send_buffer).unwrap();
+    assert!(requester
+        .handle_spdm_digest_response(None, &send_buffer[..send_used], &receive_buffer[..used])
+        .is_ok());
+    assert_eq!(
+        requester.common.peer_info.peer_provisioned_slot_mask,
+        Some(0b0000_0001)
+    );
+
+    // neither the empty slot nor an unmasked one is fetched.
+    for slot_id in [1u8, 2] {
+        assert_eq!(
+            requester.send_receive_spdm_certificate(None, slot_id),
+            Err(SPDM_STATUS_INVALID_STATE_PEER)
+        );
+    }
+    let mut sent = [0u8; config::MAX_SPDM_MSG_SIZE];
+    assert_eq!(shared_buffer.get_buffer(&mut sent), 0);
+}
+
+#[test]
+fn test_case2_handle_spdm_digest_slots_with_malformed_slot() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // slots 0, 1 and 3 are masked, the digest of slot 3 is cut short.
+    let mut digests = gen_array_clone(
+        SpdmDigestStruct::from(&[0u8; SHA384_DIGEST_SIZE][..]),
+        SPDM_MAX_SLOT_NUMBER,
+    );
+    digests[0] = SpdmDigestStruct::from(&[0x5au8; SHA384_DIGEST_SIZE][..]);
+    digests[1] = SpdmDigestStruct::from(&[0x6bu8; SHA384_DIGEST_SIZE][..]);
+    digests[2] = SpdmDigestStruct::from(&[0x7cu8; SHA384_DIGEST_SIZE][..]);
+    let response = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion12,
+            request_response_code: SpdmRequestResponseCode::SpdmResponseDigests,
+        },
+        payload: SpdmMessagePayload::SpdmDigestsResponse(SpdmDigestsResponsePayload {
+            slot_mask: 0b0000_1011,
+            digests,
+            ..Default::default()
+        }),
+    };
+    let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let mut writer = Writer::init(&mut receive_buffer);
+    let used = response
+        .spdm_encode(&mut requester.common, &mut writer)
+        .unwrap()
+        - 5;
+
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester.encode_spdm_digest(&mut send_buffer).unwrap();
+    assert_eq!(
+        requester.handle_spdm_digest_response(
+            None,
+            &send_buffer[..send_used],
+            &receive_buffer[..used]
+        ),
+        Err(SPDM_STATUS_INVALID_MSG_FIELD)
+    );
+
+    requester.common.reset_message_b();
+    let slot_results = requester
+        .handle_spdm_digest_slots_response(None, &send_buffer[..send_used], &receive_buffer[..used])
+        .unwrap();
+    assert_eq!(slot_results.slot_mask, 0b0000_1011);
+    assert_eq!(slot_results.good_slot_mask(), 0b0000_0011);
+    assert_eq!(
+        slot_results.failed_slot(),
+        Some((3, SPDM_STATUS_INVALID_MSG_FIELD))
+    );
+    match &slot_results.slots[1] {
+        Some(Ok(digest)) => assert_eq!(digest.as_ref(), &[0x6bu8; SHA384_DIGEST_SIZE][..]),
+        _ => panic!("slot 1 should hold a digest"),
+    }
+    assert!(slot_results.slots[2].is_none());
+    assert_eq!(
+        requester.common.peer_info.peer_provisioned_slot_mask,
+        Some(0b0000_0011)
+    );
+}
@@ -6,11 +6,29 @@ use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedB
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::{create_info, get_rsp_cert_chain_buff};
-use spdmlib::common::SpdmConnectionState;
-use spdmlib::message::{SpdmMeasurementAttributes, SpdmMeasurementOperation};
+use codec::{u24, Codec, Reader, Writer};
+use spdmlib::common::opaque::SpdmOpaqueStruct;
+use spdmlib::common::{
+    ManagedBufferL1L2, SpdmCodec, SpdmConnectionState, SpdmDeviceIo, SpdmMeasurementContentChanged,
+    SpdmTranscriptStorage, SpdmTransportEncap, SPDM_NONCE_HISTORY_SIZE,
+};
+use spdmlib::error::{
+    SpdmResult, SPDM_STATUS_CONTENT_CHANGE_UNSUPPORTED_PEER, SPDM_STATUS_CRYPTO_ERROR,
+    SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_MEAS_COUNT_MISMATCH,
+    SPDM_STATUS_MEAS_INVALID_INDEX, SPDM_STATUS_MEAS_RAW_BIT_STREAM_UNAVAILABLE,
+    SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE, SPDM_STATUS_NONCE_ECHO_PEER,
+    SPDM_STATUS_NONCE_REUSE_PEER, SPDM_STATUS_VERIF_FAIL,
+};
+use spdmlib::message::{
+    SpdmDigestsResponsePayload, SpdmMeasurementAttributes, SpdmMeasurementOperation,
+    SpdmMeasurementsResponsePayload, SpdmMessage, SpdmMessageHeader, SpdmMessagePayload,
+    SpdmRequestResponseCode,
+};
 use spdmlib::protocol::*;
-use spdmlib::requester::RequesterContext;
-use spdmlib::{config, responder, secret};
+use spdmlib::requester::{RequesterContext, SpdmAttestationReport, SpdmSlotErrorPolicy};
+use spdmlib::{config, crypto, responder, secret};
+use std::cell::RefCell;
+use std::rc::Rc;
 
 #[test]
 fn test_case0_send_receive_spdm_measurement() {
@@ -87,7 +105,8 @@ fn test_case0_send_receive_spdm_measurement() {
     requester.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::CERT_CAP;
 
     requester.common.negotiate_info.rsp_ct_exponent_sel = 0;
-    requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
     requester
         .common
         .negotiate_info
@@ -98,6 +117,10 @@ fn test_case0_send_receive_spdm_measurement() {
     requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
     requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
     requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
     let measurement_operation = SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber;
     let mut total_number: u8 = 0;
@@ -108,6 +131,7 @@ fn test_case0_send_receive_spdm_measurement() {
             0,
             SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
             measurement_operation,
+            None,
             &mut total_number,
             &mut spdm_measurement_record_structure,
         )
@@ -121,6 +145,7 @@ fn test_case0_send_receive_spdm_measurement() {
             0,
             SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
             measurement_operation,
+            None,
             &mut total_number,
             &mut spdm_measurement_record_structure,
         )
@@ -134,9 +159,2304 @@ fn test_case0_send_receive_spdm_measurement() {
             0,
             SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
             measurement_operation,
+            None,
             &mut total_number,
             &mut spdm_measurement_record_structure,
         )
         .is_ok();
     assert!(status);
 }
+
+#[test]
+fn test_case1_encode_spdm_measurement_record_with_nonce() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+
+    let nonce = [0x5au8; SPDM_NONCE_SIZE];
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let used = requester
+        .encode_spdm_measurement_record(
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            Some(&nonce),
+            0,
+            &mut send_buffer,
+        )
+        .unwrap();
+
+    let mut reader = Reader::init(&send_buffer[..used]);
+    let request = SpdmMessage::spdm_read(&mut requester.common, &mut reader).unwrap();
+    match request.payload {
+        SpdmMessagePayload::SpdmGetMeasurementsRequest(payload) => {
+            assert_eq!(payload.nonce.data, nonce);
+        }
+        _ => panic!("unexpected request"),
+    }
+
+    let status = requester.encode_spdm_measurement_record(
+        SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+        SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+        Some(&nonce[..SPDM_NONCE_SIZE - 1]),
+        0,
+        &mut send_buffer,
+    );
+    assert_eq!(status, Err(SPDM_STATUS_INVALID_PARAMETER));
+}
+
+#[test]
+fn test_case2_calc_message_l1l2_hash() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+
+    let message_a = [0x11u8; 16];
+    let message_m1 = [0x22u8; 40];
+    let message_m2 = [0x33u8; 80];
+    requester.common.append_message_a(&message_a).unwrap();
+    requester
+        .common
+        .append_message_m(None, &message_m1)
+        .unwrap();
+    requester
+        .common
+        .append_message_m(None, &message_m2)
+        .unwrap();
+
+    // Both the buffered and the hashed transcript must hash VCA || L1 || L2.
+    let mut message_l1l2 = Vec::new();
+    message_l1l2.extend_from_slice(&message_a);
+    message_l1l2.extend_from_slice(&message_m1);
+    message_l1l2.extend_from_slice(&message_m2);
+    let expected =
+        crypto::hash::hash_all(SpdmBaseHashAlgo::TPM_ALG_SHA_384, &message_l1l2).unwrap();
+
+    let message_l1l2_hash = requester.common.calc_message_l1l2_hash(None).unwrap();
+    assert_eq!(message_l1l2_hash.as_ref(), expected.as_ref());
+
+    // Computing the hash must not consume the transcript.
+    let message_l1l2_hash = requester.common.calc_message_l1l2_hash(None).unwrap();
+    assert_eq!(message_l1l2_hash.as_ref(), expected.as_ref());
+
+    #[cfg(feature = "hashed-transcript-data")]
+    assert!(matches!(
+        requester.common.calc_message_l1l2_hash(Some(0xdeadbeef)),
+        Err(SPDM_STATUS_INVALID_MSG_FIELD)
+    ));
+    #[cfg(not(feature = "hashed-transcript-data"))]
+    assert!(matches!(
+        requester.common.calc_message_l1l2_hash(Some(0xdeadbeef)),
+        Err(SPDM_STATUS_INVALID_PARAMETER)
+    ));
+}
+
+fn encode_block(measurement_block: &SpdmMeasurementBlockStructure) -> Vec<u8> {
+    let mut buffer = [0u8; config::MAX_SPDM_MEASUREMENT_RECORD_SIZE];
+    let mut writer = Writer::init(&mut buffer);
+    measurement_block.encode(&mut writer).unwrap();
+    writer.used_slice().to_vec()
+}
+
+#[test]
+fn test_case3_fetch_all_measurements() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP;
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder.common.provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    let measurement_blocks = requester.fetch_all_measurements(None, 0).unwrap();
+
+    // every block must match what the responder serves for its index.
+    let collect = |measurement_index: usize| {
+        (SECRET_MEASUREMENT_IMPL_INSTANCE.measurement_collection_cb)(
+            SpdmVersion::SpdmVersion12,
+            SpdmMeasurementSpecification::DMTF,
+            SpdmMeasurementHashAlgo::TPM_ALG_SHA_384,
+            measurement_index,
+        )
+        .unwrap()
+    };
+    let expected_record =
+        collect(SpdmMeasurementOperation::SpdmMeasurementRequestAll.get_u8() as usize);
+    assert_eq!(
+        measurement_blocks.len(),
+        expected_record.number_of_blocks as usize
+    );
+    let mut reader = Reader::init(
+        &expected_record.measurement_record_data
+            [..expected_record.measurement_record_length.get() as usize],
+    );
+    for measurement_block in measurement_blocks.iter() {
+        let expected_block = SpdmMeasurementBlockStructure::read(&mut reader).unwrap();
+        assert_eq!(
+            encode_block(measurement_block),
+            encode_block(&expected_block)
+        );
+    }
+}
+
+#[test]
+fn test_case4_reject_signed_measurement_with_key_exchange_only_slot() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion13;
+    requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP
+        | SpdmResponseCapabilityFlags::MEAS_CAP_SIG
+        | SpdmResponseCapabilityFlags::MULTI_KEY_CAP_ONLY;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // the DIGESTS of slots 0 and 1 limits the key of slot 1 to KEY_EXCHANGE.
+    let mut key_usage_mask = [SpdmKeyUsageMask::empty(); SPDM_MAX_SLOT_NUMBER];
+    key_usage_mask[0] = SpdmKeyUsageMask::MEASUREMENT_USE;
+    key_usage_mask[1] = SpdmKeyUsageMask::KEY_EX_USE;
+    let response = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion13,
+            request_response_code: SpdmRequestResponseCode::SpdmResponseDigests,
+        },
+        payload: SpdmMessagePayload::SpdmDigestsResponse(SpdmDigestsResponsePayload {
+            slot_mask: 0b0000_0011,
+            digests: gen_array_clone(
+                SpdmDigestStruct::from(&[0x5au8; SHA384_DIGEST_SIZE][..]),
+                SPDM_MAX_SLOT_NUMBER,
+            ),
+            key_usage_mask,
+            ..Default::default()
+        }),
+    };
+    let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let mut writer = Writer::init(&mut receive_buffer);
+    let used = response
+        .spdm_encode(&mut requester.common, &mut writer)
+        .unwrap();
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester.encode_spdm_digest(&mut send_buffer).unwrap();
+    assert!(requester
+        .handle_spdm_digest_response(None, &send_buffer[..send_used], &receive_buffer[..used])
+        .is_ok());
+    assert_eq!(
+        requester.common.peer_info.peer_key_usage_mask[1],
+        Some(SpdmKeyUsageMask::KEY_EX_USE)
+    );
+    requester.common.peer_info.peer_cert_chain[1] = Some(get_rsp_cert_chain_buff());
+
+    let mut total_number = 0u8;
+    let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+    let status = requester.send_receive_spdm_measurement(
+        None,
+        1,
+        SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+        SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+        None,
+        &mut total_number,
+        &mut spdm_measurement_record_structure,
+    );
+    assert_eq!(status, Err(SPDM_STATUS_INVALID_PARAMETER));
+
+    // the request was refused before anything went out.
+    let mut sent = [0u8; config::MAX_SPDM_MSG_SIZE];
+    assert_eq!(shared_buffer.get_buffer(&mut sent), 0);
+}
+
+#[test]
+fn test_case5_send_receive_spdm_measurement_ed25519() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let ed25519_cert_chain_buff = |cert_chain: &[u8]| {
+        let (root_cert_begin, root_cert_end) =
+            crypto::cert_operation::get_cert_from_cert_chain(cert_chain, 0).unwrap();
+        let root_cert_hash = crypto::hash::hash_all(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            &cert_chain[root_cert_begin..root_cert_end],
+        )
+        .unwrap();
+        SpdmCertChainBuffer::new(cert_chain, root_cert_hash.as_ref()).unwrap()
+    };
+    let responder_chain =
+        include_bytes!("../../../../test_key/ed25519/bundle_responder.certchain.der");
+    let requester_chain =
+        include_bytes!("../../../../test_key/ed25519/bundle_requester.certchain.der");
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+    responder.common.reset_runtime_info();
+    responder.common.provision_info.my_cert_chain[0] =
+        Some(ed25519_cert_chain_buff(responder_chain));
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_EDDSA_ED25519;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+
+    let mut total_number = 0u8;
+    let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+    requester.common.peer_info.peer_cert_chain[0] = Some(ed25519_cert_chain_buff(responder_chain));
+    let status = requester.send_receive_spdm_measurement(
+        None,
+        0,
+        SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+        SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+        None,
+        &mut total_number,
+        &mut spdm_measurement_record_structure,
+    );
+    assert!(status.is_ok());
+
+    // the responder key does not match the requester certificate.
+    requester.common.peer_info.peer_cert_chain[0] = Some(ed25519_cert_chain_buff(requester_chain));
+    let status = requester.send_receive_spdm_measurement(
+        None,
+        0,
+        SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+        SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+        None,
+        &mut total_number,
+        &mut spdm_measurement_record_structure,
+    );
+    assert!(status.is_err());
+}
+
+#[test]
+fn test_case6_fetch_attestation_report() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP;
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder.common.provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    let report = requester.fetch_attestation_report(None, 0).unwrap();
+    assert!(report.signature_verified);
+    assert!(!report.measurement_blocks.is_empty());
+    assert!(get_rsp_cert_chain_buff()
+        .as_ref()
+        .ends_with(&report.leaf_cert));
+
+    let bytes = report.to_bytes().unwrap();
+    let parsed = SpdmAttestationReport::from_bytes(&bytes).unwrap();
+    assert_eq!(parsed.spdm_version, SpdmVersion::SpdmVersion12);
+    assert_eq!(parsed.base_hash_algo, SpdmBaseHashAlgo::TPM_ALG_SHA_384);
+    assert_eq!(
+        parsed.base_asym_algo,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384
+    );
+    assert_eq!(
+        parsed.measurement_specification,
+        SpdmMeasurementSpecification::DMTF
+    );
+    assert_eq!(
+        parsed.measurement_hash_algo,
+        SpdmMeasurementHashAlgo::TPM_ALG_SHA_384
+    );
+    assert_eq!(parsed.slot_id, 0);
+    assert_eq!(parsed.leaf_cert, report.leaf_cert);
+    assert!(parsed.signature_verified);
+    assert_eq!(
+        parsed.measurement_blocks.len(),
+        report.measurement_blocks.len()
+    );
+    for (parsed_block, block) in parsed
+        .measurement_blocks
+        .iter()
+        .zip(report.measurement_blocks.iter())
+    {
+        assert_eq!(encode_block(parsed_block), encode_block(block));
+    }
+    assert_eq!(parsed.to_bytes().unwrap(), bytes);
+
+    // truncated, padded and foreign blobs are refused.
+    assert!(SpdmAttestationReport::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+    let mut padded = bytes.clone();
+    padded.push(0);
+    assert!(SpdmAttestationReport::from_bytes(&padded).is_none());
+    let mut foreign = bytes;
+    foreign[4] += 1;
+    assert!(SpdmAttestationReport::from_bytes(&foreign).is_none());
+}
+
+#[test]
+fn test_case7_decode_only_measurement_response() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    // no hash algorithm and no peer certificate: nothing can be hashed or verified.
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::empty();
+
+    let mut value = [0u8; config::MAX_SPDM_MEASUREMENT_VALUE_LEN];
+    value[..SHA384_DIGEST_SIZE].copy_from_slice(&[0x5A; SHA384_DIGEST_SIZE]);
+    let measurement_block = SpdmMeasurementBlockStructure {
+        index: 1,
+        measurement_specification: SpdmMeasurementSpecification::DMTF,
+        measurement_size: 3 + SHA384_DIGEST_SIZE as u16,
+        measurement: SpdmDmtfMeasurementStructure {
+            r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware,
+            representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest,
+            value_size: SHA384_DIGEST_SIZE as u16,
+            value,
+        },
+    };
+    let mut measurement_record = SpdmMeasurementRecordStructure::default();
+    let mut writer = Writer::init(&mut measurement_record.measurement_record_data);
+    measurement_block
+        .spdm_encode(&mut requester.common, &mut writer)
+        .unwrap();
+    measurement_record.measurement_record_length = u24::new(writer.used() as u32);
+    measurement_record.number_of_blocks = 1;
+
+    // a captured, signed MEASUREMENTS response.
+    requester.common.runtime_info.need_measurement_signature = true;
+    let response = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion12,
+            request_response_code: SpdmRequestResponseCode::SpdmResponseMeasurements,
+        },
+        payload: SpdmMessagePayload::SpdmMeasurementsResponse(SpdmMeasurementsResponsePayload {
+            number_of_measurement: 1,
+            content_changed: SpdmMeasurementContentChanged::NOT_SUPPORTED,
+            slot_id: 0,
+            measurement_record,
+            nonce: SpdmNonceStruct {
+                data: [0x11; SPDM_NONCE_SIZE],
+            },
+            opaque: SpdmOpaqueStruct::default(),
+            signature: SpdmSignatureStruct {
+                data_size: ECDSA_ECC_NIST_P384_KEY_SIZE as u16,
+                data: [0x22; SPDM_MAX_ASYM_KEY_SIZE],
+            },
+        }),
+    };
+    let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let mut writer = Writer::init(&mut receive_buffer);
+    let receive_used = response
+        .spdm_encode(&mut requester.common, &mut writer)
+        .unwrap();
+
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester
+        .encode_spdm_measurement_record(
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            Some(&[0x11; SPDM_NONCE_SIZE]),
+            0,
+            &mut send_buffer,
+        )
+        .unwrap();
+
+    let mut decoded_record = SpdmMeasurementRecordStructure::default();
+    assert!(requester
+        .handle_spdm_measurement_record_response(
+            None,
+            0,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            &mut decoded_record,
+            &send_buffer[..send_used],
+            &receive_buffer[..receive_used],
+        )
+        .is_err());
+
+    requester.decode_only = true;
+    assert_eq!(
+        requester.handle_spdm_measurement_record_response(
+            None,
+            0,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            &mut decoded_record,
+            &send_buffer[..send_used],
+            &receive_buffer[..receive_used],
+        ),
+        Ok(1)
+    );
+    assert!(!requester.last_measurement_verified);
+    assert_eq!(decoded_record.number_of_blocks, 1);
+    let mut reader = Reader::init(
+        &decoded_record.measurement_record_data
+            [..decoded_record.measurement_record_length.get() as usize],
+    );
+    let decoded_block =
+        SpdmMeasurementBlockStructure::spdm_read(&mut requester.common, &mut reader).unwrap();
+    assert_eq!(decoded_block.index, 1);
+    assert_eq!(
+        decoded_block.measurement.value[..SHA384_DIGEST_SIZE],
+        [0x5A; SHA384_DIGEST_SIZE]
+    );
+}
+
+#[test]
+fn test_case8_reject_duplicate_measurement_response() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester
+        .encode_spdm_measurement_record(
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
+            0,
+            &mut send_buffer,
+        )
+        .unwrap();
+
+    let mut responses = [[0u8; config::MAX_SPDM_MSG_SIZE]; 2];
+    let mut response_used = [0usize; 2];
+    for (i, nonce) in [0x11u8, 0x33].iter().enumerate() {
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion12,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseMeasurements,
+            },
+            payload: SpdmMessagePayload::SpdmMeasurementsResponse(
+                SpdmMeasurementsResponsePayload {
+                    number_of_measurement: 4,
+                    content_changed: SpdmMeasurementContentChanged::NOT_SUPPORTED,
+                    slot_id: 0,
+                    measurement_record: SpdmMeasurementRecordStructure::default(),
+                    nonce: SpdmNonceStruct {
+                        data: [*nonce; SPDM_NONCE_SIZE],
+                    },
+                    opaque: SpdmOpaqueStruct::default(),
+                    signature: SpdmSignatureStruct::default(),
+                },
+            ),
+        };
+        let mut writer = Writer::init(&mut responses[i]);
+        response_used[i] = response
+            .spdm_encode(&mut requester.common, &mut writer)
+            .unwrap();
+    }
+
+    let mut record = SpdmMeasurementRecordStructure::default();
+    let mut handle = |requester: &mut RequesterContext, response: &[u8]| {
+        requester.handle_spdm_measurement_record_response(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            &mut record,
+            &send_buffer[..send_used],
+            response,
+        )
+    };
+    assert_eq!(
+        handle(&mut requester, &responses[0][..response_used[0]]),
+        Ok(4)
+    );
+    // the same response delivered again.
+    assert_eq!(
+        handle(&mut requester, &responses[0][..response_used[0]]),
+        Err(SPDM_STATUS_NONCE_REUSE_PEER)
+    );
+    assert_eq!(
+        handle(&mut requester, &responses[1][..response_used[1]]),
+        Ok(4)
+    );
+}
+
+#[test]
+fn test_case9_fetch_all_measurements_per_slot() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP;
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder.common.provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    responder.common.provision_info.my_cert_chain[1] = Some(get_rsp_cert_chain_buff());
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+
+    // no chain, nothing to fetch.
+    assert_eq!(
+        requester
+            .fetch_all_measurements_per_slot(None, SpdmSlotErrorPolicy::Abort)
+            .unwrap_err(),
+        SPDM_STATUS_INVALID_PARAMETER
+    );
+
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    requester.common.peer_info.peer_cert_chain[1] = Some(get_rsp_cert_chain_buff());
+    let slot_results = requester
+        .fetch_all_measurements_per_slot(None, SpdmSlotErrorPolicy::Abort)
+        .unwrap();
+    assert_eq!(slot_results.slot_mask, 0b11);
+    assert!(slot_results.failed_slot().is_none());
+    let slot0_blocks = slot_results.slots[0].as_ref().unwrap().as_ref().unwrap();
+    let slot1_blocks = slot_results.slots[1].as_ref().unwrap().as_ref().unwrap();
+    assert!(!slot0_blocks.is_empty());
+    assert_eq!(slot0_blocks.len(), slot1_blocks.len());
+    assert!(slot_results.slots[2..].iter().all(|slot| slot.is_none()));
+
+    // slot 2 is not provisioned on the responder side.
+    requester.common.peer_info.peer_cert_chain[2] = Some(get_rsp_cert_chain_buff());
+    let slot_results = requester
+        .fetch_all_measurements_per_slot(None, SpdmSlotErrorPolicy::Collect)
+        .unwrap();
+    assert_eq!(slot_results.slot_mask, 0b111);
+    assert!(slot_results.slots[0].as_ref().unwrap().is_ok());
+    assert!(slot_results.slots[1].as_ref().unwrap().is_ok());
+    assert_eq!(
+        slot_results.failed_slot().map(|(slot_id, _)| slot_id),
+        Some(2)
+    );
+
+    // a slot skipped by DIGESTS is not fetched.
+    requester.common.peer_info.peer_provisioned_slot_mask = Some(0b011);
+    let slot_results = requester
+        .fetch_all_measurements_per_slot(None, SpdmSlotErrorPolicy::Abort)
+        .unwrap();
+    assert_eq!(slot_results.slot_mask, 0b11);
+
+    requester.common.peer_info.peer_provisioned_slot_mask = None;
+    assert!(requester
+        .fetch_all_measurements_per_slot(None, SpdmSlotErrorPolicy::Abort)
+        .is_err());
+}
+
+#[test]
+fn test_case10_require_content_change_support() {
+    let (mut rsp_config_info, rsp_provision_info) = create_info();
+    let (mut req_config_info, req_provision_info) = create_info();
+    rsp_config_info.runtime_content_change_support = false;
+    req_config_info.require_content_change_support = true;
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+
+    let mut total_number = 0u8;
+    let mut record = SpdmMeasurementRecordStructure::default();
+    let mut query = |requester: &mut RequesterContext| {
+        requester.send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
+            &mut total_number,
+            &mut record,
+        )
+    };
+    assert_eq!(
+        query(&mut requester),
+        Err(SPDM_STATUS_CONTENT_CHANGE_UNSUPPORTED_PEER)
+    );
+
+    requester.common.config_info.require_content_change_support = false;
+    assert!(query(&mut requester).is_ok());
+}
+
+#[test]
+fn test_case11_measurement_index_beyond_total_number() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+
+    let mut total_number = 0u8;
+    let mut record = SpdmMeasurementRecordStructure::default();
+    assert!(requester
+        .send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
+            &mut total_number,
+            &mut record,
+        )
+        .is_ok());
+    assert!(total_number > 0 && total_number < 0xF0);
+
+    let mut get_index = |requester: &mut RequesterContext, index: u8| {
+        let mut number_of_blocks = 0u8;
+        requester.send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::Unknown(index),
+            None,
+            &mut number_of_blocks,
+            &mut record,
+        )
+    };
+    assert_eq!(
+        get_index(&mut requester, total_number + 1),
+        Err(SPDM_STATUS_MEAS_INVALID_INDEX)
+    );
+    assert!(get_index(&mut requester, total_number).is_ok());
+
+    // one index out of range fails the batch before anything is sent.
+    assert_eq!(
+        requester
+            .send_receive_spdm_measurement_batch(
+                None,
+                0,
+                SpdmMeasurementAttributes::empty(),
+                &[1, total_number + 1],
+            )
+            .err(),
+        Some(SPDM_STATUS_MEAS_INVALID_INDEX)
+    );
+}
+
+#[test]
+fn test_case12_reject_reused_responder_nonce() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester
+        .encode_spdm_measurement_record(
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
+            0,
+            &mut send_buffer,
+        )
+        .unwrap();
+
+    let mut record = SpdmMeasurementRecordStructure::default();
+    let mut handle = |requester: &mut RequesterContext, nonce: u8| {
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion12,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseMeasurements,
+            },
+            payload: SpdmMessagePayload::SpdmMeasurementsResponse(
+                SpdmMeasurementsResponsePayload {
+                    number_of_measurement: 4,
+                    content_changed: SpdmMeasurementContentChanged::NOT_SUPPORTED,
+                    slot_id: 0,
+                    measurement_record: SpdmMeasurementRecordStructure::default(),
+                    nonce: SpdmNonceStruct {
+                        data: [nonce; SPDM_NONCE_SIZE],
+                    },
+                    opaque: SpdmOpaqueStruct::default(),
+                    signature: SpdmSignatureStruct::default(),
+                },
+            ),
+        };
+        let mut response_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut response_buffer);
+        let used = response
+            .spdm_encode(&mut requester.common, &mut writer)
+            .unwrap();
+        requester.handle_spdm_measurement_record_response(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            &mut record,
+            &send_buffer[..send_used],
+            &response_buffer[..used],
+        )
+    };
+
+    // a nonce repeated a few exchanges later, not only right away.
+    assert_eq!(handle(&mut requester, 0x11), Ok(4));
+    assert_eq!(handle(&mut requester, 0x22), Ok(4));
+    assert_eq!(handle(&mut requester, 0x33), Ok(4));
+    assert_eq!(
+        handle(&mut requester, 0x11),
+        Err(SPDM_STATUS_NONCE_REUSE_PEER)
+    );
+
+    // only the most recent nonces are remembered.
+    for nonce in 0..SPDM_NONCE_HISTORY_SIZE as u8 {
+        assert_eq!(handle(&mut requester, 0x80 + nonce), Ok(4));
+    }
+    assert_eq!(handle(&mut requester, 0x11), Ok(4));
+}
+
+#[test]
+fn test_case13_send_receive_spdm_measurement_in_psk_session() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    responder.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT
+            | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_256_GCM;
+    responder.common.reset_runtime_info();
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT
+            | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_256_GCM;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let mut psk_hint = SpdmPskHintStruct {
+        data_size: b"TestPskHint\0".len() as u16,
+        data: [0u8; config::MAX_SPDM_PSK_HINT_SIZE],
+    };
+    psk_hint.data[0..(psk_hint.data_size as usize)].copy_from_slice(b"TestPskHint\0");
+    let session_id = requester
+        .send_receive_spdm_psk_exchange(
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            Some(&psk_hint),
+        )
+        .unwrap();
+    requester.send_receive_spdm_psk_finish(session_id).unwrap();
+
+    // unsigned measurements are protected by the session itself.
+    let mut total_number: u8 = 0;
+    let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+    assert!(requester
+        .send_receive_spdm_measurement(
+            Some(session_id),
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
+            &mut total_number,
+            &mut spdm_measurement_record_structure,
+        )
+        .is_ok());
+    assert_ne!(spdm_measurement_record_structure.number_of_blocks, 0);
+
+    // signed ones have no certificate chain to be verified against, and
+    // are refused before anything is sent.
+    assert_eq!(
+        requester.send_receive_spdm_measurement(
+            Some(session_id),
+            0,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
+            &mut total_number,
+            &mut spdm_measurement_record_structure,
+        ),
+        Err(SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE)
+    );
+
+    // the session is still usable.
+    assert!(requester
+        .send_receive_spdm_measurement(
+            Some(session_id),
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
+            &mut total_number,
+            &mut spdm_measurement_record_structure,
+        )
+        .is_ok());
+    assert_ne!(total_number, 0);
+}
+
+#[test]
+fn test_case14_reject_signed_measurement_without_cert_chain() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    // only slot 0 has a chain.
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    let mut total_number = 0u8;
+    let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+    let status = requester.send_receive_spdm_measurement(
+        None,
+        2,
+        SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+        SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+        None,
+        &mut total_number,
+        &mut spdm_measurement_record_structure,
+    );
+    assert_eq!(status, Err(SPDM_STATUS_INVALID_PARAMETER));
+    assert!(!requester.common.runtime_info.need_measurement_signature);
+
+    // the request was refused before anything went out.
+    let mut sent = [0u8; config::MAX_SPDM_MSG_SIZE];
+    assert_eq!(shared_buffer.get_buffer(&mut sent), 0);
+}
+
+#[test]
+fn test_case15_reject_signature_of_other_algorithm() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester
+        .encode_spdm_measurement_record(
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
+            0,
+            &mut send_buffer,
+        )
+        .unwrap();
+
+    // a P-384 sized signature, then made an RSA 3072 and a P-256 sized one.
+    requester.common.runtime_info.need_measurement_signature = true;
+    let response = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion12,
+            request_response_code: SpdmRequestResponseCode::SpdmResponseMeasurements,
+        },
+        payload: SpdmMessagePayload::SpdmMeasurementsResponse(SpdmMeasurementsResponsePayload {
+            number_of_measurement: 4,
+            content_changed: SpdmMeasurementContentChanged::NOT_SUPPORTED,
+            slot_id: 0,
+            measurement_record: SpdmMeasurementRecordStructure::default(),
+            nonce: SpdmNonceStruct {
+                data: [0x11; SPDM_NONCE_SIZE],
+            },
+            opaque: SpdmOpaqueStruct::default(),
+            signature: SpdmSignatureStruct {
+                data_size: SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384.get_size(),
+                data: [0x5a; SPDM_MAX_ASYM_KEY_SIZE],
+            },
+        }),
+    };
+    let mut response_buffer = [0x5au8; config::MAX_SPDM_MSG_SIZE];
+    let mut writer = Writer::init(&mut response_buffer);
+    let used = response
+        .spdm_encode(&mut requester.common, &mut writer)
+        .unwrap();
+
+    let mut record = SpdmMeasurementRecordStructure::default();
+    for asym_algo in [
+        SpdmBaseAsymAlgo::TPM_ALG_RSASSA_3072,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P256,
+    ] {
+        let signature_size = asym_algo.get_size() as usize;
+        let response_size = used
+            - SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384.get_size() as usize
+            + signature_size;
+        assert_eq!(
+            requester.handle_spdm_measurement_record_response(
+                None,
+                0,
+                SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+                &mut record,
+                &send_buffer[..send_used],
+                &response_buffer[..response_size],
+            ),
+            Err(SPDM_STATUS_INVALID_MSG_FIELD)
+        );
+        assert!(!requester.last_measurement_verified);
+    }
+}
+
+#[test]
+fn test_case16_measurement_count_mismatch() {
+    let (mut req_config_info, req_provision_info) = create_info();
+    req_config_info.check_measurement_count = true;
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // unsigned, so the responder has no way to signal a content change.
+    let exchange = |requester: &mut RequesterContext,
+                    measurement_operation: SpdmMeasurementOperation| {
+        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let send_used = requester
+            .encode_spdm_measurement_record(
+                SpdmMeasurementAttributes::empty(),
+                measurement_operation,
+                None,
+                0,
+                &mut send_buffer,
+            )
+            .unwrap();
+
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion12,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseMeasurements,
+            },
+            payload: SpdmMessagePayload::SpdmMeasurementsResponse(
+                SpdmMeasurementsResponsePayload {
+                    number_of_measurement: 4,
+                    content_changed: SpdmMeasurementContentChanged::NOT_SUPPORTED,
+                    slot_id: 0,
+                    measurement_record: SpdmMeasurementRecordStructure::default(),
+                    nonce: SpdmNonceStruct::default(),
+                    opaque: SpdmOpaqueStruct::default(),
+                    signature: SpdmSignatureStruct::default(),
+                },
+            ),
+        };
+        let mut response_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut response_buffer);
+        let used = response
+            .spdm_encode(&mut requester.common, &mut writer)
+            .unwrap();
+
+        let mut record = SpdmMeasurementRecordStructure::default();
+        requester.handle_spdm_measurement_record_response(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            measurement_operation,
+            &mut record,
+            &send_buffer[..send_used],
+            &response_buffer[..used],
+        )
+    };
+
+    // 4 blocks reported, none returned.
+    assert_eq!(
+        exchange(
+            &mut requester,
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber
+        ),
+        Ok(4)
+    );
+    assert_eq!(
+        exchange(
+            &mut requester,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll
+        ),
+        Err(SPDM_STATUS_MEAS_COUNT_MISMATCH)
+    );
+
+    requester.common.config_info.check_measurement_count = false;
+    assert_eq!(
+        exchange(
+            &mut requester,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn test_case17_signed_measurement_without_meas_cap_sig() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::MEAS_CAP_NO_SIG;
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::MEAS_CAP_NO_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // the responder cannot sign, so the request is refused up front.
+    let mut total_number = 0u8;
+    let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+    assert_eq!(
+        requester.send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
+            &mut total_number,
+            &mut spdm_measurement_record_structure,
+        ),
+        Err(SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE)
+    );
+    assert!(!requester.last_measurement_signature_dropped);
+
+    // by policy the same call goes out unsigned, and says so.
+    requester.common.config_info.unsigned_measurement_fallback = true;
+    assert!(requester
+        .send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
+            &mut total_number,
+            &mut spdm_measurement_record_structure,
+        )
+        .is_ok());
+    assert_ne!(total_number, 0);
+    assert!(requester.last_measurement_signature_dropped);
+    assert!(!requester.last_measurement_verified);
+    assert!(!requester.common.runtime_info.need_measurement_signature);
+}
+
+#[test]
+fn test_case18_measurement_freshness_proof() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.negotiate_info.req_ct_exponent_sel = 0;
+    responder.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::CERT_CAP;
+
+    responder.common.negotiate_info.rsp_ct_exponent_sel = 0;
+    responder.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP;
+
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    #[cfg(not(feature = "hashed-transcript-data"))]
+    let message_m = &[0];
+    #[cfg(not(feature = "hashed-transcript-data"))]
+    responder
+        .common
+        .runtime_info
+        .message_m
+        .append_message(message_m);
+    responder.common.reset_runtime_info();
+    responder.common.provision_info.my_cert_chain = [
+        Some(SpdmCertChainBuffer {
+            data_size: 512u16,
+            data: [0u8; 4 + SPDM_MAX_HASH_SIZE + config::MAX_SPDM_CERT_CHAIN_DATA_SIZE],
+        }),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    ];
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.req_ct_exponent_sel = 0;
+    requester.common.negotiate_info.req_capabilities_sel = SpdmRequestCapabilityFlags::CERT_CAP;
+
+    requester.common.negotiate_info.rsp_ct_exponent_sel = 0;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // a signed response with an empty record, the signature covers the
+    // nonces and the opaque data only.
+    let nonce = [0x5au8; SPDM_NONCE_SIZE];
+    let total_number = requester
+        .send_receive_spdm_measurement_freshness(None, 0, Some(&nonce))
+        .unwrap();
+    assert_ne!(total_number, 0);
+    assert!(requester.last_measurement_verified);
+    assert!(requester.common.runtime_info.need_measurement_signature);
+
+    // without MEAS_CAP_SIG there is no proof to be had, fallback or not.
+    requester.common.config_info.unsigned_measurement_fallback = true;
+    requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP;
+    assert_eq!(
+        requester.send_receive_spdm_measurement_freshness(None, 0, None),
+        Err(SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE)
+    );
+}
+
+#[test]
+fn test_case19_handle_measurement_response_slot_out_of_range() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester
+        .encode_spdm_measurement_record(
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
+            0,
+            &mut send_buffer,
+        )
+        .unwrap();
+
+    // a signed RequestAll response from the fuzz seed corpus, handled for
+    // a slot the peer_cert_chain array does not have.
+    let seed =
+        include_bytes!("../../../../fuzz-target/in/measurement_record_req/request_all_signed.raw");
+    let mut receive_buffer = [0u8; 2 + 686];
+    receive_buffer[0] = SpdmVersion::SpdmVersion12.get_u8();
+    receive_buffer[1] = SpdmRequestResponseCode::SpdmResponseMeasurements.get_u8();
+    receive_buffer[2..].copy_from_slice(seed);
+
+    let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+    assert!(requester
+        .handle_spdm_measurement_record_response(
+            None,
+            SPDM_MAX_SLOT_NUMBER as u8 + 1,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            &mut spdm_measurement_record_structure,
+            &send_buffer[..send_used],
+            &receive_buffer,
+        )
+        .is_err());
+    assert!(!requester.last_measurement_verified);
+}
+
+#[test]
+fn test_case20_echoed_request_nonce() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let nonce = [0x5au8; SPDM_NONCE_SIZE];
+    let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let send_used = requester
+        .encode_spdm_measurement_record(
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            Some(&nonce),
+            0,
+            &mut send_buffer,
+        )
+        .unwrap();
+
+    // the responder puts the request nonce where its own belongs.
+    requester.common.runtime_info.need_measurement_signature = true;
+    let response = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion12,
+            request_response_code: SpdmRequestResponseCode::SpdmResponseMeasurements,
+        },
+        payload: SpdmMessagePayload::SpdmMeasurementsResponse(SpdmMeasurementsResponsePayload {
+            number_of_measurement: 4,
+            content_changed: SpdmMeasurementContentChanged::NOT_SUPPORTED,
+            slot_id: 0,
+            measurement_record: SpdmMeasurementRecordStructure::default(),
+            nonce: SpdmNonceStruct { data: nonce },
+            opaque: SpdmOpaqueStruct::default(),
+            signature: SpdmSignatureStruct {
+                data_size: SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384.get_size(),
+                ..Default::default()
+            },
+        }),
+    };
+    let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let mut writer = Writer::init(&mut receive_buffer);
+    let receive_used = response
+        .spdm_encode(&mut requester.common, &mut writer)
+        .unwrap();
+
+    let mut record = SpdmMeasurementRecordStructure::default();
+    let mut handle = |requester: &mut RequesterContext| {
+        requester.handle_spdm_measurement_record_response(
+            None,
+            0,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            &mut record,
+            &send_buffer[..send_used],
+            &receive_buffer[..receive_used],
+        )
+    };
+    // only warned about by default, the bogus signature is what fails.
+    assert_eq!(handle(&mut requester), Err(SPDM_STATUS_VERIF_FAIL));
+
+    requester.common.config_info.reject_echoed_measurement_nonce = true;
+    assert_eq!(handle(&mut requester), Err(SPDM_STATUS_NONCE_ECHO_PEER));
+}
+
+// A growable L1/L2 transcript, as a server with large measurement records
+// would plug in.
+struct HeapTranscript(Rc<RefCell<Vec<u8>>>);
+
+impl SpdmTranscriptStorage for HeapTranscript {
+    fn append(&mut self, message: &[u8]) -> SpdmResult {
+        self.0.borrow_mut().extend_from_slice(message);
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.0.borrow_mut().clear();
+    }
+
+    fn finalize(&self, base_hash_algo: SpdmBaseHashAlgo) -> SpdmResult<SpdmDigestStruct> {
+        crypto::hash::hash_all(base_hash_algo, &self.0.borrow()).ok_or(SPDM_STATUS_CRYPTO_ERROR)
+    }
+}
+
+#[test]
+fn test_case21_heap_backed_l1l2_transcript() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP;
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder.common.provision_info.my_cert_chain[0] = Some(SpdmCertChainBuffer {
+        data_size: 512u16,
+        data: [0u8; 4 + SPDM_MAX_HASH_SIZE + config::MAX_SPDM_CERT_CHAIN_DATA_SIZE],
+    });
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.reset_runtime_info();
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    let rsp_transcript = Rc::new(RefCell::new(Vec::new()));
+    responder
+        .common
+        .set_l1l2_transcript_storage(Box::new(HeapTranscript(rsp_transcript.clone())));
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    let req_transcript = Rc::new(RefCell::new(Vec::new()));
+    requester
+        .common
+        .set_l1l2_transcript_storage(Box::new(HeapTranscript(req_transcript.clone())));
+
+    let mut total_number: u8 = 0;
+    let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+    let mut get_all = |requester: &mut RequesterContext, attributes| {
+        requester.send_receive_spdm_measurement(
+            None,
+            0,
+            attributes,
+            SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+            None,
+            &mut total_number,
+            &mut spdm_measurement_record_structure,
+        )
+    };
+
+    // unsigned exchanges until the transcript is past what the fixed
+    // buffer holds.
+    let mut exchanges = 0;
+    while ManagedBufferL1L2::default()
+        .append_message(&req_transcript.borrow())
+        .is_some()
+    {
+        assert!(exchanges < 64);
+        assert!(get_all(&mut requester, SpdmMeasurementAttributes::empty()).is_ok());
+        exchanges += 1;
+    }
+    assert_eq!(*req_transcript.borrow(), *rsp_transcript.borrow());
+
+    assert!(get_all(
+        &mut requester,
+        SpdmMeasurementAttributes::SIGNATURE_REQUESTED
+    )
+    .is_ok());
+    // a signed exchange ends the transcript.
+    assert!(req_transcript.borrow().is_empty());
+    assert!(rsp_transcript.borrow().is_empty());
+
+    // SPDM 1.1 signs the transcript itself, which the storage does not give back.
+    #[cfg(not(feature = "hashed-transcript-data"))]
+    {
+        requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+        assert_eq!(
+            get_all(
+                &mut requester,
+                SpdmMeasurementAttributes::SIGNATURE_REQUESTED
+            ),
+            Err(SPDM_STATUS_MEAS_SIGNATURE_UNAVAILABLE)
+        );
+    }
+}
+
+#[test]
+fn test_case22_raw_bit_stream_of_digest_only_index() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.reset_runtime_info();
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::MEAS_CAP_NO_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let mut total_number: u8 = 0;
+    let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+    let mut get_index_1 = |requester: &mut RequesterContext, attributes| {
+        requester.send_receive_spdm_measurement(
+            None,
+            0,
+            attributes,
+            SpdmMeasurementOperation::Unknown(1),
+            None,
+            &mut total_number,
+            &mut spdm_measurement_record_structure,
+        )
+    };
+
+    // the test collection only has the digest of each index.
+    assert_eq!(
+        get_index_1(
+            &mut requester,
+            SpdmMeasurementAttributes::RAW_BIT_STREAM_REQUESTED
+        ),
+        Err(SPDM_STATUS_MEAS_RAW_BIT_STREAM_UNAVAILABLE)
+    );
+    assert!(get_index_1(&mut requester, SpdmMeasurementAttributes::empty()).is_ok());
+}
+
+#[test]
+fn test_case23_responder_content_changed_state() {
+    let (mut rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+    rsp_config_info.runtime_content_change_support = true;
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+    responder.common.provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+
+    for common in [&mut responder.common, &mut requester.common] {
+        common.negotiate_info.measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+        common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+        common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+        common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+        common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+        common.reset_runtime_info();
+        common
+            .runtime_info
+            .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    }
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    assert_eq!(
+        responder.set_measurement_content_changed(SpdmMeasurementContentChanged::NOT_SUPPORTED),
+        Err(SPDM_STATUS_INVALID_PARAMETER)
+    );
+    assert_eq!(
+        responder.set_measurement_content_changed(
+            SpdmMeasurementContentChanged::DETECTED_CHANGE
+                | SpdmMeasurementContentChanged::NO_CHANGE
+        ),
+        Err(SPDM_STATUS_INVALID_PARAMETER)
+    );
+
+    for content_changed in [
+        SpdmMeasurementContentChanged::DETECTED_CHANGE,
+        SpdmMeasurementContentChanged::NO_CHANGE,
+        SpdmMeasurementContentChanged::DETECTED_CHANGE,
+    ] {
+        assert!(responder
+            .set_measurement_content_changed(content_changed)
+            .is_ok());
+
+        // Param2 only carries the state when a signature was requested.
+        let mut send_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let send_used = requester
+            .encode_spdm_measurement_record(
+                SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+                None,
+                0,
+                &mut send_buffer,
+            )
+            .unwrap();
+
+        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut receive_buffer);
+        responder.write_spdm_measurement_response(None, &send_buffer[..send_used], &mut writer);
+        let receive_used = writer.used();
+
+        let mut spdm_measurement_record_structure = SpdmMeasurementRecordStructure::default();
+        assert!(requester
+            .handle_spdm_measurement_record_response(
+                None,
+                0,
+                SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+                &mut spdm_measurement_record_structure,
+                &send_buffer[..send_used],
+                &receive_buffer[..receive_used],
+            )
+            .is_ok());
+        assert_eq!(
+            requester.common.runtime_info.content_changed,
+            content_changed
+        );
+    }
+}
+
+// Answers every request with the same transport encoded response.
+struct CannedResponseDeviceIo<'a> {
+    response: &'a [u8],
+}
+
+impl SpdmDeviceIo for CannedResponseDeviceIo<'_> {
+    fn send(&mut self, _buffer: &[u8]) -> SpdmResult {
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], _timeout: usize) -> Result<usize, usize> {
+        buffer[..self.response.len()].copy_from_slice(self.response);
+        Ok(self.response.len())
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_case24_fetch_all_measurements_sparse_indices() {
+    // three blocks at indices 2, 5 and 9 only.
+    let mut measurement_record = SpdmMeasurementRecordStructure::default();
+    let mut writer = Writer::init(&mut measurement_record.measurement_record_data);
+    for index in [2u8, 5, 9] {
+        let mut value = [0u8; config::MAX_SPDM_MEASUREMENT_VALUE_LEN];
+        value[..SHA384_DIGEST_SIZE].fill(index);
+        SpdmMeasurementBlockStructure {
+            index,
+            measurement_specification: SpdmMeasurementSpecification::DMTF,
+            measurement_size: 3 + SHA384_DIGEST_SIZE as u16,
+            measurement: SpdmDmtfMeasurementStructure {
+                r#type: SpdmDmtfMeasurementType::SpdmDmtfMeasurementFirmware,
+                representation: SpdmDmtfMeasurementRepresentation::SpdmDmtfMeasurementDigest,
+                value_size: SHA384_DIGEST_SIZE as u16,
+                value,
+            },
+        }
+        .encode(&mut writer)
+        .unwrap();
+    }
+    measurement_record.measurement_record_length = u24::new(writer.used() as u32);
+    measurement_record.number_of_blocks = 3;
+
+    // a context only to encode the response with.
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let (config_info, provision_info) = create_info();
+    let mut encoder = RequesterContext::new(
+        &mut device_io,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+    encoder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    encoder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    encoder.common.runtime_info.need_measurement_signature = true;
+
+    let response = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion12,
+            request_response_code: SpdmRequestResponseCode::SpdmResponseMeasurements,
+        },
+        payload: SpdmMessagePayload::SpdmMeasurementsResponse(SpdmMeasurementsResponsePayload {
+            number_of_measurement: 3,
+            content_changed: SpdmMeasurementContentChanged::NOT_SUPPORTED,
+            slot_id: 0,
+            measurement_record,
+            nonce: SpdmNonceStruct {
+                data: [0x11; SPDM_NONCE_SIZE],
+            },
+            opaque: SpdmOpaqueStruct::default(),
+            signature: SpdmSignatureStruct {
+                data_size: ECDSA_ECC_NIST_P384_KEY_SIZE as u16,
+                data: [0x22; SPDM_MAX_ASYM_KEY_SIZE],
+            },
+        }),
+    };
+    let mut spdm_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+    let mut writer = Writer::init(&mut spdm_buffer);
+    let spdm_used = response
+        .spdm_encode(&mut encoder.common, &mut writer)
+        .unwrap();
+    let mut transport_buffer = [0u8; config::MAX_SPDM_MSG_SIZE + 64];
+    let transport_used = PciDoeTransportEncap {}
+        .encap(&spdm_buffer[..spdm_used], &mut transport_buffer, false)
+        .unwrap();
+
+    let mut device_io = CannedResponseDeviceIo {
+        response: &transport_buffer[..transport_used],
+    };
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let (req_config_info, req_provision_info) = create_info();
+    let mut requester = RequesterContext::new(
+        &mut device_io,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::CERT_CAP | SpdmResponseCapabilityFlags::MEAS_CAP_SIG;
+    requester
+        .common
+        .negotiate_info
+        .measurement_specification_sel = SpdmMeasurementSpecification::DMTF;
+    requester.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    // the canned signature is not a real one.
+    requester.decode_only = true;
+
+    let measurement_blocks = requester.fetch_all_measurements(None, 0).unwrap();
+    let indices: Vec<u8> = measurement_blocks.iter().map(|block| block.index).collect();
+    assert_eq!(indices, [2, 5, 9]);
+    for measurement_block in measurement_blocks.iter() {
+        assert_eq!(
+            measurement_block.measurement.value[..SHA384_DIGEST_SIZE],
+            [measurement_block.index; SHA384_DIGEST_SIZE]
+        );
+    }
+}
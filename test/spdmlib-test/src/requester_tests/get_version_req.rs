@@ -6,6 +6,9 @@ use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedB
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
+use spdmlib::common::SpdmCaptureDirection;
+use spdmlib::error::{SPDM_STATUS_INVALID_MSG_FIELD, SPDM_STATUS_NEGOTIATION_FAIL};
+use spdmlib::protocol::SpdmVersion;
 use spdmlib::requester::RequesterContext;
 use spdmlib::{responder, secret};
 
@@ -40,3 +43,209 @@ fn test_case0_send_receive_spdm_version() {
     let status = requester.send_receive_spdm_version().is_ok();
     assert!(status);
 }
+
+#[test]
+fn test_case1_send_receive_spdm_version_preference() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (mut req_config_info, req_provision_info) = create_info();
+    req_config_info.spdm_version_preference = [
+        SpdmVersion::SpdmVersion11,
+        SpdmVersion::SpdmVersion10,
+        SpdmVersion::Unknown(0),
+    ];
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    // 1.1 is preferred over the 1.2 both sides support.
+    assert!(requester.init_connection().is_ok());
+    assert_eq!(
+        requester.common.negotiate_info.spdm_version_sel,
+        SpdmVersion::SpdmVersion11
+    );
+}
+
+#[test]
+fn test_case2_send_receive_spdm_version_preference_not_offered() {
+    let (mut rsp_config_info, rsp_provision_info) = create_info();
+    let (mut req_config_info, req_provision_info) = create_info();
+    rsp_config_info.spdm_version = [SpdmVersion::SpdmVersion12; 3];
+    req_config_info.spdm_version_preference = [
+        SpdmVersion::SpdmVersion11,
+        SpdmVersion::SpdmVersion10,
+        SpdmVersion::Unknown(0),
+    ];
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    assert_eq!(
+        requester.send_receive_spdm_version(),
+        Err(SPDM_STATUS_NEGOTIATION_FAIL)
+    );
+}
+
+#[test]
+fn test_case3_capture_sent_and_received_messages() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (mut req_config_info, req_provision_info) = create_info();
+    req_config_info.message_capture_capacity = 2;
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    assert!(requester.send_receive_spdm_version().is_ok());
+
+    // GET_VERSION and VERSION are the start of the VCA transcript.
+    let message_a = requester.common.runtime_info.message_a.as_ref().to_vec();
+    let sent = requester
+        .common
+        .message_capture
+        .last_sent()
+        .unwrap()
+        .to_vec();
+    let received = requester
+        .common
+        .message_capture
+        .last_received()
+        .unwrap()
+        .to_vec();
+    assert_eq!(sent, [0x10, 0x84, 0x00, 0x00]);
+    assert_eq!(message_a, [sent.as_slice(), received.as_slice()].concat());
+    let captured: Vec<_> = requester.common.message_capture.iter().collect();
+    assert_eq!(
+        captured,
+        [
+            (SpdmCaptureDirection::Sent, sent.as_slice()),
+            (SpdmCaptureDirection::Received, received.as_slice())
+        ]
+    );
+}
+
+#[test]
+fn test_case4_version_list_must_be_unique() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    let get_version = [0x10, 0x84, 0x00, 0x00];
+    let duplicated = [0x10, 0x04, 0x00, 0x00, 0x00, 0x02, 0x00, 0x11, 0x00, 0x11];
+    assert_eq!(
+        requester.handle_spdm_version_response(0, &get_version, &duplicated),
+        Err(SPDM_STATUS_INVALID_MSG_FIELD)
+    );
+    assert!(requester
+        .common
+        .negotiate_info
+        .rsp_spdm_versions()
+        .is_empty());
+
+    // any order is fine, the list is kept as advertised.
+    let unsorted = [
+        0x10, 0x04, 0x00, 0x00, 0x00, 0x03, 0x00, 0x12, 0x00, 0x10, 0x00, 0x11,
+    ];
+    assert!(requester
+        .handle_spdm_version_response(0, &get_version, &unsorted)
+        .is_ok());
+    let advertised: Vec<_> = requester
+        .common
+        .negotiate_info
+        .rsp_spdm_versions()
+        .iter()
+        .map(|entry| entry.version)
+        .collect();
+    assert_eq!(
+        advertised,
+        [
+            SpdmVersion::SpdmVersion12,
+            SpdmVersion::SpdmVersion10,
+            SpdmVersion::SpdmVersion11
+        ]
+    );
+    assert_eq!(
+        requester.common.negotiate_info.spdm_version_sel,
+        SpdmVersion::SpdmVersion12
+    );
+}
@@ -0,0 +1,68 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::device_io::{FakeSpdmDeviceIoReceve, SharedBuffer};
+use crate::common::transport::PciDoeTransportEncap;
+use crate::common::util::create_info;
+use codec::Writer;
+use spdmlib::common::SpdmCodec;
+use spdmlib::config;
+use spdmlib::error::{SPDM_STATUS_ERROR_PEER, SPDM_STATUS_RESET_REQUIRED_PEER};
+use spdmlib::message::*;
+use spdmlib::protocol::*;
+use spdmlib::requester::RequesterContext;
+
+#[test]
+fn test_case0_handle_reset_required_error() {
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_requester = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+
+    for (error_code, status) in [
+        (
+            SpdmErrorCode::SpdmErrorResetRequired,
+            SPDM_STATUS_RESET_REQUIRED_PEER,
+        ),
+        (SpdmErrorCode::SpdmErrorUnspecified, SPDM_STATUS_ERROR_PEER),
+    ] {
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion12,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseError,
+            },
+            payload: SpdmMessagePayload::SpdmErrorResponse(SpdmErrorResponsePayload {
+                error_code,
+                error_data: 0,
+                extended_data: SpdmErrorResponseExtData::SpdmErrorExtDataNone(
+                    SpdmErrorResponseNoneExtData {},
+                ),
+            }),
+        };
+        let mut receive_buffer = [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(&mut receive_buffer);
+        let used = response
+            .spdm_encode(&mut requester.common, &mut writer)
+            .unwrap();
+
+        assert_eq!(
+            requester.spdm_handle_error_response_main(
+                None,
+                &receive_buffer[..used],
+                SpdmRequestResponseCode::SpdmRequestGetCertificate,
+                SpdmRequestResponseCode::SpdmResponseCertificate,
+            ),
+            Err(status)
+        );
+    }
+}
@@ -7,6 +7,7 @@ use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
 use spdmlib::common::session::{SpdmSession, SpdmSessionState};
+use spdmlib::common::SpdmConnectionState;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
 use spdmlib::{responder, secret};
@@ -79,6 +80,11 @@ fn test_case0_send_receive_spdm_heartbeat() {
         req_provision_info,
     );
 
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
     let rsp_session_id = 0x11u16;
     let session_id = (0x11u32 << 16) + rsp_session_id as u32;
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
@@ -97,6 +97,10 @@ fn test_case0_send_receive_spdm_key_exchange() {
     }
 
     requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
 
     requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
 
@@ -107,3 +111,117 @@ fn test_case0_send_receive_spdm_key_exchange() {
         .is_ok();
     assert!(status);
 }
+
+#[test]
+fn test_case1_key_exchange_heartbeat_period() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.provision_info.my_cert_chain = [
+        Some(get_rsp_cert_chain_buff()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    ];
+
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_128_GCM;
+    responder.common.negotiate_info.dhe_sel = SpdmDheAlgo::SECP_384_R1;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    responder.common.negotiate_info.opaque_data_support = SpdmOpaqueSupport::OPAQUE_DATA_FMT1;
+    #[cfg(feature = "mut-auth")]
+    {
+        responder.common.negotiate_info.rsp_capabilities_sel |=
+            SpdmResponseCapabilityFlags::MUT_AUTH_CAP;
+        responder.common.negotiate_info.req_capabilities_sel |=
+            SpdmRequestCapabilityFlags::MUT_AUTH_CAP;
+    }
+
+    responder.common.reset_runtime_info();
+
+    responder.common.provision_info.my_cert_chain = [
+        Some(get_rsp_cert_chain_buff()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    ];
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder.common.negotiate_info.req_capabilities_sel |= SpdmRequestCapabilityFlags::HBEAT_CAP;
+    responder.common.negotiate_info.rsp_capabilities_sel |= SpdmResponseCapabilityFlags::HBEAT_CAP;
+    responder.common.config_info.heartbeat_period = 5;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_128_GCM;
+    requester.common.negotiate_info.dhe_sel = SpdmDheAlgo::SECP_384_R1;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester.common.negotiate_info.opaque_data_support = SpdmOpaqueSupport::OPAQUE_DATA_FMT1;
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.req_capabilities_sel |= SpdmRequestCapabilityFlags::HBEAT_CAP;
+    requester.common.negotiate_info.rsp_capabilities_sel |= SpdmResponseCapabilityFlags::HBEAT_CAP;
+    #[cfg(feature = "mut-auth")]
+    {
+        requester.common.negotiate_info.rsp_capabilities_sel |=
+            SpdmResponseCapabilityFlags::MUT_AUTH_CAP;
+        requester.common.negotiate_info.req_capabilities_sel |=
+            SpdmRequestCapabilityFlags::MUT_AUTH_CAP;
+    }
+
+    requester.common.reset_runtime_info();
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    requester.common.peer_info.peer_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    let measurement_summary_hash_type =
+        SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone;
+    let session_id = requester
+        .send_receive_spdm_key_exchange(0, measurement_summary_hash_type)
+        .unwrap();
+    assert_eq!(requester.get_heartbeat_period(session_id), Ok(Some(5)));
+
+    // without HBEAT_CAP on both sides the advertised period is ignored.
+    let session = requester.common.get_session_via_id(session_id).unwrap();
+    session.teardown(session_id).unwrap();
+    requester.common.negotiate_info.req_capabilities_sel -= SpdmRequestCapabilityFlags::HBEAT_CAP;
+    let session_id = requester
+        .send_receive_spdm_key_exchange(0, measurement_summary_hash_type)
+        .unwrap();
+    assert_eq!(requester.get_heartbeat_period(session_id), Ok(None));
+}
@@ -6,10 +6,16 @@
 
 mod challenge_req;
 
+mod chunk_req;
+
 mod context;
 
+mod detached_measurements;
+
 mod end_session_req;
 
+mod event_req;
+
 #[cfg(feature = "mut-auth")]
 mod encap_certificate;
 
@@ -34,6 +40,8 @@ mod get_measurements_req;
 
 mod get_version_req;
 
+mod handle_error_response_req;
+
 mod heartbeat_req;
 
 mod key_exchange_req;
@@ -6,7 +6,14 @@ use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedB
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
-use spdmlib::common::SpdmConnectionState;
+use codec::Writer;
+use spdmlib::common::{SpdmCodec, SpdmConnectionState};
+use spdmlib::error::{SPDM_STATUS_CONTENT_CHANGE_UNSUPPORTED_PEER, SPDM_STATUS_NEGOTIATION_FAIL};
+use spdmlib::message::*;
+use spdmlib::protocol::{
+    SpdmAeadAlgo, SpdmBaseAsymAlgo, SpdmBaseHashAlgo, SpdmDheAlgo, SpdmExtAlgStruct,
+    SpdmKeyScheduleAlgo, SpdmResponseCapabilityFlags, SpdmVersion,
+};
 use spdmlib::requester::RequesterContext;
 use spdmlib::{responder, secret};
 
@@ -42,6 +49,200 @@ fn test_case0_send_receive_spdm_algorithm() {
         req_provision_info,
     );
 
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCapabilities);
+
     let status = requester.send_receive_spdm_algorithm().is_ok();
     assert!(status);
 }
+
+#[test]
+fn test_case1_send_receive_spdm_algorithm_require_content_change_support() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (mut req_config_info, req_provision_info) = create_info();
+    req_config_info.require_content_change_support = true;
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCapabilities);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCapabilities);
+
+    // an SPDM 1.1 responder has no way to report content changes.
+    assert_eq!(
+        requester.send_receive_spdm_algorithm(),
+        Err(SPDM_STATUS_CONTENT_CHANGE_UNSUPPORTED_PEER)
+    );
+    assert_eq!(
+        requester.common.runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionAfterCapabilities
+    );
+}
+
+#[test]
+fn test_case2_send_receive_spdm_algorithm_offer_ext_algorithms() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (mut req_config_info, req_provision_info) = create_info();
+    req_config_info.ext_asym_algo = Some(SpdmExtAlgStruct {
+        registry_id: 0x1,
+        algorithm_id: 0x10,
+    });
+    req_config_info.ext_hash_algo = Some(SpdmExtAlgStruct {
+        registry_id: 0x1,
+        algorithm_id: 0x20,
+    });
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCapabilities);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCapabilities);
+
+    // the responder does not select extended algorithms, so the base ones win.
+    assert!(requester.send_receive_spdm_algorithm().is_ok());
+    let negotiate_info = &requester.common.negotiate_info;
+    assert_eq!(negotiate_info.ext_asym_sel, None);
+    assert_eq!(negotiate_info.ext_hash_sel, None);
+    assert_eq!(
+        negotiate_info.base_asym_sel,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384
+    );
+    assert_eq!(
+        negotiate_info.base_hash_sel,
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384
+    );
+    assert_eq!(negotiate_info.dhe_sel, SpdmDheAlgo::SECP_384_R1);
+    assert_eq!(negotiate_info.aead_sel, SpdmAeadAlgo::AES_256_GCM);
+    assert_eq!(
+        negotiate_info.key_schedule_sel,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE
+    );
+}
+
+#[test]
+fn test_case3_handle_spdm_algorithm_ext_only_selection() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (mut req_config_info, req_provision_info) = create_info();
+    let ext_asym_algo = SpdmExtAlgStruct {
+        registry_id: 0x1,
+        algorithm_id: 0x10,
+    };
+    let ext_hash_algo = SpdmExtAlgStruct {
+        registry_id: 0x1,
+        algorithm_id: 0x20,
+    };
+    req_config_info.ext_asym_algo = Some(ext_asym_algo);
+    req_config_info.ext_hash_algo = Some(ext_hash_algo);
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    requester.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::CERT_CAP;
+
+    // the offered extended algorithms, each selected without a base one.
+    let selections = [
+        SpdmAlgorithmsResponsePayload {
+            base_asym_sel: SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+            ext_hash_sel: Some(ext_hash_algo),
+            ..Default::default()
+        },
+        SpdmAlgorithmsResponsePayload {
+            base_hash_sel: SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            ext_asym_sel: Some(ext_asym_algo),
+            ..Default::default()
+        },
+    ];
+    for selection in selections {
+        let response = SpdmMessage {
+            header: SpdmMessageHeader {
+                version: SpdmVersion::SpdmVersion12,
+                request_response_code: SpdmRequestResponseCode::SpdmResponseAlgorithms,
+            },
+            payload: SpdmMessagePayload::SpdmAlgorithmsResponse(selection),
+        };
+        let mut receive_buffer = [0u8; 256];
+        let mut writer = Writer::init(&mut receive_buffer);
+        let used = response
+            .spdm_encode(&mut requester.common, &mut writer)
+            .unwrap();
+
+        assert_eq!(
+            requester.handle_spdm_algorithm_response(0, &[], &receive_buffer[..used]),
+            Err(SPDM_STATUS_NEGOTIATION_FAIL)
+        );
+    }
+}
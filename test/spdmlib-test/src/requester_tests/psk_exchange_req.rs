@@ -48,6 +48,11 @@ fn test_case0_send_receive_spdm_psk_exchange() {
         req_provision_info,
     );
 
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     requester.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_128_GCM;
     let measurement_summary_hash_type =
@@ -65,3 +70,70 @@ fn test_case0_send_receive_spdm_psk_exchange() {
         .is_ok();
     assert!(status);
 }
+
+#[test]
+fn test_case1_send_receive_spdm_psk_exchange_with_context() {
+    let (rsp_config_info, rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_256_GCM;
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    responder.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_256_GCM;
+    requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    requester.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT;
+
+    let mut psk_key = SpdmPskHintStruct {
+        data_size: b"TestPskHint\0".len() as u16,
+        data: [0u8; MAX_SPDM_PSK_HINT_SIZE],
+    };
+    psk_key.data[0..(psk_key.data_size as usize)].copy_from_slice(b"TestPskHint\0");
+
+    let session_id = requester
+        .send_receive_spdm_psk_exchange(
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            Some(&psk_key),
+        )
+        .unwrap();
+
+    // PSK_FINISH verifies the HMAC over a transcript that covers the
+    // responder context, so it only succeeds if both sides agree on it.
+    assert!(requester.send_receive_spdm_psk_finish(session_id).is_ok());
+}
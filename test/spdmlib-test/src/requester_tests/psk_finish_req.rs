@@ -8,6 +8,7 @@ use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
 use spdmlib::common::session::{self, SpdmSession};
+use spdmlib::common::SpdmConnectionState;
 use spdmlib::config::MAX_SPDM_PSK_HINT_SIZE;
 use spdmlib::protocol::*;
 use spdmlib::requester::RequesterContext;
@@ -76,6 +77,11 @@ fn test_case0_send_receive_spdm_psk_finish() {
         req_provision_info,
     );
 
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
     requester.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
     requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
     requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
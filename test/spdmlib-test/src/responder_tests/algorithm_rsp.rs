@@ -51,6 +51,10 @@ fn test_case0_handle_spdm_algorithm() {
         other_params_support: SpdmOpaqueSupport::empty(),
         base_asym_algo: SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
         base_hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        ext_asym_count: 0,
+        ext_asym: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
+        ext_hash_count: 0,
+        ext_hash: [SpdmExtAlgStruct::default(); SPDM_MAX_EXT_ALG_COUNT],
         alg_struct_count: 4,
         alg_struct: [
             SpdmAlgStruct {
@@ -204,3 +208,132 @@ fn test_case0_handle_spdm_algorithm() {
         );
     }
 }
+
+#[test]
+fn test_case1_handle_spdm_algorithm_ext_and_unknown_structs() {
+    let (config_info, provision_info) = create_info();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    context.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionAfterCapabilities);
+
+    let ext_algo = SpdmExtAlgStruct {
+        registry_id: 0x1,
+        algorithm_id: 0x10,
+    };
+
+    // NEGOTIATE_ALGORITHMS with ExtAsym/ExtHash entries, a DHE structure
+    // carrying an ExtAlg entry and a trailing structure of an unknown type.
+    let request = &mut [0u8; 60];
+    let mut writer = Writer::init(request);
+    assert!(SpdmMessageHeader {
+        version: SpdmVersion::SpdmVersion12,
+        request_response_code: SpdmRequestResponseCode::SpdmRequestNegotiateAlgorithms,
+    }
+    .encode(&mut writer)
+    .is_ok());
+    assert!(4u8.encode(&mut writer).is_ok()); // param1
+    assert!(0u8.encode(&mut writer).is_ok()); // param2
+    assert!(60u16.encode(&mut writer).is_ok());
+    assert!(SpdmMeasurementSpecification::DMTF
+        .encode(&mut writer)
+        .is_ok());
+    assert!(SpdmOpaqueSupport::OPAQUE_DATA_FMT1
+        .encode(&mut writer)
+        .is_ok());
+    assert!(SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384
+        .encode(&mut writer)
+        .is_ok());
+    assert!(SpdmBaseHashAlgo::TPM_ALG_SHA_384
+        .encode(&mut writer)
+        .is_ok());
+    assert!(writer.extend_from_slice(&[0u8; 12]).is_some()); // reserved
+    assert!(1u8.encode(&mut writer).is_ok()); // ext_asym_count
+    assert!(1u8.encode(&mut writer).is_ok()); // ext_hash_count
+    assert!(0u16.encode(&mut writer).is_ok()); // reserved
+    assert!(ext_algo.encode(&mut writer).is_ok());
+    assert!(ext_algo.encode(&mut writer).is_ok());
+    assert!(SpdmAlgType::SpdmAlgTypeDHE.encode(&mut writer).is_ok());
+    assert!(0x21u8.encode(&mut writer).is_ok()); // fixed count 2, ext count 1
+    assert!(SpdmDheAlgo::SECP_384_R1.encode(&mut writer).is_ok());
+    assert!(ext_algo.encode(&mut writer).is_ok());
+    assert!(SpdmAlgType::SpdmAlgTypeAEAD.encode(&mut writer).is_ok());
+    assert!(0x20u8.encode(&mut writer).is_ok());
+    assert!(SpdmAeadAlgo::AES_256_GCM.encode(&mut writer).is_ok());
+    assert!(SpdmAlgType::SpdmAlgTypeKeySchedule
+        .encode(&mut writer)
+        .is_ok());
+    assert!(0x20u8.encode(&mut writer).is_ok());
+    assert!(SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE
+        .encode(&mut writer)
+        .is_ok());
+    assert!(SpdmAlgType::Unknown(0x7).encode(&mut writer).is_ok());
+    assert!(0x20u8.encode(&mut writer).is_ok());
+    assert!(0xffffu16.encode(&mut writer).is_ok());
+    assert_eq!(writer.used(), 60);
+
+    assert!(context.handle_spdm_algorithm(request).is_ok());
+
+    let negotiate_info = &context.common.negotiate_info;
+    assert_eq!(negotiate_info.ext_asym_sel, None);
+    assert_eq!(negotiate_info.ext_hash_sel, None);
+    assert_eq!(
+        negotiate_info.base_asym_sel,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384
+    );
+    assert_eq!(
+        negotiate_info.base_hash_sel,
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384
+    );
+    assert_eq!(negotiate_info.dhe_sel, SpdmDheAlgo::SECP_384_R1);
+    assert_eq!(negotiate_info.aead_sel, SpdmAeadAlgo::AES_256_GCM);
+    assert_eq!(
+        negotiate_info.key_schedule_sel,
+        SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE
+    );
+
+    // the request goes into the transcript as received, unknown structure included.
+    let message_a = context.common.runtime_info.message_a.as_ref().to_vec();
+    assert_eq!(&message_a[..60], &request[..]);
+
+    let mut reader = Reader::init(&message_a[60..]);
+    let spdm_message = SpdmMessage::spdm_read(&mut context.common, &mut reader).unwrap();
+    assert_eq!(
+        spdm_message.header.request_response_code,
+        SpdmRequestResponseCode::SpdmResponseAlgorithms
+    );
+    if let SpdmMessagePayload::SpdmAlgorithmsResponse(payload) = &spdm_message.payload {
+        assert_eq!(payload.ext_asym_sel, None);
+        assert_eq!(payload.ext_hash_sel, None);
+        assert_eq!(payload.alg_struct_count, 4);
+        assert_eq!(
+            payload.alg_struct[0].alg_supported,
+            SpdmAlg::SpdmAlgoDhe(SpdmDheAlgo::SECP_384_R1)
+        );
+        assert_eq!(
+            payload.alg_struct[1].alg_supported,
+            SpdmAlg::SpdmAlgoAead(SpdmAeadAlgo::AES_256_GCM)
+        );
+        assert_eq!(
+            payload.alg_struct[3].alg_supported,
+            SpdmAlg::SpdmAlgoKeySchedule(SpdmKeyScheduleAlgo::SPDM_KEY_SCHEDULE)
+        );
+    } else {
+        panic!("not an ALGORITHMS response");
+    }
+}
@@ -2,14 +2,16 @@
 //
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
-use crate::common::device_io::{FakeSpdmDeviceIoReceve, SharedBuffer};
+use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedBuffer};
 use crate::common::secret_callback::SECRET_ASYM_IMPL_INSTANCE;
 use crate::common::transport::PciDoeTransportEncap;
-use crate::common::util::create_info;
+use crate::common::util::{create_info, get_rsp_cert_chain_buff};
 use codec::{Codec, Writer};
 use spdmlib::common::*;
+use spdmlib::error::{SPDM_STATUS_INVALID_CERT, SPDM_STATUS_INVALID_PARAMETER};
 use spdmlib::message::*;
 use spdmlib::protocol::*;
+use spdmlib::requester::RequesterContext;
 use spdmlib::{config, responder, secret};
 
 #[test]
@@ -54,6 +56,7 @@ fn test_case0_handle_spdm_certificate() {
     let mut writer = Writer::init(capabilities);
     let value = SpdmGetCertificateRequestPayload {
         slot_id: 100,
+        request_attribute: SpdmGetCertificateRequestAttributes::empty(),
         offset: 100,
         length: 600,
     };
@@ -106,3 +109,79 @@ fn test_case0_handle_spdm_certificate() {
         }
     }
 }
+
+#[test]
+#[cfg(feature = "hashed-transcript-data")]
+fn test_case1_provision_cert_chain_from_der() {
+    let (rsp_config_info, mut rsp_provision_info) = create_info();
+    let (req_config_info, req_provision_info) = create_info();
+    rsp_provision_info.my_cert_chain_data = [None, None, None, None, None, None, None, None];
+
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        rsp_config_info,
+        rsp_provision_info,
+    );
+
+    let der_cert_chain =
+        include_bytes!("../../../../test_key/ecp384/bundle_responder.certchain.der");
+    assert_eq!(
+        responder.provision_cert_chain(SPDM_MAX_SLOT_NUMBER as u8, der_cert_chain),
+        Err(SPDM_STATUS_INVALID_PARAMETER)
+    );
+    assert_eq!(
+        responder.provision_cert_chain(0, &[0x30, 0x03, 0x02, 0x01, 0x00]),
+        Err(SPDM_STATUS_INVALID_CERT)
+    );
+    assert!(responder.common.provision_info.my_cert_chain_data[0].is_none());
+
+    // nothing negotiated yet, the header waits for the hash algorithm.
+    assert!(responder.provision_cert_chain(0, der_cert_chain).is_ok());
+    assert!(responder.common.provision_info.my_cert_chain[0].is_none());
+
+    responder.common.reset_runtime_info();
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    assert!(responder.provision_cert_chain(0, der_cert_chain).is_ok());
+    let expected = get_rsp_cert_chain_buff();
+    assert_eq!(
+        responder.common.provision_info.my_cert_chain[0]
+            .as_ref()
+            .unwrap()
+            .as_ref(),
+        expected.as_ref()
+    );
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let pcidoe_transport_encap2 = &mut PciDoeTransportEncap {};
+    let mut device_io_requester = FakeSpdmDeviceIo::new(&shared_buffer, &mut responder);
+    let mut requester = RequesterContext::new(
+        &mut device_io_requester,
+        pcidoe_transport_encap2,
+        req_config_info,
+        req_provision_info,
+    );
+    requester
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+    requester.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    requester.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+
+    assert!(requester.send_receive_spdm_certificate(None, 0).is_ok());
+    assert_eq!(
+        requester.common.peer_info.peer_cert_chain[0]
+            .as_ref()
+            .unwrap()
+            .as_ref(),
+        expected.as_ref()
+    );
+}
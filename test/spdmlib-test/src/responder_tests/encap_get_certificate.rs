@@ -84,6 +84,7 @@ fn test_handle_encap_response_certificate() {
         },
         payload: SpdmMessagePayload::SpdmCertificateResponse(SpdmCertificateResponsePayload {
             slot_id: 0,
+            cert_model: SpdmCertificateModel::default(),
             portion_length: CERT_PORTION_LEN as u16,
             remainder_length: 0x600,
             cert_chain: [0xa; CERT_PORTION_LEN],
@@ -121,6 +122,7 @@ fn test_handle_encap_response_certificate() {
     cert_rsp.payload =
         SpdmMessagePayload::SpdmCertificateResponse(SpdmCertificateResponsePayload {
             slot_id: 0xa,
+            cert_model: SpdmCertificateModel::default(),
             portion_length: CERT_PORTION_LEN as u16,
             remainder_length: 0x400,
             cert_chain: [0xa; CERT_PORTION_LEN],
@@ -138,6 +140,7 @@ fn test_handle_encap_response_certificate() {
     cert_rsp.payload =
         SpdmMessagePayload::SpdmCertificateResponse(SpdmCertificateResponsePayload {
             slot_id: 0,
+            cert_model: SpdmCertificateModel::default(),
             portion_length: CERT_PORTION_LEN as u16,
             remainder_length: 0x400,
             cert_chain: [0xa; CERT_PORTION_LEN],
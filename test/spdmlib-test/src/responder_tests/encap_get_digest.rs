@@ -84,6 +84,7 @@ fn test_handle_encap_response_digest() {
                 },
                 SPDM_MAX_SLOT_NUMBER,
             ),
+            ..Default::default()
         }),
     };
     assert!(digests_rsp
@@ -272,6 +272,7 @@ fn write_spdm_get_digest_response(
                 },
                 SPDM_MAX_SLOT_NUMBER,
             ),
+            ..Default::default()
         }),
     };
     let _ = response
@@ -292,6 +293,7 @@ fn write_spdm_get_certificate_response(
         },
         payload: SpdmMessagePayload::SpdmCertificateResponse(SpdmCertificateResponsePayload {
             slot_id: 0,
+            cert_model: SpdmCertificateModel::default(),
             portion_length: CERT_PORTION_LEN as u16,
             remainder_length: 0x200,
             cert_chain: [0xffu8; CERT_PORTION_LEN],
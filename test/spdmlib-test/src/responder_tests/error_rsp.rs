@@ -6,6 +6,8 @@ use crate::common::device_io::{FakeSpdmDeviceIoReceve, SharedBuffer};
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
+use codec::{Codec, Writer};
+use spdmlib::common::SpdmConnectionState;
 use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::{responder, secret};
@@ -28,3 +30,53 @@ fn test_case0_send_spdm_error() {
 
     context.send_spdm_error(SpdmErrorCode::SpdmErrorInvalidRequest, 0);
 }
+
+#[test]
+fn test_case1_latched_error_state() {
+    let (config_info, provision_info) = create_info();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+    assert!(context.last_error().is_none());
+
+    // RESPOND_IF_READY is answered with an UnsupportedRequest error.
+    let bytes = &mut [0u8; 4];
+    let mut writer = Writer::init(bytes);
+    let header = SpdmMessageHeader {
+        version: context.common.negotiate_info.spdm_version_sel,
+        request_response_code: SpdmRequestResponseCode::SpdmRequestResponseIfReady,
+    };
+    assert!(header.encode(&mut writer).is_ok());
+    let _ = context.dispatch_message(bytes);
+    assert_eq!(
+        context.last_error(),
+        Some(responder::SpdmResponderErrorState {
+            error_code: SpdmErrorCode::SpdmErrorUnsupportedRequest,
+            error_data: SpdmRequestResponseCode::SpdmRequestResponseIfReady.get_u8(),
+        })
+    );
+
+    context.clear_error_state();
+    assert!(context.last_error().is_none());
+
+    // the responder goes on with the next request.
+    let mut writer = Writer::init(bytes);
+    let header = SpdmMessageHeader {
+        version: SpdmVersion::SpdmVersion10,
+        request_response_code: SpdmRequestResponseCode::SpdmRequestGetVersion,
+    };
+    assert!(header.encode(&mut writer).is_ok());
+    assert!(context.dispatch_message(bytes).is_ok());
+    assert!(context.last_error().is_none());
+    assert_eq!(
+        context.common.runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionAfterVersion
+    );
+}
@@ -0,0 +1,113 @@
+// Copyright (c) 2023 Intel Corporation
+//
+// SPDX-License-Identifier: BSD-2-Clause-Patent
+
+use crate::common::device_io::{FakeSpdmDeviceIoReceve, SharedBuffer};
+use crate::common::secret_callback::*;
+use crate::common::transport::PciDoeTransportEncap;
+use crate::common::util::create_info;
+use codec::{Codec, Writer};
+use spdmlib::common::session::SpdmSession;
+use spdmlib::common::SpdmCodec;
+use spdmlib::message::*;
+use spdmlib::protocol::*;
+use spdmlib::{responder, secret};
+
+#[test]
+fn test_case0_handle_spdm_subscribe_event_types() {
+    let (config_info, provision_info) = create_info();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    let session_id = (0xffu32 << 16) + 0xffu32;
+    context.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion13;
+    context.common.negotiate_info.rsp_capabilities_sel = SpdmResponseCapabilityFlags::EVENT_CAP;
+    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session[0].setup(session_id).unwrap();
+
+    let bytes = &mut [0u8; 16];
+    let mut writer = Writer::init(bytes);
+    let value = SpdmMessageHeader {
+        version: SpdmVersion::SpdmVersion13,
+        request_response_code: SpdmRequestResponseCode::SpdmRequestSubscribeEventTypes,
+    };
+    assert!(value.encode(&mut writer).is_ok());
+    let payload = SpdmSubscribeEventTypesRequestPayload {
+        subscribe_event_group_count: 1,
+        subscribe_list: SpdmEventListStruct::from_slice(&[0, 1, 0, 1]).unwrap(),
+    };
+    assert!(payload
+        .spdm_encode(&mut context.common, &mut writer)
+        .is_ok());
+    let used = writer.used();
+
+    let response = &mut [0u8; 16];
+    let mut writer = Writer::init(response);
+    context.write_spdm_subscribe_event_types_response(session_id, &bytes[..used], &mut writer);
+    assert_eq!(
+        writer.used_slice(),
+        &[
+            0x13,
+            SpdmRequestResponseCode::SpdmResponseSubscribeEventTypesAck.get_u8(),
+            0,
+            0
+        ]
+    );
+    assert_eq!(
+        context.common.session[0]
+            .event_subscription
+            .as_ref()
+            .unwrap()
+            .as_slice(),
+        &[0, 1, 0, 1]
+    );
+}
+
+#[test]
+fn test_case1_handle_spdm_get_supported_event_types_without_event_cap() {
+    let (config_info, provision_info) = create_info();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    let session_id = (0xffu32 << 16) + 0xffu32;
+    context.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion13;
+    context.common.session = gen_array_clone(SpdmSession::new(), 4);
+    context.common.session[0].setup(session_id).unwrap();
+
+    let bytes = &mut [0u8; 4];
+    let mut writer = Writer::init(bytes);
+    let value = SpdmMessageHeader {
+        version: SpdmVersion::SpdmVersion13,
+        request_response_code: SpdmRequestResponseCode::SpdmRequestGetSupportedEventTypes,
+    };
+    assert!(value.encode(&mut writer).is_ok());
+
+    let response = &mut [0u8; 16];
+    let mut writer = Writer::init(response);
+    context.write_spdm_supported_event_types_response(session_id, bytes, &mut writer);
+    assert_eq!(
+        writer.used_slice(),
+        &[
+            0x13,
+            SpdmRequestResponseCode::SpdmResponseError.get_u8(),
+            SpdmErrorCode::SpdmErrorUnsupportedRequest.get_u8(),
+            SpdmRequestResponseCode::SpdmRequestGetSupportedEventTypes.get_u8()
+        ]
+    );
+}
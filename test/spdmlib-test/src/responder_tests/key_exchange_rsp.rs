@@ -11,7 +11,8 @@ use bytes::BytesMut;
 use codec::{Codec, Writer};
 use spdmlib::common::opaque;
 use spdmlib::common::opaque::*;
-use spdmlib::common::SpdmCodec;
+use spdmlib::common::{SpdmCodec, SpdmConnectionState};
+use spdmlib::error::SPDM_STATUS_INVALID_STATE_PEER;
 use spdmlib::message::*;
 use spdmlib::protocol::*;
 use spdmlib::{crypto, responder, secret};
@@ -83,3 +84,49 @@ fn test_case0_handle_spdm_key_exchange() {
 
     context.handle_spdm_key_exchange(bytes);
 }
+
+#[test]
+fn test_case1_require_mut_auth_unauthenticated_requester() {
+    let (mut config_info, provision_info) = create_info();
+    config_info.require_mut_auth = true;
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    context.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.common.negotiate_info.req_capabilities_sel =
+        SpdmRequestCapabilityFlags::KEY_EX_CAP | SpdmRequestCapabilityFlags::CERT_CAP;
+    context
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // refused before the request body is looked at.
+    let request = &mut [0u8; 4];
+    let mut writer = Writer::init(request);
+    let value = SpdmMessageHeader {
+        version: SpdmVersion::SpdmVersion12,
+        request_response_code: SpdmRequestResponseCode::SpdmRequestKeyExchange,
+    };
+    let _ = value.encode(&mut writer);
+
+    let response = &mut [0u8; 1024];
+    let mut writer = Writer::init(response);
+    assert_eq!(
+        context.write_spdm_key_exchange_response(request, &mut writer),
+        Err(SPDM_STATUS_INVALID_STATE_PEER)
+    );
+    assert_eq!(
+        response[1],
+        SpdmRequestResponseCode::SpdmResponseError.get_u8()
+    );
+    assert_eq!(response[2], SpdmErrorCode::SpdmErrorInvalidRequest.get_u8());
+}
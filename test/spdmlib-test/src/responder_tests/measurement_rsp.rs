@@ -5,13 +5,30 @@
 use crate::common::device_io::{FakeSpdmDeviceIoReceve, SharedBuffer};
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
-use crate::common::util::create_info;
+use crate::common::util::{create_info, get_rsp_cert_chain_buff};
 use codec::{Codec, Reader, Writer};
 use spdmlib::common::SpdmCodec;
 use spdmlib::common::SpdmConnectionState;
 use spdmlib::message::*;
 use spdmlib::protocol::*;
-use spdmlib::{responder, secret};
+use spdmlib::responder::cached_measurements::{self, SpdmCachedMeasurements};
+use spdmlib::responder::measurement_authorization::{self, SpdmMeasurementAuthorization};
+use spdmlib::{config, responder, secret};
+use std::cell::Cell;
+
+thread_local! {
+    // index refused out of session by the registered authorization callback.
+    static DENIED_MEASUREMENT_INDEX: Cell<Option<u8>> = const { Cell::new(None) };
+}
+
+fn authorize_measurement(
+    _ctx: &responder::ResponderContext,
+    session_id: Option<u32>,
+    measurement_index: u8,
+) -> bool {
+    session_id.is_some()
+        || DENIED_MEASUREMENT_INDEX.with(|denied| denied.get()) != Some(measurement_index)
+}
 
 #[test]
 fn test_case0_handle_spdm_measurement() {
@@ -209,3 +226,211 @@ fn test_case1_handle_spdm_measurement() {
         }
     }
 }
+
+#[test]
+fn test_case2_handle_spdm_measurement_unauthorized_index() {
+    let (config_info, provision_info) = create_info();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+    measurement_authorization::register(SpdmMeasurementAuthorization {
+        authorize_measurement_cb: authorize_measurement,
+    });
+    DENIED_MEASUREMENT_INDEX.with(|denied| denied.set(Some(2)));
+
+    context.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    context.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    context.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    context.common.negotiate_info.measurement_specification_sel =
+        SpdmMeasurementSpecification::DMTF;
+    context
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let get_measurements =
+        |context: &mut responder::ResponderContext,
+         measurement_operation: SpdmMeasurementOperation| {
+            let request = &mut [0u8; 64];
+            let mut writer = Writer::init(request);
+            let message = SpdmMessage {
+                header: SpdmMessageHeader {
+                    version: SpdmVersion::SpdmVersion12,
+                    request_response_code: SpdmRequestResponseCode::SpdmRequestGetMeasurements,
+                },
+                payload: SpdmMessagePayload::SpdmGetMeasurementsRequest(
+                    SpdmGetMeasurementsRequestPayload {
+                        measurement_attributes: SpdmMeasurementAttributes::empty(),
+                        measurement_operation,
+                        nonce: SpdmNonceStruct::default(),
+                        slot_id: 0,
+                    },
+                ),
+            };
+            let used = message
+                .spdm_encode(&mut context.common, &mut writer)
+                .unwrap();
+
+            let response = &mut [0u8; config::MAX_SPDM_MSG_SIZE];
+            let mut writer = Writer::init(response);
+            context.write_spdm_measurement_response(None, &request[..used], &mut writer);
+            let mut reader = Reader::init(writer.used_slice());
+            SpdmMessage::spdm_read(&mut context.common, &mut reader).unwrap()
+        };
+
+    // a refused index looks like one that does not exist.
+    let response = get_measurements(&mut context, SpdmMeasurementOperation::Unknown(2));
+    assert_eq!(
+        response.header.request_response_code,
+        SpdmRequestResponseCode::SpdmResponseError
+    );
+    if let SpdmMessagePayload::SpdmErrorResponse(SpdmErrorResponsePayload { error_code, .. }) =
+        response.payload
+    {
+        assert_eq!(error_code, SpdmErrorCode::SpdmErrorInvalidRequest);
+    } else {
+        panic!("expected an ERROR response");
+    }
+
+    let response = get_measurements(&mut context, SpdmMeasurementOperation::Unknown(1));
+    assert_eq!(
+        response.header.request_response_code,
+        SpdmRequestResponseCode::SpdmResponseMeasurements
+    );
+
+    // the refused block is left out of a request for all blocks.
+    let total_number = (SECRET_MEASUREMENT_IMPL_INSTANCE.measurement_collection_cb)(
+        SpdmVersion::SpdmVersion12,
+        SpdmMeasurementSpecification::DMTF,
+        SpdmMeasurementHashAlgo::TPM_ALG_SHA_384,
+        SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber.get_u8() as usize,
+    )
+    .unwrap()
+    .number_of_blocks;
+    let response = get_measurements(
+        &mut context,
+        SpdmMeasurementOperation::SpdmMeasurementRequestAll,
+    );
+    if let SpdmMessagePayload::SpdmMeasurementsResponse(payload) = response.payload {
+        let measurement_record = payload.measurement_record;
+        assert_eq!(payload.number_of_measurement, total_number - 1);
+        assert_eq!(measurement_record.number_of_blocks, total_number - 1);
+        let mut reader = Reader::init(
+            &measurement_record.measurement_record_data
+                [..measurement_record.measurement_record_length.get() as usize],
+        );
+        for _ in 0..measurement_record.number_of_blocks {
+            let measurement_block = SpdmMeasurementBlockStructure::read(&mut reader).unwrap();
+            assert_ne!(measurement_block.index, 2);
+        }
+        assert!(!reader.any_left());
+    } else {
+        panic!("expected a MEASUREMENTS response");
+    }
+
+    DENIED_MEASUREMENT_INDEX.with(|denied| denied.set(None));
+}
+
+// a MEASUREMENTS response for SPDM 1.2 and ECDSA P-384 without blocks,
+// signed ahead of time.
+fn cached_measurement_response(
+    _ctx: &responder::ResponderContext,
+    get_measurements: &SpdmGetMeasurementsRequestPayload,
+    response: &mut [u8],
+) -> Option<usize> {
+    let mut writer = Writer::init(response);
+    writer.extend_from_slice(&[0x12, 0x60, 0, get_measurements.slot_id])?;
+    writer.extend_from_slice(&[0u8; 4])?; // NumberOfBlocks, MeasurementRecordLength
+    writer.extend_from_slice(&[0xCAu8; SPDM_NONCE_SIZE])?;
+    writer.extend_from_slice(&[0u8; 2])?; // OpaqueDataLength
+    writer.extend_from_slice(&[0x5Au8; ECDSA_ECC_NIST_P384_KEY_SIZE])?;
+    Some(writer.used())
+}
+
+#[test]
+fn test_case3_handle_spdm_measurement_cached() {
+    let (config_info, provision_info) = create_info();
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+    let shared_buffer = SharedBuffer::new();
+    let mut socket_io_transport = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let mut context = responder::ResponderContext::new(
+        &mut socket_io_transport,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+    cached_measurements::register(SpdmCachedMeasurements {
+        cached_measurements_cb: cached_measurement_response,
+    });
+    context.common.provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+
+    context.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion12;
+    context.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    context.common.negotiate_info.base_asym_sel = SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    context.common.negotiate_info.measurement_hash_sel = SpdmMeasurementHashAlgo::TPM_ALG_SHA_384;
+    context.common.negotiate_info.measurement_specification_sel =
+        SpdmMeasurementSpecification::DMTF;
+    context
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let request = &mut [0u8; 64];
+    let mut writer = Writer::init(request);
+    let message = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion12,
+            request_response_code: SpdmRequestResponseCode::SpdmRequestGetMeasurements,
+        },
+        payload: SpdmMessagePayload::SpdmGetMeasurementsRequest(
+            SpdmGetMeasurementsRequestPayload {
+                measurement_attributes: SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+                measurement_operation: SpdmMeasurementOperation::Unknown(1),
+                nonce: SpdmNonceStruct {
+                    data: [100u8; SPDM_NONCE_SIZE],
+                },
+                slot_id: 0,
+            },
+        ),
+    };
+    let used = message
+        .spdm_encode(&mut context.common, &mut writer)
+        .unwrap();
+    let get_measurements = |context: &mut responder::ResponderContext| {
+        let response = &mut [0u8; config::MAX_SPDM_MSG_SIZE];
+        let mut writer = Writer::init(response);
+        context.write_spdm_measurement_response(None, &request[..used], &mut writer);
+        writer.used_slice().to_vec()
+    };
+
+    // off by default: the response is signed now, over the request nonce.
+    let response = get_measurements(&mut context);
+    assert_eq!(response[1], 0x60);
+    assert_ne!(response[8..8 + SPDM_NONCE_SIZE], [0xCAu8; SPDM_NONCE_SIZE]);
+
+    context.common.config_info.serve_cached_measurements = true;
+    let expected = &mut [0u8; config::MAX_SPDM_MSG_SIZE];
+    let expected_used = cached_measurement_response(
+        &context,
+        &SpdmGetMeasurementsRequestPayload::default(),
+        expected,
+    )
+    .unwrap();
+    let response = get_measurements(&mut context);
+    assert_eq!(response, &expected[..expected_used]);
+    #[cfg(not(feature = "hashed-transcript-data"))]
+    assert!(context.common.runtime_info.message_m.as_ref().is_empty());
+}
@@ -32,6 +32,8 @@ mod end_session_rsp;
 
 mod error_rsp;
 
+mod event_rsp;
+
 mod finish_rsp;
 
 mod heartbeat_rsp;
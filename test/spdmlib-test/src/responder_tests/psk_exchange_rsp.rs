@@ -6,10 +6,11 @@ use crate::common::device_io::{FakeSpdmDeviceIoReceve, SharedBuffer};
 use crate::common::secret_callback::*;
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::create_info;
-use codec::{Codec, Writer};
+use codec::{Codec, Reader, Writer};
 use spdmlib::common::opaque;
 use spdmlib::common::opaque::*;
-use spdmlib::common::SpdmCodec;
+use spdmlib::common::session::SpdmSessionState;
+use spdmlib::common::{SpdmCodec, SpdmConnectionState, SpdmSessionLimitPolicy};
 use spdmlib::config::{MAX_SPDM_PSK_CONTEXT_SIZE, MAX_SPDM_PSK_HINT_SIZE};
 use spdmlib::message::*;
 use spdmlib::protocol::*;
@@ -70,3 +71,223 @@ fn test_case0_handle_spdm_psk_exchange() {
     bytes[2..].copy_from_slice(&challenge[0..1022]);
     context.handle_spdm_psk_exchange(bytes);
 }
+
+#[test]
+fn test_case1_handle_spdm_psk_exchange_with_context() {
+    let (config_info, provision_info) = create_info();
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_256_GCM;
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    responder.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    let mut request = [0u8; 256];
+    let mut writer = Writer::init(&mut request);
+    let mut value = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion11,
+            request_response_code: SpdmRequestResponseCode::SpdmRequestPskExchange,
+        },
+        payload: SpdmMessagePayload::SpdmPskExchangeRequest(SpdmPskExchangeRequestPayload {
+            measurement_summary_hash_type:
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            req_session_id: 0xFFFE,
+            psk_hint: SpdmPskHintStruct {
+                data_size: b"TestPskHint\0".len() as u16,
+                data: [0u8; MAX_SPDM_PSK_HINT_SIZE],
+            },
+            psk_context: SpdmPskContextStruct {
+                data_size: 48,
+                data: [0xAAu8; MAX_SPDM_PSK_CONTEXT_SIZE],
+            },
+            opaque: SpdmOpaqueStruct {
+                data_size: opaque::REQ_DMTF_OPAQUE_DATA_SUPPORT_VERSION_LIST_DSP0274_FMT1.len()
+                    as u16,
+                data: [0u8; MAX_SPDM_OPAQUE_SIZE],
+            },
+        }),
+    };
+    if let SpdmMessagePayload::SpdmPskExchangeRequest(payload) = &mut value.payload {
+        payload.psk_hint.data[0..payload.psk_hint.data_size as usize]
+            .copy_from_slice(b"TestPskHint\0");
+        payload.opaque.data[0..payload.opaque.data_size as usize]
+            .copy_from_slice(&opaque::REQ_DMTF_OPAQUE_DATA_SUPPORT_VERSION_LIST_DSP0274_FMT1);
+    }
+    let used = value
+        .spdm_encode(&mut responder.common, &mut writer)
+        .unwrap();
+    let _ = responder.handle_spdm_psk_exchange(&request[..used]);
+
+    let mut response = [0u8; 1024];
+    let len = shared_buffer.get_buffer(&mut response);
+    // Skip the 8-byte PCI DOE header written by the transport.
+    let mut reader = Reader::init(&response[8..len]);
+    let message = SpdmMessage::spdm_read(&mut responder.common, &mut reader).unwrap();
+    match message.payload {
+        SpdmMessagePayload::SpdmPskExchangeResponse(payload) => {
+            let context_size = payload.psk_context.data_size as usize;
+            assert_ne!(context_size, 0);
+            assert!(payload.psk_context.data[..context_size]
+                .iter()
+                .any(|b| *b != 0));
+        }
+        _ => panic!("unexpected response"),
+    }
+}
+
+// PSK_EXCHANGE with `req_session_id`, returning the response code.
+fn psk_exchange_request_response(
+    responder: &mut responder::ResponderContext,
+    req_session_id: u16,
+) -> SpdmRequestResponseCode {
+    let mut request = [0u8; 256];
+    let mut writer = Writer::init(&mut request);
+    let mut payload = SpdmPskExchangeRequestPayload {
+        measurement_summary_hash_type:
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        req_session_id,
+        psk_hint: SpdmPskHintStruct {
+            data_size: b"TestPskHint\0".len() as u16,
+            data: [0u8; MAX_SPDM_PSK_HINT_SIZE],
+        },
+        psk_context: SpdmPskContextStruct {
+            data_size: 48,
+            data: [0xAAu8; MAX_SPDM_PSK_CONTEXT_SIZE],
+        },
+        opaque: SpdmOpaqueStruct {
+            data_size: opaque::REQ_DMTF_OPAQUE_DATA_SUPPORT_VERSION_LIST_DSP0274_FMT1.len() as u16,
+            data: [0u8; MAX_SPDM_OPAQUE_SIZE],
+        },
+    };
+    payload.psk_hint.data[0..payload.psk_hint.data_size as usize].copy_from_slice(b"TestPskHint\0");
+    payload.opaque.data[0..payload.opaque.data_size as usize]
+        .copy_from_slice(&opaque::REQ_DMTF_OPAQUE_DATA_SUPPORT_VERSION_LIST_DSP0274_FMT1);
+    let value = SpdmMessage {
+        header: SpdmMessageHeader {
+            version: SpdmVersion::SpdmVersion11,
+            request_response_code: SpdmRequestResponseCode::SpdmRequestPskExchange,
+        },
+        payload: SpdmMessagePayload::SpdmPskExchangeRequest(payload),
+    };
+    let used = value
+        .spdm_encode(&mut responder.common, &mut writer)
+        .unwrap();
+    let mut response = [0u8; 1024];
+    let mut writer = Writer::init(&mut response);
+    let _ = responder.write_spdm_psk_exchange_response(&request[..used], &mut writer);
+
+    let mut reader = Reader::init(writer.used_slice());
+    let message_header = SpdmMessageHeader::read(&mut reader).unwrap();
+    if message_header.request_response_code == SpdmRequestResponseCode::SpdmResponseError {
+        assert_eq!(
+            u8::read(&mut reader),
+            Some(SpdmErrorCode::SpdmErrorSessionLimitExceeded.get_u8())
+        );
+    }
+    message_header.request_response_code
+}
+
+#[test]
+fn test_case2_handle_spdm_psk_exchange_session_limit() {
+    let (mut config_info, provision_info) = create_info();
+    config_info.max_session_count = 2;
+    let shared_buffer = SharedBuffer::new();
+    let mut device_io_responder = FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let pcidoe_transport_encap = &mut PciDoeTransportEncap {};
+
+    secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+
+    let mut responder = responder::ResponderContext::new(
+        &mut device_io_responder,
+        pcidoe_transport_encap,
+        config_info,
+        provision_info,
+    );
+
+    responder.common.negotiate_info.base_hash_sel = SpdmBaseHashAlgo::TPM_ALG_SHA_384;
+    responder.common.negotiate_info.aead_sel = SpdmAeadAlgo::AES_256_GCM;
+    responder.common.negotiate_info.spdm_version_sel = SpdmVersion::SpdmVersion11;
+    responder.common.negotiate_info.rsp_capabilities_sel =
+        SpdmResponseCapabilityFlags::PSK_CAP_WITH_CONTEXT;
+    responder
+        .common
+        .runtime_info
+        .set_connection_state(SpdmConnectionState::SpdmConnectionNegotiated);
+
+    // up to the cap.
+    for req_session_id in [0xFFFE, 0xFFFD] {
+        assert_eq!(
+            psk_exchange_request_response(&mut responder, req_session_id),
+            SpdmRequestResponseCode::SpdmResponsePskExchangeRsp
+        );
+    }
+    let session_ids: Vec<u32> = responder
+        .common
+        .session
+        .iter()
+        .map(|session| session.get_session_id())
+        .filter(|session_id| *session_id != 0)
+        .collect();
+    assert_eq!(session_ids.len(), 2);
+
+    // rejected by default.
+    assert_eq!(
+        psk_exchange_request_response(&mut responder, 0xFFFC),
+        SpdmRequestResponseCode::SpdmResponseError
+    );
+
+    // sessions still in their handshake are not idle, nothing is evicted.
+    responder.common.config_info.session_limit_policy = SpdmSessionLimitPolicy::EvictOldestIdle;
+    assert_eq!(
+        psk_exchange_request_response(&mut responder, 0xFFFC),
+        SpdmRequestResponseCode::SpdmResponseError
+    );
+
+    // the established session idle the longest makes room.
+    for session_id in session_ids.iter() {
+        responder
+            .common
+            .get_session_via_id(*session_id)
+            .unwrap()
+            .set_session_state(SpdmSessionState::SpdmSessionEstablished);
+    }
+    responder.common.touch_session(session_ids[0]);
+    assert_eq!(
+        psk_exchange_request_response(&mut responder, 0xFFFC),
+        SpdmRequestResponseCode::SpdmResponsePskExchangeRsp
+    );
+    assert!(responder
+        .common
+        .get_immutable_session_via_id(session_ids[0])
+        .is_some());
+    assert!(responder
+        .common
+        .get_immutable_session_via_id(session_ids[1])
+        .is_none());
+    assert_eq!(
+        responder
+            .common
+            .session
+            .iter()
+            .filter(|session| session.get_session_id() != 0)
+            .count(),
+        2
+    );
+}
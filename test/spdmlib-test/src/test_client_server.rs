@@ -3,15 +3,28 @@
 // SPDX-License-Identifier: BSD-2-Clause-Patent
 
 use crate::common::device_io::{FakeSpdmDeviceIo, FakeSpdmDeviceIoReceve, SharedBuffer};
-use crate::common::secret_callback::SECRET_ASYM_IMPL_INSTANCE;
+use crate::common::secret_callback::{
+    SECRET_ASYM_IMPL_INSTANCE, SECRET_MEASUREMENT_IMPL_INSTANCE, SECRET_PSK_IMPL_INSTANCE,
+};
 use crate::common::transport::PciDoeTransportEncap;
 use crate::common::util::{get_rsp_cert_chain_buff, req_create_info, rsp_create_info};
+use mctp_transport::I2cTransportEncap;
+#[cfg(feature = "mut-auth")]
+use spdmlib::common::session::SpdmSessionState;
+use spdmlib::common::{SpdmConnectionState, SpdmDeviceIo};
+use spdmlib::error::{SpdmResult, SPDM_STATUS_INVALID_PARAMETER, SPDM_STATUS_NEGOTIATION_FAIL};
+#[cfg(feature = "mut-auth")]
+use spdmlib::message::SpdmKeyExchangeMutAuthAttributes;
+use spdmlib::message::{SpdmMeasurementAttributes, SpdmMeasurementOperation};
 use spdmlib::protocol::{
+    SpdmBaseAsymAlgo, SpdmBaseHashAlgo, SpdmMeasurementHashAlgo, SpdmMeasurementRecordStructure,
     SpdmMeasurementSummaryHashType, SpdmReqAsymAlgo, SpdmRequestCapabilityFlags,
-    SpdmResponseCapabilityFlags,
+    SpdmResponseCapabilityFlags, SpdmVersion, SHA384_DIGEST_SIZE,
 };
 use spdmlib::requester;
 use spdmlib::responder;
+use std::cell::Cell;
+use std::collections::VecDeque;
 
 #[test]
 fn intergration_client_server() {
@@ -114,3 +127,1136 @@ fn intergration_client_server() {
         log::info!("\nSession session_id not got ????? \n");
     }
 }
+
+#[test]
+fn intergration_client_server_i2c() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut I2cTransportEncap::new(0x20, 0x10, 9, 8, false);
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut I2cTransportEncap::new(0x10, 0x20, 8, 9, true);
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    // every message is split into SMBus packets and reassembled on the other side.
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+
+    let session_id = requester_context
+        .start_session(
+            false,
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+    assert!(requester_context.end_session(session_id).is_ok());
+}
+
+#[test]
+fn intergration_client_server_reuse_requester() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let shared_buffer_a = SharedBuffer::new();
+    let device_io_responder_a = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer_a);
+    let transport_encap_responder_a = &mut PciDoeTransportEncap {};
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context_a = responder::ResponderContext::new(
+        device_io_responder_a,
+        transport_encap_responder_a,
+        config_info,
+        provision_info,
+    );
+
+    let shared_buffer_b = SharedBuffer::new();
+    let device_io_responder_b = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer_b);
+    let transport_encap_responder_b = &mut PciDoeTransportEncap {};
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context_b = responder::ResponderContext::new(
+        device_io_responder_b,
+        transport_encap_responder_b,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester_a =
+        &mut FakeSpdmDeviceIo::new(&shared_buffer_a, &mut responder_context_a);
+    let device_io_requester_b =
+        &mut FakeSpdmDeviceIo::new(&shared_buffer_b, &mut responder_context_b);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let peer_root_cert_data = provision_info.peer_root_cert_data.clone();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester_a,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+    let session_id = requester_context
+        .start_session(
+            false,
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+
+    // nothing learnt from the first device survives.
+    requester_context.common.clear_peer_data();
+    requester_context.common.clear_provisioned_peer_data();
+    assert_eq!(
+        requester_context.common.runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionNotStarted
+    );
+    assert!(requester_context.common.peer_info.peer_cert_chain[0].is_none());
+    assert!(requester_context
+        .common
+        .peer_info
+        .peer_provisioned_slot_mask
+        .is_none());
+    assert!(requester_context
+        .common
+        .get_immutable_session_via_id(session_id)
+        .is_none());
+    assert!(requester_context
+        .common
+        .provision_info
+        .peer_root_cert_data
+        .is_none());
+    assert!(requester_context.send_receive_spdm_digest(None).is_err());
+
+    requester_context.common.device_io = device_io_requester_b;
+    requester_context.common.provision_info.peer_root_cert_data = peer_root_cert_data;
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+    let session_id = requester_context
+        .start_session(
+            false,
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+    assert!(requester_context.end_session(session_id).is_ok());
+}
+
+// Holds every request back until its response is read, so the requester
+// can have several requests in flight.
+struct PipelinedDeviceIo<'a> {
+    inner: FakeSpdmDeviceIo<'a>,
+    pending: VecDeque<Vec<u8>>,
+    max_pending: &'a Cell<usize>,
+}
+
+impl SpdmDeviceIo for PipelinedDeviceIo<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        self.pending.push_back(buffer.to_vec());
+        if self.pending.len() > self.max_pending.get() {
+            self.max_pending.set(self.pending.len());
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], timeout: usize) -> Result<usize, usize> {
+        if let Some(request) = self.pending.pop_front() {
+            self.inner.send(&request).map_err(|_| 0usize)?;
+        }
+        self.inner.receive(buffer, timeout)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.inner.flush_all()
+    }
+
+    fn supports_pipelining(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn intergration_client_server_measurement_batch() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    spdmlib::secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let max_pending = Cell::new(0usize);
+    let device_io_requester = &mut PipelinedDeviceIo {
+        inner: FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context),
+        pending: VecDeque::new(),
+        max_pending: &max_pending,
+    };
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+    assert_eq!(max_pending.get(), 1);
+
+    // the signature on the last response only verifies if L1/L2 holds
+    // every request and response in order.
+    let indices = [1u8, 2, 3];
+    let measurement_records = requester_context
+        .send_receive_spdm_measurement_batch(
+            None,
+            0,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            &indices,
+        )
+        .unwrap();
+    assert_eq!(max_pending.get(), indices.len());
+    assert_eq!(measurement_records.len(), indices.len());
+
+    for (index, batch_record) in indices.iter().zip(measurement_records.iter()) {
+        let mut total_number = 0u8;
+        let mut measurement_record = SpdmMeasurementRecordStructure::default();
+        assert!(requester_context
+            .send_receive_spdm_measurement(
+                None,
+                0,
+                SpdmMeasurementAttributes::empty(),
+                SpdmMeasurementOperation::Unknown(*index),
+                None,
+                &mut total_number,
+                &mut measurement_record,
+            )
+            .is_ok());
+        assert_eq!(batch_record.number_of_blocks, 1);
+        let record_length = measurement_record.measurement_record_length.get() as usize;
+        assert_eq!(
+            batch_record.measurement_record_length.get() as usize,
+            record_length
+        );
+        assert_eq!(
+            batch_record.measurement_record_data[..record_length],
+            measurement_record.measurement_record_data[..record_length]
+        );
+    }
+
+    let session_id = requester_context
+        .start_session(
+            false,
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+    let measurement_records = requester_context
+        .send_receive_spdm_measurement_batch(
+            Some(session_id),
+            0,
+            SpdmMeasurementAttributes::SIGNATURE_REQUESTED,
+            &indices,
+        )
+        .unwrap();
+    assert_eq!(measurement_records.len(), indices.len());
+    assert!(requester_context.end_session(session_id).is_ok());
+}
+
+#[test]
+fn intergration_client_server_session_transcript_hash() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+
+    let session_id = requester_context
+        .start_session(
+            false,
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+    let th2 = requester_context
+        .common
+        .get_immutable_session_via_id(session_id)
+        .unwrap()
+        .get_handshake_transcript_hash()
+        .cloned()
+        .unwrap();
+    assert_eq!(th2.data_size as usize, SHA384_DIGEST_SIZE);
+
+    // traffic in the established session leaves it untouched.
+    assert!(requester_context
+        .send_receive_spdm_heartbeat(session_id)
+        .is_ok());
+    assert_eq!(
+        requester_context
+            .common
+            .get_immutable_session_via_id(session_id)
+            .unwrap()
+            .get_handshake_transcript_hash()
+            .unwrap()
+            .as_ref(),
+        th2.as_ref()
+    );
+
+    // not available while a session is still handshaking.
+    let psk_session_id = requester_context
+        .send_receive_spdm_psk_exchange(
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            None,
+        )
+        .unwrap();
+    assert!(requester_context
+        .common
+        .get_immutable_session_via_id(psk_session_id)
+        .unwrap()
+        .get_handshake_transcript_hash()
+        .is_none());
+    assert!(requester_context
+        .send_receive_spdm_psk_finish(psk_session_id)
+        .is_ok());
+    let psk_th2 = requester_context
+        .common
+        .get_immutable_session_via_id(psk_session_id)
+        .unwrap()
+        .get_handshake_transcript_hash()
+        .cloned()
+        .unwrap();
+    assert_ne!(psk_th2.as_ref(), th2.as_ref());
+    assert!(requester_context.end_session(psk_session_id).is_ok());
+
+    assert!(requester_context.end_session(session_id).is_ok());
+    assert!(requester_context
+        .common
+        .get_immutable_session_via_id(session_id)
+        .is_none());
+}
+
+#[cfg(feature = "mut-auth")]
+#[test]
+fn intergration_client_server_basic_mut_auth() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (mut config_info, provision_info) = rsp_create_info();
+    config_info.basic_mut_auth_requested = true;
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    // basic mutual auth has no encapsulated GET_CERTIFICATE.
+    let req_slot_id = 1u8;
+    assert!(responder_context
+        .provision_requester_cert_chain(req_slot_id, get_rsp_cert_chain_buff())
+        .is_ok());
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+
+    requester_context.common.negotiate_info.req_asym_sel =
+        SpdmReqAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384;
+    requester_context.common.provision_info.my_cert_chain[req_slot_id as usize] =
+        Some(get_rsp_cert_chain_buff());
+
+    let session_id = requester_context
+        .send_receive_spdm_key_exchange(
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+    assert_eq!(
+        requester_context
+            .common
+            .get_immutable_session_via_id(session_id)
+            .unwrap()
+            .get_mut_auth_requested(),
+        SpdmKeyExchangeMutAuthAttributes::MUT_AUTH_REQ
+    );
+
+    assert!(requester_context
+        .send_receive_spdm_finish(Some(req_slot_id), session_id)
+        .is_ok());
+    assert_eq!(
+        requester_context
+            .common
+            .get_immutable_session_via_id(session_id)
+            .unwrap()
+            .get_session_state(),
+        SpdmSessionState::SpdmSessionEstablished
+    );
+}
+
+#[cfg(feature = "mut-auth")]
+#[test]
+fn intergration_client_server_require_mut_auth() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (mut config_info, provision_info) = rsp_create_info();
+    config_info.require_mut_auth = true;
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, mut provision_info) = req_create_info();
+    provision_info.my_cert_chain[0] = Some(get_rsp_cert_chain_buff());
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+
+    let session_id = requester_context
+        .start_session(
+            false,
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+    let session = requester_context
+        .common
+        .get_immutable_session_via_id(session_id)
+        .unwrap();
+    assert!(!session.get_mut_auth_requested().is_empty());
+    assert_eq!(
+        session.get_session_state(),
+        SpdmSessionState::SpdmSessionEstablished
+    );
+}
+
+#[test]
+fn intergration_client_server_require_mut_auth_unauthenticated() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (mut config_info, provision_info) = rsp_create_info();
+    config_info.require_mut_auth = true;
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    // a requester that does not offer to sign FINISH.
+    let (mut config_info, provision_info) = req_create_info();
+    config_info
+        .req_capabilities
+        .remove(SpdmRequestCapabilityFlags::MUT_AUTH_CAP);
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+
+    assert!(requester_context
+        .send_receive_spdm_key_exchange(
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .is_err());
+}
+
+// Lets the responder start a key update in the middle of a session: once
+// `start_key_update` is set to a session, the next request finds it pending.
+#[cfg(feature = "mut-auth")]
+struct EncapKeyUpdateDeviceIo<'a> {
+    inner: FakeSpdmDeviceIo<'a>,
+    start_key_update: &'a Cell<Option<u32>>,
+}
+
+#[cfg(feature = "mut-auth")]
+impl SpdmDeviceIo for EncapKeyUpdateDeviceIo<'_> {
+    fn send(&mut self, buffer: &[u8]) -> SpdmResult {
+        if let Some(session_id) = self.start_key_update.take() {
+            self.inner.responder.start_encap_key_update(session_id)?;
+        }
+        self.inner.send(buffer)
+    }
+
+    fn receive(&mut self, buffer: &mut [u8], timeout: usize) -> Result<usize, usize> {
+        self.inner.receive(buffer, timeout)
+    }
+
+    fn flush_all(&mut self) -> SpdmResult {
+        self.inner.flush_all()
+    }
+}
+
+#[cfg(feature = "mut-auth")]
+#[test]
+fn intergration_client_server_encap_key_update() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let start_key_update = Cell::new(None);
+    let device_io_requester = &mut EncapKeyUpdateDeviceIo {
+        inner: FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context),
+        start_key_update: &start_key_update,
+    };
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+
+    let session_id = requester_context
+        .start_session(
+            false,
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+    assert!(requester_context
+        .send_receive_spdm_heartbeat(session_id)
+        .is_ok());
+    let request_sequence_number = requester_context
+        .common
+        .get_immutable_session_via_id(session_id)
+        .unwrap()
+        .get_request_direction_sequence_number();
+
+    start_key_update.set(Some(session_id));
+    assert!(requester_context
+        .send_receive_spdm_encapsulated_request(session_id)
+        .is_ok());
+
+    // the response direction starts over with the new key: the
+    // ENCAPSULATED_RESPONSE_ACK carrying VerifyNewKey and the final empty
+    // one, the request direction keeps its key.
+    let session = requester_context
+        .common
+        .get_immutable_session_via_id(session_id)
+        .unwrap();
+    assert_eq!(session.get_response_direction_sequence_number(), 2);
+    assert_eq!(
+        session.get_request_direction_sequence_number(),
+        request_sequence_number + 3
+    );
+
+    // both sides agree on the new key.
+    assert!(requester_context
+        .send_receive_spdm_heartbeat(session_id)
+        .is_ok());
+    assert!(requester_context.end_session(session_id).is_ok());
+}
+
+#[cfg(feature = "mut-auth")]
+#[test]
+fn intergration_client_server_encap_key_update_two_sessions() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let start_key_update = Cell::new(None);
+    let device_io_requester = &mut EncapKeyUpdateDeviceIo {
+        inner: FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context),
+        start_key_update: &start_key_update,
+    };
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+
+    let mut session_ids = [0u32; 2];
+    for session_id in session_ids.iter_mut() {
+        *session_id = requester_context
+            .start_session(
+                false,
+                0,
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            )
+            .unwrap();
+        assert!(requester_context
+            .send_receive_spdm_heartbeat(*session_id)
+            .is_ok());
+    }
+    let [first, second] = session_ids;
+    let response_sequence_number = |requester_context: &requester::RequesterContext,
+                                    session_id: u32| {
+        requester_context
+            .common
+            .get_immutable_session_via_id(session_id)
+            .unwrap()
+            .get_response_direction_sequence_number()
+    };
+
+    // pending for the second session, the first one does not pick it up.
+    start_key_update.set(Some(second));
+    assert!(requester_context.send_receive_spdm_heartbeat(first).is_ok());
+    let first_sequence_number = response_sequence_number(&requester_context, first);
+    assert!(requester_context
+        .send_receive_spdm_encapsulated_request(first)
+        .is_ok());
+    assert!(response_sequence_number(&requester_context, first) > first_sequence_number);
+    assert!(requester_context.send_receive_spdm_heartbeat(first).is_ok());
+
+    assert!(requester_context
+        .send_receive_spdm_encapsulated_request(second)
+        .is_ok());
+    assert_eq!(response_sequence_number(&requester_context, second), 2);
+
+    // both sessions keep working with their own keys.
+    assert!(requester_context.send_receive_spdm_heartbeat(first).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_heartbeat(second)
+        .is_ok());
+    assert!(requester_context.end_session(first).is_ok());
+
+    // a key update needs an established session.
+    start_key_update.set(Some(first));
+    assert!(requester_context
+        .send_receive_spdm_heartbeat(second)
+        .is_err());
+}
+
+#[test]
+fn intergration_client_server_requester_buffer_size() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    const MSG_SIZE: usize = 2048;
+    const TRANSPORT_SIZE: usize = MSG_SIZE + 64;
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (mut config_info, provision_info) = req_create_info();
+    config_info.data_transfer_size = MSG_SIZE as u32;
+    config_info.max_spdm_msg_size = MSG_SIZE as u32;
+    let mut requester_context =
+        requester::RequesterContext::<MSG_SIZE, TRANSPORT_SIZE>::new_with_buffer_size(
+            device_io_requester,
+            transport_encap_requester,
+            config_info,
+            provision_info,
+        );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert_eq!(
+        requester_context
+            .common
+            .negotiate_info
+            .req_data_transfer_size_sel,
+        MSG_SIZE as u32
+    );
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+
+    let session_id = requester_context
+        .start_session(
+            false,
+            0,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .unwrap();
+    assert!(requester_context
+        .send_receive_spdm_heartbeat(session_id)
+        .is_ok());
+    assert!(requester_context.end_session(session_id).is_ok());
+}
+
+#[test]
+fn intergration_client_server_requester_buffer_too_small() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+
+    const MSG_SIZE: usize = 32;
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context =
+        requester::RequesterContext::<MSG_SIZE, { MSG_SIZE + 64 }>::new_with_buffer_size(
+            device_io_requester,
+            transport_encap_requester,
+            config_info,
+            provision_info,
+        );
+
+    // the ALGORITHMS response does not fit.
+    assert!(requester_context.init_connection().is_err());
+    assert_eq!(
+        requester_context.common.runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionAfterCapabilities
+    );
+}
+
+#[test]
+fn intergration_client_server_attest() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    spdmlib::secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    let policy = requester::SpdmAttestationPolicy {
+        measurement_summary_hash_type:
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeAll,
+        base_hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        ..Default::default()
+    };
+    let report = requester_context.attest(&policy).unwrap();
+    assert!(report.signature_verified);
+    assert!(!report.measurement_blocks.is_empty());
+    assert_eq!(report.slot_id, 0);
+    assert_eq!(report.base_hash_algo, SpdmBaseHashAlgo::TPM_ALG_SHA_384);
+
+    // on the negotiated connection the selection is checked as is.
+    let policy = requester::SpdmAttestationPolicy {
+        base_hash_algo: SpdmBaseHashAlgo::TPM_ALG_SHA_256,
+        ..Default::default()
+    };
+    let error = requester_context.attest(&policy).unwrap_err();
+    assert_eq!(error.step, requester::SpdmAttestationStep::Algorithms);
+    assert_eq!(error.status, SPDM_STATUS_NEGOTIATION_FAIL);
+
+    let policy = requester::SpdmAttestationPolicy {
+        slot_id: 1,
+        ..Default::default()
+    };
+    let error = requester_context.attest(&policy).unwrap_err();
+    assert_eq!(error.step, requester::SpdmAttestationStep::Certificate);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn intergration_client_server_requester_metrics() {
+    use spdmlib::protocol::SPDM_MAX_SLOT_NUMBER;
+    use spdmlib::requester::SpdmRequesterOperation;
+
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    spdmlib::secret::measurement::register(SECRET_MEASUREMENT_IMPL_INSTANCE.clone());
+
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = rsp_create_info();
+    let mut responder_context = responder::ResponderContext::new(
+        device_io_responder,
+        transport_encap_responder,
+        config_info,
+        provision_info,
+    );
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+    assert!(requester_context
+        .send_receive_spdm_certificate(None, 0)
+        .is_ok());
+    let mut total_number = 0u8;
+    let mut measurement_record = SpdmMeasurementRecordStructure::default();
+    assert!(requester_context
+        .send_receive_spdm_measurement(
+            None,
+            0,
+            SpdmMeasurementAttributes::empty(),
+            SpdmMeasurementOperation::SpdmMeasurementQueryTotalNumber,
+            None,
+            &mut total_number,
+            &mut measurement_record,
+        )
+        .is_ok());
+    // all blocks in one signed request.
+    assert!(requester_context.fetch_all_measurements(None, 0).is_ok());
+
+    let metrics = &requester_context.metrics;
+    for operation in [
+        SpdmRequesterOperation::InitConnection,
+        SpdmRequesterOperation::Digests,
+        SpdmRequesterOperation::Certificate,
+        SpdmRequesterOperation::SignedMeasurement,
+    ] {
+        let timing = metrics.get(operation);
+        assert_eq!(timing.count, 1);
+        assert_eq!(timing.failed, 0);
+        assert!(timing.total > std::time::Duration::ZERO);
+        assert_eq!(timing.total, timing.last);
+    }
+    let timing = metrics.get(SpdmRequesterOperation::Measurement);
+    assert_eq!(timing.count, 1);
+    assert!(timing.max <= timing.total);
+    assert_eq!(metrics.get(SpdmRequesterOperation::Challenge).count, 0);
+
+    // a failed call is counted too.
+    assert!(requester_context
+        .send_receive_spdm_challenge(
+            SPDM_MAX_SLOT_NUMBER as u8,
+            SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+        )
+        .is_err());
+    let timing = requester_context
+        .metrics
+        .get(SpdmRequesterOperation::Challenge);
+    assert_eq!(timing.count, 1);
+    assert_eq!(timing.failed, 1);
+
+    requester_context.metrics.reset();
+    assert_eq!(
+        requester_context
+            .metrics
+            .get(SpdmRequesterOperation::InitConnection)
+            .count,
+        0
+    );
+}
+
+#[test]
+fn intergration_client_server_handshake_in_the_clear() {
+    spdmlib::secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    spdmlib::secret::psk::register(SECRET_PSK_IMPL_INSTANCE.clone());
+
+    // the handshake is in the clear only if both sides support it.
+    for rsp_in_the_clear in [true, false] {
+        let shared_buffer = SharedBuffer::new();
+        let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+        let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+        let (mut config_info, provision_info) = rsp_create_info();
+        config_info.rsp_capabilities.set(
+            SpdmResponseCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP,
+            rsp_in_the_clear,
+        );
+        let mut responder_context = responder::ResponderContext::new(
+            device_io_responder,
+            transport_encap_responder,
+            config_info,
+            provision_info,
+        );
+
+        let device_io_requester =
+            &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+        let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+        let (mut config_info, provision_info) = req_create_info();
+        config_info.req_capabilities |= SpdmRequestCapabilityFlags::HANDSHAKE_IN_THE_CLEAR_CAP;
+        let mut requester_context = requester::RequesterContext::new(
+            device_io_requester,
+            transport_encap_requester,
+            config_info,
+            provision_info,
+        );
+
+        assert!(requester_context.init_connection().is_ok());
+        assert_eq!(
+            requester_context
+                .common
+                .negotiate_info
+                .handshake_in_the_clear(),
+            rsp_in_the_clear
+        );
+        assert!(requester_context.send_receive_spdm_digest(None).is_ok());
+        assert!(requester_context
+            .send_receive_spdm_certificate(None, 0)
+            .is_ok());
+
+        let session_id = requester_context
+            .start_session(
+                false,
+                0,
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            )
+            .unwrap();
+        assert!(requester_context.end_session(session_id).is_ok());
+
+        // PSK_FINISH stays secured either way.
+        let session_id = requester_context
+            .start_session(
+                true,
+                0,
+                SpdmMeasurementSummaryHashType::SpdmMeasurementSummaryHashTypeNone,
+            )
+            .unwrap();
+        assert!(requester_context.end_session(session_id).is_ok());
+    }
+}
+
+#[test]
+fn intergration_client_server_responder_builder() {
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    let mut responder_context = responder::ResponderBuilder::new()
+        .spdm_versions(&[SpdmVersion::SpdmVersion11, SpdmVersion::SpdmVersion12])
+        .capabilities(
+            SpdmResponseCapabilityFlags::MEAS_CAP_NO_SIG
+                | SpdmResponseCapabilityFlags::MEAS_FRESH_CAP,
+        )
+        .base_hash_algo(SpdmBaseHashAlgo::TPM_ALG_SHA_384)
+        .base_asym_algo(SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384)
+        .measurement_hash_algo(SpdmMeasurementHashAlgo::TPM_ALG_SHA_384)
+        .build(device_io_responder, transport_encap_responder)
+        .unwrap();
+
+    let device_io_requester = &mut FakeSpdmDeviceIo::new(&shared_buffer, &mut responder_context);
+    let transport_encap_requester = &mut PciDoeTransportEncap {};
+
+    let (config_info, provision_info) = req_create_info();
+    let mut requester_context = requester::RequesterContext::new(
+        device_io_requester,
+        transport_encap_requester,
+        config_info,
+        provision_info,
+    );
+
+    assert!(requester_context.init_connection().is_ok());
+    let negotiate_info = &requester_context.common.negotiate_info;
+    assert_eq!(negotiate_info.spdm_version_sel, SpdmVersion::SpdmVersion12);
+    assert_eq!(
+        negotiate_info.rsp_capabilities_sel,
+        SpdmResponseCapabilityFlags::MEAS_CAP_NO_SIG | SpdmResponseCapabilityFlags::MEAS_FRESH_CAP
+    );
+    assert_eq!(
+        negotiate_info.base_hash_sel,
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384
+    );
+    assert_eq!(
+        negotiate_info.base_asym_sel,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384
+    );
+    assert_eq!(
+        negotiate_info.measurement_hash_sel,
+        SpdmMeasurementHashAlgo::TPM_ALG_SHA_384
+    );
+    assert_eq!(
+        requester_context.common.runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionNegotiated
+    );
+}
+
+#[test]
+fn intergration_client_server_responder_builder_incomplete() {
+    let shared_buffer = SharedBuffer::new();
+    let device_io_responder = &mut FakeSpdmDeviceIoReceve::new(&shared_buffer);
+    let transport_encap_responder = &mut PciDoeTransportEncap {};
+
+    // MEAS_CAP_NO_SIG without a measurement hash algorithm.
+    assert_eq!(
+        responder::ResponderBuilder::new()
+            .capabilities(SpdmResponseCapabilityFlags::MEAS_CAP_NO_SIG)
+            .base_hash_algo(SpdmBaseHashAlgo::TPM_ALG_SHA_384)
+            .base_asym_algo(SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384)
+            .build(device_io_responder, transport_encap_responder)
+            .err(),
+        Some(SPDM_STATUS_INVALID_PARAMETER)
+    );
+    assert!(responder::ResponderBuilder::new()
+        .spdm_versions(&[])
+        .base_hash_algo(SpdmBaseHashAlgo::TPM_ALG_SHA_384)
+        .base_asym_algo(SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384)
+        .build(device_io_responder, transport_encap_responder)
+        .is_err());
+}
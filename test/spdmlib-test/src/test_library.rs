@@ -294,3 +294,119 @@ fn test_case0_spdm_measurement_block_structure() {
     }
     assert_eq!(0, reader.left());
 }
+
+#[test]
+fn test_case0_asym_sign_self_test() {
+    use crate::common::secret_callback::SECRET_ASYM_IMPL_INSTANCE;
+    use spdmlib::secret;
+
+    secret::asym_sign::register(SECRET_ASYM_IMPL_INSTANCE.clone());
+    let responder_chain = include_bytes!("../../../test_key/ecp384/bundle_responder.certchain.der");
+    let requester_chain = include_bytes!("../../../test_key/ecp384/bundle_requester.certchain.der");
+
+    assert!(secret::asym_sign::self_test(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+        responder_chain,
+    ));
+    // the test signer holds the responder key, not the requester one.
+    assert!(!secret::asym_sign::self_test(
+        SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+        requester_chain,
+    ));
+}
+
+#[test]
+fn test_case0_asym_verify_ecdsa_signature_encoding() {
+    use crate::common::secret_callback::sign_ecdsa_asym_algo;
+    use spdmlib::crypto;
+
+    let responder_chain = include_bytes!("../../../test_key/ecp384/bundle_responder.certchain.der");
+    let message = b"ecdsa signature encoding";
+    let verify = |signature: &SpdmSignatureStruct| {
+        crypto::asym_verify::verify(
+            SpdmBaseHashAlgo::TPM_ALG_SHA_384,
+            SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+            responder_chain,
+            message,
+            signature,
+        )
+    };
+
+    let p1363 =
+        sign_ecdsa_asym_algo(&ring::signature::ECDSA_P384_SHA384_FIXED_SIGNING, message).unwrap();
+    assert_eq!(p1363.data_size, ECDSA_ECC_NIST_P384_KEY_SIZE as u16);
+    assert!(verify(&p1363).is_ok());
+
+    let der =
+        sign_ecdsa_asym_algo(&ring::signature::ECDSA_P384_SHA384_ASN1_SIGNING, message).unwrap();
+    assert_ne!(der.data_size, ECDSA_ECC_NIST_P384_KEY_SIZE as u16);
+    assert!(verify(&der).is_ok());
+    let converted = crypto::ecdsa_signature_der_to_p1363(
+        SpdmBaseAsymAlgo::TPM_ALG_ECDSA_ECC_NIST_P384,
+        der.as_ref(),
+    )
+    .unwrap();
+    assert!(verify(&converted).is_ok());
+
+    // a DER signature with its outer length broken is rejected, not truncated.
+    let mut malformed = der.clone();
+    malformed.data[1] += 1;
+    assert!(verify(&malformed).is_err());
+    let mut truncated = p1363;
+    truncated.data_size -= 1;
+    assert!(verify(&truncated).is_err());
+}
+
+#[test]
+fn test_case0_spdm_connection_state_transition() {
+    use spdmlib::common::{SpdmConnectionState, SpdmRuntimeInfo};
+
+    let mut runtime_info = SpdmRuntimeInfo::default();
+    for next in [
+        SpdmConnectionState::SpdmConnectionAfterVersion,
+        SpdmConnectionState::SpdmConnectionAfterCapabilities,
+        SpdmConnectionState::SpdmConnectionNegotiated,
+        SpdmConnectionState::SpdmConnectionAfterCertificate,
+        SpdmConnectionState::SpdmConnectionAfterDigest,
+        SpdmConnectionState::SpdmConnectionAuthenticated,
+        SpdmConnectionState::SpdmConnectionAfterVersion,
+    ] {
+        assert!(runtime_info.transition_connection_state(next).is_ok());
+        assert_eq!(runtime_info.get_connection_state(), next);
+    }
+
+    let after_version = SpdmConnectionState::SpdmConnectionAfterVersion;
+    assert!(!after_version.can_transition_to(SpdmConnectionState::SpdmConnectionNegotiated));
+    assert!(!after_version.can_transition_to(SpdmConnectionState::SpdmConnectionAuthenticated));
+    assert!(!after_version.can_transition_to(SpdmConnectionState::SpdmConnectionNotStarted));
+    let negotiated = SpdmConnectionState::SpdmConnectionNegotiated;
+    assert!(!negotiated.can_transition_to(SpdmConnectionState::SpdmConnectionAfterCapabilities));
+    assert!(!negotiated.can_transition_to(SpdmConnectionState::SpdmConnectionNegotiated));
+    assert!(!SpdmConnectionState::SpdmConnectionNotStarted
+        .can_transition_to(SpdmConnectionState::SpdmConnectionAfterDigest));
+    assert!(!negotiated.can_transition_to(SpdmConnectionState::Unknown(6)));
+}
+
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    should_panic(expected = "illegal connection state transition")
+)]
+fn test_case1_spdm_connection_state_transition() {
+    use spdmlib::common::{SpdmConnectionState, SpdmRuntimeInfo};
+    use spdmlib::error::SPDM_STATUS_INVALID_STATE_LOCAL;
+
+    let mut runtime_info = SpdmRuntimeInfo::default();
+    runtime_info.set_connection_state(SpdmConnectionState::SpdmConnectionAfterVersion);
+    // asserts in debug builds, fails without moving in release builds.
+    assert_eq!(
+        runtime_info.transition_connection_state(SpdmConnectionState::SpdmConnectionNegotiated),
+        Err(SPDM_STATUS_INVALID_STATE_LOCAL)
+    );
+    assert_eq!(
+        runtime_info.get_connection_state(),
+        SpdmConnectionState::SpdmConnectionAfterVersion
+    );
+}